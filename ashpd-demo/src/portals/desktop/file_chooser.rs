@@ -146,7 +146,7 @@ impl FileChooserPage {
 
                 for uri in files.uris() {
                     imp.open_response_group
-                        .add(&adw::ActionRow::builder().title(uri).build());
+                        .add(&adw::ActionRow::builder().title(uri.as_str()).build());
                 }
                 self.send_notification(
                     "Open file request was successful",
@@ -186,7 +186,7 @@ impl FileChooserPage {
 
                 for uri in files.uris() {
                     imp.save_file_response_group
-                        .add(&adw::ActionRow::builder().title(uri).build());
+                        .add(&adw::ActionRow::builder().title(uri.as_str()).build());
                 }
 
                 self.send_notification(
@@ -225,7 +225,7 @@ impl FileChooserPage {
 
                 for uri in files.uris() {
                     imp.save_files_response_group
-                        .add(&adw::ActionRow::builder().title(uri).build());
+                        .add(&adw::ActionRow::builder().title(uri.as_str()).build());
                 }
                 self.send_notification(
                     "Save files request was successful",
@@ -250,11 +250,11 @@ async fn portal_open_file(
     let cnx = zbus::Connection::session().await?;
     let proxy = FileChooserProxy::new(&cnx).await?;
     let options = OpenFileOptions::default()
-        .directory(directory)
-        .modal(modal)
-        .multiple(multiple);
+        .with_directory(directory)
+        .with_modal(modal)
+        .with_multiple(multiple);
     let options = if let Some(accept_label) = accept_label {
-        options.accept_label(accept_label)
+        options.with_accept_label(accept_label)
     } else {
         options
     };
@@ -274,24 +274,24 @@ async fn portal_save_file(
 ) -> ashpd::Result<SelectedFiles> {
     let cnx = zbus::Connection::session().await?;
     let proxy = FileChooserProxy::new(&cnx).await?;
-    let options = SaveFileOptions::default().modal(modal);
+    let options = SaveFileOptions::default().with_modal(modal);
     let options = if let Some(accept_label) = accept_label {
-        options.accept_label(accept_label)
+        options.with_accept_label(accept_label)
     } else {
         options
     };
     let options = if let Some(current_name) = current_name {
-        options.current_name(current_name)
+        options.with_current_name(current_name)
     } else {
         options
     };
     let options = if let Some(current_folder) = current_folder {
-        options.current_folder(current_folder)
+        options.with_current_folder(current_folder)?
     } else {
         options
     };
     let options = if let Some(current_file) = current_file {
-        options.current_file(current_file)
+        options.with_current_file(current_file)?
     } else {
         options
     };
@@ -309,25 +309,25 @@ async fn portal_save_files<S: AsRef<str>>(
 ) -> ashpd::Result<SelectedFiles> {
     let cnx = zbus::Connection::session().await?;
     let proxy = FileChooserProxy::new(&cnx).await?;
-    let options = SaveFilesOptions::default().modal(modal);
+    let options = SaveFilesOptions::default().with_modal(modal);
     let options = if let Some(accept_label) = accept_label {
-        options.accept_label(accept_label)
+        options.with_accept_label(accept_label)
     } else {
         options
     };
     let options = if let Some(current_folder) = current_folder {
-        options.current_folder(current_folder)
+        options.with_current_folder(current_folder)?
     } else {
         options
     };
     let options = if let Some(files) = files {
-        options.files(
+        options.with_files(
             files
                 .iter()
                 .map(|s| s.as_ref())
                 .collect::<Vec<&str>>()
                 .as_slice(),
-        )
+        )?
     } else {
         options
     };
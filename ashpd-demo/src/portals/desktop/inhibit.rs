@@ -1,11 +1,11 @@
 use crate::widgets::{PortalPage, PortalPageImpl};
 use ashpd::{
-    desktop::inhibit::{InhibitFlags, InhibitProxy, SessionState},
-    desktop::SessionProxy,
+    desktop::inhibit::{InhibitFlags, InhibitMonitor, InhibitProxy, SessionState},
     enumflags2::BitFlags,
     zbus, WindowIdentifier,
 };
 use futures::lock::Mutex;
+use futures::StreamExt;
 use gtk::glib::{self, clone};
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
@@ -30,7 +30,7 @@ mod imp {
         pub user_switch_check: TemplateChild<gtk::CheckButton>,
         #[template_child]
         pub suspend_check: TemplateChild<gtk::CheckButton>,
-        pub session: Arc<Mutex<Option<SessionProxy<'static>>>>,
+        pub session: Arc<Mutex<Option<InhibitMonitor<'static>>>>,
     }
 
     #[glib::object_subclass]
@@ -116,13 +116,14 @@ impl InhibitPage {
 
         let connection = zbus::Connection::session().await?;
         let proxy = InhibitProxy::new(&connection).await?;
+        let mut states = proxy.receive_state_changed().await?;
         let monitor = proxy.create_monitor(&identifier).await?;
 
         imp.session.lock().await.replace(monitor);
         self.action_set_enabled("inhibit.stop", true);
         self.action_set_enabled("inhibit.start_session", false);
 
-        let state = proxy.receive_state_changed().await?;
+        let state = states.next().await.ok_or(ashpd::Error::NoResponse)?;
         match state.session_state() {
             SessionState::Running => tracing::info!("Session running"),
             SessionState::QueryEnd => {
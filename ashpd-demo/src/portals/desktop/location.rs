@@ -1,15 +1,13 @@
 use crate::widgets::{NotificationKind, PortalPage, PortalPageExt, PortalPageImpl};
 use adw::prelude::*;
 use ashpd::{
-    desktop::{
-        location::{Accuracy, Location, LocationProxy},
-        SessionProxy,
-    },
+    desktop::location::{Accuracy, Location, LocationProxy, LocationSession},
     zbus, WindowIdentifier,
 };
 use chrono::{DateTime, Local, TimeZone};
 use futures::future::{AbortHandle, Abortable};
 use futures::lock::Mutex;
+use futures::StreamExt;
 use glib::clone;
 use gtk::glib;
 use gtk::subclass::prelude::*;
@@ -52,7 +50,7 @@ mod imp {
         #[template_child(id = "license")]
         pub map_license: TemplateChild<shumate::License>,
         pub marker: shumate::Marker,
-        pub session: Arc<Mutex<Option<SessionProxy<'static>>>>,
+        pub session: Arc<Mutex<Option<LocationSession<'static>>>>,
         pub abort_handle: Arc<Mutex<Option<AbortHandle>>>,
     }
 
@@ -171,8 +169,12 @@ impl LocationPage {
                     let (abort_handle, abort_registration) = AbortHandle::new_pair();
                     let future = Abortable::new(
                         async {
-                            if let Ok(location) = location_proxy.receive_location_updated().await {
-                                self.on_location_updated(location);
+                            if let Ok(mut locations) =
+                                location_proxy.receive_location_updated().await
+                            {
+                                while let Some(location) = locations.next().await {
+                                    self.on_location_updated(location);
+                                }
                             }
                         },
                         abort_registration,
@@ -239,7 +241,7 @@ pub async fn locate<'a>(
     distance_threshold: u32,
     time_threshold: u32,
     accuracy: Accuracy,
-) -> ashpd::Result<(SessionProxy<'a>, LocationProxy<'a>)> {
+) -> ashpd::Result<(LocationSession<'a>, LocationProxy<'a>)> {
     let connection = zbus::Connection::session().await?;
     let proxy = LocationProxy::new(&connection).await?;
     let session = proxy
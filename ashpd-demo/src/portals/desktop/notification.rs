@@ -5,6 +5,7 @@ use ashpd::{
     zbus,
     zvariant::Value,
 };
+use futures::StreamExt;
 use glib::clone;
 use gtk::glib;
 use gtk::subclass::prelude::*;
@@ -88,19 +89,20 @@ impl NotificationPage {
         };
 
         let notification = Notification::new(&title)
-            .default_action("open")
-            .default_action_target(Value::U32(100).into())
-            .body(&body)
-            .priority(priority)
+            .with_default_action("open")
+            .with_default_action_target(Value::U32(100).into())
+            .with_body(&body)
+            .with_priority(priority)
             .button(Button::new("Copy", "copy").target(Value::U32(32).into()))
             .button(Button::new("Delete", "delete").target(Value::U32(40).into()));
 
         let cnx = zbus::Connection::session().await?;
         let proxy = NotificationProxy::new(&cnx).await?;
+        let mut actions = proxy.receive_action_invoked().await?;
         match proxy.add_notification(&notification_id, notification).await {
             Ok(_) => {
                 self.send_notification("Notification sent", NotificationKind::Success);
-                let action = proxy.receive_action_invoked().await?;
+                let action = actions.next().await.ok_or(ashpd::Error::NoResponse)?;
                 self.send_notification(
                     &format!("User interacted with notification \"{}\"", notification_id),
                     NotificationKind::Info,
@@ -1,5 +1,5 @@
 use crate::widgets::{NotificationKind, PortalPage, PortalPageExt, PortalPageImpl};
-use ashpd::{desktop::open_uri, WindowIdentifier};
+use ashpd::{desktop::open_uri, url::Url, WindowIdentifier};
 use gtk::glib::{self, clone};
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
@@ -62,7 +62,13 @@ impl OpenUriPage {
         let ask = imp.ask_switch.is_active();
         let root = self.native().unwrap();
         let identifier = WindowIdentifier::from_native(&root).await;
-        let uri = imp.uri_entry.text();
+        let uri = match Url::parse(&imp.uri_entry.text()) {
+            Ok(uri) => uri,
+            Err(_err) => {
+                self.send_notification("Invalid URI", NotificationKind::Error);
+                return;
+            }
+        };
         match open_uri::open_uri(&identifier, &uri, writable, ask).await {
             Ok(_) => {
                 self.send_notification(
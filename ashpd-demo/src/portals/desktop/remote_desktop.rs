@@ -1,9 +1,10 @@
 use crate::widgets::{NotificationKind, PortalPage, PortalPageExt, PortalPageImpl};
 use ashpd::{
     desktop::{
-        remote_desktop::{DeviceType, RemoteDesktopProxy},
-        screencast::{CursorMode, PersistMode, ScreenCastProxy, SourceType, Stream},
-        SessionProxy,
+        remote_desktop::{DeviceType, RemoteDesktopProxy, RemoteDesktopSession},
+        screencast::{
+            CursorMode, PersistMode, ScreenCastProxy, SelectSourcesOptions, SourceType, Stream,
+        },
     },
     enumflags2::BitFlags,
     zbus, WindowIdentifier,
@@ -25,7 +26,7 @@ mod imp {
     pub struct RemoteDesktopPage {
         #[template_child]
         pub response_group: TemplateChild<adw::PreferencesGroup>,
-        pub session: Arc<Mutex<Option<SessionProxy<'static>>>>,
+        pub session: Arc<Mutex<Option<RemoteDesktopSession<'static>>>>,
         #[template_child]
         pub screencast_switch: TemplateChild<gtk::Switch>,
         #[template_child]
@@ -217,7 +218,11 @@ impl RemoteDesktopPage {
 
     async fn remote(
         &self,
-    ) -> ashpd::Result<(BitFlags<DeviceType>, Vec<Stream>, SessionProxy<'static>)> {
+    ) -> ashpd::Result<(
+        BitFlags<DeviceType>,
+        Vec<Stream>,
+        RemoteDesktopSession<'static>,
+    )> {
         let imp = self.imp();
         let root = self.native().unwrap();
         let identifier = WindowIdentifier::from_native(&root).await;
@@ -232,22 +237,18 @@ impl RemoteDesktopPage {
         let session = proxy.create_session().await?;
         if is_screencast {
             let screencast_proxy = ScreenCastProxy::new(&connection).await?;
-            screencast_proxy
-                .select_sources(
-                    &session,
-                    cursor_mode,
-                    sources,
-                    multiple_sources,
-                    None,
-                    PersistMode::default(),
-                )
-                .await?;
+            let options = SelectSourcesOptions::default()
+                .with_cursor_mode(cursor_mode)
+                .with_types(sources)
+                .with_multiple(multiple_sources)
+                .with_persist_mode(PersistMode::default());
+            screencast_proxy.select_sources(&session, options).await?;
         }
         proxy.select_devices(&session, devices).await?;
 
         self.send_notification("Starting a remote desktop session", NotificationKind::Info);
-        let (devices, streams) = proxy.start(&session, &identifier).await?;
-        Ok((devices, streams, session))
+        let selected = proxy.start(&session, &identifier).await?;
+        Ok((selected.devices(), selected.streams().to_vec(), session))
     }
 }
 
@@ -2,9 +2,9 @@ use crate::widgets::{
     CameraPaintable, NotificationKind, PortalPage, PortalPageExt, PortalPageImpl,
 };
 use ashpd::{
-    desktop::{
-        screencast::{CursorMode, PersistMode, ScreenCastProxy, SourceType, Stream},
-        SessionProxy,
+    desktop::screencast::{
+        CursorMode, PersistMode, ScreenCastProxy, ScreenCastSession, SelectSourcesOptions,
+        SourceType, Stream,
     },
     enumflags2::BitFlags,
     zbus, WindowIdentifier,
@@ -30,7 +30,7 @@ mod imp {
         pub response_group: TemplateChild<adw::PreferencesGroup>,
         #[template_child]
         pub multiple_switch: TemplateChild<gtk::Switch>,
-        pub session: Arc<Mutex<Option<SessionProxy<'static>>>>,
+        pub session: Arc<Mutex<Option<ScreenCastSession<'static>>>>,
         #[template_child]
         pub monitor_check: TemplateChild<gtk::CheckButton>,
         #[template_child]
@@ -209,7 +209,7 @@ impl ScreenCastPage {
         imp.response_group.hide();
     }
 
-    async fn screencast(&self) -> ashpd::Result<(Vec<Stream>, RawFd, SessionProxy<'static>)> {
+    async fn screencast(&self) -> ashpd::Result<(Vec<Stream>, RawFd, ScreenCastSession<'static>)> {
         let imp = self.imp();
         let sources = self.selected_sources();
         let cursor_mode = self.selected_cursor_mode();
@@ -222,24 +222,22 @@ impl ScreenCastPage {
         let connection = zbus::Connection::session().await?;
         let proxy = ScreenCastProxy::new(&connection).await?;
         let session = proxy.create_session().await?;
-        let token = imp.session_token.lock().await;
-        proxy
-            .select_sources(
-                &session,
-                cursor_mode,
-                sources,
-                multiple,
-                token.as_deref(),
-                PersistMode::ExplicitlyRevoked,
-            )
-            .await?;
+        let mut options = SelectSourcesOptions::default()
+            .with_cursor_mode(cursor_mode)
+            .with_types(sources)
+            .with_multiple(multiple)
+            .with_persist_mode(PersistMode::ExplicitlyRevoked);
+        if let Some(token) = imp.session_token.lock().await.as_deref() {
+            options = options.with_restore_token(token);
+        }
+        proxy.select_sources(&session, options).await?;
         self.send_notification("Starting a screen cast session", NotificationKind::Info);
-        let (streams, token) = proxy.start(&session, &identifier).await?;
-        if let Some(t) = token {
-            imp.session_token.lock().await.replace(t);
+        let response = proxy.start(&session, &identifier).await?;
+        if let Some(t) = response.restore_token() {
+            imp.session_token.lock().await.replace(t.to_owned());
         }
         let fd = proxy.open_pipe_wire_remote(&session).await?;
-        Ok((streams, fd, session))
+        Ok((response.streams().to_vec(), fd, session))
     }
 }
 
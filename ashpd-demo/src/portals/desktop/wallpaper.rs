@@ -2,7 +2,7 @@ use std::cell::RefCell;
 
 use crate::widgets::{NotificationKind, PortalPage, PortalPageExt, PortalPageImpl};
 use adw::prelude::*;
-use ashpd::{desktop::wallpaper, WindowIdentifier};
+use ashpd::{desktop::wallpaper, url::Url, WindowIdentifier};
 use glib::clone;
 use gtk::glib;
 use gtk::subclass::prelude::*;
@@ -81,7 +81,7 @@ impl WallpaperPage {
             _ => unimplemented!(),
         };
         if file_chooser.run_future().await == gtk::ResponseType::Accept {
-            let wallpaper_uri = file_chooser.file().unwrap().uri();
+            let wallpaper_uri = Url::parse(&file_chooser.file().unwrap().uri()).unwrap();
 
             let identifier = WindowIdentifier::from_native(&root).await;
             match wallpaper::set_from_uri(&identifier, &wallpaper_uri, show_preview, set_on).await {
@@ -0,0 +1,112 @@
+//! Compares deserializing large portal replies into owned types with
+//! borrowing from the message body, run with `cargo bench`.
+
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    os::unix::prelude::OsStrExt,
+    path::{Path, PathBuf},
+};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use zbus::zvariant::{from_slice, to_bytes, EncodingContext, OwnedValue, Value};
+
+type Context = EncodingContext<byteorder::LE>;
+
+fn context() -> Context {
+    EncodingContext::new_dbus(0)
+}
+
+fn path(bytes: &[u8]) -> PathBuf {
+    let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+    Path::new(OsStr::from_bytes(bytes)).to_path_buf()
+}
+
+// The reply of `org.freedesktop.portal.Documents.List` with 1000 documents.
+fn documents_list() -> Vec<u8> {
+    let documents = (0..1000)
+        .map(|id| {
+            let path = format!(
+                "/home/user/Documents/some/nested/folder/document-{}.txt\0",
+                id
+            );
+            (format!("{:x}", id), path.into_bytes())
+        })
+        .collect::<HashMap<_, _>>();
+    to_bytes(context(), &documents).unwrap()
+}
+
+// The reply of `org.freedesktop.portal.Settings.ReadAll` with 20 namespaces of
+// 50 keys.
+fn settings_read_all() -> Vec<u8> {
+    let settings = (0..20)
+        .map(|namespace| {
+            let keys = (0..50)
+                .map(|key| {
+                    let value = Value::from(format!("value-{}-{}", namespace, key));
+                    (format!("key-{}", key), value)
+                })
+                .collect::<HashMap<_, _>>();
+            (format!("org.example.namespace{}", namespace), keys)
+        })
+        .collect::<HashMap<_, _>>();
+    to_bytes(context(), &settings).unwrap()
+}
+
+fn documents(c: &mut Criterion) {
+    let body = documents_list();
+    let mut group = c.benchmark_group("Documents.List");
+    group.bench_function("owned", |b| {
+        b.iter(|| {
+            let reply: HashMap<String, Vec<u8>> = from_slice(black_box(&body), context()).unwrap();
+            reply
+                .into_iter()
+                .map(|(id, bytes)| (id, path(&bytes)))
+                .collect::<HashMap<_, _>>()
+        })
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| {
+            let reply: HashMap<&str, &[u8]> = from_slice(black_box(&body), context()).unwrap();
+            reply
+                .into_iter()
+                .map(|(id, bytes)| (id.to_owned(), path(bytes)))
+                .collect::<HashMap<_, _>>()
+        })
+    });
+    group.finish();
+}
+
+fn settings(c: &mut Criterion) {
+    let body = settings_read_all();
+    let mut group = c.benchmark_group("Settings.ReadAll");
+    group.bench_function("owned", |b| {
+        b.iter(|| {
+            let reply: HashMap<String, HashMap<String, OwnedValue>> =
+                from_slice(black_box(&body), context()).unwrap();
+            reply
+        })
+    });
+    // The namespaces are returned as owned values, borrowing first only pays
+    // off if the conversion is cheaper than the deserialization.
+    group.bench_function("borrowed", |b| {
+        b.iter(|| {
+            let reply: HashMap<&str, HashMap<&str, Value<'_>>> =
+                from_slice(black_box(&body), context()).unwrap();
+            reply
+                .into_iter()
+                .map(|(namespace, keys)| {
+                    let keys = keys
+                        .into_iter()
+                        .map(|(key, value)| (key.to_owned(), OwnedValue::from(value)))
+                        .collect::<HashMap<_, _>>();
+                    (namespace.to_owned(), keys)
+                })
+                .collect::<HashMap<_, _>>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, documents, settings);
+criterion_main!(benches);
@@ -0,0 +1,189 @@
+//! Serialization of the options and responses of the portals along with the
+//! round-trip time of a request, run with `cargo bench`.
+//!
+//! The round-trip benchmark registers a mock `org.freedesktop.portal.Screenshot`
+//! portal on the session bus, it is skipped if there is no session bus or if a
+//! portal already owns `org.freedesktop.portal.Desktop`. Run it with
+//! `dbus-run-session -- cargo bench --bench options`.
+
+use std::{collections::HashMap, future::IntoFuture};
+
+use ashpd::{
+    desktop::{
+        file_chooser::{Choice, FileFilter, OpenFileOptions, SelectedFiles},
+        notification::{Button, Notification, Priority},
+        screencast::{CursorMode, PersistMode, SelectSourcesOptions, SourceType},
+        screenshot::ScreenshotProxy,
+    },
+    WindowIdentifier,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use zbus::{
+    dbus_interface,
+    zvariant::{from_slice, to_bytes, EncodingContext, ObjectPath, OwnedObjectPath, Value},
+    Connection, ConnectionBuilder, MessageHeader,
+};
+
+type Context = EncodingContext<byteorder::LE>;
+
+fn context() -> Context {
+    EncodingContext::new_dbus(0)
+}
+
+fn open_file_options() -> OpenFileOptions {
+    OpenFileOptions::default()
+        .with_accept_label("_Open")
+        .with_modal(true)
+        .with_multiple(true)
+        .add_filter(
+            FileFilter::new("Images")
+                .mimetype("image/png")
+                .mimetype("image/jpeg")
+                .glob("*.webp"),
+        )
+        .add_filter(FileFilter::new("Text").mimetype("text/plain"))
+        .with_current_filter(FileFilter::new("Text").mimetype("text/plain"))
+        .add_choice(
+            Choice::new("encoding", "Encoding", "utf8")
+                .insert("utf8", "Unicode (UTF-8)")
+                .insert("latin15", "Western"),
+        )
+        .add_choice(Choice::boolean("reencode", "Reencode", false))
+}
+
+fn select_sources_options() -> SelectSourcesOptions {
+    SelectSourcesOptions::default()
+        .with_multiple(true)
+        .with_cursor_mode(CursorMode::Metadata)
+        .with_types(SourceType::Monitor | SourceType::Window)
+        .with_persist_mode(PersistMode::ExplicitlyRevoked)
+        .with_restore_token("a-restore-token")
+}
+
+fn notification() -> Notification {
+    Notification::new("Download finished")
+        .with_body("The file has been downloaded")
+        .with_priority(Priority::High)
+        .with_default_action("open")
+        .button(Button::new("Open", "open"))
+        .button(Button::new("Show in folder", "show"))
+}
+
+// The results of a `org.freedesktop.portal.FileChooser.OpenFile` response
+// with 100 files.
+fn selected_files() -> Vec<u8> {
+    let uris = (0..100)
+        .map(|id| format!("file:///home/user/Pictures/picture-{}.png", id))
+        .collect::<Vec<_>>();
+    let mut results = HashMap::new();
+    results.insert("uris", Value::from(uris));
+    results.insert("choices", Value::from(vec![("encoding", "utf8")]));
+    to_bytes(context(), &results).unwrap()
+}
+
+fn serialization(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialize");
+    let options = open_file_options();
+    group.bench_function("OpenFileOptions", |b| {
+        b.iter(|| to_bytes(context(), black_box(&options)).unwrap())
+    });
+    let options = select_sources_options();
+    group.bench_function("SelectSourcesOptions", |b| {
+        b.iter(|| to_bytes(context(), black_box(&options)).unwrap())
+    });
+    let notification = notification();
+    group.bench_function("Notification", |b| {
+        b.iter(|| to_bytes(context(), black_box(&notification)).unwrap())
+    });
+    group.finish();
+}
+
+fn deserialization(c: &mut Criterion) {
+    let mut group = c.benchmark_group("deserialize");
+    let body = to_bytes(context(), &open_file_options()).unwrap();
+    group.bench_function("OpenFileOptions", |b| {
+        b.iter(|| from_slice::<_, OpenFileOptions>(black_box(&body), context()).unwrap())
+    });
+    let body = selected_files();
+    group.bench_function("SelectedFiles", |b| {
+        b.iter(|| from_slice::<_, SelectedFiles>(black_box(&body), context()).unwrap())
+    });
+    group.finish();
+}
+
+struct MockScreenshot;
+
+#[dbus_interface(name = "org.freedesktop.portal.Screenshot")]
+impl MockScreenshot {
+    async fn screenshot(
+        &self,
+        #[zbus(header)] header: MessageHeader<'_>,
+        #[zbus(connection)] connection: &Connection,
+        _parent_window: &str,
+        options: HashMap<&str, Value<'_>>,
+    ) -> zbus::fdo::Result<OwnedObjectPath> {
+        let sender = header.sender()?.unwrap().trim_start_matches(':');
+        let token = match options.get("handle_token") {
+            Some(Value::Str(token)) => token.as_str(),
+            _ => return Err(zbus::fdo::Error::InvalidArgs("No handle_token".into())),
+        };
+        let handle = ObjectPath::try_from(format!(
+            "/org/freedesktop/portal/desktop/request/{}/{}",
+            sender.replace('.', "_"),
+            token
+        ))
+        .unwrap();
+        let mut results = HashMap::new();
+        results.insert("uri", Value::from("file:///tmp/screenshot.png"));
+        connection
+            .emit_signal(
+                None::<()>,
+                &handle,
+                "org.freedesktop.portal.Request",
+                "Response",
+                &(0u32, results),
+            )
+            .await?;
+        Ok(handle.into())
+    }
+}
+
+fn mock_portal() -> Option<Connection> {
+    std::env::var_os("DBUS_SESSION_BUS_ADDRESS")?;
+    let service = futures::executor::block_on(async {
+        ConnectionBuilder::session()?
+            .name("org.freedesktop.portal.Desktop")?
+            .serve_at("/org/freedesktop/portal/desktop", MockScreenshot)?
+            .build()
+            .await
+    });
+    match service {
+        Ok(service) => Some(service),
+        Err(zbus::Error::NameTaken) => None,
+        Err(err) => panic!("Failed to register the mock portal: {}", err),
+    }
+}
+
+fn round_trip(c: &mut Criterion) {
+    let _service = match mock_portal() {
+        Some(service) => service,
+        None => {
+            eprintln!("No session bus or the portal name is taken, skipping");
+            return;
+        }
+    };
+    let proxy = futures::executor::block_on(async {
+        let connection = Connection::session().await.unwrap();
+        ScreenshotProxy::new(&connection).await.unwrap()
+    });
+    let identifier = WindowIdentifier::default();
+    c.bench_function("request round-trip", |b| {
+        b.iter(|| {
+            futures::executor::block_on(proxy.screenshot(&identifier, false, false).into_future())
+                .unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, serialization, deserialization, round_trip);
+criterion_main!(benches);
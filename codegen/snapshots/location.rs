@@ -0,0 +1,124 @@
+//! TODO: an example of the portal.
+
+use crate::Error;
+use crate::WindowIdentifier;
+use crate::helpers::call_request_method;
+use crate::helpers::call_window_request_method;
+use crate::helpers::receive_signal_stream;
+use crate::raw_options::RawOptions;
+use futures::Stream;
+use std::collections::HashMap;
+use zbus::zvariant::DeserializeDict;
+use zbus::zvariant::ObjectPath;
+use zbus::zvariant::OwnedObjectPath;
+use zbus::zvariant::OwnedValue;
+use zbus::zvariant::SerializeDict;
+use zbus::zvariant::Type;
+
+use super::{DESTINATION, HandleToken, INTERFACE_LOCATION, PATH, PortalRequest};
+
+#[derive(Type, Clone, Debug, Default)]
+/// Specified options for a [`LocationProxy::create_session`] request.
+#[zvariant(signature = "dict")]
+pub struct CreateSessionOptions {
+    /// A string that will be used as the last element of the handle.
+    handle_token: HandleToken,
+    // TODO: the options of the specifications, with their setters.
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(CreateSessionOptions {
+    handle_token = "handle_token",
+});
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug)]
+/// The response of a [`LocationProxy::create_session`] request.
+#[zvariant(signature = "dict")]
+pub struct CreateSessionResponse {
+    // TODO: the results of the specifications.
+}
+
+#[derive(Type, Clone, Debug, Default)]
+/// Specified options for a [`LocationProxy::start`] request.
+#[zvariant(signature = "dict")]
+pub struct StartOptions {
+    /// A string that will be used as the last element of the handle.
+    handle_token: HandleToken,
+    // TODO: the options of the specifications, with their setters.
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(StartOptions {
+    handle_token = "handle_token",
+});
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug)]
+/// The response of a [`LocationProxy::start`] request.
+#[zvariant(signature = "dict")]
+pub struct StartResponse {
+    // TODO: the results of the specifications.
+}
+
+/// TODO: describe the portal.
+///
+/// Wrapper of the DBus interface: [`org.freedesktop.portal.Location`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Location).
+#[derive(Clone)]
+#[doc(alias = "org.freedesktop.portal.Location")]
+pub struct LocationProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(LocationProxy, INTERFACE_LOCATION);
+impl_proxy_timeout!(LocationProxy);
+
+impl<'a> LocationProxy<'a> {
+    /// Create a new instance of [`LocationProxy`].
+    pub async fn new(connection: &zbus::Connection) -> Result<LocationProxy<'a>, Error> {
+        let proxy = zbus::ProxyBuilder::new_bare(connection)
+            .interface(INTERFACE_LOCATION)?
+            .path(PATH)?
+            .destination(DESTINATION)?
+            .build()
+            .await?;
+        Ok(Self(proxy, None))
+    }
+
+    /// Get a reference to the underlying Proxy.
+    pub fn inner(&self) -> &zbus::Proxy<'_> {
+        &self.0
+    }
+
+    /// TODO: document the method.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`CreateSession`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Location.CreateSession).
+    #[doc(alias = "CreateSession")]
+    pub fn create_session<'p>(&'p self, options: CreateSessionOptions) -> PortalRequest<'p, CreateSessionResponse> {
+        let handle_token = options.handle_token.copy();
+        call_request_method(self, &handle_token, "CreateSession", (options,))
+    }
+
+    /// TODO: document the method.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`Start`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Location.Start).
+    #[doc(alias = "Start")]
+    pub fn start<'p>(&'p self, session_handle: &'p ObjectPath<'p>, identifier: &'p WindowIdentifier, options: StartOptions) -> PortalRequest<'p, StartResponse> {
+        let handle_token = options.handle_token.copy();
+        call_window_request_method(self, identifier, &handle_token, "Start", (session_handle, identifier, options))
+    }
+
+    /// TODO: document the signal.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`LocationUpdated`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-Location.LocationUpdated).
+    #[doc(alias = "LocationUpdated")]
+    pub async fn receive_location_updated(
+        &self,
+    ) -> Result<impl Stream<Item = (OwnedObjectPath, HashMap<String, OwnedValue>)> + Unpin + 'a, Error> {
+        receive_signal_stream(&self.0, "LocationUpdated").await
+    }
+}
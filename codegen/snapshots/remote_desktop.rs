@@ -0,0 +1,362 @@
+//! TODO: an example of the portal.
+
+use crate::Error;
+use crate::WindowIdentifier;
+use crate::helpers::call_method;
+use crate::helpers::call_request_method;
+use crate::helpers::call_window_request_method;
+use crate::helpers::get_property;
+use crate::raw_options::RawOptions;
+use zbus::zvariant::DeserializeDict;
+use zbus::zvariant::ObjectPath;
+use zbus::zvariant::SerializeDict;
+use zbus::zvariant::Type;
+
+use super::{DESTINATION, HandleToken, INTERFACE_REMOTE_DESKTOP, PATH, PortalRequest};
+
+#[derive(Type, Clone, Debug, Default)]
+/// Specified options for a [`RemoteDesktopProxy::create_session`] request.
+#[zvariant(signature = "dict")]
+pub struct CreateSessionOptions {
+    /// A string that will be used as the last element of the handle.
+    handle_token: HandleToken,
+    // TODO: the options of the specifications, with their setters.
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(CreateSessionOptions {
+    handle_token = "handle_token",
+});
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug)]
+/// The response of a [`RemoteDesktopProxy::create_session`] request.
+#[zvariant(signature = "dict")]
+pub struct CreateSessionResponse {
+    // TODO: the results of the specifications.
+}
+
+#[derive(Type, Clone, Debug, Default)]
+/// Specified options for a [`RemoteDesktopProxy::select_devices`] request.
+#[zvariant(signature = "dict")]
+pub struct SelectDevicesOptions {
+    /// A string that will be used as the last element of the handle.
+    handle_token: HandleToken,
+    // TODO: the options of the specifications, with their setters.
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(SelectDevicesOptions {
+    handle_token = "handle_token",
+});
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug)]
+/// The response of a [`RemoteDesktopProxy::select_devices`] request.
+#[zvariant(signature = "dict")]
+pub struct SelectDevicesResponse {
+    // TODO: the results of the specifications.
+}
+
+#[derive(Type, Clone, Debug, Default)]
+/// Specified options for a [`RemoteDesktopProxy::start`] request.
+#[zvariant(signature = "dict")]
+pub struct StartOptions {
+    /// A string that will be used as the last element of the handle.
+    handle_token: HandleToken,
+    // TODO: the options of the specifications, with their setters.
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(StartOptions {
+    handle_token = "handle_token",
+});
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug)]
+/// The response of a [`RemoteDesktopProxy::start`] request.
+#[zvariant(signature = "dict")]
+pub struct StartResponse {
+    // TODO: the results of the specifications.
+}
+
+#[derive(Type, Clone, Debug, Default)]
+/// Specified options for a [`RemoteDesktopProxy::notify_pointer_motion`] call.
+#[zvariant(signature = "dict")]
+pub struct NotifyPointerMotionOptions {
+    // TODO: the options of the specifications, with their setters.
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(NotifyPointerMotionOptions {});
+
+#[derive(Type, Clone, Debug, Default)]
+/// Specified options for a [`RemoteDesktopProxy::notify_pointer_motion_absolute`] call.
+#[zvariant(signature = "dict")]
+pub struct NotifyPointerMotionAbsoluteOptions {
+    // TODO: the options of the specifications, with their setters.
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(NotifyPointerMotionAbsoluteOptions {});
+
+#[derive(Type, Clone, Debug, Default)]
+/// Specified options for a [`RemoteDesktopProxy::notify_pointer_button`] call.
+#[zvariant(signature = "dict")]
+pub struct NotifyPointerButtonOptions {
+    // TODO: the options of the specifications, with their setters.
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(NotifyPointerButtonOptions {});
+
+#[derive(Type, Clone, Debug, Default)]
+/// Specified options for a [`RemoteDesktopProxy::notify_pointer_axis`] call.
+#[zvariant(signature = "dict")]
+pub struct NotifyPointerAxisOptions {
+    // TODO: the options of the specifications, with their setters.
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(NotifyPointerAxisOptions {});
+
+#[derive(Type, Clone, Debug, Default)]
+/// Specified options for a [`RemoteDesktopProxy::notify_pointer_axis_discrete`] call.
+#[zvariant(signature = "dict")]
+pub struct NotifyPointerAxisDiscreteOptions {
+    // TODO: the options of the specifications, with their setters.
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(NotifyPointerAxisDiscreteOptions {});
+
+#[derive(Type, Clone, Debug, Default)]
+/// Specified options for a [`RemoteDesktopProxy::notify_keyboard_keycode`] call.
+#[zvariant(signature = "dict")]
+pub struct NotifyKeyboardKeycodeOptions {
+    // TODO: the options of the specifications, with their setters.
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(NotifyKeyboardKeycodeOptions {});
+
+#[derive(Type, Clone, Debug, Default)]
+/// Specified options for a [`RemoteDesktopProxy::notify_keyboard_keysym`] call.
+#[zvariant(signature = "dict")]
+pub struct NotifyKeyboardKeysymOptions {
+    // TODO: the options of the specifications, with their setters.
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(NotifyKeyboardKeysymOptions {});
+
+#[derive(Type, Clone, Debug, Default)]
+/// Specified options for a [`RemoteDesktopProxy::notify_touch_down`] call.
+#[zvariant(signature = "dict")]
+pub struct NotifyTouchDownOptions {
+    // TODO: the options of the specifications, with their setters.
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(NotifyTouchDownOptions {});
+
+#[derive(Type, Clone, Debug, Default)]
+/// Specified options for a [`RemoteDesktopProxy::notify_touch_motion`] call.
+#[zvariant(signature = "dict")]
+pub struct NotifyTouchMotionOptions {
+    // TODO: the options of the specifications, with their setters.
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(NotifyTouchMotionOptions {});
+
+#[derive(Type, Clone, Debug, Default)]
+/// Specified options for a [`RemoteDesktopProxy::notify_touch_up`] call.
+#[zvariant(signature = "dict")]
+pub struct NotifyTouchUpOptions {
+    // TODO: the options of the specifications, with their setters.
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(NotifyTouchUpOptions {});
+
+/// TODO: describe the portal.
+///
+/// Wrapper of the DBus interface: [`org.freedesktop.portal.RemoteDesktop`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.RemoteDesktop).
+#[derive(Clone)]
+#[doc(alias = "org.freedesktop.portal.RemoteDesktop")]
+pub struct RemoteDesktopProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(RemoteDesktopProxy, INTERFACE_REMOTE_DESKTOP);
+impl_proxy_timeout!(RemoteDesktopProxy);
+
+impl<'a> RemoteDesktopProxy<'a> {
+    /// Create a new instance of [`RemoteDesktopProxy`].
+    pub async fn new(connection: &zbus::Connection) -> Result<RemoteDesktopProxy<'a>, Error> {
+        let proxy = zbus::ProxyBuilder::new_bare(connection)
+            .interface(INTERFACE_REMOTE_DESKTOP)?
+            .path(PATH)?
+            .destination(DESTINATION)?
+            .build()
+            .await?;
+        Ok(Self(proxy, None))
+    }
+
+    /// Get a reference to the underlying Proxy.
+    pub fn inner(&self) -> &zbus::Proxy<'_> {
+        &self.0
+    }
+
+    /// TODO: document the method.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`CreateSession`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.CreateSession).
+    #[doc(alias = "CreateSession")]
+    pub fn create_session<'p>(&'p self, options: CreateSessionOptions) -> PortalRequest<'p, CreateSessionResponse> {
+        let handle_token = options.handle_token.copy();
+        call_request_method(self, &handle_token, "CreateSession", (options,))
+    }
+
+    /// TODO: document the method.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`SelectDevices`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.SelectDevices).
+    #[doc(alias = "SelectDevices")]
+    pub fn select_devices<'p>(&'p self, session_handle: &'p ObjectPath<'p>, options: SelectDevicesOptions) -> PortalRequest<'p, SelectDevicesResponse> {
+        let handle_token = options.handle_token.copy();
+        call_request_method(self, &handle_token, "SelectDevices", (session_handle, options))
+    }
+
+    /// TODO: document the method.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`Start`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.Start).
+    #[doc(alias = "Start")]
+    pub fn start<'p>(&'p self, session_handle: &'p ObjectPath<'p>, identifier: &'p WindowIdentifier, options: StartOptions) -> PortalRequest<'p, StartResponse> {
+        let handle_token = options.handle_token.copy();
+        call_window_request_method(self, identifier, &handle_token, "Start", (session_handle, identifier, options))
+    }
+
+    /// TODO: document the method.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyPointerMotion`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyPointerMotion).
+    #[doc(alias = "NotifyPointerMotion")]
+    pub async fn notify_pointer_motion(&self, session_handle: &ObjectPath<'_>, options: NotifyPointerMotionOptions, dx: f64, dy: f64) -> Result<(), Error> {
+        call_method(self, "NotifyPointerMotion", &(session_handle, options, dx, dy)).await
+    }
+
+    /// TODO: document the method.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyPointerMotionAbsolute`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyPointerMotionAbsolute).
+    #[doc(alias = "NotifyPointerMotionAbsolute")]
+    pub async fn notify_pointer_motion_absolute(&self, session_handle: &ObjectPath<'_>, options: NotifyPointerMotionAbsoluteOptions, stream: u32, x: f64, y: f64) -> Result<(), Error> {
+        call_method(self, "NotifyPointerMotionAbsolute", &(session_handle, options, stream, x, y)).await
+    }
+
+    /// TODO: document the method.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyPointerButton`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyPointerButton).
+    #[doc(alias = "NotifyPointerButton")]
+    pub async fn notify_pointer_button(&self, session_handle: &ObjectPath<'_>, options: NotifyPointerButtonOptions, button: i32, state: u32) -> Result<(), Error> {
+        call_method(self, "NotifyPointerButton", &(session_handle, options, button, state)).await
+    }
+
+    /// TODO: document the method.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyPointerAxis`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyPointerAxis).
+    #[doc(alias = "NotifyPointerAxis")]
+    pub async fn notify_pointer_axis(&self, session_handle: &ObjectPath<'_>, options: NotifyPointerAxisOptions, dx: f64, dy: f64) -> Result<(), Error> {
+        call_method(self, "NotifyPointerAxis", &(session_handle, options, dx, dy)).await
+    }
+
+    /// TODO: document the method.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyPointerAxisDiscrete`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyPointerAxisDiscrete).
+    #[doc(alias = "NotifyPointerAxisDiscrete")]
+    pub async fn notify_pointer_axis_discrete(&self, session_handle: &ObjectPath<'_>, options: NotifyPointerAxisDiscreteOptions, axis: u32, steps: i32) -> Result<(), Error> {
+        call_method(self, "NotifyPointerAxisDiscrete", &(session_handle, options, axis, steps)).await
+    }
+
+    /// TODO: document the method.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyKeyboardKeycode`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyKeyboardKeycode).
+    #[doc(alias = "NotifyKeyboardKeycode")]
+    pub async fn notify_keyboard_keycode(&self, session_handle: &ObjectPath<'_>, options: NotifyKeyboardKeycodeOptions, keycode: i32, state: u32) -> Result<(), Error> {
+        call_method(self, "NotifyKeyboardKeycode", &(session_handle, options, keycode, state)).await
+    }
+
+    /// TODO: document the method.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyKeyboardKeysym`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyKeyboardKeysym).
+    #[doc(alias = "NotifyKeyboardKeysym")]
+    pub async fn notify_keyboard_keysym(&self, session_handle: &ObjectPath<'_>, options: NotifyKeyboardKeysymOptions, keysym: i32, state: u32) -> Result<(), Error> {
+        call_method(self, "NotifyKeyboardKeysym", &(session_handle, options, keysym, state)).await
+    }
+
+    /// TODO: document the method.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyTouchDown`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyTouchDown).
+    #[doc(alias = "NotifyTouchDown")]
+    pub async fn notify_touch_down(&self, session_handle: &ObjectPath<'_>, options: NotifyTouchDownOptions, stream: u32, slot: u32, x: f64, y: f64) -> Result<(), Error> {
+        call_method(self, "NotifyTouchDown", &(session_handle, options, stream, slot, x, y)).await
+    }
+
+    /// TODO: document the method.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyTouchMotion`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyTouchMotion).
+    #[doc(alias = "NotifyTouchMotion")]
+    pub async fn notify_touch_motion(&self, session_handle: &ObjectPath<'_>, options: NotifyTouchMotionOptions, stream: u32, slot: u32, x: f64, y: f64) -> Result<(), Error> {
+        call_method(self, "NotifyTouchMotion", &(session_handle, options, stream, slot, x, y)).await
+    }
+
+    /// TODO: document the method.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyTouchUp`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyTouchUp).
+    #[doc(alias = "NotifyTouchUp")]
+    pub async fn notify_touch_up(&self, session_handle: &ObjectPath<'_>, options: NotifyTouchUpOptions, slot: u32) -> Result<(), Error> {
+        call_method(self, "NotifyTouchUp", &(session_handle, options, slot)).await
+    }
+
+    /// TODO: document the property.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`AvailableDeviceTypes`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-property-org-freedesktop-portal-RemoteDesktop.AvailableDeviceTypes).
+    #[doc(alias = "AvailableDeviceTypes")]
+    pub async fn available_device_types(&self) -> Result<u32, Error> {
+        get_property::<u32>(self, "AvailableDeviceTypes").await
+    }
+}
@@ -0,0 +1,535 @@
+//! Emits the skeleton of a portal module, following the layout of the ones of
+//! `src/desktop`. The documentation and the keys of the vardicts aren't part
+//! of the XML files, they are left as TODOs.
+
+use std::{collections::BTreeSet, fmt::Write};
+
+use crate::parse::{Arg, Interface, Method};
+
+const SPEC_URL: &str = "https://flatpak.github.io/xdg-desktop-portal/index.html";
+
+/// `GetUserInformation` to `get_user_information`.
+pub fn snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut snake = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev_lower = chars[i - 1].is_lowercase() || chars[i - 1].is_ascii_digit();
+            let next_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            if prev_lower || (chars[i - 1].is_uppercase() && next_lower) {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    match snake.as_str() {
+        "type" | "ref" | "move" | "mod" | "self" | "use" => format!("{}_", snake),
+        _ => snake,
+    }
+}
+
+/// `org.freedesktop.portal.Flatpak.UpdateMonitor` to `FlatpakUpdateMonitor`.
+pub fn short_name(interface: &str) -> String {
+    interface
+        .strip_prefix("org.freedesktop.portal.")
+        .unwrap_or(interface)
+        .replace('.', "")
+}
+
+// The end of the type starting `signature`, a single complete type.
+fn split_type(signature: &str) -> (&str, &str) {
+    let mut depth = 0;
+    for (i, c) in signature.char_indices() {
+        match c {
+            '(' | '{' => depth += 1,
+            ')' | '}' => depth -= 1,
+            'a' => continue,
+            _ => {}
+        }
+        if depth == 0 {
+            return signature.split_at(i + 1);
+        }
+    }
+    (signature, "")
+}
+
+/// The owned Rust type of a D-Bus `signature`, as read from a reply or a
+/// signal.
+pub fn owned_type(signature: &str, uses: &mut BTreeSet<&'static str>) -> String {
+    match signature {
+        "y" => "u8".to_owned(),
+        "b" => "bool".to_owned(),
+        "n" => "i16".to_owned(),
+        "q" => "u16".to_owned(),
+        "i" => "i32".to_owned(),
+        "u" => "u32".to_owned(),
+        "x" => "i64".to_owned(),
+        "t" => "u64".to_owned(),
+        "d" => "f64".to_owned(),
+        "s" => "String".to_owned(),
+        "o" => {
+            uses.insert("zbus::zvariant::OwnedObjectPath");
+            "OwnedObjectPath".to_owned()
+        }
+        "h" => {
+            uses.insert("zbus::zvariant::OwnedFd");
+            "OwnedFd".to_owned()
+        }
+        "v" => {
+            uses.insert("zbus::zvariant::OwnedValue");
+            "OwnedValue".to_owned()
+        }
+        _ if signature.starts_with("a{") => {
+            uses.insert("std::collections::HashMap");
+            let (key, value) = split_type(&signature[2..signature.len() - 1]);
+            format!(
+                "HashMap<{}, {}>",
+                owned_type(key, uses),
+                owned_type(value, uses)
+            )
+        }
+        _ if signature.starts_with('a') => format!("Vec<{}>", owned_type(&signature[1..], uses)),
+        _ if signature.starts_with('(') => {
+            let mut fields = &signature[1..signature.len() - 1];
+            let mut types = Vec::new();
+            while !fields.is_empty() {
+                let (field, rest) = split_type(fields);
+                types.push(owned_type(field, uses));
+                fields = rest;
+            }
+            match types.len() {
+                1 => format!("({},)", types[0]),
+                _ => format!("({})", types.join(", ")),
+            }
+        }
+        _ => format!("() /* TODO: {} */", signature),
+    }
+}
+
+// The type of a method argument, along with how it's put in the body. The
+// borrowed arguments of the requests live as long as the returned
+// `PortalRequest`, `'p`.
+fn param(arg: &Arg, request: bool, uses: &mut BTreeSet<&'static str>) -> (String, String, String) {
+    let name = snake_case(&arg.name);
+    let lifetime = if request { "'p " } else { "" };
+    let (name, ty, value) = match arg.ty.as_str() {
+        "s" if matches!(arg.name.as_str(), "parent_window" | "window") => {
+            uses.insert("crate::WindowIdentifier");
+            let value = if request { "identifier" } else { "&identifier" };
+            (
+                "identifier".to_owned(),
+                format!("&{}WindowIdentifier", lifetime),
+                value.to_owned(),
+            )
+        }
+        "s" => (name.clone(), format!("&{}str", lifetime), name),
+        "as" => (name.clone(), format!("&{0}[&{0}str]", lifetime), name),
+        "ay" => (name.clone(), format!("&{}[u8]", lifetime), name),
+        "o" => {
+            uses.insert("zbus::zvariant::ObjectPath");
+            let inner = if request { "'p" } else { "'_" };
+            (
+                name.clone(),
+                format!("&{}ObjectPath<{}>", lifetime, inner),
+                name,
+            )
+        }
+        "h" => {
+            uses.insert("std::os::unix::io::AsRawFd");
+            uses.insert("zbus::zvariant::Fd");
+            (
+                name.clone(),
+                "&impl AsRawFd".to_owned(),
+                format!("Fd::from({}.as_raw_fd())", name),
+            )
+        }
+        ty if ty.len() == 1 => (name.clone(), owned_type(ty, uses), name),
+        ty => (
+            name.clone(),
+            format!("&{}{}", lifetime, owned_type(ty, uses)),
+            name,
+        ),
+    };
+    (name, ty, value)
+}
+
+fn is_options(arg: &Arg) -> bool {
+    arg.ty == "a{sv}" && arg.name == "options"
+}
+
+fn spec_link(kind: &str, interface: &str, member: &str) -> String {
+    format!(
+        "{}#gdbus-{}-{}.{}",
+        SPEC_URL,
+        kind,
+        interface.replace('.', "-"),
+        member
+    )
+}
+
+fn tuple_type(args: &[&Arg], uses: &mut BTreeSet<&'static str>) -> String {
+    let types = args
+        .iter()
+        .map(|arg| owned_type(&arg.ty, uses))
+        .collect::<Vec<_>>();
+    match types.len() {
+        0 => "()".to_owned(),
+        1 => types[0].clone(),
+        _ => format!("({})", types.join(", ")),
+    }
+}
+
+fn write_options(out: &mut String, proxy: &str, method: &Method, handle: bool) -> String {
+    let name = format!("{}Options", method.name);
+    let method_name = snake_case(&method.name);
+    let kind = if handle { "request" } else { "call" };
+    writeln!(
+        out,
+        "#[derive(Type, Clone, Debug, Default)]
+/// Specified options for a [`{proxy}::{method_name}`] {kind}.
+#[zvariant(signature = \"dict\")]
+pub struct {name} {{"
+    )
+    .unwrap();
+    if handle {
+        out.push_str(
+            "    /// A string that will be used as the last element of the handle.
+    handle_token: HandleToken,\n",
+        );
+    }
+    out.push_str(
+        "    // TODO: the options of the specifications, with their setters.
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+",
+    );
+    if handle {
+        writeln!(
+            out,
+            "impl_options_dict!({} {{
+    handle_token = \"handle_token\",
+}});
+",
+            name
+        )
+        .unwrap();
+    } else {
+        writeln!(out, "impl_options_dict!({} {{}});\n", name).unwrap();
+    }
+    name
+}
+
+fn write_method(
+    body: &mut String,
+    items: &mut String,
+    interface: &Interface,
+    method: &Method,
+    uses: &mut BTreeSet<&'static str>,
+) {
+    let proxy = format!("{}Proxy", short_name(&interface.name));
+    let fn_name = snake_case(&method.name);
+    let request = method.is_request();
+    let mut params = Vec::new();
+    let mut values = Vec::new();
+    let mut window = false;
+    for arg in method.inputs() {
+        if is_options(arg) {
+            uses.insert("zbus::zvariant::Type");
+            uses.insert("crate::raw_options::RawOptions");
+            if request {
+                uses.insert("super::HandleToken");
+            }
+            let name = write_options(items, &proxy, method, request);
+            params.push(format!("options: {}", name));
+            values.push("options".to_owned());
+        } else {
+            let (name, ty, value) = param(arg, request, uses);
+            window |= name == "identifier";
+            params.push(format!("{}: {}", name, ty));
+            values.push(value);
+        }
+    }
+    let values = match values.len() {
+        0 => "()".to_owned(),
+        1 => format!("({},)", values[0]),
+        _ => format!("({})", values.join(", ")),
+    };
+    let params = params
+        .iter()
+        .map(|param| format!(", {}", param))
+        .collect::<String>();
+    writeln!(
+        body,
+        "
+    /// TODO: document the method.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`{name}`]({link}).
+    #[doc(alias = \"{name}\")]",
+        name = method.name,
+        link = spec_link("method", &interface.name, &method.name),
+    )
+    .unwrap();
+
+    if request {
+        let response = format!("{}Response", method.name);
+        uses.insert("zbus::zvariant::DeserializeDict");
+        uses.insert("zbus::zvariant::SerializeDict");
+        uses.insert("zbus::zvariant::Type");
+        uses.insert("super::PortalRequest");
+        writeln!(
+            items,
+            "#[derive(SerializeDict, DeserializeDict, Type, Debug)]
+/// The response of a [`{proxy}::{fn_name}`] request.
+#[zvariant(signature = \"dict\")]
+pub struct {response} {{
+    // TODO: the results of the specifications.
+}}
+"
+        )
+        .unwrap();
+        writeln!(
+            body,
+            "    pub fn {fn_name}<'p>(&'p self{params}) -> PortalRequest<'p, {response}> {{"
+        )
+        .unwrap();
+        let has_handle_token = method.inputs().any(is_options);
+        if has_handle_token {
+            body.push_str("        let handle_token = options.handle_token.copy();\n");
+        } else {
+            uses.insert("super::HandleToken");
+            body.push_str("        let handle_token = HandleToken::default();\n");
+        }
+        if window {
+            uses.insert("crate::helpers::call_window_request_method");
+            writeln!(
+                body,
+                "        call_window_request_method(self, identifier, &handle_token, \"{}\", {})",
+                method.name, values
+            )
+            .unwrap();
+        } else {
+            uses.insert("crate::helpers::call_request_method");
+            writeln!(
+                body,
+                "        call_request_method(self, &handle_token, \"{}\", {})",
+                method.name, values
+            )
+            .unwrap();
+        }
+    } else {
+        let output = tuple_type(&method.outputs().collect::<Vec<_>>(), uses);
+        uses.insert("crate::helpers::call_method");
+        writeln!(
+            body,
+            "    pub async fn {fn_name}(&self{params}) -> Result<{output}, Error> {{
+        call_method(self, \"{}\", &{}).await",
+            method.name, values
+        )
+        .unwrap();
+    }
+    body.push_str("    }\n");
+}
+
+fn write_signal(
+    body: &mut String,
+    interface: &Interface,
+    signal: &Method,
+    uses: &mut BTreeSet<&'static str>,
+) {
+    uses.insert("futures::Stream");
+    uses.insert("crate::helpers::receive_signal_stream");
+    let item = tuple_type(&signal.args.iter().collect::<Vec<_>>(), uses);
+    writeln!(
+        body,
+        "
+    /// TODO: document the signal.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`{name}`]({link}).
+    #[doc(alias = \"{name}\")]
+    pub async fn receive_{fn_name}(
+        &self,
+    ) -> Result<impl Stream<Item = {item}> + Unpin + 'a, Error> {{
+        receive_signal_stream(&self.0, \"{name}\").await
+    }}",
+        name = signal.name,
+        fn_name = snake_case(&signal.name),
+        link = spec_link("signal", &interface.name, &signal.name),
+    )
+    .unwrap();
+}
+
+/// The module wrapping `interface`.
+pub fn module(interface: &Interface) -> String {
+    let short = short_name(&interface.name);
+    let proxy = format!("{}Proxy", short);
+    let constant = format!("INTERFACE_{}", snake_case(&short).to_uppercase());
+    let mut uses = BTreeSet::new();
+    uses.insert("crate::Error");
+    let mut items = String::new();
+    let mut body = String::new();
+
+    for method in &interface.methods {
+        write_method(&mut body, &mut items, interface, method, &mut uses);
+    }
+    for signal in &interface.signals {
+        write_signal(&mut body, interface, signal, &mut uses);
+    }
+    for property in &interface.properties {
+        if property.name == "version" {
+            continue;
+        }
+        uses.insert("crate::helpers::get_property");
+        let ty = owned_type(&property.ty, &mut uses);
+        writeln!(
+            body,
+            "
+    /// TODO: document the property.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`{name}`]({link}).
+    #[doc(alias = \"{name}\")]
+    pub async fn {fn_name}(&self) -> Result<{ty}, Error> {{
+        get_property::<{ty}>(self, \"{name}\").await
+    }}",
+            name = property.name,
+            fn_name = snake_case(&property.name),
+            link = spec_link("property", &interface.name, &property.name),
+        )
+        .unwrap();
+    }
+
+    let mut out = String::from("//! TODO: an example of the portal.\n\n");
+    let mut supers = vec!["DESTINATION", constant.as_str(), "PATH"];
+    for path in &uses {
+        match path.strip_prefix("super::") {
+            Some(item) => supers.push(item),
+            None => writeln!(out, "use {};", path).unwrap(),
+        }
+    }
+    supers.sort_unstable();
+    writeln!(out, "\nuse super::{{{}}};\n", supers.join(", ")).unwrap();
+    out.push_str(&items);
+    writeln!(
+        out,
+        "/// TODO: describe the portal.
+///
+/// Wrapper of the DBus interface: [`{name}`]({url}#gdbus-{name}).
+#[derive(Clone)]
+#[doc(alias = \"{name}\")]
+pub struct {proxy}<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!({proxy}, {constant});
+impl_proxy_timeout!({proxy});
+
+impl<'a> {proxy}<'a> {{
+    /// Create a new instance of [`{proxy}`].
+    pub async fn new(connection: &zbus::Connection) -> Result<{proxy}<'a>, Error> {{
+        let proxy = zbus::ProxyBuilder::new_bare(connection)
+            .interface({constant})?
+            .path(PATH)?
+            .destination(DESTINATION)?
+            .build()
+            .await?;
+        Ok(Self(proxy, None))
+    }}
+
+    /// Get a reference to the underlying Proxy.
+    pub fn inner(&self) -> &zbus::Proxy<'_> {{
+        &self.0
+    }}
+{body}}}",
+        name = interface.name,
+        url = SPEC_URL,
+    )
+    .unwrap();
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::interfaces;
+
+    #[test]
+    fn names() {
+        assert_eq!(snake_case("GetUserInformation"), "get_user_information");
+        assert_eq!(snake_case("OpenURI"), "open_uri");
+        assert_eq!(snake_case("RTTimeUSecMax"), "rt_time_u_sec_max");
+        assert_eq!(snake_case("type"), "type_");
+        assert_eq!(
+            short_name("org.freedesktop.portal.Flatpak.UpdateMonitor"),
+            "FlatpakUpdateMonitor"
+        );
+    }
+
+    #[test]
+    fn types() {
+        let mut uses = BTreeSet::new();
+        assert_eq!(
+            owned_type("a{sa{sv}}", &mut uses),
+            "HashMap<String, HashMap<String, OwnedValue>>"
+        );
+        assert_eq!(owned_type("a(ss)", &mut uses), "Vec<(String, String)>");
+        assert_eq!(owned_type("(u)", &mut uses), "(u32,)");
+        assert_eq!(owned_type("ao", &mut uses), "Vec<OwnedObjectPath>");
+    }
+
+    #[test]
+    fn module() {
+        let xml = r#"<node><interface name="org.freedesktop.portal.Account">
+    <method name="GetUserInformation">
+      <arg type="s" name="window" direction="in" />
+      <arg type="a{sv}" name="options" direction="in" />
+      <arg type="o" name="handle" direction="out" />
+    </method>
+    <property type="u" name="version" access="read" />
+  </interface></node>"#;
+        let module = super::module(&interfaces(xml).unwrap()[0]);
+        assert!(module.contains("pub struct AccountProxy<'a>"));
+        // `Debug` is implemented by `impl_proxy_timeout!`.
+        assert!(
+            module.contains("#[derive(Clone)]\n#[doc(alias = \"org.freedesktop.portal.Account\")]")
+        );
+        assert!(module.contains("pub struct GetUserInformationOptions {"));
+        assert!(module.contains(
+            "pub fn get_user_information<'p>(&'p self, identifier: &'p WindowIdentifier, options: GetUserInformationOptions) -> PortalRequest<'p, GetUserInformationResponse> {"
+        ));
+        assert!(!module.contains("negotiate_version"));
+        assert!(module.contains(
+            "use super::{DESTINATION, HandleToken, INTERFACE_ACCOUNT, PATH, PortalRequest};"
+        ));
+        assert!(!module.contains("fn version"));
+    }
+
+    // The modules of `snapshots/` are built by the tests of the crate, they
+    // have to be the current output of the generator.
+    #[test]
+    fn snapshots() {
+        let xml = include_str!("../../interfaces/org.freedesktop.portal.Desktop.xml");
+        let interfaces = interfaces(xml).unwrap();
+        for (name, snapshot) in [
+            (
+                "org.freedesktop.portal.Location",
+                include_str!("../snapshots/location.rs"),
+            ),
+            (
+                "org.freedesktop.portal.RemoteDesktop",
+                include_str!("../snapshots/remote_desktop.rs"),
+            ),
+        ] {
+            let interface = interfaces.iter().find(|i| i.name == name).unwrap();
+            assert!(
+                super::module(interface) == snapshot,
+                "Outdated snapshot of {}, regenerate it with `cargo run --manifest-path codegen/Cargo.toml -- interfaces/org.freedesktop.portal.Desktop.xml {0} > codegen/snapshots/<name>.rs`",
+                name
+            );
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! Generates the skeleton of the module wrapping a portal, out of the
+//! xdg-desktop-portal interface definitions.
+//!
+//! ```text
+//! cargo run --manifest-path codegen/Cargo.toml -- \
+//!     interfaces/org.freedesktop.portal.Desktop.xml org.freedesktop.portal.Trash
+//! ```
+//!
+//! Without an interface name, the interfaces of the file are listed. The
+//! module is printed on the standard output, run `cargo fmt` on it once added
+//! to the crate. The modules of `snapshots/` are built along with the tests of
+//! the crate, regenerate them when changing the output.
+
+mod generate;
+mod parse;
+
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let path = match args.first() {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: ashpd-codegen <interfaces.xml> [interface]");
+            return ExitCode::FAILURE;
+        }
+    };
+    let interfaces = match std::fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|xml| parse::interfaces(&xml))
+    {
+        Ok(interfaces) => interfaces,
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", path, err);
+            return ExitCode::FAILURE;
+        }
+    };
+    match args.get(1) {
+        None => {
+            for interface in &interfaces {
+                println!("{}", interface.name);
+            }
+        }
+        Some(name) => match interfaces.iter().find(|interface| &interface.name == name) {
+            Some(interface) => print!("{}", generate::module(interface)),
+            None => {
+                eprintln!("No interface {} in {}", name, path);
+                return ExitCode::FAILURE;
+            }
+        },
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,164 @@
+//! A small reader of the D-Bus introspection XML, enough for the portals
+//! interface definitions.
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Arg {
+    pub name: String,
+    pub ty: String,
+    /// Only the methods arguments have a direction, `in` by default.
+    pub out: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Method {
+    pub name: String,
+    pub args: Vec<Arg>,
+}
+
+impl Method {
+    pub fn inputs(&self) -> impl Iterator<Item = &Arg> {
+        self.args.iter().filter(|arg| !arg.out)
+    }
+
+    pub fn outputs(&self) -> impl Iterator<Item = &Arg> {
+        self.args.iter().filter(|arg| arg.out)
+    }
+
+    /// Whether the method goes through a `org.freedesktop.portal.Request`,
+    /// the reply being a handle.
+    pub fn is_request(&self) -> bool {
+        let outputs = self.outputs().collect::<Vec<_>>();
+        outputs.len() == 1 && outputs[0].name == "handle" && outputs[0].ty == "o"
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Property {
+    pub name: String,
+    pub ty: String,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Interface {
+    pub name: String,
+    pub methods: Vec<Method>,
+    pub signals: Vec<Method>,
+    pub properties: Vec<Property>,
+}
+
+/// The `name="value"` attributes of a tag.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let start = tag.find(&format!(" {}=", name))? + name.len() + 2;
+    let quote = tag[start..].chars().next()?;
+    let value = tag[start + 1..].split(quote).next()?;
+    Some(value.replace("&gt;", ">").replace("&lt;", "<"))
+}
+
+/// Parse the interfaces of an introspection XML document. The comments and
+/// the documentation elements are skipped.
+pub fn interfaces(xml: &str) -> Result<Vec<Interface>, String> {
+    let mut interfaces = Vec::new();
+    // The signal or method being read, along with whether it's a signal.
+    let mut member: Option<(Method, bool)> = None;
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").ok_or("Unterminated comment")?;
+            rest = &rest[end + 3..];
+            continue;
+        }
+        let end = rest.find('>').ok_or("Unterminated tag")?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+        let closing = tag.starts_with('/');
+        let self_closing = tag.ends_with('/');
+        let element = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default();
+        let name = attribute(tag, "name");
+        match (element, closing) {
+            ("interface", false) => interfaces.push(Interface {
+                name: name.ok_or("An interface without a name")?,
+                ..Default::default()
+            }),
+            ("method" | "signal", false) => {
+                let method = Method {
+                    name: name.ok_or("A method or signal without a name")?,
+                    args: Vec::new(),
+                };
+                member = Some((method, element == "signal"));
+            }
+            ("arg", false) => {
+                let (method, _) = member.as_mut().ok_or("An argument outside of a method")?;
+                method.args.push(Arg {
+                    name: name.unwrap_or_else(|| format!("arg{}", method.args.len())),
+                    ty: attribute(tag, "type").ok_or("An argument without a type")?,
+                    out: attribute(tag, "direction").as_deref() == Some("out"),
+                });
+            }
+            ("property", false) => {
+                let interface = interfaces
+                    .last_mut()
+                    .ok_or("A property outside of an interface")?;
+                interface.properties.push(Property {
+                    name: name.ok_or("A property without a name")?,
+                    ty: attribute(tag, "type").ok_or("A property without a type")?,
+                });
+            }
+            _ => {}
+        }
+        let ends_member = matches!(element, "method" | "signal") && (closing || self_closing);
+        if ends_member {
+            if let Some((method, is_signal)) = member.take() {
+                let interface = interfaces
+                    .last_mut()
+                    .ok_or("A method outside of an interface")?;
+                if is_signal {
+                    interface.signals.push(method);
+                } else {
+                    interface.methods.push(method);
+                }
+            }
+        }
+    }
+    Ok(interfaces)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let xml = r#"<node>
+  <!-- <interface name="org.example.Commented"/> -->
+  <interface name="org.freedesktop.portal.Trash">
+    <method name="TrashFile">
+      <arg type="h" name="fd" direction="in"/>
+      <arg type="u" name="result" direction="out"/>
+    </method>
+    <method name="Pick">
+      <arg type="a{sv}" name="options" direction="in"/>
+      <arg type="o" name="handle" direction="out"/>
+    </method>
+    <signal name="Changed">
+      <arg type="a{sv}" name="changes"/>
+    </signal>
+    <property type="u" name="version" access="read"/>
+  </interface>
+</node>"#;
+        let interfaces = interfaces(xml).unwrap();
+        assert_eq!(interfaces.len(), 1);
+        let trash = &interfaces[0];
+        assert_eq!(trash.name, "org.freedesktop.portal.Trash");
+        assert_eq!(trash.methods.len(), 2);
+        assert_eq!(trash.methods[0].inputs().count(), 1);
+        assert!(!trash.methods[0].is_request());
+        assert!(trash.methods[1].is_request());
+        assert_eq!(trash.signals[0].args[0].ty, "a{sv}");
+        assert_eq!(trash.properties[0].name, "version");
+    }
+}
@@ -0,0 +1,195 @@
+//! Record several monitors or windows at once, restoring the previous selection
+//! without asking the user again.
+//!
+//! The restore token returned by the portal is stored in
+//! `$XDG_STATE_HOME/ashpd/screencast-restore-token` and passed back on the
+//! next run. The streams are then looked up on the PipeWire remote opened by
+//! the portal.
+//!
+//! Run it with `cargo run --example screencast --features feature_pipewire`.
+
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    os::unix::prelude::{FromRawFd, OwnedFd, RawFd},
+    path::PathBuf,
+    rc::Rc,
+};
+
+use ashpd::{
+    desktop::screencast::{
+        CursorMode, PersistMode, ScreenCastProxy, SelectSourcesOptions, SourceType, Stream,
+    },
+    enumflags2::BitFlags,
+    WindowIdentifier,
+};
+
+fn restore_token_path() -> Option<PathBuf> {
+    let state_dir = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state"))
+        })?;
+    Some(state_dir.join("ashpd").join("screencast-restore-token"))
+}
+
+fn load_restore_token() -> Option<String> {
+    let token = std::fs::read_to_string(restore_token_path()?).ok()?;
+    let token = token.trim();
+    (!token.is_empty()).then(|| token.to_owned())
+}
+
+fn save_restore_token(token: &str) -> std::io::Result<()> {
+    let path = match restore_token_path() {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, token)
+}
+
+// Prefers the cursor as metadata, so it can be drawn at will, over embedding it
+// in the frames.
+fn cursor_mode(available: BitFlags<CursorMode>) -> CursorMode {
+    if available.contains(CursorMode::Metadata) {
+        CursorMode::Metadata
+    } else if available.contains(CursorMode::Embedded) {
+        CursorMode::Embedded
+    } else {
+        CursorMode::Hidden
+    }
+}
+
+// Sorts the monitors from left to right then top to bottom, the way they are
+// laid out in the compositor, the windows and virtual sources come last.
+fn sort_streams(streams: &mut [Stream]) {
+    streams.sort_by_key(|stream| match (stream.source_type(), stream.position()) {
+        (SourceType::Monitor, Some((x, y))) => (0, x, y),
+        (SourceType::Monitor, None) => (1, 0, 0),
+        _ => (2, 0, 0),
+    });
+}
+
+// The area covering all the monitors, in the compositor's logical coordinates.
+fn bounding_box(streams: &[Stream]) -> Option<(i32, i32, i32, i32)> {
+    streams
+        .iter()
+        .filter(|stream| stream.source_type() == SourceType::Monitor)
+        .filter_map(|stream| {
+            let (x, y) = stream.position()?;
+            let (width, height) = stream.size()?;
+            Some((x, y, x + width, y + height))
+        })
+        .reduce(|(x1, y1, x2, y2), (a1, b1, a2, b2)| {
+            (x1.min(a1), y1.min(b1), x2.max(a2), y2.max(b2))
+        })
+}
+
+// Looks up the node of each stream on the PipeWire remote opened by the portal,
+// that's where a consumer would connect a `pw::stream::Stream` to receive the
+// frames.
+fn inspect_nodes(fd: RawFd, streams: &[Stream]) -> Result<(), pw::Error> {
+    let mainloop = pw::main_loop::MainLoop::new(None)?;
+    let context = pw::context::Context::new(&mainloop)?;
+    // The fd of the remote is owned by the core from now on.
+    let core = context.connect_fd(unsafe { OwnedFd::from_raw_fd(fd) }, None)?;
+    let registry = core.get_registry()?;
+
+    let pending = Rc::new(RefCell::new(
+        streams
+            .iter()
+            .map(|stream| stream.pipe_wire_node_id())
+            .collect::<HashSet<_>>(),
+    ));
+    let loop_clone = mainloop.clone();
+    let _listener = registry
+        .add_listener_local()
+        .global(move |global| {
+            if !pending.borrow_mut().remove(&global.id) {
+                return;
+            }
+            println!("Node {}:", global.id);
+            if let Some(props) = &global.props {
+                for key in ["node.name", "media.class", "node.description"] {
+                    if let Some(value) = props.get(key) {
+                        println!("    {}: {}", key, value);
+                    }
+                }
+            }
+            if pending.borrow().is_empty() {
+                loop_clone.quit();
+            }
+        })
+        .register();
+    mainloop.run();
+    Ok(())
+}
+
+async fn run() -> ashpd::Result<()> {
+    let connection = zbus::Connection::session().await?;
+    let proxy = ScreenCastProxy::new(&connection).await?;
+
+    let source_types = proxy.available_source_types().await?;
+    let types = source_types & (SourceType::Monitor | SourceType::Window);
+    let cursor_mode = cursor_mode(proxy.available_cursor_modes().await?);
+
+    let mut options = SelectSourcesOptions::default()
+        .with_multiple(true)
+        .with_types(types)
+        .with_cursor_mode(cursor_mode)
+        .with_persist_mode(PersistMode::ExplicitlyRevoked);
+    // Without a token, or if it was revoked, the user is asked to select the
+    // sources again.
+    if let Some(token) = load_restore_token() {
+        options = options.with_restore_token(token);
+    }
+
+    let session = proxy.create_session().await?;
+    proxy.select_sources(&session, options).await?;
+    let response = proxy.start(&session, &WindowIdentifier::default()).await?;
+
+    // A token can only be used once, the portal hands a new one every time.
+    if let Some(token) = response.restore_token() {
+        if let Err(err) = save_restore_token(token) {
+            eprintln!("Failed to save the restore token: {}", err);
+        }
+    }
+
+    let mut streams = response.streams().to_vec();
+    sort_streams(&mut streams);
+    for stream in &streams {
+        println!(
+            "{:?} stream {} at {:?} of size {:?}",
+            stream.source_type(),
+            stream.pipe_wire_node_id(),
+            stream.position(),
+            stream.size()
+        );
+    }
+    if let Some((x1, y1, x2, y2)) = bounding_box(&streams) {
+        println!(
+            "The monitors span {}x{} from ({}, {})",
+            x2 - x1,
+            y2 - y1,
+            x1,
+            y1
+        );
+    }
+
+    let fd = proxy.open_pipe_wire_remote(&session).await?;
+    if let Err(err) = inspect_nodes(fd, &streams) {
+        eprintln!("Failed to inspect the PipeWire nodes: {}", err);
+    }
+
+    session.close().await?;
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = futures::executor::block_on(run()) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
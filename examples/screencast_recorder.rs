@@ -0,0 +1,521 @@
+//! Record a monitor or a window to a file, in the spirit of `wf-recorder`.
+//!
+//! The example starts a screen cast session, connects a PipeWire stream to the
+//! node handed by the portal on the remote it opened, negotiates a raw video
+//! format and writes the frames either:
+//!
+//! - as they are to `OUTPUT`, the command line to play it back is printed
+//!   once done;
+//! - or into a GStreamer pipeline encoding them to a Matroska file with
+//!   `--gst`, `gst-launch-1.0` has to be installed.
+//!
+//! The recording stops after `--frames N` frames, on Ctrl+C, or when the
+//! session is closed by the compositor or the user.
+//!
+//! Run it with `cargo run --example screencast_recorder --features
+//! feature_pipewire -- [--gst] [--frames N] OUTPUT`.
+
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{BufWriter, Write},
+    os::unix::prelude::{FromRawFd, OwnedFd, RawFd},
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    rc::Rc,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use ashpd::{
+    desktop::screencast::{
+        CursorMode, PersistMode, ScreenCastProxy, SelectSourcesOptions, SourceType,
+    },
+    WindowIdentifier,
+};
+use futures::{channel::oneshot, StreamExt};
+use pw::spa::{
+    param::{
+        format::{FormatProperties, MediaSubtype, MediaType},
+        format_utils,
+        video::{VideoFormat as SpaVideoFormat, VideoInfoRaw},
+        ParamType,
+    },
+    pod::{serialize::PodSerializer, ChoiceValue, Object, Pod, Property, PropertyFlags, Value},
+    utils::{Choice, ChoiceEnum, ChoiceFlags, Direction, Fraction, Id, Rectangle, SpaTypes},
+};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_interrupt(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+struct Args {
+    output: PathBuf,
+    frames: Option<u64>,
+    gst: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut output = None;
+    let mut frames = None;
+    let mut gst = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--gst" => gst = true,
+            "--frames" => {
+                let count = args.next().ok_or("--frames expects a number")?;
+                frames = Some(count.parse().map_err(|_| "--frames expects a number")?);
+            }
+            _ if output.is_none() && !arg.starts_with("--") => output = Some(arg.into()),
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+    Ok(Args {
+        output: output.ok_or("Usage: screencast_recorder [--gst] [--frames N] OUTPUT")?,
+        frames,
+        gst,
+    })
+}
+
+// The pixel formats the example knows how to hand to GStreamer and ffmpeg,
+// 4 bytes per pixel.
+const FORMATS: &[(SpaVideoFormat, &str, &str)] = &[
+    (SpaVideoFormat::BGRx, "BGRx", "bgr0"),
+    (SpaVideoFormat::BGRA, "BGRA", "bgra"),
+    (SpaVideoFormat::RGBx, "RGBx", "rgb0"),
+    (SpaVideoFormat::RGBA, "RGBA", "rgba"),
+];
+
+#[derive(Clone, Copy, Debug)]
+struct VideoFormat {
+    format: SpaVideoFormat,
+    width: u32,
+    height: u32,
+    framerate: Fraction,
+}
+
+impl VideoFormat {
+    fn names(&self) -> (&'static str, &'static str) {
+        FORMATS
+            .iter()
+            .find(|(format, _, _)| *format == self.format)
+            .map(|(_, gst, ffmpeg)| (*gst, *ffmpeg))
+            .unwrap_or(("BGRx", "bgr0"))
+    }
+
+    // A frame rate of 0/1 means the frames are sent when the content changes,
+    // the players still need a nominal one.
+    fn framerate(&self) -> (u32, u32) {
+        if self.framerate.num == 0 || self.framerate.denom == 0 {
+            (30, 1)
+        } else {
+            (self.framerate.num, self.framerate.denom)
+        }
+    }
+}
+
+fn property(key: FormatProperties, value: Value) -> Property {
+    Property {
+        key: key.as_raw(),
+        flags: PropertyFlags::empty(),
+        value,
+    }
+}
+
+// The `EnumFormat` param offered to the node: raw video in one of `FORMATS`,
+// any size and frame rate.
+fn enum_format() -> Vec<u8> {
+    let formats = FORMATS
+        .iter()
+        .map(|(format, _, _)| Id(format.as_raw()))
+        .collect();
+    let object = Value::Object(Object {
+        type_: SpaTypes::ObjectParamFormat.as_raw(),
+        id: ParamType::EnumFormat.as_raw(),
+        properties: vec![
+            property(
+                FormatProperties::MediaType,
+                Value::Id(Id(MediaType::Video.as_raw())),
+            ),
+            property(
+                FormatProperties::MediaSubtype,
+                Value::Id(Id(MediaSubtype::Raw.as_raw())),
+            ),
+            property(
+                FormatProperties::VideoFormat,
+                Value::Choice(ChoiceValue::Id(Choice(
+                    ChoiceFlags::empty(),
+                    ChoiceEnum::Enum {
+                        default: Id(FORMATS[0].0.as_raw()),
+                        alternatives: formats,
+                    },
+                ))),
+            ),
+            property(
+                FormatProperties::VideoSize,
+                Value::Choice(ChoiceValue::Rectangle(Choice(
+                    ChoiceFlags::empty(),
+                    ChoiceEnum::Range {
+                        default: Rectangle {
+                            width: 1920,
+                            height: 1080,
+                        },
+                        min: Rectangle {
+                            width: 1,
+                            height: 1,
+                        },
+                        max: Rectangle {
+                            width: 8192,
+                            height: 8192,
+                        },
+                    },
+                ))),
+            ),
+            property(
+                FormatProperties::VideoFramerate,
+                Value::Choice(ChoiceValue::Fraction(Choice(
+                    ChoiceFlags::empty(),
+                    ChoiceEnum::Range {
+                        default: Fraction { num: 30, denom: 1 },
+                        min: Fraction { num: 0, denom: 1 },
+                        max: Fraction { num: 144, denom: 1 },
+                    },
+                ))),
+            ),
+        ],
+    });
+    PodSerializer::serialize(std::io::Cursor::new(Vec::new()), &object)
+        .expect("Failed to serialize the EnumFormat param")
+        .0
+        .into_inner()
+}
+
+// The format the node settled on, from the `Format` param.
+fn parse_format(param: &Pod) -> Option<VideoFormat> {
+    let (media_type, media_subtype) = format_utils::parse_format(param).ok()?;
+    if media_type != MediaType::Video || media_subtype != MediaSubtype::Raw {
+        return None;
+    }
+    let mut info = VideoInfoRaw::new();
+    info.parse(param).ok()?;
+    let size = info.size();
+    let format = VideoFormat {
+        format: info.format(),
+        width: size.width,
+        height: size.height,
+        framerate: info.framerate(),
+    };
+    (format.width > 0 && format.height > 0).then_some(format)
+}
+
+enum Sink {
+    File(BufWriter<File>),
+    Gst(Child),
+}
+
+impl Sink {
+    fn open(args: &Args, format: &VideoFormat) -> std::io::Result<Self> {
+        if !args.gst {
+            return Ok(Self::File(BufWriter::new(File::create(&args.output)?)));
+        }
+        let (num, denom) = format.framerate();
+        // `-e` turns the end of the input into an end of stream, so the muxer
+        // gets to finish the file.
+        let child = Command::new("gst-launch-1.0")
+            .arg("-e")
+            .arg("fdsrc")
+            .arg("!")
+            .arg(format!(
+                "rawvideoparse width={} height={} format={} framerate={}/{}",
+                format.width,
+                format.height,
+                format.names().0.to_lowercase(),
+                num,
+                denom
+            ))
+            .args(["!", "videoconvert", "!", "x264enc", "!", "matroskamux", "!"])
+            .arg(format!("filesink location={}", args.output.display()))
+            .stdin(Stdio::piped())
+            .spawn()?;
+        Ok(Self::Gst(child))
+    }
+
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Self::File(file) => file,
+            Self::Gst(child) => child.stdin.as_mut().unwrap(),
+        }
+    }
+
+    fn finish(self) -> std::io::Result<()> {
+        match self {
+            Self::File(mut file) => file.flush(),
+            Self::Gst(mut child) => {
+                drop(child.stdin.take());
+                child.wait().map(|_| ())
+            }
+        }
+    }
+}
+
+struct Recorder {
+    args: Args,
+    format: Option<VideoFormat>,
+    sink: Option<Sink>,
+    frames: u64,
+    error: Option<std::io::Error>,
+}
+
+impl Recorder {
+    // Copies the visible rows of the frame, the lines of a buffer can be
+    // padded up to its stride.
+    fn write_frame(&mut self, data: &[u8], offset: usize, stride: usize) -> std::io::Result<()> {
+        let format = match self.format {
+            Some(format) => format,
+            None => return Ok(()),
+        };
+        if self.sink.is_none() {
+            self.sink = Some(Sink::open(&self.args, &format)?);
+        }
+        let writer = self.sink.as_mut().unwrap().writer();
+        let row = format.width as usize * 4;
+        for y in 0..format.height as usize {
+            let start = offset + y * stride;
+            match data.get(start..start + row) {
+                Some(line) => writer.write_all(line)?,
+                None => return Ok(()),
+            }
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    fn done(&self) -> bool {
+        self.error.is_some()
+            || INTERRUPTED.load(Ordering::SeqCst)
+            || self.args.frames.map_or(false, |max| self.frames >= max)
+    }
+}
+
+// Writes the first data block of a dequeued buffer, mapped in memory thanks to
+// `MAP_BUFFERS`. An empty chunk only updates the metadata, the cursor for
+// example.
+fn write_buffer(recorder: &mut Recorder, buffer: &mut pw::buffer::Buffer) -> std::io::Result<()> {
+    let data = match buffer.datas_mut().first_mut() {
+        Some(data) => data,
+        None => return Ok(()),
+    };
+    let chunk = data.chunk();
+    if chunk.size() == 0 {
+        return Ok(());
+    }
+    let (offset, stride) = (chunk.offset() as usize, chunk.stride().max(0) as usize);
+    match data.data() {
+        Some(bytes) => recorder.write_frame(bytes, offset, stride),
+        None => Ok(()),
+    }
+}
+
+// Runs the PipeWire loop until the recording is done or `stop` is received,
+// on its own thread as the loop isn't `Send`.
+fn record(
+    fd: RawFd,
+    node_id: u32,
+    args: Args,
+    stop: pw::channel::Receiver<()>,
+) -> Result<Recorder, pw::Error> {
+    let mainloop = pw::main_loop::MainLoop::new(None)?;
+    let context = pw::context::Context::new(&mainloop)?;
+    // The fd of the remote is owned by the core from now on.
+    let core = context.connect_fd(unsafe { OwnedFd::from_raw_fd(fd) }, None)?;
+
+    let loop_clone = mainloop.clone();
+    let _stop = stop.attach(mainloop.loop_(), move |_| loop_clone.quit());
+
+    let recorder = Rc::new(RefCell::new(Recorder {
+        args,
+        format: None,
+        sink: None,
+        frames: 0,
+        error: None,
+    }));
+    let stream = pw::stream::Stream::new(
+        &core,
+        "ashpd-screencast-recorder",
+        pw::properties::properties! {
+            *pw::keys::MEDIA_TYPE => "Video",
+            *pw::keys::MEDIA_CATEGORY => "Capture",
+            *pw::keys::MEDIA_ROLE => "Screen",
+        },
+    )?;
+    let state_loop = mainloop.clone();
+    let process_loop = mainloop.clone();
+    let param_recorder = recorder.clone();
+    let process_recorder = recorder.clone();
+    let listener = stream
+        .add_local_listener::<()>()
+        .state_changed(move |_, _, old, new| {
+            println!("Stream state: {:?} -> {:?}", old, new);
+            if let pw::stream::StreamState::Error(_) | pw::stream::StreamState::Unconnected = new {
+                state_loop.quit();
+            }
+        })
+        .param_changed(move |_, _, id, param| {
+            let param = match param {
+                Some(param) if id == ParamType::Format.as_raw() => param,
+                _ => return,
+            };
+            let mut recorder = param_recorder.borrow_mut();
+            recorder.format = parse_format(param);
+            if let Some(format) = recorder.format {
+                println!(
+                    "Negotiated {} {}x{} at {}/{} fps",
+                    format.names().0,
+                    format.width,
+                    format.height,
+                    format.framerate.num,
+                    format.framerate.denom
+                );
+            }
+        })
+        .process(move |stream, _| {
+            let mut recorder = process_recorder.borrow_mut();
+            // The buffer is queued back to the stream when dropped.
+            if let Some(mut buffer) = stream.dequeue_buffer() {
+                if let Err(err) = write_buffer(&mut recorder, &mut buffer) {
+                    recorder.error = Some(err);
+                }
+            }
+            if recorder.done() {
+                process_loop.quit();
+            }
+        })
+        .register()?;
+
+    let format = enum_format();
+    let mut params = [Pod::from_bytes(&format).expect("The EnumFormat param is a valid pod")];
+    stream.connect(
+        Direction::Input,
+        Some(node_id),
+        pw::stream::StreamFlags::AUTOCONNECT | pw::stream::StreamFlags::MAP_BUFFERS,
+        &mut params,
+    )?;
+
+    mainloop.run();
+    stream.disconnect()?;
+    drop(listener);
+    let recorder = Rc::try_unwrap(recorder)
+        .ok()
+        .expect("The stream callbacks are dropped with the listener");
+    Ok(recorder.into_inner())
+}
+
+async fn run(args: Args) -> ashpd::Result<()> {
+    let connection = zbus::Connection::session().await?;
+    let proxy = ScreenCastProxy::new(&connection).await?;
+
+    let session = proxy.create_session().await?;
+    let options = SelectSourcesOptions::default()
+        .with_types(SourceType::Monitor | SourceType::Window)
+        .with_cursor_mode(CursorMode::Embedded)
+        .with_persist_mode(PersistMode::DoNot);
+    proxy.select_sources(&session, options).await?;
+    let response = proxy.start(&session, &WindowIdentifier::default()).await?;
+    let stream = match response.streams().first() {
+        Some(stream) => stream.clone(),
+        None => {
+            session.close().await?;
+            return Ok(());
+        }
+    };
+    println!(
+        "Recording the {:?} stream {}",
+        stream.source_type(),
+        stream.pipe_wire_node_id()
+    );
+
+    let fd = proxy.open_pipe_wire_remote(&session).await?;
+    // Ctrl+C now stops the recording instead of the process, to finish the
+    // file.
+    unsafe {
+        libc::signal(
+            libc::SIGINT,
+            on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+    }
+    let mut closed = session.receive_closed().await?.fuse();
+    let (stop, stop_receiver) = pw::channel::channel();
+    let (done, mut done_receiver) = oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = done.send(record(fd, stream.pipe_wire_node_id(), args, stop_receiver));
+    });
+
+    let result = futures::select! {
+        result = done_receiver => result,
+        _ = closed.next() => {
+            println!("The session was closed");
+            // The loop may have stopped on its own meanwhile.
+            let _ = stop.send(());
+            done_receiver.await
+        }
+    };
+    // Closing a session that was closed by the portal fails, nothing to do
+    // about it.
+    let _ = session.close().await;
+
+    match result {
+        Ok(Ok(recorder)) => finish(recorder),
+        Ok(Err(err)) => eprintln!("PipeWire failed: {}", err),
+        Err(_) => eprintln!("The recording thread panicked"),
+    }
+    Ok(())
+}
+
+fn finish(recorder: Recorder) {
+    if let Some(err) = &recorder.error {
+        eprintln!("Failed to write the frames: {}", err);
+    }
+    if let Some(sink) = recorder.sink {
+        if let Err(err) = sink.finish() {
+            eprintln!(
+                "Failed to finish {}: {}",
+                recorder.args.output.display(),
+                err
+            );
+        }
+    }
+    println!(
+        "Wrote {} frames to {}",
+        recorder.frames,
+        recorder.args.output.display()
+    );
+    if let (false, Some(format)) = (recorder.args.gst, recorder.format) {
+        let (num, denom) = format.framerate();
+        println!(
+            "Play it with: ffplay -f rawvideo -pixel_format {} -video_size {}x{} -framerate {}/{} {}",
+            format.names().1,
+            format.width,
+            format.height,
+            num,
+            denom,
+            recorder.args.output.display()
+        );
+    }
+}
+
+fn main() {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    };
+    pw::init();
+    if let Err(err) = futures::executor::block_on(run(args)) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,7 @@
+#![no_main]
+use ashpd::fuzzing::FuzzDict;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|dict: FuzzDict| {
+    ashpd::fuzzing::deserialize_response_dict(&dict);
+});
@@ -0,0 +1,70 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use zbus::zvariant::{Signature, Type};
+
+/// A token used to activate a window, preventing the focus stealing
+/// prevention of the compositor from leaving the window in the background.
+///
+/// On Wayland it is an [`xdg-activation`](https://wayland.app/protocols/xdg-activation-v1)
+/// token, on X11 a startup notification ID. It is usually obtained from the
+/// toolkit when handling a user interaction and passed to the portal, for
+/// example with [`OpenURIProxy::open_uri`](crate::desktop::open_uri::OpenURIProxy::open_uri),
+/// or received from it along with the invoked notification actions, see
+/// [`Action::activation_token`](crate::desktop::notification::Action::activation_token).
+///
+/// Requesting a new token with xdg-activation needs the Wayland connection
+/// and the input serial of the toolkit, which isn't done here.
+/// [`ActivationToken::from_env`] only covers the token the application was
+/// launched with.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ActivationToken(String);
+
+impl ActivationToken {
+    /// Wrap an activation token or a startup notification ID.
+    pub fn new(token: impl Into<String>) -> Self {
+        Self(token.into())
+    }
+
+    /// The token the application was activated with, read from the
+    /// `XDG_ACTIVATION_TOKEN` or the `DESKTOP_STARTUP_ID` environment
+    /// variables.
+    ///
+    /// The application is expected to use it to activate its first window.
+    pub fn from_env() -> Option<Self> {
+        ["XDG_ACTIVATION_TOKEN", "DESKTOP_STARTUP_ID"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|token| !token.is_empty())
+            .map(Self)
+    }
+
+    /// The token as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Type for ActivationToken {
+    fn signature() -> Signature<'static> {
+        String::signature()
+    }
+}
+
+impl fmt::Display for ActivationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for ActivationToken {
+    fn from(token: String) -> Self {
+        Self(token)
+    }
+}
+
+impl From<&str> for ActivationToken {
+    fn from(token: &str) -> Self {
+        Self(token.to_owned())
+    }
+}
@@ -0,0 +1,329 @@
+//! ```rust,no_run
+//! use ashpd::{
+//!     backend::app_chooser::{self, AppChooserImpl, ChooseApplicationOptions, ChosenApplication},
+//!     desktop::ResponseError,
+//!     zvariant::ObjectPath,
+//!     PortalError,
+//! };
+//!
+//! struct Picker;
+//!
+//! #[ashpd::async_trait]
+//! impl AppChooserImpl for Picker {
+//!     async fn choose_application(
+//!         &self,
+//!         _handle: &ObjectPath<'_>,
+//!         _app_id: &str,
+//!         _parent_window: &str,
+//!         choices: Vec<String>,
+//!         options: ChooseApplicationOptions,
+//!     ) -> Result<ChosenApplication, ResponseError> {
+//!         let choice = options
+//!             .last_choice()
+//!             .map(ToOwned::to_owned)
+//!             .or_else(|| choices.into_iter().next())
+//!             .ok_or(ResponseError::Cancelled)?;
+//!         Ok(ChosenApplication::new(choice))
+//!     }
+//!
+//!     async fn update_choices(
+//!         &self,
+//!         _handle: &ObjectPath<'_>,
+//!         _choices: Vec<String>,
+//!     ) -> Result<(), PortalError> {
+//!         Ok(())
+//!     }
+//! }
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::ConnectionBuilder::session()?
+//!         .name("org.freedesktop.impl.portal.desktop.mydesktop")?
+//!         .build()
+//!         .await?;
+//!     app_chooser::serve(&connection, Picker).await?;
+//!     Ok(())
+//! }
+//! ```
+
+use async_trait::async_trait;
+use zbus::{
+    dbus_interface,
+    zvariant::{DeserializeDict, ObjectPath, OwnedObjectPath, SerializeDict, Type},
+};
+
+use super::{request::handle_request, PATH};
+use crate::{
+    desktop::{request::Response, ResponseError},
+    ActivationToken, Error, PortalError,
+};
+
+#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, Default, PartialEq, Eq)]
+/// Specified options of a [`AppChooserImpl::choose_application`] request.
+#[zvariant(signature = "dict")]
+pub struct ChooseApplicationOptions {
+    /// The application chosen the last time.
+    last_choice: Option<String>,
+    /// Whether the dialog should be modal.
+    modal: Option<bool>,
+    /// The content type to choose an application for.
+    content_type: Option<String>,
+    /// The uri to choose an application for.
+    uri: Option<String>,
+    /// The filename to choose an application for.
+    filename: Option<String>,
+    /// Token to activate the chooser dialog with.
+    activation_token: Option<ActivationToken>,
+}
+
+impl ChooseApplicationOptions {
+    /// The identifier of the application chosen the last time, it should be
+    /// selected initially.
+    pub fn last_choice(&self) -> Option<&str> {
+        self.last_choice.as_deref()
+    }
+
+    /// Whether the dialog should be modal.
+    pub fn modal(&self) -> Option<bool> {
+        self.modal
+    }
+
+    /// The content type to choose an application for.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// The uri to choose an application for.
+    pub fn uri(&self) -> Option<&str> {
+        self.uri.as_deref()
+    }
+
+    /// The filename to choose an application for, without its path.
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// The token to activate the chooser dialog with.
+    pub fn activation_token(&self) -> Option<&ActivationToken> {
+        self.activation_token.as_ref()
+    }
+}
+
+#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, PartialEq, Eq)]
+/// The result of a [`AppChooserImpl::choose_application`] request.
+#[zvariant(signature = "dict")]
+pub struct ChosenApplication {
+    /// The identifier of the chosen application.
+    choice: String,
+    /// Token to activate the chosen application with.
+    activation_token: Option<ActivationToken>,
+}
+
+impl ChosenApplication {
+    /// The user chose the application `choice`, one of the identifiers of
+    /// the offered choices.
+    pub fn new(choice: impl Into<String>) -> Self {
+        Self {
+            choice: choice.into(),
+            activation_token: None,
+        }
+    }
+
+    /// Sets the token to activate the chosen application with.
+    #[must_use]
+    pub fn activation_token(mut self, activation_token: ActivationToken) -> Self {
+        self.activation_token = Some(activation_token);
+        self
+    }
+}
+
+/// The implementation of the `org.freedesktop.impl.portal.AppChooser`
+/// interface.
+///
+/// The request `handle` identifies an ongoing interaction, its choices can be
+/// updated with [`AppChooserImpl::update_choices`] while the user is choosing.
+#[async_trait]
+pub trait AppChooserImpl: Send + Sync + 'static {
+    /// Let the user choose an application to open a file or an uri with, on
+    /// behalf of `app_id`.
+    ///
+    /// # Arguments
+    ///
+    /// * `handle` - The object path of the request.
+    /// * `app_id` - The application requesting the choice.
+    /// * `parent_window` - The identifier of the application window, see
+    ///   [`WindowIdentifier`](crate::WindowIdentifier).
+    /// * `choices` - The identifiers of the applications to choose from.
+    /// * `options` - The options of the request.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`ChooseApplication`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-impl-portal-AppChooser.ChooseApplication).
+    #[doc(alias = "ChooseApplication")]
+    async fn choose_application(
+        &self,
+        handle: &ObjectPath<'_>,
+        app_id: &str,
+        parent_window: &str,
+        choices: Vec<String>,
+        options: ChooseApplicationOptions,
+    ) -> Result<ChosenApplication, ResponseError>;
+
+    /// Replace the choices of the ongoing request `handle`, the applications
+    /// installed in the meantime for example.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`UpdateChoices`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-impl-portal-AppChooser.UpdateChoices).
+    #[doc(alias = "UpdateChoices")]
+    async fn update_choices(
+        &self,
+        handle: &ObjectPath<'_>,
+        choices: Vec<String>,
+    ) -> Result<(), PortalError>;
+}
+
+struct AppChooserInterface(Box<dyn AppChooserImpl>);
+
+#[dbus_interface(name = "org.freedesktop.impl.portal.AppChooser")]
+impl AppChooserInterface {
+    async fn choose_application(
+        &self,
+        #[zbus(connection)] connection: &zbus::Connection,
+        handle: OwnedObjectPath,
+        app_id: &str,
+        parent_window: &str,
+        choices: Vec<String>,
+        options: ChooseApplicationOptions,
+    ) -> Response<ChosenApplication> {
+        let request = self
+            .0
+            .choose_application(&handle, app_id, parent_window, choices, options);
+        handle_request(connection, handle.clone(), request).await
+    }
+
+    async fn update_choices(
+        &self,
+        handle: OwnedObjectPath,
+        choices: Vec<String>,
+    ) -> Result<(), PortalError> {
+        self.0.update_choices(&handle, choices).await
+    }
+
+    #[dbus_interface(property, name = "version")]
+    fn version(&self) -> u32 {
+        2
+    }
+}
+
+/// Serve `imp` as the `org.freedesktop.impl.portal.AppChooser` interface on
+/// `connection`.
+pub async fn serve(connection: &zbus::Connection, imp: impl AppChooserImpl) -> Result<(), Error> {
+    connection
+        .object_server()
+        .at(PATH, AppChooserInterface(Box::new(imp)))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use async_trait::async_trait;
+    use futures::{
+        channel::oneshot::{channel, Receiver, Sender},
+        executor::block_on,
+    };
+    use zbus::zvariant::{ObjectPath, Value};
+
+    use super::{serve, AppChooserImpl, ChooseApplicationOptions, ChosenApplication, PATH};
+    use crate::{
+        backend::{frontend_proxy, peer_connections},
+        desktop::{request::Response, ResponseError},
+        PortalError,
+    };
+
+    const INTERFACE: &str = "org.freedesktop.impl.portal.AppChooser";
+    const HANDLE: &str = "/org/freedesktop/portal/desktop/request/1_42/t";
+
+    fn handle() -> ObjectPath<'static> {
+        ObjectPath::try_from(HANDLE).unwrap()
+    }
+
+    // Chooses the last application once the choices are updated.
+    struct Chooser {
+        update: Mutex<Option<Sender<Vec<String>>>>,
+        updated: Mutex<Option<Receiver<Vec<String>>>>,
+    }
+
+    #[async_trait]
+    impl AppChooserImpl for Chooser {
+        async fn choose_application(
+            &self,
+            handle: &ObjectPath<'_>,
+            _app_id: &str,
+            _parent_window: &str,
+            choices: Vec<String>,
+            options: ChooseApplicationOptions,
+        ) -> Result<ChosenApplication, ResponseError> {
+            assert_eq!(handle.as_str(), HANDLE);
+            assert_eq!(choices, ["org.gnome.Totem"]);
+            assert_eq!(options.content_type(), Some("video/webm"));
+            let updated = self.updated.lock().unwrap().take().unwrap();
+            let choices = updated.await.map_err(|_| ResponseError::Other)?;
+            let choice = choices.last().ok_or(ResponseError::Cancelled)?;
+            Ok(ChosenApplication::new(choice))
+        }
+
+        async fn update_choices(
+            &self,
+            handle: &ObjectPath<'_>,
+            choices: Vec<String>,
+        ) -> Result<(), PortalError> {
+            assert_eq!(handle.as_str(), HANDLE);
+            let update = self.update.lock().unwrap().take();
+            update
+                .ok_or_else(|| PortalError::NotFound("No ongoing request".to_owned()))?
+                .send(choices)
+                .map_err(|_| PortalError::Failed)
+        }
+    }
+
+    #[test]
+    fn choose_application() {
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            let (update, updated) = channel();
+            let chooser = Chooser {
+                update: Mutex::new(Some(update)),
+                updated: Mutex::new(Some(updated)),
+            };
+            serve(&backend, chooser).await.unwrap();
+            let proxy = frontend_proxy(&frontend, PATH, INTERFACE).await;
+
+            let options = HashMap::from([("content_type", Value::from("video/webm"))]);
+            let request = (
+                handle(),
+                "org.example.App",
+                "",
+                vec!["org.gnome.Totem"],
+                options,
+            );
+            let choose =
+                proxy.call::<_, _, Response<ChosenApplication>>("ChooseApplication", &request);
+            let choices = vec!["org.gnome.Totem", "org.videolan.VLC"];
+            let update_request = (handle(), &choices);
+            let update = proxy.call::<_, _, ()>("UpdateChoices", &update_request);
+            let (response, updated) = futures::join!(choose, update);
+            updated.unwrap();
+            let expected = ChosenApplication::new("org.videolan.VLC");
+            assert!(matches!(response.unwrap(), Response::Ok(chosen) if chosen == expected));
+
+            // The request is over.
+            assert!(proxy
+                .call::<_, _, ()>("UpdateChoices", &(handle(), &choices))
+                .await
+                .is_err());
+        });
+    }
+}
@@ -0,0 +1,544 @@
+//! ```rust,no_run
+//! use ashpd::{
+//!     backend::file_chooser::{
+//!         self, FileChooserImpl, OpenFileOptions, SaveFileOptions, SelectedFiles,
+//!     },
+//!     desktop::ResponseError,
+//!     url::Url,
+//! };
+//!
+//! struct Prompt;
+//!
+//! #[ashpd::async_trait]
+//! impl FileChooserImpl for Prompt {
+//!     async fn open_file(
+//!         &self,
+//!         _app_id: &str,
+//!         _parent_window: &str,
+//!         title: &str,
+//!         options: OpenFileOptions,
+//!     ) -> Result<SelectedFiles, ResponseError> {
+//!         println!("{}", title);
+//!         for filter in options.filters() {
+//!             println!("{}: {:?}", filter.label(), filter.globs());
+//!         }
+//!         let uri = Url::parse("file:///tmp/notes.txt").unwrap();
+//!         Ok(SelectedFiles::new(vec![uri]))
+//!     }
+//!
+//!     async fn save_file(
+//!         &self,
+//!         _app_id: &str,
+//!         _parent_window: &str,
+//!         _title: &str,
+//!         options: SaveFileOptions,
+//!     ) -> Result<SelectedFiles, ResponseError> {
+//!         let name = options.current_name().ok_or(ResponseError::Cancelled)?;
+//!         let uri = Url::from_file_path(format!("/tmp/{}", name)).unwrap();
+//!         Ok(SelectedFiles::new(vec![uri]))
+//!     }
+//! }
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::ConnectionBuilder::session()?
+//!         .name("org.freedesktop.impl.portal.desktop.mydesktop")?
+//!         .build()
+//!         .await?;
+//!     file_chooser::serve(&connection, Prompt).await?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use url::Url;
+use zbus::{
+    dbus_interface,
+    zvariant::{DeserializeDict, OwnedObjectPath, SerializeDict, Type},
+};
+
+use super::{request::handle_request, PATH};
+use crate::{
+    desktop::{
+        file_chooser::{Choice, FileFilter},
+        request::Response,
+        ResponseError,
+    },
+    helpers::path_from_null_terminated,
+    uri::Uris,
+    Error,
+};
+
+#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, Default, PartialEq, Eq)]
+/// Specified options of a [`FileChooserImpl::open_file`] request.
+#[zvariant(signature = "dict")]
+pub struct OpenFileOptions {
+    /// Label for the accept button.
+    accept_label: Option<String>,
+    /// Whether the dialog should be modal.
+    modal: Option<bool>,
+    /// Whether multiple files can be selected.
+    multiple: Option<bool>,
+    /// Whether to select folders instead of files.
+    directory: Option<bool>,
+    /// The filters the user can choose from.
+    filters: Option<Vec<FileFilter>>,
+    /// The initially selected filter.
+    current_filter: Option<FileFilter>,
+    /// The extra choices to present.
+    choices: Option<Vec<Choice>>,
+}
+
+impl OpenFileOptions {
+    /// The label of the accept button.
+    pub fn accept_label(&self) -> Option<&str> {
+        self.accept_label.as_deref()
+    }
+
+    /// Whether the dialog should be modal.
+    pub fn modal(&self) -> Option<bool> {
+        self.modal
+    }
+
+    /// Whether multiple files can be selected.
+    pub fn multiple(&self) -> Option<bool> {
+        self.multiple
+    }
+
+    /// Whether to select folders instead of files.
+    pub fn directory(&self) -> Option<bool> {
+        self.directory
+    }
+
+    /// The filters the user can choose from.
+    pub fn filters(&self) -> &[FileFilter] {
+        self.filters.as_deref().unwrap_or_default()
+    }
+
+    /// The initially selected filter.
+    pub fn current_filter(&self) -> Option<&FileFilter> {
+        self.current_filter.as_ref()
+    }
+
+    /// The extra choices to present along the files.
+    pub fn choices(&self) -> &[Choice] {
+        self.choices.as_deref().unwrap_or_default()
+    }
+}
+
+#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, Default, PartialEq, Eq)]
+/// Specified options of a [`FileChooserImpl::save_file`] request.
+#[zvariant(signature = "dict")]
+pub struct SaveFileOptions {
+    /// Label for the accept button.
+    accept_label: Option<String>,
+    /// Whether the dialog should be modal.
+    modal: Option<bool>,
+    /// Suggested filename.
+    current_name: Option<String>,
+    /// Suggested folder to save the file in.
+    current_folder: Option<Vec<u8>>,
+    /// The current file, when saving an existing one.
+    current_file: Option<Vec<u8>>,
+    /// The filters the user can choose from.
+    filters: Option<Vec<FileFilter>>,
+    /// The initially selected filter.
+    current_filter: Option<FileFilter>,
+    /// The extra choices to present.
+    choices: Option<Vec<Choice>>,
+}
+
+impl SaveFileOptions {
+    /// The label of the accept button.
+    pub fn accept_label(&self) -> Option<&str> {
+        self.accept_label.as_deref()
+    }
+
+    /// Whether the dialog should be modal.
+    pub fn modal(&self) -> Option<bool> {
+        self.modal
+    }
+
+    /// The suggested filename.
+    pub fn current_name(&self) -> Option<&str> {
+        self.current_name.as_deref()
+    }
+
+    /// The suggested folder to save the file in.
+    pub fn current_folder(&self) -> Option<PathBuf> {
+        self.current_folder
+            .as_deref()
+            .map(path_from_null_terminated)
+    }
+
+    /// The current file, when saving an existing one.
+    pub fn current_file(&self) -> Option<PathBuf> {
+        self.current_file.as_deref().map(path_from_null_terminated)
+    }
+
+    /// The filters the user can choose from.
+    pub fn filters(&self) -> &[FileFilter] {
+        self.filters.as_deref().unwrap_or_default()
+    }
+
+    /// The initially selected filter.
+    pub fn current_filter(&self) -> Option<&FileFilter> {
+        self.current_filter.as_ref()
+    }
+
+    /// The extra choices to present along the files.
+    pub fn choices(&self) -> &[Choice] {
+        self.choices.as_deref().unwrap_or_default()
+    }
+}
+
+#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, Default, PartialEq, Eq)]
+/// Specified options of a [`FileChooserImpl::save_files`] request.
+#[zvariant(signature = "dict")]
+pub struct SaveFilesOptions {
+    /// Label for the accept button.
+    accept_label: Option<String>,
+    /// Whether the dialog should be modal.
+    modal: Option<bool>,
+    /// The extra choices to present.
+    choices: Option<Vec<Choice>>,
+    /// Suggested folder to save the files in.
+    current_folder: Option<Vec<u8>>,
+    /// The names of the files to save.
+    files: Option<Vec<Vec<u8>>>,
+}
+
+impl SaveFilesOptions {
+    /// The label of the accept button.
+    pub fn accept_label(&self) -> Option<&str> {
+        self.accept_label.as_deref()
+    }
+
+    /// Whether the dialog should be modal.
+    pub fn modal(&self) -> Option<bool> {
+        self.modal
+    }
+
+    /// The extra choices to present along the folder.
+    pub fn choices(&self) -> &[Choice] {
+        self.choices.as_deref().unwrap_or_default()
+    }
+
+    /// The suggested folder to save the files in.
+    pub fn current_folder(&self) -> Option<PathBuf> {
+        self.current_folder
+            .as_deref()
+            .map(path_from_null_terminated)
+    }
+
+    /// The names of the files to save.
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.files
+            .iter()
+            .flatten()
+            .map(|file| path_from_null_terminated(file))
+            .collect()
+    }
+}
+
+#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, PartialEq, Eq)]
+/// The result of a [`FileChooserImpl`] request.
+#[zvariant(signature = "dict")]
+pub struct SelectedFiles {
+    /// The selected files.
+    uris: Uris,
+    /// The selected value of each choice.
+    choices: Option<Vec<(String, String)>>,
+    /// The selected filter.
+    current_filter: Option<FileFilter>,
+    /// Whether the files were selected read-write.
+    writable: Option<bool>,
+}
+
+impl SelectedFiles {
+    /// The user selected the files `uris`.
+    pub fn new(uris: impl IntoIterator<Item = Url>) -> Self {
+        Self {
+            uris: Uris(uris.into_iter().collect()),
+            choices: None,
+            current_filter: None,
+            writable: None,
+        }
+    }
+
+    /// Adds the selected value of the choice `id`, `true` or `false` for a
+    /// checkbox.
+    #[must_use]
+    pub fn choice(mut self, id: impl Into<String>, value: impl Into<String>) -> Self {
+        self.choices
+            .get_or_insert_with(Vec::new)
+            .push((id.into(), value.into()));
+        self
+    }
+
+    options_setters! {
+        /// Sets whether the files were selected read-write.
+        with_writable(writable: bool);
+    }
+
+    /// Sets the filter the user selected.
+    #[must_use]
+    pub fn with_current_filter(mut self, current_filter: FileFilter) -> Self {
+        self.current_filter = Some(current_filter);
+        self
+    }
+
+    /// The value set with [`with_current_filter`](Self::with_current_filter),
+    /// if any.
+    pub fn current_filter(&self) -> Option<&FileFilter> {
+        self.current_filter.as_ref()
+    }
+}
+
+/// The implementation of the `org.freedesktop.impl.portal.FileChooser`
+/// interface.
+#[async_trait]
+pub trait FileChooserImpl: Send + Sync + 'static {
+    /// Let the user select files to open on behalf of `app_id`.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The application requesting the files.
+    /// * `parent_window` - The identifier of the application window, see
+    ///   [`WindowIdentifier`](crate::WindowIdentifier).
+    /// * `title` - The title of the dialog.
+    /// * `options` - The options of the request.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`OpenFile`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-impl-portal-FileChooser.OpenFile).
+    #[doc(alias = "OpenFile")]
+    async fn open_file(
+        &self,
+        app_id: &str,
+        parent_window: &str,
+        title: &str,
+        options: OpenFileOptions,
+    ) -> Result<SelectedFiles, ResponseError>;
+
+    /// Let the user select where to save a file on behalf of `app_id`.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`SaveFile`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-impl-portal-FileChooser.SaveFile).
+    #[doc(alias = "SaveFile")]
+    async fn save_file(
+        &self,
+        app_id: &str,
+        parent_window: &str,
+        title: &str,
+        options: SaveFileOptions,
+    ) -> Result<SelectedFiles, ResponseError>;
+
+    /// Let the user select a folder to save files in on behalf of `app_id`,
+    /// the result holds an uri per file of
+    /// [`SaveFilesOptions::files`].
+    ///
+    /// Not supported unless implemented.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`SaveFiles`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-impl-portal-FileChooser.SaveFiles).
+    #[doc(alias = "SaveFiles")]
+    async fn save_files(
+        &self,
+        app_id: &str,
+        parent_window: &str,
+        title: &str,
+        options: SaveFilesOptions,
+    ) -> Result<SelectedFiles, ResponseError> {
+        let _ = (app_id, parent_window, title, options);
+        Err(ResponseError::Other)
+    }
+}
+
+struct FileChooserInterface(Box<dyn FileChooserImpl>);
+
+#[dbus_interface(name = "org.freedesktop.impl.portal.FileChooser")]
+impl FileChooserInterface {
+    async fn open_file(
+        &self,
+        #[zbus(connection)] connection: &zbus::Connection,
+        handle: OwnedObjectPath,
+        app_id: &str,
+        parent_window: &str,
+        title: &str,
+        options: OpenFileOptions,
+    ) -> Response<SelectedFiles> {
+        let request = self.0.open_file(app_id, parent_window, title, options);
+        handle_request(connection, handle, request).await
+    }
+
+    async fn save_file(
+        &self,
+        #[zbus(connection)] connection: &zbus::Connection,
+        handle: OwnedObjectPath,
+        app_id: &str,
+        parent_window: &str,
+        title: &str,
+        options: SaveFileOptions,
+    ) -> Response<SelectedFiles> {
+        let request = self.0.save_file(app_id, parent_window, title, options);
+        handle_request(connection, handle, request).await
+    }
+
+    async fn save_files(
+        &self,
+        #[zbus(connection)] connection: &zbus::Connection,
+        handle: OwnedObjectPath,
+        app_id: &str,
+        parent_window: &str,
+        title: &str,
+        options: SaveFilesOptions,
+    ) -> Response<SelectedFiles> {
+        let request = self.0.save_files(app_id, parent_window, title, options);
+        handle_request(connection, handle, request).await
+    }
+
+    #[dbus_interface(property, name = "version")]
+    fn version(&self) -> u32 {
+        3
+    }
+}
+
+/// Serve `imp` as the `org.freedesktop.impl.portal.FileChooser` interface on
+/// `connection`.
+pub async fn serve(connection: &zbus::Connection, imp: impl FileChooserImpl) -> Result<(), Error> {
+    connection
+        .object_server()
+        .at(PATH, FileChooserInterface(Box::new(imp)))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashMap, path::Path};
+
+    use async_trait::async_trait;
+    use futures::executor::block_on;
+    use url::Url;
+    use zbus::zvariant::{ObjectPath, Value};
+
+    use super::{serve, FileChooserImpl, OpenFileOptions, SaveFileOptions, SelectedFiles, PATH};
+    use crate::{
+        backend::{frontend_proxy, peer_connections},
+        desktop::{request::Response, ResponseError},
+    };
+
+    const INTERFACE: &str = "org.freedesktop.impl.portal.FileChooser";
+    const HANDLE: &str = "/org/freedesktop/portal/desktop/request/1_42/t";
+
+    fn handle() -> ObjectPath<'static> {
+        ObjectPath::try_from(HANDLE).unwrap()
+    }
+
+    fn uri(path: &str) -> Url {
+        Url::from_file_path(path).unwrap()
+    }
+
+    // Doesn't implement `save_files`.
+    struct Chooser;
+
+    #[async_trait]
+    impl FileChooserImpl for Chooser {
+        async fn open_file(
+            &self,
+            _app_id: &str,
+            _parent_window: &str,
+            title: &str,
+            options: OpenFileOptions,
+        ) -> Result<SelectedFiles, ResponseError> {
+            assert_eq!(title, "Open");
+            if options.multiple() == Some(true) {
+                Ok(SelectedFiles::new([uri("/tmp/a"), uri("/tmp/b")]))
+            } else {
+                Ok(SelectedFiles::new([uri("/tmp/a")]))
+            }
+        }
+
+        async fn save_file(
+            &self,
+            _app_id: &str,
+            _parent_window: &str,
+            _title: &str,
+            options: SaveFileOptions,
+        ) -> Result<SelectedFiles, ResponseError> {
+            let folder = options.current_folder().ok_or(ResponseError::Cancelled)?;
+            assert_eq!(folder, Path::new("/tmp"));
+            let name = options.current_name().unwrap_or("untitled");
+            let file = folder.join(name);
+            Ok(SelectedFiles::new([uri(file.to_str().unwrap())]).with_writable(true))
+        }
+    }
+
+    #[test]
+    fn open_file() {
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            serve(&backend, Chooser).await.unwrap();
+            let proxy = frontend_proxy(&frontend, PATH, INTERFACE).await;
+
+            let options = HashMap::from([("multiple", Value::from(true))]);
+            let response: Response<SelectedFiles> = proxy
+                .call(
+                    "OpenFile",
+                    &(handle(), "org.example.App", "", "Open", options),
+                )
+                .await
+                .unwrap();
+            let expected = SelectedFiles::new([uri("/tmp/a"), uri("/tmp/b")]);
+            assert!(matches!(response, Response::Ok(files) if files == expected));
+        });
+    }
+
+    #[test]
+    fn save_file() {
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            serve(&backend, Chooser).await.unwrap();
+            let proxy = frontend_proxy(&frontend, PATH, INTERFACE).await;
+
+            let options = HashMap::from([
+                ("current_name", Value::from("notes.txt")),
+                ("current_folder", Value::from(b"/tmp\0".to_vec())),
+            ]);
+            let response: Response<SelectedFiles> = proxy
+                .call(
+                    "SaveFile",
+                    &(handle(), "org.example.App", "", "Save", options),
+                )
+                .await
+                .unwrap();
+            let expected = SelectedFiles::new([uri("/tmp/notes.txt")]).with_writable(true);
+            assert!(matches!(response, Response::Ok(files) if files == expected));
+
+            let options = HashMap::<&str, Value<'_>>::new();
+            let response: Response<SelectedFiles> = proxy
+                .call(
+                    "SaveFile",
+                    &(handle(), "org.example.App", "", "Save", options),
+                )
+                .await
+                .unwrap();
+            assert!(matches!(response, Response::Err(ResponseError::Cancelled)));
+
+            // Not implemented, the request fails.
+            let options = HashMap::<&str, Value<'_>>::new();
+            let response: Response<SelectedFiles> = proxy
+                .call(
+                    "SaveFiles",
+                    &(handle(), "org.example.App", "", "Save", options),
+                )
+                .await
+                .unwrap();
+            assert!(matches!(response, Response::Err(ResponseError::Other)));
+        });
+    }
+}
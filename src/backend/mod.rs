@@ -0,0 +1,68 @@
+//! Implement the portals backends, the `org.freedesktop.impl.portal.*`
+//! interfaces `xdg-desktop-portal` forwards the requests of the sandboxed
+//! applications to.
+//!
+//! Each backend is served on the object path `/org/freedesktop/portal/desktop`
+//! of a connection owning the bus name advertised in the `.portal` file of the
+//! desktop, `org.freedesktop.impl.portal.desktop.mydesktop` for example.
+
+pub(crate) const PATH: &str = "/org/freedesktop/portal/desktop";
+
+mod request;
+
+/// Let the user choose the application to open a file or an uri with.
+pub mod app_chooser;
+/// Let the user select files to open or where to save them.
+pub mod file_chooser;
+/// Show the notifications sent by the applications.
+pub mod notification;
+/// Take screenshots and pick colors.
+pub mod screenshot;
+/// Track the sessions of the portals, closing them when the frontend leaves
+/// the bus.
+pub mod session;
+/// Provide the host settings, the color scheme for example.
+pub mod settings;
+/// Set the wallpaper of the desktop.
+pub mod wallpaper;
+
+// A connection serving the backend and one to call it as the frontend would,
+// connected to each other without a bus. The messages carry no sender then.
+#[cfg(test)]
+pub(crate) async fn peer_connections() -> (zbus::Connection, zbus::Connection) {
+    let (backend, frontend) = std::os::unix::net::UnixStream::pair().unwrap();
+    let guid = zbus::Guid::generate();
+    let (backend, frontend) = futures::try_join!(
+        zbus::ConnectionBuilder::unix_stream(backend)
+            .server(&guid)
+            .p2p()
+            .build(),
+        zbus::ConnectionBuilder::unix_stream(frontend).p2p().build(),
+    )
+    .unwrap();
+    // The calls are only dispatched once the object servers are started.
+    backend.object_server();
+    frontend.object_server();
+    (backend, frontend)
+}
+
+// A proxy calling `interface` of the backend from the `frontend` connection
+// of `peer_connections`.
+#[cfg(test)]
+pub(crate) async fn frontend_proxy<'a>(
+    frontend: &zbus::Connection,
+    path: &'a str,
+    interface: &'a str,
+) -> zbus::Proxy<'a> {
+    zbus::ProxyBuilder::new_bare(frontend)
+        .destination("org.freedesktop.impl.portal.desktop.test")
+        .unwrap()
+        .path(path)
+        .unwrap()
+        .interface(interface)
+        .unwrap()
+        .cache_properties(zbus::CacheProperties::No)
+        .build()
+        .await
+        .unwrap()
+}
@@ -0,0 +1,306 @@
+//! ```rust,no_run
+//! use ashpd::{
+//!     backend::notification::{self, NotificationImpl},
+//!     desktop::notification::Notification,
+//!     PortalError,
+//! };
+//!
+//! struct StatusBar;
+//!
+//! #[ashpd::async_trait]
+//! impl NotificationImpl for StatusBar {
+//!     async fn add_notification(
+//!         &self,
+//!         app_id: &str,
+//!         id: &str,
+//!         notification: Notification,
+//!     ) -> Result<(), PortalError> {
+//!         println!("{}/{}: {}", app_id, id, notification.title);
+//!         Ok(())
+//!     }
+//!
+//!     async fn remove_notification(&self, app_id: &str, id: &str) -> Result<(), PortalError> {
+//!         println!("{}/{} withdrawn", app_id, id);
+//!         Ok(())
+//!     }
+//! }
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::ConnectionBuilder::session()?
+//!         .name("org.freedesktop.impl.portal.desktop.mydesktop")?
+//!         .build()
+//!         .await?;
+//!     let emitter = notification::serve(&connection, StatusBar).await?;
+//!
+//!     // The user clicked on the default action of a notification.
+//!     emitter
+//!         .action_invoked("org.example.App", "new-mail", "app.open", &[])
+//!         .await?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use zbus::{dbus_interface, zvariant::Value};
+
+use super::PATH;
+use crate::{desktop::notification::Notification, ActivationToken, Error, PortalError};
+
+const INTERFACE: &str = "org.freedesktop.impl.portal.Notification";
+
+/// The implementation of the `org.freedesktop.impl.portal.Notification`
+/// interface.
+///
+/// The notifications are identified by the application sending them and an
+/// identifier chosen by that application, adding a notification with an
+/// identifier already in use replaces the previous one.
+#[async_trait]
+pub trait NotificationImpl: Send + Sync + 'static {
+    /// Show `notification` on behalf of `app_id`.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`AddNotification`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-impl-portal-Notification.AddNotification).
+    #[doc(alias = "AddNotification")]
+    async fn add_notification(
+        &self,
+        app_id: &str,
+        id: &str,
+        notification: Notification,
+    ) -> Result<(), PortalError>;
+
+    /// Withdraw a notification of `app_id`.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`RemoveNotification`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-impl-portal-Notification.RemoveNotification).
+    #[doc(alias = "RemoveNotification")]
+    async fn remove_notification(&self, app_id: &str, id: &str) -> Result<(), PortalError>;
+}
+
+struct NotificationInterface(Box<dyn NotificationImpl>);
+
+#[dbus_interface(name = "org.freedesktop.impl.portal.Notification")]
+impl NotificationInterface {
+    async fn add_notification(
+        &self,
+        app_id: &str,
+        id: &str,
+        notification: Notification,
+    ) -> Result<(), PortalError> {
+        self.0.add_notification(app_id, id, notification).await
+    }
+
+    async fn remove_notification(&self, app_id: &str, id: &str) -> Result<(), PortalError> {
+        self.0.remove_notification(app_id, id).await
+    }
+}
+
+/// Emits the signals of the `org.freedesktop.impl.portal.Notification`
+/// interface served with [`serve`].
+#[derive(Clone, Debug)]
+pub struct NotificationSignalEmitter {
+    connection: zbus::Connection,
+}
+
+impl NotificationSignalEmitter {
+    /// Notify `app_id` that the user activated an action of one of its
+    /// notifications, either the default action or the one of a button.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The application the notification belongs to.
+    /// * `id` - The identifier of the notification.
+    /// * `action` - The name of the activated action.
+    /// * `parameter` - The target of the action, empty if it has none.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`ActionInvoked`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-impl-portal-Notification.ActionInvoked).
+    #[doc(alias = "ActionInvoked")]
+    pub async fn action_invoked(
+        &self,
+        app_id: &str,
+        id: &str,
+        action: &str,
+        parameter: &[Value<'_>],
+    ) -> Result<(), Error> {
+        self.connection
+            .emit_signal(
+                None::<()>,
+                PATH,
+                INTERFACE,
+                "ActionInvoked",
+                &(app_id, id, action, parameter),
+            )
+            .await
+            .map_err(From::from)
+    }
+
+    /// Same as [`NotificationSignalEmitter::action_invoked`], along with the
+    /// token the application activates its window with, since version 2 of
+    /// the interface.
+    pub async fn action_invoked_with_activation_token(
+        &self,
+        app_id: &str,
+        id: &str,
+        action: &str,
+        parameter: &[Value<'_>],
+        activation_token: &ActivationToken,
+    ) -> Result<(), Error> {
+        let mut platform_data = HashMap::new();
+        platform_data.insert("activation-token", Value::from(activation_token.as_str()));
+        let mut parameter = parameter.to_vec();
+        parameter.push(Value::from(platform_data));
+        self.action_invoked(app_id, id, action, &parameter).await
+    }
+}
+
+/// Serve `imp` as the `org.freedesktop.impl.portal.Notification` interface on
+/// `connection`.
+///
+/// The returned emitter notifies the applications of the activated actions.
+pub async fn serve(
+    connection: &zbus::Connection,
+    imp: impl NotificationImpl,
+) -> Result<NotificationSignalEmitter, Error> {
+    connection
+        .object_server()
+        .at(PATH, NotificationInterface(Box::new(imp)))
+        .await?;
+    Ok(NotificationSignalEmitter {
+        connection: connection.clone(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Mutex,
+    };
+
+    use async_trait::async_trait;
+    use futures::{executor::block_on, StreamExt};
+    use zbus::zvariant::{OwnedValue, Value};
+
+    use super::{serve, NotificationImpl, INTERFACE, PATH};
+    use crate::{
+        backend::{frontend_proxy, peer_connections},
+        desktop::notification::{Notification, Priority},
+        ActivationToken, PortalError,
+    };
+
+    // The shown notifications, by application and identifier.
+    #[derive(Default)]
+    struct Notifications(Mutex<HashSet<(String, String)>>);
+
+    #[async_trait]
+    impl NotificationImpl for Notifications {
+        async fn add_notification(
+            &self,
+            app_id: &str,
+            id: &str,
+            notification: Notification,
+        ) -> Result<(), PortalError> {
+            let expected = Notification::new("Title")
+                .with_body("Body")
+                .with_priority(Priority::High);
+            assert_eq!(notification, expected);
+            self.0
+                .lock()
+                .unwrap()
+                .insert((app_id.to_owned(), id.to_owned()));
+            Ok(())
+        }
+
+        async fn remove_notification(&self, app_id: &str, id: &str) -> Result<(), PortalError> {
+            if self
+                .0
+                .lock()
+                .unwrap()
+                .remove(&(app_id.to_owned(), id.to_owned()))
+            {
+                Ok(())
+            } else {
+                Err(PortalError::NotFound("Unknown notification".to_owned()))
+            }
+        }
+    }
+
+    #[test]
+    fn add_remove() {
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            serve(&backend, Notifications::default()).await.unwrap();
+            let proxy = frontend_proxy(&frontend, PATH, INTERFACE).await;
+
+            let notification = Notification::new("Title")
+                .with_body("Body")
+                .with_priority(Priority::High);
+            proxy
+                .call::<_, _, ()>("AddNotification", &("org.example.App", "a", &notification))
+                .await
+                .unwrap();
+            proxy
+                .call::<_, _, ()>("RemoveNotification", &("org.example.App", "a"))
+                .await
+                .unwrap();
+            match proxy
+                .call::<_, _, ()>("RemoveNotification", &("org.example.App", "a"))
+                .await
+            {
+                Err(zbus::Error::MethodError(name, ..)) => {
+                    assert_eq!(name.as_str(), "org.freedesktop.portal.Error.NotFound")
+                }
+                _ => panic!("Expected a NotFound error"),
+            }
+        });
+    }
+
+    #[test]
+    fn action_invoked() {
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            let emitter = serve(&backend, Notifications::default()).await.unwrap();
+            let proxy = frontend_proxy(&frontend, PATH, INTERFACE).await;
+            let mut actions = proxy.receive_signal("ActionInvoked").await.unwrap();
+
+            emitter
+                .action_invoked("org.example.App", "a", "reply", &[Value::from("hi")])
+                .await
+                .unwrap();
+            let message = actions.next().await.unwrap();
+            let (app_id, id, action, parameter): (String, String, String, Vec<OwnedValue>) =
+                message.body().unwrap();
+            assert_eq!(
+                (app_id.as_str(), id.as_str(), action.as_str()),
+                ("org.example.App", "a", "reply")
+            );
+            assert_eq!(parameter, [OwnedValue::from(Value::from("hi"))]);
+
+            emitter
+                .action_invoked_with_activation_token(
+                    "org.example.App",
+                    "a",
+                    "reply",
+                    &[Value::from("hi")],
+                    &ActivationToken::from("token"),
+                )
+                .await
+                .unwrap();
+            let message = actions.next().await.unwrap();
+            let (_, _, _, parameter): (String, String, String, Vec<OwnedValue>) =
+                message.body().unwrap();
+            assert_eq!(parameter[0], OwnedValue::from(Value::from("hi")));
+            let platform_data =
+                HashMap::<String, OwnedValue>::try_from(parameter[1].clone()).unwrap();
+            assert_eq!(
+                platform_data["activation-token"],
+                OwnedValue::from(Value::from("token"))
+            );
+        });
+    }
+}
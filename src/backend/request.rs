@@ -0,0 +1,134 @@
+use std::future::Future;
+
+use futures::{
+    channel::oneshot,
+    future::{self, Either},
+};
+use serde::Deserialize;
+use zbus::{
+    dbus_interface,
+    zvariant::{OwnedObjectPath, Type},
+};
+
+use crate::desktop::{request::Response, ResponseError};
+
+// The `org.freedesktop.impl.portal.Request` object exported while a request
+// is being handled, the frontend closes it when the application does.
+struct RequestInterface {
+    close: Option<oneshot::Sender<()>>,
+}
+
+#[dbus_interface(name = "org.freedesktop.impl.portal.Request")]
+impl RequestInterface {
+    fn close(&mut self) {
+        if let Some(close) = self.close.take() {
+            let _ = close.send(());
+        }
+    }
+}
+
+// Run `request` while a Request object is exported at `handle`, dropping it if
+// the request is closed before completing.
+pub(crate) async fn handle_request<T>(
+    connection: &zbus::Connection,
+    handle: OwnedObjectPath,
+    request: impl Future<Output = Result<T, ResponseError>>,
+) -> Response<T>
+where
+    T: for<'de> Deserialize<'de> + Type,
+{
+    let (close, closed) = oneshot::channel();
+    if let Err(_err) = connection
+        .object_server()
+        .at(&handle, RequestInterface { close: Some(close) })
+        .await
+    {
+        #[cfg(feature = "log")]
+        tracing::warn!("Failed to export the request {}: {}", handle.as_str(), _err);
+        return Response::Err(ResponseError::Other);
+    }
+    futures::pin_mut!(request);
+    let response = match future::select(request, closed).await {
+        Either::Left((result, _)) => result.into(),
+        Either::Right(_) => {
+            #[cfg(feature = "log")]
+            tracing::info!("The request {} was closed", handle.as_str());
+            Response::Err(ResponseError::Other)
+        }
+    };
+    let _ = connection
+        .object_server()
+        .remove::<RequestInterface, _>(&handle)
+        .await;
+    response
+}
+
+#[cfg(test)]
+mod test {
+    use futures::{channel::oneshot, executor::block_on, future::pending};
+    use zbus::zvariant::OwnedObjectPath;
+
+    use super::{handle_request, RequestInterface};
+    use crate::{
+        backend::{frontend_proxy, peer_connections},
+        desktop::{request::Response, ResponseError},
+    };
+
+    const HANDLE: &str = "/org/freedesktop/portal/desktop/request/1_42/t";
+
+    async fn is_exported(connection: &zbus::Connection) -> bool {
+        connection
+            .object_server()
+            .interface::<_, RequestInterface>(HANDLE)
+            .await
+            .is_ok()
+    }
+
+    #[test]
+    fn completed() {
+        block_on(async {
+            let (backend, _frontend) = peer_connections().await;
+            let handle = OwnedObjectPath::try_from(HANDLE).unwrap();
+            let request = async {
+                // Exported while the request is handled.
+                assert!(is_exported(&backend).await);
+                Ok(42u32)
+            };
+            let response = handle_request(&backend, handle, request).await;
+            assert!(matches!(response, Response::Ok(42)));
+            assert!(!is_exported(&backend).await);
+
+            let handle = OwnedObjectPath::try_from(HANDLE).unwrap();
+            let request = async { Err::<u32, _>(ResponseError::Cancelled) };
+            let response = handle_request(&backend, handle, request).await;
+            assert!(matches!(response, Response::Err(ResponseError::Cancelled)));
+            assert!(!is_exported(&backend).await);
+        });
+    }
+
+    #[test]
+    fn closed() {
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            let handle = OwnedObjectPath::try_from(HANDLE).unwrap();
+            let (started, has_started) = oneshot::channel();
+            let request = async {
+                started.send(()).unwrap();
+                pending::<Result<u32, ResponseError>>().await
+            };
+            let close = async {
+                has_started.await.unwrap();
+                let proxy =
+                    frontend_proxy(&frontend, HANDLE, "org.freedesktop.impl.portal.Request").await;
+                proxy.call_method("Close", &()).await.unwrap();
+                proxy
+            };
+            let (response, proxy) =
+                futures::join!(handle_request(&backend, handle, request), close);
+            assert!(matches!(response, Response::Err(ResponseError::Other)));
+            assert!(!is_exported(&backend).await);
+            // The object is gone, closing it again fails.
+            assert!(proxy.call_method("Close", &()).await.is_err());
+        });
+    }
+}
@@ -0,0 +1,272 @@
+//! ```rust,no_run
+//! use ashpd::{
+//!     backend::screenshot::{self, ScreenshotImpl, ScreenshotOptions},
+//!     desktop::{screenshot::Color, ResponseError},
+//!     url::Url,
+//! };
+//!
+//! struct Grim;
+//!
+//! #[ashpd::async_trait]
+//! impl ScreenshotImpl for Grim {
+//!     async fn screenshot(
+//!         &self,
+//!         _app_id: &str,
+//!         _parent_window: &str,
+//!         options: ScreenshotOptions,
+//!     ) -> Result<Url, ResponseError> {
+//!         if options.interactive().unwrap_or(false) {
+//!             // Let the user pick the area to capture.
+//!         }
+//!         Url::parse("file:///tmp/screenshot.png").map_err(|_| ResponseError::Other)
+//!     }
+//!
+//!     async fn pick_color(&self, _app_id: &str, _parent_window: &str) -> Result<Color, ResponseError> {
+//!         Ok(Color::new(1.0, 0.5, 0.0))
+//!     }
+//! }
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::ConnectionBuilder::session()?
+//!         .name("org.freedesktop.impl.portal.desktop.mydesktop")?
+//!         .build()
+//!         .await?;
+//!     screenshot::serve(&connection, Grim).await?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use url::Url;
+use zbus::{
+    dbus_interface,
+    zvariant::{DeserializeDict, OwnedObjectPath, SerializeDict, Type, Value},
+};
+
+use super::{request::handle_request, PATH};
+use crate::{
+    desktop::{
+        request::Response,
+        screenshot::{Color, Screenshot},
+        ResponseError,
+    },
+    Error,
+};
+
+#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, Default, PartialEq, Eq)]
+/// Specified options of a [`ScreenshotImpl::screenshot`] request.
+#[zvariant(signature = "dict")]
+pub struct ScreenshotOptions {
+    /// Whether the dialog should be modal.
+    modal: Option<bool>,
+    /// Whether the user should be able to customize the screenshot.
+    interactive: Option<bool>,
+    /// Whether the frontend already checked the permission of the
+    /// application.
+    permission_store_checked: Option<bool>,
+}
+
+impl ScreenshotOptions {
+    /// Whether the dialog should be modal.
+    pub fn modal(&self) -> Option<bool> {
+        self.modal
+    }
+
+    /// Whether the user should be able to customize the screenshot, the area
+    /// or the window to capture for example.
+    pub fn interactive(&self) -> Option<bool> {
+        self.interactive
+    }
+
+    /// Whether the frontend already checked that the application is allowed
+    /// to take non-interactive screenshots, sent since version 2. If not, the
+    /// backend should ask the user before taking one.
+    pub fn permission_store_checked(&self) -> Option<bool> {
+        self.permission_store_checked
+    }
+}
+
+/// The implementation of the `org.freedesktop.impl.portal.Screenshot`
+/// interface.
+///
+/// The requests are dropped if the application closes them before they
+/// complete, [`ResponseError::Cancelled`] reports that the user dismissed the
+/// interaction.
+#[async_trait]
+pub trait ScreenshotImpl: Send + Sync + 'static {
+    /// Take a screenshot on behalf of `app_id`.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The application requesting the screenshot.
+    /// * `parent_window` - The identifier of the application window, see
+    ///   [`WindowIdentifier`](crate::WindowIdentifier).
+    /// * `options` - The options of the request.
+    ///
+    /// # Returns
+    ///
+    /// The uri of the screenshot.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`Screenshot`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-impl-portal-Screenshot.Screenshot).
+    #[doc(alias = "Screenshot")]
+    async fn screenshot(
+        &self,
+        app_id: &str,
+        parent_window: &str,
+        options: ScreenshotOptions,
+    ) -> Result<Url, ResponseError>;
+
+    /// Let the user pick the color of a pixel of the screen on behalf of
+    /// `app_id`.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`PickColor`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-impl-portal-Screenshot.PickColor).
+    #[doc(alias = "PickColor")]
+    async fn pick_color(&self, app_id: &str, parent_window: &str) -> Result<Color, ResponseError>;
+}
+
+struct ScreenshotInterface(Box<dyn ScreenshotImpl>);
+
+#[dbus_interface(name = "org.freedesktop.impl.portal.Screenshot")]
+impl ScreenshotInterface {
+    async fn screenshot(
+        &self,
+        #[zbus(connection)] connection: &zbus::Connection,
+        handle: OwnedObjectPath,
+        app_id: &str,
+        parent_window: &str,
+        options: ScreenshotOptions,
+    ) -> Response<Screenshot> {
+        let request = async {
+            self.0
+                .screenshot(app_id, parent_window, options)
+                .await
+                .map(Screenshot::new)
+        };
+        handle_request(connection, handle, request).await
+    }
+
+    async fn pick_color(
+        &self,
+        #[zbus(connection)] connection: &zbus::Connection,
+        handle: OwnedObjectPath,
+        app_id: &str,
+        parent_window: &str,
+        _options: HashMap<&str, Value<'_>>,
+    ) -> Response<Color> {
+        handle_request(connection, handle, self.0.pick_color(app_id, parent_window)).await
+    }
+
+    #[dbus_interface(property, name = "version")]
+    fn version(&self) -> u32 {
+        2
+    }
+}
+
+/// Serve `imp` as the `org.freedesktop.impl.portal.Screenshot` interface on
+/// `connection`.
+pub async fn serve(connection: &zbus::Connection, imp: impl ScreenshotImpl) -> Result<(), Error> {
+    connection
+        .object_server()
+        .at(PATH, ScreenshotInterface(Box::new(imp)))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use async_trait::async_trait;
+    use futures::executor::block_on;
+    use url::Url;
+    use zbus::zvariant::{ObjectPath, Value};
+
+    use super::{serve, ScreenshotImpl, ScreenshotOptions, PATH};
+    use crate::{
+        backend::{frontend_proxy, peer_connections},
+        desktop::{
+            request::Response,
+            screenshot::{Color, Screenshot},
+            ResponseError,
+        },
+    };
+
+    const INTERFACE: &str = "org.freedesktop.impl.portal.Screenshot";
+    const HANDLE: &str = "/org/freedesktop/portal/desktop/request/1_42/t";
+
+    fn handle() -> ObjectPath<'static> {
+        ObjectPath::try_from(HANDLE).unwrap()
+    }
+
+    struct Screenshooter;
+
+    #[async_trait]
+    impl ScreenshotImpl for Screenshooter {
+        async fn screenshot(
+            &self,
+            app_id: &str,
+            _parent_window: &str,
+            options: ScreenshotOptions,
+        ) -> Result<Url, ResponseError> {
+            assert_eq!(app_id, "org.example.App");
+            if options.interactive() == Some(true) {
+                return Err(ResponseError::Cancelled);
+            }
+            Ok(Url::parse("file:///tmp/screenshot.png").unwrap())
+        }
+
+        async fn pick_color(
+            &self,
+            _app_id: &str,
+            _parent_window: &str,
+        ) -> Result<Color, ResponseError> {
+            Ok(Color::new(1.0, 0.5, 0.0))
+        }
+    }
+
+    #[test]
+    fn screenshot() {
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            serve(&backend, Screenshooter).await.unwrap();
+            let proxy = frontend_proxy(&frontend, PATH, INTERFACE).await;
+
+            let options = HashMap::from([("interactive", Value::from(false))]);
+            let response: Response<Screenshot> = proxy
+                .call("Screenshot", &(handle(), "org.example.App", "", options))
+                .await
+                .unwrap();
+            let expected = Screenshot::new(Url::parse("file:///tmp/screenshot.png").unwrap());
+            assert!(matches!(response, Response::Ok(screenshot) if screenshot == expected));
+
+            let options = HashMap::from([("interactive", Value::from(true))]);
+            let response: Response<Screenshot> = proxy
+                .call("Screenshot", &(handle(), "org.example.App", "", options))
+                .await
+                .unwrap();
+            assert!(matches!(response, Response::Err(ResponseError::Cancelled)));
+        });
+    }
+
+    #[test]
+    fn pick_color() {
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            serve(&backend, Screenshooter).await.unwrap();
+            let proxy = frontend_proxy(&frontend, PATH, INTERFACE).await;
+
+            let options = HashMap::<&str, Value<'_>>::new();
+            let response: Response<Color> = proxy
+                .call("PickColor", &(handle(), "org.example.App", "", options))
+                .await
+                .unwrap();
+            assert!(matches!(response, Response::Ok(color) if color == Color::new(1.0, 0.5, 0.0)));
+        });
+    }
+}
@@ -0,0 +1,432 @@
+//! ```rust,no_run
+//! use std::collections::HashMap;
+//!
+//! use ashpd::{
+//!     backend::session::{SessionImpl, SessionInfo, Sessions},
+//!     zvariant::{OwnedObjectPath, OwnedValue},
+//! };
+//!
+//! struct Recorder;
+//!
+//! #[ashpd::async_trait]
+//! impl SessionImpl for Recorder {
+//!     async fn session_closed(&self, session: &SessionInfo) {
+//!         println!("Stop the streams of {}", session.app_id());
+//!     }
+//! }
+//!
+//! struct ScreenCast(Sessions);
+//!
+//! #[zbus::dbus_interface(name = "org.freedesktop.impl.portal.ScreenCast")]
+//! impl ScreenCast {
+//!     async fn create_session(
+//!         &self,
+//!         #[zbus(header)] header: zbus::MessageHeader<'_>,
+//!         _handle: OwnedObjectPath,
+//!         session_handle: OwnedObjectPath,
+//!         app_id: &str,
+//!         _options: HashMap<String, OwnedValue>,
+//!     ) -> (u32, HashMap<String, OwnedValue>) {
+//!         match self.0.create(&header, session_handle, app_id).await {
+//!             Ok(_) => (0, HashMap::new()),
+//!             Err(_) => (2, HashMap::new()),
+//!         }
+//!     }
+//! }
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::ConnectionBuilder::session()?
+//!         .name("org.freedesktop.impl.portal.desktop.mydesktop")?
+//!         .build()
+//!         .await?;
+//!     let sessions = Sessions::new(&connection, Recorder).await?;
+//!     connection
+//!         .object_server()
+//!         .at("/org/freedesktop/portal/desktop", ScreenCast(sessions))
+//!         .await?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex, Weak},
+};
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use zbus::{
+    dbus_interface,
+    names::{BusName, OwnedUniqueName, UniqueName},
+    zvariant::{ObjectPath, OwnedObjectPath},
+    MessageHeader, SignalContext,
+};
+
+use crate::Error;
+
+/// A session created by the frontend on behalf of an application.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionInfo {
+    handle: OwnedObjectPath,
+    app_id: String,
+    client: OwnedUniqueName,
+}
+
+impl SessionInfo {
+    /// The object path of the session.
+    pub fn handle(&self) -> &ObjectPath<'static> {
+        &self.handle
+    }
+
+    /// The application the session was created for.
+    pub fn app_id(&self) -> &str {
+        &self.app_id
+    }
+
+    /// The unique name of the frontend that created the session.
+    pub fn client(&self) -> &UniqueName<'static> {
+        &self.client
+    }
+}
+
+/// Notified when the sessions tracked by [`Sessions`] are closed.
+#[async_trait]
+pub trait SessionImpl: Send + Sync + 'static {
+    /// Release what `session` holds, the streams or the devices for example.
+    ///
+    /// Called once per session, whether it was closed by the frontend, by
+    /// [`Sessions::close`] or because the frontend left the bus.
+    async fn session_closed(&self, session: &SessionInfo);
+}
+
+struct SessionsInner {
+    connection: zbus::Connection,
+    imp: Box<dyn SessionImpl>,
+    // By object path.
+    sessions: Mutex<HashMap<String, SessionInfo>>,
+}
+
+impl SessionsInner {
+    // Forget the session, unexport it and notify the implementation. Returns
+    // None if it was removed already.
+    async fn remove(&self, handle: &ObjectPath<'_>) -> Option<SessionInfo> {
+        let session = self.sessions.lock().unwrap().remove(handle.as_str())?;
+        let _ = self
+            .connection
+            .object_server()
+            .remove::<SessionInterface, _>(handle)
+            .await;
+        #[cfg(feature = "log")]
+        tracing::debug!(
+            "Closed the session {} of {}",
+            handle.as_str(),
+            session.app_id()
+        );
+        self.imp.session_closed(&session).await;
+        Some(session)
+    }
+
+    // Close the sessions of the clients leaving the bus, as reported by
+    // `NameOwnerChanged`.
+    async fn name_owner_changed(&self, name: &BusName<'_>, new_owner: Option<&UniqueName<'_>>) {
+        if let (BusName::Unique(client), None) = (name, new_owner) {
+            self.client_vanished(client).await;
+        }
+    }
+
+    // Close the sessions created by `client`, which left the bus.
+    async fn client_vanished(&self, client: &UniqueName<'_>) {
+        let handles = self
+            .sessions
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|session| session.client() == client)
+            .map(|session| session.handle.clone())
+            .collect::<Vec<_>>();
+        for handle in handles {
+            self.remove(&handle).await;
+        }
+    }
+}
+
+// The `org.freedesktop.impl.portal.Session` object exported for each session.
+struct SessionInterface {
+    handle: OwnedObjectPath,
+    sessions: Weak<SessionsInner>,
+}
+
+#[dbus_interface(name = "org.freedesktop.impl.portal.Session")]
+impl SessionInterface {
+    async fn close(&self) {
+        if let Some(sessions) = self.sessions.upgrade() {
+            sessions.remove(&self.handle).await;
+        }
+    }
+
+    #[dbus_interface(signal)]
+    async fn closed(ctxt: &SignalContext<'_>) -> zbus::Result<()>;
+
+    #[dbus_interface(property, name = "version")]
+    fn version(&self) -> u32 {
+        1
+    }
+}
+
+/// The sessions of a backend, the `org.freedesktop.impl.portal.Session`
+/// objects of the screen cast or remote desktop portals for example.
+///
+/// The sessions are closed when the frontend closes them or leaves the bus,
+/// [`SessionImpl::session_closed`] is called then. The clones share the same
+/// sessions.
+#[derive(Clone)]
+pub struct Sessions(Arc<SessionsInner>);
+
+impl Sessions {
+    /// Track the sessions created on `connection`, notifying `imp` when they
+    /// are closed.
+    pub async fn new(connection: &zbus::Connection, imp: impl SessionImpl) -> Result<Self, Error> {
+        let inner = Arc::new(SessionsInner {
+            connection: connection.clone(),
+            imp: Box::new(imp),
+            sessions: Mutex::new(HashMap::new()),
+        });
+        let dbus = zbus::fdo::DBusProxy::new(connection).await?;
+        let mut owner_changes = dbus.receive_name_owner_changed().await?;
+        let sessions = Arc::downgrade(&inner);
+        connection
+            .executor()
+            .spawn(async move {
+                while let Some(signal) = owner_changes.next().await {
+                    let sessions = match sessions.upgrade() {
+                        Some(sessions) => sessions,
+                        None => break,
+                    };
+                    if let Ok(args) = signal.args() {
+                        sessions
+                            .name_owner_changed(args.name(), args.new_owner().as_ref())
+                            .await;
+                    }
+                }
+            })
+            .detach();
+        Ok(Self(inner))
+    }
+
+    /// Export the session `handle` created for `app_id` by the sender of the
+    /// `CreateSession` call, `header` being its header.
+    pub async fn create(
+        &self,
+        header: &MessageHeader<'_>,
+        handle: OwnedObjectPath,
+        app_id: &str,
+    ) -> Result<SessionInfo, Error> {
+        let client = header.sender()?.ok_or(zbus::Error::MissingField)?;
+        let session = SessionInfo {
+            handle: handle.clone(),
+            app_id: app_id.to_owned(),
+            client: client.to_owned().into(),
+        };
+        let interface = SessionInterface {
+            handle: handle.clone(),
+            sessions: Arc::downgrade(&self.0),
+        };
+        self.0
+            .connection
+            .object_server()
+            .at(&handle, interface)
+            .await?;
+        self.0
+            .sessions
+            .lock()
+            .unwrap()
+            .insert(handle.as_str().to_owned(), session.clone());
+        Ok(session)
+    }
+
+    /// The session at `handle`, if it is still open.
+    pub fn get(&self, handle: &ObjectPath<'_>) -> Option<SessionInfo> {
+        self.0
+            .sessions
+            .lock()
+            .unwrap()
+            .get(handle.as_str())
+            .cloned()
+    }
+
+    /// The open sessions of `app_id`.
+    pub fn app_sessions(&self, app_id: &str) -> Vec<SessionInfo> {
+        self.0
+            .sessions
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|session| session.app_id() == app_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Close the session at `handle` from the backend side, when the user
+    /// stops sharing the screen for example, emitting the `Closed` signal.
+    ///
+    /// Returns whether the session was still open.
+    pub async fn close(&self, handle: &ObjectPath<'_>) -> Result<bool, Error> {
+        if self.get(handle).is_none() {
+            return Ok(false);
+        }
+        let ctxt = SignalContext::new(&self.0.connection, handle)?;
+        SessionInterface::closed(&ctxt).await?;
+        Ok(self.0.remove(handle).await.is_some())
+    }
+}
+
+impl Debug for Sessions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set()
+            .entries(
+                self.0
+                    .sessions
+                    .lock()
+                    .unwrap()
+                    .keys()
+                    .map(|handle| handle.as_str()),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use async_trait::async_trait;
+    use futures::{
+        channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+        executor::block_on,
+        StreamExt,
+    };
+    use zbus::{
+        names::{BusName, UniqueName},
+        zvariant::OwnedObjectPath,
+        MessageBuilder,
+    };
+
+    use super::{SessionImpl, SessionInfo, SessionInterface, Sessions};
+    use crate::backend::{frontend_proxy, peer_connections};
+
+    const INTERFACE: &str = "org.freedesktop.impl.portal.Session";
+    const FIRST: &str = "/org/freedesktop/portal/desktop/session/1_42/a";
+    const SECOND: &str = "/org/freedesktop/portal/desktop/session/1_43/b";
+
+    struct Recorder(UnboundedSender<SessionInfo>);
+
+    #[async_trait]
+    impl SessionImpl for Recorder {
+        async fn session_closed(&self, session: &SessionInfo) {
+            self.0.unbounded_send(session.clone()).unwrap();
+        }
+    }
+
+    async fn sessions(backend: &zbus::Connection) -> (Sessions, UnboundedReceiver<SessionInfo>) {
+        let (sender, closed) = unbounded();
+        let sessions = Sessions::new(backend, Recorder(sender)).await.unwrap();
+        (sessions, closed)
+    }
+
+    // A `CreateSession` call of the frontend `client` creating `handle`.
+    async fn create(sessions: &Sessions, client: &str, handle: &str) -> SessionInfo {
+        let message =
+            MessageBuilder::method_call("/org/freedesktop/portal/desktop", "CreateSession")
+                .unwrap()
+                .sender(client)
+                .unwrap()
+                .build(&())
+                .unwrap();
+        let handle = OwnedObjectPath::try_from(handle).unwrap();
+        sessions
+            .create(&message.header().unwrap(), handle, "org.example.App")
+            .await
+            .unwrap()
+    }
+
+    async fn is_exported(connection: &zbus::Connection, handle: &str) -> bool {
+        connection
+            .object_server()
+            .interface::<_, SessionInterface>(handle)
+            .await
+            .is_ok()
+    }
+
+    #[test]
+    fn closed_by_frontend() {
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            let (sessions, mut closed) = sessions(&backend).await;
+
+            let session = create(&sessions, ":1.42", FIRST).await;
+            assert_eq!(session.app_id(), "org.example.App");
+            assert_eq!(session.client().as_str(), ":1.42");
+            assert!(is_exported(&backend, FIRST).await);
+            assert_eq!(sessions.get(session.handle()), Some(session.clone()));
+            assert_eq!(
+                sessions.app_sessions("org.example.App"),
+                vec![session.clone()]
+            );
+
+            let proxy = frontend_proxy(&frontend, FIRST, INTERFACE).await;
+            proxy.call_method("Close", &()).await.unwrap();
+            assert_eq!(closed.next().await, Some(session.clone()));
+            assert!(!is_exported(&backend, FIRST).await);
+            assert_eq!(sessions.get(session.handle()), None);
+            assert!(sessions.app_sessions("org.example.App").is_empty());
+
+            // Already closed, the implementation isn't notified again.
+            assert!(!sessions.close(session.handle()).await.unwrap());
+            assert!(proxy.call_method("Close", &()).await.is_err());
+            drop(sessions);
+            assert_eq!(closed.next().await, None);
+        });
+    }
+
+    #[test]
+    fn closed_by_backend() {
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            let (sessions, mut closed) = sessions(&backend).await;
+            let session = create(&sessions, ":1.42", FIRST).await;
+
+            let proxy = frontend_proxy(&frontend, FIRST, INTERFACE).await;
+            let mut closed_signal = proxy.receive_signal("Closed").await.unwrap();
+            assert!(sessions.close(session.handle()).await.unwrap());
+            assert!(closed_signal.next().await.is_some());
+            assert_eq!(closed.next().await, Some(session.clone()));
+            assert!(!is_exported(&backend, FIRST).await);
+            assert!(!sessions.close(session.handle()).await.unwrap());
+        });
+    }
+
+    #[test]
+    fn frontend_vanished() {
+        block_on(async {
+            let (backend, _frontend) = peer_connections().await;
+            let (sessions, mut closed) = sessions(&backend).await;
+            let first = create(&sessions, ":1.42", FIRST).await;
+            let second = create(&sessions, ":1.43", SECOND).await;
+
+            // A name that isn't a unique one, or a client getting a new
+            // owner, doesn't close anything.
+            let app = BusName::try_from("org.example.App").unwrap();
+            let client = BusName::try_from(":1.42").unwrap();
+            let owner = UniqueName::try_from(":1.42").unwrap();
+            sessions.0.name_owner_changed(&app, None).await;
+            sessions.0.name_owner_changed(&client, Some(&owner)).await;
+            assert_eq!(sessions.app_sessions("org.example.App").len(), 2);
+
+            sessions.0.name_owner_changed(&client, None).await;
+            assert_eq!(closed.next().await, Some(first.clone()));
+            assert!(!is_exported(&backend, FIRST).await);
+            assert_eq!(sessions.get(first.handle()), None);
+            assert_eq!(sessions.get(second.handle()), Some(second.clone()));
+            assert!(is_exported(&backend, SECOND).await);
+        });
+    }
+}
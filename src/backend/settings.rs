@@ -0,0 +1,253 @@
+//! ```rust,no_run
+//! use std::collections::HashMap;
+//!
+//! use ashpd::{
+//!     backend::settings::{self, SettingsImpl},
+//!     desktop::settings::{namespace_matches, Namespace},
+//!     zvariant::OwnedValue,
+//!     PortalError,
+//! };
+//!
+//! struct Appearance;
+//!
+//! #[ashpd::async_trait]
+//! impl SettingsImpl for Appearance {
+//!     async fn read_all(
+//!         &self,
+//!         namespaces: Vec<String>,
+//!     ) -> Result<HashMap<String, Namespace>, PortalError> {
+//!         let mut appearance = Namespace::new();
+//!         appearance.insert("color-scheme".to_owned(), OwnedValue::from(1u32));
+//!         let mut settings = HashMap::new();
+//!         if namespace_matches(&namespaces, "org.freedesktop.appearance") {
+//!             settings.insert("org.freedesktop.appearance".to_owned(), appearance);
+//!         }
+//!         Ok(settings)
+//!     }
+//!
+//!     async fn read(&self, namespace: &str, key: &str) -> Result<OwnedValue, PortalError> {
+//!         match (namespace, key) {
+//!             ("org.freedesktop.appearance", "color-scheme") => Ok(OwnedValue::from(1u32)),
+//!             _ => Err(PortalError::NotFound(format!("{}.{}", namespace, key))),
+//!         }
+//!     }
+//! }
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::ConnectionBuilder::session()?
+//!         .name("org.freedesktop.impl.portal.desktop.mydesktop")?
+//!         .build()
+//!         .await?;
+//!     let emitter = settings::serve(&connection, Appearance).await?;
+//!
+//!     // The user switched to the light appearance.
+//!     emitter
+//!         .setting_changed("org.freedesktop.appearance", "color-scheme", 2u32)
+//!         .await?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use zbus::{
+    dbus_interface,
+    zvariant::{OwnedValue, Value},
+};
+
+use super::PATH;
+use crate::{desktop::settings::Namespace, Error, PortalError};
+
+const INTERFACE: &str = "org.freedesktop.impl.portal.Settings";
+
+/// The implementation of the `org.freedesktop.impl.portal.Settings`
+/// interface.
+///
+/// The values are the ones exposed to the sandboxed applications through the
+/// [`SettingsProxy`](crate::desktop::settings::SettingsProxy), unknown
+/// namespaces and keys should be reported with [`PortalError::NotFound`].
+#[async_trait]
+pub trait SettingsImpl: Send + Sync + 'static {
+    /// Read the settings of the `namespaces`, an empty list or one containing
+    /// an empty string matches all of them. Globbing is supported for trailing
+    /// sections, e.g. `org.example.*`, see
+    /// [`namespace_matches`](crate::desktop::settings::namespace_matches).
+    ///
+    /// # Specifications
+    ///
+    /// See also [`ReadAll`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-impl-portal-Settings.ReadAll).
+    #[doc(alias = "ReadAll")]
+    async fn read_all(
+        &self,
+        namespaces: Vec<String>,
+    ) -> Result<HashMap<String, Namespace>, PortalError>;
+
+    /// Read a single value.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`Read`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-impl-portal-Settings.Read).
+    #[doc(alias = "Read")]
+    async fn read(&self, namespace: &str, key: &str) -> Result<OwnedValue, PortalError>;
+}
+
+struct SettingsInterface(Box<dyn SettingsImpl>);
+
+#[dbus_interface(name = "org.freedesktop.impl.portal.Settings")]
+impl SettingsInterface {
+    async fn read_all(
+        &self,
+        namespaces: Vec<String>,
+    ) -> Result<HashMap<String, Namespace>, PortalError> {
+        self.0.read_all(namespaces).await
+    }
+
+    async fn read(&self, namespace: &str, key: &str) -> Result<OwnedValue, PortalError> {
+        self.0.read(namespace, key).await
+    }
+
+    #[dbus_interface(property, name = "version")]
+    fn version(&self) -> u32 {
+        1
+    }
+}
+
+/// Emits the signals of the `org.freedesktop.impl.portal.Settings` interface
+/// served with [`serve`].
+#[derive(Clone, Debug)]
+pub struct SettingsSignalEmitter {
+    connection: zbus::Connection,
+}
+
+impl SettingsSignalEmitter {
+    /// Notify the sandboxed applications that the value of a setting
+    /// changed.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`SettingChanged`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-impl-portal-Settings.SettingChanged).
+    #[doc(alias = "SettingChanged")]
+    pub async fn setting_changed<'v>(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: impl Into<Value<'v>>,
+    ) -> Result<(), Error> {
+        self.connection
+            .emit_signal(
+                None::<()>,
+                PATH,
+                INTERFACE,
+                "SettingChanged",
+                &(namespace, key, value.into()),
+            )
+            .await
+            .map_err(From::from)
+    }
+}
+
+/// Serve `imp` as the `org.freedesktop.impl.portal.Settings` interface on
+/// `connection`.
+///
+/// The returned emitter notifies the changes of the settings.
+pub async fn serve(
+    connection: &zbus::Connection,
+    imp: impl SettingsImpl,
+) -> Result<SettingsSignalEmitter, Error> {
+    connection
+        .object_server()
+        .at(PATH, SettingsInterface(Box::new(imp)))
+        .await?;
+    Ok(SettingsSignalEmitter {
+        connection: connection.clone(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use async_trait::async_trait;
+    use futures::{executor::block_on, StreamExt};
+    use zbus::zvariant::{OwnedValue, Value};
+
+    use super::{serve, SettingsImpl, INTERFACE, PATH};
+    use crate::{
+        backend::{frontend_proxy, peer_connections},
+        desktop::settings::{namespace_matches, Namespace},
+        PortalError,
+    };
+
+    struct Settings;
+
+    #[async_trait]
+    impl SettingsImpl for Settings {
+        async fn read_all(
+            &self,
+            namespaces: Vec<String>,
+        ) -> Result<HashMap<String, Namespace>, PortalError> {
+            let mut settings = HashMap::new();
+            if namespace_matches(&namespaces, "org.example") {
+                let namespace = HashMap::from([("key".to_owned(), OwnedValue::from(42u32))]);
+                settings.insert("org.example".to_owned(), namespace);
+            }
+            Ok(settings)
+        }
+
+        async fn read(&self, namespace: &str, key: &str) -> Result<OwnedValue, PortalError> {
+            match (namespace, key) {
+                ("org.example", "key") => Ok(42u32.into()),
+                _ => Err(PortalError::NotFound("Unknown setting".to_owned())),
+            }
+        }
+    }
+
+    #[test]
+    fn read() {
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            serve(&backend, Settings).await.unwrap();
+            let proxy = frontend_proxy(&frontend, PATH, INTERFACE).await;
+
+            let value: OwnedValue = proxy.call("Read", &("org.example", "key")).await.unwrap();
+            assert_eq!(u32::try_from(value).unwrap(), 42);
+            match proxy
+                .call::<_, _, OwnedValue>("Read", &("org.example", "other"))
+                .await
+            {
+                Err(zbus::Error::MethodError(name, ..)) => {
+                    assert_eq!(name.as_str(), "org.freedesktop.portal.Error.NotFound")
+                }
+                _ => panic!("Expected a NotFound error"),
+            }
+
+            let all: HashMap<String, Namespace> =
+                proxy.call("ReadAll", &(vec!["org.*"],)).await.unwrap();
+            assert_eq!(all["org.example"].len(), 1);
+            let all: HashMap<String, Namespace> =
+                proxy.call("ReadAll", &(vec!["com.*"],)).await.unwrap();
+            assert!(all.is_empty());
+        });
+    }
+
+    #[test]
+    fn setting_changed() {
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            let emitter = serve(&backend, Settings).await.unwrap();
+            let proxy = frontend_proxy(&frontend, PATH, INTERFACE).await;
+            let mut changes = proxy.receive_signal("SettingChanged").await.unwrap();
+
+            emitter
+                .setting_changed("org.example", "key", 43u32)
+                .await
+                .unwrap();
+            let message = changes.next().await.unwrap();
+            let (namespace, key, value): (String, String, Value<'_>) = message.body().unwrap();
+            assert_eq!(namespace, "org.example");
+            assert_eq!(key, "key");
+            assert_eq!(value, Value::from(43u32));
+        });
+    }
+}
@@ -0,0 +1,216 @@
+//! ```rust,no_run
+//! use ashpd::{
+//!     backend::wallpaper::{self, WallpaperImpl, WallpaperOptions},
+//!     desktop::{wallpaper::SetOn, ResponseError},
+//!     url::Url,
+//! };
+//!
+//! struct Swaybg;
+//!
+//! #[ashpd::async_trait]
+//! impl WallpaperImpl for Swaybg {
+//!     async fn set_wallpaper_uri(
+//!         &self,
+//!         _app_id: &str,
+//!         _parent_window: &str,
+//!         uri: Url,
+//!         options: WallpaperOptions,
+//!     ) -> Result<(), ResponseError> {
+//!         if options.set_on() == Some(SetOn::Lockscreen) {
+//!             return Err(ResponseError::Other);
+//!         }
+//!         let path = uri.to_file_path().map_err(|_| ResponseError::Other)?;
+//!         println!("Setting {} as wallpaper", path.display());
+//!         Ok(())
+//!     }
+//! }
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::ConnectionBuilder::session()?
+//!         .name("org.freedesktop.impl.portal.desktop.mydesktop")?
+//!         .build()
+//!         .await?;
+//!     wallpaper::serve(&connection, Swaybg).await?;
+//!     Ok(())
+//! }
+//! ```
+
+use async_trait::async_trait;
+use url::Url;
+use zbus::{
+    dbus_interface,
+    zvariant::{DeserializeDict, OwnedObjectPath, SerializeDict, Type},
+};
+
+use super::{request::handle_request, PATH};
+use crate::{
+    desktop::{request::ResponseCode, wallpaper::SetOn, ResponseError},
+    Error,
+};
+
+#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, Default, PartialEq, Eq)]
+/// Specified options of a [`WallpaperImpl::set_wallpaper_uri`] request.
+#[zvariant(signature = "dict")]
+pub struct WallpaperOptions {
+    /// Whether to show a preview of the picture.
+    #[zvariant(rename = "show-preview")]
+    show_preview: Option<bool>,
+    /// Where to set the wallpaper on.
+    #[zvariant(rename = "set-on")]
+    set_on: Option<SetOn>,
+}
+
+impl WallpaperOptions {
+    /// Whether to show a preview of the picture before setting it.
+    pub fn show_preview(&self) -> Option<bool> {
+        self.show_preview
+    }
+
+    /// Where to set the wallpaper on, both the background and the
+    /// lock-screen if unset.
+    pub fn set_on(&self) -> Option<SetOn> {
+        self.set_on
+    }
+}
+
+/// The implementation of the `org.freedesktop.impl.portal.Wallpaper`
+/// interface.
+///
+/// The wallpapers the applications pass as file descriptors are converted to
+/// `file://` uris by `xdg-desktop-portal` before reaching the backend.
+#[async_trait]
+pub trait WallpaperImpl: Send + Sync + 'static {
+    /// Set the wallpaper on behalf of `app_id`.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The application setting the wallpaper.
+    /// * `parent_window` - The identifier of the application window, see
+    ///   [`WindowIdentifier`](crate::WindowIdentifier).
+    /// * `uri` - The uri of the picture.
+    /// * `options` - The options of the request.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`SetWallpaperURI`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-impl-portal-Wallpaper.SetWallpaperURI).
+    #[doc(alias = "SetWallpaperURI")]
+    async fn set_wallpaper_uri(
+        &self,
+        app_id: &str,
+        parent_window: &str,
+        uri: Url,
+        options: WallpaperOptions,
+    ) -> Result<(), ResponseError>;
+}
+
+struct WallpaperInterface(Box<dyn WallpaperImpl>);
+
+#[dbus_interface(name = "org.freedesktop.impl.portal.Wallpaper")]
+impl WallpaperInterface {
+    #[dbus_interface(name = "SetWallpaperURI")]
+    async fn set_wallpaper_uri(
+        &self,
+        #[zbus(connection)] connection: &zbus::Connection,
+        handle: OwnedObjectPath,
+        app_id: &str,
+        parent_window: &str,
+        uri: &str,
+        options: WallpaperOptions,
+    ) -> ResponseCode {
+        let uri = match Url::parse(uri) {
+            Ok(uri) => uri,
+            Err(_err) => {
+                #[cfg(feature = "log")]
+                tracing::warn!("Invalid wallpaper uri {}: {}", uri, _err);
+                return ResponseError::Other.into();
+            }
+        };
+        let request = self
+            .0
+            .set_wallpaper_uri(app_id, parent_window, uri, options);
+        handle_request(connection, handle, request).await.code()
+    }
+}
+
+/// Serve `imp` as the `org.freedesktop.impl.portal.Wallpaper` interface on
+/// `connection`.
+pub async fn serve(connection: &zbus::Connection, imp: impl WallpaperImpl) -> Result<(), Error> {
+    connection
+        .object_server()
+        .at(PATH, WallpaperInterface(Box::new(imp)))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use async_trait::async_trait;
+    use futures::executor::block_on;
+    use url::Url;
+    use zbus::zvariant::{ObjectPath, Value};
+
+    use super::{serve, WallpaperImpl, WallpaperOptions, PATH};
+    use crate::{
+        backend::{frontend_proxy, peer_connections},
+        desktop::{request::ResponseCode, wallpaper::SetOn, ResponseError},
+    };
+
+    const INTERFACE: &str = "org.freedesktop.impl.portal.Wallpaper";
+    const HANDLE: &str = "/org/freedesktop/portal/desktop/request/1_42/t";
+
+    fn handle() -> ObjectPath<'static> {
+        ObjectPath::try_from(HANDLE).unwrap()
+    }
+
+    struct Wallpaper;
+
+    #[async_trait]
+    impl WallpaperImpl for Wallpaper {
+        async fn set_wallpaper_uri(
+            &self,
+            _app_id: &str,
+            _parent_window: &str,
+            uri: Url,
+            options: WallpaperOptions,
+        ) -> Result<(), ResponseError> {
+            assert_eq!(uri.as_str(), "file:///tmp/wallpaper.png");
+            match options.set_on() {
+                Some(SetOn::Lockscreen) => Err(ResponseError::Cancelled),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    async fn set(proxy: &zbus::Proxy<'_>, uri: &str, set_on: &str) -> ResponseCode {
+        let options = HashMap::from([("set-on", Value::from(set_on))]);
+        proxy
+            .call(
+                "SetWallpaperURI",
+                &(handle(), "org.example.App", "", uri, options),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn set_wallpaper_uri() {
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            serve(&backend, Wallpaper).await.unwrap();
+            let proxy = frontend_proxy(&frontend, PATH, INTERFACE).await;
+            let uri = "file:///tmp/wallpaper.png";
+            assert_eq!(set(&proxy, uri, "both").await, ResponseCode::Success);
+            assert_eq!(
+                set(&proxy, uri, "lockscreen").await,
+                ResponseCode::Cancelled
+            );
+            // Rejected before reaching the implementation.
+            assert_eq!(
+                set(&proxy, "wallpaper.png", "both").await,
+                ResponseCode::Other
+            );
+        });
+    }
+}
@@ -0,0 +1,80 @@
+//! The public API only requires [`std::future::Future`], any executor can
+//! drive it. Small daemons that don't run one can block on each call
+//! instead.
+//!
+//! ```rust,no_run
+//! use ashpd::{blocking::block_on, desktop::screenshot::ScreenshotProxy, WindowIdentifier};
+//!
+//! fn main() -> ashpd::Result<()> {
+//!     let uri = block_on(async {
+//!         let connection = zbus::Connection::session().await?;
+//!         let proxy = ScreenshotProxy::new(&connection).await?;
+//!         proxy
+//!             .screenshot(&WindowIdentifier::default(), true, false)
+//!             .await
+//!     })?;
+//!     println!("{}", uri);
+//!     Ok(())
+//! }
+//! ```
+//!
+//! *Note* that the connection must keep its internal executor, the default,
+//! or be ticked with [`spawn_executor`](crate::spawn_executor), the responses
+//! of the requests are dispatched from there.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+};
+
+// Unparks the thread blocked on the future.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Poll `future` on the current thread until it completes, parking the thread
+/// while it is pending.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match Pin::as_mut(&mut future).poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            // A spurious unpark only polls the future once more.
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{thread, time::Duration};
+
+    use futures::channel::oneshot;
+
+    use super::block_on;
+
+    #[test]
+    fn wakes() {
+        assert_eq!(block_on(async { 42 }), 42);
+
+        let (sender, receiver) = oneshot::channel();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            sender.send("done").unwrap();
+        });
+        assert_eq!(block_on(receiver), Ok("done"));
+    }
+}
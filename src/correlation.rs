@@ -0,0 +1,108 @@
+use std::{
+    cell::RefCell,
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+thread_local! {
+    // The id of the future being polled on this thread.
+    static CURRENT: RefCell<Option<Arc<str>>> = const { RefCell::new(None) };
+}
+
+/// A future whose portal requests carry a correlation id, see
+/// [`with_correlation_id`].
+pub struct Correlated<F> {
+    id: Arc<str>,
+    future: Pin<Box<F>>,
+}
+
+impl<F> Correlated<F> {
+    /// The correlation id.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl<F: Future> Future for Correlated<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Restores the id of the enclosing future, even if `future` panics.
+        struct Restore(Option<Arc<str>>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                let previous = self.0.take();
+                CURRENT.with(|current| *current.borrow_mut() = previous);
+            }
+        }
+
+        let id = self.id.clone();
+        let _restore = Restore(CURRENT.with(|current| current.borrow_mut().replace(id)));
+        self.future.as_mut().poll(cx)
+    }
+}
+
+impl<F> Debug for Correlated<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Correlated").field(&self.id).finish()
+    }
+}
+
+/// Attach the opaque `id` to the portal requests made by `future`.
+///
+/// The id is kept by each request, it is part of the `tracing` span of the
+/// request with the `log` feature and of the [`Error::Call`](crate::Error::Call)
+/// errors, see [`Error::correlation_id`](crate::Error::correlation_id). It
+/// lets the applications running many portal operations at once match the
+/// failures with their own state.
+///
+/// The id applies to the requests made while `future` is polled, not to the
+/// tasks it spawns. An inner id replaces the outer one.
+///
+/// ```rust,no_run
+/// use ashpd::{desktop::screenshot, with_correlation_id, WindowIdentifier};
+///
+/// async fn run(job: u64) {
+///     let identifier = WindowIdentifier::default();
+///     let take = screenshot::take(&identifier, true, false);
+///     if let Err(err) = with_correlation_id(format!("job-{}", job), take).await {
+///         eprintln!("{:?} failed: {}", err.correlation_id(), err);
+///     }
+/// }
+/// ```
+pub fn with_correlation_id<F: Future>(id: impl Into<String>, future: F) -> Correlated<F> {
+    Correlated {
+        id: Arc::from(id.into()),
+        future: Box::pin(future),
+    }
+}
+
+// The id of the future being polled, if any.
+pub(crate) fn current() -> Option<Arc<str>> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+#[cfg(test)]
+mod test {
+    use futures::executor::block_on;
+
+    use super::{current, with_correlation_id};
+
+    #[test]
+    fn nested() {
+        assert!(current().is_none());
+        let ids = block_on(with_correlation_id("outer", async {
+            let outer = current();
+            let inner = with_correlation_id("inner", async { current() }).await;
+            (outer, inner, current())
+        }));
+        assert_eq!(ids.0.as_deref(), Some("outer"));
+        assert_eq!(ids.1.as_deref(), Some("inner"));
+        assert_eq!(ids.2.as_deref(), Some("outer"));
+        assert!(current().is_none());
+    }
+}
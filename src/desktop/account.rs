@@ -43,10 +43,13 @@
 
 use zbus::zvariant::{DeserializeDict, SerializeDict, Type};
 
-use super::{HandleToken, DESTINATION, PATH};
-use crate::{helpers::call_request_method, Error, WindowIdentifier};
+use super::{HandleToken, PortalRequest, DESTINATION, INTERFACE_ACCOUNT, PATH};
+use crate::{
+    helpers::{call_window_request_method, session_connection},
+    Error, WindowIdentifier,
+};
 
-#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, Default)]
+#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, Default, PartialEq, Eq)]
 /// Specified options for a [`AccountProxy::user_information`] request.
 #[zvariant(signature = "dict")]
 struct UserInfoOptions {
@@ -57,14 +60,14 @@ struct UserInfoOptions {
 }
 
 impl UserInfoOptions {
-    /// Sets a user-visible reason for the request.
-    pub fn reason(mut self, reason: &str) -> Self {
-        self.reason = Some(reason.to_string());
-        self
+    options_setters! {
+        @setters
+        /// Sets a user-visible reason for the request.
+        with_reason(reason: impl Into<String>);
     }
 }
 
-#[derive(Debug, SerializeDict, DeserializeDict, Clone, Type)]
+#[derive(Debug, SerializeDict, DeserializeDict, Clone, Type, PartialEq, Eq)]
 /// The response of a [`AccountProxy::user_information`] request.
 #[zvariant(signature = "dict")]
 pub struct UserInfo {
@@ -100,20 +103,23 @@ impl UserInfo {
 /// any) information to share.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Account`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Account).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.Account")]
-pub struct AccountProxy<'a>(zbus::Proxy<'a>);
+pub struct AccountProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(AccountProxy, INTERFACE_ACCOUNT);
+impl_proxy_timeout!(AccountProxy);
 
 impl<'a> AccountProxy<'a> {
     /// Create a new instance of [`AccountProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<AccountProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Account")?
+            .interface(INTERFACE_ACCOUNT)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -128,19 +134,20 @@ impl<'a> AccountProxy<'a> {
     /// * `identifier` - Identifier for the window.
     /// * `reason` - A user-visible reason for the request.
     #[doc(alias = "GetUserInformation")]
-    pub async fn user_information(
-        &self,
-        identifier: &WindowIdentifier,
+    pub fn user_information<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
         reason: &str,
-    ) -> Result<UserInfo, Error> {
-        let options = UserInfoOptions::default().reason(reason);
-        call_request_method(
-            self.inner(),
-            &options.handle_token,
+    ) -> PortalRequest<'p, UserInfo> {
+        let options = UserInfoOptions::default().with_reason(reason);
+        let handle_token = options.handle_token.copy();
+        call_window_request_method(
+            self,
+            identifier,
+            &handle_token,
             "GetUserInformation",
-            &(&identifier, &options),
+            (identifier, options),
         )
-        .await
     }
 }
 
@@ -151,7 +158,22 @@ pub async fn user_information(
     identifier: &WindowIdentifier,
     reason: &str,
 ) -> Result<UserInfo, Error> {
-    let connection = zbus::Connection::session().await?;
+    let connection = session_connection().await?;
     let proxy = AccountProxy::new(&connection).await?;
     proxy.user_information(identifier, reason).await
 }
+
+#[cfg(test)]
+mod test {
+    use super::UserInfoOptions;
+    use crate::{desktop::HandleToken, helpers::assert_serialized_dict};
+
+    #[test]
+    fn options() {
+        let options = UserInfoOptions {
+            handle_token: HandleToken::default(),
+            reason: Some("Fill the profile".to_owned()),
+        };
+        assert_serialized_dict(&options, &[("handle_token", "s"), ("reason", "s")]);
+    }
+}
@@ -50,10 +50,13 @@
 use serde::Serialize;
 use zbus::zvariant::{DeserializeDict, SerializeDict, Type};
 
-use super::{HandleToken, DESTINATION, PATH};
-use crate::{helpers::call_request_method, Error, WindowIdentifier};
+use super::{HandleToken, PortalRequest, DESTINATION, INTERFACE_BACKGROUND, PATH};
+use crate::{
+    helpers::{call_window_request_method, session_connection},
+    Error, WindowIdentifier,
+};
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Clone, Default)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Clone, Default, PartialEq, Eq)]
 /// Specified options for a [`BackgroundProxy::request_background`] request.
 #[zvariant(signature = "dict")]
 struct BackgroundOptions {
@@ -74,34 +77,26 @@ struct BackgroundOptions {
 }
 
 impl BackgroundOptions {
-    /// Sets a user-visible reason for the request.
-    pub fn reason(mut self, reason: &str) -> Self {
-        self.reason = Some(reason.to_string());
-        self
-    }
-
-    /// Sets whether to auto start the application or not.
-    pub fn autostart(mut self, autostart: bool) -> Self {
-        self.autostart = Some(autostart);
-        self
-    }
-
-    /// Sets whether the application is dbus activatable.
-    pub fn dbus_activatable(mut self, dbus_activatable: bool) -> Self {
-        self.dbus_activatable = Some(dbus_activatable);
-        self
+    options_setters! {
+        @setters
+        /// Sets a user-visible reason for the request.
+        with_reason(reason: impl Into<String>);
+        /// Sets whether to auto start the application or not.
+        with_autostart(autostart: bool);
+        /// Sets whether the application is dbus activatable.
+        with_dbus_activatable(dbus_activatable: bool);
     }
 
     /// Specifies the command line to execute.
     /// If this is not specified, the [`Exec`](https://specifications.freedesktop.org/desktop-entry-spec/desktop-entry-spec-latest.html#exec-variables) line from the [desktop
     /// file](https://specifications.freedesktop.org/desktop-entry-spec/desktop-entry-spec-latest.html#introduction)
-    pub fn command(mut self, command: Option<&[impl AsRef<str> + Type + Serialize]>) -> Self {
+    pub fn with_command(mut self, command: Option<&[impl AsRef<str> + Type + Serialize]>) -> Self {
         self.command = command.map(|s| s.iter().map(|s| s.as_ref().to_string()).collect());
         self
     }
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Clone, PartialEq, Eq)]
 /// The response of a [`BackgroundProxy::request_background`] request.
 #[zvariant(signature = "dict")]
 pub struct Background {
@@ -123,25 +118,194 @@ impl Background {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The autostart configuration of an application, to be requested with
+/// [`BackgroundProxy::request_autostart`].
+///
+/// The command line is validated before any request is made, as the portal
+/// backends silently write an autostart entry that fails to start the
+/// application if it is malformed.
+///
+/// ```rust
+/// use ashpd::desktop::background::Autostart;
+///
+/// let autostart = Autostart::gapplication_service("org.gnome.Geary", "geary").unwrap();
+/// assert_eq!(autostart.command_line(), ["geary", "--gapplication-service"]);
+/// assert!(autostart
+///     .desktop_entry()
+///     .contains("Exec=geary --gapplication-service\n"));
+/// ```
+pub struct Autostart {
+    app_id: String,
+    command_line: Vec<String>,
+    dbus_activatable: bool,
+}
+
+impl Autostart {
+    /// Auto-start `app_id` with `command_line`.
+    ///
+    /// # Arguments
+    ///
+    /// * `app_id` - The application ID, `org.gnome.Geary` for example.
+    /// * `command_line` - The program to start followed by its arguments.
+    pub fn new(app_id: impl Into<String>, command_line: &[impl AsRef<str>]) -> Result<Self, Error> {
+        let autostart = Self {
+            app_id: app_id.into(),
+            command_line: command_line
+                .iter()
+                .map(|arg| arg.as_ref().to_owned())
+                .collect(),
+            dbus_activatable: false,
+        };
+        autostart.validate()?;
+        Ok(autostart)
+    }
+
+    /// Auto-start `app_id`, a `GApplication`, as a service without any
+    /// window.
+    pub fn gapplication_service(
+        app_id: impl Into<String>,
+        program: impl AsRef<str>,
+    ) -> Result<Self, Error> {
+        Self::new(app_id, &[program.as_ref(), "--gapplication-service"])
+    }
+
+    /// Sets whether the application is D-Bus activatable, it is then started
+    /// by activating its well-known name instead of running the command line.
+    #[must_use]
+    pub fn dbus_activatable(mut self, dbus_activatable: bool) -> Self {
+        self.dbus_activatable = dbus_activatable;
+        self
+    }
+
+    /// The application ID.
+    pub fn app_id(&self) -> &str {
+        &self.app_id
+    }
+
+    /// The program to start followed by its arguments.
+    pub fn command_line(&self) -> &[String] {
+        &self.command_line
+    }
+
+    /// Whether the application is D-Bus activatable.
+    pub fn is_dbus_activatable(&self) -> bool {
+        self.dbus_activatable
+    }
+
+    /// The contents of the autostart desktop entry corresponding to the
+    /// configuration.
+    ///
+    /// Useful for debugging or for unsandboxed applications that have to write
+    /// the entry in `$XDG_CONFIG_HOME/autostart` themselves.
+    pub fn desktop_entry(&self) -> String {
+        let exec = self
+            .command_line
+            .iter()
+            .map(|arg| quote_exec_arg(arg))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut entry = format!(
+            "[Desktop Entry]\nType=Application\nName={}\nExec={}\nX-Flatpak={}\n",
+            self.app_id, exec, self.app_id
+        );
+        if self.dbus_activatable {
+            entry.push_str("DBusActivatable=true\n");
+        }
+        entry
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if !is_valid_app_id(&self.app_id) {
+            return Err(invalid_argument(format!(
+                "`{}` is not a valid application ID",
+                self.app_id
+            )));
+        }
+        match self.command_line.first() {
+            None => return Err(invalid_argument("The command line is empty".to_owned())),
+            Some(program) if program.trim().is_empty() => {
+                return Err(invalid_argument(
+                    "The command line doesn't start with a program".to_owned(),
+                ))
+            }
+            _ => (),
+        }
+        if let Some(arg) = self
+            .command_line
+            .iter()
+            .find(|arg| arg.contains(['\0', '\n', '\r']))
+        {
+            return Err(invalid_argument(format!(
+                "The command line argument {:?} contains a NUL byte or a line break",
+                arg
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn invalid_argument(message: String) -> Error {
+    Error::InvalidArgument(message)
+}
+
+// See https://docs.gtk.org/gio/type_func.Application.id_is_valid.html
+fn is_valid_app_id(app_id: &str) -> bool {
+    let elements = app_id.split('.').collect::<Vec<_>>();
+    app_id.len() <= 255
+        && elements.len() >= 2
+        && elements.iter().all(|element| {
+            !element.is_empty()
+                && !element.starts_with(|c: char| c.is_ascii_digit())
+                && element
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        })
+}
+
+// See https://specifications.freedesktop.org/desktop-entry-spec/desktop-entry-spec-latest.html#exec-variables
+fn quote_exec_arg(arg: &str) -> String {
+    const RESERVED: &[char] = &[
+        ' ', '\t', '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#', '(', ')', '`',
+    ];
+    let arg = arg.replace('%', "%%");
+    if !arg.is_empty() && !arg.contains(RESERVED) {
+        return arg;
+    }
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
 /// The interface lets sandboxed applications request that the application
 /// is allowed to run in the background or started automatically when the user
 /// logs in.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Background`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Background).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.Background")]
-pub struct BackgroundProxy<'a>(zbus::Proxy<'a>);
+pub struct BackgroundProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(BackgroundProxy, INTERFACE_BACKGROUND);
+impl_proxy_timeout!(BackgroundProxy);
 
 impl<'a> BackgroundProxy<'a> {
     /// Create a new instance of [`BackgroundProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<BackgroundProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Background")?
+            .interface(INTERFACE_BACKGROUND)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -166,26 +330,53 @@ impl<'a> BackgroundProxy<'a> {
     ///
     /// See also [`RequestBackground`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Background.RequestBackground).
     #[doc(alias = "RequestBackground")]
-    pub async fn request_background(
-        &self,
-        identifier: &WindowIdentifier,
+    pub fn request_background<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
         reason: &str,
         auto_start: bool,
         command_line: Option<&[impl AsRef<str> + Type + Serialize]>,
         dbus_activatable: bool,
-    ) -> Result<Background, Error> {
+    ) -> PortalRequest<'p, Background> {
         let options = BackgroundOptions::default()
-            .reason(reason)
-            .autostart(auto_start)
-            .dbus_activatable(dbus_activatable)
-            .command(command_line);
-        call_request_method(
-            self.inner(),
-            &options.handle_token,
+            .with_reason(reason)
+            .with_autostart(auto_start)
+            .with_dbus_activatable(dbus_activatable)
+            .with_command(command_line);
+        let handle_token = options.handle_token.copy();
+        call_window_request_method(
+            self,
+            identifier,
+            &handle_token,
             "RequestBackground",
-            &(&identifier, &options),
+            (identifier, options),
+        )
+    }
+
+    /// Requests that the application is allowed to run in the background and
+    /// to be started automatically at login, as described by `autostart`.
+    ///
+    /// # Arguments
+    ///
+    /// * `identifier` - Identifier for the application window.
+    /// * `reason` - Sets a user-visible reason for the request.
+    /// * `autostart` - The [`Autostart`] configuration.
+    pub fn request_autostart<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
+        reason: &str,
+        autostart: &Autostart,
+    ) -> PortalRequest<'p, Background> {
+        if let Err(err) = autostart.validate() {
+            return PortalRequest::invalid(err);
+        }
+        self.request_background(
+            identifier,
+            reason,
+            true,
+            Some(autostart.command_line()),
+            autostart.is_dbus_activatable(),
         )
-        .await
     }
 }
 
@@ -198,7 +389,7 @@ pub async fn request(
     command_line: Option<&[impl AsRef<str> + Type + Serialize]>,
     dbus_activatable: bool,
 ) -> Result<Background, Error> {
-    let connection = zbus::Connection::session().await?;
+    let connection = session_connection().await?;
     let proxy = BackgroundProxy::new(&connection).await?;
     proxy
         .request_background(
@@ -210,3 +401,30 @@ pub async fn request(
         )
         .await
 }
+
+#[cfg(test)]
+mod test {
+    use super::BackgroundOptions;
+    use crate::{desktop::HandleToken, helpers::assert_serialized_dict};
+
+    #[test]
+    fn options() {
+        let options = BackgroundOptions {
+            handle_token: HandleToken::default(),
+            reason: Some("Sync the mails".to_owned()),
+            autostart: Some(true),
+            dbus_activatable: Some(false),
+            command: Some(vec!["mail-sync".to_owned()]),
+        };
+        assert_serialized_dict(
+            &options,
+            &[
+                ("autostart", "b"),
+                ("commandline", "as"),
+                ("dbus-activatable", "b"),
+                ("handle_token", "s"),
+                ("reason", "s"),
+            ],
+        );
+    }
+}
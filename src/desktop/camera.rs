@@ -23,13 +23,16 @@ use std::{
 
 use zbus::zvariant::{DeserializeDict, OwnedFd, SerializeDict, Type, Value};
 
-use super::{HandleToken, DESTINATION, PATH};
+use super::{
+    permission_store::{self, PermissionStatus},
+    HandleToken, PortalRequest, DESTINATION, INTERFACE_CAMERA, PATH,
+};
 use crate::{
-    helpers::{call_basic_response_method, call_method},
+    helpers::{call_basic_response_method, call_method, get_property},
     Error,
 };
 
-#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, Default)]
+#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, Default, PartialEq, Eq)]
 /// Specified options for a [`CameraProxy::access_camera`] request.
 #[zvariant(signature = "dict")]
 struct CameraAccessOptions {
@@ -41,20 +44,23 @@ struct CameraAccessOptions {
 /// cams.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Camera`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Camera).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.Camera")]
-pub struct CameraProxy<'a>(zbus::Proxy<'a>);
+pub struct CameraProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(CameraProxy, INTERFACE_CAMERA);
+impl_proxy_timeout!(CameraProxy);
 
 impl<'a> CameraProxy<'a> {
     /// Create a new instance of [`CameraProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<CameraProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Camera")?
+            .interface(INTERFACE_CAMERA)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -69,15 +75,10 @@ impl<'a> CameraProxy<'a> {
     /// See also [`AccessCamera`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Camera.AccessCamera).
     #[doc(alias = "AccessCamera")]
     #[doc(alias = "xdp_portal_access_camera")]
-    pub async fn access_camera(&self) -> Result<(), Error> {
+    pub fn access_camera(&self) -> PortalRequest<'_, ()> {
         let options = CameraAccessOptions::default();
-        call_basic_response_method(
-            self.inner(),
-            &options.handle_token,
-            "AccessCamera",
-            &(&options),
-        )
-        .await
+        let handle_token = options.handle_token.copy();
+        call_basic_response_method(self, &handle_token, "AccessCamera", options)
     }
 
     /// Open a file descriptor to the PipeWire remote where the camera nodes are
@@ -96,7 +97,7 @@ impl<'a> CameraProxy<'a> {
         // `options` parameter doesn't seems to be used yet
         // see https://github.com/flatpak/xdg-desktop-portal/blob/master/src/camera.c#L178
         let options: HashMap<&str, Value<'_>> = HashMap::new();
-        let fd: OwnedFd = call_method(self.inner(), "OpenPipeWireRemote", &(options)).await?;
+        let fd: OwnedFd = call_method(self, "OpenPipeWireRemote", &(options)).await?;
         Ok(fd.into_raw_fd())
     }
 
@@ -108,13 +109,17 @@ impl<'a> CameraProxy<'a> {
     #[doc(alias = "IsCameraPresent")]
     #[doc(alias = "xdp_portal_is_camera_present")]
     pub async fn is_camera_present(&self) -> Result<bool, Error> {
-        self.inner()
-            .get_property::<bool>("IsCameraPresent")
-            .await
-            .map_err(From::from)
+        get_property::<bool>(self, "IsCameraPresent").await
     }
 }
 
+/// Whether [`CameraProxy::access_camera`] will prompt the user.
+///
+/// **Note** the permission store is not reachable from inside of the sandbox.
+pub async fn permission_status(app_id: &str) -> Result<PermissionStatus, Error> {
+    permission_store::permission_status("devices", "camera", app_id).await
+}
+
 /// A helper to get the PipeWire Node ID to use with the camera file descriptor returned by
 /// [`CameraProxy::open_pipe_wire_remote`].
 ///
@@ -168,9 +173,12 @@ fn pipewire_node_id_inner<F: FnOnce(u32) + Clone + 'static>(
     fd: RawFd,
     callback: F,
 ) -> Result<(), pw::Error> {
-    use pw::prelude::*;
-    let mainloop = pw::MainLoop::new()?;
-    let context = pw::Context::new(&mainloop)?;
+    use std::os::unix::io::FromRawFd;
+
+    // The core takes the ownership of the duplicated fd.
+    let fd = unsafe { std::os::unix::io::OwnedFd::from_raw_fd(fd) };
+    let mainloop = pw::main_loop::MainLoop::new(None)?;
+    let context = pw::context::Context::new(&mainloop)?;
     let core = context.connect_fd(fd, None)?;
     let registry = core.get_registry()?;
 
@@ -191,3 +199,17 @@ fn pipewire_node_id_inner<F: FnOnce(u32) + Clone + 'static>(
     mainloop.run();
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::CameraAccessOptions;
+    use crate::{desktop::HandleToken, helpers::assert_serialized_dict};
+
+    #[test]
+    fn options() {
+        let options = CameraAccessOptions {
+            handle_token: HandleToken::default(),
+        };
+        assert_serialized_dict(&options, &[("handle_token", "s")]);
+    }
+}
@@ -0,0 +1,109 @@
+use enumflags2::BitFlags;
+
+use super::{
+    remote_desktop::{DeviceType, SelectedDevices},
+    screencast::{CursorMode, Stream, Streams},
+};
+
+/// What a started screen cast or remote desktop session gives access to.
+///
+/// It combines the devices, streams and clipboard granted by the user in the
+/// response of `Start` with the cursor mode sent when selecting the sources,
+/// to decide which features to enable in one place.
+///
+/// ```rust,no_run
+/// use ashpd::desktop::{
+///     remote_desktop::{DeviceType, RemoteDesktopProxy},
+///     screencast::{CursorMode, ScreenCastProxy, SelectSourcesOptions},
+///     SessionCapabilities,
+/// };
+/// use ashpd::WindowIdentifier;
+///
+/// async fn run() -> ashpd::Result<()> {
+///     let connection = zbus::Connection::session().await?;
+///     let proxy = RemoteDesktopProxy::new(&connection).await?;
+///     let screencast = ScreenCastProxy::new(&connection).await?;
+///
+///     let session = proxy.create_session().await?;
+///     proxy.select_devices(&session, DeviceType::Keyboard | DeviceType::Pointer).await?;
+///     let options = SelectSourcesOptions::default().with_cursor_mode(CursorMode::Metadata);
+///     screencast.select_sources(&session, options).await?;
+///
+///     let selected = proxy.start(&session, &WindowIdentifier::default()).await?;
+///     let capabilities = SessionCapabilities::from(&selected)
+///         .with_cursor_mode(screencast.selected_cursor_mode(&session));
+///     if capabilities.cursor_mode() == Some(CursorMode::Metadata) {
+///         println!("Draw the cursor on top of the streams");
+///     }
+///     if !capabilities.is_granted(DeviceType::Keyboard) {
+///         println!("Hide the on-screen keyboard");
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SessionCapabilities {
+    devices: BitFlags<DeviceType>,
+    streams: Vec<Stream>,
+    cursor_mode: Option<CursorMode>,
+    clipboard_enabled: bool,
+}
+
+impl SessionCapabilities {
+    /// Set the cursor mode of the streams, see
+    /// [`ScreenCastProxy::selected_cursor_mode`](super::screencast::ScreenCastProxy::selected_cursor_mode).
+    #[must_use]
+    pub fn with_cursor_mode(mut self, cursor_mode: Option<CursorMode>) -> Self {
+        self.cursor_mode = cursor_mode;
+        self
+    }
+
+    /// The devices the user granted access to, empty for a screen cast
+    /// session.
+    pub fn devices(&self) -> BitFlags<DeviceType> {
+        self.devices
+    }
+
+    /// Whether the user granted access to `device`.
+    pub fn is_granted(&self, device: DeviceType) -> bool {
+        self.devices.contains(device)
+    }
+
+    /// The started streams, empty if no sources were selected.
+    pub fn streams(&self) -> &[Stream] {
+        &self.streams
+    }
+
+    /// The cursor mode of the streams, None if no mode was requested or if
+    /// it isn't known.
+    pub fn cursor_mode(&self) -> Option<CursorMode> {
+        self.cursor_mode
+    }
+
+    /// Whether the clipboard is shared with the session, see
+    /// [`ClipboardProxy`](super::clipboard::ClipboardProxy).
+    pub fn clipboard_enabled(&self) -> bool {
+        self.clipboard_enabled
+    }
+}
+
+impl From<&Streams> for SessionCapabilities {
+    fn from(streams: &Streams) -> Self {
+        Self {
+            streams: streams.streams().to_vec(),
+            cursor_mode: streams.cursor_mode(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&SelectedDevices> for SessionCapabilities {
+    fn from(selected: &SelectedDevices) -> Self {
+        Self {
+            devices: selected.devices(),
+            streams: selected.streams().to_vec(),
+            cursor_mode: None,
+            clipboard_enabled: selected.clipboard_enabled(),
+        }
+    }
+}
@@ -0,0 +1,441 @@
+//! # Examples
+//!
+//! Share the clipboard of a remote desktop session, the clipboard has to be
+//! requested before the session is started.
+//!
+//! ```rust,no_run
+//! use ashpd::desktop::{
+//!     clipboard::{ClipboardProxy, TEXT_MIME_TYPES},
+//!     remote_desktop::{DeviceType, RemoteDesktopProxy},
+//! };
+//! use ashpd::WindowIdentifier;
+//! use futures::{AsyncReadExt, StreamExt};
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::Connection::session().await?;
+//!     let proxy = RemoteDesktopProxy::new(&connection).await?;
+//!     let clipboard = ClipboardProxy::new(&connection).await?;
+//!
+//!     let mut transfers = clipboard.receive_selection_transfer().await?;
+//!     let mut owner_changes = clipboard.receive_selection_owner_changed().await?;
+//!
+//!     let session = proxy.create_session().await?;
+//!     proxy.select_devices(&session, DeviceType::Keyboard).await?;
+//!     clipboard.request_clipboard(&session).await?;
+//!     proxy.start(&session, &WindowIdentifier::default()).await?;
+//!
+//!     // Offer some text to the host.
+//!     clipboard.set_selection(&session, TEXT_MIME_TYPES).await?;
+//!     if let Some(transfer) = transfers.next().await {
+//!         clipboard
+//!             .write_selection(&session, transfer.serial(), &b"Hello from the client"[..])
+//!             .await?;
+//!     }
+//!
+//!     // Read the text copied on the host.
+//!     let changed = owner_changes.next().await;
+//!     if let Some(mime_type) = changed.as_ref().and_then(|c| c.negotiate(TEXT_MIME_TYPES)) {
+//!         let mut text = String::new();
+//!         clipboard
+//!             .selection_read(&session, mime_type)
+//!             .await?
+//!             .read_to_string(&mut text)
+//!             .await
+//!             .map_err(zbus::Error::from)?;
+//!         println!("{}", text);
+//!     }
+//!
+//!     session.close().await?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::{
+    fmt::Debug,
+    fs::File,
+    io,
+    os::unix::prelude::{FromRawFd, IntoRawFd},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use async_io::Async;
+use futures::{AsyncRead, AsyncWrite, Stream};
+use serde::Deserialize;
+use zbus::zvariant::{DeserializeDict, OwnedFd, OwnedObjectPath, SerializeDict, Type};
+
+use super::{remote_desktop::RemoteDesktopSession, DESTINATION, INTERFACE_CLIPBOARD, PATH};
+use crate::{
+    helpers::{call_method, receive_signal_stream},
+    Error,
+};
+
+/// The MIME types of plain text, from the most to the least preferred one.
+pub const TEXT_MIME_TYPES: &[&str] = &[
+    "text/plain;charset=utf-8",
+    "text/plain",
+    "UTF8_STRING",
+    "STRING",
+    "TEXT",
+];
+
+/// The first of the `accepted` MIME types that is part of `offered`, the
+/// comparison ignores the case and the spaces around the parameters.
+///
+/// ```rust
+/// use ashpd::desktop::clipboard::{negotiate_mime_type, TEXT_MIME_TYPES};
+///
+/// let offered = ["image/png".to_owned(), "text/plain; charset=UTF-8".to_owned()];
+/// assert_eq!(
+///     negotiate_mime_type(&offered, TEXT_MIME_TYPES),
+///     Some("text/plain; charset=UTF-8")
+/// );
+/// assert_eq!(negotiate_mime_type(&offered, &["text/html"]), None);
+/// ```
+pub fn negotiate_mime_type<'a>(offered: &'a [String], accepted: &[&str]) -> Option<&'a str> {
+    accepted.iter().find_map(|accepted| {
+        offered
+            .iter()
+            .find(|offered| same_mime_type(offered, accepted))
+            .map(String::as_str)
+    })
+}
+
+fn same_mime_type(a: &str, b: &str) -> bool {
+    let mut a = a.split(';').map(str::trim);
+    let mut b = b.split(';').map(str::trim);
+    loop {
+        match (a.next(), b.next()) {
+            (Some(a), Some(b)) if a.eq_ignore_ascii_case(b) => (),
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
+#[zvariant(signature = "dict")]
+struct RequestClipboardOptions {}
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
+#[zvariant(signature = "dict")]
+struct SetSelectionOptions {
+    mime_types: Option<Vec<String>>,
+}
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
+#[zvariant(signature = "dict")]
+struct SelectionOwnerChangedOptions {
+    mime_types: Option<Vec<String>>,
+    session_is_owner: Option<bool>,
+}
+
+#[derive(Deserialize, Type)]
+/// The selection was set by a new owner, see
+/// [`ClipboardProxy::receive_selection_owner_changed`].
+pub struct SelectionOwnerChanged(OwnedObjectPath, SelectionOwnerChangedOptions);
+
+impl SelectionOwnerChanged {
+    /// The handle of the session the selection changed on.
+    pub fn session_handle(&self) -> &OwnedObjectPath {
+        &self.0
+    }
+
+    /// The MIME types the selection is offered as.
+    pub fn mime_types(&self) -> &[String] {
+        self.1.mime_types.as_deref().unwrap_or_default()
+    }
+
+    /// Whether the session itself owns the selection.
+    pub fn session_is_owner(&self) -> bool {
+        self.1.session_is_owner.unwrap_or(false)
+    }
+
+    /// The first of the `accepted` MIME types the selection is offered as, see
+    /// [`negotiate_mime_type`].
+    pub fn negotiate(&self, accepted: &[&str]) -> Option<&str> {
+        negotiate_mime_type(self.mime_types(), accepted)
+    }
+}
+
+impl Debug for SelectionOwnerChanged {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SelectionOwnerChanged")
+            .field("session_handle", &self.session_handle().as_str())
+            .field("mime_types", &self.mime_types())
+            .field("session_is_owner", &self.session_is_owner())
+            .finish()
+    }
+}
+
+#[derive(Deserialize, Type)]
+/// The content of the selection set by the session is requested, see
+/// [`ClipboardProxy::receive_selection_transfer`].
+pub struct SelectionTransfer(OwnedObjectPath, String, u32);
+
+impl SelectionTransfer {
+    /// The handle of the session the selection is requested from.
+    pub fn session_handle(&self) -> &OwnedObjectPath {
+        &self.0
+    }
+
+    /// The requested MIME type.
+    pub fn mime_type(&self) -> &str {
+        &self.1
+    }
+
+    /// The serial to pass to [`ClipboardProxy::selection_write`].
+    pub fn serial(&self) -> u32 {
+        self.2
+    }
+}
+
+impl Debug for SelectionTransfer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SelectionTransfer")
+            .field("session_handle", &self.session_handle().as_str())
+            .field("mime_type", &self.mime_type())
+            .field("serial", &self.serial())
+            .finish()
+    }
+}
+
+fn into_async_file(fd: OwnedFd) -> Result<Async<File>, Error> {
+    // SAFETY: the file descriptor is owned and not used anywhere else.
+    let file = unsafe { File::from_raw_fd(fd.into_raw_fd()) };
+    Ok(Async::new(file).map_err(zbus::Error::from)?)
+}
+
+#[derive(Debug)]
+/// Reads the content of the selection, returned by
+/// [`ClipboardProxy::selection_read`].
+pub struct SelectionReader(Async<File>);
+
+impl AsyncRead for SelectionReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+#[derive(Debug)]
+/// Writes the content of the selection, returned by
+/// [`ClipboardProxy::selection_write`].
+///
+/// Close it then call [`ClipboardProxy::selection_write_done`] once all the
+/// content is written.
+pub struct SelectionWriter(Async<File>);
+
+impl AsyncWrite for SelectionWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_close(cx)
+    }
+}
+
+/// The interface allows a remote desktop session to access the clipboard of
+/// the host and to offer its own.
+///
+/// Wrapper of the DBus interface: [`org.freedesktop.portal.Clipboard`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Clipboard).
+#[derive(Clone)]
+#[doc(alias = "org.freedesktop.portal.Clipboard")]
+pub struct ClipboardProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(ClipboardProxy, INTERFACE_CLIPBOARD);
+impl_proxy_timeout!(ClipboardProxy);
+
+impl<'a> ClipboardProxy<'a> {
+    /// Create a new instance of [`ClipboardProxy`].
+    pub async fn new(connection: &zbus::Connection) -> Result<ClipboardProxy<'a>, Error> {
+        let proxy = zbus::ProxyBuilder::new_bare(connection)
+            .interface(INTERFACE_CLIPBOARD)?
+            .path(PATH)?
+            .destination(DESTINATION)?
+            .build()
+            .await?;
+        Ok(Self(proxy, None))
+    }
+
+    /// Get a reference to the underlying Proxy.
+    pub fn inner(&self) -> &zbus::Proxy<'_> {
+        &self.0
+    }
+
+    /// Request access to the clipboard, it has to be called before the
+    /// session is started.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`RequestClipboard`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Clipboard.RequestClipboard).
+    #[doc(alias = "RequestClipboard")]
+    pub async fn request_clipboard(&self, session: &RemoteDesktopSession<'_>) -> Result<(), Error> {
+        let options = RequestClipboardOptions::default();
+        call_method(self, "RequestClipboard", &(session, &options)).await
+    }
+
+    /// Offer a selection to the host, as the given MIME types.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`SetSelection`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Clipboard.SetSelection).
+    #[doc(alias = "SetSelection")]
+    pub async fn set_selection(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        mime_types: &[&str],
+    ) -> Result<(), Error> {
+        let options = SetSelectionOptions {
+            mime_types: Some(mime_types.iter().map(|m| m.to_string()).collect()),
+        };
+        call_method(self, "SetSelection", &(session, &options)).await
+    }
+
+    /// Answer a [`SelectionTransfer`], returning where to write the content of
+    /// the selection.
+    ///
+    /// See also [`ClipboardProxy::write_selection`].
+    ///
+    /// # Specifications
+    ///
+    /// See also [`SelectionWrite`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Clipboard.SelectionWrite).
+    #[doc(alias = "SelectionWrite")]
+    pub async fn selection_write(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        serial: u32,
+    ) -> Result<SelectionWriter, Error> {
+        let fd: OwnedFd = call_method(self, "SelectionWrite", &(session, serial)).await?;
+        into_async_file(fd).map(SelectionWriter)
+    }
+
+    /// Notify the host whether the content of the selection was written
+    /// successfully.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`SelectionWriteDone`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Clipboard.SelectionWriteDone).
+    #[doc(alias = "SelectionWriteDone")]
+    pub async fn selection_write_done(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        serial: u32,
+        success: bool,
+    ) -> Result<(), Error> {
+        call_method(self, "SelectionWriteDone", &(session, serial, success)).await
+    }
+
+    /// Answer a [`SelectionTransfer`] by streaming `content`, notifying the
+    /// host of the outcome. Returns the number of written bytes.
+    pub async fn write_selection(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        serial: u32,
+        content: impl AsyncRead,
+    ) -> Result<u64, Error> {
+        let mut writer = self.selection_write(session, serial).await?;
+        let written = match futures::io::copy(content, &mut writer).await {
+            Ok(written) => futures::AsyncWriteExt::close(&mut writer)
+                .await
+                .map(|_| written),
+            Err(err) => Err(err),
+        };
+        drop(writer);
+        self.selection_write_done(session, serial, written.is_ok())
+            .await?;
+        Ok(written.map_err(zbus::Error::from)?)
+    }
+
+    /// Read the content of the selection of the host as `mime_type`.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`SelectionRead`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Clipboard.SelectionRead).
+    #[doc(alias = "SelectionRead")]
+    pub async fn selection_read(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        mime_type: &str,
+    ) -> Result<SelectionReader, Error> {
+        let fd: OwnedFd = call_method(self, "SelectionRead", &(session, mime_type)).await?;
+        into_async_file(fd).map(SelectionReader)
+    }
+
+    /// Signal emitted when the selection of a session is set by a new owner.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`SelectionOwnerChanged`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-Clipboard.SelectionOwnerChanged).
+    #[doc(alias = "SelectionOwnerChanged")]
+    pub async fn receive_selection_owner_changed(
+        &self,
+    ) -> Result<impl Stream<Item = SelectionOwnerChanged> + Unpin + 'a, Error> {
+        receive_signal_stream(&self.0, "SelectionOwnerChanged").await
+    }
+
+    /// Signal emitted when the host requests the selection set by the session.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`SelectionTransfer`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-Clipboard.SelectionTransfer).
+    #[doc(alias = "SelectionTransfer")]
+    pub async fn receive_selection_transfer(
+        &self,
+    ) -> Result<impl Stream<Item = SelectionTransfer> + Unpin + 'a, Error> {
+        receive_signal_stream(&self.0, "SelectionTransfer").await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        negotiate_mime_type, SelectionOwnerChangedOptions, SetSelectionOptions, TEXT_MIME_TYPES,
+    };
+    use crate::helpers::assert_serialized_dict;
+
+    #[test]
+    fn options() {
+        let options = SetSelectionOptions {
+            mime_types: Some(vec!["text/plain".to_owned()]),
+        };
+        assert_serialized_dict(&options, &[("mime_types", "as")]);
+
+        let options = SelectionOwnerChangedOptions {
+            mime_types: Some(vec!["text/plain".to_owned()]),
+            session_is_owner: Some(false),
+        };
+        assert_serialized_dict(&options, &[("mime_types", "as"), ("session_is_owner", "b")]);
+    }
+
+    #[test]
+    fn negotiate() {
+        let offered = vec![
+            "text/html".to_owned(),
+            "UTF8_STRING".to_owned(),
+            "TEXT/PLAIN ; Charset=UTF-8".to_owned(),
+        ];
+        assert_eq!(
+            negotiate_mime_type(&offered, TEXT_MIME_TYPES),
+            Some("TEXT/PLAIN ; Charset=UTF-8")
+        );
+        assert_eq!(
+            negotiate_mime_type(&offered, &["text/html", "text/plain"]),
+            Some("text/html")
+        );
+        assert_eq!(negotiate_mime_type(&offered, &["text/plain"]), None);
+        assert_eq!(negotiate_mime_type(&[], TEXT_MIME_TYPES), None);
+    }
+}
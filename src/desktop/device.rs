@@ -18,10 +18,10 @@ use serde::{Deserialize, Serialize, Serializer};
 use std::{fmt, str::FromStr};
 use zbus::zvariant::{DeserializeDict, SerializeDict, Signature, Type};
 
-use super::{HandleToken, DESTINATION, PATH};
+use super::{HandleToken, PortalRequest, DESTINATION, INTERFACE_DEVICE, PATH};
 use crate::{helpers::call_basic_response_method, Error};
 
-#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, Default)]
+#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, Default, PartialEq, Eq)]
 /// Specified options for a [`DeviceProxy::access_device`] request.
 #[zvariant(signature = "dict")]
 struct AccessDeviceOptions {
@@ -106,20 +106,23 @@ impl Type for Device {
 /// sandbox.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Device`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Device).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.Device")]
-pub struct DeviceProxy<'a>(zbus::Proxy<'a>);
+pub struct DeviceProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(DeviceProxy, INTERFACE_DEVICE);
+impl_proxy_timeout!(DeviceProxy);
 
 impl<'a> DeviceProxy<'a> {
     /// Create a new instance of [`DeviceProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<DeviceProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Device")?
+            .interface(INTERFACE_DEVICE)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -141,14 +144,23 @@ impl<'a> DeviceProxy<'a> {
     ///
     /// See also [`AccessDevice`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Device.AccessDevice).
     #[doc(alias = "AccessDevice")]
-    pub async fn access_device(&self, pid: u32, devices: &[Device]) -> Result<(), Error> {
+    pub fn access_device<'p>(&'p self, pid: u32, devices: &'p [Device]) -> PortalRequest<'p, ()> {
         let options = AccessDeviceOptions::default();
-        call_basic_response_method(
-            self.inner(),
-            &options.handle_token,
-            "AccessDevice",
-            &(pid, devices, &options),
-        )
-        .await
+        let handle_token = options.handle_token.copy();
+        call_basic_response_method(self, &handle_token, "AccessDevice", (pid, devices, options))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AccessDeviceOptions;
+    use crate::{desktop::HandleToken, helpers::assert_serialized_dict};
+
+    #[test]
+    fn options() {
+        let options = AccessDeviceOptions {
+            handle_token: HandleToken::default(),
+        };
+        assert_serialized_dict(&options, &[("handle_token", "s")]);
     }
 }
@@ -0,0 +1,612 @@
+//! # Examples
+//!
+//! Install a launcher for a web application, the user can change its name and
+//! icon in the dialog of the portal.
+//!
+//! ```rust,no_run
+//! use ashpd::desktop::dynamic_launcher::{
+//!     DynamicLauncherProxy, LauncherIcon, LauncherType, PrepareInstallOptions,
+//! };
+//! use ashpd::WindowIdentifier;
+//!
+//! async fn run(png: Vec<u8>) -> ashpd::Result<()> {
+//!     let connection = zbus::Connection::session().await?;
+//!     let proxy = DynamicLauncherProxy::new(&connection).await?;
+//!
+//!     let icon = LauncherIcon::from_bytes(png)?;
+//!     let options = PrepareInstallOptions::default()
+//!         .with_launcher_type(LauncherType::WebApplication)
+//!         .with_target("https://example.org")
+//!         .with_editable_name(true);
+//!     let prepared = proxy
+//!         .prepare_install(&WindowIdentifier::default(), "Example", &icon, options)
+//!         .await?;
+//!
+//!     let desktop_entry = format!(
+//!         "[Desktop Entry]\nType=Application\nName={}\nExec=browser https://example.org",
+//!         prepared.name()
+//!     );
+//!     proxy
+//!         .install(prepared.token(), "org.example.App.Example.desktop", &desktop_entry)
+//!         .await?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::{collections::HashMap, fmt};
+
+use enumflags2::{bitflags, BitFlags};
+use zbus::zvariant::{DeserializeDict, OwnedValue, SerializeDict, Type, Value};
+
+use super::{HandleToken, PortalRequest, DESTINATION, INTERFACE_DYNAMIC_LAUNCHER, PATH};
+use crate::{
+    helpers::call_window_request_method, raw_options::RawOptions, ActivationToken, Error,
+    WindowIdentifier,
+};
+
+/// The largest width and height of the icons accepted by the portal.
+pub const MAX_ICON_SIZE: u32 = 512;
+
+#[bitflags]
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Type)]
+#[repr(u32)]
+#[non_exhaustive]
+/// The kinds of launchers, see
+/// [`DynamicLauncherProxy::supported_launcher_types`].
+///
+/// Converts from and to the `u32` sent on the bus.
+pub enum LauncherType {
+    /// A launcher of a regular application.
+    Application = 1,
+    /// A launcher of a web application, a website running in a browser.
+    WebApplication = 2,
+}
+
+impl_u32_conversions!(LauncherType {
+    Application = 1,
+    WebApplication = 2,
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The image formats of the icons accepted by the portal.
+pub enum IconFormat {
+    /// A PNG image.
+    Png,
+    /// A JPEG image.
+    Jpeg,
+    /// An SVG image.
+    Svg,
+}
+
+impl_str_conversions!(IconFormat {
+    Png = "png",
+    Jpeg = "jpeg",
+    Svg = "svg",
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Why an icon can't be used for a launcher, see [`LauncherIcon::from_bytes`].
+pub enum IconError {
+    /// The image isn't a PNG, JPEG or SVG one.
+    UnsupportedFormat,
+    /// The header of the image giving its size is missing or damaged.
+    Malformed(IconFormat),
+    /// The image isn't a square.
+    NotSquare {
+        /// The width of the image.
+        width: u32,
+        /// The height of the image.
+        height: u32,
+    },
+    /// The image is larger than [`MAX_ICON_SIZE`].
+    TooLarge {
+        /// The width and height of the image.
+        size: u32,
+    },
+}
+
+impl fmt::Display for IconError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedFormat => f.write_str("The icon isn't a PNG, JPEG or SVG image"),
+            Self::Malformed(format) => write!(f, "The icon isn't a valid {} image", format),
+            Self::NotSquare { width, height } => {
+                write!(f, "The icon is {}x{}, it should be a square", width, height)
+            }
+            Self::TooLarge { size } => write!(
+                f,
+                "The icon is {0}x{0}, larger than {1}x{1}",
+                size, MAX_ICON_SIZE
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IconError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The icon of a launcher, a square PNG or JPEG image of at most
+/// [`MAX_ICON_SIZE`] pixels, or an SVG one.
+///
+/// The portal backends reject the other icons with a generic error, the checks
+/// are done before sending them so the application can tell why.
+pub struct LauncherIcon {
+    bytes: Vec<u8>,
+    format: IconFormat,
+    size: Option<u32>,
+}
+
+impl LauncherIcon {
+    /// Check that `bytes` is the content of a PNG, JPEG or SVG image the
+    /// portal accepts.
+    ///
+    /// Only the headers of the raster images are read, to find their size.
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> Result<Self, IconError> {
+        let bytes = bytes.into();
+        let (format, size) = match image_size(&bytes)? {
+            (_, Some((width, height))) if width != height => {
+                return Err(IconError::NotSquare { width, height })
+            }
+            (_, Some((size, _))) if size > MAX_ICON_SIZE => {
+                return Err(IconError::TooLarge { size })
+            }
+            (format, size) => (format, size.map(|(size, _)| size)),
+        };
+        Ok(Self {
+            bytes,
+            format,
+            size,
+        })
+    }
+
+    /// Like [`LauncherIcon::from_bytes`], downscaling the square PNG and JPEG
+    /// images larger than [`MAX_ICON_SIZE`] to a PNG image of that size.
+    #[cfg(feature = "icon-resize")]
+    pub fn from_bytes_downscaled(bytes: impl Into<Vec<u8>>) -> Result<Self, IconError> {
+        let bytes = bytes.into();
+        let format = match Self::from_bytes(bytes.as_slice()) {
+            Err(IconError::TooLarge { .. }) => image_size(&bytes)?.0,
+            result => return result,
+        };
+        let image = image::load_from_memory_with_format(
+            &bytes,
+            match format {
+                IconFormat::Png => image::ImageFormat::Png,
+                _ => image::ImageFormat::Jpeg,
+            },
+        )
+        .map_err(|_| IconError::Malformed(format))?;
+        let mut png = std::io::Cursor::new(Vec::new());
+        image
+            .resize_exact(
+                MAX_ICON_SIZE,
+                MAX_ICON_SIZE,
+                image::imageops::FilterType::Lanczos3,
+            )
+            .write_to(&mut png, image::ImageOutputFormat::Png)
+            .map_err(|_| IconError::Malformed(format))?;
+        Self::from_bytes(png.into_inner())
+    }
+
+    /// The content of the image.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The format of the image.
+    pub fn format(&self) -> IconFormat {
+        self.format
+    }
+
+    /// The width and height of the image, `None` for an SVG image.
+    pub fn size(&self) -> Option<u32> {
+        self.size
+    }
+
+    // The icon serialized like `g_icon_serialize()` does for a `GBytesIcon`,
+    // the only kind the portal accepts.
+    fn serialize(&self) -> OwnedValue {
+        Value::from(("bytes", Value::from(self.bytes.clone()))).into()
+    }
+}
+
+// The format of `bytes` and the width and height of the raster images.
+fn image_size(bytes: &[u8]) -> Result<(IconFormat, Option<(u32, u32)>), IconError> {
+    const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    if let Some(png) = bytes.strip_prefix(PNG_SIGNATURE) {
+        // The first chunk is the header, starting with the width and height.
+        return match png.get(4..16) {
+            Some(header) if &header[..4] == b"IHDR" => {
+                let width = u32::from_be_bytes(header[4..8].try_into().unwrap());
+                let height = u32::from_be_bytes(header[8..12].try_into().unwrap());
+                Ok((IconFormat::Png, Some((width, height))))
+            }
+            _ => Err(IconError::Malformed(IconFormat::Png)),
+        };
+    }
+
+    if let Some(mut jpeg) = bytes.strip_prefix(b"\xff\xd8") {
+        // Walk the segments until a start of frame one, giving the height and
+        // width. The markers without a length have no payload.
+        while let [0xff, marker, rest @ ..] = jpeg {
+            match marker {
+                0xff => jpeg = &jpeg[1..],
+                0x01 | 0xd0..=0xd7 => jpeg = rest,
+                _ => {
+                    let len = match rest {
+                        [high, low, ..] => u16::from_be_bytes([*high, *low]) as usize,
+                        _ => break,
+                    };
+                    let is_frame =
+                        matches!(marker, 0xc0..=0xcf) && !matches!(marker, 0xc4 | 0xc8 | 0xcc);
+                    if is_frame {
+                        return match rest.get(3..7) {
+                            Some(size) => {
+                                let height = u16::from_be_bytes([size[0], size[1]]) as u32;
+                                let width = u16::from_be_bytes([size[2], size[3]]) as u32;
+                                Ok((IconFormat::Jpeg, Some((width, height))))
+                            }
+                            None => Err(IconError::Malformed(IconFormat::Jpeg)),
+                        };
+                    }
+                    match rest.get(len..) {
+                        Some(next) if len >= 2 => jpeg = next,
+                        _ => break,
+                    }
+                }
+            }
+        }
+        return Err(IconError::Malformed(IconFormat::Jpeg));
+    }
+
+    let text = std::str::from_utf8(bytes).map_err(|_| IconError::UnsupportedFormat)?;
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    if text.starts_with('<') && text.contains("<svg") {
+        Ok((IconFormat::Svg, None))
+    } else {
+        Err(IconError::UnsupportedFormat)
+    }
+}
+
+#[derive(Type, Clone, Debug, Default, PartialEq)]
+/// Specified options for a [`DynamicLauncherProxy::prepare_install`] request.
+#[zvariant(signature = "dict")]
+pub struct PrepareInstallOptions {
+    /// A string that will be used as the last element of the handle.
+    handle_token: HandleToken,
+    /// Whether the dialog should be modal.
+    modal: Option<bool>,
+    /// The kind of launcher.
+    launcher_type: Option<LauncherType>,
+    /// The URL of a web application.
+    target: Option<String>,
+    /// Whether the user can change the name.
+    editable_name: Option<bool>,
+    /// Whether the user can change the icon.
+    editable_icon: Option<bool>,
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(PrepareInstallOptions {
+    handle_token = "handle_token",
+    modal = "modal",
+    launcher_type = "launcher_type",
+    target = "target",
+    editable_name = "editable_name",
+    editable_icon = "editable_icon",
+});
+
+impl PrepareInstallOptions {
+    options_setters! {
+        /// Sets whether the dialog should be a modal, defaults to `true`.
+        with_modal(modal: bool);
+        /// Sets the kind of launcher, defaults to
+        /// [`LauncherType::Application`].
+        with_launcher_type(launcher_type: LauncherType);
+        /// Sets the URL of a web application, shown to the user.
+        with_target(target: impl Into<String>);
+        /// Sets whether the user can change the name, defaults to `false`.
+        with_editable_name(editable_name: bool);
+        /// Sets whether the user can change the icon, defaults to `false`.
+        with_editable_icon(editable_icon: bool);
+    }
+}
+
+#[derive(Debug, DeserializeDict, Type)]
+/// The response of a [`DynamicLauncherProxy::prepare_install`] request.
+#[zvariant(signature = "dict")]
+pub struct PreparedInstall {
+    name: String,
+    icon: OwnedValue,
+    token: String,
+}
+
+impl PreparedInstall {
+    /// The name of the launcher, possibly changed by the user.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The serialized icon of the launcher, possibly changed by the user.
+    pub fn icon(&self) -> &OwnedValue {
+        &self.icon
+    }
+
+    /// The token to pass to [`DynamicLauncherProxy::install`].
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+#[derive(SerializeDict, Type, Debug, Default)]
+/// Specified options for a [`DynamicLauncherProxy::launch`] request.
+#[zvariant(signature = "dict")]
+struct LaunchOptions {
+    activation_token: Option<ActivationToken>,
+}
+
+// The methods without options yet still take a dictionary.
+type NoOptions = HashMap<&'static str, OwnedValue>;
+
+/// The interface lets sandboxed applications install launchers, desktop
+/// entries along with an icon, for web applications or games for example.
+///
+/// Wrapper of the DBus interface: [`org.freedesktop.portal.DynamicLauncher`](https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.DynamicLauncher.html).
+#[derive(Clone)]
+#[doc(alias = "org.freedesktop.portal.DynamicLauncher")]
+pub struct DynamicLauncherProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(DynamicLauncherProxy, INTERFACE_DYNAMIC_LAUNCHER);
+impl_proxy_timeout!(DynamicLauncherProxy);
+
+impl<'a> DynamicLauncherProxy<'a> {
+    /// Create a new instance of [`DynamicLauncherProxy`].
+    pub async fn new(connection: &zbus::Connection) -> Result<DynamicLauncherProxy<'a>, Error> {
+        let proxy = zbus::ProxyBuilder::new_bare(connection)
+            .interface(INTERFACE_DYNAMIC_LAUNCHER)?
+            .path(PATH)?
+            .destination(DESTINATION)?
+            .build()
+            .await?;
+        Ok(Self(proxy, None))
+    }
+
+    /// Get a reference to the underlying Proxy.
+    pub fn inner(&self) -> &zbus::Proxy<'_> {
+        &self.0
+    }
+
+    /// Asks the user to confirm the installation of a launcher, the token of
+    /// the response is passed to [`DynamicLauncherProxy::install`].
+    ///
+    /// # Arguments
+    ///
+    /// * `identifier` - Identifier for the window.
+    /// * `name` - The default name of the launcher.
+    /// * `icon` - The default icon of the launcher.
+    /// * `options` - A [`PrepareInstallOptions`].
+    #[doc(alias = "PrepareInstall")]
+    #[doc(alias = "xdp_portal_dynamic_launcher_prepare_install")]
+    pub fn prepare_install<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
+        name: &str,
+        icon: &LauncherIcon,
+        options: PrepareInstallOptions,
+    ) -> PortalRequest<'p, PreparedInstall> {
+        let handle_token = options.handle_token.copy();
+        call_window_request_method(
+            self,
+            identifier,
+            &handle_token,
+            "PrepareInstall",
+            (identifier, name.to_owned(), icon.serialize(), options),
+        )
+    }
+
+    /// Like [`DynamicLauncherProxy::prepare_install`], without asking the
+    /// user, only allowed to some applications like the software centers.
+    pub async fn request_install_token(
+        &self,
+        name: &str,
+        icon: &LauncherIcon,
+    ) -> Result<String, Error> {
+        self.request_install_token_raw(name, icon.serialize(), NoOptions::new())
+            .await
+    }
+
+    /// Installs the launcher `desktop_entry` as `desktop_file_id`, which has
+    /// to start with the application ID followed by a period.
+    ///
+    /// The `Exec` key is rewritten by the portal to run the application
+    /// through Flatpak.
+    pub async fn install(
+        &self,
+        token: &str,
+        desktop_file_id: &str,
+        desktop_entry: &str,
+    ) -> Result<(), Error> {
+        self.install_raw(token, desktop_file_id, desktop_entry, NoOptions::new())
+            .await
+    }
+
+    /// Removes a launcher installed by the application.
+    pub async fn uninstall(&self, desktop_file_id: &str) -> Result<(), Error> {
+        self.uninstall_raw(desktop_file_id, NoOptions::new()).await
+    }
+
+    /// The icon of a launcher installed by the application.
+    #[doc(alias = "GetIcon")]
+    pub async fn icon(&self, desktop_file_id: &str) -> Result<LauncherIcon, Error> {
+        let (icon, format, size) = self.icon_raw(desktop_file_id).await?;
+        let bytes = match <(String, Vec<u8>)>::try_from(icon) {
+            Ok((kind, bytes)) if kind == "bytes" => bytes,
+            _ => return Err(Error::ParseError("Expected a bytes icon".to_owned())),
+        };
+        let format = format.parse()?;
+        let size = (format != IconFormat::Svg).then_some(size);
+        Ok(LauncherIcon {
+            bytes,
+            format,
+            size,
+        })
+    }
+
+    /// Starts a launcher installed by the application.
+    pub async fn launch(
+        &self,
+        desktop_file_id: &str,
+        activation_token: Option<&ActivationToken>,
+    ) -> Result<(), Error> {
+        let options = LaunchOptions {
+            activation_token: activation_token.cloned(),
+        };
+        self.launch_raw(desktop_file_id, options).await
+    }
+
+    portal_members! {
+        fn request_install_token_raw(
+            name: &str,
+            icon: OwnedValue,
+            options: NoOptions,
+        ) -> String = method "RequestInstallToken";
+        fn install_raw(
+            token: &str,
+            desktop_file_id: &str,
+            desktop_entry: &str,
+            options: NoOptions,
+        ) -> () = method "Install";
+        fn uninstall_raw(desktop_file_id: &str, options: NoOptions) -> () = method "Uninstall";
+        /// The content of the desktop entry of a launcher installed by the
+        /// application.
+        pub fn desktop_entry(desktop_file_id: &str) -> String = method "GetDesktopEntry";
+        fn icon_raw(desktop_file_id: &str) -> (OwnedValue, String, u32) = method "GetIcon";
+        fn launch_raw(desktop_file_id: &str, options: LaunchOptions) -> () = method "Launch";
+
+        /// The kinds of launchers the portal can install.
+        #[doc(alias = "SupportedLauncherTypes")]
+        pub fn supported_launcher_types() -> BitFlags<LauncherType> = property "SupportedLauncherTypes";
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        image_size, DynamicLauncherProxy, IconError, IconFormat, LauncherIcon, LauncherType,
+        PrepareInstallOptions, INTERFACE_DYNAMIC_LAUNCHER,
+    };
+    use crate::helpers::{assert_members, assert_serialized_dict};
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend_from_slice(&width.to_be_bytes());
+        png.extend_from_slice(&height.to_be_bytes());
+        png.extend_from_slice(&[8, 6, 0, 0, 0]);
+        png
+    }
+
+    fn jpeg(width: u16, height: u16) -> Vec<u8> {
+        let mut jpeg = b"\xff\xd8\xff\xe0\0\x10JFIF\0\x01\x01\0\0\x01\0\x01\0\0".to_vec();
+        jpeg.extend_from_slice(b"\xff\xc0\0\x11\x08");
+        jpeg.extend_from_slice(&height.to_be_bytes());
+        jpeg.extend_from_slice(&width.to_be_bytes());
+        jpeg.extend_from_slice(&[3, 1, 0x22, 0, 2, 0x11, 1, 3, 0x11, 1]);
+        jpeg
+    }
+
+    #[test]
+    fn members() {
+        assert_members(INTERFACE_DYNAMIC_LAUNCHER, &DynamicLauncherProxy::members());
+    }
+
+    #[test]
+    fn options() {
+        let options = PrepareInstallOptions::default()
+            .with_modal(true)
+            .with_launcher_type(LauncherType::WebApplication)
+            .with_target("https://example.org")
+            .with_editable_name(true)
+            .with_editable_icon(false);
+        assert_serialized_dict(
+            &options,
+            &[
+                ("editable_icon", "b"),
+                ("editable_name", "b"),
+                ("handle_token", "s"),
+                ("launcher_type", "u"),
+                ("modal", "b"),
+                ("target", "s"),
+            ],
+        );
+    }
+
+    #[test]
+    fn icon_size() {
+        assert_eq!(
+            image_size(&png(64, 32)),
+            Ok((IconFormat::Png, Some((64, 32))))
+        );
+        assert_eq!(
+            image_size(&jpeg(128, 96)),
+            Ok((IconFormat::Jpeg, Some((128, 96))))
+        );
+        assert_eq!(
+            image_size(b"<?xml version=\"1.0\"?>\n<svg width=\"16\"></svg>"),
+            Ok((IconFormat::Svg, None))
+        );
+        assert_eq!(
+            image_size(&png(64, 64)[..20]),
+            Err(IconError::Malformed(IconFormat::Png))
+        );
+        assert_eq!(
+            image_size(&jpeg(64, 64)[..24]),
+            Err(IconError::Malformed(IconFormat::Jpeg))
+        );
+        assert_eq!(image_size(b"GIF89a"), Err(IconError::UnsupportedFormat));
+    }
+
+    #[test]
+    fn icon() {
+        let icon = LauncherIcon::from_bytes(png(512, 512)).unwrap();
+        assert_eq!(icon.format(), IconFormat::Png);
+        assert_eq!(icon.size(), Some(512));
+
+        assert_eq!(
+            LauncherIcon::from_bytes(jpeg(64, 48)),
+            Err(IconError::NotSquare {
+                width: 64,
+                height: 48
+            })
+        );
+        assert_eq!(
+            LauncherIcon::from_bytes(png(1024, 1024)),
+            Err(IconError::TooLarge { size: 1024 })
+        );
+        assert_eq!(
+            LauncherIcon::from_bytes("<svg></svg>").unwrap().size(),
+            None
+        );
+    }
+
+    #[cfg(feature = "icon-resize")]
+    #[test]
+    fn downscaled() {
+        let mut large = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::new_rgba8(1024, 1024)
+            .write_to(&mut large, image::ImageOutputFormat::Png)
+            .unwrap();
+        let icon = LauncherIcon::from_bytes_downscaled(large.into_inner()).unwrap();
+        assert_eq!(icon.format(), IconFormat::Png);
+        assert_eq!(icon.size(), Some(512));
+
+        assert_eq!(
+            LauncherIcon::from_bytes_downscaled(png(1024, 512)),
+            Err(IconError::NotSquare {
+                width: 1024,
+                height: 512
+            })
+        );
+    }
+}
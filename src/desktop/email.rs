@@ -51,12 +51,16 @@
 use std::os::unix::prelude::AsRawFd;
 
 use serde::Serialize;
-use zbus::zvariant::{DeserializeDict, Fd, SerializeDict, Type};
+use zbus::zvariant::{Fd, Type};
 
-use super::{HandleToken, DESTINATION, PATH};
-use crate::{helpers::call_basic_response_method, Error, WindowIdentifier};
+use super::{HandleToken, PortalRequest, DESTINATION, INTERFACE_EMAIL, PATH};
+use crate::{
+    helpers::{call_window_basic_response_method, request_handle, session_connection},
+    raw_options::RawOptions,
+    Error, WindowIdentifier,
+};
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+#[derive(Type, Debug, Default, Clone, PartialEq)]
 /// Specified options for a [`EmailProxy::compose_email`] request.
 #[zvariant(signature = "dict")]
 pub struct Email {
@@ -76,8 +80,21 @@ pub struct Email {
     body: Option<String>,
     /// A list of file descriptors of files to attach.
     attachment_fds: Option<Vec<Fd>>,
+    /// The options without a setter.
+    raw: RawOptions,
 }
 
+impl_options_dict!(Email {
+    handle_token = "handle_token",
+    address = "address",
+    addresses = "addresses",
+    cc = "cc",
+    bcc = "bcc",
+    subject = "subject",
+    body = "body",
+    attachment_fds = "attachment_fds",
+});
+
 impl Email {
     /// Create a new instance of [`Email`].
     pub fn new() -> Self {
@@ -86,8 +103,8 @@ impl Email {
 
     /// Similar to `set_address`.
     #[must_use]
-    pub fn address(mut self, address: &str) -> Self {
-        self.address = Some(address.to_string());
+    pub fn address(mut self, address: impl Into<String>) -> Self {
+        self.address = Some(address.into());
         self
     }
 
@@ -134,8 +151,8 @@ impl Email {
 
     /// Similar to `set_subject`.
     #[must_use]
-    pub fn subject(mut self, subject: &str) -> Self {
-        self.subject = Some(subject.to_string());
+    pub fn subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
         self
     }
 
@@ -146,8 +163,8 @@ impl Email {
 
     /// Similar to `set_body`.
     #[must_use]
-    pub fn body(mut self, body: &str) -> Self {
-        self.body = Some(body.to_string());
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.body = Some(body.into());
         self
     }
 
@@ -168,25 +185,93 @@ impl Email {
         };
         self
     }
+
+    // Fails with `Error::InvalidArgument` on the first malformed address.
+    fn validate(&self) -> Result<(), Error> {
+        let recipients = self
+            .address
+            .iter()
+            .chain(self.addresses.iter().flatten())
+            .chain(self.cc.iter().flatten())
+            .chain(self.bcc.iter().flatten());
+        for address in recipients {
+            if !is_valid_address(address) {
+                return Err(Error::InvalidArgument(format!(
+                    "Invalid email address {:?}",
+                    address
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // Versions prior to 3 of the interface only know about `address`, a
+    // single one of the `addresses` is sent as such. More recipients can't be
+    // sent without dropping some, that fails with `Error::RequiresVersion`.
+    fn for_version(mut self, version: u32) -> Result<Self, Error> {
+        if version >= 3 {
+            return Ok(self);
+        }
+        let addresses = self.addresses.take().unwrap_or_default();
+        let recipients = self.address.iter().count() + addresses.len();
+        let has_copies = self.cc.iter().chain(self.bcc.iter()).any(|c| !c.is_empty());
+        if recipients > 1 || has_copies {
+            return Err(Error::RequiresVersion(3, version));
+        }
+        if self.address.is_none() {
+            self.address = addresses.into_iter().next();
+        }
+        self.cc = None;
+        self.bcc = None;
+        Ok(self)
+    }
+}
+
+// A basic check of the `local@domain` syntax of RFC 5322, quoted local parts
+// and address literals aren't supported.
+fn is_valid_address(address: &str) -> bool {
+    let (local, domain) = match address.rsplit_once('@') {
+        Some(parts) => parts,
+        None => return false,
+    };
+    let is_atext = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c);
+    let local_is_valid = !local.is_empty()
+        && local.len() <= 64
+        && local
+            .split('.')
+            .all(|atom| !atom.is_empty() && atom.chars().all(is_atext));
+    let domain_is_valid = !domain.is_empty()
+        && domain.len() <= 255
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+    local_is_valid && domain_is_valid
 }
 
 /// The interface lets sandboxed applications request sending an email.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Email`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Email).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.Email")]
-pub struct EmailProxy<'a>(zbus::Proxy<'a>);
+pub struct EmailProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(EmailProxy, INTERFACE_EMAIL);
+impl_proxy_timeout!(EmailProxy);
 
 impl<'a> EmailProxy<'a> {
     /// Create a new instance of [`EmailProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<EmailProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Email")?
+            .interface(INTERFACE_EMAIL)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -199,6 +284,11 @@ impl<'a> EmailProxy<'a> {
     /// **Note** the default email client for the host will need to support
     /// `mailto:` URIs following RFC 2368.
     ///
+    /// The addresses are checked beforehand, a malformed one fails with
+    /// [`Error::InvalidArgument`]. Versions prior to 3 of the interface only
+    /// support a single recipient without CC nor BCC, more of them fail with
+    /// [`Error::RequiresVersion`] rather than being dropped.
+    ///
     /// # Arguments
     ///
     /// * `identifier` - Identifier for the application window.
@@ -208,27 +298,120 @@ impl<'a> EmailProxy<'a> {
     ///
     /// See also [`ComposeEmail`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Email.ComposeEmail).
     #[doc(alias = "ComposeEmail")]
-    pub async fn compose_email(
-        &self,
-        identifier: &WindowIdentifier,
+    pub fn compose_email<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
         email: Email,
-    ) -> Result<(), Error> {
-        call_basic_response_method(
-            self.inner(),
-            &email.handle_token,
-            "ComposeEmail",
-            &(&identifier, &email),
-        )
-        .await
+    ) -> PortalRequest<'p, ()> {
+        if let Err(err) = email.validate() {
+            return PortalRequest::invalid(err);
+        }
+        let handle_token = email.handle_token.copy();
+        let handle = request_handle(self, &handle_token, "ComposeEmail");
+        PortalRequest::new(handle, async move {
+            let version = self
+                .inner()
+                .get_property::<u32>("version")
+                .await
+                .unwrap_or(1);
+            let email = email.for_version(version)?;
+            call_window_basic_response_method(
+                self,
+                identifier,
+                &handle_token,
+                "ComposeEmail",
+                (identifier, email),
+            )
+            .await
+        })
     }
 }
 
 /// A handy wrapper around [`EmailProxy::compose_email`]
 #[doc(alias = "xdp_portal_compose_email")]
 pub async fn compose(identifier: &WindowIdentifier, email: Email) -> Result<(), Error> {
-    let connection = zbus::Connection::session().await?;
+    let connection = session_connection().await?;
     let proxy = EmailProxy::new(&connection).await?;
     proxy.compose_email(identifier, email).await?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use zbus::zvariant::Fd;
+
+    use super::{is_valid_address, Email};
+    use crate::{desktop::HandleToken, helpers::assert_serialized_dict, Error};
+
+    #[test]
+    fn addresses() {
+        assert!(is_valid_address("john.doe+portal@example.org"));
+        assert!(is_valid_address("x@localhost"));
+        assert!(!is_valid_address("john.doe"));
+        assert!(!is_valid_address("@example.org"));
+        assert!(!is_valid_address("john..doe@example.org"));
+        assert!(!is_valid_address("john doe@example.org"));
+        assert!(!is_valid_address("john@-example.org"));
+        assert!(!is_valid_address("john@example..org"));
+
+        assert!(Email::new().address("john@example.org").validate().is_ok());
+        assert!(Email::new().cc(&["john@"]).validate().is_err());
+    }
+
+    #[test]
+    fn version_fallback() {
+        let email = Email::new()
+            .addresses(&["a@example.org"])
+            .cc(&[] as &[&str])
+            .for_version(2)
+            .unwrap();
+        assert_eq!(email.address.as_deref(), Some("a@example.org"));
+        assert!(email.addresses.is_none() && email.cc.is_none());
+
+        let email = Email::new().addresses(&["a@example.org", "b@example.org"]);
+        assert!(matches!(
+            email.clone().for_version(2),
+            Err(Error::RequiresVersion(3, 2))
+        ));
+        let email = email.for_version(3).unwrap();
+        assert!(email.address.is_none());
+        assert_eq!(email.addresses.unwrap().len(), 2);
+
+        let email = Email::new()
+            .address("a@example.org")
+            .bcc(&["b@example.org"]);
+        assert!(matches!(
+            email.for_version(1),
+            Err(Error::RequiresVersion(3, 1))
+        ));
+    }
+
+    #[test]
+    fn options() {
+        let options = Email {
+            handle_token: HandleToken::default(),
+            address: Some("a@example.org".to_owned()),
+            addresses: Some(vec!["b@example.org".to_owned()]),
+            cc: Some(vec!["c@example.org".to_owned()]),
+            bcc: Some(vec!["d@example.org".to_owned()]),
+            subject: Some("Hello".to_owned()),
+            body: Some("Hello there".to_owned()),
+            attachment_fds: Some(vec![Fd::from(0)]),
+            raw: Default::default(),
+        };
+        assert_serialized_dict(
+            &options,
+            &[
+                ("address", "s"),
+                ("addresses", "as"),
+                ("attachment_fds", "ah"),
+                ("bcc", "as"),
+                ("body", "s"),
+                ("cc", "as"),
+                ("handle_token", "s"),
+                ("subject", "s"),
+            ],
+        );
+    }
+}
@@ -0,0 +1,862 @@
+//! # Examples
+//!
+//! ## Opening a file
+//!
+//! ```rust,no_run
+//! use ashpd::desktop::file_chooser::{Choice, FileChooserProxy, FileFilter, OpenFileOptions};
+//! use ashpd::WindowIdentifier;
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::Connection::session().await?;
+//!
+//!     let proxy = FileChooserProxy::new(&connection).await?;
+//!     let files = proxy
+//!         .open_file(
+//!             &WindowIdentifier::default(),
+//!             "open a file to read",
+//!             OpenFileOptions::default()
+//!                 .with_accept_label("read")
+//!                 .with_modal(true)
+//!                 .with_multiple(true)
+//!                 .add_choice(
+//!                     Choice::new("encoding", "Encoding", "latin15")
+//!                         .insert("utf8", "Unicode (UTF-8)")
+//!                         .insert("latin15", "Western"),
+//!                 )
+//!                 // A trick to have a checkbox
+//!                 .add_choice(Choice::boolean("re-encode", "Re-encode", false))
+//!                 .add_filter(FileFilter::new("SVG Image").mimetype("image/svg+xml")),
+//!         )
+//!         .await?;
+//!
+//!     println!("{:#?}", files);
+//!     println!("encoding: {:?}", files.choice("encoding"));
+//!     println!("re-encode: {:?}", files.boolean_choice("re-encode"));
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! ## Ask to save a file
+//!
+//! ```rust,no_run
+//! use ashpd::desktop::file_chooser::{FileChooserProxy, FileFilter, SaveFileOptions};
+//! use ashpd::WindowIdentifier;
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::Connection::session().await?;
+//!     let proxy = FileChooserProxy::new(&connection).await?;
+//!     let files = proxy
+//!         .save_file(
+//!             &WindowIdentifier::default(),
+//!             "open a file to write",
+//!             SaveFileOptions::default()
+//!                 .with_accept_label("write")
+//!                 .with_current_name("image.jpg")
+//!                 .with_modal(true)
+//!                 .add_filter(FileFilter::new("JPEG Image").glob("*.jpg")),
+//!         )
+//!         .await?;
+//!
+//!     println!("{:#?}", files);
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! ## Ask to save multiple files
+//!
+//! ```rust,no_run
+//! use ashpd::desktop::file_chooser::{FileChooserProxy, SaveFilesOptions};
+//! use ashpd::WindowIdentifier;
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::Connection::session().await?;
+//!
+//!     let proxy = FileChooserProxy::new(&connection).await?;
+//!     let files = proxy
+//!         .save_files(
+//!             &WindowIdentifier::default(),
+//!             "open files to write",
+//!             SaveFilesOptions::default()
+//!                 .with_accept_label("write files")
+//!                 .with_modal(true)
+//!                 .with_current_folder("/home/bilelmoussaoui/Pictures")?
+//!                 .with_files(&["test.jpg", "awesome.png"])?,
+//!         )
+//!         .await?;
+//!
+//!     println!("{:#?}", files);
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! ## Without a backend
+//!
+//! With the `fallback-dialogs` feature, the requests made while no portal
+//! backend implements the FileChooser interface ask for the paths on the
+//! terminal the application was started from, if any. The prompt blocks the
+//! calling thread, it is meant for the development machines and the headless
+//! sessions, not as a replacement of the portal. The choices keep their
+//! initial selection.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use url::Url;
+use zbus::zvariant::{DeserializeDict, SerializeDict, Type};
+
+use super::{HandleToken, PortalRequest, DESTINATION, INTERFACE_FILE_CHOOSER, PATH};
+use crate::{
+    helpers::{call_window_request_method, null_terminated_from_path, path_from_null_terminated},
+    raw_options::RawOptions,
+    uri::Uris,
+    Error, WindowIdentifier,
+};
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+/// A file filter, to limit the available file choices to a mimetype or a glob
+/// pattern.
+pub struct FileFilter(String, Vec<(FilterType, String)>);
+
+#[derive(Serialize, Clone, Deserialize, PartialEq, Eq, Debug, Type)]
+enum FilterType {
+    GlobPattern = 0,
+    MimeType = 1,
+}
+
+impl FileFilter {
+    /// Create a new file filter
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - user-visible name of the file filter.
+    pub fn new(label: impl Into<String>) -> Self {
+        Self(label.into(), vec![])
+    }
+
+    /// Adds a mime type to the file filter.
+    #[must_use]
+    pub fn mimetype(mut self, mimetype: impl Into<String>) -> Self {
+        self.1.push((FilterType::MimeType, mimetype.into()));
+        self
+    }
+
+    /// Adds a glob pattern to the file filter.
+    #[must_use]
+    pub fn glob(mut self, pattern: impl Into<String>) -> Self {
+        self.1.push((FilterType::GlobPattern, pattern.into()));
+        self
+    }
+
+    /// The user-visible name of the file filter.
+    pub fn label(&self) -> &str {
+        &self.0
+    }
+
+    /// The mime types of the file filter.
+    pub fn mimetypes(&self) -> Vec<&str> {
+        self.filters(FilterType::MimeType)
+    }
+
+    /// The glob patterns of the file filter.
+    pub fn globs(&self) -> Vec<&str> {
+        self.filters(FilterType::GlobPattern)
+    }
+
+    fn filters(&self, type_: FilterType) -> Vec<&str> {
+        self.1
+            .iter()
+            .filter(|(filter_type, _)| *filter_type == type_)
+            .map(|(_, filter)| filter.as_str())
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+/// Presents the user with a choice to select from or as a checkbox.
+pub struct Choice(String, String, Vec<(String, String)>, String);
+
+impl Choice {
+    /// Creates a checkbox choice.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - A unique identifier of the choice.
+    /// * `label` - user-visible name of the choice.
+    /// * `state` - the initial state value.
+    pub fn boolean(id: impl Into<String>, label: impl Into<String>, state: bool) -> Self {
+        Self::new(id, label, state.to_string())
+    }
+
+    /// Creates a new choice.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - A unique identifier of the choice.
+    /// * `label` - user-visible name of the choice.
+    /// * `initial_selection` - the initially selected value.
+    pub fn new(
+        id: impl Into<String>,
+        label: impl Into<String>,
+        initial_selection: impl Into<String>,
+    ) -> Self {
+        Self(id.into(), label.into(), vec![], initial_selection.into())
+    }
+
+    /// Adds a (key, value) as a choice.
+    #[must_use]
+    pub fn insert(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.2.push((key.into(), value.into()));
+        self
+    }
+
+    /// The choice's unique id
+    pub fn id(&self) -> &str {
+        &self.0
+    }
+
+    /// The user visible label of the choice.
+    pub fn label(&self) -> &str {
+        &self.1
+    }
+
+    /// The (key, value) pairs to choose from, empty for a checkbox.
+    pub fn options(&self) -> &[(String, String)] {
+        &self.2
+    }
+
+    /// Whether the choice is presented as a checkbox.
+    pub fn is_boolean(&self) -> bool {
+        self.2.is_empty()
+    }
+
+    /// The initially selected value.
+    pub fn initial_selection(&self) -> &str {
+        &self.3
+    }
+}
+
+#[derive(Type, Clone, Debug, Default, PartialEq)]
+/// Specified options for a [`FileChooserProxy::open_file`] request.
+#[zvariant(signature = "dict")]
+pub struct OpenFileOptions {
+    /// A string that will be used as the last element of the handle.
+    handle_token: HandleToken,
+    /// Label for the accept button. Mnemonic underlines are allowed.
+    accept_label: Option<String>,
+    /// Whether the dialog should be modal.
+    modal: Option<bool>,
+    /// Whether multiple files can be selected or not.
+    multiple: Option<bool>,
+    /// Whether to select for folders instead of files.
+    directory: Option<bool>,
+    /// List of serialized file filters.
+    filters: Vec<FileFilter>,
+    /// Request that this filter be set by default at dialog creation.
+    current_filter: Option<FileFilter>,
+    /// List of serialized combo boxes to add to the file chooser
+    choices: Vec<Choice>,
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(OpenFileOptions {
+    handle_token = "handle_token",
+    accept_label = "accept_label",
+    modal = "modal",
+    multiple = "multiple",
+    directory = "directory",
+    filters = "filters",
+    current_filter = "current_filter",
+    choices = "choices",
+});
+
+impl OpenFileOptions {
+    options_setters! {
+        /// Sets a user-visible string to the "accept" button.
+        with_accept_label(accept_label: impl Into<String>);
+        /// Sets whether the dialog should be a modal.
+        with_modal(modal: bool);
+        /// Sets whether to allow multiple files selection.
+        with_multiple(multiple: bool);
+        /// Sets whether to select directories or not.
+        with_directory(directory: bool);
+    }
+
+    /// Adds a files filter.
+    #[must_use]
+    pub fn add_filter(mut self, filter: FileFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Specifies the default filter.
+    #[must_use]
+    pub fn with_current_filter(mut self, current_filter: FileFilter) -> Self {
+        self.current_filter = Some(current_filter);
+        self
+    }
+
+    /// Adds a choice.
+    #[must_use]
+    pub fn add_choice(mut self, choice: Choice) -> Self {
+        self.choices.push(choice);
+        self
+    }
+
+    /// The files filters.
+    pub fn filters(&self) -> &[FileFilter] {
+        &self.filters
+    }
+
+    /// The default filter, if set.
+    pub fn current_filter(&self) -> Option<&FileFilter> {
+        self.current_filter.as_ref()
+    }
+
+    /// The choices.
+    pub fn choices(&self) -> &[Choice] {
+        &self.choices
+    }
+}
+
+#[derive(Type, Debug, Default, Clone, PartialEq)]
+/// Specified options for a [`FileChooserProxy::save_file`] request.
+#[zvariant(signature = "dict")]
+pub struct SaveFileOptions {
+    /// A string that will be used as the last element of the handle.
+    handle_token: HandleToken,
+    /// Label for the accept button. Mnemonic underlines are allowed.
+    accept_label: Option<String>,
+    /// Whether the dialog should be modal.
+    modal: Option<bool>,
+    /// Suggested filename.
+    current_name: Option<String>,
+    /// Suggested folder to save the file in.
+    current_folder: Option<Vec<u8>>,
+    /// The current file (when saving an existing file).
+    current_file: Option<Vec<u8>>,
+    /// List of serialized file filters.
+    filters: Vec<FileFilter>,
+    /// Request that this filter be set by default at dialog creation.
+    current_filter: Option<FileFilter>,
+    /// List of serialized combo boxes to add to the file chooser
+    choices: Vec<Choice>,
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(SaveFileOptions {
+    handle_token = "handle_token",
+    accept_label = "accept_label",
+    modal = "modal",
+    current_name = "current_name",
+    current_folder = "current_folder",
+    current_file = "current_file",
+    filters = "filters",
+    current_filter = "current_filter",
+    choices = "choices",
+});
+
+impl SaveFileOptions {
+    options_setters! {
+        /// Sets a user-visible string to the "accept" button.
+        with_accept_label(accept_label: impl Into<String>);
+        /// Sets whether the dialog should be a modal.
+        with_modal(modal: bool);
+        /// Sets the current file name.
+        with_current_name(current_name: impl Into<String>);
+    }
+
+    /// Sets the current folder.
+    ///
+    /// Fails with [`Error::NulTerminated`] if the path contains a NUL byte.
+    pub fn with_current_folder(mut self, current_folder: impl AsRef<Path>) -> Result<Self, Error> {
+        self.current_folder = Some(null_terminated_from_path(current_folder)?);
+        Ok(self)
+    }
+
+    /// Sets the absolute path of the file.
+    ///
+    /// Fails with [`Error::NulTerminated`] if the path contains a NUL byte.
+    pub fn with_current_file(mut self, current_file: impl AsRef<Path>) -> Result<Self, Error> {
+        self.current_file = Some(null_terminated_from_path(current_file)?);
+        Ok(self)
+    }
+
+    /// Adds a files filter.
+    #[must_use]
+    pub fn add_filter(mut self, filter: FileFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Sets the default filter.
+    #[must_use]
+    pub fn with_current_filter(mut self, current_filter: FileFilter) -> Self {
+        self.current_filter = Some(current_filter);
+        self
+    }
+
+    /// Adds a choice.
+    #[must_use]
+    pub fn add_choice(mut self, choice: Choice) -> Self {
+        self.choices.push(choice);
+        self
+    }
+
+    /// The current folder, if set.
+    pub fn current_folder(&self) -> Option<PathBuf> {
+        self.current_folder
+            .as_deref()
+            .map(path_from_null_terminated)
+    }
+
+    /// The absolute path of the file, if set.
+    pub fn current_file(&self) -> Option<PathBuf> {
+        self.current_file.as_deref().map(path_from_null_terminated)
+    }
+
+    /// The files filters.
+    pub fn filters(&self) -> &[FileFilter] {
+        &self.filters
+    }
+
+    /// The default filter, if set.
+    pub fn current_filter(&self) -> Option<&FileFilter> {
+        self.current_filter.as_ref()
+    }
+
+    /// The choices.
+    pub fn choices(&self) -> &[Choice] {
+        &self.choices
+    }
+}
+
+#[derive(Type, Clone, Debug, Default, PartialEq)]
+/// Specified options for a [`FileChooserProxy::save_files`] request.
+#[zvariant(signature = "dict")]
+pub struct SaveFilesOptions {
+    /// A string that will be used as the last element of the handle.
+    handle_token: HandleToken,
+    /// Label for the accept button. Mnemonic underlines are allowed.
+    accept_label: Option<String>,
+    /// Whether the dialog should be modal.
+    modal: Option<bool>,
+    /// List of serialized combo boxes to add to the file chooser
+    choices: Vec<Choice>,
+    /// Suggested folder to save the file in.
+    current_folder: Option<Vec<u8>>,
+    /// An array of file names to be saved.
+    files: Option<Vec<Vec<u8>>>,
+    /// The options without a setter.
+    raw: RawOptions,
+}
+
+impl_options_dict!(SaveFilesOptions {
+    handle_token = "handle_token",
+    accept_label = "accept_label",
+    modal = "modal",
+    choices = "choices",
+    current_folder = "current_folder",
+    files = "files",
+});
+
+impl SaveFilesOptions {
+    options_setters! {
+        /// Sets a user-visible string to the "accept" button.
+        with_accept_label(accept_label: impl Into<String>);
+        /// Sets whether the dialog should be a modal.
+        with_modal(modal: bool);
+    }
+
+    /// Adds a choice.
+    #[must_use]
+    pub fn add_choice(mut self, choice: Choice) -> Self {
+        self.choices.push(choice);
+        self
+    }
+
+    /// Specifies the current folder path.
+    ///
+    /// Fails with [`Error::NulTerminated`] if the path contains a NUL byte.
+    pub fn with_current_folder(mut self, current_folder: impl AsRef<Path>) -> Result<Self, Error> {
+        self.current_folder = Some(null_terminated_from_path(current_folder)?);
+        Ok(self)
+    }
+
+    /// Sets the names of the files to save.
+    ///
+    /// The names are sent as NUL terminated bytestrings, non UTF-8 names are
+    /// kept as is. Fails with [`Error::NulTerminated`] if a name contains a
+    /// NUL byte.
+    pub fn with_files(mut self, files: &[impl AsRef<Path>]) -> Result<Self, Error> {
+        self.files = Some(
+            files
+                .iter()
+                .map(null_terminated_from_path)
+                .collect::<Result<_, _>>()?,
+        );
+        Ok(self)
+    }
+
+    /// The choices.
+    pub fn choices(&self) -> &[Choice] {
+        &self.choices
+    }
+
+    /// The current folder, if set.
+    pub fn current_folder(&self) -> Option<PathBuf> {
+        self.current_folder
+            .as_deref()
+            .map(path_from_null_terminated)
+    }
+
+    /// The names of the files to save, empty if not set.
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.files
+            .iter()
+            .flatten()
+            .map(|file| path_from_null_terminated(file))
+            .collect()
+    }
+}
+
+#[derive(Debug, Type, SerializeDict, Clone, DeserializeDict, PartialEq, Eq)]
+/// A response to a
+/// [`FileChooserProxy::open_file`]/[`FileChooserProxy::save_file`]/
+/// [`FileChooserProxy::save_files`] request.
+#[zvariant(signature = "dict")]
+pub struct SelectedFiles {
+    uris: Uris,
+    choices: Option<Vec<(String, String)>>,
+}
+
+impl SelectedFiles {
+    /// The selected files uris.
+    pub fn uris(&self) -> &[Url] {
+        self.uris.0.as_slice()
+    }
+
+    /// The selected value of each choice as a tuple of (key, value)
+    pub fn choices(&self) -> &[(String, String)] {
+        self.choices.as_deref().unwrap_or_default()
+    }
+
+    /// The selected key of the choice `id`.
+    pub fn choice(&self, id: &str) -> Option<&str> {
+        self.choices()
+            .iter()
+            .find(|(choice_id, _)| choice_id == id)
+            .map(|(_, key)| key.as_str())
+    }
+
+    /// The state of the checkbox choice `id`, see [`Choice::boolean`].
+    ///
+    /// Returns `None` if the choice is missing or if its value isn't a
+    /// boolean.
+    pub fn boolean_choice(&self, id: &str) -> Option<bool> {
+        self.choice(id)?.parse().ok()
+    }
+}
+
+/// The interface lets sandboxed applications ask the user for access to files
+/// outside the sandbox. The portal backend will present the user with a file
+/// chooser dialog.
+///
+/// Wrapper of the DBus interface: [`org.freedesktop.portal.FileChooser`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.FileChooser).
+#[derive(Clone)]
+#[doc(alias = "org.freedesktop.portal.FileChooser")]
+pub struct FileChooserProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(FileChooserProxy, INTERFACE_FILE_CHOOSER);
+impl_proxy_timeout!(FileChooserProxy);
+
+impl<'a> FileChooserProxy<'a> {
+    /// Create a new instance of [`FileChooserProxy`].
+    pub async fn new(connection: &zbus::Connection) -> Result<FileChooserProxy<'a>, Error> {
+        let proxy = zbus::ProxyBuilder::new_bare(connection)
+            .interface(INTERFACE_FILE_CHOOSER)?
+            .path(PATH)?
+            .destination(DESTINATION)?
+            .build()
+            .await?;
+        Ok(Self(proxy, None))
+    }
+
+    /// Get a reference to the underlying Proxy.
+    pub fn inner(&self) -> &zbus::Proxy<'_> {
+        &self.0
+    }
+
+    /// Asks to open one or more files.
+    ///
+    /// # Arguments
+    ///
+    /// * `identifier` - Identifier for the application window.
+    /// * `title` - Title for the file chooser dialog.
+    /// * `options` - [`OpenFileOptions`].
+    ///
+    /// # Specifications
+    ///
+    /// See also [`OpenFile`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-FileChooser.OpenFile).
+    #[doc(alias = "OpenFile")]
+    #[doc(alias = "xdp_portal_open_file")]
+    pub fn open_file<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
+        title: &'p str,
+        options: OpenFileOptions,
+    ) -> PortalRequest<'p, SelectedFiles> {
+        let handle_token = options.handle_token.copy();
+        #[cfg(feature = "fallback-dialogs")]
+        let (prompt_title, prompt_options) = (title.to_owned(), options.clone());
+        let request = call_window_request_method(
+            self,
+            identifier,
+            &handle_token,
+            "OpenFile",
+            (identifier, title, options),
+        );
+        #[cfg(feature = "fallback-dialogs")]
+        let request = request.then_result(move |response| {
+            prompt::or_prompt(response, move |input, output| {
+                prompt::open_file(input, output, &prompt_title, &prompt_options)
+            })
+        });
+        request
+    }
+
+    /// Asks for a location to save a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `identifier` - Identifier for the application window.
+    /// * `title` - Title for the file chooser dialog.
+    /// * `options` - [`SaveFileOptions`].
+    ///
+    /// # Specifications
+    ///
+    /// See also [`SaveFile`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-FileChooser.SaveFile).
+    #[doc(alias = "SaveFile")]
+    #[doc(alias = "xdp_portal_save_file")]
+    pub fn save_file<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
+        title: &'p str,
+        options: SaveFileOptions,
+    ) -> PortalRequest<'p, SelectedFiles> {
+        let handle_token = options.handle_token.copy();
+        #[cfg(feature = "fallback-dialogs")]
+        let (prompt_title, prompt_options) = (title.to_owned(), options.clone());
+        let request = call_window_request_method(
+            self,
+            identifier,
+            &handle_token,
+            "SaveFile",
+            (identifier, title, options),
+        );
+        #[cfg(feature = "fallback-dialogs")]
+        let request = request.then_result(move |response| {
+            prompt::or_prompt(response, move |input, output| {
+                prompt::save_file(input, output, &prompt_title, &prompt_options)
+            })
+        });
+        request
+    }
+
+    /// Asks for a folder as a location to save one or more files.
+    /// The names of the files will be used as-is and appended to the
+    /// selected folder's path in the list of returned files.
+    /// If the selected folder already contains a file with one of the given
+    /// names, the portal may prompt or take some other action to
+    /// construct a unique file name and return that instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `identifier` - Identifier for the application window.
+    /// * `title` - Title for the file chooser dialog.
+    /// * `options` - [`SaveFilesOptions`].
+    ///
+    /// # Specifications
+    ///
+    /// See also [`SaveFiles`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-FileChooser.SaveFiles).
+    #[doc(alias = "SaveFiles")]
+    #[doc(alias = "xdp_portal_save_files")]
+    pub fn save_files<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
+        title: &'p str,
+        options: SaveFilesOptions,
+    ) -> PortalRequest<'p, SelectedFiles> {
+        let handle_token = options.handle_token.copy();
+        #[cfg(feature = "fallback-dialogs")]
+        let (prompt_title, prompt_options) = (title.to_owned(), options.clone());
+        let request = call_window_request_method(
+            self,
+            identifier,
+            &handle_token,
+            "SaveFiles",
+            (identifier, title, options),
+        );
+        #[cfg(feature = "fallback-dialogs")]
+        let request = request.then_result(move |response| {
+            prompt::or_prompt(response, move |input, output| {
+                prompt::save_files(input, output, &prompt_title, &prompt_options)
+            })
+        });
+        request
+    }
+}
+
+/// Ask for the files on the terminal without a FileChooser backend.
+#[cfg(feature = "fallback-dialogs")]
+mod prompt;
+
+#[cfg(test)]
+mod test {
+    use super::{Choice, FileFilter, OpenFileOptions, SaveFileOptions, SaveFilesOptions};
+    use zbus::zvariant::{from_slice, to_bytes, EncodingContext, Value};
+
+    use crate::{desktop::HandleToken, helpers::assert_serialized_dict, Error};
+
+    #[test]
+    fn open_file_options() {
+        let options = OpenFileOptions {
+            handle_token: HandleToken::default(),
+            accept_label: Some("Open".to_owned()),
+            modal: Some(true),
+            multiple: Some(true),
+            directory: Some(false),
+            filters: vec![FileFilter::new("Images").mimetype("image/*")],
+            current_filter: Some(FileFilter::new("Images").mimetype("image/*")),
+            choices: vec![Choice::boolean("readonly", "Read only", false)],
+            raw: Default::default(),
+        };
+        assert_serialized_dict(
+            &options,
+            &[
+                ("accept_label", "s"),
+                ("choices", "a(ssa(ss)s)"),
+                ("current_filter", "(sa(us))"),
+                ("directory", "b"),
+                ("filters", "a(sa(us))"),
+                ("handle_token", "s"),
+                ("modal", "b"),
+                ("multiple", "b"),
+            ],
+        );
+    }
+
+    #[test]
+    fn save_file_options() {
+        let options = SaveFileOptions {
+            handle_token: HandleToken::default(),
+            accept_label: Some("Save".to_owned()),
+            modal: Some(true),
+            current_name: Some("notes.txt".to_owned()),
+            current_folder: Some(b"/tmp\0".to_vec()),
+            current_file: Some(b"/tmp/notes.txt\0".to_vec()),
+            filters: vec![FileFilter::new("Text").mimetype("text/plain")],
+            current_filter: Some(FileFilter::new("Text").mimetype("text/plain")),
+            choices: vec![Choice::boolean("readonly", "Read only", false)],
+            raw: Default::default(),
+        };
+        assert_serialized_dict(
+            &options,
+            &[
+                ("accept_label", "s"),
+                ("choices", "a(ssa(ss)s)"),
+                ("current_file", "ay"),
+                ("current_filter", "(sa(us))"),
+                ("current_folder", "ay"),
+                ("current_name", "s"),
+                ("filters", "a(sa(us))"),
+                ("handle_token", "s"),
+                ("modal", "b"),
+            ],
+        );
+    }
+
+    #[test]
+    fn save_files_options() {
+        let options = SaveFilesOptions {
+            handle_token: HandleToken::default(),
+            accept_label: Some("Save".to_owned()),
+            modal: Some(true),
+            choices: vec![Choice::boolean("readonly", "Read only", false)],
+            current_folder: Some(b"/tmp\0".to_vec()),
+            files: Some(vec![b"notes.txt\0".to_vec()]),
+            raw: Default::default(),
+        };
+        assert_serialized_dict(
+            &options,
+            &[
+                ("accept_label", "s"),
+                ("choices", "a(ssa(ss)s)"),
+                ("current_folder", "ay"),
+                ("files", "aay"),
+                ("handle_token", "s"),
+                ("modal", "b"),
+            ],
+        );
+    }
+
+    #[test]
+    fn getters() {
+        let options = SaveFileOptions::default()
+            .with_accept_label("Save")
+            .with_current_folder("/tmp")
+            .unwrap()
+            .add_filter(FileFilter::new("Text").mimetype("text/plain"));
+        assert_eq!(options.accept_label(), Some("Save"));
+        assert_eq!(options.modal(), None);
+        assert_eq!(options.current_folder(), Some("/tmp".into()));
+        assert_eq!(options.current_file(), None);
+        assert_eq!(options.filters().len(), 1);
+
+        let options = SaveFilesOptions::default()
+            .with_files(&["a.txt", "b.txt"])
+            .unwrap();
+        assert_eq!(
+            options.files(),
+            vec![std::path::PathBuf::from("a.txt"), "b.txt".into()]
+        );
+
+        assert!(matches!(
+            SaveFilesOptions::default().with_files(&["a.txt", "b\0.txt"]),
+            Err(Error::NulTerminated(1))
+        ));
+    }
+
+    #[test]
+    fn raw() {
+        let options = OpenFileOptions::default()
+            .with_modal(true)
+            .insert_raw("current_folder", b"/tmp\0".to_vec())
+            .insert_raw("modal", false);
+        assert_serialized_dict(
+            &options,
+            &[
+                ("choices", "a(ssa(ss)s)"),
+                ("current_folder", "ay"),
+                ("filters", "a(sa(us))"),
+                ("handle_token", "s"),
+                ("modal", "b"),
+            ],
+        );
+
+        let ctxt = EncodingContext::<byteorder::LE>::new_dbus(0);
+        let bytes = to_bytes(ctxt, &options).unwrap();
+        let options: OpenFileOptions = from_slice(&bytes, ctxt).unwrap();
+        assert_eq!(options.modal(), Some(true));
+        assert_eq!(options.raw().len(), 1);
+        assert!(options.raw().contains_key("current_folder"));
+
+        // A known option of another type is an error, not a missing value.
+        let dict = std::collections::HashMap::from([("modal", Value::from("yes"))]);
+        let bytes = to_bytes(ctxt, &dict).unwrap();
+        assert!(from_slice::<_, OpenFileOptions>(&bytes, ctxt).is_err());
+    }
+}
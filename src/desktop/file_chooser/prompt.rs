@@ -0,0 +1,257 @@
+use std::{
+    io::{self, BufRead, IsTerminal, Write},
+    path::PathBuf,
+};
+
+use url::Url;
+use zbus::fdo;
+
+use super::{Choice, OpenFileOptions, SaveFileOptions, SaveFilesOptions, SelectedFiles};
+use crate::{desktop::request::ResponseError, uri::Uris, Error};
+
+// The errors of the bus when nothing implements the FileChooser portal.
+const MISSING_BACKEND: &[&str] = &[
+    "org.freedesktop.DBus.Error.ServiceUnknown",
+    "org.freedesktop.DBus.Error.UnknownMethod",
+    "org.freedesktop.DBus.Error.UnknownInterface",
+    "org.freedesktop.DBus.Error.UnknownObject",
+];
+
+// Whether the call failed because there is no portal or no FileChooser
+// backend to show the dialog, rather than because of the user or the request.
+pub(super) fn is_backend_missing(err: &Error) -> bool {
+    match err.root() {
+        Error::Unsupported => true,
+        Error::Zbus(zbus::Error::MethodError(name, _, _)) => {
+            MISSING_BACKEND.contains(&name.as_str())
+        }
+        Error::Zbus(zbus::Error::FDO(err)) => matches!(
+            **err,
+            fdo::Error::ServiceUnknown(_)
+                | fdo::Error::UnknownMethod(_)
+                | fdo::Error::UnknownInterface(_)
+                | fdo::Error::UnknownObject(_)
+        ),
+        _ => false,
+    }
+}
+
+// Replace the failure of a call without a backend by a prompt on the terminal
+// the application was started from. The other failures, or a missing
+// terminal, are returned as is. The prompt blocks on stdin, so it runs on its
+// own thread instead of the executor polling the request.
+pub(super) async fn or_prompt(
+    response: Result<SelectedFiles, Error>,
+    prompt: impl FnOnce(&mut dyn BufRead, &mut dyn Write) -> io::Result<Option<SelectedFiles>>
+        + Send
+        + 'static,
+) -> Result<SelectedFiles, Error> {
+    match response {
+        Err(err) if is_backend_missing(&err) && io::stdin().is_terminal() => {
+            #[cfg(feature = "log")]
+            tracing::warn!("No FileChooser backend, prompting on the terminal: {}", err);
+            let (sender, receiver) = futures::channel::oneshot::channel();
+            std::thread::spawn(move || {
+                let stdin = io::stdin();
+                let _ = sender.send(prompt(&mut stdin.lock(), &mut io::stderr()));
+            });
+            match receiver.await {
+                Ok(Ok(Some(files))) => Ok(files),
+                Ok(Ok(None)) => Err(Error::Response(ResponseError::Cancelled)),
+                Ok(Err(_)) | Err(_) => Err(err),
+            }
+        }
+        response => response,
+    }
+}
+
+pub(super) fn open_file(
+    input: &mut dyn BufRead,
+    output: &mut dyn Write,
+    title: &str,
+    options: &OpenFileOptions,
+) -> io::Result<Option<SelectedFiles>> {
+    let directory = options.directory.unwrap_or(false);
+    let multiple = options.multiple.unwrap_or(false);
+    let kind = if directory { "folder" } else { "file" };
+    writeln!(output, "{}", title)?;
+    let mut paths = Vec::new();
+    loop {
+        if multiple {
+            write!(output, "Path of a {} to open, empty to finish: ", kind)?;
+        } else {
+            write!(output, "Path of the {} to open, empty to cancel: ", kind)?;
+        }
+        output.flush()?;
+        let path = match read_path(input)? {
+            Some(path) => path,
+            None => break,
+        };
+        if directory && !path.is_dir() || !directory && !path.is_file() {
+            writeln!(output, "No such {}: {}", kind, path.display())?;
+            continue;
+        }
+        paths.push(path);
+        if !multiple {
+            break;
+        }
+    }
+    Ok(selected(paths, &options.choices))
+}
+
+pub(super) fn save_file(
+    input: &mut dyn BufRead,
+    output: &mut dyn Write,
+    title: &str,
+    options: &SaveFileOptions,
+) -> io::Result<Option<SelectedFiles>> {
+    let current = options.current_file().or_else(|| {
+        let name = options.current_name()?;
+        Some(options.current_folder().unwrap_or_default().join(name))
+    });
+    writeln!(output, "{}", title)?;
+    write!(output, "Path to save to")?;
+    if let Some(current) = &current {
+        write!(output, " [{}]", current.display())?;
+    }
+    write!(output, ", empty to {}: ", empty_action(&current))?;
+    output.flush()?;
+    let path = read_path(input)?.or(current);
+    Ok(path.and_then(|path| selected(vec![path], &options.choices)))
+}
+
+pub(super) fn save_files(
+    input: &mut dyn BufRead,
+    output: &mut dyn Write,
+    title: &str,
+    options: &SaveFilesOptions,
+) -> io::Result<Option<SelectedFiles>> {
+    let current = options.current_folder();
+    writeln!(output, "{}", title)?;
+    let folder = loop {
+        write!(output, "Folder to save into")?;
+        if let Some(current) = &current {
+            write!(output, " [{}]", current.display())?;
+        }
+        write!(output, ", empty to {}: ", empty_action(&current))?;
+        output.flush()?;
+        match read_path(input)?.or_else(|| current.clone()) {
+            Some(folder) if folder.is_dir() => break absolute(folder),
+            Some(folder) => writeln!(output, "No such folder: {}", folder.display())?,
+            None => return Ok(None),
+        }
+    };
+    let paths = options
+        .files()
+        .into_iter()
+        .map(|file| folder.join(file))
+        .collect();
+    Ok(selected(paths, &options.choices))
+}
+
+// The next path typed by the user, None on an empty line or at the end of the
+// input.
+fn read_path(input: &mut dyn BufRead) -> io::Result<Option<PathBuf>> {
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    let line = line.trim();
+    Ok((!line.is_empty()).then(|| PathBuf::from(line)))
+}
+
+fn empty_action(current: &Option<PathBuf>) -> &'static str {
+    if current.is_some() {
+        "accept"
+    } else {
+        "cancel"
+    }
+}
+
+fn absolute(path: PathBuf) -> PathBuf {
+    if path.is_absolute() {
+        path
+    } else {
+        std::env::current_dir()
+            .map(|dir| dir.join(&path))
+            .unwrap_or(path)
+    }
+}
+
+// The files selected with the initial value of each choice, None if nothing
+// was selected.
+fn selected(paths: Vec<PathBuf>, choices: &[Choice]) -> Option<SelectedFiles> {
+    let uris = paths
+        .into_iter()
+        .map(|path| Url::from_file_path(absolute(path)))
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    if uris.is_empty() {
+        return None;
+    }
+    let choices = choices
+        .iter()
+        .map(|choice| {
+            (
+                choice.id().to_owned(),
+                choice.initial_selection().to_owned(),
+            )
+        })
+        .collect::<Vec<_>>();
+    Some(SelectedFiles {
+        uris: Uris(uris),
+        choices: (!choices.is_empty()).then_some(choices),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::{io::Cursor, path::Path};
+
+    use zbus::fdo;
+
+    use super::{is_backend_missing, open_file, save_files};
+    use crate::{
+        desktop::{
+            file_chooser::{Choice, OpenFileOptions, SaveFilesOptions},
+            request::ResponseError,
+        },
+        Error,
+    };
+
+    #[test]
+    fn backend_missing() {
+        let missing = Error::from(fdo::Error::ServiceUnknown("no portal".to_owned()));
+        assert!(is_backend_missing(&missing));
+        assert!(!is_backend_missing(&Error::Response(
+            ResponseError::Cancelled
+        )));
+    }
+
+    #[test]
+    fn prompt() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let manifest = root.join("Cargo.toml");
+        let options = OpenFileOptions::default()
+            .with_multiple(true)
+            .add_choice(Choice::boolean("readonly", "Read only", true));
+        let mut input = Cursor::new(format!("/no/such/file\n{}\n\n", manifest.display()));
+        let files = open_file(&mut input, &mut Vec::new(), "Open", &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(files.uris()[0].to_file_path().unwrap(), manifest);
+        assert_eq!(files.boolean_choice("readonly"), Some(true));
+
+        let options = SaveFilesOptions::default()
+            .with_current_folder(root)
+            .unwrap()
+            .with_files(&["a.txt"])
+            .unwrap();
+        let files = save_files(&mut Cursor::new("\n"), &mut Vec::new(), "Save", &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(files.uris()[0].to_file_path().unwrap(), root.join("a.txt"));
+
+        let options = OpenFileOptions::default();
+        let files = open_file(&mut Cursor::new(""), &mut Vec::new(), "Open", &options).unwrap();
+        assert!(files.is_none());
+    }
+}
@@ -18,13 +18,14 @@
 
 use std::{fmt::Debug, os::unix::io::AsRawFd};
 
-use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
 use zbus::zvariant::{Fd, Type};
 
-use super::{DESTINATION, PATH};
-use crate::{error::PortalError, helpers::call_method, Error};
+use super::{DESTINATION, INTERFACE_GAME_MODE, PATH};
+use crate::{error::PortalError, Error};
 
-#[derive(Serialize, Deserialize, PartialEq, Debug, Type)]
+#[derive(Serialize_repr, Deserialize_repr, PartialEq, Eq, Debug, Clone, Copy, Type)]
+#[repr(i32)]
 /// The status of the game mode.
 pub enum Status {
     /// GameMode is inactive.
@@ -37,7 +38,8 @@ pub enum Status {
     Rejected = -1,
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Debug, Type)]
+#[derive(Serialize_repr, Deserialize_repr, PartialEq, Eq, Debug, Type)]
+#[repr(i32)]
 /// The status of a (un-)register game mode request.
 enum RegisterStatus {
     /// If the game was successfully (un-)registered.
@@ -46,6 +48,15 @@ enum RegisterStatus {
     Rejected = -1,
 }
 
+impl RegisterStatus {
+    fn into_result(self) -> Result<(), Error> {
+        match self {
+            RegisterStatus::Success => Ok(()),
+            RegisterStatus::Rejected => Err(Error::Portal(PortalError::Failed)),
+        }
+    }
+}
+
 /// The interface lets sandboxed applications access GameMode from within the
 /// sandbox.
 ///
@@ -70,20 +81,23 @@ enum RegisterStatus {
 /// un-register the client. This might happen with a (small) delay.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.GameMode`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.GameMode).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.GameMode")]
-pub struct GameModeProxy<'a>(zbus::Proxy<'a>);
+pub struct GameModeProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(GameModeProxy, INTERFACE_GAME_MODE);
+impl_proxy_timeout!(GameModeProxy);
 
 impl<'a> GameModeProxy<'a> {
     /// Create a new instance of [`GameModeProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<GameModeProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.GameMode")?
+            .interface(INTERFACE_GAME_MODE)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -91,20 +105,39 @@ impl<'a> GameModeProxy<'a> {
         &self.0
     }
 
-    /// Query the GameMode status for a process.
-    /// If the caller is running inside a sandbox with pid namespace isolation,
-    /// the pid will be translated to the respective host pid.
-    ///
-    /// # Arguments
-    ///
-    /// * `pid` - Process id to query the GameMode status of.
-    ///
-    /// # Specifications
-    ///
-    /// See also [`QueryStatus`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-GameMode.QueryStatus).
-    #[doc(alias = "QueryStatus")]
-    pub async fn query_status(&self, pid: u32) -> Result<Status, Error> {
-        call_method(self.inner(), "QueryStatus", &(pid)).await
+    portal_members! {
+        /// Query the GameMode status for a process.
+        /// If the caller is running inside a sandbox with pid namespace isolation,
+        /// the pid will be translated to the respective host pid.
+        ///
+        /// # Arguments
+        ///
+        /// * `pid` - Process id to query the GameMode status of.
+        ///
+        /// # Specifications
+        ///
+        /// See also [`QueryStatus`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-GameMode.QueryStatus).
+        pub fn query_status(pid: i32) -> Status = method "QueryStatus";
+
+        /// Query the GameMode status for a process.
+        ///
+        /// # Arguments
+        ///
+        /// * `target` - Process id to query the GameMode status of.
+        /// * `requester` - Process id of the process requesting the information.
+        ///
+        /// # Specifications
+        ///
+        /// See also [`QueryStatusByPid`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-GameMode.QueryStatusByPid).
+        pub fn query_status_by_pid(target: i32, requester: i32) -> Status = method "QueryStatusByPid";
+
+        fn query_status_by_pidfd_raw(target: Fd, requester: Fd) -> Status = method "QueryStatusByPIDFd";
+        fn register_game_raw(pid: i32) -> RegisterStatus = method "RegisterGame";
+        fn register_game_by_pid_raw(target: i32, requester: i32) -> RegisterStatus = method "RegisterGameByPid";
+        fn register_game_by_pidfd_raw(target: Fd, requester: Fd) -> RegisterStatus = method "RegisterGameByPIDFd";
+        fn unregister_game_raw(pid: i32) -> RegisterStatus = method "UnregisterGame";
+        fn unregister_game_by_pid_raw(target: i32, requester: i32) -> RegisterStatus = method "UnregisterGameByPid";
+        fn unregister_game_by_pidfd_raw(target: Fd, requester: Fd) -> RegisterStatus = method "UnregisterGameByPIDFd";
     }
 
     /// Query the GameMode status for a process.
@@ -124,32 +157,13 @@ impl<'a> GameModeProxy<'a> {
         target: &impl AsRawFd,
         requester: &impl AsRawFd,
     ) -> Result<Status, Error> {
-        call_method(
-            self.inner(),
-            "QueryStatusByPIDFd",
-            &(
-                Fd::from(target.as_raw_fd()),
-                Fd::from(requester.as_raw_fd()),
-            ),
+        self.query_status_by_pidfd_raw(
+            Fd::from(target.as_raw_fd()),
+            Fd::from(requester.as_raw_fd()),
         )
         .await
     }
 
-    /// Query the GameMode status for a process.
-    ///
-    /// # Arguments
-    ///
-    /// * `target` - Process id to query the GameMode status of.
-    /// * `requester` - Process id of the process requesting the information.
-    ///
-    /// # Specifications
-    ///
-    /// See also [`QueryStatusByPid`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-GameMode.QueryStatusByPid).
-    #[doc(alias = "QueryStatusByPid")]
-    pub async fn query_status_by_pid(&self, target: u32, requester: u32) -> Result<Status, Error> {
-        call_method(self.inner(), "QueryStatusByPid", &(target, requester)).await
-    }
-
     /// Register a game with GameMode and thus request GameMode to be activated.
     /// If the caller is running inside a sandbox with pid namespace isolation,
     /// the pid will be translated to the respective host pid. See the general
@@ -164,12 +178,8 @@ impl<'a> GameModeProxy<'a> {
     ///
     /// See also [`RegisterGame`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-GameMode.RegisterGame).
     #[doc(alias = "RegisterGame")]
-    pub async fn register_game(&self, pid: u32) -> Result<(), Error> {
-        let status = call_method(self.inner(), "RegisterGame", &(pid)).await?;
-        match status {
-            RegisterStatus::Success => Ok(()),
-            RegisterStatus::Rejected => Err(Error::Portal(PortalError::Failed)),
-        }
+    pub async fn register_game(&self, pid: i32) -> Result<(), Error> {
+        self.register_game_raw(pid).await?.into_result()
     }
 
     /// Register a game with GameMode.
@@ -189,19 +199,12 @@ impl<'a> GameModeProxy<'a> {
         target: &impl AsRawFd,
         requester: &impl AsRawFd,
     ) -> Result<(), Error> {
-        let status = call_method(
-            self.inner(),
-            "RegisterGameByPIDFd",
-            &(
-                Fd::from(target.as_raw_fd()),
-                Fd::from(requester.as_raw_fd()),
-            ),
+        self.register_game_by_pidfd_raw(
+            Fd::from(target.as_raw_fd()),
+            Fd::from(requester.as_raw_fd()),
         )
-        .await?;
-        match status {
-            RegisterStatus::Success => Ok(()),
-            RegisterStatus::Rejected => Err(Error::Portal(PortalError::Failed)),
-        }
+        .await?
+        .into_result()
     }
 
     /// Register a game with GameMode.
@@ -215,12 +218,10 @@ impl<'a> GameModeProxy<'a> {
     ///
     /// See also [`RegisterGameByPid`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-GameMode.RegisterGameByPid).
     #[doc(alias = "RegisterGameByPid")]
-    pub async fn register_game_by_pid(&self, target: u32, requester: u32) -> Result<(), Error> {
-        let status = call_method(self.inner(), "RegisterGameByPid", &(target, requester)).await?;
-        match status {
-            RegisterStatus::Success => Ok(()),
-            RegisterStatus::Rejected => Err(Error::Portal(PortalError::Failed)),
-        }
+    pub async fn register_game_by_pid(&self, target: i32, requester: i32) -> Result<(), Error> {
+        self.register_game_by_pid_raw(target, requester)
+            .await?
+            .into_result()
     }
 
     /// Un-register a game from GameMode.
@@ -237,12 +238,8 @@ impl<'a> GameModeProxy<'a> {
     ///
     /// See also [`UnregisterGame`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-GameMode.UnregisterGame).
     #[doc(alias = "UnregisterGame")]
-    pub async fn unregister_game(&self, pid: u32) -> Result<(), Error> {
-        let status = call_method(self.inner(), "UnregisterGame", &(pid)).await?;
-        match status {
-            RegisterStatus::Success => Ok(()),
-            RegisterStatus::Rejected => Err(Error::Portal(PortalError::Failed)),
-        }
+    pub async fn unregister_game(&self, pid: i32) -> Result<(), Error> {
+        self.unregister_game_raw(pid).await?.into_result()
     }
 
     /// Un-register a game from GameMode.
@@ -262,19 +259,12 @@ impl<'a> GameModeProxy<'a> {
         target: &impl AsRawFd,
         requester: &impl AsRawFd,
     ) -> Result<(), Error> {
-        let status = call_method(
-            self.inner(),
-            "UnregisterGameByPIDFd",
-            &(
-                Fd::from(target.as_raw_fd()),
-                Fd::from(requester.as_raw_fd()),
-            ),
+        self.unregister_game_by_pidfd_raw(
+            Fd::from(target.as_raw_fd()),
+            Fd::from(requester.as_raw_fd()),
         )
-        .await?;
-        match status {
-            RegisterStatus::Success => Ok(()),
-            RegisterStatus::Rejected => Err(Error::Portal(PortalError::Failed)),
-        }
+        .await?
+        .into_result()
     }
 
     /// Un-register a game from GameMode.
@@ -289,11 +279,20 @@ impl<'a> GameModeProxy<'a> {
     ///
     /// See also [`UnregisterGameByPid`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-GameMode.UnregisterGameByPid).
     #[doc(alias = "UnregisterGameByPid")]
-    pub async fn unregister_game_by_pid(&self, target: u32, requester: u32) -> Result<(), Error> {
-        let status = call_method(self.inner(), "UnregisterGameByPid", &(target, requester)).await?;
-        match status {
-            RegisterStatus::Success => Ok(()),
-            RegisterStatus::Rejected => Err(Error::Portal(PortalError::Failed)),
-        }
+    pub async fn unregister_game_by_pid(&self, target: i32, requester: i32) -> Result<(), Error> {
+        self.unregister_game_by_pid_raw(target, requester)
+            .await?
+            .into_result()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::GameModeProxy;
+    use crate::{desktop::INTERFACE_GAME_MODE, helpers::assert_members};
+
+    #[test]
+    fn members() {
+        assert_members(INTERFACE_GAME_MODE, &GameModeProxy::members());
     }
 }
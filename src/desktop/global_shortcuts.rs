@@ -0,0 +1,376 @@
+//! # Examples
+//!
+//! ```rust,no_run
+//! use ashpd::desktop::global_shortcuts::{GlobalShortcutsProxy, NewShortcut};
+//! use ashpd::WindowIdentifier;
+//! use futures::StreamExt;
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::Connection::session().await?;
+//!     let proxy = GlobalShortcutsProxy::new(&connection).await?;
+//!     let identifier = WindowIdentifier::default();
+//!
+//!     let session = proxy.create_session().await?;
+//!     let shortcuts = [NewShortcut::new("mute", "Mute the microphone").with_preferred_trigger("CTRL+M")];
+//!     let bound = proxy.bind_shortcuts(&session, &shortcuts, &identifier).await?;
+//!     for shortcut in bound.shortcuts() {
+//!         println!("{}: {}", shortcut.id(), shortcut.trigger_description());
+//!     }
+//!
+//!     let mut activated = proxy.receive_activated().await?;
+//!     while let Some(activated) = activated.next().await {
+//!         println!("{} activated", activated.shortcut_id());
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::{collections::HashMap, fmt::Debug, time::Duration};
+
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use zbus::zvariant::{
+    DeserializeDict, ObjectPath, OwnedObjectPath, OwnedValue, SerializeDict, Type,
+};
+
+use super::{
+    GlobalShortcutsPortal, HandleToken, PortalRequest, SessionProxy, DESTINATION,
+    INTERFACE_GLOBAL_SHORTCUTS, PATH,
+};
+use crate::{
+    helpers::{call_request_method, call_window_request_method, receive_signal_stream},
+    Error, WindowIdentifier,
+};
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
+/// Specified options for a [`GlobalShortcutsProxy::create_session`] request.
+#[zvariant(signature = "dict")]
+struct CreateSessionOptions {
+    /// A string that will be used as the last element of the handle.
+    handle_token: HandleToken,
+    /// A string that will be used as the last element of the session handle.
+    session_handle_token: HandleToken,
+}
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
+/// Specified options for the [`GlobalShortcutsProxy::bind_shortcuts`] and
+/// [`GlobalShortcutsProxy::list_shortcuts`] requests.
+#[zvariant(signature = "dict")]
+struct ShortcutsOptions {
+    /// A string that will be used as the last element of the handle.
+    handle_token: HandleToken,
+}
+
+#[derive(Debug, SerializeDict, DeserializeDict, Type, Clone, PartialEq, Eq)]
+/// A response to a [`GlobalShortcutsProxy::create_session`] request.
+#[zvariant(signature = "dict")]
+struct CreateSession {
+    // The handle is sent as a string, see `inhibit::CreateMonitor`.
+    session_handle: String,
+}
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
+#[zvariant(signature = "dict")]
+struct NewShortcutInfo {
+    description: String,
+    preferred_trigger: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone, PartialEq, Eq)]
+/// A shortcut to bind with [`GlobalShortcutsProxy::bind_shortcuts`].
+pub struct NewShortcut(String, NewShortcutInfo);
+
+impl NewShortcut {
+    /// A shortcut identified by `id` in the
+    /// [`GlobalShortcutsProxy::receive_activated`] signals, with a
+    /// user-visible `description` of its action.
+    pub fn new(id: impl Into<String>, description: impl Into<String>) -> Self {
+        Self(
+            id.into(),
+            NewShortcutInfo {
+                description: description.into(),
+                preferred_trigger: None,
+            },
+        )
+    }
+
+    /// Sets the keys the application would like to trigger the shortcut, in
+    /// the format of the [shortcuts
+    /// specification](https://specifications.freedesktop.org/shortcuts-spec/latest/),
+    /// `CTRL+ALT+Escape` for example. The user has the final word.
+    #[must_use]
+    pub fn with_preferred_trigger<'a>(mut self, trigger: impl Into<Option<&'a str>>) -> Self {
+        self.1.preferred_trigger = trigger.into().map(ToOwned::to_owned);
+        self
+    }
+
+    /// The id of the shortcut.
+    pub fn id(&self) -> &str {
+        &self.0
+    }
+
+    /// The description of the action of the shortcut.
+    pub fn description(&self) -> &str {
+        &self.1.description
+    }
+
+    /// The preferred trigger, if set.
+    pub fn preferred_trigger(&self) -> Option<&str> {
+        self.1.preferred_trigger.as_deref()
+    }
+}
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
+#[zvariant(signature = "dict")]
+struct ShortcutInfo {
+    description: String,
+    trigger_description: String,
+}
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone, PartialEq, Eq)]
+/// A shortcut bound to a session.
+pub struct Shortcut(String, ShortcutInfo);
+
+impl Shortcut {
+    /// The id of the shortcut.
+    pub fn id(&self) -> &str {
+        &self.0
+    }
+
+    /// The description of the action of the shortcut.
+    pub fn description(&self) -> &str {
+        &self.1.description
+    }
+
+    /// A user-visible description of the keys triggering the shortcut.
+    pub fn trigger_description(&self) -> &str {
+        &self.1.trigger_description
+    }
+}
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Clone, PartialEq, Eq)]
+/// A response to the [`GlobalShortcutsProxy::bind_shortcuts`] and
+/// [`GlobalShortcutsProxy::list_shortcuts`] requests.
+#[zvariant(signature = "dict")]
+pub struct Shortcuts {
+    shortcuts: Vec<Shortcut>,
+}
+
+impl Shortcuts {
+    /// The shortcuts bound to the session.
+    pub fn shortcuts(&self) -> &[Shortcut] {
+        &self.shortcuts
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Type)]
+/// A shortcut pressed or released, received with
+/// [`GlobalShortcutsProxy::receive_activated`] or
+/// [`GlobalShortcutsProxy::receive_deactivated`].
+pub struct Activation(OwnedObjectPath, String, u64, HashMap<String, OwnedValue>);
+
+impl Activation {
+    /// The handle of the session of the shortcut.
+    pub fn session_handle(&self) -> ObjectPath<'_> {
+        self.0.as_ref()
+    }
+
+    /// The id of the shortcut.
+    pub fn shortcut_id(&self) -> &str {
+        &self.1
+    }
+
+    /// The time of the activation, with a millisecond granularity.
+    pub fn timestamp(&self) -> Duration {
+        Duration::from_millis(self.2)
+    }
+
+    /// The options sent alongside the activation.
+    pub fn options(&self) -> &HashMap<String, OwnedValue> {
+        &self.3
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Type)]
+/// The shortcuts of a session changed by the user, received with
+/// [`GlobalShortcutsProxy::receive_shortcuts_changed`].
+pub struct ShortcutsChanged(OwnedObjectPath, Vec<Shortcut>);
+
+impl ShortcutsChanged {
+    /// The handle of the session of the shortcuts.
+    pub fn session_handle(&self) -> ObjectPath<'_> {
+        self.0.as_ref()
+    }
+
+    /// The shortcuts bound to the session.
+    pub fn shortcuts(&self) -> &[Shortcut] {
+        &self.1
+    }
+}
+
+/// A session created by [`GlobalShortcutsProxy::create_session`].
+pub type GlobalShortcutsSession<'a> = SessionProxy<'a, GlobalShortcutsPortal>;
+
+/// The interface lets sandboxed applications bind shortcuts triggered while
+/// they are not focused.
+///
+/// Wrapper of the DBus interface: [`org.freedesktop.portal.GlobalShortcuts`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.GlobalShortcuts).
+#[derive(Clone)]
+#[doc(alias = "org.freedesktop.portal.GlobalShortcuts")]
+pub struct GlobalShortcutsProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(GlobalShortcutsProxy, INTERFACE_GLOBAL_SHORTCUTS);
+impl_proxy_timeout!(GlobalShortcutsProxy);
+
+impl<'a> GlobalShortcutsProxy<'a> {
+    /// Create a new instance of [`GlobalShortcutsProxy`].
+    pub async fn new(connection: &zbus::Connection) -> Result<GlobalShortcutsProxy<'a>, Error> {
+        let proxy = zbus::ProxyBuilder::new_bare(connection)
+            .interface(INTERFACE_GLOBAL_SHORTCUTS)?
+            .path(PATH)?
+            .destination(DESTINATION)?
+            .build()
+            .await?;
+        Ok(Self(proxy, None))
+    }
+
+    /// Get a reference to the underlying Proxy.
+    pub fn inner(&self) -> &zbus::Proxy<'_> {
+        &self.0
+    }
+
+    /// Create a global shortcuts session, the shortcuts are bound for its
+    /// lifetime.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`CreateSession`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-GlobalShortcuts.CreateSession).
+    #[doc(alias = "CreateSession")]
+    pub fn create_session(&self) -> PortalRequest<'_, GlobalShortcutsSession<'a>> {
+        let options = CreateSessionOptions::default();
+        let handle_token = options.handle_token.copy();
+        let session_handle_token = options.session_handle_token.copy();
+        call_request_method::<CreateSession, _>(self, &handle_token, "CreateSession", options).then(
+            move |session| async move {
+                let proxy: GlobalShortcutsSession<'a> = SessionProxy::from_unique_name(
+                    self.inner().connection(),
+                    &session_handle_token,
+                )
+                .await?;
+                assert_eq!(proxy.inner().path().as_str(), &session.session_handle);
+                Ok(proxy)
+            },
+        )
+    }
+
+    /// Bind `shortcuts` to `session`, the portal may let the user review and
+    /// change their triggers.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - A [`GlobalShortcutsSession`], created with
+    ///   [`create_session()`][`GlobalShortcutsProxy::create_session`].
+    /// * `shortcuts` - The shortcuts to bind.
+    /// * `identifier` - Identifier for the application window.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`BindShortcuts`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-GlobalShortcuts.BindShortcuts).
+    #[doc(alias = "BindShortcuts")]
+    pub fn bind_shortcuts<'p>(
+        &'p self,
+        session: &'p GlobalShortcutsSession<'_>,
+        shortcuts: &'p [NewShortcut],
+        identifier: &'p WindowIdentifier,
+    ) -> PortalRequest<'p, Shortcuts> {
+        let options = ShortcutsOptions::default();
+        let handle_token = options.handle_token.copy();
+        call_window_request_method(
+            self,
+            identifier,
+            &handle_token,
+            "BindShortcuts",
+            (session, shortcuts, identifier, options),
+        )
+    }
+
+    /// The shortcuts bound to `session`.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`ListShortcuts`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-GlobalShortcuts.ListShortcuts).
+    #[doc(alias = "ListShortcuts")]
+    pub fn list_shortcuts<'p>(
+        &'p self,
+        session: &'p GlobalShortcutsSession<'_>,
+    ) -> PortalRequest<'p, Shortcuts> {
+        let options = ShortcutsOptions::default();
+        let handle_token = options.handle_token.copy();
+        call_request_method(self, &handle_token, "ListShortcuts", (session, options))
+    }
+
+    /// Signal emitted when a shortcut is pressed, for all the sessions of the
+    /// application.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`Activated`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-GlobalShortcuts.Activated).
+    #[doc(alias = "Activated")]
+    pub async fn receive_activated(
+        &self,
+    ) -> Result<impl Stream<Item = Activation> + Unpin + 'a, Error> {
+        receive_signal_stream(&self.0, "Activated").await
+    }
+
+    /// Signal emitted when a shortcut is released.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`Deactivated`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-GlobalShortcuts.Deactivated).
+    #[doc(alias = "Deactivated")]
+    pub async fn receive_deactivated(
+        &self,
+    ) -> Result<impl Stream<Item = Activation> + Unpin + 'a, Error> {
+        receive_signal_stream(&self.0, "Deactivated").await
+    }
+
+    /// Signal emitted when the user changed the shortcuts of a session.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`ShortcutsChanged`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-GlobalShortcuts.ShortcutsChanged).
+    #[doc(alias = "ShortcutsChanged")]
+    pub async fn receive_shortcuts_changed(
+        &self,
+    ) -> Result<impl Stream<Item = ShortcutsChanged> + Unpin + 'a, Error> {
+        receive_signal_stream(&self.0, "ShortcutsChanged").await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use zbus::zvariant::{from_slice, to_bytes, EncodingContext, Type};
+
+    use super::{NewShortcut, Shortcut, ShortcutInfo};
+
+    #[test]
+    fn shortcuts() {
+        assert_eq!(NewShortcut::signature(), "(sa{sv})");
+        let ctxt = EncodingContext::<byteorder::LE>::new_dbus(0);
+        let shortcut = NewShortcut::new("release", "Release the input")
+            .with_preferred_trigger("CTRL+ALT+Escape");
+        let bytes = to_bytes(ctxt, &shortcut).unwrap();
+        let decoded: NewShortcut = from_slice(&bytes, ctxt).unwrap();
+        assert_eq!(decoded, shortcut);
+        assert_eq!(decoded.preferred_trigger(), Some("CTRL+ALT+Escape"));
+
+        let info = ShortcutInfo {
+            description: "Release the input".to_owned(),
+            trigger_description: "Ctrl+Alt+Esc".to_owned(),
+        };
+        let bytes = to_bytes(ctxt, &("release", info)).unwrap();
+        let shortcut: Shortcut = from_slice(&bytes, ctxt).unwrap();
+        assert_eq!(shortcut.id(), "release");
+        assert_eq!(shortcut.trigger_description(), "Ctrl+Alt+Esc");
+    }
+}
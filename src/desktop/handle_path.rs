@@ -0,0 +1,115 @@
+use zbus::{
+    names::UniqueName,
+    zvariant::{ObjectPath, OwnedObjectPath},
+};
+
+use crate::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The kind of object the portal exports for a call, under
+/// `/org/freedesktop/portal/desktop/KIND/SENDER/TOKEN`.
+pub enum HandleKind {
+    /// An `org.freedesktop.portal.Request`, alive until its `Response`.
+    Request,
+    /// An `org.freedesktop.portal.Session`, alive until it is closed.
+    Session,
+}
+
+impl HandleKind {
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::Request => "/org/freedesktop/portal/desktop/request/",
+            Self::Session => "/org/freedesktop/portal/desktop/session/",
+        }
+    }
+}
+
+/// The `SENDER` element of the handles of the calls made by `unique_name`,
+/// `1_42` for `:1.42`.
+pub fn sender_token(unique_name: &UniqueName<'_>) -> String {
+    unique_name.trim_start_matches(':').replace('.', "_")
+}
+
+/// The path of the object the portal exports for the call made by
+/// `unique_name` with `handle_token`.
+///
+/// Knowing the path before making the call allows to listen to the signals of
+/// the object before the portal can emit them.
+///
+/// ```rust
+/// use ashpd::desktop::{handle_path, HandleKind};
+/// use zbus::names::UniqueName;
+///
+/// let sender = UniqueName::try_from(":1.42").unwrap();
+/// let path = handle_path(HandleKind::Request, &sender, "ashpd_token").unwrap();
+/// assert_eq!(path.as_str(), "/org/freedesktop/portal/desktop/request/1_42/ashpd_token");
+/// ```
+pub fn handle_path(
+    kind: HandleKind,
+    unique_name: &UniqueName<'_>,
+    handle_token: &str,
+) -> Result<OwnedObjectPath, Error> {
+    let path = format!(
+        "{}{}/{}",
+        kind.prefix(),
+        sender_token(unique_name),
+        handle_token
+    );
+    Ok(ObjectPath::try_from(path)?.into())
+}
+
+/// Split the handle `path` of a `kind` object into its `(sender_token,
+/// handle_token)` elements, None if it isn't such a handle.
+///
+/// ```rust
+/// use ashpd::desktop::{parse_handle_path, HandleKind};
+/// use zbus::zvariant::ObjectPath;
+///
+/// let path = ObjectPath::try_from("/org/freedesktop/portal/desktop/session/1_42/ashpd_token").unwrap();
+/// assert_eq!(
+///     parse_handle_path(HandleKind::Session, &path),
+///     Some(("1_42", "ashpd_token"))
+/// );
+/// assert_eq!(parse_handle_path(HandleKind::Request, &path), None);
+/// ```
+pub fn parse_handle_path<'p>(
+    kind: HandleKind,
+    path: &'p ObjectPath<'_>,
+) -> Option<(&'p str, &'p str)> {
+    let elements = path.as_str().strip_prefix(kind.prefix())?;
+    match elements.split_once('/') {
+        Some((sender, token))
+            if !sender.is_empty() && !token.is_empty() && !token.contains('/') =>
+        {
+            Some((sender, token))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use zbus::{names::UniqueName, zvariant::ObjectPath};
+
+    use super::{handle_path, parse_handle_path, sender_token, HandleKind};
+
+    #[test]
+    fn round_trip() {
+        let sender = UniqueName::try_from(":1.2").unwrap();
+        assert_eq!(sender_token(&sender), "1_2");
+        let path = handle_path(HandleKind::Session, &sender, "ashpd_1").unwrap();
+        assert_eq!(
+            parse_handle_path(HandleKind::Session, &path),
+            Some(("1_2", "ashpd_1"))
+        );
+
+        for path in [
+            "/org/freedesktop/portal/desktop/request/1_2",
+            "/org/freedesktop/portal/desktop/request/1_2/ashpd_1/child",
+            "/org/freedesktop/portal/desktop",
+        ] {
+            let path = ObjectPath::try_from(path).unwrap();
+            assert_eq!(parse_handle_path(HandleKind::Request, &path), None);
+        }
+    }
+}
@@ -15,9 +15,39 @@ use zbus::{names::OwnedMemberName, zvariant::Type};
 ///
 /// A valid object path element must only contain the ASCII characters
 /// `[A-Z][a-z][0-9]_`
-#[derive(Clone, PartialEq, Hash, Serialize, Deserialize, Type)]
+///
+/// The token is unique to a request rather than part of its options. A clone
+/// gets a new token, so that options cloned and sent twice don't share the
+/// path of their requests. All the tokens are equal, so that the options are
+/// compared without them. `HandleToken::copy` keeps the same token.
+#[derive(Serialize, Deserialize, Type)]
 pub struct HandleToken(OwnedMemberName);
 
+impl HandleToken {
+    pub(crate) fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// The same token, to compute the path of the request sending it.
+    pub(crate) fn copy(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl Clone for HandleToken {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl PartialEq for HandleToken {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for HandleToken {}
+
 impl Display for HandleToken {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(&self.0)
@@ -97,4 +127,12 @@ mod test {
 
         HandleToken::default(); // ensure we don't panic
     }
+
+    #[test]
+    fn clone() {
+        let token = HandleToken::default();
+        assert_ne!(token.clone().as_str(), token.as_str());
+        assert_eq!(token.copy().as_str(), token.as_str());
+        assert_eq!(token, HandleToken::default());
+    }
 }
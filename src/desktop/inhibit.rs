@@ -5,6 +5,7 @@
 //! ```rust,no_run
 //! use ashpd::desktop::inhibit::{InhibitFlags, InhibitProxy, SessionState};
 //! use ashpd::WindowIdentifier;
+//! use futures::StreamExt;
 //! use std::{thread, time};
 //!
 //! async fn run() -> ashpd::Result<()> {
@@ -12,43 +13,56 @@
 //!     let proxy = InhibitProxy::new(&connection).await?;
 //!     let identifier = WindowIdentifier::default();
 //!
+//!     let mut states = proxy.receive_state_changed().await?;
 //!     let session = proxy.create_monitor(&identifier).await?;
 //!
-//!     let state = proxy.receive_state_changed().await?;
-//!     match state.session_state() {
-//!         SessionState::Running => (),
-//!         SessionState::QueryEnd => {
-//!             proxy
-//!                 .inhibit(
-//!                     &identifier,
-//!                     InhibitFlags::Logout | InhibitFlags::UserSwitch,
-//!                     "please save the opened project first",
-//!                 )
-//!                 .await?;
-//!             thread::sleep(time::Duration::from_secs(1));
-//!             proxy.query_end_response(&session).await?;
-//!         }
-//!         SessionState::Ending => {
-//!             println!("ending the session");
+//!     while let Some(state) = states.next().await {
+//!         match state.session_state() {
+//!             SessionState::QueryEnd => {
+//!                 proxy
+//!                     .inhibit(
+//!                         &identifier,
+//!                         InhibitFlags::Logout | InhibitFlags::UserSwitch,
+//!                         "please save the opened project first",
+//!                     )
+//!                     .await?;
+//!                 thread::sleep(time::Duration::from_secs(1));
+//!                 proxy.query_end_response(&session).await?;
+//!             }
+//!             SessionState::Ending => {
+//!                 println!("ending the session");
+//!                 break;
+//!             }
+//!             _ => (),
 //!         }
 //!     }
 //!     Ok(())
 //! }
 //! ```
 
+use std::{future::Future, pin::Pin, sync::Mutex};
+
 use enumflags2::{bitflags, BitFlags};
-use futures::TryFutureExt;
+use futures::{
+    future::{self, Either},
+    stream, Stream, StreamExt,
+};
 use serde::{Deserialize, Serialize};
-use serde_repr::{Deserialize_repr, Serialize_repr};
-use zbus::zvariant::{DeserializeDict, OwnedObjectPath, SerializeDict, Type};
+use zbus::zvariant::{DeserializeDict, ObjectPath, OwnedObjectPath, SerializeDict, Type};
 
-use super::{HandleToken, SessionProxy, DESTINATION, PATH};
+use super::{
+    HandleToken, InhibitPortal, PortalRequest, SessionGuard, SessionProxy, DESTINATION,
+    INTERFACE_INHIBIT, PATH,
+};
 use crate::{
-    helpers::{call_basic_response_method, call_method, call_request_method, receive_signal},
+    helpers::{
+        call_method, call_request_method, call_window_basic_response_method, receive_signal_stream,
+        session_connection,
+    },
     Error, WindowIdentifier,
 };
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
 /// Specified options for a [`InhibitProxy::create_monitor`] request.
 #[zvariant(signature = "dict")]
 struct CreateMonitorOptions {
@@ -58,7 +72,7 @@ struct CreateMonitorOptions {
     session_handle_token: HandleToken,
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
 /// Specified options for a [`InhibitProxy::inhibit`] request.
 #[zvariant(signature = "dict")]
 struct InhibitOptions {
@@ -69,18 +83,21 @@ struct InhibitOptions {
 }
 
 impl InhibitOptions {
-    /// Sets a user visible reason for the inhibit request.
-    pub fn reason(mut self, reason: &str) -> Self {
-        self.reason = Some(reason.to_string());
-        self
+    options_setters! {
+        @setters
+        /// Sets a user visible reason for the inhibit request.
+        with_reason(reason: impl Into<String>);
     }
 }
 
 #[bitflags]
-#[derive(Serialize_repr, Deserialize_repr, PartialEq, Debug, Clone, Copy, Type)]
+#[derive(PartialEq, Debug, Clone, Copy, Type)]
 #[repr(u32)]
 #[doc(alias = "XdpInhibitFlags")]
+#[non_exhaustive]
 /// The actions to inhibit that can end the user's session
+///
+/// Converts from and to the `u32` sent on the bus.
 pub enum InhibitFlags {
     #[doc(alias = "XDP_INHIBIT_FLAG_LOGOUT")]
     /// Logout.
@@ -96,16 +113,23 @@ pub enum InhibitFlags {
     Idle,
 }
 
-#[derive(Debug, SerializeDict, DeserializeDict, Type)]
+impl_u32_conversions!(InhibitFlags {
+    Logout = 1,
+    UserSwitch = 2,
+    Suspend = 4,
+    Idle = 8,
+});
+
+#[derive(Debug, SerializeDict, DeserializeDict, Type, Clone, PartialEq, Eq)]
 /// A response to a [`InhibitProxy::create_monitor`] request.
 #[zvariant(signature = "dict")]
-struct CreateMonitor {
+pub(crate) struct CreateMonitor {
     // TODO: investigate why this doesn't return an ObjectPath
     // replace with an ObjectPath once https://github.com/flatpak/xdg-desktop-portal/pull/609's merged
     session_handle: String,
 }
 
-#[derive(Debug, SerializeDict, DeserializeDict, Type)]
+#[derive(Debug, SerializeDict, DeserializeDict, Type, Clone, PartialEq, Eq)]
 #[zvariant(signature = "dict")]
 struct State {
     #[zvariant(rename = "screensaver-active")]
@@ -119,6 +143,11 @@ struct State {
 pub struct InhibitState(OwnedObjectPath, State);
 
 impl InhibitState {
+    /// The handle of the monitor session the state was sent to.
+    pub fn session_handle(&self) -> ObjectPath<'_> {
+        self.0.as_ref()
+    }
+
     /// Whether screensaver is active or not.
     pub fn screensaver_active(&self) -> bool {
         self.1.screensaver_active
@@ -130,9 +159,12 @@ impl InhibitState {
     }
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy, Type)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Type)]
 #[doc(alias = "XdpLoginSessionState")]
+#[non_exhaustive]
 /// The current state of the user's session.
+///
+/// Converts from and to the `u32` sent on the bus.
 pub enum SessionState {
     #[doc(alias = "XDP_LOGIN_SESSION_RUNNING")]
     /// Running.
@@ -145,24 +177,36 @@ pub enum SessionState {
     Ending = 3,
 }
 
+impl_u32_conversions!(SessionState {
+    Running = 1,
+    QueryEnd = 2,
+    Ending = 3,
+});
+
+/// A monitor of the session state, created by [`InhibitProxy::create_monitor`].
+pub type InhibitMonitor<'a> = SessionProxy<'a, InhibitPortal>;
+
 /// The interface lets sandboxed applications inhibit the user session from
 /// ending, suspending, idling or getting switched away.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Inhibit`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Inhibit).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.Inhibit")]
-pub struct InhibitProxy<'a>(zbus::Proxy<'a>);
+pub struct InhibitProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(InhibitProxy, INTERFACE_INHIBIT);
+impl_proxy_timeout!(InhibitProxy);
 
 impl<'a> InhibitProxy<'a> {
     /// Create a new instance of [`InhibitProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<InhibitProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Inhibit")?
+            .interface(INTERFACE_INHIBIT)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -183,23 +227,26 @@ impl<'a> InhibitProxy<'a> {
     /// See also [`CreateMonitor`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Inhibit.CreateMonitor).
     #[doc(alias = "CreateMonitor")]
     #[doc(alias = "xdp_portal_session_monitor_start")]
-    pub async fn create_monitor(
-        &self,
-        identifier: &WindowIdentifier,
-    ) -> Result<SessionProxy<'a>, Error> {
+    pub fn create_monitor<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
+    ) -> PortalRequest<'p, InhibitMonitor<'a>> {
         let options = CreateMonitorOptions::default();
-        let body = &(&identifier, &options);
-        let (monitor, proxy): (CreateMonitor, SessionProxy) = futures::try_join!(
-            call_request_method(self.inner(), &options.handle_token, "CreateMonitor", body)
-                .into_future(),
-            SessionProxy::from_unique_name(
-                self.inner().connection(),
-                &options.session_handle_token
-            )
-            .into_future(),
-        )?;
-        assert_eq!(proxy.inner().path().as_str(), &monitor.session_handle);
-        Ok(proxy)
+        let handle_token = options.handle_token.copy();
+        let session_handle_token = options.session_handle_token.copy();
+        call_request_method::<CreateMonitor, _>(
+            self,
+            &handle_token,
+            "CreateMonitor",
+            (identifier, options),
+        )
+        .then(move |monitor| async move {
+            let proxy: InhibitMonitor<'a> =
+                SessionProxy::from_unique_name(self.inner().connection(), &session_handle_token)
+                    .await?;
+            assert_eq!(proxy.inner().path().as_str(), &monitor.session_handle);
+            Ok(proxy)
+        })
     }
 
     /// Inhibits a session status changes.
@@ -215,20 +262,21 @@ impl<'a> InhibitProxy<'a> {
     /// See also [`Inhibit`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Inhibit.Inhibit).
     #[doc(alias = "Inhibit")]
     #[doc(alias = "xdp_portal_session_inhibit")]
-    pub async fn inhibit(
-        &self,
-        identifier: &WindowIdentifier,
+    pub fn inhibit<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
         flags: BitFlags<InhibitFlags>,
         reason: &str,
-    ) -> Result<(), Error> {
-        let options = InhibitOptions::default().reason(reason);
-        call_basic_response_method(
-            self.inner(),
-            &options.handle_token,
+    ) -> PortalRequest<'p, ()> {
+        let options = InhibitOptions::default().with_reason(reason);
+        let handle_token = options.handle_token.copy();
+        call_window_basic_response_method(
+            self,
+            identifier,
+            &handle_token,
             "Inhibit",
-            &(&identifier, flags, &options),
+            (identifier, flags, options),
         )
-        .await
     }
 
     /// Signal emitted when the session state changes.
@@ -238,8 +286,10 @@ impl<'a> InhibitProxy<'a> {
     /// See also [`StateChanged`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-Inhibit.StateChanged).
     #[doc(alias = "StateChanged")]
     #[doc(alias = "XdpPortal::session-state-changed")]
-    pub async fn receive_state_changed(&self) -> Result<InhibitState, Error> {
-        receive_signal(self.inner(), "StateChanged").await
+    pub async fn receive_state_changed(
+        &self,
+    ) -> Result<impl Stream<Item = InhibitState> + Unpin + 'a, Error> {
+        receive_signal_stream(&self.0, "StateChanged").await
     }
 
     /// Acknowledges that the caller received the "state_changed" signal.
@@ -249,7 +299,7 @@ impl<'a> InhibitProxy<'a> {
     ///
     /// # Arguments
     ///
-    /// * `session` - A [`SessionProxy`], created with
+    /// * `session` - A [`InhibitMonitor`], created with
     ///   [`create_monitor()`][`InhibitProxy::create_monitor`].
     ///
     /// # Specifications
@@ -257,7 +307,187 @@ impl<'a> InhibitProxy<'a> {
     /// See also [`QueryEndResponse`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-Inhibit.QueryEndResponse).
     #[doc(alias = "QueryEndResponse")]
     #[doc(alias = "xdp_portal_session_monitor_query_end_response")]
-    pub async fn query_end_response(&self, session: &SessionProxy<'_>) -> Result<(), Error> {
-        call_method(self.inner(), "QueryEndResponse", &(session)).await
+    pub async fn query_end_response(&self, session: &InhibitMonitor<'_>) -> Result<(), Error> {
+        call_method(self, "QueryEndResponse", &(session)).await
+    }
+}
+
+/// A monitor of the session state that outlives its session.
+///
+/// The backend may close the session created by
+/// [`InhibitProxy::create_monitor`], when it restarts for example. The monitor
+/// then creates a new one, [`Monitor::receive_state_changed`] keeps reporting
+/// the state of the current session without interruption. Useful for long
+/// running services tracking whether the session is locked.
+///
+/// ```rust,no_run
+/// use ashpd::desktop::inhibit::{InhibitProxy, Monitor, SessionState};
+/// use ashpd::WindowIdentifier;
+/// use futures::StreamExt;
+///
+/// async fn run() -> ashpd::Result<()> {
+///     let connection = zbus::Connection::session().await?;
+///     let proxy = InhibitProxy::new(&connection).await?;
+///
+///     let monitor = Monitor::new(proxy, WindowIdentifier::default()).await?;
+///     let mut states = monitor.receive_state_changed().await?;
+///     while let Some(state) = states.next().await {
+///         let state = state?;
+///         println!("Screensaver active: {}", state.screensaver_active());
+///         if state.session_state() == SessionState::QueryEnd {
+///             monitor.query_end_response().await?;
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Monitor<'a> {
+    proxy: InhibitProxy<'a>,
+    identifier: WindowIdentifier,
+    session: Mutex<InhibitMonitor<'a>>,
+}
+
+impl<'a> Monitor<'a> {
+    /// Create a monitor session on behalf of the window `identifier`.
+    pub async fn new(
+        proxy: InhibitProxy<'a>,
+        identifier: WindowIdentifier,
+    ) -> Result<Monitor<'a>, Error> {
+        let session = proxy.create_monitor(&identifier).await?;
+        Ok(Self {
+            proxy,
+            identifier,
+            session: Mutex::new(session),
+        })
+    }
+
+    /// The current monitor session, it changes when the session is recreated.
+    pub fn session(&self) -> InhibitMonitor<'a> {
+        self.session.lock().unwrap().clone()
+    }
+
+    /// The states of the current monitor session.
+    ///
+    /// The stream fails, then ends, if the session couldn't be recreated after
+    /// being closed by the backend.
+    pub async fn receive_state_changed(
+        &self,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<InhibitState, Error>> + Send + '_>>, Error> {
+        let states = self.proxy.receive_state_changed().await?;
+        let closed = self.session().receive_closed().await?;
+        Ok(Box::pin(stream::unfold(
+            Some((self, states, closed)),
+            |state| async move {
+                let (monitor, mut states, mut closed) = state?;
+                loop {
+                    let state = match future::select(states.next(), closed.next()).await {
+                        Either::Left((state, _)) => Some(state?),
+                        Either::Right(_) => None,
+                    };
+                    match state {
+                        Some(state)
+                            if state.session_handle() == *monitor.session().inner().path() =>
+                        {
+                            return Some((Ok(state), Some((monitor, states, closed))));
+                        }
+                        // Sent to a previous session.
+                        Some(_) => (),
+                        None => {
+                            #[cfg(feature = "log")]
+                            tracing::warn!("The inhibit monitor was closed, recreating it");
+                            let session = async {
+                                let session =
+                                    monitor.proxy.create_monitor(&monitor.identifier).await?;
+                                let session_closed = session.receive_closed().await?;
+                                Ok((session, session_closed))
+                            };
+                            match session.await {
+                                Ok((session, session_closed)) => {
+                                    *monitor.session.lock().unwrap() = session;
+                                    closed = session_closed;
+                                }
+                                Err(err) => return Some((Err(err), None)),
+                            }
+                        }
+                    }
+                }
+            },
+        )))
+    }
+
+    /// Acknowledge a [`SessionState::QueryEnd`] state of the current session,
+    /// see [`InhibitProxy::query_end_response`].
+    pub async fn query_end_response(&self) -> Result<(), Error> {
+        self.proxy.query_end_response(&self.session()).await
+    }
+
+    /// Close the current monitor session.
+    pub async fn close(self) -> Result<(), Error> {
+        self.session().close().await
+    }
+}
+
+/// Create a monitor session on behalf of the window `identifier`, then run
+/// `f` with the proxy, to listen to the state changes, and the session.
+///
+/// The session is closed once `f` completes, successfully or not, or if it
+/// is interrupted, the same way as with
+/// [`run_with_session`](crate::desktop::remote_desktop::run_with_session).
+/// [`Monitor`] follows the sessions recreated by the backend instead.
+///
+/// ```rust,no_run
+/// use ashpd::desktop::inhibit::{self, SessionState};
+/// use ashpd::WindowIdentifier;
+/// use futures::StreamExt;
+///
+/// async fn run() -> ashpd::Result<()> {
+///     inhibit::run_with_session(&WindowIdentifier::default(), |proxy, session| async move {
+///         let mut states = proxy.receive_state_changed().await?;
+///         while let Some(state) = states.next().await {
+///             if state.session_state() == SessionState::QueryEnd {
+///                 proxy.query_end_response(&session).await?;
+///                 break;
+///             }
+///         }
+///         Ok(())
+///     })
+///     .await
+/// }
+/// ```
+pub async fn run_with_session<T, F, Fut>(identifier: &WindowIdentifier, f: F) -> Result<T, Error>
+where
+    F: FnOnce(InhibitProxy<'static>, InhibitMonitor<'static>) -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    let connection = session_connection().await?;
+    let proxy = InhibitProxy::new(&connection).await?;
+    let session = proxy.create_monitor(identifier).await?;
+    let guard = SessionGuard::new(&session);
+    let result = f(proxy, session).await;
+    guard.close_after(result).await
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CreateMonitorOptions, InhibitOptions};
+    use crate::{desktop::HandleToken, helpers::assert_serialized_dict};
+
+    #[test]
+    fn options() {
+        let options = CreateMonitorOptions {
+            handle_token: HandleToken::default(),
+            session_handle_token: HandleToken::default(),
+        };
+        assert_serialized_dict(
+            &options,
+            &[("handle_token", "s"), ("session_handle_token", "s")],
+        );
+
+        let options = InhibitOptions {
+            handle_token: HandleToken::default(),
+            reason: Some("Playing a video".to_owned()),
+        };
+        assert_serialized_dict(&options, &[("handle_token", "s"), ("reason", "s")]);
     }
 }
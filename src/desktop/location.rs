@@ -3,7 +3,7 @@
 //! ```rust,no_run
 //! use ashpd::desktop::location::{Accuracy, LocationProxy};
 //! use ashpd::WindowIdentifier;
-//! use futures::TryFutureExt;
+//! use futures::StreamExt;
 //!
 //! async fn run() -> ashpd::Result<()> {
 //!     let connection = zbus::Connection::session().await?;
@@ -12,35 +12,44 @@
 //!
 //!     let session = proxy.create_session(None, None, Some(Accuracy::Street)).await?;
 //!
-//!     let (_, location) = futures::try_join!(
-//!         proxy.start(&session, &identifier).into_future(),
-//!         proxy.receive_location_updated().into_future()
-//!     )?;
+//!     // Listen before starting the session to not miss the first update.
+//!     let mut locations = proxy.receive_location_updated().await?;
+//!     proxy.start(&session, &identifier).await?;
 //!
-//!     println!("{}", location.accuracy());
-//!     println!("{}", location.longitude());
-//!     println!("{}", location.latitude());
+//!     if let Some(location) = locations.next().await {
+//!         println!("{}", location.accuracy());
+//!         println!("{}", location.longitude());
+//!         println!("{}", location.latitude());
+//!     }
 //!     session.close().await?;
 //!
 //!     Ok(())
 //! }
 //! ```
 
-use std::fmt::Debug;
+use std::{fmt::Debug, future::Future, pin::Pin};
 
-use futures::TryFutureExt;
+use futures::{Stream, TryFutureExt};
 use serde::{Deserialize, Serialize};
 use zbus::zvariant::{DeserializeDict, OwnedObjectPath, SerializeDict, Type};
 
-use super::{HandleToken, SessionProxy, DESTINATION, PATH};
+use super::{
+    HandleToken, LocationPortal, PortalRequest, SessionGuard, SessionProxy, DESTINATION,
+    INTERFACE_LOCATION, PATH,
+};
 use crate::{
-    helpers::{call_basic_response_method, call_method, receive_signal},
+    helpers::{
+        call_method, call_window_basic_response_method, receive_signal_stream, session_connection,
+    },
     Error, WindowIdentifier,
 };
 
-#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug, Type)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Type)]
 #[doc(alias = "XdpLocationAccuracy")]
+#[non_exhaustive]
 /// The accuracy of the location.
+///
+/// Converts from and to the `u32` sent on the bus.
 pub enum Accuracy {
     #[doc(alias = "XDP_LOCATION_ACCURACY_NONE")]
     /// None.
@@ -62,7 +71,16 @@ pub enum Accuracy {
     Exact = 5,
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+impl_u32_conversions!(Accuracy {
+    None = 0,
+    Country = 1,
+    City = 2,
+    Neighborhood = 3,
+    Street = 4,
+    Exact = 5,
+});
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
 /// Specified options for a [`LocationProxy::create_session`] request.
 #[zvariant(signature = "dict")]
 struct CreateSessionOptions {
@@ -79,26 +97,18 @@ struct CreateSessionOptions {
 }
 
 impl CreateSessionOptions {
-    /// Sets the distance threshold in meters.
-    pub fn distance_threshold(mut self, distance_threshold: u32) -> Self {
-        self.distance_threshold = Some(distance_threshold);
-        self
-    }
-
-    /// Sets the time threshold in seconds.
-    pub fn time_threshold(mut self, time_threshold: u32) -> Self {
-        self.time_threshold = Some(time_threshold);
-        self
-    }
-
-    /// Sets the location accuracy.
-    pub fn accuracy(mut self, accuracy: Accuracy) -> Self {
-        self.accuracy = Some(accuracy);
-        self
+    options_setters! {
+        @setters
+        /// Sets the distance threshold in meters.
+        with_distance_threshold(distance_threshold: u32);
+        /// Sets the time threshold in seconds.
+        with_time_threshold(time_threshold: u32);
+        /// Sets the location accuracy.
+        with_accuracy(accuracy: Accuracy);
     }
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
 /// Specified options for a [`LocationProxy::start`] request.
 #[zvariant(signature = "dict")]
 struct SessionStartOptions {
@@ -106,7 +116,11 @@ struct SessionStartOptions {
     handle_token: HandleToken,
 }
 
-#[derive(Serialize, Deserialize, Type)]
+/// The mean radius of the Earth, in meters, used by
+/// [`Location::distance_to`].
+pub const EARTH_RADIUS: f64 = 6_371_008.8;
+
+#[derive(Serialize, Deserialize, Type, Clone, PartialEq)]
 /// The response received on a `location_updated` signal.
 pub struct Location(OwnedObjectPath, LocationInner);
 
@@ -167,6 +181,122 @@ impl Location {
     pub fn timestamp(&self) -> std::time::Duration {
         std::time::Duration::from_secs(self.1.timestamp.0)
     }
+
+    // The timestamp with its microseconds, which `timestamp` drops.
+    fn precise_timestamp(&self) -> f64 {
+        self.1.timestamp.0 as f64 + self.1.timestamp.1 as f64 / 1_000_000.0
+    }
+
+    /// The great-circle distance to `other`, in meters, with the haversine
+    /// formula. The altitudes are ignored.
+    pub fn distance_to(&self, other: &Location) -> f64 {
+        let (lat1, lat2) = (self.latitude().to_radians(), other.latitude().to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.longitude() - self.longitude()).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS * a.sqrt().min(1.0).asin()
+    }
+
+    /// The initial heading to follow to go to `other` on a great circle, in
+    /// degrees like [`Location::heading`].
+    pub fn bearing_to(&self, other: &Location) -> f64 {
+        let (lat1, lat2) = (self.latitude().to_radians(), other.latitude().to_radians());
+        let d_lon = (other.longitude() - self.longitude()).to_radians();
+        let y = d_lon.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * d_lon.cos();
+        y.atan2(x).to_degrees().rem_euclid(360.0)
+    }
+
+    /// The average speed since the `previous` update, in meters per second.
+    /// None if the updates were retrieved at the same time or out of order.
+    pub fn speed_since(&self, previous: &Location) -> Option<f64> {
+        let elapsed = self.precise_timestamp() - previous.precise_timestamp();
+        if elapsed > 0.0 {
+            Some(previous.distance_to(self) / elapsed)
+        } else {
+            None
+        }
+    }
+
+    /// The location as a `geo` URI, see [RFC 5870](https://www.rfc-editor.org/rfc/rfc5870).
+    ///
+    /// The altitude is included if known and the accuracy is given as the
+    /// uncertainty.
+    pub fn to_geo_uri(&self) -> String {
+        let mut uri = format!("geo:{},{}", self.latitude(), self.longitude());
+        if let Some(altitude) = self.altitude() {
+            uri.push_str(&format!(",{}", altitude));
+        }
+        if self.accuracy() > 0.0 {
+            uri.push_str(&format!(";u={}", self.accuracy()));
+        }
+        uri
+    }
+}
+
+/// Smooths the speed of the location updates with an exponential moving
+/// average.
+///
+/// The speed reported by the portal is used when known, the one derived from
+/// the distance to the previous update otherwise.
+///
+/// ```rust,no_run
+/// use ashpd::desktop::location::{LocationProxy, SpeedSmoother};
+/// use futures::StreamExt;
+///
+/// async fn run(proxy: &LocationProxy<'_>) -> ashpd::Result<()> {
+///     let mut smoother = SpeedSmoother::new(0.3);
+///     let mut locations = proxy.receive_location_updated().await?;
+///     while let Some(location) = locations.next().await {
+///         if let Some(speed) = smoother.push(&location) {
+///             println!("{:.1} m/s", speed);
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpeedSmoother {
+    factor: f64,
+    speed: Option<f64>,
+    previous: Option<Location>,
+}
+
+impl SpeedSmoother {
+    /// Create a smoother giving the weight `factor`, between `0` and `1`, to
+    /// each new speed. The higher it is, the faster the smoothed speed
+    /// follows the changes.
+    pub fn new(factor: f64) -> Self {
+        Self {
+            factor: factor.clamp(0.0, 1.0),
+            speed: None,
+            previous: None,
+        }
+    }
+
+    /// Add an update, returning the smoothed speed in meters per second.
+    ///
+    /// None until a speed is known, when the portal doesn't report one and
+    /// there is no previous update.
+    pub fn push(&mut self, location: &Location) -> Option<f64> {
+        let derived = self
+            .previous
+            .as_ref()
+            .and_then(|previous| location.speed_since(previous));
+        self.previous = Some(location.clone());
+        if let Some(speed) = location.speed().or(derived) {
+            self.speed = Some(match self.speed {
+                Some(smoothed) => smoothed + self.factor * (speed - smoothed),
+                None => speed,
+            });
+        }
+        self.speed
+    }
+
+    /// The smoothed speed, in meters per second.
+    pub fn speed(&self) -> Option<f64> {
+        self.speed
+    }
 }
 
 impl Debug for Location {
@@ -184,7 +314,7 @@ impl Debug for Location {
     }
 }
 
-#[derive(Debug, SerializeDict, DeserializeDict, Type)]
+#[derive(Debug, SerializeDict, DeserializeDict, Type, Clone, PartialEq)]
 #[zvariant(signature = "dict")]
 struct LocationInner {
     #[zvariant(rename = "Accuracy")]
@@ -205,24 +335,30 @@ struct LocationInner {
     timestamp: (u64, u64),
 }
 
+/// A session created by [`LocationProxy::create_session`].
+pub type LocationSession<'a> = SessionProxy<'a, LocationPortal>;
+
 /// The interface lets sandboxed applications query basic information about the
 /// location.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Location`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Location).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.Location")]
-pub struct LocationProxy<'a>(zbus::Proxy<'a>);
+pub struct LocationProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(LocationProxy, INTERFACE_LOCATION);
+impl_proxy_timeout!(LocationProxy);
 
 impl<'a> LocationProxy<'a> {
     /// Create a new instance of [`LocationProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<LocationProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Location")?
+            .interface(INTERFACE_LOCATION)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -237,8 +373,10 @@ impl<'a> LocationProxy<'a> {
     /// See also [`LocationUpdated`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-Location.LocationUpdated).
     #[doc(alias = "LocationUpdated")]
     #[doc(alias = "XdpPortal::location-updated")]
-    pub async fn receive_location_updated(&self) -> Result<Location, Error> {
-        receive_signal(&self.0, "LocationUpdated").await
+    pub async fn receive_location_updated(
+        &self,
+    ) -> Result<impl Stream<Item = Location> + Unpin + 'a, Error> {
+        receive_signal_stream(&self.0, "LocationUpdated").await
     }
 
     /// Create a location session.
@@ -260,11 +398,11 @@ impl<'a> LocationProxy<'a> {
         distance_threshold: Option<u32>,
         time_threshold: Option<u32>,
         accuracy: Option<Accuracy>,
-    ) -> Result<SessionProxy<'a>, Error> {
+    ) -> Result<LocationSession<'a>, Error> {
         let options = CreateSessionOptions::default()
-            .distance_threshold(distance_threshold.unwrap_or(0))
-            .time_threshold(time_threshold.unwrap_or(0))
-            .accuracy(accuracy.unwrap_or(Accuracy::Exact));
+            .with_distance_threshold(distance_threshold.unwrap_or(0))
+            .with_time_threshold(time_threshold.unwrap_or(0))
+            .with_accuracy(accuracy.unwrap_or(Accuracy::Exact));
         let (path, proxy) = futures::try_join!(
             call_method::<OwnedObjectPath, CreateSessionOptions>(
                 &self.0,
@@ -284,7 +422,7 @@ impl<'a> LocationProxy<'a> {
     ///
     /// # Arguments
     ///
-    /// * `session` - A [`SessionProxy`], created with
+    /// * `session` - A [`LocationSession`], created with
     ///   [`create_session()`][`LocationProxy::create_session`].
     /// * `identifier` - Identifier for the application window.
     ///
@@ -293,18 +431,156 @@ impl<'a> LocationProxy<'a> {
     /// See also [`Start`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Location.Start).
     #[doc(alias = "Start")]
     #[doc(alias = "xdp_portal_location_monitor_start")]
-    pub async fn start(
-        &self,
-        session: &SessionProxy<'_>,
-        identifier: &WindowIdentifier,
-    ) -> Result<(), Error> {
+    pub fn start<'p>(
+        &'p self,
+        session: &'p LocationSession<'_>,
+        identifier: &'p WindowIdentifier,
+    ) -> PortalRequest<'p, ()> {
         let options = SessionStartOptions::default();
-        call_basic_response_method(
+        let handle_token = options.handle_token.copy();
+        call_window_basic_response_method(
             &self.0,
-            &options.handle_token,
+            identifier,
+            &handle_token,
             "Start",
-            &(session, &identifier, &options),
+            (session, identifier, options),
         )
-        .await
+    }
+}
+
+/// Create a location session and start it, then run `f` with the session and
+/// the stream of its location updates.
+///
+/// The stream listens before the session is started, the first update isn't
+/// missed. See [`LocationProxy::create_session`] for the arguments.
+///
+/// The session is closed once `f` completes, successfully or not, or if it
+/// is interrupted, the same way as with
+/// [`run_with_session`](crate::desktop::remote_desktop::run_with_session).
+///
+/// ```rust,no_run
+/// use ashpd::desktop::location::{self, Accuracy};
+/// use ashpd::WindowIdentifier;
+/// use futures::StreamExt;
+///
+/// async fn run() -> ashpd::Result<()> {
+///     location::run_with_session(
+///         &WindowIdentifier::default(),
+///         None,
+///         None,
+///         Some(Accuracy::Street),
+///         |_session, mut locations| async move {
+///             if let Some(location) = locations.next().await {
+///                 println!("{}, {}", location.latitude(), location.longitude());
+///             }
+///             Ok(())
+///         },
+///     )
+///     .await
+/// }
+/// ```
+pub async fn run_with_session<T, F, Fut>(
+    identifier: &WindowIdentifier,
+    distance_threshold: Option<u32>,
+    time_threshold: Option<u32>,
+    accuracy: Option<Accuracy>,
+    f: F,
+) -> Result<T, Error>
+where
+    F: FnOnce(LocationSession<'static>, LocationUpdates) -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    let connection = session_connection().await?;
+    let proxy = LocationProxy::new(&connection).await?;
+    let session = proxy
+        .create_session(distance_threshold, time_threshold, accuracy)
+        .await?;
+    let guard = SessionGuard::new(&session);
+    let locations = Box::pin(proxy.receive_location_updated().await?);
+    proxy.start(&session, identifier).await?;
+    let result = f(session, locations).await;
+    guard.close_after(result).await
+}
+
+/// The location updates given to the closure of [`run_with_session`].
+pub type LocationUpdates = Pin<Box<dyn Stream<Item = Location> + Send>>;
+
+#[cfg(test)]
+mod test {
+    use zbus::zvariant::OwnedObjectPath;
+
+    use super::{
+        Accuracy, CreateSessionOptions, Location, LocationInner, SessionStartOptions, SpeedSmoother,
+    };
+    use crate::{desktop::HandleToken, helpers::assert_serialized_dict};
+
+    fn location(latitude: f64, longitude: f64, speed: f64, timestamp: u64) -> Location {
+        Location(
+            OwnedObjectPath::try_from("/org/freedesktop/portal/desktop/session/1_2/t").unwrap(),
+            LocationInner {
+                accuracy: 10.0,
+                altitude: -f64::MAX,
+                speed,
+                heading: -1.0,
+                description: String::new(),
+                latitude,
+                longitude,
+                timestamp: (timestamp, 0),
+            },
+        )
+    }
+
+    #[test]
+    fn distances() {
+        let paris = location(48.8566, 2.3522, -1.0, 0);
+        let london = location(51.5074, -0.1278, -1.0, 3600);
+        assert!((paris.distance_to(&london) - 343_900.0).abs() < 1_000.0);
+        assert_eq!(paris.distance_to(&paris), 0.0);
+        assert!((paris.bearing_to(&london) - 330.0).abs() < 1.0);
+        assert!(
+            (location(0.0, 0.0, -1.0, 0).bearing_to(&location(0.0, 1.0, -1.0, 0)) - 90.0).abs()
+                < 1e-9
+        );
+
+        let speed = london.speed_since(&paris).unwrap();
+        assert!((speed - paris.distance_to(&london) / 3600.0).abs() < 1e-9);
+        assert_eq!(paris.speed_since(&london), None);
+
+        assert_eq!(paris.to_geo_uri(), "geo:48.8566,2.3522;u=10");
+    }
+
+    #[test]
+    fn speed_smoother() {
+        let mut smoother = SpeedSmoother::new(0.5);
+        assert_eq!(smoother.push(&location(0.0, 0.0, -1.0, 0)), None);
+        assert_eq!(smoother.push(&location(0.0, 0.0, 4.0, 1)), Some(4.0));
+        assert_eq!(smoother.push(&location(0.0, 0.0, 2.0, 2)), Some(3.0));
+        // Not moving, derived from the previous update.
+        assert_eq!(smoother.push(&location(0.0, 0.0, -1.0, 3)), Some(1.5));
+        assert_eq!(smoother.speed(), Some(1.5));
+    }
+
+    #[test]
+    fn options() {
+        let options = CreateSessionOptions {
+            session_handle_token: HandleToken::default(),
+            distance_threshold: Some(100),
+            time_threshold: Some(60),
+            accuracy: Some(Accuracy::City),
+        };
+        assert_serialized_dict(
+            &options,
+            &[
+                ("accuracy", "u"),
+                ("distance-threshold", "u"),
+                ("session_handle_token", "s"),
+                ("time-threshold", "u"),
+            ],
+        );
+
+        let options = SessionStartOptions {
+            handle_token: HandleToken::default(),
+        };
+        assert_serialized_dict(&options, &[("handle_token", "s")]);
     }
 }
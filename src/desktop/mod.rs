@@ -1,12 +1,90 @@
-pub(crate) const DESTINATION: &str = "org.freedesktop.portal.Desktop";
-pub(crate) const PATH: &str = "/org/freedesktop/portal/desktop";
-
+/// The bus name of the `org.freedesktop.portal.*` interfaces, to use in match
+/// rules or diagnostics.
+pub const DESTINATION: &str = "org.freedesktop.portal.Desktop";
+/// The object path the `org.freedesktop.portal.*` interfaces are exported at.
+pub const PATH: &str = "/org/freedesktop/portal/desktop";
+
+/// The interface wrapped by [`AccountProxy`](account::AccountProxy).
+pub const INTERFACE_ACCOUNT: &str = "org.freedesktop.portal.Account";
+/// The interface wrapped by [`BackgroundProxy`](background::BackgroundProxy).
+pub const INTERFACE_BACKGROUND: &str = "org.freedesktop.portal.Background";
+/// The interface wrapped by [`CameraProxy`](camera::CameraProxy).
+pub const INTERFACE_CAMERA: &str = "org.freedesktop.portal.Camera";
+/// The interface wrapped by [`ClipboardProxy`](clipboard::ClipboardProxy).
+pub const INTERFACE_CLIPBOARD: &str = "org.freedesktop.portal.Clipboard";
+/// The interface wrapped by [`DeviceProxy`](device::DeviceProxy).
+pub const INTERFACE_DEVICE: &str = "org.freedesktop.portal.Device";
+/// The interface wrapped by [`DynamicLauncherProxy`](dynamic_launcher::DynamicLauncherProxy).
+pub const INTERFACE_DYNAMIC_LAUNCHER: &str = "org.freedesktop.portal.DynamicLauncher";
+/// The interface wrapped by [`EmailProxy`](email::EmailProxy).
+pub const INTERFACE_EMAIL: &str = "org.freedesktop.portal.Email";
+/// The interface wrapped by [`FileChooserProxy`](file_chooser::FileChooserProxy).
+pub const INTERFACE_FILE_CHOOSER: &str = "org.freedesktop.portal.FileChooser";
+/// The interface wrapped by [`GameModeProxy`](game_mode::GameModeProxy).
+pub const INTERFACE_GAME_MODE: &str = "org.freedesktop.portal.GameMode";
+/// The interface wrapped by [`GlobalShortcutsProxy`](global_shortcuts::GlobalShortcutsProxy).
+pub const INTERFACE_GLOBAL_SHORTCUTS: &str = "org.freedesktop.portal.GlobalShortcuts";
+/// The interface wrapped by [`InhibitProxy`](inhibit::InhibitProxy).
+pub const INTERFACE_INHIBIT: &str = "org.freedesktop.portal.Inhibit";
+/// The interface wrapped by [`LocationProxy`](location::LocationProxy).
+pub const INTERFACE_LOCATION: &str = "org.freedesktop.portal.Location";
+/// The interface wrapped by [`MemoryMonitorProxy`](memory_monitor::MemoryMonitorProxy).
+pub const INTERFACE_MEMORY_MONITOR: &str = "org.freedesktop.portal.MemoryMonitor";
+/// The interface wrapped by [`NetworkMonitorProxy`](network_monitor::NetworkMonitorProxy).
+pub const INTERFACE_NETWORK_MONITOR: &str = "org.freedesktop.portal.NetworkMonitor";
+/// The interface wrapped by [`NotificationProxy`](notification::NotificationProxy).
+pub const INTERFACE_NOTIFICATION: &str = "org.freedesktop.portal.Notification";
+/// The interface wrapped by [`OpenURIProxy`](open_uri::OpenURIProxy).
+pub const INTERFACE_OPEN_URI: &str = "org.freedesktop.portal.OpenURI";
+/// The bus name of the permission store of the portals.
+pub const PERMISSION_STORE_DESTINATION: &str = "org.freedesktop.impl.portal.PermissionStore";
+/// The object path the permission store is exported at.
+pub const PERMISSION_STORE_PATH: &str = "/org/freedesktop/impl/portal/PermissionStore";
+/// The interface wrapped by [`PermissionStoreProxy`](permission_store::PermissionStoreProxy).
+pub const INTERFACE_PERMISSION_STORE: &str = "org.freedesktop.impl.portal.PermissionStore";
+/// The interface wrapped by [`PowerProfileMonitorProxy`](power_profile_monitor::PowerProfileMonitorProxy).
+pub const INTERFACE_POWER_PROFILE_MONITOR: &str = "org.freedesktop.portal.PowerProfileMonitor";
+/// The interface wrapped by [`PrintProxy`](print::PrintProxy).
+pub const INTERFACE_PRINT: &str = "org.freedesktop.portal.Print";
+/// The interface wrapped by [`ProxyResolverProxy`](proxy_resolver::ProxyResolverProxy).
+pub const INTERFACE_PROXY_RESOLVER: &str = "org.freedesktop.portal.ProxyResolver";
+/// The interface wrapped by [`RealtimeProxy`](realtime::RealtimeProxy).
+pub const INTERFACE_REALTIME: &str = "org.freedesktop.portal.Realtime";
+/// The interface wrapped by [`RemoteDesktopProxy`](remote_desktop::RemoteDesktopProxy).
+pub const INTERFACE_REMOTE_DESKTOP: &str = "org.freedesktop.portal.RemoteDesktop";
+/// The interface of the objects tracking the requests of the other portals.
+pub const INTERFACE_REQUEST: &str = "org.freedesktop.portal.Request";
+/// The interface wrapped by [`ScreenCastProxy`](screencast::ScreenCastProxy).
+pub const INTERFACE_SCREENCAST: &str = "org.freedesktop.portal.ScreenCast";
+/// The interface wrapped by [`ScreenshotProxy`](screenshot::ScreenshotProxy).
+pub const INTERFACE_SCREENSHOT: &str = "org.freedesktop.portal.Screenshot";
+/// The interface wrapped by [`SecretProxy`](secret::SecretProxy).
+pub const INTERFACE_SECRET: &str = "org.freedesktop.portal.Secret";
+/// The interface wrapped by [`SessionProxy`](SessionProxy).
+pub const INTERFACE_SESSION: &str = "org.freedesktop.portal.Session";
+/// The interface wrapped by [`SettingsProxy`](settings::SettingsProxy).
+pub const INTERFACE_SETTINGS: &str = "org.freedesktop.portal.Settings";
+/// The interface wrapped by [`TrashProxy`](trash::TrashProxy).
+pub const INTERFACE_TRASH: &str = "org.freedesktop.portal.Trash";
+/// The interface wrapped by [`WallpaperProxy`](wallpaper::WallpaperProxy).
+pub const INTERFACE_WALLPAPER: &str = "org.freedesktop.portal.Wallpaper";
+
+mod capabilities;
+mod handle_path;
 mod handle_token;
 pub(crate) mod request;
 mod session;
+pub use self::capabilities::SessionCapabilities;
+pub use self::handle_path::{handle_path, parse_handle_path, sender_token, HandleKind};
 pub(crate) use self::handle_token::HandleToken;
-pub use self::request::ResponseError;
-pub use self::session::SessionProxy;
+pub use self::request::{
+    close_request, PortalRequest, PreparedRequest, ResponseCode, ResponseError,
+};
+pub(crate) use self::session::SessionGuard;
+pub use self::session::{
+    GlobalShortcutsPortal, InhibitPortal, LocationPortal, RemoteDesktopPortal,
+    ScreenCastCompatible, ScreenCastPortal, SessionDetails, SessionPortal, SessionProxy,
+};
 
 /// Request access to the current logged user information such as the id, name
 /// or their avatar uri.
@@ -19,9 +97,15 @@ pub mod background;
 /// remote stream.
 pub mod camera;
 
+/// Share the clipboard with a remote desktop session.
+pub mod clipboard;
+
 /// Request access to specific devices such as camera, speakers or microphone.
 pub mod device;
 
+/// Install launchers for web applications or games.
+pub mod dynamic_launcher;
+
 /// Compose an email.
 pub mod email;
 
@@ -31,6 +115,9 @@ pub mod file_chooser;
 /// Enable/disable/query the status of Game Mode.
 pub mod game_mode;
 
+/// Bind shortcuts triggered while the application isn't focused.
+pub mod global_shortcuts;
+
 /// Inhibit the session from being restarted or the user from logging out.
 pub mod inhibit;
 
@@ -49,6 +136,9 @@ pub mod notification;
 /// Open a file or a directory.
 pub mod open_uri;
 
+/// Query the permissions granted through the portals.
+pub mod permission_store;
+
 /// Print a document.
 pub mod print;
 
@@ -67,6 +157,11 @@ pub mod realtime;
 /// Start a screencast session and get the PipeWire remote of it.
 pub mod screencast;
 
+/// Capture the screen through the ScreenCast portal, or with the
+/// wlr-screencopy and wlr-export-dmabuf protocols of the wlroots based
+/// compositors when no portal backend is running.
+pub mod screen_capture;
+
 /// Take a screenshot or pick a color.
 pub mod screenshot;
 
@@ -82,3 +177,17 @@ pub mod trash;
 
 /// Set a wallpaper on lockscreen, background or both.
 pub mod wallpaper;
+
+// The output of `codegen`, built to check that it keeps up with the helpers
+// and macros of the crate.
+#[cfg(test)]
+#[rustfmt::skip]
+#[allow(dead_code)]
+#[path = "../../codegen/snapshots/location.rs"]
+mod generated_location;
+
+#[cfg(test)]
+#[rustfmt::skip]
+#[allow(dead_code)]
+#[path = "../../codegen/snapshots/remote_desktop.rs"]
+mod generated_remote_desktop;
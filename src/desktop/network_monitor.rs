@@ -20,16 +20,12 @@
 
 use std::fmt;
 
-use serde::{Deserialize, Serialize};
 use zbus::zvariant::{DeserializeDict, SerializeDict, Type};
 
-use super::{DESTINATION, PATH};
-use crate::{
-    helpers::{call_method, receive_signal},
-    Error,
-};
+use super::{DESTINATION, INTERFACE_NETWORK_MONITOR, PATH};
+use crate::Error;
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Clone, PartialEq, Eq)]
 /// The network status, composed of the availability, metered & connectivity
 #[zvariant(signature = "dict")]
 pub struct NetworkStatus {
@@ -55,8 +51,11 @@ impl NetworkStatus {
     }
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy, Type)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Type)]
+#[non_exhaustive]
 /// Host's network activity
+///
+/// Converts from and to the `u32` sent on the bus.
 pub enum Connectivity {
     /// The host is not configured with a route to the internet.
     Local = 1,
@@ -68,6 +67,13 @@ pub enum Connectivity {
     FullNetwork = 4,
 }
 
+impl_u32_conversions!(Connectivity {
+    Local = 1,
+    Limited = 2,
+    CaptivePortal = 3,
+    FullNetwork = 4,
+});
+
 impl fmt::Display for Connectivity {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let connectivity = match self {
@@ -86,20 +92,23 @@ impl fmt::Display for Connectivity {
 /// via a library API such as the GLib [`gio::NetworkMonitor`](https://gtk-rs.org/gtk-rs-core/stable/latest/docs/gio/struct.NetworkMonitor.html) interface.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.NetworkMonitor`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.NetworkMonitor).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.NetworkMonitor")]
-pub struct NetworkMonitorProxy<'a>(zbus::Proxy<'a>);
+pub struct NetworkMonitorProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(NetworkMonitorProxy, INTERFACE_NETWORK_MONITOR);
+impl_proxy_timeout!(NetworkMonitorProxy);
 
 impl<'a> NetworkMonitorProxy<'a> {
     /// Create a new instance of [`NetworkMonitorProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<NetworkMonitorProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.NetworkMonitor")?
+            .interface(INTERFACE_NETWORK_MONITOR)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -107,75 +116,83 @@ impl<'a> NetworkMonitorProxy<'a> {
         &self.0
     }
 
-    /// Returns whether the given hostname is believed to be reachable.
-    ///
-    /// # Arguments
-    ///
-    /// * `hostname` - The hostname to reach.
-    /// * `port` - The port to reach.
-    ///
-    /// # Specifications
-    ///
-    /// See also [`CanReach`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-NetworkMonitor.CanReach).
-    #[doc(alias = "CanReach")]
-    pub async fn can_reach(&self, hostname: &str, port: u32) -> Result<bool, Error> {
-        call_method(self.inner(), "CanReach", &(hostname, port)).await
-    }
-
-    /// Returns whether the network is considered available.
-    /// That is, whether the system as a default route for at least one of IPv4
-    /// or IPv6.
-    ///
-    /// # Specifications
-    ///
-    /// See also [`GetAvailable`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-NetworkMonitor.GetAvailable).
-    #[doc(alias = "GetAvailable")]
-    #[doc(alias = "get_available")]
-    pub async fn is_available(&self) -> Result<bool, Error> {
-        call_method(self.inner(), "GetAvailable", &()).await
+    portal_members! {
+        /// Returns whether the given hostname is believed to be reachable.
+        ///
+        /// # Arguments
+        ///
+        /// * `hostname` - The hostname to reach.
+        /// * `port` - The port to reach.
+        ///
+        /// # Specifications
+        ///
+        /// See also [`CanReach`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-NetworkMonitor.CanReach).
+        pub fn can_reach(hostname: &str, port: u32) -> bool = method "CanReach";
+
+        /// Returns whether the network is considered available.
+        /// That is, whether the system as a default route for at least one of IPv4
+        /// or IPv6.
+        ///
+        /// # Specifications
+        ///
+        /// See also [`GetAvailable`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-NetworkMonitor.GetAvailable).
+        #[doc(alias = "get_available")]
+        pub fn is_available() -> bool = method "GetAvailable";
+
+        /// Returns more detailed information about the host's network connectivity
+        ///
+        /// # Specifications
+        ///
+        /// See also [`GetConnectivity`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-NetworkMonitor.GetConnectivity).
+        #[doc(alias = "get_connectivity")]
+        pub fn connectivity() -> Connectivity = method "GetConnectivity";
+
+        /// Returns whether the network is considered metered.
+        /// That is, whether the system as traffic flowing through the default
+        /// connection that is subject to limitations by service providers.
+        ///
+        /// # Specifications
+        ///
+        /// See also [`GetMetered`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-NetworkMonitor.GetMetered).
+        #[doc(alias = "get_metered")]
+        pub fn is_metered() -> bool = method "GetMetered";
+
+        /// Returns the three values all at once.
+        ///
+        /// # Specifications
+        ///
+        /// See also [`GetStatus`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-NetworkMonitor.GetStatus).
+        #[doc(alias = "get_status")]
+        pub fn status() -> NetworkStatus = method "GetStatus";
+
+        /// Emitted when the network configuration changes.
+        ///
+        /// # Specifications
+        ///
+        /// See also [`changed`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-NetworkMonitor.changed).
+        pub fn receive_changed() -> Stream<()> = signal "changed";
     }
+}
 
-    /// Returns more detailed information about the host's network connectivity
-    ///
-    /// # Specifications
-    ///
-    /// See also [`GetConnectivity`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-NetworkMonitor.GetConnectivity).
-    #[doc(alias = "GetConnectivity")]
-    #[doc(alias = "get_connectivity")]
-    pub async fn connectivity(&self) -> Result<Connectivity, Error> {
-        call_method(self.inner(), "GetConnectivity", &()).await
-    }
+#[cfg(test)]
+mod test {
+    use zbus::zvariant::{from_slice, to_bytes, EncodingContext};
 
-    /// Returns whether the network is considered metered.
-    /// That is, whether the system as traffic flowing through the default
-    /// connection that is subject to limitations by service providers.
-    ///
-    /// # Specifications
-    ///
-    /// See also [`GetMetered`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-NetworkMonitor.GetMetered).
-    #[doc(alias = "GetMetered")]
-    #[doc(alias = "get_metered")]
-    pub async fn is_metered(&self) -> Result<bool, Error> {
-        call_method(self.inner(), "GetMetered", &()).await
-    }
+    use super::{Connectivity, NetworkMonitorProxy};
+    use crate::{desktop::INTERFACE_NETWORK_MONITOR, helpers::assert_members};
 
-    /// Returns the three values all at once.
-    ///
-    /// # Specifications
-    ///
-    /// See also [`GetStatus`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-NetworkMonitor.GetStatus).
-    #[doc(alias = "GetStatus")]
-    #[doc(alias = "get_status")]
-    pub async fn status(&self) -> Result<NetworkStatus, Error> {
-        call_method(self.inner(), "GetStatus", &()).await
+    #[test]
+    fn members() {
+        assert_members(INTERFACE_NETWORK_MONITOR, &NetworkMonitorProxy::members());
     }
 
-    /// Emitted when the network configuration changes.
-    ///
-    /// # Specifications
-    ///
-    /// See also [`changed`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-NetworkMonitor.changed).
-    pub async fn receive_changed(&self) -> Result<(), Error> {
-        receive_signal(self.inner(), "changed").await
+    #[test]
+    fn connectivity() {
+        let ctxt = EncodingContext::<byteorder::LE>::new_dbus(0);
+        let bytes = to_bytes(ctxt, &4u32).unwrap();
+        let connectivity: Connectivity = from_slice(&bytes, ctxt).unwrap();
+        assert_eq!(connectivity, Connectivity::FullNetwork);
+        assert_eq!(u32::from(Connectivity::Local), 1);
+        assert!(Connectivity::try_from(0).is_err());
     }
 }
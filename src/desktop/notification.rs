@@ -2,6 +2,7 @@
 //!
 //! ```rust,no_run
 //! use ashpd::desktop::notification::{Action, Button, Notification, NotificationProxy, Priority};
+//! use futures::StreamExt;
 //! use std::{thread, time};
 //! use zbus::zvariant::Value;
 //!
@@ -14,44 +15,50 @@
 //!         .add_notification(
 //!             notification_id,
 //!             Notification::new("Contrast")
-//!                 .default_action("open")
-//!                 .default_action_target(Value::U32(100).into())
-//!                 .body("color copied to clipboard")
-//!                 .priority(Priority::High)
+//!                 .with_default_action("open")
+//!                 .with_default_action_target(Value::U32(100).into())
+//!                 .with_body("color copied to clipboard")
+//!                 .with_priority(Priority::High)
 //!                 .button(Button::new("Copy", "copy").target(Value::U32(32).into()))
 //!                 .button(Button::new("Delete", "delete").target(Value::U32(40).into())),
 //!         )
 //!         .await?;
 //!
-//!     let action = proxy.receive_action_invoked().await?;
-//!     match action.name() {
-//!         "copy" => (),   // Copy something to clipboard
-//!         "delete" => (), // Delete the file
-//!         _ => (),
-//!     };
-//!     println!("{:#?}", action.id());
-//!     println!(
-//!         "{:#?}",
-//!         action.parameter().get(0).unwrap().downcast_ref::<u32>()
-//!     );
+//!     let mut actions = proxy.receive_action_invoked().await?;
+//!     if let Some(action) = actions.next().await {
+//!         match action.name() {
+//!             "copy" => (),   // Copy something to clipboard
+//!             "delete" => (), // Delete the file
+//!             _ => (),
+//!         };
+//!         println!("{:#?}", action.id());
+//!         println!(
+//!             "{:#?}",
+//!             action.parameter().get(0).unwrap().downcast_ref::<u32>()
+//!         );
+//!     }
 //!
 //!     proxy.remove_notification(notification_id).await?;
 //!     Ok(())
 //! }
 //! ```
 
-use std::{fmt, str::FromStr};
+use std::{collections::HashMap, fmt, fs::File, os::unix::io::AsRawFd, str::FromStr};
 
+use enumflags2::{bitflags, BitFlags};
+use futures::Stream;
 use serde::{self, Deserialize, Serialize, Serializer};
-use zbus::zvariant::{DeserializeDict, OwnedValue, SerializeDict, Signature, Type};
+use zbus::zvariant::{Array, Fd, OwnedValue, Signature, Type, Value};
 
-use super::{DESTINATION, PATH};
+use super::{DESTINATION, INTERFACE_NOTIFICATION, PATH};
 use crate::{
-    helpers::{call_method, receive_signal},
-    Error,
+    helpers::{call_method, get_property, receive_signal_stream},
+    raw_options::RawOptions,
+    ActivationToken, Error,
 };
 
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
 /// The notification priority
 pub enum Priority {
     /// Low.
@@ -128,79 +135,282 @@ impl Type for Priority {
     }
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug)]
+#[bitflags]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+/// How the notification should be presented, since version 2 of the
+/// portal.
+///
+/// Sent as the list of their names, see
+/// [`NotificationCapabilities::display_hints`] for the ones the backend
+/// knows about.
+pub enum DisplayHint {
+    /// Remove the notification once it was shown, without keeping it in a
+    /// notification center.
+    Transient,
+    /// Show the notification in the system tray only, if supported.
+    Tray,
+    /// Keep the notification until it is withdrawn by the application, a
+    /// resident notification for an ongoing call for example.
+    Persistent,
+    /// Hide the content of the notification on the lock screen.
+    HideOnLockscreen,
+    /// Present the notification as a new one, even if it replaces a
+    /// previous notification with the same id.
+    ShowAsNew,
+}
+
+impl_str_conversions!(DisplayHint {
+    Transient = "transient",
+    Tray = "tray",
+    Persistent = "persistent",
+    HideOnLockscreen = "hide-on-lockscreen",
+    ShowAsNew = "show-as-new",
+});
+
+impl Serialize for DisplayHint {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DisplayHint {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl Type for DisplayHint {
+    fn signature() -> Signature<'static> {
+        String::signature()
+    }
+}
+
+// The names of an `as` entry of the `SupportedOptions` property, the ones that
+// aren't strings are skipped.
+fn supported_names(options: &HashMap<String, OwnedValue>, key: &str) -> Option<Vec<String>> {
+    let names = options.get(key)?.downcast_ref::<Array<'_>>()?;
+    Some(
+        names
+            .get()
+            .iter()
+            .filter_map(|name| name.downcast_ref::<str>().map(ToOwned::to_owned))
+            .collect(),
+    )
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The features of the notifications the backend supports, see
+/// [`NotificationProxy::capabilities`].
+///
+/// Nothing but the options of version 1 of the interface is supported before
+/// version 2, which introduced the `SupportedOptions` property.
+pub struct NotificationCapabilities {
+    version: u32,
+    options: HashMap<String, OwnedValue>,
+}
+
+impl NotificationCapabilities {
+    /// The capabilities of the version `version` of the interface, the
+    /// backend supporting `options`.
+    pub fn new(version: u32, options: HashMap<String, OwnedValue>) -> Self {
+        Self { version, options }
+    }
+
+    /// The version of the interface.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Whether the backend supports the option `name`, `sound` for example.
+    pub fn supports(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+
+    /// Whether a sound can be played along the notifications, since version
+    /// 2.
+    pub fn sound(&self) -> bool {
+        self.version >= 2
+    }
+
+    /// Whether the body of the notifications can contain markup, the
+    /// `markup-body` option of version 2.
+    pub fn markup_body(&self) -> bool {
+        self.version >= 2
+    }
+
+    /// The categories the backend knows about, None if any category is
+    /// accepted or if they aren't supported.
+    pub fn categories(&self) -> Option<Vec<String>> {
+        supported_names(&self.options, "category")
+    }
+
+    /// The display hints the backend supports, the ones it lists or all of
+    /// them since version 2.
+    pub fn display_hints(&self) -> BitFlags<DisplayHint> {
+        match supported_names(&self.options, "display-hint") {
+            Some(names) => names
+                .iter()
+                .filter_map(|name| name.parse::<DisplayHint>().ok())
+                .collect(),
+            None if self.version >= 2 => BitFlags::all(),
+            None => BitFlags::empty(),
+        }
+    }
+
+    /// The supported options, with the values the backend accepts when they
+    /// are restricted.
+    pub fn options(&self) -> &HashMap<String, OwnedValue> {
+        &self.options
+    }
+
+    // Only the options listed in `SupportedOptions` are checked.
+    fn validate(&self, notification: &Notification) -> Result<(), Error> {
+        if self.version < 2 {
+            return Ok(());
+        }
+        if let (Some(category), Some(categories)) = (&notification.category, self.categories()) {
+            if !categories.contains(category) {
+                return Err(Error::InvalidArgument(format!(
+                    "Unsupported category {}, the backend supports {:?}",
+                    category, categories
+                )));
+            }
+        }
+        if let Some(hints) = &notification.display_hint {
+            if self.supports("display-hint") {
+                let supported = self.display_hints();
+                if let Some(hint) = hints.iter().find(|hint| !supported.contains(**hint)) {
+                    return Err(Error::InvalidArgument(format!(
+                        "Unsupported display hint {}, the backend supports {}",
+                        hint,
+                        crate::str_form::format_flags(supported)
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Type, Debug, Clone, PartialEq)]
 /// A notification
 #[zvariant(signature = "dict")]
 pub struct Notification {
     /// User-visible string to display as the title.
-    title: String,
+    pub title: String,
     /// User-visible string to display as the body.
-    body: Option<String>,
-    /// Serialized icon (e.g using gio::Icon::serialize).
-    icon: Option<OwnedValue>,
+    pub body: Option<String>,
+    /// Serialized icon (e.g using gio::Icon::serialize), see also [`Icon`].
+    pub icon: Option<OwnedValue>,
     /// The priority for the notification.
-    priority: Option<Priority>,
+    pub priority: Option<Priority>,
     /// Name of an action that is exported by the application.
     /// This action will be activated when the user clicks on the notification.
-    #[zvariant(rename = "default-action")]
-    default_action: Option<String>,
+    pub default_action: Option<String>,
     /// Target parameter to send along when activating the default action.
-    #[zvariant(rename = "default-action-target")]
-    default_action_target: Option<OwnedValue>,
+    pub default_action_target: Option<OwnedValue>,
     /// Array of buttons to add to the notification.
-    buttons: Option<Vec<Button>>,
+    pub buttons: Option<Vec<Button>>,
+    /// The kind of event the notification is about, `im.received` for a
+    /// message of a conversation for example. Since version 2.
+    pub category: Option<String>,
+    /// How the notification should be presented. Since version 2.
+    pub display_hint: Option<Vec<DisplayHint>>,
+    /// The options without a field.
+    raw: RawOptions,
 }
 
+impl_options_dict!(Notification {
+    title = "title",
+    body = "body",
+    icon = "icon",
+    priority = "priority",
+    default_action = "default-action",
+    default_action_target = "default-action-target",
+    buttons = "buttons",
+    category = "category",
+    display_hint = "display-hint",
+});
+
 impl Notification {
     /// Create a new notification.
     ///
     /// # Arguments
     ///
     /// * `title` - the notification title.
-    pub fn new(title: &str) -> Self {
+    pub fn new(title: impl Into<String>) -> Self {
         Self {
-            title: title.to_string(),
+            title: title.into(),
             body: None,
             priority: None,
             icon: None,
             default_action: None,
             default_action_target: None,
             buttons: None,
+            category: None,
+            display_hint: None,
+            raw: RawOptions::default(),
         }
     }
 
-    /// Sets the notification body.
-    #[must_use]
-    pub fn body(mut self, body: &str) -> Self {
-        self.body = Some(body.to_string());
-        self
+    options_setters! {
+        /// Sets the notification body.
+        with_body(body: impl Into<String>);
+        /// Sets the notification priority.
+        with_priority(priority: Priority);
+        /// Sets the default action when the user clicks on the notification.
+        with_default_action(default_action: impl Into<String>);
+        /// Sets the category, `im.received` or `call.incoming` for example.
+        with_category(category: impl Into<String>);
     }
 
     /// Sets an icon to the notification.
     #[must_use]
-    pub fn icon(mut self, icon: OwnedValue) -> Self {
+    pub fn with_icon(mut self, icon: OwnedValue) -> Self {
         self.icon = Some(icon);
         self
     }
 
-    /// Sets the notification priority.
-    #[must_use]
-    pub fn priority(mut self, priority: Priority) -> Self {
-        self.priority = Some(priority);
-        self
+    /// The value set with [`with_icon`](Self::with_icon), if any.
+    pub fn icon(&self) -> Option<&OwnedValue> {
+        self.icon.as_ref()
     }
 
-    /// Sets the default action when the user clicks on the notification.
+    /// Sets a value to be sent in the `action_invoked` signal.
     #[must_use]
-    pub fn default_action(mut self, default_action: &str) -> Self {
-        self.default_action = Some(default_action.to_string());
+    pub fn with_default_action_target(mut self, default_action_target: OwnedValue) -> Self {
+        self.default_action_target = Some(default_action_target);
         self
     }
 
-    /// Sets a value to be sent in the `action_invoked` signal.
+    /// The value set with
+    /// [`with_default_action_target`](Self::with_default_action_target), if
+    /// any.
+    pub fn default_action_target(&self) -> Option<&OwnedValue> {
+        self.default_action_target.as_ref()
+    }
+
+    /// Sets how the notification should be presented.
+    ///
+    /// ```rust
+    /// use ashpd::desktop::notification::{DisplayHint, Notification};
+    ///
+    /// let notification = Notification::new("Incoming call")
+    ///     .with_category("call.incoming")
+    ///     .with_display_hint(DisplayHint::Persistent | DisplayHint::ShowAsNew);
+    /// ```
     #[must_use]
-    pub fn default_action_target(mut self, default_action_target: OwnedValue) -> Self {
-        self.default_action_target = Some(default_action_target);
+    pub fn with_display_hint(mut self, hints: impl Into<BitFlags<DisplayHint>>) -> Self {
+        self.display_hint = Some(hints.into().iter().collect());
         self
     }
 
@@ -217,19 +427,27 @@ impl Notification {
     }
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug)]
+#[derive(Type, Debug, Clone, PartialEq)]
 /// A notification button
 #[zvariant(signature = "dict")]
 pub struct Button {
     /// User-visible label for the button. Mandatory.
-    label: String,
+    pub label: String,
     /// Name of an action that is exported by the application. The action will
     /// be activated when the user clicks on the button.
-    action: String,
+    pub action: String,
     /// Target parameter to send along when activating the action.
-    target: Option<OwnedValue>,
+    pub target: Option<OwnedValue>,
+    /// The options without a field.
+    raw: RawOptions,
 }
 
+impl_options_dict!(Button {
+    label = "label",
+    action = "action",
+    target = "target",
+});
+
 impl Button {
     /// Create a new notification button.
     ///
@@ -238,11 +456,12 @@ impl Button {
     /// * `label` - the user visible label of the button.
     /// * `action` - the action name to be invoked when the user clicks on the
     ///   button.
-    pub fn new(label: &str, action: &str) -> Self {
+    pub fn new(label: impl Into<String>, action: impl Into<String>) -> Self {
         Self {
-            label: label.to_string(),
-            action: action.to_string(),
+            label: label.into(),
+            action: action.into(),
             target: None,
+            raw: RawOptions::default(),
         }
     }
 
@@ -254,6 +473,167 @@ impl Button {
     }
 }
 
+#[derive(Debug)]
+/// The icon of a notification, to pass to [`Notification::icon`] once
+/// serialized with [`Icon::serialize`].
+///
+/// ```rust,no_run
+/// use ashpd::desktop::notification::{Icon, Notification, NotificationProxy};
+///
+/// async fn run(pixels: &[u8]) -> ashpd::Result<()> {
+///     let connection = zbus::Connection::session().await?;
+///     let proxy = NotificationProxy::new(&connection).await?;
+///
+///     // A 32x32 RGBA image generated by the application.
+///     let icon = Icon::from_rgba(32, 32, pixels)?;
+///     proxy
+///         .add_notification("download", Notification::new("Done").with_icon(icon.serialize()))
+///         .await?;
+///     Ok(())
+/// }
+/// ```
+pub enum Icon {
+    /// The names of themed icons, the first one found in the icon theme is
+    /// used.
+    Names(Vec<String>),
+    /// The content of a PNG, JPEG or SVG image.
+    Bytes(Vec<u8>),
+    /// A sealed memfd containing a PNG, JPEG or SVG image, requires version 2
+    /// of the portal.
+    FileDescriptor(File),
+}
+
+impl Icon {
+    /// A themed icon, looked up by `name` in the icon theme.
+    pub fn with_name(name: impl Into<String>) -> Self {
+        Self::Names(vec![name.into()])
+    }
+
+    /// Wrap the content of a PNG, JPEG or SVG image in a sealed memfd,
+    /// without going through a temporary file.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn from_image(image: &[u8]) -> Result<Self, Error> {
+        sealed_memfd(image)
+            .map(Self::FileDescriptor)
+            .map_err(|err| zbus::Error::from(err).into())
+    }
+
+    /// Encode `width`x`height` RGBA pixels, 8 bits per channel, as a PNG
+    /// image in a sealed memfd.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn from_rgba(width: u32, height: u32, rgba: &[u8]) -> Result<Self, Error> {
+        if width == 0 || height == 0 || rgba.len() as u64 != width as u64 * height as u64 * 4 {
+            return Err(Error::InvalidArgument(format!(
+                "Expected {}x{} RGBA pixels, found {} bytes",
+                width,
+                height,
+                rgba.len()
+            )));
+        }
+        Self::from_image(&encode_png(width, height, rgba))
+    }
+
+    /// The icon serialized like `g_icon_serialize()` does, the file descriptor
+    /// has to stay open until the notification is added.
+    pub fn serialize(&self) -> OwnedValue {
+        match self {
+            Self::Names(names) => Value::from(("themed", Value::from(names.clone()))).into(),
+            Self::Bytes(bytes) => Value::from(("bytes", Value::from(bytes.clone()))).into(),
+            Self::FileDescriptor(file) => {
+                Value::from(("file-descriptor", Value::from(Fd::from(file.as_raw_fd())))).into()
+            }
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn sealed_memfd(content: &[u8]) -> std::io::Result<File> {
+    use std::{
+        io::{Seek, SeekFrom, Write},
+        os::unix::io::FromRawFd,
+    };
+
+    use nix::{
+        fcntl::{fcntl, FcntlArg, SealFlag},
+        sys::memfd::{memfd_create, MemFdCreateFlag},
+    };
+
+    let fd = memfd_create(
+        c"ashpd-notification-icon",
+        MemFdCreateFlag::MFD_CLOEXEC | MemFdCreateFlag::MFD_ALLOW_SEALING,
+    )?;
+    // SAFETY: the file descriptor was just created and isn't used anywhere else.
+    let mut file = unsafe { File::from_raw_fd(fd) };
+    file.write_all(content)?;
+    file.seek(SeekFrom::Start(0))?;
+    fcntl(
+        file.as_raw_fd(),
+        FcntlArg::F_ADD_SEALS(
+            SealFlag::F_SEAL_SEAL
+                | SealFlag::F_SEAL_SHRINK
+                | SealFlag::F_SEAL_GROW
+                | SealFlag::F_SEAL_WRITE,
+        ),
+    )?;
+    Ok(file)
+}
+
+// A PNG image of RGBA pixels, the image data is stored without compression.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    fn crc32(bytes: &[u8]) -> u32 {
+        !bytes.iter().fold(!0u32, |crc, byte| {
+            (0..8).fold(crc ^ *byte as u32, |crc, _| {
+                (crc >> 1) ^ (0xedb8_8320 & (!(crc & 1)).wrapping_add(1))
+            })
+        })
+    }
+
+    fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let start = png.len();
+        png.extend_from_slice(kind);
+        png.extend_from_slice(data);
+        let crc = crc32(&png[start..]);
+        png.extend_from_slice(&crc.to_be_bytes());
+    }
+
+    // Every row starts with its filter type, none.
+    let raw = rgba
+        .chunks(width as usize * 4)
+        .flat_map(|row| std::iter::once(&0).chain(row))
+        .copied()
+        .collect::<Vec<u8>>();
+
+    // A zlib stream made of stored deflate blocks.
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(u16::MAX as usize).peekable();
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        zlib.push(blocks.peek().is_none() as u8);
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    let (a, b) = raw.iter().fold((1u32, 0u32), |(a, b), byte| {
+        let a = (a + *byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    zlib.extend_from_slice(&((b << 16) | a).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8 bits per channel, RGBA, deflate, adaptive filtering, no interlacing.
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut png, b"IHDR", &header);
+    chunk(&mut png, b"IDAT", &zlib);
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
 #[derive(Debug, Serialize, Deserialize, Type)]
 /// An invoked action.
 pub struct Action(String, String, Vec<OwnedValue>);
@@ -273,6 +653,21 @@ impl Action {
     pub fn parameter(&self) -> &Vec<OwnedValue> {
         &self.2
     }
+
+    /// The token to activate the window of the application with, sent along
+    /// the parameters since version 2 of the interface.
+    pub fn activation_token(&self) -> Option<ActivationToken> {
+        // The platform data is appended after the target of the action.
+        let platform_data = match self.2.last().map(|value| &**value) {
+            Some(Value::Dict(dict)) => dict,
+            _ => return None,
+        };
+        platform_data
+            .get::<str, str>("activation-token")
+            .ok()
+            .flatten()
+            .map(ActivationToken::from)
+    }
 }
 
 /// The interface lets sandboxed applications send and withdraw notifications.
@@ -293,20 +688,23 @@ impl Action {
 /// application.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Notification`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Notification).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.Notification")]
-pub struct NotificationProxy<'a>(zbus::Proxy<'a>);
+pub struct NotificationProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(NotificationProxy, INTERFACE_NOTIFICATION);
+impl_proxy_timeout!(NotificationProxy);
 
 impl<'a> NotificationProxy<'a> {
     /// Create a new instance of [`NotificationProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<NotificationProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Notification")?
+            .interface(INTERFACE_NOTIFICATION)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -321,8 +719,10 @@ impl<'a> NotificationProxy<'a> {
     /// See also [`ActionInvoked`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-Notification.ActionInvoked).
     #[doc(alias = "ActionInvoked")]
     #[doc(alias = "XdpPortal::notification-action-invoked")]
-    pub async fn receive_action_invoked(&self) -> Result<Action, Error> {
-        receive_signal(self.inner(), "ActionInvoked").await
+    pub async fn receive_action_invoked(
+        &self,
+    ) -> Result<impl Stream<Item = Action> + Unpin + 'a, Error> {
+        receive_signal_stream(&self.0, "ActionInvoked").await
     }
 
     /// Sends a notification.
@@ -346,7 +746,77 @@ impl<'a> NotificationProxy<'a> {
         id: &str,
         notification: Notification,
     ) -> Result<(), Error> {
-        call_method(self.inner(), "AddNotification", &(id, notification)).await
+        if notification.category.is_some() || notification.display_hint.is_some() {
+            self.validate(&notification).await?;
+        }
+        call_method(self, "AddNotification", &(id, notification)).await
+    }
+
+    /// The options of the notifications the backend supports, with the
+    /// values it accepts when they are restricted. Since version 2.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`SupportedOptions`](https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.Notification.html#org-freedesktop-portal-notification-supportedoptions).
+    #[doc(alias = "SupportedOptions")]
+    pub async fn supported_options(&self) -> Result<HashMap<String, OwnedValue>, Error> {
+        get_property(self, "SupportedOptions").await
+    }
+
+    /// What the backend supports, probed from the version of the interface
+    /// and from its [`supported_options`][`NotificationProxy::supported_options`].
+    ///
+    /// ```rust,no_run
+    /// use ashpd::desktop::notification::{Notification, NotificationProxy};
+    ///
+    /// async fn run() -> ashpd::Result<()> {
+    ///     let connection = zbus::Connection::session().await?;
+    ///     let proxy = NotificationProxy::new(&connection).await?;
+    ///
+    ///     let capabilities = proxy.capabilities().await?;
+    ///     let mut notification = Notification::new("Build finished");
+    ///     if capabilities.markup_body() {
+    ///         notification = notification.with_body("<b>ashpd</b> built in 2s");
+    ///     } else {
+    ///         notification = notification.with_body("ashpd built in 2s");
+    ///     }
+    ///     proxy.add_notification("build", notification).await
+    /// }
+    /// ```
+    pub async fn capabilities(&self) -> Result<NotificationCapabilities, Error> {
+        let version = get_property::<u32>(self, "version").await?;
+        let options = if version >= 2 {
+            self.supported_options().await?
+        } else {
+            HashMap::new()
+        };
+        Ok(NotificationCapabilities::new(version, options))
+    }
+
+    /// The display hints the backend supports, none before version 2, see
+    /// [`NotificationCapabilities::display_hints`].
+    pub async fn supported_display_hints(&self) -> Result<BitFlags<DisplayHint>, Error> {
+        Ok(self.capabilities().await?.display_hints())
+    }
+
+    /// Check the category and the display hints of `notification` against
+    /// the [`supported_options`][`NotificationProxy::supported_options`] of
+    /// the backend, [`add_notification`][`NotificationProxy::add_notification`]
+    /// does it when one of them is set.
+    ///
+    /// Fails with [`Error::InvalidArgument`] if the backend doesn't support
+    /// them. Only the options the backend lists are checked, and nothing is
+    /// checked with the backends older than version 2, they ignore the
+    /// unknown options.
+    pub async fn validate(&self, notification: &Notification) -> Result<(), Error> {
+        match self.capabilities().await {
+            Ok(capabilities) => capabilities.validate(notification),
+            Err(_err) => {
+                #[cfg(feature = "log")]
+                tracing::debug!("Not checking the notification options: {}", _err);
+                Ok(())
+            }
+        }
     }
 
     /// Withdraws a notification.
@@ -361,6 +831,284 @@ impl<'a> NotificationProxy<'a> {
     #[doc(alias = "RemoveNotification")]
     #[doc(alias = "xdp_portal_remove_notification")]
     pub async fn remove_notification(&self, id: &str) -> Result<(), Error> {
-        call_method(self.inner(), "RemoveNotification", &(id)).await
+        call_method(self, "RemoveNotification", &(id)).await
+    }
+
+    /// Sends a notification that is withdrawn once the returned
+    /// [`ScopedNotification`] is dropped.
+    ///
+    /// Useful for notifications tied to the lifetime of an operation, like a
+    /// progress notification.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Application-provided ID for this notification.
+    /// * `notification` - The notification.
+    pub async fn add_notification_scoped(
+        &self,
+        id: &str,
+        notification: Notification,
+    ) -> Result<ScopedNotification, Error> {
+        self.add_notification(id, notification).await?;
+        Ok(ScopedNotification {
+            connection: self.inner().connection().clone(),
+            id: id.to_owned(),
+            withdrawn: false,
+        })
+    }
+}
+
+/// A notification sent with [`NotificationProxy::add_notification_scoped`].
+///
+/// The notification is withdrawn in the background when the value is dropped,
+/// use [`ScopedNotification::remove`] to wait for it and get the error if any.
+///
+/// ```rust,no_run
+/// use ashpd::desktop::notification::{Notification, NotificationProxy};
+///
+/// async fn run() -> ashpd::Result<()> {
+///     let connection = zbus::Connection::session().await?;
+///     let proxy = NotificationProxy::new(&connection).await?;
+///
+///     let _notification = proxy
+///         .add_notification_scoped("copy", Notification::new("Copying files…"))
+///         .await?;
+///     // Copy the files, the notification is withdrawn at the end of the scope.
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ScopedNotification {
+    connection: zbus::Connection,
+    id: String,
+    withdrawn: bool,
+}
+
+impl ScopedNotification {
+    /// The application-provided ID of the notification.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Withdraw the notification now.
+    pub async fn remove(mut self) -> Result<(), Error> {
+        self.withdrawn = true;
+        remove_notification(&self.connection, &self.id).await
+    }
+
+    /// Keep the notification around, it won't be withdrawn when dropped.
+    pub fn keep(mut self) {
+        self.withdrawn = true;
+    }
+}
+
+impl Drop for ScopedNotification {
+    fn drop(&mut self) {
+        if self.withdrawn {
+            return;
+        }
+        let connection = self.connection.clone();
+        let id = std::mem::take(&mut self.id);
+        self.connection
+            .executor()
+            .spawn(async move {
+                #[allow(unused_variables)]
+                if let Err(err) = remove_notification(&connection, &id).await {
+                    #[cfg(feature = "log")]
+                    tracing::warn!("Failed to withdraw the notification '{}': {}", id, err);
+                }
+            })
+            .detach();
+    }
+}
+
+async fn remove_notification(connection: &zbus::Connection, id: &str) -> Result<(), Error> {
+    connection
+        .call_method(
+            Some(DESTINATION),
+            PATH,
+            Some(INTERFACE_NOTIFICATION),
+            "RemoveNotification",
+            &(id),
+        )
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use enumflags2::BitFlags;
+    use zbus::zvariant::Value;
+
+    use super::{
+        Action, Button, DisplayHint, Icon, Notification, NotificationCapabilities, Priority,
+    };
+    use crate::{helpers::assert_serialized_dict, ActivationToken, Error};
+
+    #[test]
+    fn notification() {
+        let button = Button {
+            label: "Reply".to_owned(),
+            action: "reply".to_owned(),
+            target: Some(Value::from(42u32).into()),
+            raw: Default::default(),
+        };
+        assert_serialized_dict(&button, &[("action", "s"), ("label", "s"), ("target", "v")]);
+
+        let notification = Notification {
+            title: "New message".to_owned(),
+            body: Some("Hello".to_owned()),
+            icon: Some(Value::from("mail-unread").into()),
+            priority: Some(Priority::High),
+            default_action: Some("open".to_owned()),
+            default_action_target: Some(Value::from(42u32).into()),
+            buttons: Some(vec![button]),
+            category: Some("im.received".to_owned()),
+            display_hint: Some(vec![DisplayHint::Persistent, DisplayHint::ShowAsNew]),
+            raw: Default::default(),
+        };
+        assert_serialized_dict(
+            &notification,
+            &[
+                ("body", "s"),
+                ("buttons", "aa{sv}"),
+                ("category", "s"),
+                ("default-action", "s"),
+                ("default-action-target", "v"),
+                ("display-hint", "as"),
+                ("icon", "v"),
+                ("priority", "s"),
+                ("title", "s"),
+            ],
+        );
+    }
+
+    #[test]
+    fn capabilities() {
+        let mut options = HashMap::new();
+        options.insert(
+            "display-hint".to_owned(),
+            Value::from(vec!["transient", "show-as-new", "unknown"]).into(),
+        );
+        options.insert("category".to_owned(), Value::from(42u32).into());
+        let capabilities = NotificationCapabilities::new(2, options);
+        assert_eq!(
+            capabilities.display_hints(),
+            DisplayHint::Transient | DisplayHint::ShowAsNew
+        );
+        assert_eq!(capabilities.categories(), None);
+        assert!(capabilities.sound());
+        assert!(capabilities.markup_body());
+
+        // The options of version 2 come with the version.
+        let capabilities = NotificationCapabilities::new(2, HashMap::new());
+        assert_eq!(capabilities.display_hints(), BitFlags::all());
+        let capabilities = NotificationCapabilities::new(1, HashMap::new());
+        assert!(capabilities.display_hints().is_empty());
+        assert!(!capabilities.sound());
+        assert!(!capabilities.markup_body());
+        assert_eq!(
+            "hide-on-lockscreen".parse::<DisplayHint>().unwrap(),
+            DisplayHint::HideOnLockscreen
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let notification = Notification::new("Incoming call")
+            .with_category("call.incoming")
+            .with_display_hint(DisplayHint::Persistent);
+
+        // Neither option is listed.
+        let capabilities = NotificationCapabilities::new(2, HashMap::new());
+        assert!(capabilities.validate(&notification).is_ok());
+
+        let mut options = HashMap::new();
+        options.insert(
+            "display-hint".to_owned(),
+            Value::from(vec!["transient"]).into(),
+        );
+        let capabilities = NotificationCapabilities::new(2, options.clone());
+        assert!(matches!(
+            capabilities.validate(&notification),
+            Err(Error::InvalidArgument(_))
+        ));
+        // Version 1 backends ignore the unknown options.
+        let capabilities = NotificationCapabilities::new(1, options);
+        assert!(capabilities.validate(&notification).is_ok());
+
+        let mut options = HashMap::new();
+        options.insert(
+            "category".to_owned(),
+            Value::from(vec!["im.received"]).into(),
+        );
+        let capabilities = NotificationCapabilities::new(2, options);
+        assert!(matches!(
+            capabilities.validate(&notification),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn activation_token() {
+        let mut platform_data = HashMap::new();
+        platform_data.insert("activation-token", Value::from("token"));
+        let action = Action(
+            "a".to_owned(),
+            "reply".to_owned(),
+            vec![Value::from("hi").into(), Value::from(platform_data).into()],
+        );
+        assert_eq!(
+            action.activation_token(),
+            Some(ActivationToken::from("token"))
+        );
+
+        // Version 1 only sends the target.
+        let action = Action(
+            "a".to_owned(),
+            "reply".to_owned(),
+            vec![Value::from("hi").into()],
+        );
+        assert_eq!(action.activation_token(), None);
+    }
+
+    #[test]
+    fn icon() {
+        let icon = Icon::with_name("mail-unread").serialize();
+        assert_eq!(icon.value_signature(), "(sv)");
+        let icon = Icon::Bytes(vec![0, 1, 2]).serialize();
+        assert_eq!(icon.value_signature(), "(sv)");
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn sealed_icon() {
+        use std::{io::Read, os::unix::io::AsRawFd};
+
+        use nix::fcntl::{fcntl, FcntlArg, SealFlag};
+
+        let png = super::encode_png(2, 1, &[255, 0, 0, 255, 0, 0, 255, 128]);
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x02\0\0\0\x01"));
+        // The IEND chunk, with its well known CRC.
+        assert!(png.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+
+        let mut file = match Icon::from_image(&png).unwrap() {
+            Icon::FileDescriptor(file) => file,
+            icon => panic!("Expected a file descriptor, found {:?}", icon),
+        };
+        let seals = fcntl(file.as_raw_fd(), FcntlArg::F_GET_SEALS).unwrap();
+        assert!(SealFlag::from_bits_truncate(seals).contains(
+            SealFlag::F_SEAL_SEAL
+                | SealFlag::F_SEAL_SHRINK
+                | SealFlag::F_SEAL_GROW
+                | SealFlag::F_SEAL_WRITE
+        ));
+        let mut content = Vec::new();
+        file.read_to_end(&mut content).unwrap();
+        assert_eq!(content, png);
+
+        assert!(Icon::from_rgba(2, 2, &[0; 4]).is_err());
     }
 }
@@ -27,7 +27,9 @@
 //!     let connection = zbus::Connection::session().await?;
 //!     let proxy = OpenURIProxy::new(&connection).await?;
 //!
-//!     proxy.open_file(&WindowIdentifier::default(), &file, false, true).await?;
+//!     proxy
+//!         .open_file(&WindowIdentifier::default(), &file, false, true, None)
+//!         .await?;
 //!     Ok(())
 //! }
 //! ```
@@ -59,7 +61,9 @@
 //!     let connection = zbus::Connection::session().await?;
 //!     let proxy = OpenURIProxy::new(&connection).await?;
 //!
-//!     proxy.open_directory(&WindowIdentifier::default(), &directory).await?;
+//!     proxy
+//!         .open_directory(&WindowIdentifier::default(), &directory, None)
+//!         .await?;
 //!     Ok(())
 //! }
 //! ```
@@ -69,11 +73,11 @@
 //!
 //!```rust,no_run
 //! use ashpd::desktop::open_uri;
-//! use ashpd::WindowIdentifier;
+//! use ashpd::{url::Url, WindowIdentifier};
 //!
 //! async fn run() -> ashpd::Result<()> {
-//!     let uri = "file:///home/bilelmoussaoui/Downloads/adwaita-night.jpg";
-//!     open_uri::open_uri(&WindowIdentifier::default(), uri, false, true).await?;
+//!     let uri = Url::parse("file:///home/bilelmoussaoui/Downloads/adwaita-night.jpg").unwrap();
+//!     open_uri::open_uri(&WindowIdentifier::default(), &uri, false, true).await?;
 //!     Ok(())
 //! }
 //! ```
@@ -82,43 +86,54 @@
 //!
 //! ```rust,no_run
 //! use ashpd::desktop::open_uri::OpenURIProxy;
-//! use ashpd::WindowIdentifier;
+//! use ashpd::{url::Url, WindowIdentifier};
 //!
 //! async fn run() -> ashpd::Result<()> {
 //!     let connection = zbus::Connection::session().await?;
 //!     let proxy = OpenURIProxy::new(&connection).await?;
-//!     let uri = "https://github.com/bilelmoussaoui/ashpd";
+//!     let uri = Url::parse("https://github.com/bilelmoussaoui/ashpd").unwrap();
 //!
-//!     proxy.open_uri(&WindowIdentifier::default(), uri, false, true).await?;
+//!     proxy
+//!         .open_uri(&WindowIdentifier::default(), &uri, false, true, None)
+//!         .await?;
 //!     Ok(())
 //! }
 //! ```
 
-use std::os::unix::prelude::AsRawFd;
+use std::{
+    collections::HashMap,
+    os::unix::prelude::AsRawFd,
+    sync::{Arc, Mutex},
+};
 
+use url::Url;
 use zbus::zvariant::{DeserializeDict, Fd, SerializeDict, Type};
 
-use super::{HandleToken, DESTINATION, PATH};
-use crate::{helpers::call_basic_response_method, Error, WindowIdentifier};
+use super::{HandleToken, PortalRequest, DESTINATION, INTERFACE_OPEN_URI, PATH};
+use crate::{
+    helpers::{call_method, call_window_basic_response_method, session_connection},
+    ActivationToken, Error, WindowIdentifier,
+};
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
 /// Specified options for a [`OpenURIProxy::open_directory`] request.
 #[zvariant(signature = "dict")]
 struct OpenDirOptions {
     /// A string that will be used as the last element of the handle.
     handle_token: HandleToken,
     // Token to activate the chosen application.
-    activation_token: Option<String>,
+    activation_token: Option<ActivationToken>,
 }
 
 impl OpenDirOptions {
-    #[allow(dead_code)]
-    pub fn set_activation_token(&mut self, activation_token: &str) {
-        self.activation_token = Some(activation_token.to_string());
+    /// Sets the token to activate the chosen application with.
+    pub fn with_activation_token(mut self, activation_token: Option<&ActivationToken>) -> Self {
+        self.activation_token = activation_token.cloned();
+        self
     }
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
 /// Specified options for a [`OpenURIProxy::open_file`] or
 /// [`OpenURIProxy::open_uri`] request.
 #[zvariant(signature = "dict")]
@@ -134,47 +149,56 @@ struct OpenFileOptions {
     /// false, the portal may use a default or pick the last choice.
     ask: Option<bool>,
     // Token to activate the chosen application.
-    activation_token: Option<String>,
+    activation_token: Option<ActivationToken>,
 }
 
 impl OpenFileOptions {
-    /// Whether the file should be writeable or not.
-    pub fn writeable(mut self, writeable: bool) -> Self {
-        self.writeable = Some(writeable);
-        self
+    options_setters! {
+        @setters
+        /// Whether the file should be writeable or not.
+        with_writeable(writeable: bool);
+        /// Whether to always ask the user which application to use or not.
+        with_ask(ask: bool);
     }
 
-    /// Whether to always ask the user which application to use or not.
-    pub fn ask(mut self, ask: bool) -> Self {
-        self.ask = Some(ask);
+    /// Sets the token to activate the chosen application with.
+    pub fn with_activation_token(mut self, activation_token: Option<&ActivationToken>) -> Self {
+        self.activation_token = activation_token.cloned();
         self
     }
-
-    #[allow(dead_code)]
-    pub fn set_activation_token(&mut self, activation_token: &str) {
-        self.activation_token = Some(activation_token.to_string());
-    }
 }
 
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
+/// Specified options for a [`OpenURIProxy::scheme_supported`] request.
+#[zvariant(signature = "dict")]
+struct SchemeSupportedOptions {}
+
 /// The interface lets sandboxed applications open URIs
 /// (e.g. a http: link to the applications homepage) under the control of the
 /// user.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.OpenURI`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.OpenURI).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.OpenURI")]
-pub struct OpenURIProxy<'a>(zbus::Proxy<'a>);
+pub struct OpenURIProxy<'a>(
+    zbus::Proxy<'a>,
+    Option<std::time::Duration>,
+    Arc<Mutex<HashMap<String, bool>>>,
+);
+
+impl_try_from_proxy!(OpenURIProxy, INTERFACE_OPEN_URI, Default::default());
+impl_proxy_timeout!(OpenURIProxy);
 
 impl<'a> OpenURIProxy<'a> {
     /// Create a new instance of [`OpenURIProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<OpenURIProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.OpenURI")?
+            .interface(INTERFACE_OPEN_URI)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None, Default::default()))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -188,25 +212,28 @@ impl<'a> OpenURIProxy<'a> {
     ///
     /// * `identifier` - Identifier for the application window.
     /// * `directory` - File descriptor for a file.
+    /// * `activation_token` - The token to activate the file browser with.
     ///
     /// # Specifications
     ///
     /// See also [`OpenDirectory`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-OpenURI.OpenDirectory).
     #[doc(alias = "OpenDirectory")]
     #[doc(alias = "xdp_portal_open_directory")]
-    pub async fn open_directory(
-        &self,
-        identifier: &WindowIdentifier,
+    pub fn open_directory<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
         directory: &impl AsRawFd,
-    ) -> Result<(), Error> {
-        let options = OpenDirOptions::default();
-        call_basic_response_method(
-            self.inner(),
-            &options.handle_token,
+        activation_token: Option<&ActivationToken>,
+    ) -> PortalRequest<'p, ()> {
+        let options = OpenDirOptions::default().with_activation_token(activation_token);
+        let handle_token = options.handle_token.copy();
+        call_window_basic_response_method(
+            self,
+            identifier,
+            &handle_token,
             "OpenDirectory",
-            &(&identifier, Fd::from(directory.as_raw_fd()), &options),
+            (identifier, Fd::from(directory.as_raw_fd()), options),
         )
-        .await
     }
 
     /// Asks to open a local file.
@@ -218,26 +245,33 @@ impl<'a> OpenURIProxy<'a> {
     /// * `writeable` - Whether the file should be writeable or not.
     /// * `ask` - Whether to always ask the user which application to use or
     ///   not.
+    /// * `activation_token` - The token to activate the chosen application
+    ///   with.
     ///
     /// # Specifications
     ///
     /// See also [`OpenFile`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-OpenURI.OpenFile).
     #[doc(alias = "OpenFile")]
-    pub async fn open_file(
-        &self,
-        identifier: &WindowIdentifier,
+    pub fn open_file<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
         file: &impl AsRawFd,
         writeable: bool,
         ask: bool,
-    ) -> Result<(), Error> {
-        let options = OpenFileOptions::default().ask(ask).writeable(writeable);
-        call_basic_response_method(
-            self.inner(),
-            &options.handle_token,
+        activation_token: Option<&ActivationToken>,
+    ) -> PortalRequest<'p, ()> {
+        let options = OpenFileOptions::default()
+            .with_ask(ask)
+            .with_writeable(writeable)
+            .with_activation_token(activation_token);
+        let handle_token = options.handle_token.copy();
+        call_window_basic_response_method(
+            self,
+            identifier,
+            &handle_token,
             "OpenFile",
-            &(&identifier, Fd::from(file.as_raw_fd()), &options),
+            (identifier, Fd::from(file.as_raw_fd()), options),
         )
-        .await
     }
 
     /// Asks to open a local file.
@@ -249,6 +283,8 @@ impl<'a> OpenURIProxy<'a> {
     /// * `writeable` - Whether the file should be writeable or not.
     /// * `ask` - Whether to always ask the user which application to use or
     ///   not.
+    /// * `activation_token` - The token to activate the chosen application
+    ///   with.
     ///
     /// *Note* that `file` uris are explicitly not supported by this method.
     /// Use [`Self::open_file`] or [`Self::open_directory`] instead.
@@ -258,35 +294,87 @@ impl<'a> OpenURIProxy<'a> {
     /// See also [`OpenURI`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-OpenURI.OpenURI).
     #[doc(alias = "OpenURI")]
     #[doc(alias = "xdp_portal_open_uri")]
-    pub async fn open_uri(
-        &self,
-        identifier: &WindowIdentifier,
-        uri: &str,
+    pub fn open_uri<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
+        uri: &'p Url,
         writeable: bool,
         ask: bool,
-    ) -> Result<(), Error> {
-        let options = OpenFileOptions::default().ask(ask).writeable(writeable);
-        call_basic_response_method(
-            self.inner(),
-            &options.handle_token,
+        activation_token: Option<&ActivationToken>,
+    ) -> PortalRequest<'p, ()> {
+        let options = OpenFileOptions::default()
+            .with_ask(ask)
+            .with_writeable(writeable)
+            .with_activation_token(activation_token);
+        let handle_token = options.handle_token.copy();
+        call_window_basic_response_method(
+            self,
+            identifier,
+            &handle_token,
             "OpenURI",
-            &(&identifier, uri, &options),
+            (identifier, uri.as_str(), options),
         )
-        .await
+    }
+
+    /// Checks whether the given scheme is handled by an application on the
+    /// host, before offering to open a `matrix:` URI for example.
+    ///
+    /// The answers are cached for the lifetime of the proxy and its clones,
+    /// create a new proxy to query the portal again.
+    ///
+    /// # Arguments
+    ///
+    /// * `scheme` - The URI scheme, with or without the trailing colon.
+    ///
+    /// # Required version
+    ///
+    /// The method requires the 5th version implementation of the portal and
+    /// fails with [`Error::RequiresVersion`] otherwise.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`SchemeSupported`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-OpenURI.SchemeSupported).
+    #[doc(alias = "SchemeSupported")]
+    pub async fn scheme_supported(&self, scheme: &str) -> Result<bool, Error> {
+        let scheme = scheme.trim_end_matches(':').to_ascii_lowercase();
+        if let Some(supported) = self.2.lock().unwrap().get(&scheme) {
+            return Ok(*supported);
+        }
+        let version = self
+            .inner()
+            .get_property::<u32>("version")
+            .await
+            .unwrap_or(1);
+        if version < 5 {
+            return Err(Error::RequiresVersion(5, version));
+        }
+        let options = SchemeSupportedOptions::default();
+        let supported: bool = call_method(self, "SchemeSupported", &(&scheme, &options)).await?;
+        self.2.lock().unwrap().insert(scheme, supported);
+        Ok(supported)
     }
 }
 
+/// A handy wrapper around [`OpenURIProxy::scheme_supported`].
+pub async fn scheme_supported(scheme: &str) -> Result<bool, Error> {
+    let connection = session_connection().await?;
+    let proxy = OpenURIProxy::new(&connection).await?;
+    proxy.scheme_supported(scheme).await
+}
+
 #[doc(alias = "xdp_portal_open_uri")]
 /// A handy wrapper around [`OpenURIProxy::open_uri`].
 pub async fn open_uri(
     identifier: &WindowIdentifier,
-    uri: &str,
+    uri: &Url,
     writeable: bool,
     ask: bool,
 ) -> Result<(), Error> {
-    let connection = zbus::Connection::session().await?;
+    let connection = session_connection().await?;
     let proxy = OpenURIProxy::new(&connection).await?;
-    proxy.open_uri(identifier, uri, writeable, ask).await?;
+    proxy
+        .open_uri(identifier, uri, writeable, ask, None)
+        .await?;
     Ok(())
 }
 
@@ -297,9 +385,11 @@ pub async fn open_file(
     writeable: bool,
     ask: bool,
 ) -> Result<(), Error> {
-    let connection = zbus::Connection::session().await?;
+    let connection = session_connection().await?;
     let proxy = OpenURIProxy::new(&connection).await?;
-    proxy.open_file(identifier, file, writeable, ask).await?;
+    proxy
+        .open_file(identifier, file, writeable, ask, None)
+        .await?;
     Ok(())
 }
 
@@ -309,8 +399,72 @@ pub async fn open_directory(
     identifier: &WindowIdentifier,
     directory: &impl AsRawFd,
 ) -> Result<(), Error> {
-    let connection = zbus::Connection::session().await?;
+    let connection = session_connection().await?;
     let proxy = OpenURIProxy::new(&connection).await?;
-    proxy.open_directory(identifier, directory).await?;
+    proxy.open_directory(identifier, directory, None).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::{OpenDirOptions, OpenFileOptions};
+    use crate::{desktop::HandleToken, helpers::assert_serialized_dict, ActivationToken};
+
+    #[test]
+    fn options() {
+        let options = OpenDirOptions {
+            handle_token: HandleToken::default(),
+            activation_token: Some(ActivationToken::from("token")),
+        };
+        assert_serialized_dict(
+            &options,
+            &[("activation_token", "s"), ("handle_token", "s")],
+        );
+
+        let options = OpenFileOptions {
+            handle_token: HandleToken::default(),
+            writeable: Some(true),
+            ask: Some(true),
+            activation_token: Some(ActivationToken::from("token")),
+        };
+        assert_serialized_dict(
+            &options,
+            &[
+                ("activation_token", "s"),
+                ("ask", "b"),
+                ("handle_token", "s"),
+                ("writeable", "b"),
+            ],
+        );
+    }
+
+    #[cfg(feature = "backend")]
+    #[test]
+    fn scheme_supported_requires_version() {
+        use futures::executor::block_on;
+        use zbus::dbus_interface;
+
+        use super::OpenURIProxy;
+        use crate::{backend::peer_connections, desktop::PATH, Error};
+
+        struct OpenURI;
+
+        #[dbus_interface(name = "org.freedesktop.portal.OpenURI")]
+        impl OpenURI {
+            #[dbus_interface(property, name = "version")]
+            fn version(&self) -> u32 {
+                4
+            }
+        }
+
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            backend.object_server().at(PATH, OpenURI).await.unwrap();
+            let proxy = OpenURIProxy::new(&frontend).await.unwrap();
+            assert!(matches!(
+                proxy.scheme_supported("https").await,
+                Err(Error::RequiresVersion(5, 4))
+            ));
+        });
+    }
+}
@@ -0,0 +1,200 @@
+//! # Examples
+//!
+//! Whether starting a remote desktop session with a previous restore token
+//! will show a dialog.
+//!
+//! ```rust,no_run
+//! use ashpd::desktop::{permission_store::PermissionStatus, remote_desktop};
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let status = remote_desktop::permission_status("org.example.App", Some("token")).await?;
+//!     if status == PermissionStatus::Prompt {
+//!         println!("The user will be asked to select the devices");
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use zbus::zvariant::OwnedValue;
+
+use super::{INTERFACE_PERMISSION_STORE, PERMISSION_STORE_DESTINATION, PERMISSION_STORE_PATH};
+use crate::{
+    helpers::{call_method, get_property, session_connection},
+    Error,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// What happens when an application uses a portal, according to the
+/// permissions stored for it.
+pub enum PermissionStatus {
+    /// The user will be asked.
+    Prompt,
+    /// The access is granted without asking, the portal may still show a
+    /// dialog for the interactive requests.
+    Allowed,
+    /// The access is denied without asking.
+    Denied,
+}
+
+impl PermissionStatus {
+    // The permissions of the portals are `yes` or `no`, the other values are
+    // portal specific data of a granted access. An entry without any value
+    // grants the access as well, the restore tokens of the screen casts for
+    // example.
+    fn from_permissions(permissions: Option<&[String]>) -> Self {
+        match permissions {
+            Some([permission, ..]) if permission == "no" => Self::Denied,
+            Some(_) => Self::Allowed,
+            None => Self::Prompt,
+        }
+    }
+}
+
+fn is_not_found(err: &Error) -> bool {
+    matches!(
+        err.root(),
+        Error::Zbus(zbus::Error::MethodError(name, _, _))
+            if name.as_str() == "org.freedesktop.portal.Error.NotFound"
+    )
+}
+
+/// The store of the permissions granted by the user through the portals,
+/// organized in tables of ids, a table per portal.
+///
+/// **Note** the store is not reachable from inside of the sandbox.
+///
+/// Wrapper of the DBus interface: [`org.freedesktop.impl.portal.PermissionStore`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.impl.portal.PermissionStore).
+#[derive(Clone)]
+#[doc(alias = "org.freedesktop.impl.portal.PermissionStore")]
+pub struct PermissionStoreProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(PermissionStoreProxy, INTERFACE_PERMISSION_STORE);
+impl_proxy_timeout!(PermissionStoreProxy);
+
+impl<'a> PermissionStoreProxy<'a> {
+    /// Create a new instance of [`PermissionStoreProxy`].
+    pub async fn new(connection: &zbus::Connection) -> Result<PermissionStoreProxy<'a>, Error> {
+        let proxy = zbus::ProxyBuilder::new_bare(connection)
+            .interface(INTERFACE_PERMISSION_STORE)?
+            .path(PERMISSION_STORE_PATH)?
+            .destination(PERMISSION_STORE_DESTINATION)?
+            .build()
+            .await?;
+        Ok(Self(proxy, None))
+    }
+
+    /// Get a reference to the underlying Proxy.
+    pub fn inner(&self) -> &zbus::Proxy<'_> {
+        &self.0
+    }
+
+    /// The permissions of the applications for `id`, along with the data
+    /// stored by the portal.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table of the portal, `screencast` for example.
+    /// * `id` - The resource of the portal.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`Lookup`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-impl-portal-PermissionStore.Lookup).
+    #[doc(alias = "Lookup")]
+    pub async fn lookup(
+        &self,
+        table: &str,
+        id: &str,
+    ) -> Result<(HashMap<String, Vec<String>>, OwnedValue), Error> {
+        call_method(self, "Lookup", &(table, id)).await
+    }
+
+    /// The permissions of `app_id` for `id`, none if nothing is stored for
+    /// them.
+    pub async fn permissions(
+        &self,
+        table: &str,
+        id: &str,
+        app_id: &str,
+    ) -> Result<Option<Vec<String>>, Error> {
+        match self.lookup(table, id).await {
+            Ok((mut permissions, _)) => Ok(permissions.remove(app_id)),
+            Err(err) if is_not_found(&err) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Whether `app_id` will be asked, allowed or denied access to `id`.
+    pub async fn permission_status(
+        &self,
+        table: &str,
+        id: &str,
+        app_id: &str,
+    ) -> Result<PermissionStatus, Error> {
+        let permissions = self.permissions(table, id, app_id).await?;
+        Ok(PermissionStatus::from_permissions(permissions.as_deref()))
+    }
+}
+
+// The status of the portal `interface` storing its restore tokens in `table`
+// since `version`.
+pub(crate) async fn restore_token_status(
+    interface: &str,
+    version: u32,
+    table: &str,
+    app_id: &str,
+    restore_token: Option<&str>,
+) -> Result<PermissionStatus, Error> {
+    let restore_token = match restore_token {
+        Some(restore_token) => restore_token,
+        None => return Ok(PermissionStatus::Prompt),
+    };
+    let connection = session_connection().await?;
+    let portal: zbus::Proxy<'_> = zbus::ProxyBuilder::new_bare(&connection)
+        .interface(interface)?
+        .path(super::PATH)?
+        .destination(super::DESTINATION)?
+        .build()
+        .await?;
+    if get_property::<u32>(&portal, "version").await? < version {
+        return Ok(PermissionStatus::Prompt);
+    }
+    let store = PermissionStoreProxy::new(&connection).await?;
+    store.permission_status(table, restore_token, app_id).await
+}
+
+// The status of the portals storing a `yes` or `no` permission for `id`.
+pub(crate) async fn permission_status(
+    table: &str,
+    id: &str,
+    app_id: &str,
+) -> Result<PermissionStatus, Error> {
+    let connection = session_connection().await?;
+    let store = PermissionStoreProxy::new(&connection).await?;
+    store.permission_status(table, id, app_id).await
+}
+
+#[cfg(test)]
+mod test {
+    use super::PermissionStatus;
+
+    #[test]
+    fn from_permissions() {
+        let status = |permissions: &[&str]| {
+            let permissions = permissions
+                .iter()
+                .map(|permission| permission.to_string())
+                .collect::<Vec<_>>();
+            PermissionStatus::from_permissions(Some(&permissions))
+        };
+        assert_eq!(status(&["yes"]), PermissionStatus::Allowed);
+        assert_eq!(status(&["no"]), PermissionStatus::Denied);
+        assert_eq!(status(&["EXACT", "1647525124"]), PermissionStatus::Allowed);
+        assert_eq!(status(&[]), PermissionStatus::Allowed);
+        assert_eq!(
+            PermissionStatus::from_permissions(None),
+            PermissionStatus::Prompt
+        );
+    }
+}
@@ -1,4 +1,6 @@
-use super::{DESTINATION, PATH};
+use futures::{Stream, StreamExt};
+
+use super::{DESTINATION, INTERFACE_POWER_PROFILE_MONITOR, PATH};
 use crate::Error;
 
 /// The interface provides information about the user-selected system-wide power profile, to sandboxed applications.
@@ -7,20 +9,23 @@ use crate::Error;
 /// via a library API such as the GLib [`gio::PowerProfileMonitor`](https://gtk-rs.org/gtk-rs-core/stable/latest/docs/gio/struct.PowerProfileMonitor.html) interface.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.PowerProfileMonitor`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.PowerProfileMonitor).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.PowerProfileMonitor")]
-pub struct PowerProfileMonitorProxy<'a>(zbus::Proxy<'a>);
+pub struct PowerProfileMonitorProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(PowerProfileMonitorProxy, INTERFACE_POWER_PROFILE_MONITOR);
+impl_proxy_timeout!(PowerProfileMonitorProxy);
 
 impl<'a> PowerProfileMonitorProxy<'a> {
     /// Create a new instance of [`PowerProfileMonitorProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<PowerProfileMonitorProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.PowerProfileMonitor")?
+            .interface(INTERFACE_POWER_PROFILE_MONITOR)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -28,12 +33,36 @@ impl<'a> PowerProfileMonitorProxy<'a> {
         &self.0
     }
 
-    /// Whether the power saver is enabled.
+    portal_members! {
+        /// Whether the power saver is enabled.
+        #[doc(alias = "power-saver-enabled")]
+        pub fn is_enabled() -> bool = property "power-saver-enabled";
+    }
+
+    /// Listen to the changes of whether the power saver is enabled, see
+    /// [`PowerProfileMonitorProxy::is_enabled`].
     #[doc(alias = "power-saver-enabled")]
-    pub async fn is_enabled(&self) -> Result<bool, Error> {
-        self.inner()
-            .get_property::<bool>("power-saver-enabled")
-            .await
-            .map_err(From::from)
+    pub async fn receive_power_saver_changed(
+        &self,
+    ) -> Result<impl Stream<Item = bool> + Unpin + 'a, Error> {
+        let changes = self.0.receive_property_changed("power-saver-enabled").await;
+        Ok(changes
+            .then(|changed| async move { changed.get().await.ok() })
+            .filter_map(futures::future::ready)
+            .boxed())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PowerProfileMonitorProxy;
+    use crate::{desktop::INTERFACE_POWER_PROFILE_MONITOR, helpers::assert_members};
+
+    #[test]
+    fn members() {
+        assert_members(
+            INTERFACE_POWER_PROFILE_MONITOR,
+            &PowerProfileMonitorProxy::members(),
+        );
     }
 }
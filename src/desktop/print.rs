@@ -42,13 +42,14 @@ use std::{fmt, str::FromStr};
 use serde::{Deserialize, Serialize, Serializer};
 use zbus::zvariant::{DeserializeDict, Fd, SerializeDict, Signature, Type};
 
-use super::{HandleToken, DESTINATION, PATH};
+use super::{HandleToken, PortalRequest, DESTINATION, INTERFACE_PRINT, PATH};
 use crate::{
-    helpers::{call_basic_response_method, call_request_method},
+    helpers::{call_window_basic_response_method, call_window_request_method},
+    raw_options::RawOptions,
     Error, WindowIdentifier,
 };
 
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
 /// The page orientation.
 pub enum Orientation {
     /// Landscape.
@@ -130,7 +131,7 @@ impl Type for Orientation {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
 /// The print quality.
 pub enum Quality {
     /// Draft quality.
@@ -207,33 +208,27 @@ impl Type for Quality {
     }
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+#[derive(Type, Debug, Default, Clone, PartialEq)]
 /// Print settings to set in the print dialog.
 #[zvariant(signature = "dict")]
 pub struct Settings {
     /// One of landscape, portrait, reverse_landscape or reverse_portrait.
     pub orientation: Option<Orientation>,
     /// A paper name according to [PWG 5101.1-2002](ftp://ftp.pwg.org/pub/pwg/candidates/cs-pwgmsn10-20020226-5101.1.pdf)
-    #[zvariant(rename = "paper-format")]
     pub paper_format: Option<String>,
     /// Paper width, in millimeters.
-    #[zvariant(rename = "paper-width")]
     pub paper_width: Option<String>,
     /// Paper height, in millimeters.
-    #[zvariant(rename = "paper-height")]
     pub paper_height: Option<String>,
     /// The number of copies to print.
-    #[zvariant(rename = "n-copies")]
     pub n_copies: Option<String>,
     /// The default paper source.
-    #[zvariant(rename = "default-source")]
     pub default_source: Option<String>,
     /// Print quality.
     pub quality: Option<Quality>,
     /// The resolution, sets both resolution-x & resolution-y
     pub resolution: Option<String>,
     /// Whether to use color.
-    #[zvariant(rename = "use-color")]
     pub use_color: Option<bool>,
     /// Duplex printing mode, one of simplex, horizontal or vertical.
     pub duplex: Option<String>,
@@ -242,7 +237,6 @@ pub struct Settings {
     /// Whether to reverse the order of printed pages.
     pub reverse: Option<String>,
     /// A media type according to [PWG 5101.1-2002](ftp://ftp.pwg.org/pub/pwg/candidates/cs-pwgmsn10-20020226-5101.1.pdf)
-    #[zvariant(rename = "media-type")]
     pub media_type: Option<String>,
     /// The dithering to use, one of fine, none, coarse, lineart, grayscale or
     /// error-diffusion.
@@ -250,250 +244,133 @@ pub struct Settings {
     /// The scale in percent
     pub scale: Option<String>,
     /// What pages to print, one of all, selection, current or ranges.
-    #[zvariant(rename = "print-pages")]
     pub print_pages: Option<String>,
     /// A list of page ranges, formatted like this: 0-2,4,9-11.
-    #[zvariant(rename = "page-ranges")]
     pub page_ranges: Option<String>,
     /// What pages to print, one of all, even or odd.
-    #[zvariant(rename = "page-set")]
     pub page_set: Option<String>,
     /// The finishings.
     pub finishings: Option<String>,
     /// The number of pages per sheet.
-    #[zvariant(rename = "number-up")]
     pub number_up: Option<String>,
     /// One of lrtb, lrbt, rltb, rlbt, tblr, tbrl, btlr, btrl.
-    #[zvariant(rename = "number-up-layout")]
     pub number_up_layout: Option<String>,
-    #[zvariant(rename = "output-bin")]
     /// The output bin.
     pub output_bin: Option<String>,
     /// The horizontal resolution in dpi.
-    #[zvariant(rename = "resolution-x")]
     pub resolution_x: Option<String>,
     /// The vertical resolution in dpi.
-    #[zvariant(rename = "resolution-y")]
     pub resolution_y: Option<String>,
     /// The resolution in lpi (lines per inch).
-    #[zvariant(rename = "printer-lpi")]
     pub print_lpi: Option<String>,
     /// Basename to use for print-to-file.
-    #[zvariant(rename = "output-basename")]
     pub output_basename: Option<String>,
     /// Format to use for print-to-file, one of PDF, PS, SVG
-    #[zvariant(rename = "output-file-format")]
     pub output_file_format: Option<String>,
     /// The uri used for print-to file.
-    #[zvariant(rename = "output-uri")]
     pub output_uri: Option<String>,
+    /// The options without a field.
+    raw: RawOptions,
 }
 
-impl Settings {
-    /// Sets the orientation.
-    #[must_use]
-    pub fn orientation(mut self, orientation: Orientation) -> Self {
-        self.orientation = Some(orientation);
-        self
-    }
-
-    /// Sets the paper name.
-    #[must_use]
-    pub fn paper_format(mut self, paper_format: &str) -> Self {
-        self.paper_format = Some(paper_format.to_string());
-        self
-    }
-
-    /// Sets the paper width.
-    #[must_use]
-    pub fn paper_width(mut self, paper_width: &str) -> Self {
-        self.paper_width = Some(paper_width.to_string());
-        self
-    }
-
-    /// Sets the paper height.
-    #[must_use]
-    pub fn paper_height(mut self, paper_height: &str) -> Self {
-        self.paper_height = Some(paper_height.to_string());
-        self
-    }
-
-    /// Sets the number of copies to print.
-    #[must_use]
-    pub fn n_copies(mut self, n_copies: &str) -> Self {
-        self.n_copies = Some(n_copies.to_string());
-        self
-    }
-
-    /// Sets the default paper source.
-    #[must_use]
-    pub fn default_source(mut self, default_source: &str) -> Self {
-        self.default_source = Some(default_source.to_string());
-        self
-    }
-
-    /// Sets the print quality.
-    #[must_use]
-    pub fn quality(mut self, quality: Quality) -> Self {
-        self.quality = Some(quality);
-        self
-    }
-
-    /// Sets the resolution, both resolution-x & resolution-y.
-    #[must_use]
-    pub fn resolution(mut self, resolution: &str) -> Self {
-        self.resolution = Some(resolution.to_string());
-        self
-    }
-
-    /// Sets whether to use color.
-    #[must_use]
-    pub fn use_color(mut self, use_color: bool) -> Self {
-        self.use_color = Some(use_color);
-        self
-    }
-
-    /// Sets the duplex printing mode.
-    #[must_use]
-    pub fn duplex(mut self, duplex: &str) -> Self {
-        self.duplex = Some(duplex.to_string());
-        self
-    }
-
-    /// Whether to collate copies.
-    #[must_use]
-    pub fn collate(mut self, collate: &str) -> Self {
-        self.collate = Some(collate.to_string());
-        self
-    }
-
-    /// Sets whether to reverse the order of the printed pages.
-    #[must_use]
-    pub fn reverse(mut self, reverse: &str) -> Self {
-        self.reverse = Some(reverse.to_string());
-        self
-    }
-
-    /// Sets the media type.
-    #[must_use]
-    pub fn media_type(mut self, media_type: &str) -> Self {
-        self.media_type = Some(media_type.to_string());
-        self
-    }
-
-    /// Sets the dithering to use.
-    #[must_use]
-    pub fn dither(mut self, dither: &str) -> Self {
-        self.dither = Some(dither.to_string());
-        self
-    }
-
-    /// Sets the page scale in percent.
-    #[must_use]
-    pub fn scale(mut self, scale: &str) -> Self {
-        self.scale = Some(scale.to_string());
-        self
-    }
-
-    /// Sets what pages to print, one of all, selection, current or ranges.
-    #[must_use]
-    pub fn print_pages(mut self, print_pages: &str) -> Self {
-        self.print_pages = Some(print_pages.to_string());
-        self
-    }
-
-    /// Sets a list of page ranges, formatted like this: 0-2,4,9-11.
-    #[must_use]
-    pub fn page_ranges(mut self, page_ranges: &str) -> Self {
-        self.page_ranges = Some(page_ranges.to_string());
-        self
-    }
-
-    /// Sets what pages to print, one of all, even or odd.
-    #[must_use]
-    pub fn page_set(mut self, page_set: &str) -> Self {
-        self.page_set = Some(page_set.to_string());
-        self
-    }
-
-    /// Sets the finishings.
-    #[must_use]
-    pub fn finishings(mut self, finishings: &str) -> Self {
-        self.finishings = Some(finishings.to_string());
-        self
-    }
-
-    /// Sets the number of pages per sheet.
-    #[must_use]
-    pub fn number_up(mut self, number_up: &str) -> Self {
-        self.number_up = Some(number_up.to_string());
-        self
-    }
+impl_options_dict!(Settings {
+    orientation = "orientation",
+    paper_format = "paper-format",
+    paper_width = "paper-width",
+    paper_height = "paper-height",
+    n_copies = "n-copies",
+    default_source = "default-source",
+    quality = "quality",
+    resolution = "resolution",
+    use_color = "use-color",
+    duplex = "duplex",
+    collate = "collate",
+    reverse = "reverse",
+    media_type = "media-type",
+    dither = "dither",
+    scale = "scale",
+    print_pages = "print-pages",
+    page_ranges = "page-ranges",
+    page_set = "page-set",
+    finishings = "finishings",
+    number_up = "number-up",
+    number_up_layout = "number-up-layout",
+    output_bin = "output-bin",
+    resolution_x = "resolution-x",
+    resolution_y = "resolution-y",
+    print_lpi = "printer-lpi",
+    output_basename = "output-basename",
+    output_file_format = "output-file-format",
+    output_uri = "output-uri",
+});
 
-    /// Sets the number up layout, one of lrtb, lrbt, rltb, rlbt, tblr, tbrl,
-    /// btlr, btrl.
-    #[must_use]
-    pub fn number_up_layout(mut self, number_up_layout: &str) -> Self {
-        self.number_up_layout = Some(number_up_layout.to_string());
-        self
-    }
-
-    /// Sets the output bin
-    #[must_use]
-    pub fn output_bin(mut self, output_bin: &str) -> Self {
-        self.output_bin = Some(output_bin.to_string());
-        self
-    }
-
-    /// Sets the horizontal resolution in dpi.
-    #[must_use]
-    pub fn resolution_x(mut self, resolution_x: &str) -> Self {
-        self.resolution_x = Some(resolution_x.to_string());
-        self
-    }
-
-    /// Sets the vertical resolution in dpi.
-    #[must_use]
-    pub fn resolution_y(mut self, resolution_y: &str) -> Self {
-        self.resolution_y = Some(resolution_y.to_string());
-        self
-    }
-
-    /// Sets the resolution in lines per inch.
-    #[must_use]
-    pub fn print_lpi(mut self, print_lpi: &str) -> Self {
-        self.print_lpi = Some(print_lpi.to_string());
-        self
-    }
-
-    /// Sets the print-to-file base name.
-    #[must_use]
-    pub fn output_basename(mut self, output_basename: &str) -> Self {
-        self.output_basename = Some(output_basename.to_string());
-        self
-    }
-
-    /// Sets the print-to-file format, one of PS, PDF, SVG.
-    #[must_use]
-    pub fn output_file_format(mut self, output_file_format: &str) -> Self {
-        self.output_file_format = Some(output_file_format.to_string());
-        self
-    }
-
-    /// Sets the print-to-file output uri.
-    #[must_use]
-    pub fn output_uri(mut self, output_uri: &str) -> Self {
-        self.output_uri = Some(output_uri.to_string());
-        self
+impl Settings {
+    options_setters! {
+        /// Sets the orientation.
+        with_orientation(orientation: Orientation);
+        /// Sets the paper name.
+        with_paper_format(paper_format: impl Into<String>);
+        /// Sets the paper width.
+        with_paper_width(paper_width: impl Into<String>);
+        /// Sets the paper height.
+        with_paper_height(paper_height: impl Into<String>);
+        /// Sets the number of copies to print.
+        with_n_copies(n_copies: impl Into<String>);
+        /// Sets the default paper source.
+        with_default_source(default_source: impl Into<String>);
+        /// Sets the print quality.
+        with_quality(quality: Quality);
+        /// Sets the resolution, both resolution-x & resolution-y.
+        with_resolution(resolution: impl Into<String>);
+        /// Sets whether to use color.
+        with_use_color(use_color: bool);
+        /// Sets the duplex printing mode.
+        with_duplex(duplex: impl Into<String>);
+        /// Whether to collate copies.
+        with_collate(collate: impl Into<String>);
+        /// Sets whether to reverse the order of the printed pages.
+        with_reverse(reverse: impl Into<String>);
+        /// Sets the media type.
+        with_media_type(media_type: impl Into<String>);
+        /// Sets the dithering to use.
+        with_dither(dither: impl Into<String>);
+        /// Sets the page scale in percent.
+        with_scale(scale: impl Into<String>);
+        /// Sets what pages to print, one of all, selection, current or ranges.
+        with_print_pages(print_pages: impl Into<String>);
+        /// Sets a list of page ranges, formatted like this: 0-2,4,9-11.
+        with_page_ranges(page_ranges: impl Into<String>);
+        /// Sets what pages to print, one of all, even or odd.
+        with_page_set(page_set: impl Into<String>);
+        /// Sets the finishings.
+        with_finishings(finishings: impl Into<String>);
+        /// Sets the number of pages per sheet.
+        with_number_up(number_up: impl Into<String>);
+        /// Sets the number up layout, one of lrtb, lrbt, rltb, rlbt, tblr, tbrl,
+        /// btlr, btrl.
+        with_number_up_layout(number_up_layout: impl Into<String>);
+        /// Sets the output bin
+        with_output_bin(output_bin: impl Into<String>);
+        /// Sets the horizontal resolution in dpi.
+        with_resolution_x(resolution_x: impl Into<String>);
+        /// Sets the vertical resolution in dpi.
+        with_resolution_y(resolution_y: impl Into<String>);
+        /// Sets the resolution in lines per inch.
+        with_print_lpi(print_lpi: impl Into<String>);
+        /// Sets the print-to-file base name.
+        with_output_basename(output_basename: impl Into<String>);
+        /// Sets the print-to-file format, one of PS, PDF, SVG.
+        with_output_file_format(output_file_format: impl Into<String>);
+        /// Sets the print-to-file output uri.
+        with_output_uri(output_uri: impl Into<String>);
     }
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+#[derive(Type, Debug, Default, Clone, PartialEq)]
 /// Setup the printed pages.
 #[zvariant(signature = "dict")]
 pub struct PageSetup {
     /// the PPD name. It's the name to select a given driver.
-    #[zvariant(rename = "PPDName")]
     pub ppdname: Option<String>,
     /// The name of the page setup.
     pub name: Option<String>,
@@ -513,81 +390,49 @@ pub struct PageSetup {
     pub margin_left: Option<f64>,
     /// The page orientation.
     pub orientation: Option<Orientation>,
+    /// The options without a field.
+    raw: RawOptions,
 }
 
-impl PageSetup {
-    /// Sets the ppdname.
-    #[must_use]
-    pub fn ppdname(mut self, ppdname: &str) -> Self {
-        self.ppdname = Some(ppdname.to_string());
-        self
-    }
-
-    /// Sets the name of the page setup.
-    #[must_use]
-    pub fn name(mut self, name: &str) -> Self {
-        self.name = Some(name.to_string());
-        self
-    }
-
-    /// Sets the user visible name of the page setup.
-    #[must_use]
-    pub fn display_name(mut self, display_name: &str) -> Self {
-        self.display_name = Some(display_name.to_string());
-        self
-    }
-
-    /// Sets the orientation.
-    #[must_use]
-    pub fn orientation(mut self, orientation: Orientation) -> Self {
-        self.orientation = Some(orientation);
-        self
-    }
-
-    /// Sets the page width.
-    #[must_use]
-    pub fn width(mut self, width: f64) -> Self {
-        self.width = Some(width);
-        self
-    }
-
-    /// Sets the page height.
-    #[must_use]
-    pub fn height(mut self, height: f64) -> Self {
-        self.height = Some(height);
-        self
-    }
-
-    /// Sets the page top margin.
-    #[must_use]
-    pub fn margin_top(mut self, margin_top: f64) -> Self {
-        self.margin_top = Some(margin_top);
-        self
-    }
-
-    /// Sets the page bottom margin.
-    #[must_use]
-    pub fn margin_bottom(mut self, margin_bottom: f64) -> Self {
-        self.margin_bottom = Some(margin_bottom);
-        self
-    }
-
-    /// Sets the page right margin.
-    #[must_use]
-    pub fn margin_right(mut self, margin_right: f64) -> Self {
-        self.margin_right = Some(margin_right);
-        self
-    }
+impl_options_dict!(PageSetup {
+    ppdname = "PPDName",
+    name = "Name",
+    display_name = "DisplayName",
+    width = "Width",
+    height = "Height",
+    margin_top = "MarginTop",
+    margin_bottom = "MarginBottom",
+    margin_right = "MarginRight",
+    margin_left = "MarginLeft",
+    orientation = "Orientation",
+});
 
-    /// Sets the page margin left.
-    #[must_use]
-    pub fn margin_left(mut self, margin_left: f64) -> Self {
-        self.margin_left = Some(margin_left);
-        self
+impl PageSetup {
+    options_setters! {
+        /// Sets the ppdname.
+        with_ppdname(ppdname: impl Into<String>);
+        /// Sets the name of the page setup.
+        with_name(name: impl Into<String>);
+        /// Sets the user visible name of the page setup.
+        with_display_name(display_name: impl Into<String>);
+        /// Sets the orientation.
+        with_orientation(orientation: Orientation);
+        /// Sets the page width.
+        with_width(width: f64);
+        /// Sets the page height.
+        with_height(height: f64);
+        /// Sets the page top margin.
+        with_margin_top(margin_top: f64);
+        /// Sets the page bottom margin.
+        with_margin_bottom(margin_bottom: f64);
+        /// Sets the page right margin.
+        with_margin_right(margin_right: f64);
+        /// Sets the page margin left.
+        with_margin_left(margin_left: f64);
     }
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
 /// Specified options for a [`PrintProxy::prepare_print`] request.
 #[zvariant(signature = "dict")]
 struct PreparePrintOptions {
@@ -598,14 +443,14 @@ struct PreparePrintOptions {
 }
 
 impl PreparePrintOptions {
-    /// Sets whether the dialog should be a modal.
-    pub fn modal(mut self, modal: bool) -> Self {
-        self.modal = Some(modal);
-        self
+    options_setters! {
+        @setters
+        /// Sets whether the dialog should be a modal.
+        with_modal(modal: bool);
     }
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
 /// Specified options for a [`PrintProxy::print`] request.
 #[zvariant(signature = "dict")]
 struct PrintOptions {
@@ -619,20 +464,16 @@ struct PrintOptions {
 }
 
 impl PrintOptions {
-    /// A token retrieved from [`PrintProxy::prepare_print`].
-    pub fn token(mut self, token: u32) -> Self {
-        self.token = Some(token);
-        self
-    }
-
-    /// Sets whether the dialog should be a modal.
-    pub fn modal(mut self, modal: bool) -> Self {
-        self.modal = Some(modal);
-        self
+    options_setters! {
+        @setters
+        /// A token retrieved from [`PrintProxy::prepare_print`].
+        with_token(token: u32);
+        /// Sets whether the dialog should be a modal.
+        with_modal(modal: bool);
     }
 }
 
-#[derive(DeserializeDict, SerializeDict, Type, Debug)]
+#[derive(DeserializeDict, SerializeDict, Type, Debug, Clone, PartialEq)]
 /// A response to a [`PrintProxy::prepare_print`] request.
 #[zvariant(signature = "dict")]
 pub struct PreparePrint {
@@ -648,20 +489,23 @@ pub struct PreparePrint {
 /// The interface lets sandboxed applications print.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Print`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Print).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.Print")]
-pub struct PrintProxy<'a>(zbus::Proxy<'a>);
+pub struct PrintProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(PrintProxy, INTERFACE_PRINT);
+impl_proxy_timeout!(PrintProxy);
 
 impl<'a> PrintProxy<'a> {
     /// Create a new instance of [`PrintProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<PrintProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Print")?
+            .interface(INTERFACE_PRINT)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -685,22 +529,23 @@ impl<'a> PrintProxy<'a> {
     /// See also [`PreparePrint`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Print.PreparePrint).
     #[doc(alias = "PreparePrint")]
     #[doc(alias = "xdp_portal_prepare_print")]
-    pub async fn prepare_print(
-        &self,
-        identifier: &WindowIdentifier,
-        title: &str,
+    pub fn prepare_print<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
+        title: &'p str,
         settings: Settings,
         page_setup: PageSetup,
         modal: bool,
-    ) -> Result<PreparePrint, Error> {
-        let options = PreparePrintOptions::default().modal(modal);
-        call_request_method(
-            self.inner(),
-            &options.handle_token,
+    ) -> PortalRequest<'p, PreparePrint> {
+        let options = PreparePrintOptions::default().with_modal(modal);
+        let handle_token = options.handle_token.copy();
+        call_window_request_method(
+            self,
+            identifier,
+            &handle_token,
             "PreparePrint",
-            &(&identifier, title, settings, page_setup, &options),
+            (identifier, title, settings, page_setup, options),
         )
-        .await
     }
 
     /// Asks to print a file.
@@ -722,23 +567,165 @@ impl<'a> PrintProxy<'a> {
     /// See also [`Print`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Print.Print).
     #[doc(alias = "Print")]
     #[doc(alias = "xdp_portal_print_file")]
-    pub async fn print(
-        &self,
-        identifier: &WindowIdentifier,
-        title: &str,
+    pub fn print<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
+        title: &'p str,
         fd: &impl AsRawFd,
         token: Option<u32>,
         modal: bool,
-    ) -> Result<(), Error> {
+    ) -> PortalRequest<'p, ()> {
         let options = PrintOptions::default()
-            .token(token.unwrap_or(0))
-            .modal(modal);
-        call_basic_response_method(
-            self.inner(),
-            &options.handle_token,
+            .with_token(token.unwrap_or(0))
+            .with_modal(modal);
+        let handle_token = options.handle_token.copy();
+        call_window_basic_response_method(
+            self,
+            identifier,
+            &handle_token,
             "Print",
-            &(&identifier, title, Fd::from(fd.as_raw_fd()), &options),
+            (identifier, title, Fd::from(fd.as_raw_fd()), options),
         )
-        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use zbus::zvariant::{from_slice, to_bytes, EncodingContext};
+
+    use super::{Orientation, PageSetup, PreparePrintOptions, PrintOptions, Quality, Settings};
+    use crate::{desktop::HandleToken, helpers::assert_serialized_dict};
+
+    #[test]
+    fn settings() {
+        let value = || Some("1".to_owned());
+        let settings = Settings {
+            orientation: Some(Orientation::Landscape),
+            paper_format: value(),
+            paper_width: value(),
+            paper_height: value(),
+            n_copies: value(),
+            default_source: value(),
+            quality: Some(Quality::High),
+            resolution: value(),
+            use_color: Some(true),
+            duplex: value(),
+            collate: value(),
+            reverse: value(),
+            media_type: value(),
+            dither: value(),
+            scale: value(),
+            print_pages: value(),
+            page_ranges: value(),
+            page_set: value(),
+            finishings: value(),
+            number_up: value(),
+            number_up_layout: value(),
+            output_bin: value(),
+            resolution_x: value(),
+            resolution_y: value(),
+            print_lpi: value(),
+            output_basename: value(),
+            output_file_format: value(),
+            output_uri: value(),
+            raw: Default::default(),
+        };
+        assert_serialized_dict(
+            &settings,
+            &[
+                ("collate", "s"),
+                ("default-source", "s"),
+                ("dither", "s"),
+                ("duplex", "s"),
+                ("finishings", "s"),
+                ("media-type", "s"),
+                ("n-copies", "s"),
+                ("number-up", "s"),
+                ("number-up-layout", "s"),
+                ("orientation", "s"),
+                ("output-basename", "s"),
+                ("output-bin", "s"),
+                ("output-file-format", "s"),
+                ("output-uri", "s"),
+                ("page-ranges", "s"),
+                ("page-set", "s"),
+                ("paper-format", "s"),
+                ("paper-height", "s"),
+                ("paper-width", "s"),
+                ("print-pages", "s"),
+                ("printer-lpi", "s"),
+                ("quality", "s"),
+                ("resolution", "s"),
+                ("resolution-x", "s"),
+                ("resolution-y", "s"),
+                ("reverse", "s"),
+                ("scale", "s"),
+                ("use-color", "b"),
+            ],
+        );
+    }
+
+    #[test]
+    fn page_setup() {
+        let page_setup = PageSetup {
+            ppdname: Some("A4".to_owned()),
+            name: Some("iso_a4".to_owned()),
+            display_name: Some("A4".to_owned()),
+            width: Some(210.0),
+            height: Some(297.0),
+            margin_top: Some(10.0),
+            margin_bottom: Some(10.0),
+            margin_right: Some(10.0),
+            margin_left: Some(10.0),
+            orientation: Some(Orientation::Portrait),
+            raw: Default::default(),
+        };
+        assert_serialized_dict(
+            &page_setup,
+            &[
+                ("DisplayName", "s"),
+                ("Height", "d"),
+                ("MarginBottom", "d"),
+                ("MarginLeft", "d"),
+                ("MarginRight", "d"),
+                ("MarginTop", "d"),
+                ("Name", "s"),
+                ("Orientation", "s"),
+                ("PPDName", "s"),
+                ("Width", "d"),
+            ],
+        );
+    }
+
+    #[test]
+    fn raw() {
+        let page_setup = PageSetup::default()
+            .with_width(210.0)
+            .insert_raw("Unit", "mm");
+        let ctxt = EncodingContext::<byteorder::LE>::new_dbus(0);
+        let bytes = to_bytes(ctxt, &page_setup).unwrap();
+        let page_setup: PageSetup = from_slice(&bytes, ctxt).unwrap();
+        assert_eq!(page_setup.width, Some(210.0));
+        assert_eq!(page_setup.raw().len(), 1);
+        assert!(page_setup.raw().contains_key("Unit"));
+    }
+
+    #[test]
+    fn options() {
+        let options = PreparePrintOptions {
+            handle_token: HandleToken::default(),
+            modal: Some(true),
+        };
+        assert_serialized_dict(&options, &[("handle_token", "s"), ("modal", "b")]);
+
+        let options = PrintOptions {
+            handle_token: HandleToken::default(),
+            modal: Some(true),
+            token: Some(1),
+        };
+        assert_serialized_dict(
+            &options,
+            &[("handle_token", "s"), ("modal", "b"), ("token", "u")],
+        );
     }
 }
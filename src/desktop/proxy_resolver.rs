@@ -14,8 +14,8 @@
 //! }
 //! ```
 
-use super::{DESTINATION, PATH};
-use crate::{helpers::call_method, Error};
+use super::{DESTINATION, INTERFACE_PROXY_RESOLVER, PATH};
+use crate::Error;
 
 /// The interface provides network proxy information to sandboxed applications.
 /// It is not a portal in the strict sense, since it does not involve user
@@ -23,20 +23,23 @@ use crate::{helpers::call_method, Error};
 /// via a library API such as the GLib [`gio::ProxyResolver`](https://gtk-rs.org/gtk-rs-core/stable/latest/docs/gio/struct.ProxyResolver.html) interface.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.ProxyResolver`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.ProxyResolver).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.ProxyResolver")]
-pub struct ProxyResolverProxy<'a>(zbus::Proxy<'a>);
+pub struct ProxyResolverProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(ProxyResolverProxy, INTERFACE_PROXY_RESOLVER);
+impl_proxy_timeout!(ProxyResolverProxy);
 
 impl<'a> ProxyResolverProxy<'a> {
     /// Create a new instance of [`ProxyResolverProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<ProxyResolverProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.ProxyResolver")?
+            .interface(INTERFACE_PROXY_RESOLVER)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -44,19 +47,29 @@ impl<'a> ProxyResolverProxy<'a> {
         &self.0
     }
 
-    /// Looks up which proxy to use to connect to `uri`.
-    ///
-    /// # Returns
-    ///
-    /// A list of proxy uris of the form `protocol://[user[:password]host:port`
-    /// The protocol can be `http`, `rtsp`, `socks` or another proxying
-    /// protocol. `direct://` is used when no proxy is needed.
-    ///
-    /// # Specifications
-    ///
-    /// See also [`Lookup`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-ProxyResolver.Lookup).
-    #[doc(alias = "Lookup")]
-    pub async fn lookup(&self, uri: &str) -> Result<Vec<String>, Error> {
-        call_method(self.inner(), "Lookup", &(uri)).await
+    portal_members! {
+        /// Looks up which proxy to use to connect to `uri`.
+        ///
+        /// # Returns
+        ///
+        /// A list of proxy uris of the form `protocol://[user[:password]host:port`
+        /// The protocol can be `http`, `rtsp`, `socks` or another proxying
+        /// protocol. `direct://` is used when no proxy is needed.
+        ///
+        /// # Specifications
+        ///
+        /// See also [`Lookup`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-ProxyResolver.Lookup).
+        pub fn lookup(uri: &str) -> Vec<String> = method "Lookup";
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ProxyResolverProxy;
+    use crate::{desktop::INTERFACE_PROXY_RESOLVER, helpers::assert_members};
+
+    #[test]
+    fn members() {
+        assert_members(INTERFACE_PROXY_RESOLVER, &ProxyResolverProxy::members());
     }
 }
@@ -1,23 +1,26 @@
-use super::{DESTINATION, PATH};
-use crate::{helpers::call_method, Error};
+use super::{DESTINATION, INTERFACE_REALTIME, PATH};
+use crate::Error;
 
 /// Interface for setting a thread to realtime from within the sandbox.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Realtime`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Realtime).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.Realtime")]
-pub struct RealtimeProxy<'a>(zbus::Proxy<'a>);
+pub struct RealtimeProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(RealtimeProxy, INTERFACE_REALTIME);
+impl_proxy_timeout!(RealtimeProxy);
 
 impl<'a> RealtimeProxy<'a> {
     /// Create a new instance of [`RealtimeProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<RealtimeProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Realtime")?
+            .interface(INTERFACE_REALTIME)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -25,57 +28,33 @@ impl<'a> RealtimeProxy<'a> {
         &self.0
     }
 
-    #[doc(alias = "MakeThreadRealtimeWithPID")]
-    pub async fn max_thread_realtime_with_pid(
-        &self,
-        process: u64,
-        thread: u64,
-        priority: u32,
-    ) -> Result<(), Error> {
-        call_method(
-            self.inner(),
-            "MakeThreadRealtimeWithPID",
-            &(&process, &thread, &priority),
-        )
-        .await
-    }
-
-    #[doc(alias = "MakeThreadHighPriorityWithPID")]
-    pub async fn max_thread_high_priority_with_pid(
-        &self,
-        process: u64,
-        thread: u64,
-        priority: u32,
-    ) -> Result<(), Error> {
-        call_method(
-            self.inner(),
-            "MakeThreadHighPriorityWithPID",
-            &(&process, &thread, &priority),
-        )
-        .await
-    }
-
-    #[doc(alias = "MaxRealtimePriority")]
-    pub async fn max_realtime_priority(&self) -> Result<i64, Error> {
-        self.inner()
-            .get_property::<i64>("MaxRealtimePriority")
-            .await
-            .map_err(From::from)
+    portal_members! {
+        pub fn max_thread_realtime_with_pid(
+            process: u64,
+            thread: u64,
+            priority: u32,
+        ) -> () = method "MakeThreadRealtimeWithPID";
+        pub fn max_thread_high_priority_with_pid(
+            process: u64,
+            thread: u64,
+            priority: i32,
+        ) -> () = method "MakeThreadHighPriorityWithPID";
+        #[doc(alias = "MaxRealtimePriority")]
+        pub fn max_realtime_priority() -> i32 = property "MaxRealtimePriority";
+        #[doc(alias = "MinNiceLevel")]
+        pub fn min_nice_level() -> i32 = property "MinNiceLevel";
+        #[doc(alias = "RTTimeUSecMax")]
+        pub fn rt_time_usec_max() -> i64 = property "RTTimeUSecMax";
     }
+}
 
-    #[doc(alias = "MinNiceLevel")]
-    pub async fn min_nice_level(&self) -> Result<u32, Error> {
-        self.inner()
-            .get_property::<u32>("MinNiceLevel")
-            .await
-            .map_err(From::from)
-    }
+#[cfg(test)]
+mod test {
+    use super::RealtimeProxy;
+    use crate::{desktop::INTERFACE_REALTIME, helpers::assert_members};
 
-    #[doc(alias = "RTTimeUSecMax")]
-    pub async fn rt_time_usec_max(&self) -> Result<u32, Error> {
-        self.inner()
-            .get_property::<u32>("RTTimeUSecMax")
-            .await
-            .map_err(From::from)
+    #[test]
+    fn members() {
+        assert_members(INTERFACE_REALTIME, &RealtimeProxy::members());
     }
 }
@@ -0,0 +1,296 @@
+use std::{hash::Hash, sync::Arc, time::Instant};
+
+use enumflags2::BitFlags;
+
+use super::{
+    metrics::MetricsHandle, Axis, DeviceType, InputEvent, Key, KeyState, Metrics,
+    RemoteDesktopProxy, RemoteDesktopSession, SlotAllocator,
+};
+use crate::{
+    desktop::{screencast::Stream, SessionCapabilities},
+    Error,
+};
+
+/// A remote desktop session that was started with
+/// [`RemoteDesktopProxy::start_session`].
+///
+/// The devices granted by the user are recorded when the session starts, the
+/// `notify_*` methods return [`Error::DeviceNotGranted`] without calling the
+/// portal if the event requires a device the user didn't grant access to.
+///
+/// ```rust,no_run
+/// use ashpd::desktop::remote_desktop::{DeviceType, KeyState, RemoteDesktopProxy};
+/// use ashpd::WindowIdentifier;
+///
+/// async fn run() -> ashpd::Result<()> {
+///     let connection = zbus::Connection::session().await?;
+///     let proxy = RemoteDesktopProxy::new(&connection).await?;
+///
+///     let session = proxy.create_session().await?;
+///     proxy.select_devices(&session, DeviceType::Keyboard | DeviceType::Pointer).await?;
+///
+///     let session = proxy
+///         .start_session(session, &WindowIdentifier::default())
+///         .await?;
+///
+///     match session.notify_keyboard_keycode(13, KeyState::Pressed).await {
+///         Err(ashpd::Error::DeviceNotGranted(device)) => {
+///             println!("{:?} access was denied", device)
+///         }
+///         result => result?,
+///     }
+///
+///     session.close().await?;
+///     Ok(())
+/// }
+/// ```
+///
+/// The session can be cloned to be used from several tasks, see
+/// [`SessionProxy`](crate::desktop::SessionProxy).
+#[derive(Clone, Debug)]
+pub struct ActiveSession<'a> {
+    proxy: RemoteDesktopProxy<'a>,
+    session: RemoteDesktopSession<'a>,
+    capabilities: SessionCapabilities,
+    metrics: Option<MetricsHandle>,
+}
+
+impl<'a> ActiveSession<'a> {
+    pub(super) fn new(
+        proxy: RemoteDesktopProxy<'a>,
+        session: RemoteDesktopSession<'a>,
+        capabilities: SessionCapabilities,
+    ) -> Self {
+        Self {
+            proxy,
+            session,
+            capabilities,
+            metrics: None,
+        }
+    }
+
+    /// Report the latency of the forwarded events to `metrics`.
+    pub fn set_metrics(&mut self, metrics: impl Metrics + 'static) {
+        self.metrics = Some(MetricsHandle(Arc::new(metrics)));
+    }
+
+    /// The remote desktop proxy the session was started with.
+    pub fn proxy(&self) -> &RemoteDesktopProxy<'a> {
+        &self.proxy
+    }
+
+    /// The underlying session.
+    pub fn session(&self) -> &RemoteDesktopSession<'a> {
+        &self.session
+    }
+
+    /// The devices the user granted access to.
+    pub fn devices(&self) -> BitFlags<DeviceType> {
+        self.capabilities.devices()
+    }
+
+    /// The screen cast streams, if sources were selected on the session.
+    pub fn streams(&self) -> &[Stream] {
+        self.capabilities.streams()
+    }
+
+    /// What the user granted access to when the session started.
+    pub fn capabilities(&self) -> &SessionCapabilities {
+        &self.capabilities
+    }
+
+    /// Close the session, ending the remote control.
+    ///
+    /// See also [`SessionProxy::close`](crate::desktop::SessionProxy::close).
+    pub async fn close(self) -> Result<(), Error> {
+        self.session.close().await
+    }
+
+    /// Whether the session was closed, see
+    /// [`SessionProxy::is_closed`](crate::desktop::SessionProxy::is_closed).
+    pub fn is_closed(&self) -> bool {
+        self.session.is_closed()
+    }
+
+    /// Whether the user granted access to `device`.
+    pub fn is_granted(&self, device: DeviceType) -> bool {
+        self.capabilities.is_granted(device)
+    }
+
+    fn ensure_granted(&self, device: DeviceType) -> Result<(), Error> {
+        if self.is_granted(device) {
+            Ok(())
+        } else {
+            Err(Error::DeviceNotGranted(device))
+        }
+    }
+
+    /// Forward an input event.
+    pub async fn notify(&self, event: InputEvent) -> Result<(), Error> {
+        self.ensure_granted(event.device())?;
+        self.forward(event).await
+    }
+
+    /// Forward a batch of input events, in order.
+    ///
+    /// Every event is checked against the granted devices before sending
+    /// anything, so a batch is either refused as a whole with
+    /// [`Error::DeviceNotGranted`] or forwarded until the first D-Bus error.
+    pub async fn notify_all(
+        &self,
+        events: impl IntoIterator<Item = InputEvent>,
+    ) -> Result<(), Error> {
+        let events = events.into_iter().collect::<Vec<_>>();
+        for event in &events {
+            self.ensure_granted(event.device())?;
+        }
+        if let Some(metrics) = &self.metrics {
+            metrics.0.batch_queued(events.len());
+        }
+        for event in events {
+            self.forward(event).await?;
+        }
+        Ok(())
+    }
+
+    async fn forward(&self, event: InputEvent) -> Result<(), Error> {
+        let start = Instant::now();
+        let result = self.send(event).await;
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .0
+                .event_forwarded(&event, start.elapsed(), result.is_ok());
+        }
+        result
+    }
+
+    async fn send(&self, event: InputEvent) -> Result<(), Error> {
+        let (proxy, session) = (&self.proxy, &self.session);
+        match event {
+            InputEvent::KeyboardKeycode { keycode, state } => {
+                proxy.notify_keyboard_keycode(session, keycode, state).await
+            }
+            InputEvent::KeyboardKeysym { keysym, state } => {
+                proxy.notify_keyboard_keysym(session, keysym, state).await
+            }
+            InputEvent::PointerMotion { dx, dy } => {
+                proxy.notify_pointer_motion(session, dx, dy).await
+            }
+            InputEvent::PointerMotionAbsolute { stream, x, y } => {
+                proxy
+                    .notify_pointer_motion_absolute(session, stream, x, y)
+                    .await
+            }
+            InputEvent::PointerButton { button, state } => {
+                proxy.notify_pointer_button(session, button, state).await
+            }
+            InputEvent::PointerAxis { dx, dy, finish } => {
+                proxy.notify_pointer_axis(session, dx, dy, finish).await
+            }
+            InputEvent::PointerAxisDiscrete { axis, steps } => {
+                proxy
+                    .notify_pointer_axis_discrete(session, axis, steps)
+                    .await
+            }
+            InputEvent::TouchDown { stream, slot, x, y } => {
+                proxy.notify_touch_down(session, stream, slot, x, y).await
+            }
+            InputEvent::TouchMotion { stream, slot, x, y } => {
+                proxy.notify_touch_motion(session, stream, slot, x, y).await
+            }
+            InputEvent::TouchUp { slot } => proxy.notify_touch_up(session, slot).await,
+        }
+    }
+
+    /// See [`RemoteDesktopProxy::notify_keyboard_keycode`].
+    pub async fn notify_keyboard_keycode(
+        &self,
+        keycode: i32,
+        state: KeyState,
+    ) -> Result<(), Error> {
+        self.notify(InputEvent::KeyboardKeycode { keycode, state })
+            .await
+    }
+
+    /// See [`RemoteDesktopProxy::notify_keyboard_keysym`].
+    pub async fn notify_keyboard_keysym(&self, keysym: i32, state: KeyState) -> Result<(), Error> {
+        self.notify(InputEvent::KeyboardKeysym { keysym, state })
+            .await
+    }
+
+    /// See [`RemoteDesktopProxy::notify_key`].
+    pub async fn notify_key(&self, key: Key, state: KeyState) -> Result<(), Error> {
+        self.notify(key.event(state)).await
+    }
+
+    /// See [`RemoteDesktopProxy::notify_touch_up`].
+    pub async fn notify_touch_up(&self, slot: u32) -> Result<(), Error> {
+        self.notify(InputEvent::TouchUp { slot }).await
+    }
+
+    /// See [`RemoteDesktopProxy::notify_touch_down`].
+    pub async fn notify_touch_down(
+        &self,
+        stream: u32,
+        slot: u32,
+        x: f64,
+        y: f64,
+    ) -> Result<(), Error> {
+        self.notify(InputEvent::TouchDown { stream, slot, x, y })
+            .await
+    }
+
+    /// See [`RemoteDesktopProxy::notify_touch_motion`].
+    pub async fn notify_touch_motion(
+        &self,
+        stream: u32,
+        slot: u32,
+        x: f64,
+        y: f64,
+    ) -> Result<(), Error> {
+        self.notify(InputEvent::TouchMotion { stream, slot, x, y })
+            .await
+    }
+
+    /// See [`RemoteDesktopProxy::cancel_all_touches`].
+    pub async fn cancel_all_touches<K: Hash + Eq>(
+        &self,
+        slots: &mut SlotAllocator<K>,
+    ) -> Result<(), Error> {
+        self.proxy.cancel_all_touches(&self.session, slots).await
+    }
+
+    /// See [`RemoteDesktopProxy::notify_pointer_motion_absolute`].
+    pub async fn notify_pointer_motion_absolute(
+        &self,
+        stream: u32,
+        x: f64,
+        y: f64,
+    ) -> Result<(), Error> {
+        self.notify(InputEvent::PointerMotionAbsolute { stream, x, y })
+            .await
+    }
+
+    /// See [`RemoteDesktopProxy::notify_pointer_motion`].
+    pub async fn notify_pointer_motion(&self, dx: f64, dy: f64) -> Result<(), Error> {
+        self.notify(InputEvent::PointerMotion { dx, dy }).await
+    }
+
+    /// See [`RemoteDesktopProxy::notify_pointer_button`].
+    pub async fn notify_pointer_button(&self, button: i32, state: KeyState) -> Result<(), Error> {
+        self.notify(InputEvent::PointerButton { button, state })
+            .await
+    }
+
+    /// See [`RemoteDesktopProxy::notify_pointer_axis_discrete`].
+    pub async fn notify_pointer_axis_discrete(&self, axis: Axis, steps: i32) -> Result<(), Error> {
+        self.notify(InputEvent::PointerAxisDiscrete { axis, steps })
+            .await
+    }
+
+    /// See [`RemoteDesktopProxy::notify_pointer_axis`].
+    pub async fn notify_pointer_axis(&self, dx: f64, dy: f64, finish: bool) -> Result<(), Error> {
+        self.notify(InputEvent::PointerAxis { dx, dy, finish })
+            .await
+    }
+}
@@ -0,0 +1,668 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, io,
+    os::unix::{
+        io::{AsRawFd, OwnedFd},
+        net::UnixStream,
+    },
+    time::{Duration, Instant},
+};
+
+use enumflags2::BitFlags;
+use nix::{
+    poll::{poll, PollFd, PollFlags},
+    time::{clock_gettime, ClockId},
+};
+use reis::{
+    ei,
+    event::{Device, DeviceCapability, EiEvent, EiEventConverter, Region, Seat},
+    handshake::{self, HandshakeError},
+    Interface, PendingRequestResult,
+};
+
+use super::{read_keymap, DeviceType, KeyState};
+use crate::Error;
+
+// The interfaces the sender knows about, with their highest version.
+const INTERFACES: [(&str, u32); 11] = [
+    ("ei_callback", 1),
+    ("ei_connection", 1),
+    ("ei_pingpong", 1),
+    ("ei_seat", 1),
+    ("ei_device", 1),
+    ("ei_pointer", 1),
+    ("ei_pointer_absolute", 1),
+    ("ei_button", 1),
+    ("ei_scroll", 1),
+    ("ei_keyboard", 1),
+    ("ei_touchscreen", 1),
+];
+
+/// The names of the EI interfaces exposing the devices of `types`.
+///
+/// A seat advertises a capability for each of them, the ones to bind to
+/// receive the matching sender devices.
+pub fn ei_interfaces(types: BitFlags<DeviceType>) -> Vec<&'static str> {
+    let mut interfaces = vec![];
+    if types.contains(DeviceType::Keyboard) {
+        interfaces.push("ei_keyboard");
+    }
+    if types.contains(DeviceType::Pointer) {
+        interfaces.extend([
+            "ei_pointer",
+            "ei_pointer_absolute",
+            "ei_button",
+            "ei_scroll",
+        ]);
+    }
+    if types.contains(DeviceType::Touchscreen) {
+        interfaces.push("ei_touchscreen");
+    }
+    interfaces
+}
+
+// The capabilities to bind on the seats for `types`, see `ei_interfaces`.
+fn capabilities(types: BitFlags<DeviceType>) -> Vec<DeviceCapability> {
+    let mut capabilities = vec![];
+    if types.contains(DeviceType::Keyboard) {
+        capabilities.push(DeviceCapability::Keyboard);
+    }
+    if types.contains(DeviceType::Pointer) {
+        capabilities.extend([
+            DeviceCapability::Pointer,
+            DeviceCapability::PointerAbsolute,
+            DeviceCapability::Button,
+            DeviceCapability::Scroll,
+        ]);
+    }
+    if types.contains(DeviceType::Touchscreen) {
+        capabilities.push(DeviceCapability::Touch);
+    }
+    capabilities
+}
+
+// Whether `device` emulates a device of type `device_type`.
+fn is_of_type(device: &Device, device_type: DeviceType) -> bool {
+    match device_type {
+        DeviceType::Keyboard => device.interface::<ei::Keyboard>().is_some(),
+        DeviceType::Pointer => {
+            device.interface::<ei::Pointer>().is_some()
+                || device.interface::<ei::PointerAbsolute>().is_some()
+        }
+        DeviceType::Touchscreen => device.interface::<ei::Touchscreen>().is_some(),
+    }
+}
+
+#[derive(Debug)]
+/// The communication with the EIS server of a remote desktop session failed,
+/// see [`EiSender`].
+pub enum EiError {
+    /// Reading from or writing to the socket failed.
+    Io(io::Error),
+    /// The handshake was rejected by the server.
+    Handshake(HandshakeError),
+    /// The server sent a malformed or unexpected message.
+    Protocol(String),
+    /// The server closed the connection, with its explanation.
+    Disconnected(String),
+    /// None of the resumed devices implements the EI interface needed to
+    /// send the event, `ei_touchscreen` for example.
+    NoDevice(&'static str),
+}
+
+impl fmt::Display for EiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "EIS socket error: {}", e),
+            Self::Handshake(e) => write!(f, "EIS handshake failed: {}", e),
+            Self::Protocol(e) => write!(f, "EIS protocol error: {}", e),
+            Self::Disconnected(e) => write!(f, "Disconnected by the EIS server: {}", e),
+            Self::NoDevice(interface) => write!(f, "No device implements {}", interface),
+        }
+    }
+}
+
+impl std::error::Error for EiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Handshake(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for EiError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::UnexpectedEof => Self::Disconnected(e.to_string()),
+            _ => Self::Io(e),
+        }
+    }
+}
+
+/// A sender `reis` context, connected to the EIS server of a remote desktop
+/// session, emulating the devices the server offers.
+///
+/// The calls block until the server replies or the socket is writable, they
+/// are meant for a dedicated thread.
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+///
+/// use ashpd::desktop::remote_desktop::{DeviceType, EiSender, KeyState, RemoteDesktopProxy};
+/// use ashpd::WindowIdentifier;
+///
+/// async fn run() -> ashpd::Result<()> {
+///     let connection = zbus::Connection::session().await?;
+///     let proxy = RemoteDesktopProxy::new(&connection).await?;
+///
+///     let session = proxy.create_session().await?;
+///     let types = DeviceType::Keyboard | DeviceType::Pointer;
+///     proxy.select_devices(&session, types).await?;
+///     proxy.start(&session, &WindowIdentifier::default()).await?;
+///
+///     let fd = proxy.connect_to_eis(&session).await?;
+///     let mut sender = EiSender::new(fd, "ashpd")?;
+///     sender.bind(types, Duration::from_secs(5))?;
+///     sender.pointer_motion(10.0, 10.0)?;
+///     // The A key
+///     sender.keyboard_key(30, KeyState::Pressed)?;
+///     sender.keyboard_key(30, KeyState::Released)?;
+///     Ok(())
+/// }
+/// ```
+pub struct EiSender {
+    context: ei::Context,
+    connection: ei::Connection,
+    converter: EiEventConverter,
+    interfaces: HashMap<String, u32>,
+    capabilities: Vec<DeviceCapability>,
+    seats: Vec<Seat>,
+    // The resumed devices, the events can only be sent to these.
+    devices: Vec<Device>,
+    emulating: HashSet<Device>,
+    sequence: u32,
+}
+
+impl EiSender {
+    /// Run the handshake as a sender named `name` on the socket returned by
+    /// [`RemoteDesktopProxy::connect_to_eis`](super::RemoteDesktopProxy::connect_to_eis).
+    ///
+    /// The handshake blocks until the server replies.
+    pub fn new(fd: OwnedFd, name: &str) -> Result<Self, Error> {
+        let socket = UnixStream::from(fd);
+        let context = ei::Context::new(socket).map_err(EiError::from)?;
+        let interfaces = HashMap::from(INTERFACES);
+        let response = handshake::ei_handshake_blocking(
+            &context,
+            name,
+            ei::handshake::ContextType::Sender,
+            &interfaces,
+        )
+        .map_err(EiError::Handshake)?;
+        Ok(Self {
+            context,
+            connection: response.connection,
+            converter: EiEventConverter::new(response.serial),
+            interfaces: response.negotiated_interfaces,
+            capabilities: vec![],
+            seats: vec![],
+            devices: vec![],
+            emulating: HashSet::new(),
+            sequence: 0,
+        })
+    }
+
+    /// Bind the capabilities of `types` on the seats of the server, and wait
+    /// until it resumed a device for each of them.
+    ///
+    /// `types` should be among the ones selected with
+    /// [`RemoteDesktopProxy::select_devices`](super::RemoteDesktopProxy::select_devices),
+    /// the devices the server doesn't offer within `timeout` fail with
+    /// [`EiError::NoDevice`].
+    pub fn bind(&mut self, types: BitFlags<DeviceType>, timeout: Duration) -> Result<(), Error> {
+        self.capabilities = capabilities(types);
+        for seat in &self.seats {
+            seat.bind_capabilities(&self.capabilities);
+        }
+        let deadline = Instant::now() + timeout;
+        loop {
+            // The handshake may have buffered the first events already.
+            self.dispatch()?;
+            let missing = types
+                .iter()
+                .find(|device_type| !self.devices.iter().any(|d| is_of_type(d, *device_type)));
+            let missing = match missing {
+                Some(missing) => missing,
+                None => return Ok(()),
+            };
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() || !self.wait(remaining)? {
+                let interface = match missing {
+                    DeviceType::Keyboard => ei::Keyboard::NAME,
+                    DeviceType::Pointer => ei::Pointer::NAME,
+                    DeviceType::Touchscreen => ei::Touchscreen::NAME,
+                };
+                return Err(EiError::NoDevice(interface).into());
+            }
+        }
+    }
+
+    /// Process the messages received from the server without blocking: the
+    /// new seats are bound with the capabilities given to
+    /// [`EiSender::bind`] and the devices paused or resumed.
+    pub fn dispatch(&mut self) -> Result<(), Error> {
+        match self.context.read() {
+            Ok(_) => (),
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => (),
+            Err(err) => return Err(EiError::from(err).into()),
+        }
+        while let Some(result) = self.context.pending_event() {
+            let event = match result {
+                PendingRequestResult::Request(event) => event,
+                PendingRequestResult::ParseError(err) => {
+                    return Err(EiError::Protocol(err.to_string()).into())
+                }
+                // Destroyed on our side already.
+                PendingRequestResult::InvalidObject(_) => continue,
+            };
+            self.converter
+                .handle_event(event)
+                .map_err(|err| EiError::Protocol(err.to_string()))?;
+        }
+        while let Some(event) = self.converter.next_event() {
+            match event {
+                EiEvent::SeatAdded(added) => {
+                    added.seat.bind_capabilities(&self.capabilities);
+                    self.seats.push(added.seat);
+                }
+                EiEvent::SeatRemoved(removed) => self.seats.retain(|seat| *seat != removed.seat),
+                EiEvent::DeviceResumed(resumed) if !self.devices.contains(&resumed.device) => {
+                    self.devices.push(resumed.device);
+                }
+                EiEvent::DevicePaused(paused) => self.forget(&paused.device),
+                EiEvent::DeviceRemoved(removed) => self.forget(&removed.device),
+                EiEvent::Disconnected(disconnected) => {
+                    return Err(EiError::Disconnected(disconnected.explanation).into())
+                }
+                _ => (),
+            }
+        }
+        self.flush()
+    }
+
+    /// The devices resumed by the server, the ones the events are sent to.
+    pub fn devices(&self) -> &[Device] {
+        &self.devices
+    }
+
+    /// The EI interfaces agreed on during the handshake, with their version.
+    pub fn interfaces(&self) -> &HashMap<String, u32> {
+        &self.interfaces
+    }
+
+    /// The regions of the resumed devices, the areas of the screens the
+    /// absolute pointer and touch coordinates are mapped to.
+    pub fn regions(&self) -> impl Iterator<Item = &Region> {
+        self.devices.iter().flat_map(|device| device.regions())
+    }
+
+    /// The XKB keymap of the resumed keyboard, in the text format, to
+    /// translate the keysyms to the key codes the host expects.
+    ///
+    /// `None` if the server didn't send one, the key codes are then
+    /// interpreted with the US layout.
+    pub fn keymap(&self) -> Result<Option<String>, Error> {
+        let keymap = self
+            .devices
+            .iter()
+            .filter(|device| device.interface::<ei::Keyboard>().is_some())
+            .find_map(|device| device.keymap())
+            .filter(|keymap| keymap.type_ == ei::keyboard::KeymapType::Xkb);
+        keymap
+            .map(|keymap| read_keymap(&keymap.fd, keymap.size as usize))
+            .transpose()
+    }
+
+    /// Move the pointer by `dx`, `dy` logical pixels.
+    pub fn pointer_motion(&mut self, dx: f32, dy: f32) -> Result<(), Error> {
+        self.emulate(|pointer: ei::Pointer| pointer.motion_relative(dx, dy))
+    }
+
+    /// Move the pointer to `x`, `y` in the logical coordinates of the
+    /// regions of the device.
+    pub fn pointer_motion_absolute(&mut self, x: f32, y: f32) -> Result<(), Error> {
+        self.emulate(|pointer: ei::PointerAbsolute| pointer.motion_absolute(x, y))
+    }
+
+    /// Press or release the Linux evdev `button`, `BTN_LEFT` for example.
+    pub fn pointer_button(&mut self, button: u32, state: KeyState) -> Result<(), Error> {
+        let state = match state {
+            KeyState::Pressed => ei::button::ButtonState::Press,
+            KeyState::Released => ei::button::ButtonState::Released,
+        };
+        self.emulate(|buttons: ei::Button| buttons.button(button, state))
+    }
+
+    /// Scroll by `dx`, `dy` logical pixels.
+    pub fn scroll(&mut self, dx: f32, dy: f32) -> Result<(), Error> {
+        self.emulate(|scroll: ei::Scroll| scroll.scroll(dx, dy))
+    }
+
+    /// Press or release the Linux evdev key `keycode`.
+    pub fn keyboard_key(&mut self, keycode: u32, state: KeyState) -> Result<(), Error> {
+        let state = match state {
+            KeyState::Pressed => ei::keyboard::KeyState::Press,
+            KeyState::Released => ei::keyboard::KeyState::Released,
+        };
+        self.emulate(|keyboard: ei::Keyboard| keyboard.key(keycode, state))
+    }
+
+    /// Put the touch point `slot` down at `x`, `y`.
+    pub fn touch_down(&mut self, slot: u32, x: f32, y: f32) -> Result<(), Error> {
+        self.emulate(|touchscreen: ei::Touchscreen| touchscreen.down(slot, x, y))
+    }
+
+    /// Move the touch point `slot` to `x`, `y`.
+    pub fn touch_motion(&mut self, slot: u32, x: f32, y: f32) -> Result<(), Error> {
+        self.emulate(|touchscreen: ei::Touchscreen| touchscreen.motion(slot, x, y))
+    }
+
+    /// Lift the touch point `slot`.
+    pub fn touch_up(&mut self, slot: u32) -> Result<(), Error> {
+        self.emulate(|touchscreen: ei::Touchscreen| touchscreen.up(slot))
+    }
+
+    /// Notify the server that the devices stopped emulating, done when
+    /// dropped as well.
+    pub fn stop_emulating(&mut self) -> Result<(), Error> {
+        let serial = self.converter.serial();
+        for device in self.emulating.drain() {
+            device.device().stop_emulating(serial);
+        }
+        self.flush()
+    }
+
+    // Send an event as a frame of the first resumed device implementing `T`,
+    // starting to emulate it if needed.
+    fn emulate<T: ei::Interface>(&mut self, send: impl FnOnce(T)) -> Result<(), Error> {
+        self.dispatch()?;
+        let (device, interface) = self
+            .devices
+            .iter()
+            .find_map(|device| Some((device.clone(), device.interface::<T>()?)))
+            .ok_or(EiError::NoDevice(T::NAME))?;
+        let serial = self.converter.serial();
+        if self.emulating.insert(device.clone()) {
+            device.device().start_emulating(serial, self.sequence);
+            self.sequence += 1;
+        }
+        send(interface);
+        device.device().frame(serial, timestamp());
+        self.flush()
+    }
+
+    fn forget(&mut self, device: &Device) {
+        self.devices.retain(|resumed| resumed != device);
+        self.emulating.remove(device);
+    }
+
+    // Wait for the socket to be readable, false on timeout.
+    fn wait(&self, timeout: Duration) -> Result<bool, EiError> {
+        let mut fds = [PollFd::new(self.context.as_raw_fd(), PollFlags::POLLIN)];
+        let timeout = timeout.as_millis().min(i32::MAX as u128) as i32;
+        match poll(&mut fds, timeout) {
+            Ok(ready) => Ok(ready > 0),
+            Err(nix::Error::EINTR) => Ok(true),
+            Err(err) => Err(EiError::Io(err.into())),
+        }
+    }
+
+    fn flush(&self) -> Result<(), Error> {
+        self.context
+            .flush()
+            .map_err(|err| EiError::Io(err.into()).into())
+    }
+
+    /// The underlying context.
+    pub fn context(&self) -> &ei::Context {
+        &self.context
+    }
+
+    /// The connection established by the handshake, where the seats are
+    /// advertised.
+    pub fn connection(&self) -> &ei::Connection {
+        &self.connection
+    }
+
+    /// The last serial sent by the server.
+    pub fn serial(&self) -> u32 {
+        self.converter.serial()
+    }
+}
+
+impl Drop for EiSender {
+    fn drop(&mut self) {
+        let _ = self.stop_emulating();
+    }
+}
+
+impl fmt::Debug for EiSender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EiSender")
+            .field("context", &self.context)
+            .field("devices", &self.devices)
+            .finish()
+    }
+}
+
+// The time of the events, in microseconds of `CLOCK_MONOTONIC`.
+fn timestamp() -> u64 {
+    clock_gettime(ClockId::CLOCK_MONOTONIC)
+        .map(|time| time.tv_sec() as u64 * 1_000_000 + time.tv_nsec() as u64 / 1_000)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{os::unix::io::AsFd, sync::mpsc, thread};
+
+    use reis::{
+        eis,
+        handshake::EisHandshaker,
+        request::{EisRequest, EisRequestConverter},
+    };
+
+    use super::*;
+
+    // Block until the next request of the client, `None` once it's gone.
+    fn next_request(context: &eis::Context) -> Option<eis::Request> {
+        loop {
+            if let Some(result) = context.pending_request() {
+                match result {
+                    PendingRequestResult::Request(request) => return Some(request),
+                    result => panic!("{:?}", result),
+                }
+            }
+            let mut fds = [PollFd::new(context.as_raw_fd(), PollFlags::POLLIN)];
+            poll(&mut fds, -1).unwrap();
+            if context.read().is_err() {
+                return None;
+            }
+        }
+    }
+
+    type AddDevices = fn(&eis::Context, &mut EisRequestConverter, &reis::request::Seat);
+
+    // An EIS server offering the devices of `add_devices` once bound,
+    // forwarding the requests of the devices.
+    fn serve(listener: eis::Listener, add_devices: AddDevices, requests: mpsc::Sender<EisRequest>) {
+        let context = loop {
+            if let Some(context) = listener.accept().unwrap() {
+                break context;
+            }
+            let mut fds = [PollFd::new(listener.as_raw_fd(), PollFlags::POLLIN)];
+            poll(&mut fds, -1).unwrap();
+        };
+        let interfaces = HashMap::from(INTERFACES);
+        let mut handshaker = EisHandshaker::new(&context, &interfaces, 1);
+        let response = loop {
+            let request = next_request(&context).unwrap();
+            if let Some(response) = handshaker.handle_request(request).unwrap() {
+                break response;
+            }
+        };
+        let mut converter = EisRequestConverter::new(&response.connection, 1);
+        let seat = converter.add_seat(
+            Some("default"),
+            &[
+                DeviceCapability::Pointer,
+                DeviceCapability::Button,
+                DeviceCapability::Keyboard,
+            ],
+        );
+        context.flush().unwrap();
+
+        while let Some(request) = next_request(&context) {
+            converter.handle_request(request).unwrap();
+            while let Some(request) = converter.next_request() {
+                if let EisRequest::Bind(_) = request {
+                    add_devices(&context, &mut converter, &seat);
+                } else {
+                    requests.send(request).unwrap();
+                }
+            }
+        }
+    }
+
+    fn pointer_and_keyboard(
+        context: &eis::Context,
+        converter: &mut EisRequestConverter,
+        seat: &reis::request::Seat,
+    ) {
+        for capabilities in [
+            &[DeviceCapability::Pointer, DeviceCapability::Button][..],
+            &[DeviceCapability::Keyboard],
+        ] {
+            let device =
+                converter.add_device(seat, None, eis::device::DeviceType::Virtual, capabilities);
+            device.device().resumed(converter.next_serial());
+        }
+        context.flush().unwrap();
+    }
+
+    // A keyboard with regions and a keymap, which the converter of `reis`
+    // can't describe: its requests can't be handled.
+    fn keyboard_with_keymap(
+        context: &eis::Context,
+        converter: &mut EisRequestConverter,
+        seat: &reis::request::Seat,
+    ) {
+        let path = std::env::temp_dir().join(format!("ashpd-keymap-{}", std::process::id()));
+        std::fs::write(&path, b"xkb_keymap {};\0").unwrap();
+        let keymap = std::fs::File::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let device = seat.eis_seat().device(1);
+        device.device_type(eis::device::DeviceType::Virtual);
+        device.region(0, 0, 1920, 1080, 1.0);
+        device.region(1920, 0, 1280, 720, 2.0);
+        let keyboard = device.interface::<eis::Keyboard>(1);
+        keyboard.keymap(eis::keyboard::KeymapType::Xkb, 15, keymap.as_fd());
+        device.done();
+        device.resumed(converter.next_serial());
+        context.flush().unwrap();
+    }
+
+    fn connect(add_devices: AddDevices) -> (EiSender, thread::JoinHandle<Vec<EisRequest>>) {
+        let path = std::env::temp_dir().join(format!(
+            "ashpd-eis-{}-{:?}",
+            std::process::id(),
+            thread::current().id()
+        ));
+        let listener = eis::Listener::bind(&path).unwrap();
+        let (sender, requests) = mpsc::channel();
+        let server = thread::spawn(move || {
+            serve(listener, add_devices, sender);
+            requests.iter().collect()
+        });
+
+        let client = UnixStream::connect(&path).unwrap();
+        (EiSender::new(client.into(), "ashpd").unwrap(), server)
+    }
+
+    #[test]
+    fn emulate() {
+        let (mut sender, server) = connect(pointer_and_keyboard);
+        sender
+            .bind(
+                DeviceType::Pointer | DeviceType::Keyboard,
+                Duration::from_secs(5),
+            )
+            .unwrap();
+        assert_eq!(sender.devices().len(), 2);
+
+        sender.pointer_motion(1.0, 2.0).unwrap();
+        sender.pointer_motion(3.0, 4.0).unwrap();
+        sender.keyboard_key(30, KeyState::Pressed).unwrap();
+        assert!(matches!(
+            sender.touch_down(0, 1.0, 1.0),
+            Err(Error::Ei(EiError::NoDevice("ei_touchscreen")))
+        ));
+        assert!(matches!(
+            sender.bind(DeviceType::Touchscreen.into(), Duration::from_millis(50)),
+            Err(Error::Ei(EiError::NoDevice("ei_touchscreen")))
+        ));
+        drop(sender);
+
+        let requests = server.join().unwrap();
+        assert!(matches!(
+            &requests[..],
+            [
+                EisRequest::DeviceStartEmulating(_),
+                EisRequest::PointerMotion(first),
+                EisRequest::Frame(_),
+                EisRequest::PointerMotion(second),
+                EisRequest::Frame(_),
+                EisRequest::DeviceStartEmulating(_),
+                EisRequest::KeyboardKey(key),
+                EisRequest::Frame(_),
+                EisRequest::DeviceStopEmulating(_),
+                EisRequest::DeviceStopEmulating(_),
+            ] if (first.dx, first.dy) == (1.0, 2.0)
+                && (second.dx, second.dy) == (3.0, 4.0)
+                && key.key == 30
+                && key.state == eis::keyboard::KeyState::Press
+        ));
+    }
+
+    #[test]
+    fn regions_and_keymap() {
+        let (mut sender, server) = connect(keyboard_with_keymap);
+        assert_eq!(sender.interfaces().get("ei_keyboard"), Some(&1));
+        sender
+            .bind(DeviceType::Keyboard.into(), Duration::from_secs(5))
+            .unwrap();
+
+        let regions = sender
+            .regions()
+            .map(|region| {
+                (
+                    region.x,
+                    region.y,
+                    region.width,
+                    region.height,
+                    region.scale,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            regions,
+            [(0, 0, 1920, 1080, 1.0), (1920, 0, 1280, 720, 2.0)]
+        );
+        assert_eq!(sender.keymap().unwrap().as_deref(), Some("xkb_keymap {};"));
+        drop(sender);
+        assert!(server.join().unwrap().is_empty());
+    }
+}
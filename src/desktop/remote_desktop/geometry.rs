@@ -0,0 +1,319 @@
+use super::InputEvent;
+use crate::desktop::screencast::Stream;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[doc(alias = "wl_output_transform")]
+/// The transform applied to the content of a monitor before it is displayed,
+/// as advertised by the `transform` of its `wl_output`.
+///
+/// The rotations are counter-clockwise, the flips are around the vertical axis
+/// and applied before the rotation.
+pub enum Transform {
+    /// No transform.
+    #[default]
+    Normal,
+    /// Rotated by 90 degrees.
+    Rotate90,
+    /// Rotated by 180 degrees.
+    Rotate180,
+    /// Rotated by 270 degrees.
+    Rotate270,
+    /// Flipped.
+    Flipped,
+    /// Flipped then rotated by 90 degrees.
+    Flipped90,
+    /// Flipped then rotated by 180 degrees.
+    Flipped180,
+    /// Flipped then rotated by 270 degrees.
+    Flipped270,
+}
+
+impl Transform {
+    /// The transform matching the `wl_output::transform` value, `None` if it
+    /// is unknown.
+    pub fn from_wayland(transform: u32) -> Option<Self> {
+        match transform {
+            0 => Some(Self::Normal),
+            1 => Some(Self::Rotate90),
+            2 => Some(Self::Rotate180),
+            3 => Some(Self::Rotate270),
+            4 => Some(Self::Flipped),
+            5 => Some(Self::Flipped90),
+            6 => Some(Self::Flipped180),
+            7 => Some(Self::Flipped270),
+            _ => None,
+        }
+    }
+
+    /// Whether the width and the height are swapped by the transform.
+    pub fn swaps_axes(&self) -> bool {
+        matches!(
+            self,
+            Self::Rotate90 | Self::Rotate270 | Self::Flipped90 | Self::Flipped270
+        )
+    }
+
+    /// The transform undoing this one.
+    pub fn inverse(&self) -> Self {
+        match self {
+            Self::Rotate90 => Self::Rotate270,
+            Self::Rotate270 => Self::Rotate90,
+            transform => *transform,
+        }
+    }
+
+    /// Apply the transform to a point of the unit square, `(0, 0)` being the
+    /// top left corner.
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        match self {
+            Self::Normal => (x, y),
+            Self::Rotate90 => (y, 1.0 - x),
+            Self::Rotate180 => (1.0 - x, 1.0 - y),
+            Self::Rotate270 => (1.0 - y, x),
+            Self::Flipped => (1.0 - x, y),
+            Self::Flipped90 => (y, x),
+            Self::Flipped180 => (x, 1.0 - y),
+            Self::Flipped270 => (1.0 - y, 1.0 - x),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+/// Maps the points of the frames of a stream, as received from PipeWire, to
+/// the coordinates expected by the absolute pointer and touch methods of the
+/// [`RemoteDesktopProxy`](super::RemoteDesktopProxy).
+///
+/// The portal doesn't expose the transform of the monitors, it has to be
+/// retrieved from the compositor, from the `wl_output` of the monitor for
+/// example.
+///
+/// ```rust
+/// use ashpd::desktop::remote_desktop::{InputEvent, StreamGeometry, Transform};
+///
+/// // A 1920x1080 monitor rotated by 90 degrees, the frames are 1080x1920.
+/// let geometry = StreamGeometry::new(42, (1080, 1920), Transform::Rotate90);
+/// assert_eq!(geometry.buffer_size(), (1920, 1080));
+/// assert_eq!(geometry.map(0.0, 0.0, 1920.0, 1080.0), (0.0, 1920.0));
+/// assert_eq!(
+///     geometry.touch_down(0, 1920.0, 0.0, 1920.0, 1080.0),
+///     InputEvent::TouchDown {
+///         stream: 42,
+///         slot: 0,
+///         x: 0.0,
+///         y: 0.0,
+///     }
+/// );
+/// ```
+pub struct StreamGeometry {
+    stream: u32,
+    size: (i32, i32),
+    transform: Transform,
+}
+
+impl StreamGeometry {
+    /// Create the geometry of the stream `stream` of the logical size `size`,
+    /// the one returned by [`Stream::size`].
+    pub fn new(stream: u32, size: (i32, i32), transform: Transform) -> Self {
+        Self {
+            stream,
+            size,
+            transform,
+        }
+    }
+
+    /// The geometry of `stream`, `None` if the portal didn't send its size.
+    pub fn from_stream(stream: &Stream, transform: Transform) -> Option<Self> {
+        Some(Self::new(
+            stream.pipe_wire_node_id(),
+            stream.size()?,
+            transform,
+        ))
+    }
+
+    /// The PipeWire node ID of the stream.
+    pub fn stream(&self) -> u32 {
+        self.stream
+    }
+
+    /// The size of the stream in the compositor coordinate space.
+    pub fn size(&self) -> (i32, i32) {
+        self.size
+    }
+
+    /// The transform of the monitor.
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    /// The size of the frames before the transform, in the compositor
+    /// coordinate space.
+    pub fn buffer_size(&self) -> (i32, i32) {
+        if self.transform.swaps_axes() {
+            (self.size.1, self.size.0)
+        } else {
+            self.size
+        }
+    }
+
+    /// Map the point `(x, y)` of a frame displayed with a size of
+    /// `width`x`height` to the stream coordinates.
+    pub fn map(&self, x: f64, y: f64, width: f64, height: f64) -> (f64, f64) {
+        let (x, y) = self.transform.apply(x / width, y / height);
+        (x * self.size.0 as f64, y * self.size.1 as f64)
+    }
+
+    /// Map a point of the stream to the frame displayed with a size of
+    /// `width`x`height`, the reverse of [`StreamGeometry::map`].
+    pub fn unmap(&self, x: f64, y: f64, width: f64, height: f64) -> (f64, f64) {
+        let (x, y) = self
+            .transform
+            .inverse()
+            .apply(x / self.size.0 as f64, y / self.size.1 as f64);
+        (x * width, y * height)
+    }
+
+    /// A [`InputEvent::PointerMotionAbsolute`] at the point `(x, y)` of the
+    /// displayed frame.
+    pub fn pointer_motion_absolute(&self, x: f64, y: f64, width: f64, height: f64) -> InputEvent {
+        let (x, y) = self.map(x, y, width, height);
+        InputEvent::PointerMotionAbsolute {
+            stream: self.stream,
+            x,
+            y,
+        }
+    }
+
+    /// A [`InputEvent::TouchDown`] at the point `(x, y)` of the displayed
+    /// frame.
+    pub fn touch_down(&self, slot: u32, x: f64, y: f64, width: f64, height: f64) -> InputEvent {
+        let (x, y) = self.map(x, y, width, height);
+        InputEvent::TouchDown {
+            stream: self.stream,
+            slot,
+            x,
+            y,
+        }
+    }
+
+    /// A [`InputEvent::TouchMotion`] at the point `(x, y)` of the displayed
+    /// frame.
+    pub fn touch_motion(&self, slot: u32, x: f64, y: f64, width: f64, height: f64) -> InputEvent {
+        let (x, y) = self.map(x, y, width, height);
+        InputEvent::TouchMotion {
+            stream: self.stream,
+            slot,
+            x,
+            y,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{StreamGeometry, Transform};
+
+    const TRANSFORMS: [Transform; 8] = [
+        Transform::Normal,
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::Flipped,
+        Transform::Flipped90,
+        Transform::Flipped180,
+        Transform::Flipped270,
+    ];
+
+    // The corners of a frame, top left, top right, bottom right and bottom left.
+    const CORNERS: [(f64, f64); 4] = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+
+    #[test]
+    fn from_wayland() {
+        for (value, transform) in TRANSFORMS.iter().enumerate() {
+            assert_eq!(Transform::from_wayland(value as u32), Some(*transform));
+        }
+        assert_eq!(Transform::from_wayland(8), None);
+    }
+
+    #[test]
+    fn corners() {
+        // Where the top left, top right, bottom right and bottom left corners
+        // of the frame end up once transformed.
+        let expected = [
+            (Transform::Normal, CORNERS),
+            (
+                Transform::Rotate90,
+                [(0.0, 1.0), (0.0, 0.0), (1.0, 0.0), (1.0, 1.0)],
+            ),
+            (
+                Transform::Rotate180,
+                [(1.0, 1.0), (0.0, 1.0), (0.0, 0.0), (1.0, 0.0)],
+            ),
+            (
+                Transform::Rotate270,
+                [(1.0, 0.0), (1.0, 1.0), (0.0, 1.0), (0.0, 0.0)],
+            ),
+            (
+                Transform::Flipped,
+                [(1.0, 0.0), (0.0, 0.0), (0.0, 1.0), (1.0, 1.0)],
+            ),
+            (
+                Transform::Flipped90,
+                [(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)],
+            ),
+            (
+                Transform::Flipped180,
+                [(0.0, 1.0), (1.0, 1.0), (1.0, 0.0), (0.0, 0.0)],
+            ),
+            (
+                Transform::Flipped270,
+                [(1.0, 1.0), (1.0, 0.0), (0.0, 0.0), (0.0, 1.0)],
+            ),
+        ];
+        for (transform, corners) in expected {
+            for ((x, y), corner) in CORNERS.iter().zip(corners) {
+                assert_eq!(transform.apply(*x, *y), corner, "{:?}", transform);
+            }
+        }
+    }
+
+    #[test]
+    fn inverse() {
+        for transform in TRANSFORMS {
+            for (x, y) in [(0.25, 0.75), (0.5, 0.1), (0.9, 0.4)] {
+                let (tx, ty) = transform.apply(x, y);
+                let (rx, ry) = transform.inverse().apply(tx, ty);
+                assert!((rx - x).abs() < f64::EPSILON, "{:?}", transform);
+                assert!((ry - y).abs() < f64::EPSILON, "{:?}", transform);
+            }
+        }
+    }
+
+    #[test]
+    fn map() {
+        for transform in TRANSFORMS {
+            let geometry = StreamGeometry::new(1, (1920, 1080), transform);
+            let (width, height) = geometry.buffer_size();
+            // Displayed at half the size of the frames.
+            let (width, height) = (width as f64 / 2.0, height as f64 / 2.0);
+
+            let (x, y) = geometry.map(width / 4.0, height / 2.0, width, height);
+            assert!((0.0..=1920.0).contains(&x), "{:?}", transform);
+            assert!((0.0..=1080.0).contains(&y), "{:?}", transform);
+            assert_eq!(
+                geometry.unmap(x, y, width, height),
+                (width / 4.0, height / 2.0),
+                "{:?}",
+                transform
+            );
+            // The center stays in place.
+            assert_eq!(
+                geometry.map(width / 2.0, height / 2.0, width, height),
+                (960.0, 540.0)
+            );
+        }
+
+        let geometry = StreamGeometry::new(1, (1080, 1920), Transform::Rotate270);
+        assert_eq!(geometry.map(1920.0, 0.0, 1920.0, 1080.0), (1080.0, 1920.0));
+        assert_eq!(geometry.map(0.0, 1080.0, 1920.0, 1080.0), (0.0, 0.0));
+    }
+}
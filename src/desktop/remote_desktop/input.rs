@@ -0,0 +1,92 @@
+use super::{Axis, DeviceType, KeyState};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+/// An input event that can be forwarded to a remote desktop session, see
+/// [`ActiveSession::notify`](super::ActiveSession::notify).
+pub enum InputEvent {
+    /// See [`RemoteDesktopProxy::notify_keyboard_keycode`](super::RemoteDesktopProxy::notify_keyboard_keycode).
+    KeyboardKeycode { keycode: i32, state: KeyState },
+    /// See [`RemoteDesktopProxy::notify_keyboard_keysym`](super::RemoteDesktopProxy::notify_keyboard_keysym).
+    KeyboardKeysym { keysym: i32, state: KeyState },
+    /// See [`RemoteDesktopProxy::notify_pointer_motion`](super::RemoteDesktopProxy::notify_pointer_motion).
+    PointerMotion { dx: f64, dy: f64 },
+    /// See [`RemoteDesktopProxy::notify_pointer_motion_absolute`](super::RemoteDesktopProxy::notify_pointer_motion_absolute).
+    PointerMotionAbsolute { stream: u32, x: f64, y: f64 },
+    /// See [`RemoteDesktopProxy::notify_pointer_button`](super::RemoteDesktopProxy::notify_pointer_button).
+    PointerButton { button: i32, state: KeyState },
+    /// See [`RemoteDesktopProxy::notify_pointer_axis`](super::RemoteDesktopProxy::notify_pointer_axis).
+    PointerAxis { dx: f64, dy: f64, finish: bool },
+    /// See [`RemoteDesktopProxy::notify_pointer_axis_discrete`](super::RemoteDesktopProxy::notify_pointer_axis_discrete).
+    PointerAxisDiscrete { axis: Axis, steps: i32 },
+    /// See [`RemoteDesktopProxy::notify_touch_down`](super::RemoteDesktopProxy::notify_touch_down).
+    TouchDown {
+        stream: u32,
+        slot: u32,
+        x: f64,
+        y: f64,
+    },
+    /// See [`RemoteDesktopProxy::notify_touch_motion`](super::RemoteDesktopProxy::notify_touch_motion).
+    TouchMotion {
+        stream: u32,
+        slot: u32,
+        x: f64,
+        y: f64,
+    },
+    /// See [`RemoteDesktopProxy::notify_touch_up`](super::RemoteDesktopProxy::notify_touch_up).
+    TouchUp { slot: u32 },
+}
+
+impl InputEvent {
+    /// The device type the event requires access to.
+    pub fn device(&self) -> DeviceType {
+        match self {
+            Self::KeyboardKeycode { .. } | Self::KeyboardKeysym { .. } => DeviceType::Keyboard,
+            Self::PointerMotion { .. }
+            | Self::PointerMotionAbsolute { .. }
+            | Self::PointerButton { .. }
+            | Self::PointerAxis { .. }
+            | Self::PointerAxisDiscrete { .. } => DeviceType::Pointer,
+            Self::TouchDown { .. } | Self::TouchMotion { .. } | Self::TouchUp { .. } => {
+                DeviceType::Touchscreen
+            }
+        }
+    }
+
+    /// Merge `next` into `self` if sending only the merged event is
+    /// equivalent to sending both of them.
+    pub(super) fn coalesce(&mut self, next: &Self) -> bool {
+        match (self, next) {
+            (Self::PointerMotion { dx, dy }, Self::PointerMotion { dx: ndx, dy: ndy }) => {
+                *dx += ndx;
+                *dy += ndy;
+                true
+            }
+            (
+                Self::PointerMotionAbsolute { stream, x, y },
+                Self::PointerMotionAbsolute {
+                    stream: nstream,
+                    x: nx,
+                    y: ny,
+                },
+            ) if stream == nstream => {
+                *x = *nx;
+                *y = *ny;
+                true
+            }
+            (
+                Self::TouchMotion { stream, slot, x, y },
+                Self::TouchMotion {
+                    stream: nstream,
+                    slot: nslot,
+                    x: nx,
+                    y: ny,
+                },
+            ) if stream == nstream && slot == nslot => {
+                *x = *nx;
+                *y = *ny;
+                true
+            }
+            _ => false,
+        }
+    }
+}
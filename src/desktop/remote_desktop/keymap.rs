@@ -0,0 +1,298 @@
+use std::{os::unix::io::AsRawFd, ptr::NonNull};
+
+use nix::sys::mman::{mmap, munmap, MapFlags, ProtFlags};
+use zbus::zvariant::{OwnedValue, Value};
+
+use crate::{desktop::settings::SettingsProxy, helpers::call_method, Error};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The rules, model, layout, variant and options names describing the XKB
+/// keymap of the host, needed to translate the key codes sent with
+/// [`RemoteDesktopProxy::notify_keyboard_keycode`](super::RemoteDesktopProxy::notify_keyboard_keycode)
+/// to key symbols.
+///
+/// Empty names stand for the defaults of the system.
+pub struct Rmlvo {
+    rules: String,
+    model: String,
+    layout: String,
+    variant: String,
+    options: Option<String>,
+}
+
+impl Rmlvo {
+    /// Read the names from the `XKB_DEFAULT_RULES`, `XKB_DEFAULT_MODEL`,
+    /// `XKB_DEFAULT_LAYOUT`, `XKB_DEFAULT_VARIANT` and `XKB_DEFAULT_OPTIONS`
+    /// environment variables, like libxkbcommon does.
+    pub fn from_env() -> Self {
+        let var = |name| std::env::var(name).unwrap_or_default();
+        Self {
+            rules: var("XKB_DEFAULT_RULES"),
+            model: var("XKB_DEFAULT_MODEL"),
+            layout: var("XKB_DEFAULT_LAYOUT"),
+            variant: var("XKB_DEFAULT_VARIANT"),
+            options: std::env::var("XKB_DEFAULT_OPTIONS").ok(),
+        }
+    }
+
+    /// Read the input sources exposed through the Settings portal by the
+    /// desktop, falling back to [`Rmlvo::from_env`] for the missing names.
+    ///
+    /// Only the `org.gnome.desktop.input-sources` namespace is known so far.
+    pub async fn from_settings(settings: &SettingsProxy<'_>) -> Result<Self, Error> {
+        let mut rmlvo = Self::from_env();
+        let sources = read(settings, "org.gnome.desktop.input-sources", "sources").await;
+        let (layouts, variants): (Vec<_>, Vec<_>) = sources
+            .as_ref()
+            .map(input_sources)
+            .unwrap_or_default()
+            .into_iter()
+            .unzip();
+        if !layouts.is_empty() {
+            rmlvo.layout = layouts.join(",");
+            rmlvo.variant = variants.join(",");
+        }
+        if let Ok(options) = read(settings, "org.gnome.desktop.input-sources", "xkb-options").await
+        {
+            let options = strings(&options);
+            if !options.is_empty() {
+                rmlvo.options = Some(options.join(","));
+            }
+        }
+        Ok(rmlvo)
+    }
+
+    /// Describe a keymap with the names of its components.
+    ///
+    /// # Arguments
+    ///
+    /// * `rules` - The rules, `evdev` for example.
+    /// * `model` - The model, `pc105` for example.
+    /// * `layout` - The comma separated layouts, `us,de` for example.
+    /// * `variant` - The comma separated variants of the layouts, `dvorak,`
+    ///   for example.
+    /// * `options` - The comma separated options, `compose:ralt` for example.
+    pub fn new(
+        rules: impl Into<String>,
+        model: impl Into<String>,
+        layout: impl Into<String>,
+        variant: impl Into<String>,
+        options: Option<String>,
+    ) -> Self {
+        Self {
+            rules: rules.into(),
+            model: model.into(),
+            layout: layout.into(),
+            variant: variant.into(),
+            options,
+        }
+    }
+
+    /// The rules.
+    pub fn rules(&self) -> &str {
+        &self.rules
+    }
+
+    /// The model.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// The comma separated layouts.
+    pub fn layout(&self) -> &str {
+        &self.layout
+    }
+
+    /// The comma separated variants.
+    pub fn variant(&self) -> &str {
+        &self.variant
+    }
+
+    /// The comma separated options.
+    pub fn options(&self) -> Option<&str> {
+        self.options.as_deref()
+    }
+}
+
+/// Read the XKB keymap of `size` bytes shared through `fd` by memory mapping
+/// it, the way the EIS server sends the keymap of a keyboard device after
+/// [`RemoteDesktopProxy::connect_to_eis`](super::RemoteDesktopProxy::connect_to_eis),
+/// or a Wayland compositor the one of a `wl_keyboard`.
+///
+/// The keymap is returned in the text format, without its trailing nul
+/// bytes.
+pub fn read_keymap(fd: &impl AsRawFd, size: usize) -> Result<String, Error> {
+    let invalid = |err| Error::ParseError(format!("Failed to map the keymap: {}", err));
+    if size == 0 {
+        return Err(Error::ParseError("The keymap is empty".to_owned()));
+    }
+    // SAFETY: the mapping is private and read only, it is copied before
+    // being unmapped.
+    let bytes = unsafe {
+        let addr = mmap(
+            std::ptr::null_mut(),
+            size,
+            ProtFlags::PROT_READ,
+            MapFlags::MAP_PRIVATE,
+            fd.as_raw_fd(),
+            0,
+        )
+        .map_err(invalid)?;
+        let addr = NonNull::new(addr).ok_or_else(|| invalid(nix::Error::EINVAL))?;
+        let bytes = std::slice::from_raw_parts(addr.as_ptr() as *const u8, size).to_vec();
+        munmap(addr.as_ptr(), size).map_err(invalid)?;
+        bytes
+    };
+    let len = bytes
+        .iter()
+        .rposition(|byte| *byte != 0)
+        .map_or(0, |i| i + 1);
+    String::from_utf8(bytes[..len].to_vec())
+        .map_err(|_| Error::ParseError("The keymap isn't valid UTF-8".to_owned()))
+}
+
+async fn read(
+    settings: &SettingsProxy<'_>,
+    namespace: &str,
+    key: &str,
+) -> Result<OwnedValue, Error> {
+    call_method(settings, "Read", &(namespace, key)).await
+}
+
+// The `xkb` sources of an `a(ss)` value, split in layout and variant.
+fn input_sources(value: &OwnedValue) -> Vec<(String, String)> {
+    let mut sources = vec![];
+    if let Value::Array(array) = unwrap_variant(value) {
+        for source in array.get() {
+            if let Value::Structure(source) = source {
+                if let [Value::Str(kind), Value::Str(id)] = source.fields() {
+                    if kind.as_str() == "xkb" {
+                        let (layout, variant) =
+                            id.as_str().split_once('+').unwrap_or((id.as_str(), ""));
+                        sources.push((layout.to_owned(), variant.to_owned()));
+                    }
+                }
+            }
+        }
+    }
+    sources
+}
+
+fn strings(value: &OwnedValue) -> Vec<String> {
+    match unwrap_variant(value) {
+        Value::Array(array) => array
+            .get()
+            .iter()
+            .filter_map(|value| match value {
+                Value::Str(value) => Some(value.to_string()),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+// The Settings portal wraps the values in a variant.
+fn unwrap_variant<'a>(mut value: &'a Value<'a>) -> &'a Value<'a> {
+    while let Value::Value(inner) = value {
+        value = inner;
+    }
+    value
+}
+
+#[cfg(feature = "xkbcommon")]
+/// A compiled XKB keymap, translating the key codes forwarded to a remote
+/// desktop session to key symbols.
+///
+/// ```rust,no_run
+/// use ashpd::desktop::remote_desktop::{Keymap, Rmlvo};
+///
+/// let keymap = Keymap::new(&Rmlvo::new("", "", "fr", "", None)).unwrap();
+/// // The evdev key code of the Q key of a QWERTY keyboard
+/// assert_eq!(keymap.keysym(16, 0), Some(0x61)); // `a`
+/// ```
+pub struct Keymap {
+    keymap: xkbcommon::xkb::Keymap,
+}
+
+#[cfg(feature = "xkbcommon")]
+impl Keymap {
+    /// Compile the keymap described by `rmlvo`.
+    pub fn new(rmlvo: &Rmlvo) -> Result<Self, Error> {
+        use xkbcommon::xkb;
+
+        let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+        let keymap = xkb::Keymap::new_from_names(
+            &context,
+            &rmlvo.rules,
+            &rmlvo.model,
+            &rmlvo.layout,
+            &rmlvo.variant,
+            rmlvo.options.clone(),
+            xkb::KEYMAP_COMPILE_NO_FLAGS,
+        )
+        .ok_or_else(|| Error::ParseError(format!("Failed to compile the keymap {:?}", rmlvo)))?;
+        Ok(Self { keymap })
+    }
+
+    /// Compile a keymap in the XKB text format.
+    pub fn from_string(keymap: &str) -> Result<Self, Error> {
+        use xkbcommon::xkb;
+
+        let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+        let keymap = xkb::Keymap::new_from_string(
+            &context,
+            keymap.to_owned(),
+            xkb::KEYMAP_FORMAT_TEXT_V1,
+            xkb::KEYMAP_COMPILE_NO_FLAGS,
+        )
+        .ok_or_else(|| Error::ParseError("Failed to compile the keymap".to_owned()))?;
+        Ok(Self { keymap })
+    }
+
+    /// Compile the keymap of `size` bytes shared through `fd`, see
+    /// [`read_keymap`].
+    pub fn from_fd(fd: &impl AsRawFd, size: usize) -> Result<Self, Error> {
+        Self::from_string(&read_keymap(fd, size)?)
+    }
+
+    /// The key symbol produced by the Linux evdev `keycode` at shift `level`,
+    /// `0` for the base level and `1` for the shifted one, using the first
+    /// layout.
+    pub fn keysym(&self, keycode: i32, level: u32) -> Option<i32> {
+        // XKB key codes are offset by 8 from the evdev ones.
+        let keycode = u32::try_from(keycode).ok()?.checked_add(8)?;
+        self.keymap
+            .key_get_syms_by_level(keycode, 0, level)
+            .first()
+            .map(|keysym| *keysym as i32)
+    }
+}
+
+#[cfg(feature = "xkbcommon")]
+impl std::fmt::Debug for Keymap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Keymap")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs::File, io::Write, os::unix::io::FromRawFd};
+
+    use nix::sys::memfd::{memfd_create, MemFdCreateFlag};
+
+    use super::read_keymap;
+
+    #[test]
+    fn keymap_fd() {
+        let keymap = "xkb_keymap { xkb_keycodes { include \"evdev\" }; };";
+        let fd = memfd_create(c"keymap", MemFdCreateFlag::MFD_CLOEXEC).unwrap();
+        let mut file = unsafe { File::from_raw_fd(fd) };
+        file.write_all(keymap.as_bytes()).unwrap();
+        file.write_all(b"\0").unwrap();
+
+        assert_eq!(read_keymap(&file, keymap.len() + 1).unwrap(), keymap);
+        assert!(read_keymap(&file, 0).is_err());
+    }
+}
@@ -0,0 +1,52 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use super::InputEvent;
+
+/// Receives measurements of the input events forwarded by an
+/// [`ActiveSession`](super::ActiveSession), see
+/// [`ActiveSession::set_metrics`](super::ActiveSession::set_metrics).
+///
+/// The methods are called from the task forwarding the events and should
+/// return quickly, by updating a counter or a histogram for example.
+///
+/// ```rust
+/// use std::{
+///     sync::atomic::{AtomicU64, Ordering},
+///     time::Duration,
+/// };
+///
+/// use ashpd::desktop::remote_desktop::{InputEvent, Metrics};
+///
+/// #[derive(Default)]
+/// struct SlowEvents(AtomicU64);
+///
+/// impl Metrics for SlowEvents {
+///     fn event_forwarded(&self, _event: &InputEvent, latency: Duration, _succeeded: bool) {
+///         if latency > Duration::from_millis(20) {
+///             self.0.fetch_add(1, Ordering::Relaxed);
+///         }
+///     }
+/// }
+/// ```
+pub trait Metrics: Send + Sync {
+    /// An event was forwarded, `latency` is the time between the call to the
+    /// portal and its reply.
+    fn event_forwarded(&self, event: &InputEvent, latency: Duration, succeeded: bool) {
+        let _ = (event, latency, succeeded);
+    }
+
+    /// A batch of `depth` events is about to be forwarded with
+    /// [`ActiveSession::notify_all`](super::ActiveSession::notify_all).
+    fn batch_queued(&self, depth: usize) {
+        let _ = depth;
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct MetricsHandle(pub(super) Arc<dyn Metrics>);
+
+impl fmt::Debug for MetricsHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Metrics")
+    }
+}
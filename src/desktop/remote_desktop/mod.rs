@@ -0,0 +1,1387 @@
+//! # Examples
+//!
+//! ```rust,no_run
+//! use ashpd::desktop::remote_desktop::{DeviceType, KeyState, RemoteDesktopProxy};
+//! use ashpd::WindowIdentifier;
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::Connection::session().await?;
+//!     let proxy = RemoteDesktopProxy::new(&connection).await?;
+//!
+//!     let session = proxy.create_session().await?;
+//!
+//!     proxy.select_devices(&session, DeviceType::Keyboard | DeviceType::Pointer).await?;
+//!
+//!     let selected = proxy.start(&session, &WindowIdentifier::default()).await?;
+//!     println!("{:#?}", selected.devices());
+//!
+//!     // 13 for Enter key code
+//!     proxy.notify_keyboard_keycode(&session, 13, KeyState::Pressed).await?;
+//!     proxy.notify_keyboard_keycode(&session, 13, KeyState::Released).await?;
+//!
+//!     session.close().await?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! You can also use the Remote Desktop portal with the ScreenCast one. In order to do so,
+//! you need to call [`ScreenCastProxy::select_sources()`](crate::desktop::screencast::ScreenCastProxy::select_sources)
+//! on the session created with [`RemoteDesktopProxy::create_session()`](crate::desktop::remote_desktop::RemoteDesktopProxy::create_session)
+//!
+//! ```rust,no_run
+//! use ashpd::desktop::remote_desktop::{DeviceType, KeyState, RemoteDesktopProxy};
+//! use ashpd::desktop::screencast::{
+//!     CursorMode, PersistMode, ScreenCastProxy, SelectSourcesOptions, SourceType,
+//! };
+//! use ashpd::WindowIdentifier;
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::Connection::session().await?;
+//!     let proxy = RemoteDesktopProxy::new(&connection).await?;
+//!     let screencast = ScreenCastProxy::new(&connection).await?;
+//!     let identifier = WindowIdentifier::default();
+//!
+//!     let session = proxy.create_session().await?;
+//!
+//!     proxy.select_devices(&session, DeviceType::Keyboard | DeviceType::Pointer).await?;
+//!     let options = SelectSourcesOptions::default()
+//!         .with_cursor_mode(CursorMode::Metadata)
+//!         .with_types(SourceType::Monitor | SourceType::Window)
+//!         .with_multiple(true)
+//!         .with_persist_mode(PersistMode::DoNot);
+//!     screencast.select_sources(&session, options).await?;
+//!
+//!     let selected = proxy.start(&session, &identifier).await?;
+//!     println!("{:#?}", selected.devices());
+//!     println!("{:#?}", selected.streams());
+//!
+//!     // 13 for Enter key code
+//!     proxy.notify_keyboard_keycode(&session, 13, KeyState::Pressed).await?;
+//!     proxy.notify_keyboard_keycode(&session, 13, KeyState::Released).await?;
+//!
+//!     session.close().await?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    hash::Hash,
+    os::unix::prelude::{FromRawFd, IntoRawFd},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use enumflags2::{bitflags, BitFlags};
+use zbus::zvariant::{DeserializeDict, OwnedFd, OwnedObjectPath, SerializeDict, Type, Value};
+
+use super::{
+    permission_store::{restore_token_status, PermissionStatus},
+    screencast::Stream,
+    HandleToken, PortalRequest, RemoteDesktopPortal, SessionCapabilities, SessionGuard,
+    SessionProxy, DESTINATION, INTERFACE_REMOTE_DESKTOP, PATH,
+};
+
+use crate::{
+    helpers::{
+        call_basic_response_method, call_method, call_request_method, call_window_request_method,
+        get_property, session_connection,
+    },
+    Error, TolerantBitFlags, WindowIdentifier,
+};
+
+#[derive(Copy, Clone, PartialEq, Debug, Type)]
+#[doc(alias = "XdpKeyState")]
+#[non_exhaustive]
+/// The keyboard key state.
+///
+/// Converts from and to the `u32` sent on the bus.
+pub enum KeyState {
+    #[doc(alias = "XDP_KEY_PRESSED")]
+    /// The key is pressed.
+    Pressed = 0,
+    #[doc(alias = "XDP_KEY_RELEASED")]
+    /// The key is released..
+    Released = 1,
+}
+
+impl_u32_conversions!(KeyState {
+    Pressed = 0,
+    Released = 1,
+});
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// A key to press or release with [`RemoteDesktopProxy::notify_key`], either
+/// the physical key or the symbol it produces.
+///
+/// - A keycode is the evdev code of a physical key, as defined in
+///   `linux/input-event-codes.h`, `KEY_A` (30) for example. The symbol it
+///   produces depends on the keyboard layout of the session, `KEY_A` types a
+///   `q` with a French layout. The X11 keycodes are the evdev ones plus 8.
+/// - A keysym is the XKB symbol to type, `XKB_KEY_a` (0x61) for example. The
+///   compositor presses a key producing it in the current layout, a symbol
+///   missing from the layout may be ignored.
+///
+/// A keycode sent as a keysym, or the other way around, types an unrelated
+/// key or nothing: evdev's `KEY_A`, 30, isn't a keysym.
+pub enum Key {
+    /// An evdev keycode, layout dependent.
+    Keycode(i32),
+    /// An XKB keysym, layout independent.
+    Keysym(i32),
+}
+
+impl Key {
+    /// The event pressing or releasing the key, to forward with
+    /// [`ActiveSession::notify`].
+    pub fn event(self, state: KeyState) -> InputEvent {
+        match self {
+            Self::Keycode(keycode) => InputEvent::KeyboardKeycode { keycode, state },
+            Self::Keysym(keysym) => InputEvent::KeyboardKeysym { keysym, state },
+        }
+    }
+}
+
+#[bitflags]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Type)]
+#[repr(u32)]
+#[doc(alias = "XdpDeviceType")]
+#[non_exhaustive]
+/// A bit flag for the available devices.
+///
+/// Converts from and to the `u32` bit of the device, and from and to its
+/// name, see [`str_form`](crate::str_form).
+pub enum DeviceType {
+    #[doc(alias = "XDP_DEVICE_KEYBOARD")]
+    /// A keyboard.
+    Keyboard,
+    #[doc(alias = "XDP_DEVICE_POINTER")]
+    /// A mouse pointer.
+    Pointer,
+    #[doc(alias = "XDP_DEVICE_TOUCHSCREEN")]
+    /// A touchscreen
+    Touchscreen,
+}
+
+impl_u32_conversions!(DeviceType {
+    Keyboard = 1,
+    Pointer = 2,
+    Touchscreen = 4,
+});
+
+impl_str_conversions!(DeviceType {
+    Keyboard = "keyboard",
+    Pointer = "pointer",
+    Touchscreen = "touchscreen",
+});
+
+#[derive(Copy, Clone, PartialEq, Debug, Type)]
+#[doc(alias = "XdpDiscreteAxis")]
+#[non_exhaustive]
+/// The available axis.
+///
+/// Converts from and to the `u32` sent on the bus.
+pub enum Axis {
+    #[doc(alias = "XDP_AXIS_VERTICAL_SCROLL")]
+    /// Vertical axis.
+    Vertical = 0,
+    #[doc(alias = "XDP_AXIS_HORIZONTAL_SCROLL")]
+    /// Horizontal axis.
+    Horizontal = 1,
+}
+
+impl_u32_conversions!(Axis {
+    Vertical = 0,
+    Horizontal = 1,
+});
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+/// A scroll event, see [`RemoteDesktopProxy::notify_scroll`].
+pub enum ScrollDelta {
+    /// The axis movement from a "smooth scroll" device, such as a touchpad.
+    Smooth {
+        /// Relative axis movement on the x axis.
+        dx: f64,
+        /// Relative axis movement on the y axis.
+        dy: f64,
+        /// Whether it is the last axis event.
+        finish: bool,
+    },
+    /// The movement of a high-resolution wheel, in fractions of a detent
+    /// where 120 is a full one, like the `value120` of libinput or Wayland.
+    Value120 {
+        /// The axis that was scrolled.
+        axis: Axis,
+        /// The movement, positive when scrolling down or right.
+        value: i32,
+    },
+}
+
+// Adds `value` to the `remainder` of a wheel, returning the whole detents
+// scrolled so far. A change of direction drops the previous remainder.
+fn accumulate_value120(remainder: &mut i32, value: i32) -> i32 {
+    if remainder.signum() * value.signum() < 0 {
+        *remainder = 0;
+    }
+    *remainder += value;
+    let steps = *remainder / 120;
+    *remainder %= 120;
+    steps
+}
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
+/// Specified options for a [`RemoteDesktopProxy::create_session`] request.
+#[zvariant(signature = "dict")]
+struct CreateRemoteOptions {
+    /// A string that will be used as the last element of the handle.
+    handle_token: HandleToken,
+    /// A string that will be used as the last element of the session handle.
+    session_handle_token: HandleToken,
+}
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Clone, PartialEq, Eq)]
+/// A response to a [`RemoteDesktopProxy::create_session`] request.
+#[zvariant(signature = "dict")]
+pub(crate) struct CreateSession {
+    // TODO: investigate why this doesn't return an ObjectPath
+    // replace with an ObjectPath once https://github.com/flatpak/xdg-desktop-portal/pull/609's merged
+    /// A string that will be used as the last element of the session handle.
+    session_handle: String,
+}
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
+/// Specified options for a [`RemoteDesktopProxy::select_devices`] request.
+#[zvariant(signature = "dict")]
+struct SelectDevicesOptions {
+    /// A string that will be used as the last element of the handle.
+    handle_token: HandleToken,
+    /// The device types to request remote controlling of. Default is all.
+    types: Option<BitFlags<DeviceType>>,
+}
+
+impl SelectDevicesOptions {
+    options_setters! {
+        @setters
+        /// Sets the device types to request remote controlling of.
+        with_types(types: impl Into<BitFlags<DeviceType>>);
+    }
+}
+
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
+/// Specified options for a [`RemoteDesktopProxy::start`] request.
+#[zvariant(signature = "dict")]
+struct StartRemoteOptions {
+    /// A string that will be used as the last element of the handle.
+    handle_token: HandleToken,
+}
+
+#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, Default, PartialEq, Eq)]
+/// A response to a [`RemoteDesktopProxy::start`] request.
+///
+/// Iterating over it yields the granted devices.
+///
+/// ```rust,no_run
+/// use ashpd::desktop::remote_desktop::{DeviceType, RemoteDesktopProxy};
+/// use ashpd::WindowIdentifier;
+///
+/// async fn run() -> ashpd::Result<()> {
+///     let connection = zbus::Connection::session().await?;
+///     let proxy = RemoteDesktopProxy::new(&connection).await?;
+///     let session = proxy.create_session().await?;
+///     proxy.select_devices(&session, DeviceType::Keyboard | DeviceType::Pointer).await?;
+///
+///     let selected = proxy.start(&session, &WindowIdentifier::default()).await?;
+///     if !selected.contains(DeviceType::Keyboard) {
+///         println!("No keyboard access");
+///     }
+///     for device in &selected {
+///         println!("{:?}", device);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[zvariant(signature = "dict")]
+pub struct SelectedDevices {
+    /// The selected devices.
+    devices: TolerantBitFlags<DeviceType>,
+    /// The selected streams if a ScreenCast portal is used on the same session
+    streams: Option<Vec<Stream>>,
+    /// Whether the clipboard was enabled, since version 2 of the portal.
+    clipboard_enabled: Option<bool>,
+}
+
+impl SelectedDevices {
+    /// The devices the user granted access to.
+    pub fn devices(&self) -> BitFlags<DeviceType> {
+        self.devices.flags()
+    }
+
+    /// Whether the user granted access to `device`.
+    pub fn contains(&self, device: DeviceType) -> bool {
+        self.devices().contains(device)
+    }
+
+    /// An iterator over the granted devices.
+    pub fn iter(&self) -> enumflags2::Iter<DeviceType> {
+        self.devices().iter()
+    }
+
+    /// The selected streams if a ScreenCast portal is used on the same
+    /// session.
+    pub fn streams(&self) -> &[Stream] {
+        self.streams.as_deref().unwrap_or_default()
+    }
+
+    /// Whether the clipboard is shared with the session, requested with
+    /// [`ClipboardProxy::request_clipboard`](crate::desktop::clipboard::ClipboardProxy::request_clipboard).
+    pub fn clipboard_enabled(&self) -> bool {
+        self.clipboard_enabled.unwrap_or_default()
+    }
+
+    /// The capabilities of the started session, see [`SessionCapabilities`].
+    pub fn capabilities(&self) -> SessionCapabilities {
+        SessionCapabilities::from(self)
+    }
+}
+
+impl From<SelectedDevices> for BitFlags<DeviceType> {
+    fn from(selected: SelectedDevices) -> Self {
+        selected.devices()
+    }
+}
+
+impl IntoIterator for SelectedDevices {
+    type Item = DeviceType;
+    type IntoIter = enumflags2::Iter<DeviceType>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for &SelectedDevices {
+    type Item = DeviceType;
+    type IntoIter = enumflags2::Iter<DeviceType>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// The timeout of the `notify_*` methods when the proxy has none, an input
+/// event delivered late is worse than a lost one.
+pub const NOTIFY_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// A session created by [`RemoteDesktopProxy::create_session`].
+pub type RemoteDesktopSession<'a> = SessionProxy<'a, RemoteDesktopPortal>;
+
+/// The interface lets sandboxed applications create remote desktop sessions.
+///
+/// The `notify_*` methods injecting the input events default to
+/// [`NOTIFY_TIMEOUT`], see [`RemoteDesktopProxy::with_timeout`].
+///
+/// Wrapper of the DBus interface: [`org.freedesktop.portal.RemoteDesktop`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.RemoteDesktop).
+#[derive(Clone)]
+#[doc(alias = "org.freedesktop.portal.RemoteDesktop")]
+pub struct RemoteDesktopProxy<'a>(
+    zbus::Proxy<'a>,
+    Option<std::time::Duration>,
+    Arc<Mutex<HashMap<OwnedObjectPath, [i32; 2]>>>,
+);
+
+impl_try_from_proxy!(
+    RemoteDesktopProxy,
+    INTERFACE_REMOTE_DESKTOP,
+    Default::default()
+);
+impl_proxy_timeout!(RemoteDesktopProxy);
+
+impl<'a> RemoteDesktopProxy<'a> {
+    /// Create a new instance of [`RemoteDesktopProxy`].
+    pub async fn new(connection: &zbus::Connection) -> Result<RemoteDesktopProxy<'a>, Error> {
+        let proxy = zbus::ProxyBuilder::new_bare(connection)
+            .interface(INTERFACE_REMOTE_DESKTOP)?
+            .path(PATH)?
+            .destination(DESTINATION)?
+            .build()
+            .await?;
+        Ok(Self(proxy, None, Default::default()))
+    }
+
+    /// Get a reference to the underlying Proxy.
+    pub fn inner(&self) -> &zbus::Proxy<'_> {
+        &self.0
+    }
+
+    fn notify_proxy(&self) -> (&zbus::Proxy<'_>, Option<Duration>) {
+        (&self.0, Some(self.1.unwrap_or(NOTIFY_TIMEOUT)))
+    }
+
+    /// Create a remote desktop session.
+    /// A remote desktop session is used to allow remote controlling a desktop
+    /// session. It can also be used together with a screen cast session.
+    ///
+    /// The session stays alive until it is closed with
+    /// [`SessionProxy::close`] or by the portal.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`CreateSession`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.CreateSession).
+    #[doc(alias = "CreateSession")]
+    #[doc(alias = "xdp_portal_create_remote_desktop_session")]
+    pub fn create_session(&self) -> PortalRequest<'_, RemoteDesktopSession<'a>> {
+        let options = CreateRemoteOptions::default();
+        let handle_token = options.handle_token.copy();
+        let session_handle_token = options.session_handle_token.copy();
+        call_request_method::<CreateSession, _>(self, &handle_token, "CreateSession", options).then(
+            move |session| async move {
+                let proxy: RemoteDesktopSession<'a> = SessionProxy::from_unique_name(
+                    self.inner().connection(),
+                    &session_handle_token,
+                )
+                .await?;
+                assert_eq!(proxy.inner().path().as_str(), &session.session_handle);
+                Ok(proxy)
+            },
+        )
+    }
+
+    /// Select input devices to remote control.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - A [`RemoteDesktopSession`], created with
+    ///   [`create_session()`][`RemoteDesktopProxy::create_session`].
+    /// * `types` - The device types to request remote controlling of.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`SelectDevices`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.SelectDevices).
+    #[doc(alias = "SelectDevices")]
+    pub fn select_devices<'p>(
+        &'p self,
+        session: &'p RemoteDesktopSession<'_>,
+        types: impl Into<BitFlags<DeviceType>>,
+    ) -> PortalRequest<'p, ()> {
+        let options = SelectDevicesOptions::default().with_types(types);
+        let handle_token = options.handle_token.copy();
+        call_basic_response_method(self, &handle_token, "SelectDevices", (session, options))
+    }
+
+    ///  Start the remote desktop session.
+    ///
+    /// This will typically result in the portal presenting a dialog letting
+    /// the user select what to share, including devices and optionally screen
+    /// content if screen cast sources was selected.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - A [`RemoteDesktopSession`], created with
+    ///   [`create_session()`][`RemoteDesktopProxy::create_session`].
+    /// * `identifier` - The application window identifier.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`Start`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.Start).
+    #[doc(alias = "Start")]
+    pub fn start<'p>(
+        &'p self,
+        session: &'p RemoteDesktopSession<'_>,
+        identifier: &'p WindowIdentifier,
+    ) -> PortalRequest<'p, SelectedDevices> {
+        let options = StartRemoteOptions::default();
+        let handle_token = options.handle_token.copy();
+        call_window_request_method(
+            self,
+            identifier,
+            &handle_token,
+            "Start",
+            (session, identifier, options),
+        )
+    }
+
+    /// Start the remote desktop session and wrap it in an [`ActiveSession`].
+    ///
+    /// Unlike [`RemoteDesktopProxy::start`], the returned session remembers
+    /// the devices the user granted access to and refuses to forward events
+    /// of the other ones with [`Error::DeviceNotGranted`].
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - A [`RemoteDesktopSession`], created with
+    ///   [`create_session()`][`RemoteDesktopProxy::create_session`].
+    /// * `identifier` - The application window identifier.
+    pub fn start_session<'p>(
+        &'p self,
+        session: RemoteDesktopSession<'a>,
+        identifier: &'p WindowIdentifier,
+    ) -> PortalRequest<'p, ActiveSession<'a>> {
+        let options = StartRemoteOptions::default();
+        let handle_token = options.handle_token.copy();
+        call_window_request_method::<SelectedDevices, _>(
+            self,
+            identifier,
+            &handle_token,
+            "Start",
+            (session.clone(), identifier, options),
+        )
+        .map(move |selected| {
+            Ok(ActiveSession::new(
+                self.clone(),
+                session,
+                selected.capabilities(),
+            ))
+        })
+    }
+
+    /// Notify keyboard code.
+    ///
+    /// **Note** only works if [`DeviceType::Keyboard`] access was provided
+    /// after starting the session.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - A [`RemoteDesktopSession`], created with
+    ///   [`create_session()`][`RemoteDesktopProxy::create_session`].
+    /// * `keycode` - Keyboard code that was pressed or released.
+    /// * `state` - The new state of the keyboard code.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyKeyboardKeycode`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyKeyboardKeycode).
+    #[doc(alias = "NotifyKeyboardKeycode")]
+    pub async fn notify_keyboard_keycode(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        keycode: i32,
+        state: KeyState,
+    ) -> Result<(), Error> {
+        // The `notify` methods don't take any options for now
+        // see https://github.com/flatpak/xdg-desktop-portal/blob/master/src/remote-desktop.c#L723
+        let options: HashMap<&str, Value<'_>> = HashMap::new();
+        call_method(
+            &self.notify_proxy(),
+            "NotifyKeyboardKeycode",
+            &(session, options, keycode, state),
+        )
+        .await
+    }
+
+    /// Notify keyboard symbol.
+    ///
+    /// **Note** only works if [`DeviceType::Keyboard`] access was provided
+    /// after starting the session.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - A [`RemoteDesktopSession`], created with
+    ///   [`create_session()`][`RemoteDesktopProxy::create_session`].
+    /// * `keysym` - Keyboard symbol that was pressed or released.
+    /// * `state` - The new state of the keyboard code.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyKeyboardKeysym`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyKeyboardKeysym).
+    #[doc(alias = "NotifyKeyboardKeysym")]
+    pub async fn notify_keyboard_keysym(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        keysym: i32,
+        state: KeyState,
+    ) -> Result<(), Error> {
+        // The `notify` methods don't take any options for now
+        // see https://github.com/flatpak/xdg-desktop-portal/blob/master/src/remote-desktop.c#L723
+        let options: HashMap<&str, Value<'_>> = HashMap::new();
+        call_method(
+            &self.notify_proxy(),
+            "NotifyKeyboardKeysym",
+            &(session, options, keysym, state),
+        )
+        .await
+    }
+
+    /// Notify a key press or release, through
+    /// [`notify_keyboard_keycode()`][`RemoteDesktopProxy::notify_keyboard_keycode`]
+    /// or [`notify_keyboard_keysym()`][`RemoteDesktopProxy::notify_keyboard_keysym`]
+    /// depending on the kind of `key`.
+    ///
+    /// **Note** only works if [`DeviceType::Keyboard`] access was provided
+    /// after starting the session.
+    ///
+    /// ```rust,no_run
+    /// use ashpd::desktop::remote_desktop::{Key, KeyState, RemoteDesktopSession, RemoteDesktopProxy};
+    ///
+    /// async fn type_a(proxy: &RemoteDesktopProxy<'_>, session: &RemoteDesktopSession<'_>) -> ashpd::Result<()> {
+    ///     // The `a` symbol, whichever the layout.
+    ///     let key = Key::Keysym(0x61);
+    ///     proxy.notify_key(session, key, KeyState::Pressed).await?;
+    ///     proxy.notify_key(session, key, KeyState::Released).await
+    /// }
+    /// ```
+    pub async fn notify_key(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        key: Key,
+        state: KeyState,
+    ) -> Result<(), Error> {
+        match key {
+            Key::Keycode(keycode) => self.notify_keyboard_keycode(session, keycode, state).await,
+            Key::Keysym(keysym) => self.notify_keyboard_keysym(session, keysym, state).await,
+        }
+    }
+
+    /// Notify about a new touch up event.
+    ///
+    /// **Note** only works if [`DeviceType::Touchscreen`] access was provided
+    /// after starting the session.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - A [`RemoteDesktopSession`], created with
+    ///   [`create_session()`][`RemoteDesktopProxy::create_session`].
+    /// * `slot` - Touch slot of the touch point that was released.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyTouchUp`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyTouchUp).
+    #[doc(alias = "NotifyTouchUp")]
+    pub async fn notify_touch_up(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        slot: u32,
+    ) -> Result<(), Error> {
+        // The `notify` methods don't take any options for now
+        // see https://github.com/flatpak/xdg-desktop-portal/blob/master/src/remote-desktop.c#L723
+        let options: HashMap<&str, Value<'_>> = HashMap::new();
+        call_method(
+            &self.notify_proxy(),
+            "NotifyTouchUp",
+            &(session, options, slot),
+        )
+        .await
+    }
+
+    /// Notify about a new touch down event.
+    /// The (x, y) position represents the new touch point position in the
+    /// streams logical coordinate space.
+    ///
+    /// The slot identifies the touch point until the matching
+    /// [`notify_touch_up`][`RemoteDesktopProxy::notify_touch_up`], it can be
+    /// reused afterwards. [`SlotAllocator`] maps the touch points of the
+    /// platform to the slots.
+    ///
+    /// **Note** only works if [`DeviceType::Touchscreen`] access was provided
+    /// after starting the session.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - A [`RemoteDesktopSession`], created with
+    ///   [`create_session()`][`RemoteDesktopProxy::create_session`].
+    /// * `stream` - The PipeWire stream node the coordinate is relative to.
+    /// * `slot` - Touch slot where touch point appeared.
+    /// * `x` - Touch down x coordinate.
+    /// * `y` - Touch down y coordinate.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyTouchDown`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyTouchDown).
+    #[doc(alias = "NotifyTouchDown")]
+    pub async fn notify_touch_down(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        stream: u32,
+        slot: u32,
+        x: f64,
+        y: f64,
+    ) -> Result<(), Error> {
+        // The `notify` methods don't take any options for now
+        // see https://github.com/flatpak/xdg-desktop-portal/blob/master/src/remote-desktop.c#L723
+        let options: HashMap<&str, Value<'_>> = HashMap::new();
+        call_method(
+            &self.notify_proxy(),
+            "NotifyTouchDown",
+            &(session, options, stream, slot, x, y),
+        )
+        .await
+    }
+
+    /// Notify about a new touch motion event.
+    /// The (x, y) position represents where the touch point position in the
+    /// streams logical coordinate space moved.
+    ///
+    /// **Note** only works if [`DeviceType::Touchscreen`] access was provided
+    /// after starting the session.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - A [`RemoteDesktopSession`], created with
+    ///   [`create_session()`][`RemoteDesktopProxy::create_session`].
+    /// * `stream` - The PipeWire stream node the coordinate is relative to.
+    /// * `slot` - Touch slot of the touch point that moved.
+    /// * `x` - Touch motion x coordinate.
+    /// * `y` - Touch motion y coordinate.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyTouchMotion`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyTouchMotion).
+    #[doc(alias = "NotifyTouchMotion")]
+    pub async fn notify_touch_motion(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        stream: u32,
+        slot: u32,
+        x: f64,
+        y: f64,
+    ) -> Result<(), Error> {
+        // The `notify` methods don't take any options for now
+        // see https://github.com/flatpak/xdg-desktop-portal/blob/master/src/remote-desktop.c#L723
+        let options: HashMap<&str, Value<'_>> = HashMap::new();
+        call_method(
+            &self.notify_proxy(),
+            "NotifyTouchMotion",
+            &(session, options, stream, slot, x, y),
+        )
+        .await
+    }
+
+    /// Release all the touch points of `slots`, interrupting the ongoing
+    /// gestures.
+    ///
+    /// The portal has no way to cancel a touch sequence, a touch up is sent
+    /// for each slot in use instead. Otherwise the touch points the platform
+    /// stopped reporting, because the window lost the focus for example, stay
+    /// pressed on the remote host.
+    ///
+    /// `slots` is emptied even if a call fails, the first error is returned
+    /// once the other slots were released.
+    ///
+    /// **Note** only works if [`DeviceType::Touchscreen`] access was provided
+    /// after starting the session.
+    ///
+    /// ```rust,no_run
+    /// use ashpd::desktop::remote_desktop::{RemoteDesktopProxy, RemoteDesktopSession, SlotAllocator};
+    ///
+    /// async fn focus_lost(
+    ///     proxy: &RemoteDesktopProxy<'_>,
+    ///     session: &RemoteDesktopSession<'_>,
+    ///     slots: &mut SlotAllocator,
+    /// ) -> ashpd::Result<()> {
+    ///     proxy.cancel_all_touches(session, slots).await?;
+    ///     assert!(slots.is_empty());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn cancel_all_touches<K: Hash + Eq>(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        slots: &mut SlotAllocator<K>,
+    ) -> Result<(), Error> {
+        let mut released = slots.release_all();
+        released.sort_unstable();
+        let mut result = Ok(());
+        for slot in released {
+            let res = self.notify_touch_up(session, slot).await;
+            if result.is_ok() {
+                result = res;
+            }
+        }
+        result
+    }
+
+    /// Notify about a new absolute pointer motion event.
+    /// The (x, y) position represents the new pointer position in the streams
+    /// logical coordinate space.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - A [`RemoteDesktopSession`], created with
+    ///   [`create_session()`][`RemoteDesktopProxy::create_session`].
+    /// * `stream` - The PipeWire stream node the coordinate is relative to.
+    /// * `x` - Pointer motion x coordinate.
+    /// * `y` - Pointer motion y coordinate.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyPointerMotionAbsolute`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyPointerMotionAbsolute).
+    #[doc(alias = "NotifyPointerMotionAbsolute")]
+    pub async fn notify_pointer_motion_absolute(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        stream: u32,
+        x: f64,
+        y: f64,
+    ) -> Result<(), Error> {
+        // The `notify` methods don't take any options for now
+        // see https://github.com/flatpak/xdg-desktop-portal/blob/master/src/remote-desktop.c#L723
+        let options: HashMap<&str, Value<'_>> = HashMap::new();
+        call_method(
+            &self.notify_proxy(),
+            "NotifyPointerMotionAbsolute",
+            &(session, options, stream, x, y),
+        )
+        .await
+    }
+
+    /// Notify about a new relative pointer motion event.
+    /// The (dx, dy) vector represents the new pointer position in the streams
+    /// logical coordinate space.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - A [`RemoteDesktopSession`], created with
+    ///   [`create_session()`][`RemoteDesktopProxy::create_session`].
+    /// * `dx` - Relative movement on the x axis.
+    /// * `dy` - Relative movement on the y axis.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyPointerMotion`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyPointerMotion).
+    #[doc(alias = "NotifyPointerMotion")]
+    pub async fn notify_pointer_motion(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        dx: f64,
+        dy: f64,
+    ) -> Result<(), Error> {
+        // The `notify` methods don't take any options for now
+        // see https://github.com/flatpak/xdg-desktop-portal/blob/master/src/remote-desktop.c#L723
+        let options: HashMap<&str, Value<'_>> = HashMap::new();
+        call_method(
+            &self.notify_proxy(),
+            "NotifyPointerMotion",
+            &(session, options, dx, dy),
+        )
+        .await
+    }
+
+    /// Notify pointer button.
+    /// The pointer button is encoded according to Linux Evdev button codes.
+    ///
+    ///
+    /// **Note** only works if [`DeviceType::Pointer`] access was provided after
+    /// starting the session.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - A [`RemoteDesktopSession`], created with
+    ///   [`create_session()`][`RemoteDesktopProxy::create_session`].
+    /// * `button` - The pointer button was pressed or released.
+    /// * `state` - The new state of the keyboard code.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyPointerButton`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyPointerButton).
+    #[doc(alias = "NotifyPointerButton")]
+    pub async fn notify_pointer_button(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        button: i32,
+        state: KeyState,
+    ) -> Result<(), Error> {
+        // The `notify` methods don't take any options for now
+        // see https://github.com/flatpak/xdg-desktop-portal/blob/master/src/remote-desktop.c#L723
+        let options: HashMap<&str, Value<'_>> = HashMap::new();
+        call_method(
+            &self.notify_proxy(),
+            "NotifyPointerButton",
+            &(session, options, button, state),
+        )
+        .await
+    }
+
+    /// Notify pointer axis discrete.
+    ///
+    /// **Note** only works if [`DeviceType::Pointer`] access was provided after
+    /// starting the session.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - A [`RemoteDesktopSession`], created with
+    ///   [`create_session()`][`RemoteDesktopProxy::create_session`].
+    /// * `axis` - The axis that was scrolled.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyPointerAxisDiscrete`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyPointerAxisDiscrete).
+    #[doc(alias = "NotifyPointerAxisDiscrete")]
+    pub async fn notify_pointer_axis_discrete(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        axis: Axis,
+        steps: i32,
+    ) -> Result<(), Error> {
+        // The `notify` methods don't take any options for now
+        // see https://github.com/flatpak/xdg-desktop-portal/blob/master/src/remote-desktop.c#L723
+        let options: HashMap<&str, Value<'_>> = HashMap::new();
+        call_method(
+            &self.notify_proxy(),
+            "NotifyPointerAxisDiscrete",
+            &(session, options, axis, steps),
+        )
+        .await
+    }
+
+    /// Notify pointer axis.
+    /// The axis movement from a "smooth scroll" device, such as a touchpad.
+    /// When applicable, the size of the motion delta should be equivalent to
+    /// the motion vector of a pointer motion done using the same advice.
+    ///
+    ///
+    /// **Note** only works if [`DeviceType::Pointer`] access was provided after
+    /// starting the session.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - A [`RemoteDesktopSession`], created with
+    ///   [`create_session()`][`RemoteDesktopProxy::create_session`].
+    /// * `dx` - Relative axis movement on the x axis.
+    /// * `dy` - Relative axis movement on the y axis.
+    /// * `finish` - Whether it is the last axis event.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`NotifyPointerAxis`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.NotifyPointerAxis).
+    #[doc(alias = "NotifyPointerAxis")]
+    pub async fn notify_pointer_axis(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        dx: f64,
+        dy: f64,
+        finish: bool,
+    ) -> Result<(), Error> {
+        // see https://github.com/flatpak/xdg-desktop-portal/blob/master/src/remote-desktop.c#L911
+        let mut options: HashMap<&str, Value<'_>> = HashMap::new();
+        options.insert("finish", Value::Bool(finish));
+        call_method(
+            &self.notify_proxy(),
+            "NotifyPointerAxis",
+            &(session, options, dx, dy),
+        )
+        .await
+    }
+
+    /// Notify a scroll event, from a touchpad or a wheel.
+    ///
+    /// The [`ScrollDelta::Value120`] movements of a session are accumulated
+    /// per axis and sent with
+    /// [`notify_pointer_axis_discrete()`][`RemoteDesktopProxy::notify_pointer_axis_discrete`]
+    /// once they add up to whole detents, nothing is sent until then. The
+    /// [`ScrollDelta::Smooth`] ones are sent with
+    /// [`notify_pointer_axis()`][`RemoteDesktopProxy::notify_pointer_axis`].
+    ///
+    /// **Note** only works if [`DeviceType::Pointer`] access was provided after
+    /// starting the session.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - A [`RemoteDesktopSession`], created with
+    ///   [`create_session()`][`RemoteDesktopProxy::create_session`].
+    /// * `delta` - The scroll movement.
+    pub async fn notify_scroll(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+        delta: ScrollDelta,
+    ) -> Result<(), Error> {
+        match delta {
+            ScrollDelta::Smooth { dx, dy, finish } => {
+                self.notify_pointer_axis(session, dx, dy, finish).await
+            }
+            ScrollDelta::Value120 { axis, value } => {
+                let steps = {
+                    let mut remainders = self.2.lock().unwrap();
+                    let path = OwnedObjectPath::from(session.inner().path().to_owned());
+                    let remainder = remainders.entry(path.clone()).or_default();
+                    let steps = accumulate_value120(&mut remainder[axis as usize], value);
+                    if *remainder == [0, 0] {
+                        remainders.remove(&path);
+                    }
+                    steps
+                };
+                if steps == 0 {
+                    return Ok(());
+                }
+                self.notify_pointer_axis_discrete(session, axis, steps)
+                    .await
+            }
+        }
+    }
+
+    /// Open a socket to the EIS server of the session, to send the input
+    /// events with libei instead of the `Notify*` methods.
+    ///
+    /// The keymaps of the keyboard devices are sent by the server during the
+    /// handshake, see [`read_keymap`]. With the `libei` feature, the socket
+    /// can be handed to `EiSender`, which exposes them along with the regions
+    /// of the devices.
+    ///
+    /// **Note** it has to be called after starting the session. The method
+    /// was added in the version 2 of the interface, older portals fail with
+    /// [`Error::RequiresVersion`] without being called.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - A [`RemoteDesktopSession`], created with
+    ///   [`create_session()`][`RemoteDesktopProxy::create_session`].
+    ///
+    /// # Returns
+    ///
+    /// File descriptor of a socket connected to the EIS server, closed when
+    /// dropped.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`ConnectToEIS`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-RemoteDesktop.ConnectToEIS).
+    #[doc(alias = "ConnectToEIS")]
+    pub async fn connect_to_eis(
+        &self,
+        session: &RemoteDesktopSession<'_>,
+    ) -> Result<std::os::unix::io::OwnedFd, Error> {
+        let version = get_property::<u32>(self, "version").await?;
+        if version < 2 {
+            return Err(Error::RequiresVersion(2, version));
+        }
+        // No options are defined yet.
+        let options: HashMap<&str, Value<'_>> = HashMap::new();
+        let fd: OwnedFd = call_method(self, "ConnectToEIS", &(session, options)).await?;
+        // SAFETY: the file descriptor is owned by `fd`, which releases it.
+        Ok(unsafe { std::os::unix::io::OwnedFd::from_raw_fd(fd.into_raw_fd()) })
+    }
+
+    /// Available source types.
+    ///
+    /// Unknown bits sent by the backend are ignored, see [`TolerantBitFlags`].
+    ///
+    /// # Specifications
+    ///
+    /// See also [`AvailableDeviceTypes`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-property-org-freedesktop-portal-RemoteDesktop.AvailableDeviceTypes).
+    #[doc(alias = "AvailableDeviceTypes")]
+    pub async fn available_device_types(&self) -> Result<BitFlags<DeviceType>, Error> {
+        get_property::<TolerantBitFlags<DeviceType>>(self, "AvailableDeviceTypes")
+            .await
+            .map(|flags| flags.flags())
+    }
+}
+
+/// Keep track of the keyboard modifiers forwarded to a session.
+mod modifiers;
+pub use modifiers::{Modifier, ModifierSequence, ModifierTracker};
+
+/// A started remote desktop session.
+mod active_session;
+pub use active_session::ActiveSession;
+
+/// Allocate the touch slots of a session.
+mod slots;
+pub use slots::SlotAllocator;
+
+/// Input events that can be forwarded in batches.
+mod input;
+pub use input::InputEvent;
+
+/// Limit the rate of the forwarded input events.
+mod throttle;
+pub use throttle::InputThrottle;
+
+/// Repeat the held keys.
+mod repeat;
+pub use repeat::KeyRepeater;
+
+/// Map the points of the streams to the coordinates of the session.
+mod geometry;
+pub use geometry::{StreamGeometry, Transform};
+
+/// Give the input back to the user of the host with a global shortcut.
+mod release;
+pub use release::{InputRelease, InputReleased};
+
+/// Measure the latency of the forwarded input events.
+mod metrics;
+pub use metrics::Metrics;
+
+/// Emulate the input devices with libei.
+#[cfg(feature = "libei")]
+mod eis;
+#[cfg(feature = "libei")]
+pub use eis::{ei_interfaces, EiError, EiSender};
+
+/// Translate the forwarded key codes with the keymap of the host.
+mod keymap;
+#[cfg(feature = "xkbcommon")]
+pub use keymap::Keymap;
+pub use keymap::{read_keymap, Rmlvo};
+
+/// Create and start a remote desktop session with access to `devices`, then
+/// run `f` with it.
+///
+/// The session is closed once `f` completes, successfully or not. It is also
+/// closed if `f` panics or if the returned future is dropped before
+/// completing, without waiting for the portal to reply in that case. That
+/// close is a task spawned on the executor of the connection: with a
+/// connection built with [`zbus::ConnectionBuilder::internal_executor`] set to
+/// `false`, it only runs once the application ticks that executor.
+///
+/// ```rust,no_run
+/// use ashpd::desktop::remote_desktop::{self, DeviceType, KeyState};
+/// use ashpd::WindowIdentifier;
+///
+/// async fn run() -> ashpd::Result<()> {
+///     remote_desktop::run_with_session(
+///         &WindowIdentifier::default(),
+///         DeviceType::Keyboard,
+///         |session| async move {
+///             session.notify_keyboard_keycode(13, KeyState::Pressed).await?;
+///             session.notify_keyboard_keycode(13, KeyState::Released).await
+///         },
+///     )
+///     .await
+/// }
+/// ```
+pub async fn run_with_session<T, F, Fut>(
+    identifier: &WindowIdentifier,
+    devices: impl Into<BitFlags<DeviceType>>,
+    f: F,
+) -> Result<T, Error>
+where
+    F: FnOnce(ActiveSession<'static>) -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    let connection = session_connection().await?;
+    let proxy = RemoteDesktopProxy::new(&connection).await?;
+    let session = proxy.create_session().await?;
+    let guard = SessionGuard::new(&session);
+    proxy.select_devices(&session, devices).await?;
+    let session = proxy.start_session(session, identifier).await?;
+    let result = f(session).await;
+    guard.close_after(result).await
+}
+
+/// Whether starting a remote desktop session with `restore_token`, received
+/// from a previous one, will prompt the user.
+///
+/// The restore tokens are only supported since the version 2 of the portal,
+/// [`PermissionStatus::Prompt`] is returned for the older ones or without a
+/// token.
+///
+/// **Note** the permission store is not reachable from inside of the sandbox.
+pub async fn permission_status(
+    app_id: &str,
+    restore_token: Option<&str>,
+) -> Result<PermissionStatus, Error> {
+    restore_token_status(
+        INTERFACE_REMOTE_DESKTOP,
+        2,
+        "remote-desktop",
+        app_id,
+        restore_token,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod test {
+    use enumflags2::BitFlags;
+    use serde::{de::DeserializeOwned, Serialize};
+    use zbus::zvariant::{from_slice, to_bytes, EncodingContext, Type};
+
+    use super::{
+        accumulate_value120, Axis, CreateRemoteOptions, CreateSession, DeviceType, InputEvent, Key,
+        KeyState, SelectDevicesOptions, SelectedDevices, StartRemoteOptions,
+    };
+    use crate::{desktop::HandleToken, helpers::assert_serialized_dict, TolerantBitFlags};
+
+    fn round_trip<T: Serialize + DeserializeOwned + Type>(value: &T) -> T {
+        let ctxt = EncodingContext::<byteorder::LE>::new_dbus(0);
+        from_slice(&to_bytes(ctxt, value).unwrap(), ctxt).unwrap()
+    }
+
+    #[test]
+    fn signatures() {
+        assert_eq!(KeyState::signature(), "u");
+        assert_eq!(DeviceType::signature(), "u");
+        assert_eq!(BitFlags::<DeviceType>::signature(), "u");
+        assert_eq!(Axis::signature(), "u");
+        assert_eq!(CreateSession::signature(), "a{sv}");
+        assert_eq!(SelectedDevices::signature(), "a{sv}");
+    }
+
+    #[test]
+    fn round_trips() {
+        assert_eq!(round_trip(&KeyState::Pressed), KeyState::Pressed);
+        assert_eq!(round_trip(&KeyState::Released), KeyState::Released);
+        assert_eq!(round_trip(&Axis::Vertical), Axis::Vertical);
+        assert_eq!(round_trip(&Axis::Horizontal), Axis::Horizontal);
+        let ctxt = EncodingContext::<byteorder::LE>::new_dbus(0);
+        assert_eq!(
+            from_slice::<_, u32>(&to_bytes(ctxt, &Axis::Horizontal).unwrap(), ctxt).unwrap(),
+            1
+        );
+
+        let devices = DeviceType::Keyboard | DeviceType::Touchscreen;
+        assert_eq!(round_trip(&devices), devices);
+
+        let session = round_trip(&CreateSession {
+            session_handle: "/org/freedesktop/portal/desktop/session/1_2/ashpd_1".to_owned(),
+        });
+        assert_eq!(
+            session.session_handle,
+            "/org/freedesktop/portal/desktop/session/1_2/ashpd_1"
+        );
+
+        let selected = round_trip(&SelectedDevices {
+            devices: TolerantBitFlags::from(devices),
+            streams: None,
+            clipboard_enabled: Some(true),
+        });
+        assert_eq!(selected.devices(), devices);
+        assert!(selected.streams.is_none());
+
+        let capabilities = selected.capabilities();
+        assert!(capabilities.is_granted(DeviceType::Touchscreen));
+        assert!(!capabilities.is_granted(DeviceType::Pointer));
+        assert!(capabilities.streams().is_empty());
+        assert!(capabilities.clipboard_enabled());
+        assert_eq!(capabilities.cursor_mode(), None);
+    }
+
+    #[test]
+    fn keys() {
+        assert_eq!(
+            Key::Keycode(30).event(KeyState::Pressed),
+            InputEvent::KeyboardKeycode {
+                keycode: 30,
+                state: KeyState::Pressed
+            }
+        );
+        assert_eq!(
+            Key::Keysym(0x61).event(KeyState::Released),
+            InputEvent::KeyboardKeysym {
+                keysym: 0x61,
+                state: KeyState::Released
+            }
+        );
+    }
+
+    #[test]
+    fn u32_conversions() {
+        assert_eq!(KeyState::try_from(0), Ok(KeyState::Pressed));
+        assert_eq!(u32::from(KeyState::Released), 1);
+        assert_eq!(Axis::try_from(1), Ok(Axis::Horizontal));
+        assert_eq!(u32::from(Axis::Vertical), 0);
+        for device in BitFlags::<DeviceType>::all().iter() {
+            assert_eq!(u32::from(device), BitFlags::from(device).bits());
+            assert_eq!(DeviceType::try_from(u32::from(device)), Ok(device));
+        }
+
+        let err = DeviceType::try_from(3).unwrap_err();
+        assert_eq!((err.type_name(), err.value()), ("DeviceType", 3));
+        let ctxt = EncodingContext::<byteorder::LE>::new_dbus(0);
+        assert!(from_slice::<_, KeyState>(&to_bytes(ctxt, &2u32).unwrap(), ctxt).is_err());
+    }
+
+    #[test]
+    fn options() {
+        let options = CreateRemoteOptions {
+            handle_token: HandleToken::default(),
+            session_handle_token: HandleToken::default(),
+        };
+        assert_serialized_dict(
+            &options,
+            &[("handle_token", "s"), ("session_handle_token", "s")],
+        );
+
+        let options = SelectDevicesOptions {
+            handle_token: HandleToken::default(),
+            types: Some(DeviceType::Keyboard | DeviceType::Pointer),
+        };
+        assert_serialized_dict(&options, &[("handle_token", "s"), ("types", "u")]);
+
+        let options = StartRemoteOptions {
+            handle_token: HandleToken::default(),
+        };
+        assert_serialized_dict(&options, &[("handle_token", "s")]);
+    }
+
+    #[cfg(feature = "backend")]
+    #[test]
+    fn cancel_all_touches() {
+        use std::{collections::HashMap, sync::Mutex};
+
+        use futures::executor::block_on;
+        use zbus::{
+            dbus_interface, fdo,
+            zvariant::{ObjectPath, OwnedObjectPath, OwnedValue},
+        };
+
+        use super::{RemoteDesktopProxy, SlotAllocator, PATH};
+        use crate::{backend::peer_connections, desktop::SessionProxy, Error};
+
+        // Fails the touch up of the slots 1 and 2.
+        #[derive(Default)]
+        struct RemoteDesktop(Mutex<Vec<u32>>);
+
+        #[dbus_interface(name = "org.freedesktop.portal.RemoteDesktop")]
+        impl RemoteDesktop {
+            fn notify_touch_up(
+                &self,
+                _session: OwnedObjectPath,
+                _options: HashMap<String, OwnedValue>,
+                slot: u32,
+            ) -> fdo::Result<()> {
+                self.0.lock().unwrap().push(slot);
+                match slot {
+                    1 | 2 => Err(fdo::Error::Failed(format!("Slot {}", slot))),
+                    _ => Ok(()),
+                }
+            }
+        }
+
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            let server = backend.object_server();
+            server.at(PATH, RemoteDesktop::default()).await.unwrap();
+            let proxy = RemoteDesktopProxy::new(&frontend).await.unwrap();
+            let session = SessionProxy::new(
+                &frontend,
+                ObjectPath::try_from("/org/freedesktop/portal/desktop/session/1_2/ashpd_1")
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+            let mut slots = SlotAllocator::new();
+            for id in 0..4 {
+                slots.acquire(id);
+            }
+            let err = proxy
+                .cancel_all_touches(&session, &mut slots)
+                .await
+                .unwrap_err();
+            match err.root() {
+                Error::Zbus(zbus::Error::MethodError(_, message, _)) => {
+                    assert_eq!(message.as_deref(), Some("Slot 1"))
+                }
+                err => panic!("unexpected {:?}", err),
+            }
+            assert!(slots.is_empty());
+
+            let remote = server.interface::<_, RemoteDesktop>(PATH).await.unwrap();
+            assert_eq!(*remote.get().await.0.lock().unwrap(), [0, 1, 2, 3]);
+        });
+    }
+
+    #[test]
+    fn value120() {
+        let mut remainder = 0;
+        assert_eq!(accumulate_value120(&mut remainder, 60), 0);
+        assert_eq!(accumulate_value120(&mut remainder, 90), 1);
+        assert_eq!(remainder, 30);
+        assert_eq!(accumulate_value120(&mut remainder, 240), 2);
+        assert_eq!(remainder, 30);
+        // Changing direction drops the remainder.
+        assert_eq!(accumulate_value120(&mut remainder, -100), 0);
+        assert_eq!(remainder, -100);
+        assert_eq!(accumulate_value120(&mut remainder, -20), -1);
+        assert_eq!(remainder, 0);
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::BTreeSet;
+
+use enumflags2::{bitflags, BitFlags};
+
+use super::KeyState;
+
+#[bitflags]
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[repr(u8)]
+/// A keyboard modifier.
+pub enum Modifier {
+    /// Shift.
+    Shift,
+    /// Control.
+    Control,
+    /// Alt.
+    Alt,
+    /// Super, also known as the Windows or Command key.
+    Super,
+}
+
+impl Modifier {
+    /// The Linux evdev key codes of the modifier, the left one first.
+    pub fn keycodes(&self) -> [i32; 2] {
+        match self {
+            Self::Shift => [42, 54],
+            Self::Control => [29, 97],
+            Self::Alt => [56, 100],
+            Self::Super => [125, 126],
+        }
+    }
+
+    /// The X11 key symbols of the modifier, the left one first.
+    pub fn keysyms(&self) -> [i32; 2] {
+        match self {
+            Self::Shift => [0xffe1, 0xffe2],
+            Self::Control => [0xffe3, 0xffe4],
+            Self::Alt => [0xffe9, 0xffea],
+            Self::Super => [0xffeb, 0xffec],
+        }
+    }
+
+    /// The modifier corresponding to a Linux evdev key code.
+    pub fn from_keycode(keycode: i32) -> Option<Self> {
+        BitFlags::<Self>::all()
+            .iter()
+            .find(|m| m.keycodes().contains(&keycode))
+    }
+
+    /// The modifier corresponding to an X11 key symbol.
+    pub fn from_keysym(keysym: i32) -> Option<Self> {
+        BitFlags::<Self>::all()
+            .iter()
+            .find(|m| m.keysyms().contains(&keysym))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// The key code events to send around an injected key, see
+/// [`ModifierTracker::wrap`].
+pub struct ModifierSequence {
+    before: Vec<(i32, KeyState)>,
+    after: Vec<(i32, KeyState)>,
+}
+
+impl ModifierSequence {
+    /// The key code events to send before the key.
+    pub fn before(&self) -> &[(i32, KeyState)] {
+        &self.before
+    }
+
+    /// The key code events to send after the key, restoring the previous
+    /// modifiers state.
+    pub fn after(&self) -> &[(i32, KeyState)] {
+        &self.after
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// Keeps track of the keyboard modifiers forwarded to a remote desktop
+/// session.
+///
+/// Feed it every key forwarded with
+/// [`RemoteDesktopProxy::notify_keyboard_keycode`](super::RemoteDesktopProxy::notify_keyboard_keycode)
+/// or
+/// [`RemoteDesktopProxy::notify_keyboard_keysym`](super::RemoteDesktopProxy::notify_keyboard_keysym),
+/// it can then tell which modifiers have to be pressed or released around an
+/// injected key symbol and which keys to release when the client loses focus,
+/// avoiding stuck modifiers on the remote side.
+///
+/// ```rust,no_run
+/// use ashpd::desktop::remote_desktop::{
+///     KeyState, Modifier, ModifierTracker, RemoteDesktopProxy, RemoteDesktopSession,
+/// };
+///
+/// async fn type_upper_a(
+///     proxy: &RemoteDesktopProxy<'_>,
+///     session: &RemoteDesktopSession<'_>,
+///     tracker: &ModifierTracker,
+/// ) -> ashpd::Result<()> {
+///     let sequence = tracker.wrap(Modifier::Shift);
+///     for (keycode, state) in sequence.before() {
+///         proxy.notify_keyboard_keycode(session, *keycode, *state).await?;
+///     }
+///     // 0x41 for the `A` key symbol
+///     proxy.notify_keyboard_keysym(session, 0x41, KeyState::Pressed).await?;
+///     proxy.notify_keyboard_keysym(session, 0x41, KeyState::Released).await?;
+///     for (keycode, state) in sequence.after() {
+///         proxy.notify_keyboard_keycode(session, *keycode, *state).await?;
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct ModifierTracker {
+    pressed: BTreeSet<i32>,
+}
+
+impl ModifierTracker {
+    /// Create a new tracker, with no modifiers pressed.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a forwarded key code event.
+    pub fn update_keycode(&mut self, keycode: i32, state: KeyState) {
+        if Modifier::from_keycode(keycode).is_some() {
+            self.set(keycode, state);
+        }
+    }
+
+    /// Record a forwarded key symbol event.
+    pub fn update_keysym(&mut self, keysym: i32, state: KeyState) {
+        if let Some(modifier) = Modifier::from_keysym(keysym) {
+            let side = modifier.keysyms().iter().position(|k| *k == keysym);
+            self.set(modifier.keycodes()[side.unwrap_or_default()], state);
+        }
+    }
+
+    /// The currently active modifiers.
+    pub fn modifiers(&self) -> BitFlags<Modifier> {
+        self.pressed
+            .iter()
+            .filter_map(|keycode| Modifier::from_keycode(*keycode))
+            .collect()
+    }
+
+    /// Whether `modifier` is currently active.
+    pub fn is_active(&self, modifier: Modifier) -> bool {
+        self.modifiers().contains(modifier)
+    }
+
+    /// The key code events that bring the remote side to exactly the
+    /// `modifiers` state before injecting a key and back to the current state
+    /// afterwards.
+    pub fn wrap(&self, modifiers: impl Into<BitFlags<Modifier>>) -> ModifierSequence {
+        let modifiers = modifiers.into();
+        let mut sequence = ModifierSequence::default();
+        for keycode in &self.pressed {
+            let held = matches!(Modifier::from_keycode(*keycode), Some(m) if modifiers.contains(m));
+            if !held {
+                sequence.before.push((*keycode, KeyState::Released));
+                sequence.after.push((*keycode, KeyState::Pressed));
+            }
+        }
+        for modifier in (modifiers & !self.modifiers()).iter() {
+            let keycode = modifier.keycodes()[0];
+            sequence.before.push((keycode, KeyState::Pressed));
+            sequence.after.push((keycode, KeyState::Released));
+        }
+        sequence.after.reverse();
+        sequence
+    }
+
+    /// Forget every pressed modifier, returning the key code events that
+    /// release them on the remote side.
+    pub fn release_all(&mut self) -> Vec<(i32, KeyState)> {
+        std::mem::take(&mut self.pressed)
+            .into_iter()
+            .map(|keycode| (keycode, KeyState::Released))
+            .collect()
+    }
+
+    fn set(&mut self, keycode: i32, state: KeyState) {
+        match state {
+            KeyState::Pressed => self.pressed.insert(keycode),
+            KeyState::Released => self.pressed.remove(&keycode),
+        };
+    }
+}
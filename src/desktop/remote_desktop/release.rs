@@ -0,0 +1,155 @@
+use std::{fmt, time::Duration};
+
+use futures::{future, Stream, StreamExt};
+use zbus::zvariant::OwnedObjectPath;
+
+use super::RemoteDesktopSession;
+use crate::{
+    desktop::{
+        global_shortcuts::{GlobalShortcutsProxy, GlobalShortcutsSession, NewShortcut, Shortcut},
+        SessionGuard,
+    },
+    Error, WindowIdentifier,
+};
+
+/// The release shortcut of an [`InputRelease`] was pressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputReleased {
+    timestamp: Duration,
+}
+
+impl InputReleased {
+    /// The time the shortcut was pressed, with a millisecond granularity.
+    pub fn timestamp(&self) -> Duration {
+        self.timestamp
+    }
+}
+
+/// A global shortcut giving the input back to the user of the host while a
+/// remote desktop session is forwarding events.
+///
+/// The shortcut is bound through the GlobalShortcuts portal, it is triggered
+/// even if the application grabbed the keyboard or isn't focused. The
+/// application decides what releasing the input means, usually to stop
+/// forwarding the events and to release the held keys, see
+/// [`ModifierTracker`](super::ModifierTracker).
+///
+/// The shortcut is unbound once the [`InputRelease`] is closed or dropped.
+///
+/// ```rust,no_run
+/// use ashpd::{
+///     desktop::{
+///         global_shortcuts::NewShortcut,
+///         remote_desktop::{InputRelease, RemoteDesktopSession},
+///     },
+///     WindowIdentifier,
+/// };
+/// use futures::StreamExt;
+///
+/// async fn run(session: &RemoteDesktopSession<'static>) -> ashpd::Result<()> {
+///     let shortcut = NewShortcut::new("release-input", "Release the keyboard and mouse")
+///         .with_preferred_trigger("CTRL+ALT+Escape");
+///     let release = InputRelease::bind(
+///         session.inner().connection(),
+///         session,
+///         shortcut,
+///         &WindowIdentifier::default(),
+///     )
+///     .await?;
+///     println!("Press {} to get the input back", release.shortcut().trigger_description());
+///
+///     let mut released = release.receive_released().await?;
+///     while released.next().await.is_some() {
+///         println!("Stopped forwarding the input");
+///     }
+///     // The remote desktop session was closed.
+///     release.close().await
+/// }
+/// ```
+pub struct InputRelease<'a> {
+    proxy: GlobalShortcutsProxy<'a>,
+    session: GlobalShortcutsSession<'a>,
+    remote: RemoteDesktopSession<'a>,
+    shortcut: Shortcut,
+    guard: SessionGuard,
+}
+
+impl<'a> InputRelease<'a> {
+    /// Bind `shortcut` for the lifetime of the `remote` session, the portal
+    /// may let the user review and change its trigger.
+    ///
+    /// Fails with [`Error::Unsupported`] if the portal didn't bind the
+    /// shortcut.
+    pub async fn bind(
+        connection: &zbus::Connection,
+        remote: &RemoteDesktopSession<'a>,
+        shortcut: NewShortcut,
+        identifier: &WindowIdentifier,
+    ) -> Result<InputRelease<'a>, Error> {
+        let proxy = GlobalShortcutsProxy::new(connection).await?;
+        let session = proxy.create_session().await?;
+        let guard = SessionGuard::new(&session);
+        let bound = proxy
+            .bind_shortcuts(&session, std::slice::from_ref(&shortcut), identifier)
+            .await?;
+        let shortcut = bound
+            .shortcuts()
+            .iter()
+            .find(|bound| bound.id() == shortcut.id())
+            .cloned()
+            .ok_or(Error::Unsupported)?;
+        Ok(Self {
+            proxy,
+            session,
+            remote: remote.clone(),
+            shortcut,
+            guard,
+        })
+    }
+
+    /// The shortcut as bound by the portal, with the trigger chosen by the
+    /// user.
+    pub fn shortcut(&self) -> &Shortcut {
+        &self.shortcut
+    }
+
+    /// The global shortcuts session the shortcut is bound to.
+    pub fn session(&self) -> &GlobalShortcutsSession<'a> {
+        &self.session
+    }
+
+    /// Listen to the presses of the shortcut. The stream ends once the remote
+    /// desktop session is closed.
+    pub async fn receive_released(
+        &self,
+    ) -> Result<impl Stream<Item = InputReleased> + Unpin + 'a, Error> {
+        let path = OwnedObjectPath::from(self.session.inner().path().to_owned());
+        let id = self.shortcut.id().to_owned();
+        let closed = self.remote.receive_closed().await?;
+        let activated = self.proxy.receive_activated().await?;
+        Ok(activated
+            .filter_map(move |activation| {
+                let released = activation.session_handle().as_str() == path.as_str()
+                    && activation.shortcut_id() == id;
+                future::ready(released.then(|| InputReleased {
+                    timestamp: activation.timestamp(),
+                }))
+            })
+            .take_until(closed.into_future()))
+    }
+
+    /// Close the global shortcuts session, unbinding the shortcut.
+    pub async fn close(self) -> Result<(), Error> {
+        self.guard.close().await
+    }
+}
+
+impl fmt::Debug for InputRelease<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InputRelease")
+            .field("session", &self.session)
+            .field("remote", &self.remote)
+            .field("shortcut", &self.shortcut)
+            .finish()
+    }
+}
@@ -0,0 +1,129 @@
+use std::time::{Duration, Instant};
+
+use super::{InputEvent, KeyState, Modifier};
+
+#[derive(Debug, Clone)]
+/// Re-emits the press of a held key, the portal doesn't repeat them on its
+/// own.
+///
+/// Like a local keyboard only the last pressed key is repeated, the modifiers
+/// never are. The repeater doesn't wait by itself, sleep for
+/// [`KeyRepeater::next_deadline`] with the timer of your async runtime and
+/// send the events returned by [`KeyRepeater::drain`].
+///
+/// ```rust
+/// use std::time::{Duration, Instant};
+///
+/// use ashpd::desktop::remote_desktop::{InputEvent, KeyRepeater, KeyState};
+///
+/// let mut repeater = KeyRepeater::new(Duration::from_millis(500), 25);
+/// let start = Instant::now();
+///
+/// repeater.push(&InputEvent::KeyboardKeycode { keycode: 30, state: KeyState::Pressed }, start);
+/// assert_eq!(repeater.next_deadline(start), Some(Duration::from_millis(500)));
+/// assert!(repeater.drain(start).is_empty());
+///
+/// let events = repeater.drain(start + Duration::from_millis(500));
+/// assert_eq!(events, vec![InputEvent::KeyboardKeycode { keycode: 30, state: KeyState::Pressed }]);
+/// assert_eq!(
+///     repeater.next_deadline(start + Duration::from_millis(500)),
+///     Some(Duration::from_millis(40))
+/// );
+///
+/// repeater.push(&InputEvent::KeyboardKeycode { keycode: 30, state: KeyState::Released }, start);
+/// assert_eq!(repeater.next_deadline(start), None);
+/// ```
+pub struct KeyRepeater {
+    delay: Duration,
+    interval: Option<Duration>,
+    held: Option<(i32, Instant)>,
+}
+
+impl Default for KeyRepeater {
+    /// A delay of 500 milliseconds and 30 repeats per second, the defaults of
+    /// GNOME.
+    fn default() -> Self {
+        Self::new(Duration::from_millis(500), 30)
+    }
+}
+
+impl KeyRepeater {
+    /// Create a new repeater starting to repeat a key after it was held for
+    /// `delay`, then `rate` times per second. A `rate` of `0` disables the
+    /// repeat.
+    pub fn new(delay: Duration, rate: u32) -> Self {
+        Self {
+            delay,
+            interval: (rate > 0).then(|| Duration::from_secs(1) / rate),
+            held: None,
+        }
+    }
+
+    /// Track an event forwarded to the session at `now`, only the key code
+    /// ones are taken into account.
+    pub fn push(&mut self, event: &InputEvent, now: Instant) {
+        match event {
+            InputEvent::KeyboardKeycode {
+                keycode,
+                state: KeyState::Pressed,
+            } => self.press(*keycode, now),
+            InputEvent::KeyboardKeycode {
+                keycode,
+                state: KeyState::Released,
+            } => self.release(*keycode),
+            _ => (),
+        }
+    }
+
+    /// Start repeating `keycode` if it isn't a modifier.
+    pub fn press(&mut self, keycode: i32, now: Instant) {
+        if self.interval.is_none() || Modifier::from_keycode(keycode).is_some() {
+            return;
+        }
+        self.held = Some((keycode, now + self.delay));
+    }
+
+    /// Stop repeating `keycode`.
+    pub fn release(&mut self, keycode: i32) {
+        if matches!(self.held, Some((held, _)) if held == keycode) {
+            self.held = None;
+        }
+    }
+
+    /// Stop repeating the held key, returning it.
+    pub fn release_all(&mut self) -> Option<i32> {
+        self.held.take().map(|(keycode, _)| keycode)
+    }
+
+    /// The key being repeated.
+    pub fn held(&self) -> Option<i32> {
+        self.held.map(|(keycode, _)| keycode)
+    }
+
+    /// The repeated presses due at `now`.
+    ///
+    /// At most one press is returned, the ones missed because the repeater
+    /// wasn't drained in time are dropped instead of being sent in a burst.
+    pub fn drain(&mut self, now: Instant) -> Vec<InputEvent> {
+        match (&mut self.held, self.interval) {
+            (Some((keycode, next)), Some(interval)) if *next <= now => {
+                *next += interval;
+                if *next <= now {
+                    *next = now + interval;
+                }
+                vec![InputEvent::KeyboardKeycode {
+                    keycode: *keycode,
+                    state: KeyState::Pressed,
+                }]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// How long to wait from `now` before the next repeat, [`None`] if no key
+    /// is held.
+    pub fn next_deadline(&self, now: Instant) -> Option<Duration> {
+        self.held
+            .map(|(_, next)| next.saturating_duration_since(now))
+    }
+}
@@ -0,0 +1,94 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    hash::Hash,
+};
+
+#[derive(Debug, Clone)]
+/// Maps the touch points identifiers of the platform to the slots expected by
+/// [`RemoteDesktopProxy::notify_touch_down`](super::RemoteDesktopProxy::notify_touch_down)
+/// and friends.
+///
+/// A slot stays attached to a touch point until it is released, the lowest
+/// free slot is handed out first.
+///
+/// ```rust
+/// use ashpd::desktop::remote_desktop::SlotAllocator;
+///
+/// let mut slots = SlotAllocator::new();
+/// assert_eq!(slots.acquire(1234), 0);
+/// assert_eq!(slots.acquire(5678), 1);
+/// assert_eq!(slots.acquire(1234), 0);
+/// assert_eq!(slots.release(&1234), Some(0));
+/// assert_eq!(slots.acquire(9012), 0);
+/// ```
+pub struct SlotAllocator<K = u64> {
+    slots: HashMap<K, u32>,
+    free: BTreeSet<u32>,
+    next: u32,
+}
+
+impl<K> Default for SlotAllocator<K> {
+    fn default() -> Self {
+        Self {
+            slots: HashMap::new(),
+            free: BTreeSet::new(),
+            next: 0,
+        }
+    }
+}
+
+impl<K: Hash + Eq> SlotAllocator<K> {
+    /// Create a new allocator with no slot in use.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The slot of the touch point `id`, allocating one if needed.
+    pub fn acquire(&mut self, id: K) -> u32 {
+        if let Some(slot) = self.slots.get(&id) {
+            return *slot;
+        }
+        let slot = match self.free.iter().next().copied() {
+            Some(slot) => {
+                self.free.remove(&slot);
+                slot
+            }
+            None => {
+                self.next += 1;
+                self.next - 1
+            }
+        };
+        self.slots.insert(id, slot);
+        slot
+    }
+
+    /// The slot of the touch point `id`, if it has one.
+    pub fn get(&self, id: &K) -> Option<u32> {
+        self.slots.get(id).copied()
+    }
+
+    /// Release the slot of the touch point `id`, returning it.
+    pub fn release(&mut self, id: &K) -> Option<u32> {
+        let slot = self.slots.remove(id)?;
+        self.free.insert(slot);
+        Some(slot)
+    }
+
+    /// Release all the slots, returning the ones that were in use.
+    pub fn release_all(&mut self) -> Vec<u32> {
+        let slots = self.slots.drain().map(|(_, slot)| slot).collect::<Vec<_>>();
+        self.free.clear();
+        self.next = 0;
+        slots
+    }
+
+    /// The number of slots in use.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Whether no slot is in use.
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+}
@@ -0,0 +1,285 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use futures::{Stream, StreamExt};
+
+use super::InputEvent;
+use crate::{desktop::power_profile_monitor::PowerProfileMonitorProxy, Error};
+
+#[derive(Debug, Clone)]
+/// Limits the rate of the input events forwarded to a remote desktop session.
+///
+/// Events are queued with [`InputThrottle::push`], successive pointer or
+/// touch motions are merged together while waiting. [`InputThrottle::drain`]
+/// hands out at most `max_events_per_second` events per second, ready to be
+/// sent with [`ActiveSession::notify_all`](super::ActiveSession::notify_all).
+///
+/// While the power saver is enabled, see
+/// [`InputThrottle::follow_power_saver`] and
+/// [`InputThrottle::set_power_saver`], the rate drops to
+/// [`InputThrottle::power_saver_rate`], so that more motions are merged
+/// together.
+///
+/// ```rust,no_run
+/// use std::time::Instant;
+///
+/// use ashpd::desktop::remote_desktop::{ActiveSession, InputEvent, InputThrottle};
+///
+/// async fn forward(
+///     session: &ActiveSession<'_>,
+///     throttle: &mut InputThrottle,
+///     events: Vec<InputEvent>,
+/// ) -> ashpd::Result<()> {
+///     events.into_iter().for_each(|event| throttle.push(event));
+///     session.notify_all(throttle.drain(Instant::now())).await
+/// }
+/// ```
+pub struct InputThrottle {
+    max_events_per_second: u32,
+    power_saver_rate: u32,
+    // Updated by the task spawned in `follow_power_saver`.
+    power_saver: Arc<AtomicBool>,
+    power_saver_override: Option<bool>,
+    tokens: f64,
+    last_refill: Option<Instant>,
+    queue: VecDeque<InputEvent>,
+}
+
+impl InputThrottle {
+    /// Create a new throttle letting through at most `max_events_per_second`
+    /// events per second, `0` only merges the motion events without limiting
+    /// the rate.
+    ///
+    /// The rate of the power saver defaults to half of it, or 60 events per
+    /// second without limit.
+    pub fn new(max_events_per_second: u32) -> Self {
+        let power_saver_rate = match max_events_per_second {
+            0 => 60,
+            max => (max / 2).max(1),
+        };
+        Self {
+            max_events_per_second,
+            power_saver_rate,
+            power_saver: Arc::new(AtomicBool::new(false)),
+            power_saver_override: None,
+            tokens: max_events_per_second as f64,
+            last_refill: None,
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Sets the rate used while the power saver is enabled, `0` doesn't limit
+    /// it.
+    #[must_use]
+    pub fn power_saver_rate(mut self, max_events_per_second: u32) -> Self {
+        self.power_saver_rate = max_events_per_second;
+        self
+    }
+
+    /// Use the power saver rate while the power saver of `monitor` is
+    /// enabled, the changes are followed from a task spawned on the executor
+    /// of its connection.
+    ///
+    /// ```rust,no_run
+    /// use std::time::Instant;
+    ///
+    /// use ashpd::desktop::{
+    ///     power_profile_monitor::PowerProfileMonitorProxy,
+    ///     remote_desktop::{ActiveSession, InputEvent, InputThrottle},
+    /// };
+    /// use futures::{channel::mpsc::Receiver, StreamExt};
+    ///
+    /// async fn forward(
+    ///     session: &ActiveSession<'_>,
+    ///     mut events: Receiver<InputEvent>,
+    /// ) -> ashpd::Result<()> {
+    ///     let connection = zbus::Connection::session().await?;
+    ///     let monitor = PowerProfileMonitorProxy::new(&connection).await?;
+    ///     let mut throttle = InputThrottle::new(120)
+    ///         .power_saver_rate(30)
+    ///         .follow_power_saver(&monitor)
+    ///         .await?;
+    ///     while let Some(event) = events.next().await {
+    ///         throttle.push(event);
+    ///         session.notify_all(throttle.drain(Instant::now())).await?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn follow_power_saver(
+        self,
+        monitor: &PowerProfileMonitorProxy<'static>,
+    ) -> Result<Self, Error> {
+        let changes = monitor.receive_power_saver_changed().await?;
+        self.power_saver
+            .store(monitor.is_enabled().await?, Ordering::SeqCst);
+        monitor
+            .inner()
+            .connection()
+            .executor()
+            .spawn(self.follow(changes))
+            .detach();
+        Ok(self)
+    }
+
+    // Applies the power saver `changes` until the end of the stream, or the
+    // first change after the throttle and its clones are dropped.
+    fn follow(&self, changes: impl Stream<Item = bool>) -> impl Future<Output = ()> {
+        let power_saver = Arc::downgrade(&self.power_saver);
+        async move {
+            futures::pin_mut!(changes);
+            while let Some(enabled) = changes.next().await {
+                match power_saver.upgrade() {
+                    Some(power_saver) => power_saver.store(enabled, Ordering::SeqCst),
+                    None => break,
+                }
+            }
+        }
+    }
+
+    /// Enable or disable the power saver rate, overriding the state followed
+    /// with [`InputThrottle::follow_power_saver`].
+    pub fn set_power_saver(&mut self, enabled: bool) {
+        self.power_saver_override = Some(enabled);
+        let max = self.rate() as f64;
+        self.tokens = self.tokens.min(max);
+    }
+
+    /// Whether the power saver rate is used.
+    pub fn is_power_saver(&self) -> bool {
+        self.power_saver_override
+            .unwrap_or_else(|| self.power_saver.load(Ordering::SeqCst))
+    }
+
+    /// Queue an event, merging it with the last queued one when possible.
+    pub fn push(&mut self, event: InputEvent) {
+        if let Some(last) = self.queue.back_mut() {
+            if last.coalesce(&event) {
+                return;
+            }
+        }
+        self.queue.push_back(event);
+    }
+
+    /// The events that can be sent at `now`, in the order they were queued.
+    pub fn drain(&mut self, now: Instant) -> Vec<InputEvent> {
+        if self.rate() == 0 {
+            return self.queue.drain(..).collect();
+        }
+        self.refill(now);
+        let count = (self.tokens as usize).min(self.queue.len());
+        self.tokens -= count as f64;
+        self.queue.drain(..count).collect()
+    }
+
+    /// The number of events waiting to be sent.
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// How long to wait before the next queued event can be sent, [`None`] if
+    /// there are no queued events.
+    pub fn next_deadline(&self) -> Option<Duration> {
+        if self.queue.is_empty() {
+            None
+        } else if self.tokens >= 1.0 || self.rate() == 0 {
+            Some(Duration::ZERO)
+        } else {
+            Some(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.rate() as f64,
+            ))
+        }
+    }
+
+    // The events per second currently let through.
+    fn rate(&self) -> u32 {
+        if self.is_power_saver() {
+            self.power_saver_rate
+        } else {
+            self.max_events_per_second
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let max = self.rate() as f64;
+        if let Some(last) = self.last_refill {
+            let elapsed = now.saturating_duration_since(last).as_secs_f64();
+            self.tokens = (self.tokens + elapsed * max).min(max);
+        }
+        self.last_refill = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use futures::FutureExt;
+
+    use super::InputThrottle;
+    use crate::desktop::remote_desktop::{InputEvent, KeyState};
+
+    #[test]
+    fn power_saver() {
+        let start = Instant::now();
+        let mut throttle = InputThrottle::new(100).power_saver_rate(10);
+        throttle.set_power_saver(true);
+        assert!(throttle.is_power_saver());
+        for i in 0..20 {
+            throttle.push(InputEvent::KeyboardKeycode {
+                keycode: i,
+                state: KeyState::Pressed,
+            });
+        }
+        assert_eq!(throttle.drain(start).len(), 10);
+        assert_eq!(throttle.next_deadline(), Some(Duration::from_secs_f64(0.1)));
+        assert_eq!(throttle.drain(start + Duration::from_millis(500)).len(), 5);
+
+        throttle.set_power_saver(false);
+        assert_eq!(throttle.drain(start + Duration::from_millis(550)).len(), 5);
+    }
+
+    #[test]
+    fn follow_power_saver() {
+        let mut throttle = InputThrottle::new(100);
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        let mut follow = Box::pin(throttle.follow(receiver));
+        sender.unbounded_send(true).unwrap();
+        assert!(follow.as_mut().now_or_never().is_none());
+        assert!(throttle.is_power_saver());
+        assert_eq!(throttle.rate(), 50);
+
+        // The last change is kept once the stream closes.
+        sender.unbounded_send(false).unwrap();
+        sender.close_channel();
+        assert!(follow.as_mut().now_or_never().is_some());
+        assert!(!throttle.is_power_saver());
+        assert_eq!(throttle.rate(), 100);
+
+        throttle.set_power_saver(true);
+        assert_eq!(throttle.rate(), 50);
+
+        // The changes stop being followed once the throttle and its clones
+        // are dropped, even though the sender is still there.
+        let throttle = InputThrottle::new(100);
+        let clone = throttle.clone();
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        let mut follow = Box::pin(throttle.follow(receiver));
+        drop(throttle);
+        sender.unbounded_send(true).unwrap();
+        assert!(follow.as_mut().now_or_never().is_none());
+        assert!(clone.is_power_saver());
+
+        drop(clone);
+        sender.unbounded_send(false).unwrap();
+        assert!(follow.as_mut().now_or_never().is_some());
+        assert!(sender.is_closed());
+    }
+}
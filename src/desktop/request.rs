@@ -1,19 +1,23 @@
 use std::{
     collections::HashMap,
     fmt::{self, Debug},
+    future::{Future, IntoFuture},
     marker::PhantomData,
+    sync::Arc,
 };
 
+use futures::future::BoxFuture;
+
 use serde::{
     de::{self, Error as SeError, Visitor},
     Deserialize, Deserializer, Serialize,
 };
-use zbus::zvariant::{ObjectPath, OwnedValue, Signature, Type};
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Signature, Type};
 
-use super::DESTINATION;
+use super::{DESTINATION, INTERFACE_REQUEST};
 use crate::{
-    desktop::HandleToken,
     helpers::{call_method, receive_signal},
+    window_identifier::ExportGuard,
     Error,
 };
 
@@ -35,7 +39,7 @@ where
     T: for<'de> Deserialize<'de> + Type,
 {
     fn signature() -> Signature<'static> {
-        <(ResponseType, HashMap<&str, OwnedValue>)>::signature()
+        <(ResponseCode, HashMap<&str, OwnedValue>)>::signature()
     }
 }
 
@@ -47,13 +51,13 @@ where
     where
         D: Deserializer<'de>,
     {
-        struct ResponseVisitor<T>(PhantomData<fn() -> (ResponseType, T)>);
+        struct ResponseVisitor<T>(PhantomData<fn() -> (ResponseCode, T)>);
 
         impl<'de, T> Visitor<'de> for ResponseVisitor<T>
         where
             T: Deserialize<'de>,
         {
-            type Value = (ResponseType, Option<T>);
+            type Value = (ResponseCode, Option<T>);
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
                 write!(
@@ -66,38 +70,82 @@ where
             where
                 A: de::SeqAccess<'de>,
             {
-                let type_: ResponseType = seq.next_element()?.ok_or_else(|| A::Error::custom(
+                let code: ResponseCode = seq.next_element()?.ok_or_else(|| A::Error::custom(
                     "Failed to deserialize the response. Expected a numeric (u) value as the first item of the returned tuple",
                 ))?;
-                if type_ == ResponseType::Success {
+                if code == ResponseCode::Success {
                     let data: T = seq.next_element()?.ok_or_else(|| A::Error::custom(
                         "Failed to deserialize the response. Expected a vardict (a{sv}) with the returned results",
                     ))?;
-                    Ok((type_, Some(data)))
+                    Ok((code, Some(data)))
                 } else {
-                    Ok((type_, None))
+                    Ok((code, None))
                 }
             }
         }
 
         let visitor = ResponseVisitor::<T>(PhantomData);
-        let response: (ResponseType, Option<T>) = deserializer.deserialize_tuple(2, visitor)?;
+        let response: (ResponseCode, Option<T>) = deserializer.deserialize_tuple(2, visitor)?;
         Ok(response.into())
     }
 }
 
 #[doc(hidden)]
-impl<T> From<(ResponseType, Option<T>)> for Response<T>
+impl<T> From<(ResponseCode, Option<T>)> for Response<T>
 where
     T: for<'de> Deserialize<'de> + Type,
 {
-    fn from(f: (ResponseType, Option<T>)) -> Self {
+    fn from(f: (ResponseCode, Option<T>)) -> Self {
         match f.0 {
-            ResponseType::Success => {
+            ResponseCode::Success => {
                 Response::Ok(f.1.expect("Expected a valid response, found nothing."))
             }
-            ResponseType::Cancelled => Response::Err(ResponseError::Cancelled),
-            ResponseType::Other => Response::Err(ResponseError::Other),
+            ResponseCode::Cancelled => Response::Err(ResponseError::Cancelled),
+            ResponseCode::Other => Response::Err(ResponseError::Other),
+        }
+    }
+}
+
+impl<T> Serialize for Response<T>
+where
+    T: for<'de> Deserialize<'de> + Serialize + Type,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Ok(results) => (ResponseCode::Success, results).serialize(serializer),
+            Self::Err(err) => {
+                (ResponseCode::from(*err), HashMap::<&str, OwnedValue>::new()).serialize(serializer)
+            }
+        }
+    }
+}
+
+impl<T> Response<T>
+where
+    T: for<'de> Deserialize<'de> + Type,
+{
+    // The status of the response, the only part of it some backends reply
+    // with.
+    #[cfg(feature = "backend")]
+    pub(crate) fn code(&self) -> ResponseCode {
+        match self {
+            Self::Ok(_) => ResponseCode::Success,
+            Self::Err(err) => ResponseCode::from(*err),
+        }
+    }
+}
+
+impl<T> From<Result<T, ResponseError>> for Response<T>
+where
+    T: for<'de> Deserialize<'de> + Type,
+{
+    fn from(result: Result<T, ResponseError>) -> Self {
+        match result {
+            Ok(results) => Self::Ok(results),
+            Err(err) => Self::Err(err),
         }
     }
 }
@@ -123,20 +171,31 @@ pub enum ResponseError {
     Other,
 }
 
+impl ResponseError {
+    /// The status code the portal replied with.
+    pub fn code(&self) -> ResponseCode {
+        ResponseCode::from(*self)
+    }
+}
+
 impl std::error::Error for ResponseError {}
 
 impl std::fmt::Display for ResponseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Cancelled => f.write_str("Cancelled"),
-            Self::Other => f.write_str("Other"),
+            Self::Cancelled => f.write_str("Cancelled by the user"),
+            Self::Other => f.write_str("Ended in some other way"),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Debug, Type)]
-#[doc(hidden)]
-enum ResponseType {
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Hash, Debug, Type)]
+/// The status a portal replies with as the first element of the `Response`
+/// signal of a request.
+///
+/// Useful to tell apart a request the user dismissed from one that failed,
+/// see [`ResponseCode::of`].
+pub enum ResponseCode {
     /// Success, the request is carried out.
     Success = 0,
     /// The user cancelled the interaction.
@@ -145,8 +204,35 @@ enum ResponseType {
     Other = 2,
 }
 
-#[doc(hidden)]
-impl From<ResponseError> for ResponseType {
+impl ResponseCode {
+    /// The status code of the result of a portal request, `None` if the
+    /// request failed before the portal replied.
+    ///
+    /// ```rust,no_run
+    /// use ashpd::{
+    ///     desktop::{screenshot, ResponseCode},
+    ///     WindowIdentifier,
+    /// };
+    ///
+    /// async fn run() {
+    ///     let result = screenshot::take(&WindowIdentifier::default(), true, false).await;
+    ///     match ResponseCode::of(&result) {
+    ///         Some(ResponseCode::Success) => println!("Screenshot taken"),
+    ///         Some(ResponseCode::Cancelled) => println!("Dismissed by the user"),
+    ///         Some(ResponseCode::Other) => println!("The portal failed"),
+    ///         None => println!("The request failed: {}", result.unwrap_err()),
+    ///     }
+    /// }
+    /// ```
+    pub fn of<T>(result: &Result<T, Error>) -> Option<Self> {
+        match result {
+            Ok(_) => Some(Self::Success),
+            Err(err) => err.response_code(),
+        }
+    }
+}
+
+impl From<ResponseError> for ResponseCode {
     fn from(err: ResponseError) -> Self {
         match err {
             ResponseError::Other => Self::Other,
@@ -155,6 +241,267 @@ impl From<ResponseError> for ResponseType {
     }
 }
 
+/// A portal request that wasn't sent yet, returned by the methods that wait
+/// for the user, [`FileChooserProxy::open_file`](crate::desktop::file_chooser::FileChooserProxy::open_file)
+/// for example.
+///
+/// Awaiting it sends the request and waits for its response. It can be sent
+/// in two phases instead, to know when the portal gets involved:
+///
+/// - [`PortalRequest::prepare`] computes the handle of the request, without
+///   any call on the bus. Dropping a request before it is sent has no effect.
+/// - [`PreparedRequest::send`] calls the portal and waits for the response.
+///   Dropping its future once polled, when another branch of a `select!`
+///   completes first for example, closes the request and its dialog.
+///
+/// That close is a task spawned on the executor of the connection, with a
+/// connection built with [`zbus::ConnectionBuilder::internal_executor`] set to
+/// `false` it only runs once the application ticks that executor.
+/// [`close_request`] closes the request from the caller instead.
+///
+/// ```rust,no_run
+/// use ashpd::{desktop::file_chooser::{FileChooserProxy, OpenFileOptions}, WindowIdentifier};
+/// use futures::{channel::oneshot, FutureExt};
+///
+/// async fn run(proxy: &FileChooserProxy<'_>, cancel: oneshot::Receiver<()>) -> ashpd::Result<()> {
+///     let identifier = WindowIdentifier::default();
+///     let request = proxy
+///         .open_file(&identifier, "Open a file", OpenFileOptions::default())
+///         .prepare()?;
+///     println!("Opening {}", request.handle().as_str());
+///     futures::select! {
+///         files = request.send().fuse() => println!("{:#?}", files?),
+///         // The dialog is closed.
+///         _ = cancel.fuse() => (),
+///     }
+///     Ok(())
+/// }
+/// ```
+#[must_use = "requests do nothing unless awaited or sent"]
+pub struct PortalRequest<'p, R> {
+    handle: Result<OwnedObjectPath, Error>,
+    send: BoxFuture<'p, Result<R, Error>>,
+}
+
+impl<'p, R: Send + 'p> PortalRequest<'p, R> {
+    // `send` is only polled once the request is sent, it must close the
+    // request at `handle` if dropped before its response.
+    pub(crate) fn new(
+        handle: Result<OwnedObjectPath, Error>,
+        send: impl Future<Output = Result<R, Error>> + Send + 'p,
+    ) -> Self {
+        Self {
+            handle,
+            send: Box::pin(send),
+        }
+    }
+
+    // A request whose arguments are invalid, failing to be prepared.
+    pub(crate) fn invalid(err: Error) -> Self {
+        Self::new(Err(err), futures::future::pending())
+    }
+
+    /// Compute the handle of the request, without sending it.
+    pub fn prepare(self) -> Result<PreparedRequest<'p, R>, Error> {
+        Ok(PreparedRequest {
+            handle: self.handle?,
+            send: self.send,
+        })
+    }
+
+    // Transform the response, once received.
+    pub(crate) fn map<T: Send + 'p>(
+        self,
+        f: impl FnOnce(R) -> Result<T, Error> + Send + 'p,
+    ) -> PortalRequest<'p, T> {
+        self.map_result(|response| response.and_then(f))
+    }
+
+    // Same as `map`, for the failures of the request as well.
+    pub(crate) fn map_result<T: Send + 'p>(
+        self,
+        f: impl FnOnce(Result<R, Error>) -> Result<T, Error> + Send + 'p,
+    ) -> PortalRequest<'p, T> {
+        let send = self.send;
+        PortalRequest::new(self.handle, async move { f(send.await) })
+    }
+
+    // Same as `map_result`, with an asynchronous transformation.
+    #[cfg(feature = "fallback-dialogs")]
+    pub(crate) fn then_result<T: Send + 'p, F>(
+        self,
+        f: impl FnOnce(Result<R, Error>) -> F + Send + 'p,
+    ) -> PortalRequest<'p, T>
+    where
+        F: Future<Output = Result<T, Error>> + Send + 'p,
+    {
+        let send = self.send;
+        PortalRequest::new(self.handle, async move { f(send.await).await })
+    }
+
+    // Same as `map`, with an asynchronous transformation.
+    pub(crate) fn then<T: Send + 'p, F>(
+        self,
+        f: impl FnOnce(R) -> F + Send + 'p,
+    ) -> PortalRequest<'p, T>
+    where
+        F: Future<Output = Result<T, Error>> + Send + 'p,
+    {
+        let send = self.send;
+        PortalRequest::new(self.handle, async move { f(send.await?).await })
+    }
+}
+
+impl<'p, R: Send + 'p> IntoFuture for PortalRequest<'p, R> {
+    type Output = Result<R, Error>;
+    type IntoFuture = BoxFuture<'p, Result<R, Error>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        match self.prepare() {
+            Ok(request) => request.send,
+            Err(err) => Box::pin(futures::future::ready(Err(err))),
+        }
+    }
+}
+
+impl<R> Debug for PortalRequest<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PortalRequest")
+            .field(&self.handle.as_ref().map(|handle| handle.as_str()))
+            .finish()
+    }
+}
+
+/// A [`PortalRequest`] whose handle is known, ready to be sent.
+#[must_use = "requests do nothing unless sent"]
+pub struct PreparedRequest<'p, R> {
+    handle: OwnedObjectPath,
+    send: BoxFuture<'p, Result<R, Error>>,
+}
+
+impl<'p, R> PreparedRequest<'p, R> {
+    /// The path of the `org.freedesktop.portal.Request` object the portal
+    /// will export once the request is sent.
+    pub fn handle(&self) -> &ObjectPath<'_> {
+        &self.handle
+    }
+
+    /// Send the request and wait for its response.
+    ///
+    /// The request is closed if the future is dropped after being polled and
+    /// before the response arrived.
+    pub fn send(self) -> BoxFuture<'p, Result<R, Error>> {
+        self.send
+    }
+}
+
+impl<R> Debug for PreparedRequest<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PreparedRequest")
+            .field(&self.handle.as_str())
+            .finish()
+    }
+}
+
+/// Close the request at `handle`, see [`PreparedRequest::handle`], and end its
+/// user interaction. The future of [`PreparedRequest::send`] then never
+/// completes, drop it.
+///
+/// A request already closed or whose response arrived isn't an error.
+///
+/// ```rust,no_run
+/// use ashpd::{
+///     desktop::{
+///         close_request,
+///         file_chooser::{FileChooserProxy, OpenFileOptions},
+///     },
+///     WindowIdentifier,
+/// };
+/// use futures::{channel::oneshot, FutureExt};
+///
+/// async fn run(proxy: &FileChooserProxy<'_>, cancel: oneshot::Receiver<()>) -> ashpd::Result<()> {
+///     let identifier = WindowIdentifier::default();
+///     let request = proxy
+///         .open_file(&identifier, "Open a file", OpenFileOptions::default())
+///         .prepare()?;
+///     let handle = request.handle().to_owned();
+///     futures::select! {
+///         files = request.send().fuse() => println!("{:#?}", files?),
+///         _ = cancel.fuse() => close_request(proxy.inner().connection(), &handle).await?,
+///     }
+///     Ok(())
+/// }
+/// ```
+pub async fn close_request(
+    connection: &zbus::Connection,
+    handle: &ObjectPath<'_>,
+) -> Result<(), Error> {
+    let request = RequestProxy::new(connection, handle.clone()).await?;
+    match request.close().await {
+        Err(err) if is_unknown_object(&err) => Ok(()),
+        result => result,
+    }
+}
+
+fn is_unknown_object(err: &Error) -> bool {
+    match err.root() {
+        Error::Zbus(zbus::Error::FDO(err)) => {
+            matches!(**err, zbus::fdo::Error::UnknownObject(_))
+        }
+        Error::Zbus(zbus::Error::MethodError(name, _, _)) => {
+            name.as_str() == "org.freedesktop.DBus.Error.UnknownObject"
+        }
+        _ => false,
+    }
+}
+
+// Closes a request that was sent once dropped, unless its response arrived.
+// See `PortalRequest` for its dependency on the executor of the connection.
+pub(crate) struct RequestGuard {
+    connection: zbus::Connection,
+    handle: Option<OwnedObjectPath>,
+}
+
+impl RequestGuard {
+    pub(crate) fn new(connection: &zbus::Connection, handle: OwnedObjectPath) -> Self {
+        Self {
+            connection: connection.clone(),
+            handle: Some(handle),
+        }
+    }
+
+    // The request ended on its own, there is nothing to close.
+    pub(crate) fn disarm(mut self) {
+        self.handle = None;
+    }
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            let connection = self.connection.clone();
+            self.connection
+                .executor()
+                .spawn(async move {
+                    let result = connection
+                        .call_method(
+                            Some(DESTINATION),
+                            handle.as_str(),
+                            Some(INTERFACE_REQUEST),
+                            "Close",
+                            &(),
+                        )
+                        .await;
+                    if let Err(_err) = result {
+                        #[cfg(feature = "log")]
+                        tracing::warn!("Failed to close the request {}: {}", handle.as_str(), _err);
+                    }
+                })
+                .detach();
+        }
+    }
+}
+
 /// The Request interface is shared by all portal interfaces.
 /// When a portal method is called, the reply includes a handle (i.e. object
 /// path) for a Request object, which will stay alive for the duration of the
@@ -166,9 +513,13 @@ impl From<ResponseError> for ResponseType {
 /// The application can abort the interaction calling
 /// [`close()`][`RequestProxy::close`] on the Request object.
 ///
+/// The handle exported by the [`WindowIdentifier`](crate::WindowIdentifier)
+/// the dialog is attached to is kept alive for as long as the request, see
+/// [`RequestProxy::keep_exported`].
+///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Request`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Request).
 #[doc(alias = "org.freedesktop.portal.Request")]
-pub(crate) struct RequestProxy<'a>(zbus::Proxy<'a>);
+pub(crate) struct RequestProxy<'a>(zbus::Proxy<'a>, ExportGuard, Option<Arc<str>>);
 
 impl<'a> RequestProxy<'a> {
     pub async fn new(
@@ -176,28 +527,12 @@ impl<'a> RequestProxy<'a> {
         path: ObjectPath<'a>,
     ) -> Result<RequestProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Request")?
+            .interface(INTERFACE_REQUEST)?
             .path(path)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
-    }
-
-    pub async fn from_unique_name(
-        connection: &zbus::Connection,
-        handle_token: &HandleToken,
-    ) -> Result<RequestProxy<'a>, Error> {
-        let unique_name = connection.unique_name().unwrap();
-        let unique_identifier = unique_name.trim_start_matches(':').replace('.', "_");
-        let path = ObjectPath::try_from(format!(
-            "/org/freedesktop/portal/desktop/request/{}/{}",
-            unique_identifier, handle_token
-        ))
-        .unwrap();
-        #[cfg(feature = "log")]
-        tracing::info!("Creating a org.freedesktop.portal.Request {}", path);
-        RequestProxy::new(connection, path).await
+        Ok(Self(proxy, ExportGuard::default(), None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -205,6 +540,27 @@ impl<'a> RequestProxy<'a> {
         &self.0
     }
 
+    /// Keeps the handle exported by a window identifier alive until the
+    /// request is dropped, i.e. once its `Response` arrived.
+    pub fn keep_exported(mut self, export: ExportGuard) -> Self {
+        self.1 = export;
+        self
+    }
+
+    /// Attach the id given to [`with_correlation_id`](crate::with_correlation_id)
+    /// to the request.
+    pub fn correlated(mut self, id: Option<Arc<str>>) -> Self {
+        self.2 = id;
+        self
+    }
+
+    /// The id given to [`with_correlation_id`](crate::with_correlation_id)
+    /// when the request was made.
+    #[allow(dead_code)]
+    pub fn correlation_id(&self) -> Option<&str> {
+        self.2.as_deref()
+    }
+
     /// See also [`Response`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-Request.Response).
     #[doc(alias = "Response")]
     #[allow(dead_code)]
@@ -226,7 +582,6 @@ impl<'a> RequestProxy<'a> {
     /// # Specifications
     ///
     /// See also [`Close`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Request.Close).
-    #[allow(dead_code)]
     #[doc(alias = "Close")]
     pub async fn close(&self) -> Result<(), Error> {
         call_method(self.inner(), "Close", &()).await
@@ -237,6 +592,73 @@ impl<'a> Debug for RequestProxy<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_tuple("RequestProxy")
             .field(&self.inner().path().as_str())
+            .field(&self.1)
+            .field(&self.2)
             .finish()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::future::IntoFuture;
+
+    use futures::executor::block_on;
+    use zbus::zvariant::OwnedObjectPath;
+
+    use super::PortalRequest;
+    use crate::Error;
+
+    #[test]
+    fn two_phases() {
+        let path = "/org/freedesktop/portal/desktop/request/1_2/ashpd_1";
+        let handle = OwnedObjectPath::try_from(path).unwrap();
+        let request = PortalRequest::new(Ok(handle), async { Ok(1) })
+            .map(|n| Ok(n + 1))
+            .prepare()
+            .unwrap();
+        assert_eq!(request.handle().as_str(), path);
+        assert_eq!(block_on(request.send()).unwrap(), 2);
+
+        let invalid = PortalRequest::<()>::invalid(Error::InvalidArgument("no handle".to_owned()));
+        assert!(matches!(
+            block_on(invalid.into_future()),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[cfg(feature = "backend")]
+    #[test]
+    fn close_request() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        use zbus::{dbus_interface, zvariant::ObjectPath};
+
+        use crate::backend::peer_connections;
+
+        #[derive(Default)]
+        struct Request(AtomicU32);
+
+        #[dbus_interface(name = "org.freedesktop.portal.Request")]
+        impl Request {
+            fn close(&self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            let path = "/org/freedesktop/portal/desktop/request/1_2/ashpd_1";
+            let server = backend.object_server();
+            server.at(path, Request::default()).await.unwrap();
+
+            let handle = ObjectPath::try_from(path).unwrap();
+            super::close_request(&frontend, &handle).await.unwrap();
+            let request = server.interface::<_, Request>(path).await.unwrap();
+            assert_eq!(request.get().await.0.load(Ordering::SeqCst), 1);
+
+            // The request is gone once its response arrived.
+            server.remove::<Request, _>(path).await.unwrap();
+            super::close_request(&frontend, &handle).await.unwrap();
+        });
+    }
+}
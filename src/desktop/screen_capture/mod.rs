@@ -0,0 +1,302 @@
+//! ```rust,no_run
+//! use ashpd::desktop::{
+//!     screen_capture::{CaptureBackend, ScreenCapture},
+//!     screencast::{SelectSourcesOptions, SourceType},
+//! };
+//! use ashpd::WindowIdentifier;
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::Connection::session().await?;
+//!     let options = SelectSourcesOptions::default().with_types(SourceType::Monitor);
+//!     let capture = ScreenCapture::start(&connection, &WindowIdentifier::default(), options).await?;
+//!
+//!     if capture.backend() == CaptureBackend::Portal {
+//!         let _fd = capture.open_pipe_wire_remote().await?;
+//!     }
+//!     for stream in capture.streams() {
+//!         println!("{:?} at {:?}", stream.size(), stream.position());
+//!     }
+//!     capture.stop().await?;
+//!     Ok(())
+//! }
+//! ```
+
+#[cfg(feature = "wlr")]
+mod wlr;
+
+use std::{fmt::Debug, os::unix::prelude::RawFd};
+
+#[cfg(feature = "wlr")]
+pub use self::wlr::{DmabufFrame, DmabufObject, Frame, WlrOutput};
+#[cfg(feature = "wlr")]
+use super::screencast::CursorMode;
+use super::screencast::{
+    ScreenCastProxy, ScreenCastSession, SelectSourcesOptions, SourceType, Stream, Streams,
+};
+use crate::{Error, WindowIdentifier};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Where the frames of a [`ScreenCapture`] come from.
+///
+/// The variants of the fallbacks only exist with the feature enabling them.
+#[non_exhaustive]
+pub enum CaptureBackend {
+    /// The ScreenCast portal, the frames are sent through PipeWire.
+    Portal,
+    /// The wlr-screencopy and wlr-export-dmabuf protocols of the wlroots
+    /// based compositors, the frames are copied on demand with
+    /// [`ScreenCapture::capture_frame`] or exported with
+    /// [`ScreenCapture::export_frame`].
+    #[cfg(feature = "wlr")]
+    Wlr,
+}
+
+impl CaptureBackend {
+    /// The backend [`ScreenCapture::start`] uses, the portal if the running
+    /// `xdg-desktop-portal` has a ScreenCast backend.
+    pub async fn detect(connection: &zbus::Connection) -> Self {
+        let portal = match ScreenCastProxy::new(connection).await {
+            // The interface is only exported when a backend implements it.
+            Ok(proxy) => proxy.available_source_types().await.is_ok(),
+            Err(_) => false,
+        };
+        if portal {
+            Self::Portal
+        } else {
+            Self::fallback()
+        }
+    }
+
+    #[cfg(feature = "wlr")]
+    fn fallback() -> Self {
+        Self::Wlr
+    }
+
+    #[cfg(not(feature = "wlr"))]
+    fn fallback() -> Self {
+        Self::Portal
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A source captured by a [`ScreenCapture`].
+///
+/// The variants of the fallbacks only exist with the feature enabling them.
+#[non_exhaustive]
+pub enum CaptureStream {
+    /// A PipeWire stream started by the portal.
+    PipeWire(Stream),
+    /// An output captured with wlr-screencopy or wlr-export-dmabuf.
+    #[cfg(feature = "wlr")]
+    Wlr(WlrOutput),
+}
+
+impl CaptureStream {
+    /// The size of the source in the compositor coordinate space.
+    pub fn size(&self) -> Option<(i32, i32)> {
+        match self {
+            Self::PipeWire(stream) => stream.size(),
+            #[cfg(feature = "wlr")]
+            Self::Wlr(output) => output.size(),
+        }
+    }
+
+    /// The position of the source in the compositor coordinate space, only
+    /// known for the monitors.
+    pub fn position(&self) -> Option<(i32, i32)> {
+        match self {
+            Self::PipeWire(stream) => stream.position(),
+            #[cfg(feature = "wlr")]
+            Self::Wlr(output) => Some(output.position()),
+        }
+    }
+
+    /// The type of the source, the wlr protocols only capture monitors.
+    pub fn source_type(&self) -> SourceType {
+        match self {
+            Self::PipeWire(stream) => stream.source_type(),
+            #[cfg(feature = "wlr")]
+            Self::Wlr(_) => SourceType::Monitor,
+        }
+    }
+
+    /// The PipeWire node of the stream, None for the wlr protocols.
+    pub fn pipe_wire_node_id(&self) -> Option<u32> {
+        match self {
+            Self::PipeWire(stream) => Some(stream.pipe_wire_node_id()),
+            #[cfg(feature = "wlr")]
+            Self::Wlr(_) => None,
+        }
+    }
+}
+
+enum Backend<'a> {
+    Portal {
+        proxy: ScreenCastProxy<'a>,
+        session: ScreenCastSession<'a>,
+        streams: Streams,
+    },
+    #[cfg(feature = "wlr")]
+    Wlr {
+        capture: Box<wlr::WlrCapture>,
+        outputs: Vec<WlrOutput>,
+    },
+}
+
+/// A started screen capture, the same whichever the [`CaptureBackend`].
+pub struct ScreenCapture<'a>(Backend<'a>);
+
+impl<'a> ScreenCapture<'a> {
+    /// Start capturing with the backend returned by
+    /// [`CaptureBackend::detect`].
+    ///
+    /// With the portal, the user picks the sources in a dialog. With the wlr
+    /// protocols, the first output is captured, or all of them if
+    /// `options` allows multiple sources. The cursor is part of the frames
+    /// with [`CursorMode::Embedded`](super::screencast::CursorMode::Embedded)
+    /// only.
+    pub async fn start(
+        connection: &zbus::Connection,
+        identifier: &WindowIdentifier,
+        options: SelectSourcesOptions,
+    ) -> Result<ScreenCapture<'a>, Error> {
+        let backend = CaptureBackend::detect(connection).await;
+        Self::start_with(backend, connection, identifier, options).await
+    }
+
+    /// Start capturing with `backend`.
+    ///
+    /// Fails with [`Error::Unsupported`] if the wlr protocols are requested
+    /// for other sources than monitors or if the compositor implements none
+    /// of them.
+    pub async fn start_with(
+        backend: CaptureBackend,
+        connection: &zbus::Connection,
+        identifier: &WindowIdentifier,
+        options: SelectSourcesOptions,
+    ) -> Result<ScreenCapture<'a>, Error> {
+        match backend {
+            CaptureBackend::Portal => {
+                let proxy = ScreenCastProxy::new(connection).await?;
+                let session = proxy.create_session().await?;
+                proxy.select_sources(&session, options).await?;
+                let streams = proxy.start(&session, identifier).await?;
+                Ok(Self(Backend::Portal {
+                    proxy,
+                    session,
+                    streams,
+                }))
+            }
+            #[cfg(feature = "wlr")]
+            CaptureBackend::Wlr => {
+                if let Some(types) = options.types() {
+                    if !types.contains(SourceType::Monitor) {
+                        return Err(Error::Unsupported);
+                    }
+                }
+                let overlay_cursor = options
+                    .cursor_mode()
+                    .map_or(false, |mode| mode.contains(CursorMode::Embedded));
+                let capture = wlr::WlrCapture::connect(overlay_cursor)?;
+                let count = if options.multiple() == Some(true) {
+                    usize::MAX
+                } else {
+                    1
+                };
+                let outputs = capture.outputs().take(count).cloned().collect();
+                Ok(Self(Backend::Wlr {
+                    capture: Box::new(capture),
+                    outputs,
+                }))
+            }
+        }
+    }
+
+    /// The backend used.
+    pub fn backend(&self) -> CaptureBackend {
+        match self.0 {
+            Backend::Portal { .. } => CaptureBackend::Portal,
+            #[cfg(feature = "wlr")]
+            Backend::Wlr { .. } => CaptureBackend::Wlr,
+        }
+    }
+
+    /// The captured sources.
+    pub fn streams(&self) -> Vec<CaptureStream> {
+        match &self.0 {
+            Backend::Portal { streams, .. } => streams
+                .streams()
+                .iter()
+                .cloned()
+                .map(CaptureStream::PipeWire)
+                .collect(),
+            #[cfg(feature = "wlr")]
+            Backend::Wlr { outputs, .. } => {
+                outputs.iter().cloned().map(CaptureStream::Wlr).collect()
+            }
+        }
+    }
+
+    /// Open the PipeWire remote of the portal streams, see
+    /// [`ScreenCastProxy::open_pipe_wire_remote`].
+    ///
+    /// Fails with [`Error::Unsupported`] for the wlr protocols.
+    pub async fn open_pipe_wire_remote(&self) -> Result<RawFd, Error> {
+        match &self.0 {
+            Backend::Portal { proxy, session, .. } => proxy.open_pipe_wire_remote(session).await,
+            #[cfg(feature = "wlr")]
+            Backend::Wlr { .. } => Err(Error::Unsupported),
+        }
+    }
+
+    /// Copy the next frame of `stream`, blocking until the compositor is
+    /// done, with wlr-screencopy.
+    ///
+    /// Fails with [`Error::Unsupported`] for the portal streams, their frames
+    /// come through PipeWire, or if the compositor doesn't implement
+    /// wlr-screencopy.
+    #[cfg(feature = "wlr")]
+    pub fn capture_frame(&mut self, stream: &CaptureStream) -> Result<Frame, Error> {
+        match (&mut self.0, stream) {
+            (Backend::Wlr { capture, .. }, CaptureStream::Wlr(output)) => {
+                capture.capture_frame(output)
+            }
+            _ => Err(Error::Unsupported),
+        }
+    }
+
+    /// Export the next frame of `stream` as DMA-BUF objects, blocking until
+    /// the compositor is done, with wlr-export-dmabuf. Unlike
+    /// [`capture_frame`](Self::capture_frame), the frame stays in the GPU
+    /// memory.
+    ///
+    /// Fails with [`Error::Unsupported`] for the portal streams or if the
+    /// compositor doesn't implement wlr-export-dmabuf.
+    #[cfg(feature = "wlr")]
+    pub fn export_frame(&mut self, stream: &CaptureStream) -> Result<DmabufFrame, Error> {
+        match (&mut self.0, stream) {
+            (Backend::Wlr { capture, .. }, CaptureStream::Wlr(output)) => {
+                capture.export_frame(output)
+            }
+            _ => Err(Error::Unsupported),
+        }
+    }
+
+    /// Stop capturing, closing the portal session.
+    pub async fn stop(self) -> Result<(), Error> {
+        match self.0 {
+            Backend::Portal { session, .. } => session.close().await,
+            #[cfg(feature = "wlr")]
+            Backend::Wlr { .. } => Ok(()),
+        }
+    }
+}
+
+impl<'a> Debug for ScreenCapture<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScreenCapture")
+            .field("backend", &self.backend())
+            .field("streams", &self.streams())
+            .finish()
+    }
+}
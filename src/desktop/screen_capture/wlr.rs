@@ -0,0 +1,751 @@
+use std::{
+    ffi::CStr,
+    fmt::Debug,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    os::unix::{
+        io::OwnedFd,
+        prelude::{AsRawFd, FromRawFd},
+    },
+};
+
+use nix::sys::memfd::{memfd_create, MemFdCreateFlag};
+use wayland_client::{
+    protocol::{
+        wl_buffer::WlBuffer,
+        wl_output::{self, WlOutput},
+        wl_registry::{self, WlRegistry},
+        wl_shm::{self, WlShm},
+        wl_shm_pool::WlShmPool,
+    },
+    Connection, ConnectionHandle, Dispatch, EventQueue, Proxy, QueueHandle, WEnum,
+};
+use wayland_protocols::wlr::unstable::{
+    export_dmabuf::v1::client::{
+        zwlr_export_dmabuf_frame_v1::{self, ZwlrExportDmabufFrameV1},
+        zwlr_export_dmabuf_manager_v1::ZwlrExportDmabufManagerV1,
+    },
+    screencopy::v1::client::{
+        zwlr_screencopy_frame_v1::{self, ZwlrScreencopyFrameV1},
+        zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1,
+    },
+};
+
+use crate::Error;
+
+/// An output of a wlroots based compositor, captured with the
+/// wlr-screencopy or wlr-export-dmabuf protocol.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WlrOutput {
+    id: u32,
+    name: Option<String>,
+    position: (i32, i32),
+    size: Option<(i32, i32)>,
+}
+
+impl WlrOutput {
+    /// The name of the `wl_output` global, unique for the lifetime of the
+    /// compositor.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// The name of the output, `DP-1` for example. Requires version 4 of
+    /// `wl_output`.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The position of the output in the compositor space.
+    pub fn position(&self) -> (i32, i32) {
+        self.position
+    }
+
+    /// The size of the current mode of the output.
+    pub fn size(&self) -> Option<(i32, i32)> {
+        self.size
+    }
+}
+
+/// A frame copied from a [`WlrOutput`] into shared memory.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Frame {
+    format: u32,
+    width: u32,
+    height: u32,
+    stride: u32,
+    y_inverted: bool,
+    data: Vec<u8>,
+}
+
+impl Frame {
+    /// The `wl_shm` format of the pixels, `0` for ARGB8888 for example.
+    pub fn format(&self) -> u32 {
+        self.format
+    }
+
+    /// The size of the frame.
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// The number of bytes of a row of pixels.
+    pub fn stride(&self) -> u32 {
+        self.stride
+    }
+
+    /// Whether the rows are stored from the bottom to the top.
+    pub fn y_inverted(&self) -> bool {
+        self.y_inverted
+    }
+
+    /// The pixels.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl Debug for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Frame")
+            .field("format", &self.format)
+            .field("size", &self.size())
+            .field("stride", &self.stride)
+            .field("y_inverted", &self.y_inverted)
+            .finish()
+    }
+}
+
+/// A frame of a [`WlrOutput`] exported by the compositor as DMA-BUF objects
+/// with the wlr-export-dmabuf protocol, without copying it.
+#[derive(Debug)]
+pub struct DmabufFrame {
+    format: u32,
+    modifier: u64,
+    width: u32,
+    height: u32,
+    offset: (u32, u32),
+    buffer_flags: u32,
+    objects: Vec<DmabufObject>,
+}
+
+impl DmabufFrame {
+    /// The DRM format of the pixels, a fourcc code.
+    pub fn format(&self) -> u32 {
+        self.format
+    }
+
+    /// The DRM format modifier of the objects.
+    pub fn modifier(&self) -> u64 {
+        self.modifier
+    }
+
+    /// The size of the frame.
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// The position of the frame in the output, usually `(0, 0)`.
+    pub fn offset(&self) -> (u32, u32) {
+        self.offset
+    }
+
+    /// Whether the rows are stored from the bottom to the top.
+    pub fn y_inverted(&self) -> bool {
+        self.buffer_flags & 1 != 0
+    }
+
+    /// The objects holding the planes of the frame, in the order of their
+    /// index.
+    pub fn objects(&self) -> &[DmabufObject] {
+        &self.objects
+    }
+}
+
+/// A DMA-BUF object of a [`DmabufFrame`].
+#[derive(Debug)]
+pub struct DmabufObject {
+    index: u32,
+    fd: OwnedFd,
+    size: u32,
+    offset: u32,
+    stride: u32,
+    plane_index: u32,
+}
+
+impl DmabufObject {
+    /// The file descriptor of the object, closed when the frame is dropped.
+    pub fn fd(&self) -> &OwnedFd {
+        &self.fd
+    }
+
+    /// The size of the object in bytes.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// The offset of the plane in the object.
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// The number of bytes of a row of pixels of the plane.
+    pub fn stride(&self) -> u32 {
+        self.stride
+    }
+
+    /// The index of the plane held by the object.
+    pub fn plane_index(&self) -> u32 {
+        self.plane_index
+    }
+}
+
+#[derive(Default)]
+struct FrameState {
+    buffer: Option<(wl_shm::Format, u32, u32, u32)>,
+    buffer_done: bool,
+    y_inverted: bool,
+    ready: bool,
+    failed: bool,
+}
+
+impl FrameState {
+    // Since version 3 the compositor lists every buffer type it supports
+    // before `buffer_done`.
+    fn buffer_known(&self, version: u32) -> bool {
+        self.buffer.is_some() && (version < 3 || self.buffer_done)
+    }
+}
+
+#[derive(Default)]
+struct ExportState {
+    frame: Option<DmabufFrame>,
+    num_objects: u32,
+    ready: bool,
+    cancelled: Option<String>,
+}
+
+impl ExportState {
+    fn done(&self) -> bool {
+        self.ready || self.cancelled.is_some()
+    }
+
+    fn object(&mut self, object: DmabufObject) {
+        if let Some(frame) = &mut self.frame {
+            frame.objects.push(object);
+        }
+    }
+
+    // The frame, once the compositor sent all of it.
+    fn finish(self) -> Result<DmabufFrame, Error> {
+        if let Some(reason) = self.cancelled {
+            return Err(Error::Wayland(format!(
+                "The compositor cancelled the frame: {}",
+                reason
+            )));
+        }
+        match self.frame {
+            Some(mut frame) if self.ready && frame.objects.len() == self.num_objects as usize => {
+                frame.objects.sort_by_key(|object| object.index);
+                Ok(frame)
+            }
+            _ => Err(Error::Wayland(
+                "The compositor sent an incomplete frame".to_owned(),
+            )),
+        }
+    }
+}
+
+fn modifier(high: u32, low: u32) -> u64 {
+    (u64::from(high) << 32) | u64::from(low)
+}
+
+#[derive(Default)]
+struct State {
+    outputs: Vec<(WlOutput, WlrOutput)>,
+    shm: Option<WlShm>,
+    manager: Option<ZwlrScreencopyManagerV1>,
+    export_manager: Option<ZwlrExportDmabufManagerV1>,
+    frame: FrameState,
+    export: ExportState,
+}
+
+// A connection to the compositor, the frames are copied on demand.
+pub(crate) struct WlrCapture {
+    cnx: Connection,
+    queue: EventQueue<State>,
+    state: State,
+    overlay_cursor: bool,
+}
+
+impl WlrCapture {
+    // Fails with `Error::Unsupported` if the compositor implements neither
+    // wlr-screencopy nor wlr-export-dmabuf.
+    pub(crate) fn connect(overlay_cursor: bool) -> Result<Self, Error> {
+        let cnx = Connection::connect_to_env().map_err(wayland_error)?;
+        let mut queue = cnx.new_event_queue();
+        let queue_handle = queue.handle();
+        let mut handle = cnx.handle();
+        handle
+            .display()
+            .get_registry(&mut handle, &queue_handle, ())
+            .map_err(wayland_error)?;
+        let mut state = State::default();
+        // The globals, then the events of the bound outputs.
+        for _ in 0..2 {
+            cnx.roundtrip().map_err(wayland_error)?;
+            queue.dispatch_pending(&mut state).map_err(wayland_error)?;
+        }
+        let screencopy = state.manager.is_some() && state.shm.is_some();
+        if !screencopy && state.export_manager.is_none() {
+            return Err(Error::Unsupported);
+        }
+        Ok(Self {
+            cnx,
+            queue,
+            state,
+            overlay_cursor,
+        })
+    }
+
+    pub(crate) fn outputs(&self) -> impl Iterator<Item = &WlrOutput> {
+        self.state.outputs.iter().map(|(_, output)| output)
+    }
+
+    fn wl_output(&self, output: &WlrOutput) -> Result<WlOutput, Error> {
+        self.state
+            .outputs
+            .iter()
+            .find(|(_, known)| known.id == output.id)
+            .map(|(wl_output, _)| wl_output.clone())
+            .ok_or_else(|| Error::Wayland(format!("Unknown output {}", output.id)))
+    }
+
+    // Blocks until the compositor copied the next frame of `output`.
+    pub(crate) fn capture_frame(&mut self, output: &WlrOutput) -> Result<Frame, Error> {
+        let wl_output = self.wl_output(output)?;
+        let manager = self.state.manager.clone().ok_or(Error::Unsupported)?;
+        let shm = self.state.shm.clone().ok_or(Error::Unsupported)?;
+        let queue_handle = self.queue.handle();
+        let mut handle = self.cnx.handle();
+
+        self.state.frame = FrameState::default();
+        let frame = manager
+            .capture_output(
+                &mut handle,
+                self.overlay_cursor as i32,
+                &wl_output,
+                &queue_handle,
+                (),
+            )
+            .map_err(wayland_error)?;
+        while !self.state.frame.failed && !self.state.frame.buffer_known(manager.version()) {
+            self.queue
+                .blocking_dispatch(&mut self.state)
+                .map_err(wayland_error)?;
+        }
+        let (format, width, height, stride) = match self.state.frame.buffer {
+            Some(buffer) if !self.state.frame.failed => buffer,
+            _ => {
+                frame.destroy(&mut handle);
+                return Err(Error::Wayland(
+                    "The compositor failed to copy the frame".to_owned(),
+                ));
+            }
+        };
+
+        let size = (stride * height) as usize;
+        let mut file = shm_file(size)?;
+        let pool = shm
+            .create_pool(
+                &mut handle,
+                file.as_raw_fd(),
+                size as i32,
+                &queue_handle,
+                (),
+            )
+            .map_err(wayland_error)?;
+        let buffer = pool
+            .create_buffer(
+                &mut handle,
+                0,
+                width as i32,
+                height as i32,
+                stride as i32,
+                format,
+                &queue_handle,
+                (),
+            )
+            .map_err(wayland_error)?;
+        frame.copy(&mut handle, &buffer);
+        while !self.state.frame.ready && !self.state.frame.failed {
+            self.queue
+                .blocking_dispatch(&mut self.state)
+                .map_err(wayland_error)?;
+        }
+        frame.destroy(&mut handle);
+        buffer.destroy(&mut handle);
+        pool.destroy(&mut handle);
+        if self.state.frame.failed {
+            return Err(Error::Wayland(
+                "The compositor failed to copy the frame".to_owned(),
+            ));
+        }
+
+        let mut data = vec![0; size];
+        file.seek(SeekFrom::Start(0))
+            .and_then(|_| file.read_exact(&mut data))
+            .map_err(wayland_error)?;
+        Ok(Frame {
+            format: format as u32,
+            width,
+            height,
+            stride,
+            y_inverted: self.state.frame.y_inverted,
+            data,
+        })
+    }
+
+    // Blocks until the compositor exported the next frame of `output`.
+    pub(crate) fn export_frame(&mut self, output: &WlrOutput) -> Result<DmabufFrame, Error> {
+        let wl_output = self.wl_output(output)?;
+        let manager = self
+            .state
+            .export_manager
+            .clone()
+            .ok_or(Error::Unsupported)?;
+        let queue_handle = self.queue.handle();
+        let mut handle = self.cnx.handle();
+
+        self.state.export = ExportState::default();
+        let frame = manager
+            .capture_output(
+                &mut handle,
+                self.overlay_cursor as i32,
+                &wl_output,
+                &queue_handle,
+                (),
+            )
+            .map_err(wayland_error)?;
+        while !self.state.export.done() {
+            self.queue
+                .blocking_dispatch(&mut self.state)
+                .map_err(wayland_error)?;
+        }
+        frame.destroy(&mut handle);
+        std::mem::take(&mut self.state.export).finish()
+    }
+}
+
+// An anonymous file of `size` bytes shared with the compositor.
+fn shm_file(size: usize) -> Result<File, Error> {
+    let name = CStr::from_bytes_with_nul(b"ashpd-screencopy\0").unwrap();
+    let fd = memfd_create(name, MemFdCreateFlag::MFD_CLOEXEC).map_err(wayland_error)?;
+    // SAFETY: the file descriptor was just created and isn't owned elsewhere.
+    let file = unsafe { File::from_raw_fd(fd) };
+    file.set_len(size as u64).map_err(wayland_error)?;
+    Ok(file)
+}
+
+fn wayland_error(err: impl std::fmt::Display) -> Error {
+    Error::Wayland(err.to_string())
+}
+
+impl Dispatch<WlRegistry> for State {
+    type UserData = ();
+
+    fn event(
+        &mut self,
+        registry: &WlRegistry,
+        event: <WlRegistry as Proxy>::Event,
+        _data: &Self::UserData,
+        connhandle: &mut ConnectionHandle<'_>,
+        qhandle: &QueueHandle<Self>,
+    ) {
+        if let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        {
+            match interface.as_str() {
+                "wl_output" => {
+                    if let Ok(output) =
+                        registry.bind::<WlOutput, _>(connhandle, name, version.min(4), qhandle, ())
+                    {
+                        let info = WlrOutput {
+                            id: name,
+                            name: None,
+                            position: (0, 0),
+                            size: None,
+                        };
+                        self.outputs.push((output, info));
+                    }
+                }
+                "wl_shm" => {
+                    self.shm = registry
+                        .bind::<WlShm, _>(connhandle, name, 1, qhandle, ())
+                        .ok();
+                }
+                "zwlr_screencopy_manager_v1" => {
+                    self.manager = registry
+                        .bind::<ZwlrScreencopyManagerV1, _>(
+                            connhandle,
+                            name,
+                            version.min(3),
+                            qhandle,
+                            (),
+                        )
+                        .ok();
+                }
+                "zwlr_export_dmabuf_manager_v1" => {
+                    self.export_manager = registry
+                        .bind::<ZwlrExportDmabufManagerV1, _>(connhandle, name, 1, qhandle, ())
+                        .ok();
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+impl Dispatch<WlOutput> for State {
+    type UserData = ();
+
+    fn event(
+        &mut self,
+        proxy: &WlOutput,
+        event: <WlOutput as Proxy>::Event,
+        _data: &Self::UserData,
+        _connhandle: &mut ConnectionHandle<'_>,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+        let info = match self.outputs.iter_mut().find(|(output, _)| output == proxy) {
+            Some((_, info)) => info,
+            None => return,
+        };
+        match event {
+            wl_output::Event::Geometry { x, y, .. } => info.position = (x, y),
+            wl_output::Event::Mode {
+                flags: WEnum::Value(flags),
+                width,
+                height,
+                ..
+            } if flags.contains(wl_output::Mode::Current) => info.size = Some((width, height)),
+            wl_output::Event::Name { name } => info.name = Some(name),
+            _ => (),
+        }
+    }
+}
+
+impl Dispatch<ZwlrScreencopyFrameV1> for State {
+    type UserData = ();
+
+    fn event(
+        &mut self,
+        _proxy: &ZwlrScreencopyFrameV1,
+        event: <ZwlrScreencopyFrameV1 as Proxy>::Event,
+        _data: &Self::UserData,
+        _connhandle: &mut ConnectionHandle<'_>,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_screencopy_frame_v1::Event::Buffer {
+                format: WEnum::Value(format),
+                width,
+                height,
+                stride,
+            } => self.frame.buffer = Some((format, width, height, stride)),
+            zwlr_screencopy_frame_v1::Event::BufferDone => self.frame.buffer_done = true,
+            zwlr_screencopy_frame_v1::Event::Flags {
+                flags: WEnum::Value(flags),
+            } => self.frame.y_inverted = flags.contains(zwlr_screencopy_frame_v1::Flags::YInvert),
+            zwlr_screencopy_frame_v1::Event::Ready { .. } => self.frame.ready = true,
+            zwlr_screencopy_frame_v1::Event::Failed => self.frame.failed = true,
+            _ => (),
+        }
+    }
+}
+
+impl Dispatch<ZwlrExportDmabufFrameV1> for State {
+    type UserData = ();
+
+    fn event(
+        &mut self,
+        _proxy: &ZwlrExportDmabufFrameV1,
+        event: <ZwlrExportDmabufFrameV1 as Proxy>::Event,
+        _data: &Self::UserData,
+        _connhandle: &mut ConnectionHandle<'_>,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_export_dmabuf_frame_v1::Event::Frame {
+                width,
+                height,
+                offset_x,
+                offset_y,
+                buffer_flags,
+                format,
+                mod_high,
+                mod_low,
+                num_objects,
+                ..
+            } => {
+                self.export.frame = Some(DmabufFrame {
+                    format,
+                    modifier: modifier(mod_high, mod_low),
+                    width,
+                    height,
+                    offset: (offset_x, offset_y),
+                    buffer_flags,
+                    objects: Vec::new(),
+                });
+                self.export.num_objects = num_objects;
+            }
+            zwlr_export_dmabuf_frame_v1::Event::Object {
+                index,
+                fd,
+                size,
+                offset,
+                stride,
+                plane_index,
+            } => self.export.object(DmabufObject {
+                index,
+                // SAFETY: the file descriptor received with the event is
+                // ours to close.
+                fd: unsafe { OwnedFd::from_raw_fd(fd) },
+                size,
+                offset,
+                stride,
+                plane_index,
+            }),
+            zwlr_export_dmabuf_frame_v1::Event::Ready { .. } => self.export.ready = true,
+            zwlr_export_dmabuf_frame_v1::Event::Cancel { reason } => {
+                self.export.cancelled = Some(format!("{:?}", reason))
+            }
+            _ => (),
+        }
+    }
+}
+
+// The objects whose events are ignored.
+macro_rules! ignore_events {
+    ($($proxy:ty),*) => {
+        $(
+            impl Dispatch<$proxy> for State {
+                type UserData = ();
+
+                fn event(
+                    &mut self,
+                    _proxy: &$proxy,
+                    _event: <$proxy as Proxy>::Event,
+                    _data: &Self::UserData,
+                    _connhandle: &mut ConnectionHandle<'_>,
+                    _qhandle: &QueueHandle<Self>,
+                ) {
+                }
+            }
+        )*
+    };
+}
+
+ignore_events!(
+    WlShm,
+    WlShmPool,
+    WlBuffer,
+    ZwlrScreencopyManagerV1,
+    ZwlrExportDmabufManagerV1
+);
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+
+    use wayland_client::protocol::wl_shm;
+
+    use super::{modifier, DmabufFrame, DmabufObject, ExportState, FrameState};
+    use crate::Error;
+
+    fn object(index: u32) -> DmabufObject {
+        DmabufObject {
+            index,
+            fd: File::open("/dev/null").unwrap().into(),
+            size: 4096,
+            offset: 0,
+            stride: 64,
+            plane_index: index,
+        }
+    }
+
+    fn export(num_objects: u32) -> ExportState {
+        ExportState {
+            frame: Some(DmabufFrame {
+                format: 0x34325241,
+                modifier: modifier(0x0100_0000, 2),
+                width: 16,
+                height: 16,
+                offset: (0, 0),
+                buffer_flags: 1,
+                objects: Vec::new(),
+            }),
+            num_objects,
+            ready: false,
+            cancelled: None,
+        }
+    }
+
+    #[test]
+    fn export_frame() {
+        let mut state = export(2);
+        state.object(object(1));
+        state.object(object(0));
+        assert!(!state.done());
+        state.ready = true;
+        assert!(state.done());
+
+        let frame = state.finish().unwrap();
+        assert_eq!(frame.modifier(), 0x0100_0000_0000_0002);
+        assert!(frame.y_inverted());
+        let planes = frame
+            .objects()
+            .iter()
+            .map(DmabufObject::plane_index)
+            .collect::<Vec<_>>();
+        assert_eq!(planes, [0, 1]);
+    }
+
+    #[test]
+    fn export_frame_incomplete() {
+        let mut state = export(2);
+        state.object(object(0));
+        state.ready = true;
+        assert!(matches!(state.finish(), Err(Error::Wayland(_))));
+
+        assert!(matches!(
+            ExportState::default().finish(),
+            Err(Error::Wayland(_))
+        ));
+    }
+
+    #[test]
+    fn export_frame_cancelled() {
+        let mut state = export(1);
+        state.cancelled = Some("Resizing".to_owned());
+        assert!(state.done());
+        match state.finish() {
+            Err(Error::Wayland(message)) => assert!(message.contains("Resizing")),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn buffer_known() {
+        let mut frame = FrameState::default();
+        assert!(!frame.buffer_known(2));
+        frame.buffer = Some((wl_shm::Format::Argb8888, 16, 16, 64));
+        assert!(frame.buffer_known(2));
+        assert!(!frame.buffer_known(3));
+        frame.buffer_done = true;
+        assert!(frame.buffer_known(3));
+    }
+}
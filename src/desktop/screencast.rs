@@ -4,9 +4,10 @@
 //! The portal is currently useless without PipeWire & Rust support.
 //!
 //! ```rust,no_run
-//! use ashpd::desktop::screencast::{CursorMode, PersistMode, ScreenCastProxy, SourceType};
+//! use ashpd::desktop::screencast::{
+//!     CursorMode, PersistMode, ScreenCastProxy, SelectSourcesOptions, SourceType,
+//! };
 //! use ashpd::WindowIdentifier;
-//! use enumflags2::BitFlags;
 //!
 //! async fn run() -> ashpd::Result<()> {
 //!     let connection = zbus::Connection::session().await?;
@@ -14,20 +15,16 @@
 //!
 //!     let session = proxy.create_session().await?;
 //!
-//!     proxy
-//!         .select_sources(
-//!             &session,
-//!             BitFlags::from(CursorMode::Metadata),
-//!             SourceType::Monitor | SourceType::Window,
-//!             true,
-//!             None,
-//!             PersistMode::DoNot,
-//!         )
-//!         .await?;
+//!     let options = SelectSourcesOptions::default()
+//!         .with_cursor_mode(CursorMode::Metadata)
+//!         .with_types(SourceType::Monitor | SourceType::Window)
+//!         .with_multiple(true)
+//!         .with_persist_mode(PersistMode::DoNot);
+//!     proxy.select_sources(&session, options).await?;
 //!
-//!     let (streams, token) = proxy.start(&session, &WindowIdentifier::default()).await?;
+//!     let response = proxy.start(&session, &WindowIdentifier::default()).await?;
 //!
-//!     streams.iter().for_each(|stream| {
+//!     response.streams().iter().for_each(|stream| {
 //!         println!("node id: {}", stream.pipe_wire_node_id());
 //!         println!("size: {:?}", stream.size());
 //!         println!("position: {:?}", stream.position());
@@ -38,27 +35,42 @@
 
 use std::{
     collections::HashMap,
+    convert::TryFrom,
     fmt::Debug,
+    future::Future,
     os::unix::prelude::{IntoRawFd, RawFd},
+    sync::{Arc, Mutex},
 };
 
 use enumflags2::{bitflags, BitFlags};
-use futures::TryFutureExt;
 use serde::{Deserialize, Serialize};
-use serde_repr::{Deserialize_repr, Serialize_repr};
-use zbus::zvariant::{DeserializeDict, OwnedFd, SerializeDict, Type, Value};
+use zbus::zvariant::{
+    DeserializeDict, OwnedFd, OwnedObjectPath, OwnedValue, SerializeDict, Type, Value,
+};
 
-use super::{HandleToken, SessionProxy, DESTINATION, PATH};
+use super::{
+    permission_store::{restore_token_status, PermissionStatus},
+    session::ClosedFlag,
+    HandleToken, PortalRequest, ScreenCastCompatible, ScreenCastPortal, SessionCapabilities,
+    SessionGuard, SessionProxy, DESTINATION, INTERFACE_SCREENCAST, PATH,
+};
 use crate::{
-    helpers::{call_basic_response_method, call_method, call_request_method},
-    Error, WindowIdentifier,
+    helpers::{
+        call_basic_response_method, call_method, call_request_method, call_window_request_method,
+        get_property, is_unknown_property, request_handle, session_connection,
+    },
+    raw_options::RawOptions,
+    Error, TolerantBitFlags, WindowIdentifier,
 };
 
 #[bitflags]
-#[derive(Serialize_repr, Deserialize_repr, PartialEq, Copy, Clone, Debug, Type)]
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Type)]
 #[repr(u32)]
 #[doc(alias = "XdpOutputType")]
+#[non_exhaustive]
 /// A bit flag for the available sources to record.
+///
+/// Converts from and to the `u32` sent on the bus.
 pub enum SourceType {
     #[doc(alias = "XDP_OUTPUT_MONITOR")]
     /// A monitor.
@@ -71,11 +83,26 @@ pub enum SourceType {
     Virtual,
 }
 
+impl_u32_conversions!(SourceType {
+    Monitor = 1,
+    Window = 2,
+    Virtual = 4,
+});
+
+impl_str_conversions!(SourceType {
+    Monitor = "monitor",
+    Window = "window",
+    Virtual = "virtual",
+});
+
 #[bitflags]
-#[derive(Serialize_repr, Deserialize_repr, PartialEq, Debug, Copy, Clone, Type)]
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Type)]
 #[repr(u32)]
 #[doc(alias = "XdpCursorMode")]
+#[non_exhaustive]
 /// A bit flag for the possible cursor modes.
+///
+/// Converts from and to the `u32` sent on the bus.
 pub enum CursorMode {
     #[doc(alias = "XDP_CURSOR_MODE_HIDDEN")]
     /// The cursor is not part of the screen cast stream.
@@ -89,8 +116,87 @@ pub enum CursorMode {
     Metadata,
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Debug, Copy, Clone, Type)]
+impl_u32_conversions!(CursorMode {
+    Hidden = 1,
+    Embedded = 2,
+    Metadata = 4,
+});
+
+impl_str_conversions!(CursorMode {
+    Hidden = "hidden",
+    Embedded = "embedded",
+    Metadata = "metadata",
+});
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+/// What [`ScreenCastProxy::select_sources`] does when the requested
+/// [`CursorMode`] isn't one of the
+/// [`available_cursor_modes()`][`ScreenCastProxy::available_cursor_modes`].
+///
+/// The mode sent is available through [`Streams::cursor_mode`].
+pub enum CursorModeFallback {
+    /// Use the closest available mode: [`CursorMode::Metadata`] then
+    /// [`CursorMode::Embedded`] for [`CursorMode::Hidden`], keeping the
+    /// cursor out of the frames, and for the other two the one still showing
+    /// the cursor before [`CursorMode::Hidden`].
+    Closest,
+    /// Use [`CursorMode::Hidden`].
+    Hidden,
+    /// Fail with [`Error::InvalidArgument`].
+    Fail,
+}
+
+impl Default for CursorModeFallback {
+    fn default() -> Self {
+        Self::Closest
+    }
+}
+
+impl CursorModeFallback {
+    // The mode to send for `requested`, none if it can't be checked against
+    // `available`.
+    fn resolve(
+        self,
+        requested: BitFlags<CursorMode>,
+        available: BitFlags<CursorMode>,
+    ) -> Result<Option<CursorMode>, Error> {
+        let mode = match requested.iter().next() {
+            Some(mode) => mode,
+            None => return Ok(None),
+        };
+        if available.is_empty() {
+            return Ok(None);
+        }
+        if let Some(mode) = (requested & available).iter().next() {
+            return Ok(Some(mode));
+        }
+        let candidates: &[CursorMode] = match (self, mode) {
+            (Self::Fail, _) => &[],
+            (Self::Hidden, _) => &[CursorMode::Hidden],
+            (Self::Closest, CursorMode::Hidden) => &[CursorMode::Metadata, CursorMode::Embedded],
+            (Self::Closest, CursorMode::Embedded) => &[CursorMode::Metadata, CursorMode::Hidden],
+            (Self::Closest, CursorMode::Metadata) => &[CursorMode::Embedded, CursorMode::Hidden],
+        };
+        candidates
+            .iter()
+            .copied()
+            .find(|candidate| available.contains(*candidate))
+            .map(Some)
+            .ok_or_else(|| {
+                Error::InvalidArgument(format!(
+                    "The cursor mode {:?} isn't available, only {:?}",
+                    mode, available
+                ))
+            })
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Type)]
 #[doc(alias = "XdpPersistMode")]
+#[non_exhaustive]
+/// How long a permission given to a session is kept.
+///
+/// Converts from and to the `u32` sent on the bus.
 pub enum PersistMode {
     #[doc(alias = "XDP_PERSIST_MODE_NONE")]
     /// Do not persist.
@@ -103,13 +209,25 @@ pub enum PersistMode {
     ExplicitlyRevoked = 2,
 }
 
+impl_u32_conversions!(PersistMode {
+    DoNot = 0,
+    Application = 1,
+    ExplicitlyRevoked = 2,
+});
+
+impl_str_conversions!(PersistMode {
+    DoNot = "none",
+    Application = "transient",
+    ExplicitlyRevoked = "persistent",
+});
+
 impl Default for PersistMode {
     fn default() -> Self {
         Self::DoNot
     }
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
 /// Specified options for a [`ScreenCastProxy::create_session`] request.
 #[zvariant(signature = "dict")]
 struct CreateSessionOptions {
@@ -119,10 +237,57 @@ struct CreateSessionOptions {
     session_handle_token: HandleToken,
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq)]
+/// Compositor specific data used to restore a previous screen cast session.
+///
+/// The content is opaque to the application, it can be persisted, for example
+/// with `serde`, and passed back with
+/// [`SelectSourcesOptions::restore_data`]. Only available with version 4 or
+/// later of the interface.
+pub struct RestoreData(String, u32, OwnedValue);
+
+impl RestoreData {
+    /// The vendor identifier of the data.
+    pub fn vendor(&self) -> &str {
+        &self.0
+    }
+
+    /// The version of the data format.
+    pub fn version(&self) -> u32 {
+        self.1
+    }
+
+    /// The data itself.
+    pub fn data(&self) -> &OwnedValue {
+        &self.2
+    }
+}
+
+impl TryFrom<OwnedValue> for RestoreData {
+    type Error = Error;
+
+    fn try_from(value: OwnedValue) -> Result<Self, Self::Error> {
+        match &*value {
+            Value::Structure(s) => match s.fields() {
+                [Value::Str(vendor), Value::U32(version), Value::Value(data)] => {
+                    Ok(Self(vendor.to_string(), *version, Value::to_owned(data)))
+                }
+                _ => Err(Error::ParseError(format!(
+                    "Invalid restore data signature: {}",
+                    s.signature()
+                ))),
+            },
+            _ => Err(Error::ParseError(
+                "Restore data is not a structure".to_owned(),
+            )),
+        }
+    }
+}
+
+#[derive(Type, Clone, Debug, Default, PartialEq)]
 /// Specified options for a [`ScreenCastProxy::select_sources`] request.
 #[zvariant(signature = "dict")]
-struct SelectSourcesOptions {
+pub struct SelectSourcesOptions {
     /// A string that will be used as the last element of the handle.
     handle_token: HandleToken,
     /// What types of content to record.
@@ -131,44 +296,55 @@ struct SelectSourcesOptions {
     multiple: Option<bool>,
     /// Determines how the cursor will be drawn in the screen cast stream.
     cursor_mode: Option<BitFlags<CursorMode>>,
+    /// The token of a previous session to restore.
     restore_token: Option<String>,
+    /// The compositor specific data of a previous session to restore.
+    restore_data: Option<RestoreData>,
+    /// How the permission should be persisted.
     persist_mode: Option<PersistMode>,
+    /// The options without a setter.
+    raw: RawOptions,
 }
 
-impl SelectSourcesOptions {
-    /// Sets whether to allow selecting multiple sources.
-    #[must_use]
-    pub fn multiple(mut self, multiple: bool) -> Self {
-        self.multiple = Some(multiple);
-        self
-    }
+impl_options_dict!(SelectSourcesOptions {
+    handle_token = "handle_token",
+    types = "types",
+    multiple = "multiple",
+    cursor_mode = "cursor_mode",
+    restore_token = "restore_token",
+    restore_data = "restore_data",
+    persist_mode = "persist_mode",
+});
 
-    /// Sets how the cursor will be drawn on the screen cast stream.
-    #[must_use]
-    pub fn cursor_mode(mut self, cursor_mode: BitFlags<CursorMode>) -> Self {
-        self.cursor_mode = Some(cursor_mode);
-        self
-    }
-
-    /// Sets the types of content to record.
-    #[must_use]
-    pub fn types(mut self, types: BitFlags<SourceType>) -> Self {
-        self.types = Some(types);
-        self
+impl SelectSourcesOptions {
+    options_setters! {
+        /// Sets whether to allow selecting multiple sources.
+        with_multiple(multiple: bool);
+        /// Sets how the cursor will be drawn on the screen cast stream.
+        with_cursor_mode(cursor_mode: impl Into<BitFlags<CursorMode>>);
+        /// Sets the types of content to record.
+        with_types(types: impl Into<BitFlags<SourceType>>);
+        /// Sets how the permission should be persisted.
+        with_persist_mode(persist_mode: PersistMode);
+        /// Sets the restore token received from a previous
+        /// [`ScreenCastProxy::start`] request.
+        with_restore_token(restore_token: impl Into<String>);
     }
 
+    /// Sets the restore data received from a previous session.
     #[must_use]
-    pub fn persist_mode(mut self, persist_mode: PersistMode) -> Self {
-        self.persist_mode = Some(persist_mode);
+    pub fn with_restore_data(mut self, data: RestoreData) -> Self {
+        self.restore_data = Some(data);
         self
     }
 
-    pub fn set_restore_token(&mut self, token: &str) {
-        self.restore_token = Some(token.to_string());
+    /// The restore data of a previous session, if set.
+    pub fn restore_data(&self) -> Option<&RestoreData> {
+        self.restore_data.as_ref()
     }
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
 /// Specified options for a [`ScreenCastProxy::start`] request.
 #[zvariant(signature = "dict")]
 struct StartCastOptions {
@@ -176,31 +352,70 @@ struct StartCastOptions {
     handle_token: HandleToken,
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Clone, PartialEq, Eq)]
 /// A response to a [`ScreenCastProxy::create_session`] request.
 #[zvariant(signature = "dict")]
-struct CreateSession {
+pub(crate) struct CreateSession {
     // TODO: investigate why this doesn't return an ObjectPath
     // replace with an ObjectPath once https://github.com/flatpak/xdg-desktop-portal/pull/609's merged
     /// A string that will be used as the last element of the session handle.
     session_handle: String,
 }
 
-#[derive(SerializeDict, DeserializeDict, Type)]
+#[derive(SerializeDict, DeserializeDict, Type, Clone, PartialEq)]
 /// A response to a [`ScreenCastProxy::start`] request.
 #[zvariant(signature = "dict")]
-struct Streams {
+pub struct Streams {
     streams: Vec<Stream>,
     restore_token: Option<String>,
+    restore_data: Option<RestoreData>,
+    // Not part of the response, set by `ScreenCastProxy::start`.
+    cursor_mode: Option<CursorMode>,
+}
+
+impl Streams {
+    /// The started streams.
+    pub fn streams(&self) -> &[Stream] {
+        &self.streams
+    }
+
+    /// A token that can be used to restore the session later on, only sent
+    /// if a [`PersistMode`] other than [`PersistMode::DoNot`] was requested.
+    pub fn restore_token(&self) -> Option<&str> {
+        self.restore_token.as_deref()
+    }
+
+    /// The compositor specific data that can be used to restore the session
+    /// later on.
+    pub fn restore_data(&self) -> Option<&RestoreData> {
+        self.restore_data.as_ref()
+    }
+
+    /// The cursor mode sent by [`ScreenCastProxy::select_sources`], after
+    /// the [`CursorModeFallback`] of the proxy. None if no mode was requested
+    /// or if the available ones aren't known.
+    pub fn cursor_mode(&self) -> Option<CursorMode> {
+        self.cursor_mode
+    }
+
+    /// The capabilities of the started session, see [`SessionCapabilities`].
+    pub fn capabilities(&self) -> SessionCapabilities {
+        SessionCapabilities::from(self)
+    }
 }
 
 impl Debug for Streams {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_list().entries(self.streams.iter()).finish()
+        f.debug_struct("Streams")
+            .field("streams", &self.streams)
+            .field("restore_token", &self.restore_token)
+            .field("restore_data", &self.restore_data)
+            .field("cursor_mode", &self.cursor_mode)
+            .finish()
     }
 }
 
-#[derive(Serialize, Deserialize, Type, Clone)]
+#[derive(Serialize, Deserialize, Type, Clone, PartialEq, Eq)]
 /// A PipeWire stream.
 pub struct Stream(u32, StreamProperties);
 
@@ -249,7 +464,7 @@ impl Debug for Stream {
             .finish()
     }
 }
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Clone)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Clone, PartialEq, Eq)]
 /// The stream properties.
 #[zvariant(signature = "dict")]
 struct StreamProperties {
@@ -259,23 +474,74 @@ struct StreamProperties {
     source_type: SourceType,
 }
 
+/// A session created by [`ScreenCastProxy::create_session`].
+pub type ScreenCastSession<'a> = SessionProxy<'a, ScreenCastPortal>;
+
+/// A virtual monitor created by [`ScreenCastProxy::create_virtual_output`].
+#[derive(Debug)]
+pub struct VirtualOutput<'a> {
+    session: ScreenCastSession<'a>,
+    stream: Stream,
+    size_hint: Option<(i32, i32)>,
+}
+
+impl<'a> VirtualOutput<'a> {
+    /// The session streaming the monitor, closing it removes the monitor.
+    pub fn session(&self) -> &ScreenCastSession<'a> {
+        &self.session
+    }
+
+    /// The stream of the monitor.
+    pub fn stream(&self) -> &Stream {
+        &self.stream
+    }
+
+    /// The size of the monitor reported by the portal, the size hint given to
+    /// [`ScreenCastProxy::create_virtual_output`] otherwise.
+    pub fn size(&self) -> Option<(i32, i32)> {
+        self.stream.size().or(self.size_hint)
+    }
+
+    /// The session streaming the monitor.
+    pub fn into_session(self) -> ScreenCastSession<'a> {
+        self.session
+    }
+}
+
 /// The interface lets sandboxed applications create screen cast sessions.
 ///
+/// The requested cursor mode is checked against the available ones, see
+/// [`CursorModeFallback`].
+///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.ScreenCast`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.ScreenCast).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.ScreenCast")]
-pub struct ScreenCastProxy<'a>(zbus::Proxy<'a>);
+pub struct ScreenCastProxy<'a>(
+    zbus::Proxy<'a>,
+    Option<std::time::Duration>,
+    CursorModeFallback,
+    // The cursor modes selected for the sessions, until they are started.
+    Arc<Mutex<HashMap<OwnedObjectPath, (CursorMode, ClosedFlag)>>>,
+);
+
+impl_try_from_proxy!(
+    ScreenCastProxy,
+    INTERFACE_SCREENCAST,
+    Default::default(),
+    Default::default()
+);
+impl_proxy_timeout!(ScreenCastProxy);
 
 impl<'a> ScreenCastProxy<'a> {
     /// Create a new instance of [`ScreenCastProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<ScreenCastProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.ScreenCast")?
+            .interface(INTERFACE_SCREENCAST)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None, Default::default(), Default::default()))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -283,6 +549,14 @@ impl<'a> ScreenCastProxy<'a> {
         &self.0
     }
 
+    /// Set what to do when the requested cursor mode isn't available,
+    /// [`CursorModeFallback::Closest`] by default.
+    #[must_use]
+    pub fn with_cursor_mode_fallback(mut self, fallback: CursorModeFallback) -> Self {
+        self.2 = fallback;
+        self
+    }
+
     /// Create a screen cast session.
     ///
     /// # Specifications
@@ -290,24 +564,21 @@ impl<'a> ScreenCastProxy<'a> {
     /// See also [`CreateSession`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-ScreenCast.CreateSession).
     #[doc(alias = "CreateSession")]
     #[doc(alias = "xdp_portal_create_screencast_session")]
-    pub async fn create_session(&self) -> Result<SessionProxy<'a>, Error> {
+    pub fn create_session(&self) -> PortalRequest<'_, ScreenCastSession<'a>> {
         let options = CreateSessionOptions::default();
-        let (session, proxy) = futures::try_join!(
-            call_request_method::<CreateSession, _>(
-                self.inner(),
-                &options.handle_token,
-                "CreateSession",
-                &options
-            )
-            .into_future(),
-            SessionProxy::from_unique_name(
-                self.inner().connection(),
-                &options.session_handle_token
-            )
-            .into_future(),
-        )?;
-        assert_eq!(proxy.inner().path().as_str(), &session.session_handle);
-        Ok(proxy)
+        let handle_token = options.handle_token.copy();
+        let session_handle_token = options.session_handle_token.copy();
+        call_request_method::<CreateSession, _>(self, &handle_token, "CreateSession", options).then(
+            move |session| async move {
+                let proxy: ScreenCastSession<'a> = SessionProxy::from_unique_name(
+                    self.inner().connection(),
+                    &session_handle_token,
+                )
+                .await?;
+                assert_eq!(proxy.inner().path().as_str(), &session.session_handle);
+                Ok(proxy)
+            },
+        )
     }
 
     /// Open a file descriptor to the PipeWire remote where the screen cast
@@ -315,8 +586,9 @@ impl<'a> ScreenCastProxy<'a> {
     ///
     /// # Arguments
     ///
-    /// * `session` - A [`SessionProxy`], created with
-    ///   [`create_session()`][`ScreenCastProxy::create_session`].
+    /// * `session` - A [`ScreenCastSession`], created with
+    ///   [`create_session()`][`ScreenCastProxy::create_session`], or a
+    ///   [`RemoteDesktopSession`](crate::desktop::remote_desktop::RemoteDesktopSession).
     ///
     /// # Returns
     ///
@@ -326,12 +598,14 @@ impl<'a> ScreenCastProxy<'a> {
     ///
     /// See also [`OpenPipeWireRemote`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-ScreenCast.OpenPipeWireRemote).
     #[doc(alias = "OpenPipeWireRemote")]
-    pub async fn open_pipe_wire_remote(&self, session: &SessionProxy<'_>) -> Result<RawFd, Error> {
+    pub async fn open_pipe_wire_remote(
+        &self,
+        session: &SessionProxy<'_, impl ScreenCastCompatible>,
+    ) -> Result<RawFd, Error> {
         // `options` parameter doesn't seems to be used yet
         // see https://github.com/flatpak/xdg-desktop-portal/blob/master/src/screen-cast.c#L812
         let options: HashMap<&str, Value<'_>> = HashMap::new();
-        let fd: OwnedFd =
-            call_method(self.inner(), "OpenPipeWireRemote", &(session, options)).await?;
+        let fd: OwnedFd = call_method(self, "OpenPipeWireRemote", &(session, options)).await?;
         Ok(fd.into_raw_fd())
     }
 
@@ -342,43 +616,52 @@ impl<'a> ScreenCastProxy<'a> {
     /// closed. An application may only attempt to select sources once per
     /// session.
     ///
+    /// A cursor mode that isn't available is replaced according to the
+    /// [`CursorModeFallback`] of the proxy before calling the portal.
+    ///
     /// # Arguments
     ///
-    /// * `session` - A [`SessionProxy`], created with
-    ///   [`create_session()`][`ScreenCastProxy::create_session`].
-    /// * `cursor_mode` - Sets how the cursor will be drawn on the screen cast
-    ///   stream.
-    /// * `types` - Sets the types of content to record.
-    /// * `multiple`- Sets whether to allow selecting multiple sources.
+    /// * `session` - A [`ScreenCastSession`], created with
+    ///   [`create_session()`][`ScreenCastProxy::create_session`], or a
+    ///   [`RemoteDesktopSession`](crate::desktop::remote_desktop::RemoteDesktopSession).
+    /// * `options` - A [`SelectSourcesOptions`].
     ///
     /// # Specifications
     ///
     /// See also [`SelectSources`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-ScreenCast.SelectSources).
     #[doc(alias = "SelectSources")]
-    pub async fn select_sources(
-        &self,
-        session: &SessionProxy<'_>,
-        cursor_mode: BitFlags<CursorMode>,
-        types: BitFlags<SourceType>,
-        multiple: bool,
-        restore_token: Option<&str>,
-        persist_mode: PersistMode,
-    ) -> Result<(), Error> {
-        let mut options = SelectSourcesOptions::default()
-            .cursor_mode(cursor_mode)
-            .multiple(multiple)
-            .types(types)
-            .persist_mode(persist_mode);
-        if let Some(token) = restore_token {
-            options.set_restore_token(token);
-        }
-        call_basic_response_method(
-            self.inner(),
-            &options.handle_token,
-            "SelectSources",
-            &(session, &options),
-        )
-        .await
+    pub fn select_sources<'p>(
+        &'p self,
+        session: &'p SessionProxy<'_, impl ScreenCastCompatible>,
+        mut options: SelectSourcesOptions,
+    ) -> PortalRequest<'p, ()> {
+        let handle_token = options.handle_token.copy();
+        let handle = request_handle(self, &handle_token, "SelectSources");
+        PortalRequest::new(handle, async move {
+            let mut selected = None;
+            if let Some(requested) = options.cursor_mode {
+                let available = match self.available_cursor_modes().await {
+                    Ok(available) => available,
+                    // The property is missing before the version 2 of the portal.
+                    Err(err) if is_unknown_property(&err) => BitFlags::empty(),
+                    Err(err) => return Err(err),
+                };
+                selected = self.2.resolve(requested, available)?;
+                if let Some(mode) = selected {
+                    options.cursor_mode = Some(mode.into());
+                }
+            }
+            call_basic_response_method(self, &handle_token, "SelectSources", (session, options))
+                .await?;
+            if let Some(mode) = selected {
+                let path = OwnedObjectPath::from(session.inner().path().to_owned());
+                let mut modes = self.3.lock().unwrap();
+                // Forget the sessions that were closed without being started.
+                modes.retain(|_, (_, closed)| !closed.is_closed());
+                modes.insert(path, (mode, session.closed_flag()));
+            }
+            Ok(())
+        })
     }
 
     /// Start the screen cast session.
@@ -390,57 +673,505 @@ impl<'a> ScreenCastProxy<'a> {
     ///
     /// # Arguments
     ///
-    /// * `session` - A [`SessionProxy`], created with
+    /// * `session` - A [`ScreenCastSession`], created with
     ///   [`create_session()`][`ScreenCastProxy::create_session`].
     /// * `identifier` - Identifier for the application window.
     ///
     /// # Return
     ///
-    /// A list of [`Stream`] and an optional restore token.
+    /// The started [`Streams`] alongside the optional restore token and data.
     ///
     /// # Specifications
     ///
     /// See also [`Start`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-ScreenCast.Start).
     #[doc(alias = "Start")]
-    pub async fn start(
-        &self,
-        session: &SessionProxy<'_>,
-        identifier: &WindowIdentifier,
-    ) -> Result<(Vec<Stream>, Option<String>), Error> {
+    pub fn start<'p>(
+        &'p self,
+        session: &'p ScreenCastSession<'_>,
+        identifier: &'p WindowIdentifier,
+    ) -> PortalRequest<'p, Streams> {
         let options = StartCastOptions::default();
-        let streams: Streams = call_request_method(
-            self.inner(),
-            &options.handle_token,
+        let handle_token = options.handle_token.copy();
+        call_window_request_method::<Streams, _>(
+            self,
+            identifier,
+            &handle_token,
             "Start",
-            &(session, &identifier, &options),
+            (session, identifier, options),
         )
-        .await?;
-        Ok((streams.streams.to_vec(), streams.restore_token))
+        .map_result(move |streams| {
+            // The session can't be started again, even if it failed.
+            let path = OwnedObjectPath::from(session.inner().path().to_owned());
+            let cursor_mode = self.3.lock().unwrap().remove(&path).map(|(mode, _)| mode);
+            let mut streams = streams?;
+            streams.cursor_mode = cursor_mode;
+            Ok(streams)
+        })
+    }
+
+    /// Create a virtual monitor and start streaming it, for the headless
+    /// remote desktop servers. **Experimental**, few backends offer the
+    /// [`SourceType::Virtual`] sources.
+    ///
+    /// The portal doesn't let the application choose the size of the monitor.
+    /// Compositors like Mutter size it after the format negotiated on the
+    /// PipeWire stream, `size_hint` is the size to negotiate when the portal
+    /// doesn't report one, see [`VirtualOutput::size`].
+    ///
+    /// Fails with [`Error::Unsupported`] if the portal doesn't offer virtual
+    /// sources, or doesn't start a virtual monitor.
+    ///
+    /// ```rust,no_run
+    /// use ashpd::{desktop::screencast::ScreenCastProxy, WindowIdentifier};
+    ///
+    /// async fn run() -> ashpd::Result<()> {
+    ///     let connection = zbus::Connection::session().await?;
+    ///     let proxy = ScreenCastProxy::new(&connection).await?;
+    ///     let output = proxy
+    ///         .create_virtual_output(&WindowIdentifier::default(), Some((1920, 1080)))
+    ///         .await?;
+    ///     println!("node id: {}", output.stream().pipe_wire_node_id());
+    ///     println!("size: {:?}", output.size());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn create_virtual_output(
+        &self,
+        identifier: &WindowIdentifier,
+        size_hint: Option<(i32, i32)>,
+    ) -> Result<VirtualOutput<'a>, Error> {
+        if !self
+            .available_source_types()
+            .await?
+            .contains(SourceType::Virtual)
+        {
+            return Err(Error::Unsupported);
+        }
+        let session = self.create_session().await?;
+        let result = self.start_virtual_output(&session, identifier).await;
+        match result {
+            Ok(stream) => Ok(VirtualOutput {
+                session,
+                stream,
+                size_hint,
+            }),
+            Err(err) => {
+                let _ = session.close().await;
+                Err(err)
+            }
+        }
+    }
+
+    async fn start_virtual_output(
+        &self,
+        session: &ScreenCastSession<'_>,
+        identifier: &WindowIdentifier,
+    ) -> Result<Stream, Error> {
+        let options = SelectSourcesOptions::default()
+            .with_types(SourceType::Virtual)
+            .with_multiple(false)
+            .with_persist_mode(PersistMode::DoNot);
+        self.select_sources(session, options).await?;
+        let streams = self.start(session, identifier).await?;
+        streams
+            .streams()
+            .iter()
+            .find(|stream| stream.source_type() == SourceType::Virtual)
+            .cloned()
+            .ok_or(Error::Unsupported)
+    }
+
+    /// The cursor mode sent by [`ScreenCastProxy::select_sources`] for
+    /// `session`, after the [`CursorModeFallback`] of the proxy.
+    ///
+    /// Meant for the remote desktop sessions, the screen cast ones get it
+    /// with [`Streams::cursor_mode`] once started.
+    pub fn selected_cursor_mode(
+        &self,
+        session: &SessionProxy<'_, impl ScreenCastCompatible>,
+    ) -> Option<CursorMode> {
+        let path = OwnedObjectPath::from(session.inner().path().to_owned());
+        self.3
+            .lock()
+            .unwrap()
+            .get(&path)
+            .filter(|(_, closed)| !closed.is_closed())
+            .map(|(mode, _)| *mode)
     }
 
     /// Available cursor mode.
     ///
+    /// Unknown bits sent by the backend are ignored, see [`TolerantBitFlags`].
+    ///
     /// # Specifications
     ///
     /// See also [`AvailableCursorModes`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-property-org-freedesktop-portal-ScreenCast.AvailableCursorModes).
     #[doc(alias = "AvailableCursorModes")]
     pub async fn available_cursor_modes(&self) -> Result<BitFlags<CursorMode>, Error> {
-        self.inner()
-            .get_property::<BitFlags<CursorMode>>("AvailableCursorModes")
+        get_property::<TolerantBitFlags<CursorMode>>(self, "AvailableCursorModes")
             .await
-            .map_err(From::from)
+            .map(|flags| flags.flags())
     }
 
     /// Available source types.
     ///
+    /// Unknown bits sent by the backend are ignored, see [`TolerantBitFlags`].
+    ///
     /// # Specifications
     ///
     /// See also [`AvailableSourceTypes`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-property-org-freedesktop-portal-ScreenCast.AvailableSourceTypes).
     #[doc(alias = "AvailableSourceTypes")]
     pub async fn available_source_types(&self) -> Result<BitFlags<SourceType>, Error> {
-        self.inner()
-            .get_property::<BitFlags<SourceType>>("AvailableSourceTypes")
+        get_property::<TolerantBitFlags<SourceType>>(self, "AvailableSourceTypes")
             .await
-            .map_err(From::from)
+            .map(|flags| flags.flags())
+    }
+}
+
+/// Whether starting a screen cast session with `restore_token`, received from
+/// a previous one, will prompt the user.
+///
+/// The restore tokens are only supported since the version 4 of the portal,
+/// [`PermissionStatus::Prompt`] is returned for the older ones or without a
+/// token.
+///
+/// **Note** the permission store is not reachable from inside of the sandbox.
+pub async fn permission_status(
+    app_id: &str,
+    restore_token: Option<&str>,
+) -> Result<PermissionStatus, Error> {
+    restore_token_status(INTERFACE_SCREENCAST, 4, "screencast", app_id, restore_token).await
+}
+
+/// Create a screen cast session, select its sources with `options` and start
+/// it, then run `f` with the session and its streams.
+///
+/// The session is closed once `f` completes, successfully or not, or if it
+/// is interrupted, the same way as with
+/// [`run_with_session`](crate::desktop::remote_desktop::run_with_session).
+///
+/// ```rust,no_run
+/// use ashpd::desktop::screencast::{self, SelectSourcesOptions, SourceType};
+/// use ashpd::WindowIdentifier;
+///
+/// async fn run() -> ashpd::Result<()> {
+///     let options = SelectSourcesOptions::default().with_types(SourceType::Monitor);
+///     screencast::run_with_session(
+///         &WindowIdentifier::default(),
+///         options,
+///         |_session, streams| async move {
+///             for stream in streams.streams() {
+///                 println!("node id: {}", stream.pipe_wire_node_id());
+///             }
+///             Ok(())
+///         },
+///     )
+///     .await
+/// }
+/// ```
+pub async fn run_with_session<T, F, Fut>(
+    identifier: &WindowIdentifier,
+    options: SelectSourcesOptions,
+    f: F,
+) -> Result<T, Error>
+where
+    F: FnOnce(ScreenCastSession<'static>, Streams) -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    let connection = session_connection().await?;
+    let proxy = ScreenCastProxy::new(&connection).await?;
+    let session = proxy.create_session().await?;
+    let guard = SessionGuard::new(&session);
+    proxy.select_sources(&session, options).await?;
+    let streams = proxy.start(&session, identifier).await?;
+    let result = f(session, streams).await;
+    guard.close_after(result).await
+}
+
+#[cfg(feature = "feature_pipewire")]
+#[derive(Clone, PartialEq, Eq)]
+/// A cursor bitmap sent alongside a [`CursorUpdate`].
+pub struct CursorBitmap {
+    format: u32,
+    size: (u32, u32),
+    stride: i32,
+    data: Vec<u8>,
+}
+
+#[cfg(feature = "feature_pipewire")]
+impl CursorBitmap {
+    /// The `spa_video_format` of the bitmap.
+    pub fn format(&self) -> u32 {
+        self.format
+    }
+
+    /// A tuple consisting of (width, height).
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// The number of bytes per line of the bitmap.
+    pub fn stride(&self) -> i32 {
+        self.stride
+    }
+
+    /// The pixels of the bitmap.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+#[cfg(feature = "feature_pipewire")]
+impl Debug for CursorBitmap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CursorBitmap")
+            .field("format", &self.format())
+            .field("size", &self.size())
+            .field("stride", &self.stride())
+            .finish()
+    }
+}
+
+#[cfg(feature = "feature_pipewire")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// The cursor metadata of a PipeWire buffer.
+///
+/// Sent with each frame of a stream when [`CursorMode::Metadata`] was
+/// requested, it lets the application draw the cursor itself.
+pub struct CursorUpdate {
+    id: u32,
+    position: (i32, i32),
+    hotspot: (i32, i32),
+    bitmap: Option<CursorBitmap>,
+}
+
+#[cfg(feature = "feature_pipewire")]
+impl CursorUpdate {
+    const CURSOR_SIZE: usize = 28;
+    const BITMAP_SIZE: usize = 20;
+
+    /// Parse the content of a `SPA_META_Cursor` buffer metadata, a
+    /// `spa_meta_cursor` optionally followed by a `spa_meta_bitmap`.
+    ///
+    /// Returns [`None`] if the metadata is invalid or too short.
+    pub fn from_meta(meta: &[u8]) -> Option<Self> {
+        let id = read_u32(meta, 0)?;
+        if id == 0 {
+            return None;
+        }
+        let position = (read_i32(meta, 8)?, read_i32(meta, 12)?);
+        let hotspot = (read_i32(meta, 16)?, read_i32(meta, 20)?);
+        let bitmap_offset = read_u32(meta, 24)? as usize;
+        let bitmap = if bitmap_offset >= Self::CURSOR_SIZE {
+            meta.get(bitmap_offset..).and_then(parse_bitmap)
+        } else {
+            None
+        };
+        Some(Self {
+            id,
+            position,
+            hotspot,
+            bitmap,
+        })
+    }
+
+    /// The cursor identifier.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// The cursor position in the stream coordinate space.
+    pub fn position(&self) -> (i32, i32) {
+        self.position
+    }
+
+    /// The hotspot of the cursor, relative to its bitmap.
+    pub fn hotspot(&self) -> (i32, i32) {
+        self.hotspot
+    }
+
+    /// The new cursor bitmap, [`None`] if the cursor image didn't change.
+    pub fn bitmap(&self) -> Option<&CursorBitmap> {
+        self.bitmap.as_ref()
+    }
+}
+
+#[cfg(feature = "feature_pipewire")]
+fn parse_bitmap(meta: &[u8]) -> Option<CursorBitmap> {
+    let format = read_u32(meta, 0)?;
+    if format == 0 {
+        return None;
+    }
+    let size = (read_u32(meta, 4)?, read_u32(meta, 8)?);
+    let stride = read_i32(meta, 12)?;
+    let offset = read_u32(meta, 16)? as usize;
+    if offset < CursorUpdate::BITMAP_SIZE {
+        return None;
+    }
+    let len = (stride.unsigned_abs() as usize).checked_mul(size.1 as usize)?;
+    let data = meta.get(offset..offset.checked_add(len)?)?.to_vec();
+    Some(CursorBitmap {
+        format,
+        size,
+        stride,
+        data,
+    })
+}
+
+#[cfg(feature = "feature_pipewire")]
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 4)?;
+    Some(u32::from_ne_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(feature = "feature_pipewire")]
+fn read_i32(bytes: &[u8], offset: usize) -> Option<i32> {
+    read_u32(bytes, offset).map(|v| v as i32)
+}
+
+#[cfg(test)]
+mod test {
+    use zbus::zvariant::Value;
+
+    use enumflags2::BitFlags;
+
+    use super::{
+        CreateSessionOptions, CursorMode, CursorModeFallback, PersistMode, RestoreData,
+        SelectSourcesOptions, SourceType, StartCastOptions,
+    };
+    use crate::{desktop::HandleToken, helpers::assert_serialized_dict, Error};
+
+    #[test]
+    fn u32_conversions() {
+        for source in BitFlags::<SourceType>::all().iter() {
+            assert_eq!(SourceType::try_from(u32::from(source)), Ok(source));
+            assert_eq!(u32::from(source), BitFlags::from(source).bits());
+        }
+        for mode in BitFlags::<CursorMode>::all().iter() {
+            assert_eq!(CursorMode::try_from(u32::from(mode)), Ok(mode));
+            assert_eq!(u32::from(mode), BitFlags::from(mode).bits());
+        }
+        assert_eq!(PersistMode::try_from(2), Ok(PersistMode::ExplicitlyRevoked));
+        assert_eq!(u32::from(PersistMode::DoNot), 0);
+        assert!(PersistMode::try_from(3).is_err());
+    }
+
+    #[test]
+    fn cursor_mode_fallback() {
+        let available = CursorMode::Hidden | CursorMode::Embedded;
+        let resolve = |fallback: CursorModeFallback, mode: CursorMode| {
+            fallback.resolve(mode.into(), available)
+        };
+        assert_eq!(
+            resolve(CursorModeFallback::Fail, CursorMode::Embedded).unwrap(),
+            Some(CursorMode::Embedded)
+        );
+        assert_eq!(
+            resolve(CursorModeFallback::Closest, CursorMode::Metadata).unwrap(),
+            Some(CursorMode::Embedded)
+        );
+        assert_eq!(
+            resolve(CursorModeFallback::Hidden, CursorMode::Metadata).unwrap(),
+            Some(CursorMode::Hidden)
+        );
+        assert!(matches!(
+            resolve(CursorModeFallback::Fail, CursorMode::Metadata),
+            Err(Error::InvalidArgument(_))
+        ));
+        assert_eq!(
+            CursorModeFallback::Closest
+                .resolve(CursorMode::Embedded.into(), CursorMode::Metadata.into())
+                .unwrap(),
+            Some(CursorMode::Metadata)
+        );
+        // Nothing to check against.
+        assert_eq!(
+            CursorModeFallback::Fail
+                .resolve(CursorMode::Metadata.into(), BitFlags::empty())
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn options() {
+        let options = CreateSessionOptions {
+            handle_token: HandleToken::default(),
+            session_handle_token: HandleToken::default(),
+        };
+        assert_serialized_dict(
+            &options,
+            &[("handle_token", "s"), ("session_handle_token", "s")],
+        );
+
+        let options = SelectSourcesOptions {
+            handle_token: HandleToken::default(),
+            types: Some(SourceType::Monitor | SourceType::Window),
+            multiple: Some(true),
+            cursor_mode: Some(CursorMode::Metadata.into()),
+            restore_token: Some("token".to_owned()),
+            restore_data: Some(RestoreData(
+                "GNOME".to_owned(),
+                1,
+                Value::from(42u32).into(),
+            )),
+            persist_mode: Some(PersistMode::Application),
+            raw: Default::default(),
+        };
+        assert_serialized_dict(
+            &options,
+            &[
+                ("cursor_mode", "u"),
+                ("handle_token", "s"),
+                ("multiple", "b"),
+                ("persist_mode", "u"),
+                ("restore_data", "(suv)"),
+                ("restore_token", "s"),
+                ("types", "u"),
+            ],
+        );
+
+        // The options are compared without their handle token.
+        let mut other = options.clone();
+        assert_ne!(other.handle_token.as_str(), options.handle_token.as_str());
+        assert_eq!(other, options);
+        other.multiple = Some(false);
+        assert_ne!(other, options);
+
+        let options = StartCastOptions {
+            handle_token: HandleToken::default(),
+        };
+        assert_serialized_dict(&options, &[("handle_token", "s")]);
+    }
+
+    #[cfg(feature = "backend")]
+    #[test]
+    fn create_virtual_output_unsupported() {
+        use futures::executor::block_on;
+        use zbus::dbus_interface;
+
+        use super::{ScreenCastProxy, PATH};
+        use crate::{backend::peer_connections, WindowIdentifier};
+
+        struct ScreenCast;
+
+        #[dbus_interface(name = "org.freedesktop.portal.ScreenCast")]
+        impl ScreenCast {
+            #[dbus_interface(property, name = "AvailableSourceTypes")]
+            fn available_source_types(&self) -> u32 {
+                (SourceType::Monitor | SourceType::Window).bits()
+            }
+        }
+
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            backend.object_server().at(PATH, ScreenCast).await.unwrap();
+            let proxy = ScreenCastProxy::new(&frontend).await.unwrap();
+            let err = proxy
+                .create_virtual_output(&WindowIdentifier::default(), None)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::Unsupported));
+        });
     }
 }
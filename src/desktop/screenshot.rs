@@ -62,15 +62,28 @@
 
 use std::fmt::Debug;
 
+use url::Url;
 use zbus::zvariant::{DeserializeDict, SerializeDict, Type};
 
-use super::{HandleToken, DESTINATION, PATH};
-use crate::{helpers::call_request_method, Error, WindowIdentifier};
+use super::{
+    permission_store::{self, PermissionStatus},
+    HandleToken, PortalRequest, DESTINATION, INTERFACE_SCREENSHOT, PATH,
+};
+use crate::{
+    helpers::{call_window_request_method, get_property, session_connection},
+    raw_options::RawOptions,
+    uri::Uri,
+    Error, WindowIdentifier,
+};
 
-#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, Default)]
-/// Specified options for a [`ScreenshotProxy::screenshot`] request.
+#[derive(Type, Clone, Debug, Default, PartialEq)]
+/// Specified options for a [`ScreenshotProxy::screenshot_with_options`]
+/// request.
+///
+/// The options are hints, [`ScreenshotProxy::capabilities`] tells which ones
+/// the running desktop knows about.
 #[zvariant(signature = "dict")]
-struct ScreenshotOptions {
+pub struct ScreenshotOptions {
     /// A string that will be used as the last element of the handle.
     handle_token: HandleToken,
     /// Whether the dialog should be modal.
@@ -78,40 +91,104 @@ struct ScreenshotOptions {
     /// Hint whether the dialog should offer customization before taking a
     /// screenshot.
     interactive: Option<bool>,
+    /// The options without a setter.
+    raw: RawOptions,
 }
 
+impl_options_dict!(ScreenshotOptions {
+    handle_token = "handle_token",
+    modal = "modal",
+    interactive = "interactive",
+});
+
 impl ScreenshotOptions {
-    /// Sets whether the dialog should be a modal.
-    #[must_use]
-    pub fn modal(mut self, modal: bool) -> Self {
-        self.modal = Some(modal);
-        self
+    options_setters! {
+        /// Sets whether the dialog should be a modal, defaults to `true`.
+        with_modal(modal: bool);
+        /// Sets whether the dialog should offer customization before a
+        /// screenshot, selecting the area or the window to capture for
+        /// example.
+        ///
+        /// Known by all the versions of the interface. Since version 2, a
+        /// non-interactive screenshot is only taken once the user allowed the
+        /// application to, the permission being remembered. An interactive one
+        /// doesn't need the permission, the user confirms each screenshot
+        /// instead. Older versions take the non-interactive screenshots
+        /// without asking.
+        with_interactive(interactive: bool);
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// What the Screenshot portal of the running desktop supports, returned by
+/// [`ScreenshotProxy::capabilities`].
+///
+/// Derived from the version of the interface, the backends of the desktops
+/// may still honor the options differently. GNOME and KDE show a dialog for
+/// the interactive screenshots for example, while others ignore the option and
+/// always capture the whole screen.
+pub struct ScreenshotCapabilities {
+    version: u32,
+}
+
+impl ScreenshotCapabilities {
+    /// The capabilities of the version `version` of the interface.
+    pub fn from_version(version: u32) -> Self {
+        Self { version }
+    }
+
+    /// The version of the interface.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Whether the [`ScreenshotOptions::modal`] option is known, always the
+    /// case.
+    pub fn modal(&self) -> bool {
+        true
+    }
+
+    /// Whether the [`ScreenshotOptions::interactive`] option is known, always
+    /// the case.
+    pub fn interactive(&self) -> bool {
+        self.version >= 1
     }
 
-    /// Sets whether the dialog should offer customization before a screenshot
-    /// or not.
-    #[must_use]
-    pub fn interactive(mut self, interactive: bool) -> Self {
-        self.interactive = Some(interactive);
-        self
+    /// Whether the non-interactive screenshots go through the permission
+    /// store, see [`permission_status`]. Requires version 2.
+    pub fn permission(&self) -> bool {
+        self.version >= 2
+    }
+
+    /// Whether [`ScreenshotProxy::pick_color`] is available. Requires version
+    /// 2.
+    pub fn pick_color(&self) -> bool {
+        self.version >= 2
     }
 }
 
-#[derive(DeserializeDict, SerializeDict, Clone, Type)]
+#[derive(DeserializeDict, SerializeDict, Clone, Type, PartialEq, Eq)]
 /// A response to a [`ScreenshotProxy::screenshot`] request.
 #[zvariant(signature = "dict")]
-struct Screenshot {
+pub(crate) struct Screenshot {
     /// The screenshot uri.
-    uri: String,
+    uri: Uri,
+}
+
+#[cfg(feature = "backend")]
+impl Screenshot {
+    pub(crate) fn new(uri: Url) -> Self {
+        Self { uri: Uri(uri) }
+    }
 }
 
 impl Debug for Screenshot {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&self.uri)
+        f.write_str(self.uri.0.as_str())
     }
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, Default)]
+#[derive(SerializeDict, DeserializeDict, Type, Clone, Debug, Default, PartialEq, Eq)]
 /// Specified options for a [`ScreenshotProxy::pick_color`] request.
 #[zvariant(signature = "dict")]
 struct PickColorOptions {
@@ -128,6 +205,13 @@ pub struct Color {
 }
 
 impl Color {
+    /// Create a new color from its normalized components.
+    pub fn new(red: f64, green: f64, blue: f64) -> Self {
+        Self {
+            color: [red, green, blue],
+        }
+    }
+
     /// Red.
     pub fn red(&self) -> f64 {
         self.color[0]
@@ -186,20 +270,23 @@ impl std::fmt::Display for Color {
 /// The interface lets sandboxed applications request a screenshot.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Screenshot`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Screenshot).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.Screenshot")]
-pub struct ScreenshotProxy<'a>(zbus::Proxy<'a>);
+pub struct ScreenshotProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(ScreenshotProxy, INTERFACE_SCREENSHOT);
+impl_proxy_timeout!(ScreenshotProxy);
 
 impl<'a> ScreenshotProxy<'a> {
     /// Create a new instance of [`ScreenshotProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<ScreenshotProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Screenshot")?
+            .interface(INTERFACE_SCREENSHOT)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -218,15 +305,16 @@ impl<'a> ScreenshotProxy<'a> {
     /// See also [`PickColor`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Screenshot.PickColor).
     #[doc(alias = "PickColor")]
     #[doc(alias = "xdp_portal_pick_color")]
-    pub async fn pick_color(&self, identifier: &WindowIdentifier) -> Result<Color, Error> {
+    pub fn pick_color<'p>(&'p self, identifier: &'p WindowIdentifier) -> PortalRequest<'p, Color> {
         let options = PickColorOptions::default();
-        call_request_method(
-            self.inner(),
-            &options.handle_token,
+        let handle_token = options.handle_token.copy();
+        call_window_request_method(
+            self,
+            identifier,
+            &handle_token,
             "PickColor",
-            &(&identifier, &options),
+            (identifier, options),
         )
-        .await
     }
 
     /// Takes a screenshot.
@@ -247,30 +335,66 @@ impl<'a> ScreenshotProxy<'a> {
     /// See also [`Screenshot`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Screenshot.Screenshot).
     #[doc(alias = "Screenshot")]
     #[doc(alias = "xdp_portal_take_screenshot")]
-    pub async fn screenshot(
-        &self,
-        identifier: &WindowIdentifier,
+    pub fn screenshot<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
         interactive: bool,
         modal: bool,
-    ) -> Result<String, Error> {
+    ) -> PortalRequest<'p, Url> {
         let options = ScreenshotOptions::default()
-            .interactive(interactive)
-            .modal(modal);
-        let response: Screenshot = call_request_method(
-            self.inner(),
-            &options.handle_token,
+            .with_interactive(interactive)
+            .with_modal(modal);
+        self.screenshot_with_options(identifier, options)
+    }
+
+    /// Takes a screenshot, like [`screenshot()`][`ScreenshotProxy::screenshot`]
+    /// with all the options.
+    ///
+    /// # Arguments
+    ///
+    /// * `identifier` - Identifier for the application window.
+    /// * `options` - A [`ScreenshotOptions`].
+    ///
+    /// # Returns
+    ///
+    /// The screenshot URI.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`Screenshot`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Screenshot.Screenshot).
+    #[doc(alias = "Screenshot")]
+    pub fn screenshot_with_options<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
+        options: ScreenshotOptions,
+    ) -> PortalRequest<'p, Url> {
+        let handle_token = options.handle_token.copy();
+        call_window_request_method::<Screenshot, _>(
+            self,
+            identifier,
+            &handle_token,
             "Screenshot",
-            &(&identifier, &options),
+            (identifier, options),
         )
-        .await?;
-        Ok(response.uri)
+        .map(|response| Ok(response.uri.0))
+    }
+
+    /// What the portal supports, probed from the version of the interface.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`version`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-property-org-freedesktop-portal-Screenshot.version).
+    pub async fn capabilities(&self) -> Result<ScreenshotCapabilities, Error> {
+        get_property::<u32>(self, "version")
+            .await
+            .map(ScreenshotCapabilities::from_version)
     }
 }
 
 #[doc(alias = "xdp_portal_pick_color")]
 /// A handy wrapper around [`ScreenshotProxy::pick_color`].
 pub async fn pick_color(identifier: &WindowIdentifier) -> Result<Color, Error> {
-    let connection = zbus::Connection::session().await?;
+    let connection = session_connection().await?;
     let proxy = ScreenshotProxy::new(&connection).await?;
     proxy.pick_color(identifier).await
 }
@@ -281,8 +405,54 @@ pub async fn take(
     identifier: &WindowIdentifier,
     interactive: bool,
     modal: bool,
-) -> Result<String, Error> {
-    let connection = zbus::Connection::session().await?;
+) -> Result<Url, Error> {
+    let connection = session_connection().await?;
     let proxy = ScreenshotProxy::new(&connection).await?;
     proxy.screenshot(identifier, interactive, modal).await
 }
+
+/// Whether taking a non interactive screenshot will prompt the user.
+///
+/// **Note** the permission store is not reachable from inside of the sandbox.
+pub async fn permission_status(app_id: &str) -> Result<PermissionStatus, Error> {
+    permission_store::permission_status("screenshot", "screenshot", app_id).await
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PickColorOptions, ScreenshotCapabilities, ScreenshotOptions};
+    use crate::{desktop::HandleToken, helpers::assert_serialized_dict};
+
+    #[test]
+    fn options() {
+        let options = ScreenshotOptions {
+            handle_token: HandleToken::default(),
+            modal: Some(true),
+            interactive: Some(true),
+            raw: Default::default(),
+        };
+        assert_serialized_dict(
+            &options,
+            &[("handle_token", "s"), ("interactive", "b"), ("modal", "b")],
+        );
+
+        let options = PickColorOptions {
+            handle_token: HandleToken::default(),
+        };
+        assert_serialized_dict(&options, &[("handle_token", "s")]);
+    }
+
+    #[test]
+    fn capabilities() {
+        let capabilities = ScreenshotCapabilities::from_version(1);
+        assert!(capabilities.modal());
+        assert!(capabilities.interactive());
+        assert!(!capabilities.permission());
+        assert!(!capabilities.pick_color());
+
+        let capabilities = ScreenshotCapabilities::from_version(2);
+        assert!(capabilities.interactive());
+        assert!(capabilities.permission());
+        assert!(capabilities.pick_color());
+    }
+}
@@ -21,10 +21,10 @@ use std::os::unix::prelude::AsRawFd;
 
 use zbus::zvariant::{DeserializeDict, Fd, SerializeDict, Type};
 
-use super::{DESTINATION, PATH};
+use super::{DESTINATION, INTERFACE_SECRET, PATH};
 use crate::{helpers::call_method, Error};
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
 /// Specified options for a [`SecretProxy::retrieve_secret`] request.
 #[zvariant(signature = "dict")]
 struct RetrieveOptions {
@@ -33,12 +33,11 @@ struct RetrieveOptions {
 }
 
 impl RetrieveOptions {
-    /// Sets the token received on a previous call to
-    /// [`SecretProxy::retrieve_secret`].
-    #[must_use]
-    pub fn token(mut self, token: &str) -> Self {
-        self.token = Some(token.to_string());
-        self
+    options_setters! {
+        @setters
+        /// Sets the token received on a previous call to
+        /// [`SecretProxy::retrieve_secret`].
+        with_token(token: impl Into<String>);
     }
 }
 
@@ -47,20 +46,23 @@ impl RetrieveOptions {
 /// sandbox.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Secret`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Secret).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.Secret")]
-pub struct SecretProxy<'a>(zbus::Proxy<'a>);
+pub struct SecretProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(SecretProxy, INTERFACE_SECRET);
+impl_proxy_timeout!(SecretProxy);
 
 impl<'a> SecretProxy<'a> {
     /// Create a new instance of [`SecretProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<SecretProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Secret")?
+            .interface(INTERFACE_SECRET)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -82,15 +84,24 @@ impl<'a> SecretProxy<'a> {
         token: Option<&str>,
     ) -> Result<String, Error> {
         let options = if let Some(token) = token {
-            RetrieveOptions::default().token(token)
+            RetrieveOptions::default().with_token(token)
         } else {
             RetrieveOptions::default()
         };
-        call_method(
-            self.inner(),
-            "RetrieveSecret",
-            &(Fd::from(fd.as_raw_fd()), options),
-        )
-        .await
+        call_method(self, "RetrieveSecret", &(Fd::from(fd.as_raw_fd()), options)).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RetrieveOptions;
+    use crate::helpers::assert_serialized_dict;
+
+    #[test]
+    fn options() {
+        let options = RetrieveOptions {
+            token: Some("token".to_owned()),
+        };
+        assert_serialized_dict(&options, &[("token", "s")]);
     }
 }
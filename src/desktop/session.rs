@@ -1,15 +1,111 @@
-use std::{collections::HashMap, convert::TryFrom, fmt::Debug};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    fmt::Debug,
+    future::Future,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Weak,
+    },
+};
 
-use serde::{Serialize, Serializer};
-use zbus::zvariant::{ObjectPath, OwnedValue, Signature, Type};
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize, Serializer};
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Signature, Type};
 
 use crate::{
-    desktop::{HandleToken, DESTINATION},
-    helpers::{call_method, receive_signal},
+    desktop::{
+        handle_path, screencast::RestoreData, HandleKind, HandleToken, DESTINATION,
+        INTERFACE_SESSION,
+    },
+    helpers::{call_method, receive_signal_stream},
     Error,
 };
 
-pub type SessionDetails = HashMap<String, OwnedValue>;
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default)]
+/// The details received alongside the [`SessionProxy::receive_closed`] signal.
+///
+/// The specifications don't define the content of the details, each portal
+/// backend is free to send extra information. The known keys are exposed with
+/// typed accessors, the rest can be retrieved with [`SessionDetails::get`] or
+/// [`SessionDetails::raw`].
+pub struct SessionDetails(HashMap<String, OwnedValue>);
+
+impl SessionDetails {
+    /// Retrieves the value of `key`, if it exists and can be converted to `T`.
+    pub fn get<T>(&self, key: &str) -> Option<T>
+    where
+        T: TryFrom<OwnedValue>,
+    {
+        self.0.get(key).and_then(|v| T::try_from(v.clone()).ok())
+    }
+
+    /// The ScreenCast restore data.
+    ///
+    /// Only sent by backends implementing version 4 or later of the ScreenCast
+    /// interface.
+    pub fn restore_data(&self) -> Option<RestoreData> {
+        self.get("restore_data")
+    }
+
+    /// The raw details.
+    pub fn raw(&self) -> &HashMap<String, OwnedValue> {
+        &self.0
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The portal a [`SessionProxy`] was created by.
+///
+/// The sessions are tied to their portal with it, passing a session of a
+/// portal to the methods of another one doesn't compile.
+pub trait SessionPortal: sealed::Sealed + Send + Sync + 'static {}
+
+/// The portals whose sessions the ScreenCast portal can select sources for,
+/// the ScreenCast and RemoteDesktop ones.
+pub trait ScreenCastCompatible: SessionPortal {}
+
+/// The portal of the sessions created by
+/// [`ScreenCastProxy::create_session`](crate::desktop::screencast::ScreenCastProxy::create_session).
+#[derive(Debug)]
+pub enum ScreenCastPortal {}
+
+/// The portal of the sessions created by
+/// [`RemoteDesktopProxy::create_session`](crate::desktop::remote_desktop::RemoteDesktopProxy::create_session).
+#[derive(Debug)]
+pub enum RemoteDesktopPortal {}
+
+/// The portal of the sessions created by
+/// [`LocationProxy::create_session`](crate::desktop::location::LocationProxy::create_session).
+#[derive(Debug)]
+pub enum LocationPortal {}
+
+/// The portal of the sessions created by
+/// [`InhibitProxy::create_monitor`](crate::desktop::inhibit::InhibitProxy::create_monitor).
+#[derive(Debug)]
+pub enum InhibitPortal {}
+
+/// The portal of the sessions created by
+/// [`GlobalShortcutsProxy::create_session`](crate::desktop::global_shortcuts::GlobalShortcutsProxy::create_session).
+#[derive(Debug)]
+pub enum GlobalShortcutsPortal {}
+
+impl sealed::Sealed for ScreenCastPortal {}
+impl sealed::Sealed for RemoteDesktopPortal {}
+impl sealed::Sealed for LocationPortal {}
+impl sealed::Sealed for InhibitPortal {}
+impl sealed::Sealed for GlobalShortcutsPortal {}
+impl SessionPortal for ScreenCastPortal {}
+impl SessionPortal for RemoteDesktopPortal {}
+impl SessionPortal for LocationPortal {}
+impl SessionPortal for InhibitPortal {}
+impl SessionPortal for GlobalShortcutsPortal {}
+impl ScreenCastCompatible for ScreenCastPortal {}
+impl ScreenCastCompatible for RemoteDesktopPortal {}
 
 /// The Session interface is shared by all portal interfaces that involve long
 /// lived sessions. When a method that creates a session is called, if
@@ -21,41 +117,65 @@ pub type SessionDetails = HashMap<String, OwnedValue>;
 /// and a signal [`SessionProxy::receive_closed`]. Whether it is allowed to
 /// directly call [`SessionProxy::close`] depends on the interface.
 ///
+/// The [`SessionPortal`] `P` is the portal that created the session, each one
+/// has an alias like
+/// [`LocationSession`](crate::desktop::location::LocationSession).
+///
+/// ```rust,compile_fail
+/// use ashpd::desktop::{location::LocationSession, remote_desktop::RemoteDesktopProxy};
+///
+/// async fn run(proxy: &RemoteDesktopProxy<'_>, session: &LocationSession<'_>) {
+///     // A Location session can't be used to forward input events.
+///     proxy.notify_pointer_motion(session, 10.0, 10.0).await;
+/// }
+/// ```
+///
+/// A session is `Send`, `Sync` and cheap to clone, a task forwarding the input
+/// events and another one managing the lifetime of the session can each hold
+/// a clone. The clones refer to the same portal session and share whether it
+/// is closed, see [`SessionProxy::is_closed`].
+///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Session`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Session).
 #[doc(alias = "org.freedesktop.portal.Session")]
-pub struct SessionProxy<'a>(zbus::Proxy<'a>);
+pub struct SessionProxy<'a, P: SessionPortal>(zbus::Proxy<'a>, Arc<SessionState>, PhantomData<P>);
+
+// The state shared by the clones of a session.
+#[derive(Debug, Default)]
+struct SessionState {
+    closed: AtomicBool,
+    // Held while closing, the concurrent closes wait for the reply.
+    closing: futures::lock::Mutex<()>,
+}
 
-impl<'a> SessionProxy<'a> {
+impl<'a, P: SessionPortal> SessionProxy<'a, P> {
     /// Create a new instance of [`SessionProxy`].
     ///
     /// **Note** A [`SessionProxy`] is not supposed to be created manually.
     pub(crate) async fn new(
         connection: &zbus::Connection,
         path: ObjectPath<'a>,
-    ) -> Result<SessionProxy<'a>, Error> {
+    ) -> Result<SessionProxy<'a, P>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Session")?
+            .interface(INTERFACE_SESSION)?
             .path(path)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, Default::default(), PhantomData))
     }
 
     pub(crate) async fn from_unique_name(
         connection: &zbus::Connection,
         handle_token: &HandleToken,
-    ) -> Result<SessionProxy<'a>, crate::Error> {
+    ) -> Result<SessionProxy<'a, P>, crate::Error> {
         let unique_name = connection.unique_name().unwrap();
-        let unique_identifier = unique_name.trim_start_matches(':').replace('.', "_");
-        let path = ObjectPath::try_from(format!(
-            "/org/freedesktop/portal/desktop/session/{}/{}",
-            unique_identifier, handle_token
-        ))
-        .unwrap();
+        let path = handle_path(HandleKind::Session, unique_name, handle_token.as_str())?;
         #[cfg(feature = "log")]
-        tracing::info!("Creating a org.freedesktop.portal.Session {}", path);
-        SessionProxy::new(connection, path).await
+        tracing::info!(
+            "Creating a org.freedesktop.portal.Session {}",
+            path.as_str()
+        );
+        SessionProxy::new(connection, path.into_inner()).await
     }
 
     /// Get a reference to the underlying Proxy.
@@ -69,8 +189,25 @@ impl<'a> SessionProxy<'a> {
     ///
     /// See also [`Closed`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-Session.Closed).
     #[doc(alias = "Closed")]
-    pub async fn receive_closed(&self) -> Result<SessionDetails, Error> {
-        receive_signal(self.inner(), "Closed").await
+    pub async fn receive_closed(
+        &self,
+    ) -> Result<impl Stream<Item = SessionDetails> + Unpin + 'a, Error> {
+        let state = self.1.clone();
+        let closed = receive_signal_stream(&self.0, "Closed").await?;
+        Ok(closed.inspect(move |_| state.closed.store(true, Ordering::SeqCst)))
+    }
+
+    /// Whether the session was closed, through this session or one of its
+    /// clones, or the portal emitted the [`SessionProxy::receive_closed`]
+    /// signal to one of the streams listening to it.
+    pub fn is_closed(&self) -> bool {
+        self.1.closed.load(Ordering::SeqCst)
+    }
+
+    // Tells whether the session was closed or dropped, without keeping it
+    // alive.
+    pub(crate) fn closed_flag(&self) -> ClosedFlag {
+        ClosedFlag(Arc::downgrade(&self.1))
     }
 
     /// Closes the portal session to which this object refers and ends all
@@ -79,13 +216,152 @@ impl<'a> SessionProxy<'a> {
     /// # Specifications
     ///
     /// See also [`Close`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Session.Close).
+    ///
+    /// Closing a session that is already closed, see
+    /// [`SessionProxy::is_closed`], does nothing. A close made while another
+    /// clone is closing the session waits for it to complete, and only calls
+    /// the portal again if it failed.
     #[doc(alias = "Close")]
     pub async fn close(&self) -> Result<(), Error> {
-        call_method(self.inner(), "Close", &()).await
+        self.1
+            .close_with(call_method::<(), _>(self.inner(), "Close", &()))
+            .await
     }
 }
 
-impl<'a> Serialize for SessionProxy<'a> {
+impl SessionState {
+    // Marks the session closed once `close` succeeds, unless it was closed
+    // already.
+    async fn close_with(
+        &self,
+        close: impl Future<Output = Result<(), Error>>,
+    ) -> Result<(), Error> {
+        let _closing = self.closing.lock().await;
+        if self.closed.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        close.await?;
+        self.closed.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+// See `SessionProxy::closed_flag`.
+#[derive(Debug, Clone)]
+pub(crate) struct ClosedFlag(Weak<SessionState>);
+
+impl ClosedFlag {
+    pub(crate) fn is_closed(&self) -> bool {
+        match self.0.upgrade() {
+            Some(state) => state.closed.load(Ordering::SeqCst),
+            None => true,
+        }
+    }
+}
+
+// Closes a session once dropped, on a panic or a cancellation for example.
+// The portal reply isn't waited for, see `SessionGuard::close` otherwise.
+//
+// The close on drop is a task spawned on the executor of the connection, it
+// only runs while that executor is ticked when the connection was built with
+// `internal_executor(false)`. `SessionGuard::close` doesn't depend on it.
+pub(crate) struct SessionGuard {
+    connection: zbus::Connection,
+    path: Option<OwnedObjectPath>,
+    state: Arc<SessionState>,
+}
+
+impl SessionGuard {
+    pub(crate) fn new<P: SessionPortal>(session: &SessionProxy<'_, P>) -> Self {
+        Self {
+            connection: session.inner().connection().clone(),
+            path: Some(session.inner().path().to_owned().into()),
+            state: session.1.clone(),
+        }
+    }
+
+    // A session closed already, through its proxy or by the portal, isn't an
+    // error.
+    async fn close_path(
+        connection: &zbus::Connection,
+        path: &OwnedObjectPath,
+    ) -> Result<(), Error> {
+        let result = connection
+            .call_method(
+                Some(DESTINATION),
+                path.as_str(),
+                Some(INTERFACE_SESSION),
+                "Close",
+                &(),
+            )
+            .await;
+        match result {
+            Ok(_) => Ok(()),
+            Err(zbus::Error::MethodError(name, _, _))
+                if name.as_str() == "org.freedesktop.DBus.Error.UnknownObject" =>
+            {
+                Ok(())
+            }
+            Err(err) => Err(Error::Call {
+                interface: INTERFACE_SESSION.to_owned(),
+                method: "Close".to_owned(),
+                handle: None,
+                correlation_id: crate::correlation::current().map(|id| id.to_string()),
+                source: Box::new(err.into()),
+            }),
+        }
+    }
+
+    // Close the session and wait for the reply.
+    pub(crate) async fn close(mut self) -> Result<(), Error> {
+        match self.path.take() {
+            Some(path) => {
+                self.state
+                    .close_with(Self::close_path(&self.connection, &path))
+                    .await
+            }
+            None => Ok(()),
+        }
+    }
+
+    // Close the session once `result` is available, the error of `result`
+    // takes precedence over the one of the close.
+    pub(crate) async fn close_after<T>(self, result: Result<T, Error>) -> Result<T, Error> {
+        let closed = self.close().await;
+        let value = result?;
+        closed?;
+        Ok(value)
+    }
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        let path = match self.path.take() {
+            Some(path) if !self.state.closed.load(Ordering::SeqCst) => path,
+            _ => return,
+        };
+        let connection = self.connection.clone();
+        let state = self.state.clone();
+        self.connection
+            .executor()
+            .spawn(async move {
+                let closed = state.close_with(Self::close_path(&connection, &path));
+                if let Err(_err) = closed.await {
+                    #[cfg(feature = "log")]
+                    tracing::warn!("Failed to close the session {}: {}", path.as_str(), _err);
+                }
+            })
+            .detach();
+    }
+}
+
+impl<'a, P: SessionPortal> Clone for SessionProxy<'a, P> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), self.1.clone(), PhantomData)
+    }
+}
+
+impl<'a, P: SessionPortal> Serialize for SessionProxy<'a, P> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
@@ -94,16 +370,118 @@ impl<'a> Serialize for SessionProxy<'a> {
     }
 }
 
-impl<'a> Type for SessionProxy<'a> {
+impl<'a, P: SessionPortal> Type for SessionProxy<'a, P> {
     fn signature() -> Signature<'static> {
         ObjectPath::signature()
     }
 }
 
-impl<'a> Debug for SessionProxy<'a> {
+impl<'a, P: SessionPortal> Debug for SessionProxy<'a, P> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_tuple("SessionProxy")
-            .field(&self.inner().path().as_str())
+        f.debug_struct("SessionProxy")
+            .field(
+                "portal",
+                &std::any::type_name::<P>()
+                    .rsplit("::")
+                    .next()
+                    .unwrap_or_default(),
+            )
+            .field("destination", &self.inner().destination().as_str())
+            .field("path", &self.inner().path().as_str())
             .finish()
     }
 }
+
+#[cfg(all(test, feature = "backend"))]
+mod test {
+    use std::{
+        sync::atomic::{AtomicU32, Ordering},
+        time::Duration,
+    };
+
+    use async_io::Timer;
+    use futures::executor::block_on;
+    use zbus::{dbus_interface, fdo, zvariant::ObjectPath};
+
+    use super::{SessionGuard, SessionProxy};
+    use crate::{
+        backend::peer_connections,
+        desktop::{screencast::ScreenCastSession, ScreenCastPortal},
+    };
+
+    const PATH: &str = "/org/freedesktop/portal/desktop/session/1_42/ashpd_test";
+
+    // Fails the first close, after a delay letting another one start.
+    #[derive(Default)]
+    struct Session(AtomicU32);
+
+    #[dbus_interface(name = "org.freedesktop.portal.Session")]
+    impl Session {
+        async fn close(&self) -> fdo::Result<()> {
+            let calls = self.0.fetch_add(1, Ordering::SeqCst);
+            Timer::after(Duration::from_millis(20)).await;
+            match calls {
+                0 => Err(fdo::Error::Failed("Busy".to_owned())),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    async fn session(
+        backend: &zbus::Connection,
+        frontend: &zbus::Connection,
+    ) -> ScreenCastSession<'static> {
+        backend
+            .object_server()
+            .at(PATH, Session::default())
+            .await
+            .unwrap();
+        SessionProxy::<ScreenCastPortal>::new(frontend, ObjectPath::try_from(PATH).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn close_calls(backend: &zbus::Connection) -> u32 {
+        let server = backend.object_server();
+        let calls = server.interface::<_, Session>(PATH).await.unwrap();
+        let calls = calls.get().await.0.load(Ordering::SeqCst);
+        calls
+    }
+
+    #[test]
+    fn concurrent_close() {
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            let session = session(&backend, &frontend).await;
+            let clone = session.clone();
+
+            // The second close waits for the first one, which failed.
+            let (first, second) = futures::join!(session.close(), clone.close());
+            assert!(first.is_err());
+            assert!(second.is_ok());
+            assert!(session.is_closed());
+            session.close().await.unwrap();
+            assert_eq!(close_calls(&backend).await, 2);
+        });
+    }
+
+    #[test]
+    fn guard_close() {
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            let session = session(&backend, &frontend).await;
+
+            // A failed close of the guard leaves the session open.
+            assert!(SessionGuard::new(&session).close().await.is_err());
+            assert!(!session.is_closed());
+            SessionGuard::new(&session).close().await.unwrap();
+            assert!(session.is_closed());
+
+            // Neither the proxy nor a dropped guard close it again.
+            session.close().await.unwrap();
+            drop(SessionGuard::new(&session));
+            Timer::after(Duration::from_millis(50)).await;
+            assert_eq!(close_calls(&backend).await, 2);
+        });
+    }
+}
@@ -0,0 +1,246 @@
+//! The accessibility preferences of the user, to adapt the interface of the
+//! application to them.
+//!
+//! The standard `org.freedesktop.appearance` keys are read first. With the
+//! `desktop-settings-extras` feature, the GNOME and KDE settings are read as
+//! well when the portal doesn't expose them.
+//!
+//! ```rust,no_run
+//! use ashpd::desktop::settings::{
+//!     accessibility::{Contrast, Motion},
+//!     SettingsProxy,
+//! };
+//! use futures::StreamExt;
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::Connection::session().await?;
+//!     let proxy = SettingsProxy::new(&connection).await?;
+//!
+//!     let animate = proxy.motion().await? == Motion::NoPreference;
+//!     println!("Animations enabled: {}", animate);
+//!
+//!     let mut changes = proxy.receive_contrast_changed().await?;
+//!     while let Some(contrast) = changes.next().await {
+//!         println!("High contrast: {}", contrast == Contrast::High);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use std::convert::TryFrom;
+
+use futures::{future::ready, Stream, StreamExt};
+use zbus::zvariant::OwnedValue;
+
+use super::{Setting, SettingsProxy, APPEARANCE};
+use crate::Error;
+
+#[cfg(feature = "desktop-settings-extras")]
+const GNOME_A11Y_INTERFACE: &str = "org.gnome.desktop.a11y.interface";
+#[cfg(feature = "desktop-settings-extras")]
+const KDE_KDE: &str = "org.kde.kdeglobals.KDE";
+
+/// The contrast preferred by the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Contrast {
+    /// No preference.
+    NoPreference,
+    /// Prefers a higher contrast.
+    High,
+}
+
+/// Whether the user prefers the motion and animations to be reduced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Motion {
+    /// No preference.
+    NoPreference,
+    /// Prefers to reduce the animations.
+    Reduced,
+}
+
+// The contrast from one of the keys storing it, None for the other keys or an
+// unexpected value.
+fn contrast(namespace: &str, key: &str, value: &OwnedValue) -> Option<Contrast> {
+    let high = match (namespace, key) {
+        (APPEARANCE, "contrast") => u32::try_from(value).ok()? == 1,
+        #[cfg(feature = "desktop-settings-extras")]
+        (GNOME_A11Y_INTERFACE, "high-contrast") => bool::try_from(value).ok()?,
+        _ => return None,
+    };
+    Some(if high {
+        Contrast::High
+    } else {
+        Contrast::NoPreference
+    })
+}
+
+// Same as `contrast`, for the reduced motion.
+fn motion(namespace: &str, key: &str, value: &OwnedValue) -> Option<Motion> {
+    let reduced = match (namespace, key) {
+        (APPEARANCE, "reduced-motion") => u32::try_from(value).ok()? == 1,
+        #[cfg(feature = "desktop-settings-extras")]
+        (super::GNOME_INTERFACE, "enable-animations") => !bool::try_from(value).ok()?,
+        // A factor of 0 makes the animations instant.
+        #[cfg(feature = "desktop-settings-extras")]
+        (KDE_KDE, "AnimationDurationFactor") => f64::try_from(value).ok()? == 0.0,
+        _ => return None,
+    };
+    Some(if reduced {
+        Motion::Reduced
+    } else {
+        Motion::NoPreference
+    })
+}
+
+// The `(namespace, key)` storing the contrast, in the order they are read.
+fn contrast_keys() -> &'static [(&'static str, &'static str)] {
+    &[
+        (APPEARANCE, "contrast"),
+        #[cfg(feature = "desktop-settings-extras")]
+        (GNOME_A11Y_INTERFACE, "high-contrast"),
+    ]
+}
+
+fn motion_keys() -> &'static [(&'static str, &'static str)] {
+    &[
+        (APPEARANCE, "reduced-motion"),
+        #[cfg(feature = "desktop-settings-extras")]
+        (super::GNOME_INTERFACE, "enable-animations"),
+        #[cfg(feature = "desktop-settings-extras")]
+        (KDE_KDE, "AnimationDurationFactor"),
+    ]
+}
+
+impl<'a> SettingsProxy<'a> {
+    // Reads the first of `keys` known to the portal, the error of the first
+    // one if none is.
+    async fn read_first<T>(
+        &self,
+        keys: &[(&str, &str)],
+        parse: fn(&str, &str, &OwnedValue) -> Option<T>,
+    ) -> Result<T, Error> {
+        let mut first_error = None;
+        for (namespace, key) in keys {
+            match self.read_raw(namespace, key).await {
+                Ok(value) => {
+                    if let Some(value) = parse(namespace, key, &value) {
+                        return Ok(value);
+                    }
+                    first_error.get_or_insert_with(|| {
+                        Error::ParseError(format!("Unexpected value of {}.{}", namespace, key))
+                    });
+                }
+                Err(err) => {
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+        Err(first_error.unwrap_or(Error::NoResponse))
+    }
+
+    fn changes_of<T>(
+        changes: impl Stream<Item = Setting> + Unpin + 'a,
+        parse: fn(&str, &str, &OwnedValue) -> Option<T>,
+    ) -> impl Stream<Item = T> + Unpin + 'a
+    where
+        T: 'a,
+    {
+        changes.filter_map(move |setting| {
+            ready(parse(setting.namespace(), setting.key(), setting.value()))
+        })
+    }
+
+    /// The contrast preferred by the user, from the `contrast` key of
+    /// `org.freedesktop.appearance`.
+    pub async fn contrast(&self) -> Result<Contrast, Error> {
+        self.read_first(contrast_keys(), contrast).await
+    }
+
+    /// Listen to the changes of the contrast preferred by the user, see
+    /// [`SettingsProxy::contrast`].
+    pub async fn receive_contrast_changed(
+        &self,
+    ) -> Result<impl Stream<Item = Contrast> + Unpin + 'a, Error> {
+        Ok(Self::changes_of(
+            self.receive_setting_changed().await?,
+            contrast,
+        ))
+    }
+
+    /// Whether the user prefers to reduce the animations, from the
+    /// `reduced-motion` key of `org.freedesktop.appearance`.
+    pub async fn motion(&self) -> Result<Motion, Error> {
+        self.read_first(motion_keys(), motion).await
+    }
+
+    /// Listen to the changes of the motion preference of the user, see
+    /// [`SettingsProxy::motion`].
+    pub async fn receive_motion_changed(
+        &self,
+    ) -> Result<impl Stream<Item = Motion> + Unpin + 'a, Error> {
+        Ok(Self::changes_of(
+            self.receive_setting_changed().await?,
+            motion,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use zbus::zvariant::{OwnedValue, Value};
+
+    use super::{contrast, motion, Contrast, Motion};
+    use crate::desktop::settings::APPEARANCE;
+
+    fn value<'a>(value: impl Into<Value<'a>>) -> OwnedValue {
+        value.into().into()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            contrast(APPEARANCE, "contrast", &value(1u32)),
+            Some(Contrast::High)
+        );
+        assert_eq!(
+            contrast(APPEARANCE, "contrast", &value(0u32)),
+            Some(Contrast::NoPreference)
+        );
+        assert_eq!(contrast(APPEARANCE, "contrast", &value("high")), None);
+        assert_eq!(contrast(APPEARANCE, "color-scheme", &value(1u32)), None);
+        assert_eq!(
+            motion(APPEARANCE, "reduced-motion", &value(1u32)),
+            Some(Motion::Reduced)
+        );
+    }
+
+    #[cfg(feature = "desktop-settings-extras")]
+    #[test]
+    fn extras() {
+        assert_eq!(
+            contrast(
+                "org.gnome.desktop.a11y.interface",
+                "high-contrast",
+                &value(true)
+            ),
+            Some(Contrast::High)
+        );
+        assert_eq!(
+            motion(
+                "org.gnome.desktop.interface",
+                "enable-animations",
+                &value(false)
+            ),
+            Some(Motion::Reduced)
+        );
+        assert_eq!(
+            motion(
+                "org.kde.kdeglobals.KDE",
+                "AnimationDurationFactor",
+                &value(0.5f64)
+            ),
+            Some(Motion::NoPreference)
+        );
+    }
+}
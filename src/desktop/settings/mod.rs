@@ -0,0 +1,366 @@
+//! ```rust,no_run
+//! use ashpd::desktop::settings::SettingsProxy;
+//! use futures::StreamExt;
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::Connection::session().await?;
+//!     let proxy = SettingsProxy::new(&connection).await?;
+//!
+//!     println!(
+//!         "{:#?}",
+//!         proxy
+//!             .read::<String>("org.gnome.desktop.interface", "clock-format")
+//!             .await?
+//!     );
+//!
+//!     let settings = proxy.read_all(&["org.gnome.desktop.interface"]).await?;
+//!     println!("{:#?}", settings);
+//!
+//!     let mut changes = proxy.receive_setting_changed().await?;
+//!     while let Some(setting) = changes.next().await {
+//!         println!("{}", setting.namespace());
+//!         println!("{}", setting.key());
+//!         println!("{:#?}", setting.value());
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use std::{collections::HashMap, convert::TryFrom, fmt::Debug};
+
+use futures::{future::ready, Stream, StreamExt};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use zbus::zvariant::{OwnedValue, Type};
+
+use super::{DESTINATION, INTERFACE_SETTINGS, PATH};
+use crate::Error;
+
+/// The preferences of the user related to accessibility.
+pub mod accessibility;
+
+// The namespace of the standard appearance settings.
+const APPEARANCE: &str = "org.freedesktop.appearance";
+
+/// A HashMap of the <key, value> settings found on a specific namespace.
+pub type Namespace = HashMap<String, OwnedValue>;
+
+#[derive(Serialize, Clone, Deserialize, Type)]
+/// A specific `namespace.key = value` setting.
+pub struct Setting(String, String, OwnedValue);
+
+impl Setting {
+    /// The setting namespace.
+    pub fn namespace(&self) -> &str {
+        &self.0
+    }
+
+    /// The setting key.
+    pub fn key(&self) -> &str {
+        &self.1
+    }
+
+    /// The setting value.
+    pub fn value(&self) -> &OwnedValue {
+        &self.2
+    }
+}
+
+impl std::fmt::Debug for Setting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Setting")
+            .field("namespace", &self.namespace())
+            .field("key", &self.key())
+            .field("value", self.value())
+            .finish()
+    }
+}
+
+/// Whether `namespace` is matched by the `filters` of
+/// [`SettingsProxy::read_all`].
+///
+/// An empty list or one containing an empty string matches all the
+/// namespaces. A filter ending with `*` matches the namespaces starting with
+/// the rest of it, the other ones only match the namespace equal to them.
+///
+/// ```rust
+/// use ashpd::desktop::settings::namespace_matches;
+///
+/// assert!(namespace_matches(&["org.gnome.*"], "org.gnome.desktop.interface"));
+/// assert!(namespace_matches(&["org.freedesktop.appearance"], "org.freedesktop.appearance"));
+/// assert!(!namespace_matches(&["org.freedesktop"], "org.freedesktop.appearance"));
+/// assert!(namespace_matches(&[] as &[&str], "org.kde.kdeglobals.General"));
+/// ```
+pub fn namespace_matches(filters: &[impl AsRef<str>], namespace: &str) -> bool {
+    filters.is_empty()
+        || filters.iter().any(|filter| {
+            let filter = filter.as_ref();
+            match filter.strip_suffix('*') {
+                Some(prefix) => namespace.starts_with(prefix),
+                None => filter.is_empty() || filter == namespace,
+            }
+        })
+}
+
+/// The system's preferred color scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorScheme {
+    /// No preference
+    NoPreference,
+    /// Prefers dark appearance
+    PreferDark,
+    /// Prefers light appearance
+    PreferLight,
+}
+
+/// The namespace of the GNOME interface settings.
+#[cfg(feature = "desktop-settings-extras")]
+pub const GNOME_INTERFACE: &str = "org.gnome.desktop.interface";
+/// The namespace of the general KDE settings, from `kdeglobals`.
+#[cfg(feature = "desktop-settings-extras")]
+pub const KDE_GENERAL: &str = "org.kde.kdeglobals.General";
+
+/// The format of the clock, from the `clock-format` GNOME setting.
+#[cfg(feature = "desktop-settings-extras")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockFormat {
+    /// `12h`, with AM/PM.
+    TwelveHour,
+    /// `24h`.
+    TwentyFourHour,
+}
+
+#[cfg(feature = "desktop-settings-extras")]
+impl std::str::FromStr for ClockFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "12h" => Ok(ClockFormat::TwelveHour),
+            "24h" => Ok(ClockFormat::TwentyFourHour),
+            _ => Err(Error::ParseError(
+                "Failed to parse clock format, invalid value".to_string(),
+            )),
+        }
+    }
+}
+
+// KDE stores the fonts as a `QFont` description, `Noto Sans,10,-1,5,50,...`,
+// converted to the `Noto Sans 10` form of GNOME.
+#[cfg(feature = "desktop-settings-extras")]
+fn kde_font_name(font: &str) -> String {
+    let mut fields = font.split(',');
+    let family = fields.next().unwrap_or_default();
+    match fields.next().and_then(|size| size.parse::<f64>().ok()) {
+        Some(size) if size > 0.0 => format!("{} {}", family, size),
+        _ => family.to_owned(),
+    }
+}
+
+/// The interface provides read-only access to a small number of host settings
+/// required for toolkits similar to XSettings. It is not for general purpose
+/// settings.
+///
+/// Wrapper of the DBus interface: [`org.freedesktop.portal.Settings`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Settings).
+#[derive(Clone)]
+#[doc(alias = "org.freedesktop.portal.Settings")]
+pub struct SettingsProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(SettingsProxy, INTERFACE_SETTINGS);
+impl_proxy_timeout!(SettingsProxy);
+
+impl<'a> SettingsProxy<'a> {
+    /// Create a new instance of [`SettingsProxy`].
+    pub async fn new(connection: &zbus::Connection) -> Result<SettingsProxy<'a>, Error> {
+        let proxy = zbus::ProxyBuilder::new_bare(connection)
+            .interface(INTERFACE_SETTINGS)?
+            .path(PATH)?
+            .destination(DESTINATION)?
+            .build()
+            .await?;
+        Ok(Self(proxy, None))
+    }
+
+    /// Get a reference to the underlying Proxy.
+    pub fn inner(&self) -> &zbus::Proxy<'_> {
+        &self.0
+    }
+
+    /// Reads a single value. Returns an error on any unknown namespace or key.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespaces` - List of namespaces to filter results by.
+    ///
+    /// If `namespaces` is an empty array or contains an empty string it matches
+    /// all. Globing is supported but only for trailing sections, e.g.
+    /// `org.example.*`.
+    ///
+    /// # Returns
+    ///
+    /// A `HashMap` of namespaces to its keys and values.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`ReadAll`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Settings.ReadAll).
+    #[doc(alias = "ReadAll")]
+    pub async fn read_all(
+        &self,
+        namespaces: &[impl AsRef<str> + Type + Serialize + Debug],
+    ) -> Result<HashMap<String, Namespace>, Error> {
+        let namespaces = namespaces.iter().map(AsRef::as_ref).collect();
+        self.read_all_raw(namespaces).await
+    }
+
+    /// Reads a single value. Returns an error on any unknown namespace or key.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Namespace to look up key in.
+    /// * `key` - The key to get.
+    ///
+    /// # Returns
+    ///
+    /// The value `key` is to to as a `zvariant::OwnedValue`.
+    ///
+    /// # Specifications
+    ///
+    /// See also [`Read`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Settings.Read).
+    #[doc(alias = "Read")]
+    pub async fn read<T>(&self, namespace: &str, key: &str) -> Result<T, Error>
+    where
+        T: TryFrom<OwnedValue> + DeserializeOwned + Type,
+        Error: From<<T as TryFrom<OwnedValue>>::Error>,
+    {
+        let value = self.read_raw(namespace, key).await?;
+        T::try_from(value).map_err(From::from)
+    }
+
+    /// Reads the value of namespace: `org.freedesktop.appearance` and `color-scheme` key.
+    pub async fn color_scheme(&self) -> Result<ColorScheme, Error> {
+        let scheme = match self.read::<u32>(APPEARANCE, "color-scheme").await? {
+            1 => ColorScheme::PreferDark,
+            2 => ColorScheme::PreferLight,
+            _ => ColorScheme::NoPreference,
+        };
+        Ok(scheme)
+    }
+
+    /// Listen to changes of the namespace `org.freedesktop.appearance` for `color-scheme` key.
+    pub async fn receive_color_scheme_changed(
+        &self,
+    ) -> Result<impl Stream<Item = ColorScheme> + Unpin + 'a, Error> {
+        Ok(self.receive_setting_changed().await?.filter_map(|setting| {
+            let scheme = (setting.namespace() == APPEARANCE && setting.key() == "color-scheme")
+                .then(|| match u32::try_from(setting.value()) {
+                    Ok(1) => ColorScheme::PreferDark,
+                    Ok(2) => ColorScheme::PreferLight,
+                    _ => ColorScheme::NoPreference,
+                });
+            ready(scheme)
+        }))
+    }
+
+    /// Signal emitted when a setting of the `namespaces` changes, filtered
+    /// like [`SettingsProxy::read_all`], see [`namespace_matches`].
+    pub async fn receive_setting_changed_in(
+        &self,
+        namespaces: &[impl AsRef<str>],
+    ) -> Result<impl Stream<Item = Setting> + Unpin + 'a, Error> {
+        let namespaces = namespaces
+            .iter()
+            .map(|n| n.as_ref().to_owned())
+            .collect::<Vec<_>>();
+        Ok(self
+            .receive_setting_changed()
+            .await?
+            .filter(move |setting| ready(namespace_matches(&namespaces, setting.namespace()))))
+    }
+
+    portal_members! {
+        fn read_raw(namespace: &str, key: &str) -> OwnedValue = method "Read";
+        fn read_all_raw(namespaces: Vec<&str>) -> HashMap<String, Namespace> = method "ReadAll";
+
+        /// Signal emitted when a setting changes.
+        ///
+        /// # Specifications
+        ///
+        /// See also [`SettingChanged`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-Settings.SettingChanged).
+        pub fn receive_setting_changed() -> Stream<Setting> = signal "SettingChanged";
+    }
+}
+
+#[cfg(feature = "desktop-settings-extras")]
+impl<'a> SettingsProxy<'a> {
+    /// The name and size of the interface font, `Cantarell 11` for example.
+    ///
+    /// Read from the GNOME settings, or from the KDE ones if missing.
+    pub async fn font_name(&self) -> Result<String, Error> {
+        match self.read::<String>(GNOME_INTERFACE, "font-name").await {
+            Ok(font) => Ok(font),
+            Err(err) => self
+                .read::<String>(KDE_GENERAL, "font")
+                .await
+                .map(|font| kde_font_name(&font))
+                .map_err(|_| err),
+        }
+    }
+
+    /// The format of the clock, from the GNOME settings.
+    pub async fn clock_format(&self) -> Result<ClockFormat, Error> {
+        self.read::<String>(GNOME_INTERFACE, "clock-format")
+            .await?
+            .parse()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{namespace_matches, SettingsProxy};
+    use crate::{desktop::INTERFACE_SETTINGS, helpers::assert_members};
+
+    #[test]
+    fn members() {
+        assert_members(INTERFACE_SETTINGS, &SettingsProxy::members());
+    }
+
+    #[test]
+    fn namespaces() {
+        assert!(namespace_matches(&[""], "org.freedesktop.appearance"));
+        assert!(namespace_matches(
+            &["org.kde.*", "org.freedesktop.appearance"],
+            "org.freedesktop.appearance"
+        ));
+        assert!(namespace_matches(
+            &["org.kde.*"],
+            "org.kde.kdeglobals.General"
+        ));
+        assert!(namespace_matches(&["*"], "org.kde.kdeglobals.General"));
+        assert!(!namespace_matches(
+            &["org.kde.*"],
+            "org.gnome.desktop.interface"
+        ));
+        assert!(!namespace_matches(
+            &["org.gnome.desktop"],
+            "org.gnome.desktop.interface"
+        ));
+    }
+
+    #[cfg(feature = "desktop-settings-extras")]
+    #[test]
+    fn extras() {
+        use super::{kde_font_name, ClockFormat};
+
+        assert_eq!(
+            kde_font_name("Noto Sans,10,-1,5,50,0,0,0,0,0"),
+            "Noto Sans 10"
+        );
+        assert_eq!(kde_font_name("Noto Sans,10.5,-1"), "Noto Sans 10.5");
+        assert_eq!(kde_font_name("Noto Sans"), "Noto Sans");
+        assert_eq!(
+            "24h".parse::<ClockFormat>().unwrap(),
+            ClockFormat::TwentyFourHour
+        );
+        assert!("24".parse::<ClockFormat>().is_err());
+    }
+}
@@ -33,8 +33,8 @@ use std::os::unix::io::AsRawFd;
 use serde::{Deserialize, Serialize};
 use zbus::zvariant::{Fd, Type};
 
-use super::{DESTINATION, PATH};
-use crate::{error::PortalError, helpers::call_method, Error};
+use super::{DESTINATION, INTERFACE_TRASH, PATH};
+use crate::{error::PortalError, helpers::session_connection, Error};
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Hash, Debug, Type)]
 /// The status of moving a file to the trash.
@@ -48,20 +48,23 @@ enum TrashStatus {
 /// The interface lets sandboxed applications send files to the trashcan.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Trash`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Trash).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.Trash")]
-pub struct TrashProxy<'a>(zbus::Proxy<'a>);
+pub struct TrashProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(TrashProxy, INTERFACE_TRASH);
+impl_proxy_timeout!(TrashProxy);
 
 impl<'a> TrashProxy<'a> {
     /// Create a new instance of [`TrashProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<TrashProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Trash")?
+            .interface(INTERFACE_TRASH)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -69,6 +72,10 @@ impl<'a> TrashProxy<'a> {
         &self.0
     }
 
+    portal_members! {
+        fn trash_file_raw(fd: Fd) -> TrashStatus = method "TrashFile";
+    }
+
     /// Sends a file to the trashcan.
     /// Applications are allowed to trash a file if they can open it in
     /// read/write mode.
@@ -83,8 +90,7 @@ impl<'a> TrashProxy<'a> {
     #[doc(alias = "TrashFile")]
     #[doc(alias = "xdp_portal_trash_file")]
     pub async fn trash_file(&self, fd: &impl AsRawFd) -> Result<(), Error> {
-        let status = call_method(self.inner(), "TrashFile", &(Fd::from(fd.as_raw_fd()))).await?;
-        match status {
+        match self.trash_file_raw(Fd::from(fd.as_raw_fd())).await? {
             TrashStatus::Failed => Err(Error::Portal(PortalError::Failed)),
             TrashStatus::Succeeded => Ok(()),
         }
@@ -94,7 +100,18 @@ impl<'a> TrashProxy<'a> {
 #[doc(alias = "xdp_portal_trash_file")]
 /// A handy wrapper around [`TrashProxy::trash_file`].
 pub async fn trash_file(fd: &impl AsRawFd) -> Result<(), Error> {
-    let connection = zbus::Connection::session().await?;
+    let connection = session_connection().await?;
     let proxy = TrashProxy::new(&connection).await?;
     proxy.trash_file(fd).await
 }
+
+#[cfg(test)]
+mod test {
+    use super::TrashProxy;
+    use crate::{desktop::INTERFACE_TRASH, helpers::assert_members};
+
+    #[test]
+    fn members() {
+        assert_members(INTERFACE_TRASH, &TrashProxy::members());
+    }
+}
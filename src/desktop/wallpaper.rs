@@ -37,10 +37,10 @@
 //!
 //!```rust,no_run
 //! use ashpd::desktop::wallpaper::{self, SetOn};
-//! use ashpd::WindowIdentifier;
+//! use ashpd::{url::Url, WindowIdentifier};
 //!
 //! async fn run() -> ashpd::Result<()> {
-//!     let uri = "file:///home/bilelmoussaoui/Downloads/adwaita-night.jpg";
+//!     let uri = Url::parse("file:///home/bilelmoussaoui/Downloads/adwaita-night.jpg").unwrap();
 //!     wallpaper::set_from_uri(&WindowIdentifier::default(), &uri, true, SetOn::Both).await?;
 //!     Ok(())
 //! }
@@ -50,7 +50,7 @@
 //!
 //! ```rust,no_run
 //! use ashpd::desktop::wallpaper::{SetOn, WallpaperProxy};
-//! use ashpd::WindowIdentifier;
+//! use ashpd::{url::Url, WindowIdentifier};
 //!
 //! async fn run() -> ashpd::Result<()> {
 //!     let connection = zbus::Connection::session().await?;
@@ -58,7 +58,7 @@
 //!     proxy
 //!         .set_wallpaper_uri(
 //!             &WindowIdentifier::default(),
-//!             "file:///home/bilelmoussaoui/Downloads/adwaita-night.jpg",
+//!             &Url::parse("file:///home/bilelmoussaoui/Downloads/adwaita-night.jpg").unwrap(),
 //!             true,
 //!             SetOn::Both,
 //!         )
@@ -71,15 +71,17 @@ use std::os::unix::prelude::AsRawFd;
 use std::{fmt, str::FromStr};
 
 use serde::{self, Deserialize, Serialize, Serializer};
+use url::Url;
 use zbus::zvariant::{DeserializeDict, Fd, SerializeDict, Signature, Type};
 
 use crate::{
-    desktop::{HandleToken, DESTINATION, PATH},
-    helpers::call_basic_response_method,
+    desktop::{HandleToken, PortalRequest, DESTINATION, INTERFACE_WALLPAPER, PATH},
+    helpers::{call_window_basic_response_method, session_connection},
     Error, WindowIdentifier,
 };
 
-#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Hash)]
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
 /// Where to set the wallpaper on.
 pub enum SetOn {
     /// Set the wallpaper only on the lock-screen.
@@ -150,7 +152,7 @@ impl Type for SetOn {
     }
 }
 
-#[derive(SerializeDict, DeserializeDict, Clone, Type, Debug, Default)]
+#[derive(SerializeDict, DeserializeDict, Clone, Type, Debug, Default, PartialEq, Eq)]
 /// Specified options for a [`WallpaperProxy::set_wallpaper_file`] or a
 /// [`WallpaperProxy::set_wallpaper_uri`] request.
 #[zvariant(signature = "dict")]
@@ -166,40 +168,37 @@ struct WallpaperOptions {
 }
 
 impl WallpaperOptions {
-    /// Whether to show a preview of the picture.
-    /// **Note** the portal may decide to show a preview even if this option is
-    /// not set.
-    #[must_use]
-    pub fn show_preview(mut self, show_preview: bool) -> Self {
-        self.show_preview = Some(show_preview);
-        self
-    }
-
-    /// Sets where to set the wallpaper on.
-    #[must_use]
-    pub fn set_on(mut self, set_on: SetOn) -> Self {
-        self.set_on = Some(set_on);
-        self
+    options_setters! {
+        @setters
+        /// Whether to show a preview of the picture.
+        /// **Note** the portal may decide to show a preview even if this option is
+        /// not set.
+        with_show_preview(show_preview: bool);
+        /// Sets where to set the wallpaper on.
+        with_set_on(set_on: SetOn);
     }
 }
 /// The interface lets sandboxed applications set the user's desktop background
 /// picture.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Wallpaper`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Wallpaper).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.Wallpaper")]
-pub struct WallpaperProxy<'a>(zbus::Proxy<'a>);
+pub struct WallpaperProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(WallpaperProxy, INTERFACE_WALLPAPER);
+impl_proxy_timeout!(WallpaperProxy);
 
 impl<'a> WallpaperProxy<'a> {
     /// Create a new instance of [`WallpaperProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<WallpaperProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Wallpaper")?
+            .interface(INTERFACE_WALLPAPER)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -222,23 +221,24 @@ impl<'a> WallpaperProxy<'a> {
     /// See also [`SetWallpaperFile`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Wallpaper.SetWallpaperFile).
     #[doc(alias = "SetWallpaperFile")]
     #[doc(alias = "xdp_portal_set_wallpaper")]
-    pub async fn set_wallpaper_file(
-        &self,
-        identifier: &WindowIdentifier,
+    pub fn set_wallpaper_file<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
         file: &impl AsRawFd,
         show_preview: bool,
         set_on: SetOn,
-    ) -> Result<(), Error> {
+    ) -> PortalRequest<'p, ()> {
         let options = WallpaperOptions::default()
-            .show_preview(show_preview)
-            .set_on(set_on);
-        call_basic_response_method(
-            self.inner(),
-            &options.handle_token,
+            .with_show_preview(show_preview)
+            .with_set_on(set_on);
+        let handle_token = options.handle_token.copy();
+        call_window_basic_response_method(
+            self,
+            identifier,
+            &handle_token,
             "SetWallpaperFile",
-            &(&identifier, Fd::from(file.as_raw_fd()), &options),
+            (identifier, Fd::from(file.as_raw_fd()), options),
         )
-        .await
     }
 
     /// Sets the lock-screen, background or both wallpaper's from an URI.
@@ -255,23 +255,24 @@ impl<'a> WallpaperProxy<'a> {
     /// See also [`SetWallpaperURI`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Wallpaper.SetWallpaperURI).
     #[doc(alias = "SetWallpaperURI")]
     #[doc(alias = "xdp_portal_set_wallpaper")]
-    pub async fn set_wallpaper_uri(
-        &self,
-        identifier: &WindowIdentifier,
-        uri: &str,
+    pub fn set_wallpaper_uri<'p>(
+        &'p self,
+        identifier: &'p WindowIdentifier,
+        uri: &'p Url,
         show_preview: bool,
         set_on: SetOn,
-    ) -> Result<(), Error> {
+    ) -> PortalRequest<'p, ()> {
         let options = WallpaperOptions::default()
-            .show_preview(show_preview)
-            .set_on(set_on);
-        call_basic_response_method(
-            self.inner(),
-            &options.handle_token,
+            .with_show_preview(show_preview)
+            .with_set_on(set_on);
+        let handle_token = options.handle_token.copy();
+        call_window_basic_response_method(
+            self,
+            identifier,
+            &handle_token,
             "SetWallpaperURI",
-            &(&identifier, uri, &options),
+            (identifier, uri.as_str(), options),
         )
-        .await
     }
 }
 
@@ -279,11 +280,11 @@ impl<'a> WallpaperProxy<'a> {
 /// A handy wrapper around [`WallpaperProxy::set_wallpaper_uri`].
 pub async fn set_from_uri(
     identifier: &WindowIdentifier,
-    uri: &str,
+    uri: &Url,
     show_preview: bool,
     set_on: SetOn,
 ) -> Result<(), Error> {
-    let connection = zbus::Connection::session().await?;
+    let connection = session_connection().await?;
     let proxy = WallpaperProxy::new(&connection).await?;
     proxy
         .set_wallpaper_uri(identifier, uri, show_preview, set_on)
@@ -299,10 +300,33 @@ pub async fn set_from_file(
     show_preview: bool,
     set_on: SetOn,
 ) -> Result<(), Error> {
-    let connection = zbus::Connection::session().await?;
+    let connection = session_connection().await?;
     let proxy = WallpaperProxy::new(&connection).await?;
     proxy
         .set_wallpaper_file(identifier, file, show_preview, set_on)
         .await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::{SetOn, WallpaperOptions};
+    use crate::{desktop::HandleToken, helpers::assert_serialized_dict};
+
+    #[test]
+    fn options() {
+        let options = WallpaperOptions {
+            handle_token: HandleToken::default(),
+            show_preview: Some(true),
+            set_on: Some(SetOn::Both),
+        };
+        assert_serialized_dict(
+            &options,
+            &[
+                ("handle_token", "s"),
+                ("set-on", "s"),
+                ("show-preview", "b"),
+            ],
+        );
+    }
+}
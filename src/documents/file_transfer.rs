@@ -28,17 +28,15 @@ use std::{collections::HashMap, os::unix::prelude::AsRawFd};
 
 use zbus::zvariant::{DeserializeDict, Fd, SerializeDict, Type, Value};
 
-use super::{DESTINATION, PATH};
-use crate::{
-    helpers::{call_method, receive_signal},
-    Error,
-};
+use super::{DESTINATION, INTERFACE_FILE_TRANSFER, PATH};
+use crate::Error;
 
-#[derive(SerializeDict, DeserializeDict, Debug, Type, Default)]
+#[derive(SerializeDict, DeserializeDict, Debug, Type, Default, Clone, PartialEq, Eq)]
 /// Specified options for a [`FileTransferProxy::start_transfer`] request.
 #[zvariant(signature = "dict")]
 struct TransferOptions {
     /// Whether to allow the chosen application to write to the files.
+    #[zvariant(rename = "writable")]
     writeable: Option<bool>,
     /// Whether to stop the transfer automatically after the first
     /// [`retrieve_files()`][`FileTransferProxy::retrieve_files`] call.
@@ -47,19 +45,13 @@ struct TransferOptions {
 }
 
 impl TransferOptions {
-    /// Sets whether the chosen application can write to the files or not.
-    #[must_use]
-    pub fn writeable(mut self, writeable: bool) -> Self {
-        self.writeable = Some(writeable);
-        self
-    }
-
-    /// Whether to stop the transfer automatically after the first
-    /// [`retrieve_files()`][`FileTransferProxy::retrieve_files`] call.
-    #[must_use]
-    pub fn auto_stop(mut self, auto_stop: bool) -> Self {
-        self.auto_stop = Some(auto_stop);
-        self
+    options_setters! {
+        @setters
+        /// Sets whether the chosen application can write to the files or not.
+        with_writeable(writeable: bool);
+        /// Whether to stop the transfer automatically after the first
+        /// [`retrieve_files()`][`FileTransferProxy::retrieve_files`] call.
+        with_auto_stop(auto_stop: bool);
     }
 }
 
@@ -78,20 +70,23 @@ impl TransferOptions {
 /// them accessible to the target.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.FileTransfer`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.FileTransfer).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.FileTransfer")]
-pub struct FileTransferProxy<'a>(zbus::Proxy<'a>);
+pub struct FileTransferProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(FileTransferProxy, INTERFACE_FILE_TRANSFER);
+impl_proxy_timeout!(FileTransferProxy);
 
 impl<'a> FileTransferProxy<'a> {
     /// Create a new instance of [`FileTransferProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<FileTransferProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.FileTransfer")?
+            .interface(INTERFACE_FILE_TRANSFER)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -118,7 +113,7 @@ impl<'a> FileTransferProxy<'a> {
         let options: HashMap<&str, Value<'_>> = HashMap::new();
         let files: Vec<Fd> = fds.iter().map(|f| Fd::from(f.as_raw_fd())).collect();
 
-        call_method(self.inner(), "AddFiles", &(key, files, options)).await
+        self.add_files_raw(key, files, options).await
     }
 
     /// Retrieves files that were previously added to the session with
@@ -144,7 +139,7 @@ impl<'a> FileTransferProxy<'a> {
         // see https://github.com/GNOME/gtk/blob/master/gdk/filetransferportal.c#L284
         let options: HashMap<&str, Value<'_>> = HashMap::new();
 
-        call_method(self.inner(), "RetrieveFiles", &(key, options)).await
+        self.retrieve_files_raw(key, options).await
     }
 
     /// Starts a session for a file transfer.
@@ -169,41 +164,71 @@ impl<'a> FileTransferProxy<'a> {
     /// See also [`StartTransfer`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-FileTransfer.StartTransfer).
     pub async fn start_transfer(&self, writeable: bool, auto_stop: bool) -> Result<String, Error> {
         let options = TransferOptions::default()
-            .writeable(writeable)
-            .auto_stop(auto_stop);
-        call_method(self.inner(), "StartTransfer", &(options)).await
+            .with_writeable(writeable)
+            .with_auto_stop(auto_stop);
+        self.start_transfer_raw(options).await
     }
 
-    /// Ends the transfer.
-    /// Further calls to [`add_files()`][`FileTransferProxy::add_files`] or
-    /// [`retrieve_files()`][`FileTransferProxy::retrieve_files`] for this key
-    /// will return an error.
-    ///
-    /// # Arguments
-    ///
-    /// * `key` - A key returned by
-    ///   [`start_transfer()`][`FileTransferProxy::start_transfer`].
-    ///
-    /// # Specifications
-    ///
-    /// See also [`StopTransfer`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-FileTransfer.StopTransfer).
-    #[doc(alias = "StopTransfer")]
-    pub async fn stop_transfer(&self, key: &str) -> Result<(), Error> {
-        call_method(self.inner(), "StopTransfer", &(key)).await
+    portal_members! {
+        fn add_files_raw(
+            key: &str,
+            files: Vec<Fd>,
+            options: HashMap<&str, Value<'_>>,
+        ) -> () = method "AddFiles";
+        fn retrieve_files_raw(
+            key: &str,
+            options: HashMap<&str, Value<'_>>,
+        ) -> Vec<String> = method "RetrieveFiles";
+        fn start_transfer_raw(options: TransferOptions) -> String = method "StartTransfer";
+
+        /// Ends the transfer.
+        /// Further calls to [`add_files()`][`FileTransferProxy::add_files`] or
+        /// [`retrieve_files()`][`FileTransferProxy::retrieve_files`] for this key
+        /// will return an error.
+        ///
+        /// # Arguments
+        ///
+        /// * `key` - A key returned by
+        ///   [`start_transfer()`][`FileTransferProxy::start_transfer`].
+        ///
+        /// # Specifications
+        ///
+        /// See also [`StopTransfer`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-FileTransfer.StopTransfer).
+        pub fn stop_transfer(key: &str) -> () = method "StopTransfer";
+
+        /// Emitted when the transfer is closed.
+        ///
+        /// # Returns
+        ///
+        /// * The key returned by
+        ///   [`start_transfer()`][`FileTransferProxy::start_transfer`].
+        ///
+        /// # Specifications
+        ///
+        /// See also [`TransferClosed`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-FileTransfer.TransferClosed).
+        pub fn receive_transfer_closed() -> Stream<String> = signal "TransferClosed";
     }
+}
 
-    /// Emitted when the transfer is closed.
-    ///
-    /// # Returns
-    ///
-    /// * The key returned by
-    ///   [`start_transfer()`][`FileTransferProxy::start_transfer`].
-    ///
-    /// # Specifications
-    ///
-    /// See also [`TransferClosed`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-FileTransfer.TransferClosed).
-    #[doc(alias = "TransferClosed")]
-    pub async fn transfer_closed(&self) -> Result<String, Error> {
-        receive_signal(self.inner(), "TransferClosed").await
+#[cfg(test)]
+mod test {
+    use super::{FileTransferProxy, TransferOptions};
+    use crate::{
+        documents::INTERFACE_FILE_TRANSFER,
+        helpers::{assert_members, assert_serialized_dict},
+    };
+
+    #[test]
+    fn options() {
+        let options = TransferOptions {
+            writeable: Some(true),
+            auto_stop: Some(true),
+        };
+        assert_serialized_dict(&options, &[("autostop", "b"), ("writable", "b")]);
+    }
+
+    #[test]
+    fn members() {
+        assert_members(INTERFACE_FILE_TRANSFER, &FileTransferProxy::members());
     }
 }
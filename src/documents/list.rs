@@ -0,0 +1,94 @@
+use std::{collections::HashMap, fmt, path::PathBuf};
+
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use zbus::zvariant::{Signature, Type};
+
+use super::OwnedDocumentID;
+use crate::helpers::path_from_null_terminated;
+
+// The entries of the `a{say}` body of a `List` reply, borrowed from the
+// message in the order they were sent, instead of hashed into a map.
+pub(super) struct ListEntries<'m>(Vec<(&'m str, &'m [u8])>);
+
+impl ListEntries<'_> {
+    // The first `limit` entries, copied out of the message.
+    pub(super) fn into_owned(self, limit: Option<usize>) -> Vec<(OwnedDocumentID, PathBuf)> {
+        self.0
+            .into_iter()
+            .take(limit.unwrap_or(usize::MAX))
+            .map(|(id, path)| (id.to_owned(), path_from_null_terminated(path)))
+            .collect()
+    }
+}
+
+impl Type for ListEntries<'_> {
+    fn signature() -> Signature<'static> {
+        HashMap::<&str, &[u8]>::signature()
+    }
+}
+
+impl<'de> Deserialize<'de> for ListEntries<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(ListEntriesVisitor)
+    }
+}
+
+struct ListEntriesVisitor;
+
+impl<'de> Visitor<'de> for ListEntriesVisitor {
+    type Value = ListEntries<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a dictionary of document IDs and paths")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(ListEntries(entries))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashMap, path::PathBuf};
+
+    use zbus::zvariant::{from_slice, to_bytes, EncodingContext};
+
+    use super::ListEntries;
+
+    #[test]
+    fn entries() {
+        let ctxt = EncodingContext::<byteorder::LE>::new_dbus(0);
+        let mut entries = HashMap::new();
+        entries.insert("f2ee988d", &b"/home/user/a.txt\0"[..]);
+        entries.insert("a1", &b"/home/user/directory/b\0"[..]);
+        let bytes = to_bytes(ctxt, &entries).unwrap();
+
+        let listed = from_slice::<_, ListEntries<'_>>(&bytes, ctxt).unwrap();
+        assert_eq!(listed.0.iter().copied().collect::<HashMap<_, _>>(), entries);
+        let owned = listed.into_owned(None);
+        assert!(owned.contains(&("a1".to_owned(), PathBuf::from("/home/user/directory/b"))));
+        assert_eq!(owned.len(), 2);
+
+        let listed = from_slice::<_, ListEntries<'_>>(&bytes, ctxt).unwrap();
+        assert_eq!(listed.into_owned(Some(1)).len(), 1);
+
+        let empty = to_bytes(ctxt, &HashMap::<&str, &[u8]>::new()).unwrap();
+        let listed = from_slice::<_, ListEntries<'_>>(&empty, ctxt).unwrap();
+        assert!(listed.into_owned(None).is_empty());
+
+        assert!(from_slice::<_, ListEntries<'_>>(&bytes[..bytes.len() - 1], ctxt).is_err());
+    }
+}
@@ -33,25 +33,33 @@
 //! }
 //! ```
 
-pub(crate) const DESTINATION: &str = "org.freedesktop.portal.Documents";
-pub(crate) const PATH: &str = "/org/freedesktop/portal/documents";
-
-use std::{
-    collections::HashMap, ffi::CString, os::unix::ffi::OsStrExt, os::unix::prelude::AsRawFd,
-};
+/// The bus name of the documents store interfaces.
+pub const DESTINATION: &str = "org.freedesktop.portal.Documents";
+/// The object path the documents store interfaces are exported at.
+pub const PATH: &str = "/org/freedesktop/portal/documents";
+/// The interface wrapped by [`DocumentsProxy`].
+pub const INTERFACE_DOCUMENTS: &str = "org.freedesktop.portal.Documents";
+/// The interface wrapped by [`FileTransferProxy`].
+pub const INTERFACE_FILE_TRANSFER: &str = "org.freedesktop.portal.FileTransfer";
+
+use std::{collections::HashMap, os::unix::prelude::AsRawFd};
 use std::{
     fmt,
     path::{Path, PathBuf},
     str::FromStr,
+    sync::{Arc, Mutex},
 };
 
 use enumflags2::{bitflags, BitFlags};
-use serde::{de::Deserializer, Deserialize, Serialize, Serializer};
+use futures::{stream, Stream};
+use serde::{de, de::Deserializer, Deserialize, Serialize, Serializer};
 use serde_repr::{Deserialize_repr, Serialize_repr};
-use zbus::zvariant::{Fd, OwnedValue, Signature, Type};
+use zbus::zvariant::{DeserializeDict, Fd, SerializeDict, Signature, Type};
 
 use crate::{
-    helpers::{call_method, path_from_null_terminated},
+    helpers::{
+        call_method, call_method_with, null_terminated_from_path, path_from_null_terminated,
+    },
     Error,
 };
 
@@ -75,6 +83,22 @@ pub type OwnedDocumentID = String;
 pub type ApplicationID<'a> = &'a str;
 pub type OwnedApplicationID = String;
 
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
+/// The extra information returned by [`DocumentsProxy::add_full`] and
+/// [`DocumentsProxy::add_named_full`].
+#[zvariant(signature = "dict")]
+pub struct ExtraResults {
+    mountpoint: Option<Vec<u8>>,
+}
+
+impl ExtraResults {
+    /// The mount point of the documents store, the exported files are found
+    /// under `<mount point>/<document ID>/`.
+    pub fn mount_point(&self) -> Option<PathBuf> {
+        self.mountpoint.as_deref().map(path_from_null_terminated)
+    }
+}
+
 /// A [`HashMap`] mapping application IDs to the permissions for that
 /// application
 pub type Permissions = HashMap<OwnedApplicationID, Vec<Permission>>;
@@ -161,7 +185,7 @@ impl<'de> Deserialize<'de> for Permission {
     where
         D: Deserializer<'de>,
     {
-        Ok(Permission::from_str(&String::deserialize(deserializer)?).expect("invalid permission"))
+        Permission::from_str(&String::deserialize(deserializer)?).map_err(de::Error::custom)
     }
 }
 
@@ -189,20 +213,27 @@ impl Type for Permission {
 /// in the fuse filesystem.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Documents`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Documents).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.Documents")]
-pub struct DocumentsProxy<'a>(zbus::Proxy<'a>);
+pub struct DocumentsProxy<'a>(
+    zbus::Proxy<'a>,
+    Option<std::time::Duration>,
+    Arc<Mutex<Option<PathBuf>>>,
+);
+
+impl_try_from_proxy!(DocumentsProxy, INTERFACE_DOCUMENTS, Default::default());
+impl_proxy_timeout!(DocumentsProxy);
 
 impl<'a> DocumentsProxy<'a> {
     /// Create a new instance of [`DocumentsProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<DocumentsProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Documents")?
+            .interface(INTERFACE_DOCUMENTS)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None, Default::default()))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -237,7 +268,7 @@ impl<'a> DocumentsProxy<'a> {
         persistent: bool,
     ) -> Result<OwnedDocumentID, Error> {
         call_method(
-            self.inner(),
+            self,
             "Add",
             &(Fd::from(o_path_fd.as_raw_fd()), reuse_existing, persistent),
         )
@@ -259,6 +290,35 @@ impl<'a> DocumentsProxy<'a> {
     ///
     /// The IDs of the files in the document store along with other extra info.
     ///
+    /// # Examples
+    ///
+    /// Share a file with a chat application, in a single call.
+    ///
+    /// ```rust,no_run
+    /// use std::fs::File;
+    ///
+    /// use ashpd::documents::{DocumentsProxy, Flags, Permission};
+    ///
+    /// async fn run() -> ashpd::Result<()> {
+    ///     let connection = zbus::Connection::session().await?;
+    ///     let proxy = DocumentsProxy::new(&connection).await?;
+    ///
+    ///     let file = File::open("/home/user/Pictures/holidays.png").unwrap();
+    ///     let (ids, extra) = proxy
+    ///         .add_full(
+    ///             &[&file],
+    ///             Flags::ReuseExisting | Flags::AsNeededByApp,
+    ///             "org.gnome.Fractal",
+    ///             &[Permission::Read],
+    ///         )
+    ///         .await?;
+    ///     if let Some(mount_point) = extra.mount_point() {
+    ///         println!("{}", mount_point.join(&ids[0]).join("holidays.png").display());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
     /// # Specifications
     ///
     /// See also [`AddFull`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Documents.AddFull).
@@ -269,14 +329,9 @@ impl<'a> DocumentsProxy<'a> {
         flags: BitFlags<Flags>,
         app_id: ApplicationID<'_>,
         permissions: &[Permission],
-    ) -> Result<(Vec<OwnedDocumentID>, HashMap<String, OwnedValue>), Error> {
+    ) -> Result<(Vec<OwnedDocumentID>, ExtraResults), Error> {
         let o_path: Vec<Fd> = o_path_fds.iter().map(|f| Fd::from(f.as_raw_fd())).collect();
-        call_method(
-            self.inner(),
-            "AddFull",
-            &(o_path, flags, app_id, permissions),
-        )
-        .await
+        call_method(self, "AddFull", &(o_path, flags, app_id, permissions)).await
     }
 
     /// Creates an entry in the document store for writing a new file.
@@ -305,14 +360,13 @@ impl<'a> DocumentsProxy<'a> {
         reuse_existing: bool,
         persistent: bool,
     ) -> Result<OwnedDocumentID, Error> {
-        let cstr = CString::new(filename.as_ref().as_os_str().as_bytes())
-            .expect("`filename` should not be null terminated");
+        let filename = null_terminated_from_path(filename)?;
         call_method(
-            self.inner(),
+            self,
             "AddNamed",
             &(
                 Fd::from(o_path_parent_fd.as_raw_fd()),
-                cstr.as_bytes_with_nul(),
+                filename.as_slice(),
                 reuse_existing,
                 persistent,
             ),
@@ -347,15 +401,14 @@ impl<'a> DocumentsProxy<'a> {
         flags: BitFlags<Flags>,
         app_id: ApplicationID<'_>,
         permissions: &[Permission],
-    ) -> Result<(OwnedDocumentID, HashMap<String, OwnedValue>), Error> {
-        let cstr = CString::new(filename.as_ref().as_os_str().as_bytes())
-            .expect("`filename` should not be null terminated");
+    ) -> Result<(OwnedDocumentID, ExtraResults), Error> {
+        let filename = null_terminated_from_path(filename)?;
         call_method(
-            self.inner(),
+            self,
             "AddNamedFull",
             &(
                 Fd::from(o_path_fd.as_raw_fd()),
-                cstr.as_bytes_with_nul(),
+                filename.as_slice(),
                 flags,
                 app_id,
                 permissions,
@@ -379,20 +432,49 @@ impl<'a> DocumentsProxy<'a> {
     /// See also [`Delete`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Documents.Delete).
     #[doc(alias = "Delete")]
     pub async fn delete(&self, doc_id: DocumentID<'_>) -> Result<(), Error> {
-        call_method(self.inner(), "Delete", &(doc_id)).await
+        call_method(self, "Delete", &(doc_id)).await
     }
 
     /// Returns the path at which the document store fuse filesystem is mounted.
     /// This will typically be `/run/user/$UID/doc/`.
     ///
+    /// The mount point is only asked to the portal once, and shared by the
+    /// clones of the proxy.
+    ///
     /// # Specifications
     ///
     /// See also [`GetMountPoint`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Documents.GetMountPoint).
     #[doc(alias = "GetMountPoint")]
     #[doc(alias = "get_mount_point")]
     pub async fn mount_point(&self) -> Result<PathBuf, Error> {
-        let bytes: Vec<u8> = call_method(self.inner(), "GetMountPoint", &()).await?;
-        Ok(path_from_null_terminated(bytes))
+        if let Some(mount_point) = self.2.lock().unwrap().as_ref() {
+            return Ok(mount_point.clone());
+        }
+        let mount_point = call_method_with(self, "GetMountPoint", &(), |reply| {
+            Ok(path_from_null_terminated(reply.body::<&[u8]>()?))
+        })
+        .await?;
+        *self.2.lock().unwrap() = Some(mount_point.clone());
+        Ok(mount_point)
+    }
+
+    /// The directory of the document in the fuse filesystem, where the file
+    /// it was created for can be opened.
+    ///
+    /// ```rust,no_run
+    /// use ashpd::documents::DocumentsProxy;
+    ///
+    /// async fn run() -> ashpd::Result<()> {
+    ///     let connection = zbus::Connection::session().await?;
+    ///     let proxy = DocumentsProxy::new(&connection).await?;
+    ///
+    ///     let path = proxy.document_path("f2ee988d").await?;
+    ///     println!("{}", path.display());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn document_path(&self, doc_id: DocumentID<'_>) -> Result<PathBuf, Error> {
+        Ok(self.mount_point().await?.join(doc_id))
     }
 
     /// Grants access permissions for a file in the document store to an
@@ -417,12 +499,7 @@ impl<'a> DocumentsProxy<'a> {
         app_id: ApplicationID<'_>,
         permissions: &[Permission],
     ) -> Result<(), Error> {
-        call_method(
-            self.inner(),
-            "GrantPermissions",
-            &(doc_id, app_id, permissions),
-        )
-        .await
+        call_method(self, "GrantPermissions", &(doc_id, app_id, permissions)).await
     }
 
     /// Gets the filesystem path and application permissions for a document
@@ -444,9 +521,11 @@ impl<'a> DocumentsProxy<'a> {
     /// See also [`Info`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Documents.Info).
     #[doc(alias = "Info")]
     pub async fn info(&self, doc_id: DocumentID<'_>) -> Result<(PathBuf, Permissions), Error> {
-        let (bytes, permissions): (Vec<u8>, Permissions) =
-            call_method(self.inner(), "Info", &(doc_id)).await?;
-        Ok((path_from_null_terminated(bytes), permissions))
+        call_method_with(self, "Info", &(doc_id), |reply| {
+            let (bytes, permissions) = reply.body::<(&[u8], Permissions)>()?;
+            Ok((path_from_null_terminated(bytes), permissions))
+        })
+        .await
     }
 
     /// Lists documents in the document store for an application (or for all
@@ -471,15 +550,72 @@ impl<'a> DocumentsProxy<'a> {
         &self,
         app_id: ApplicationID<'_>,
     ) -> Result<HashMap<OwnedDocumentID, PathBuf>, Error> {
-        let response: HashMap<String, Vec<u8>> =
-            call_method(self.inner(), "List", &(app_id)).await?;
+        // The paths are only copied once, out of the message body.
+        call_method_with(self, "List", &(app_id), |reply| {
+            let response = reply.body::<HashMap<&str, &[u8]>>()?;
+            Ok(response
+                .into_iter()
+                .map(|(key, bytes)| (key.to_owned(), path_from_null_terminated(bytes)))
+                .collect())
+        })
+        .await
+    }
 
-        let mut new_response: HashMap<String, PathBuf> = HashMap::new();
-        for (key, bytes) in response {
-            new_response.insert(key, path_from_null_terminated(bytes));
-        }
+    /// Same as [`DocumentsProxy::list`], without collecting the documents
+    /// into a [`HashMap`].
+    ///
+    /// The portal replies with all the documents at once, the entries are
+    /// read from the reply in the order it lists them, without hashing them
+    /// into a map.
+    ///
+    /// **Note** This call is not available inside the sandbox.
+    ///
+    /// ```rust,no_run
+    /// use ashpd::documents::DocumentsProxy;
+    /// use futures::StreamExt;
+    ///
+    /// async fn run() -> ashpd::Result<()> {
+    ///     let connection = zbus::Connection::session().await?;
+    ///     let proxy = DocumentsProxy::new(&connection).await?;
+    ///
+    ///     let mut documents = proxy.list_stream("").await?;
+    ///     while let Some((doc_id, host_path)) = documents.next().await {
+    ///         println!("{}: {}", doc_id, host_path.display());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[doc(alias = "List")]
+    pub async fn list_stream(
+        &self,
+        app_id: ApplicationID<'_>,
+    ) -> Result<impl Stream<Item = (OwnedDocumentID, PathBuf)> + Unpin + 'static, Error> {
+        self.list_entries(app_id, None).await
+    }
 
-        Ok(new_response)
+    /// Same as [`DocumentsProxy::list_stream`], with at most `limit`
+    /// documents, the other entries of the reply aren't copied.
+    ///
+    /// **Note** This call is not available inside the sandbox.
+    #[doc(alias = "List")]
+    pub async fn list_stream_limited(
+        &self,
+        app_id: ApplicationID<'_>,
+        limit: usize,
+    ) -> Result<impl Stream<Item = (OwnedDocumentID, PathBuf)> + Unpin + 'static, Error> {
+        self.list_entries(app_id, Some(limit)).await
+    }
+
+    async fn list_entries(
+        &self,
+        app_id: ApplicationID<'_>,
+        limit: Option<usize>,
+    ) -> Result<impl Stream<Item = (OwnedDocumentID, PathBuf)> + Unpin + 'static, Error> {
+        let entries = call_method_with(self, "List", &(app_id), |reply| {
+            Ok(reply.body::<ListEntries<'_>>()?.into_owned(limit))
+        })
+        .await?;
+        Ok(stream::iter(entries))
     }
 
     /// Looks up the document ID for a file.
@@ -503,10 +639,8 @@ impl<'a> DocumentsProxy<'a> {
         &self,
         filename: (impl AsRef<Path> + Serialize + Type + fmt::Debug),
     ) -> Result<Option<OwnedDocumentID>, Error> {
-        let cstr = CString::new(filename.as_ref().as_os_str().as_bytes())
-            .expect("`filename` should not be null terminated");
-        let doc_id: String =
-            call_method(self.inner(), "Lookup", &(cstr.as_bytes_with_nul())).await?;
+        let filename = null_terminated_from_path(filename)?;
+        let doc_id: String = call_method(self, "Lookup", &(filename.as_slice())).await?;
         if doc_id.is_empty() {
             Ok(None)
         } else {
@@ -537,12 +671,7 @@ impl<'a> DocumentsProxy<'a> {
         app_id: ApplicationID<'_>,
         permissions: &[Permission],
     ) -> Result<(), Error> {
-        call_method(
-            self.inner(),
-            "RevokePermissions",
-            &(doc_id, app_id, permissions),
-        )
-        .await
+        call_method(self, "RevokePermissions", &(doc_id, app_id, permissions)).await
     }
 }
 
@@ -550,3 +679,25 @@ impl<'a> DocumentsProxy<'a> {
 mod file_transfer;
 
 pub use file_transfer::FileTransferProxy;
+
+/// Read the documents of a `List` reply one at a time.
+mod list;
+use list::ListEntries;
+
+#[cfg(test)]
+mod test {
+    use super::ExtraResults;
+    use crate::helpers::assert_serialized_dict;
+
+    #[test]
+    fn extra_results() {
+        let results = ExtraResults {
+            mountpoint: Some(b"/run/user/1000/doc\0".to_vec()),
+        };
+        assert_serialized_dict(&results, &[("mountpoint", "ay")]);
+        assert_eq!(
+            results.mount_point().unwrap().to_str(),
+            Some("/run/user/1000/doc")
+        );
+    }
+}
@@ -1,5 +1,9 @@
-use crate::desktop::request::ResponseError;
-use zbus::DBusError;
+use crate::desktop::{
+    dynamic_launcher::IconError,
+    remote_desktop::DeviceType,
+    request::{ResponseCode, ResponseError},
+};
+use zbus::{zvariant::OwnedObjectPath, DBusError};
 
 /// An error type that describes the various DBus errors.
 ///
@@ -29,6 +33,10 @@ pub enum PortalError {
 
 #[derive(Debug)]
 /// The error type for ashpd.
+///
+/// Some variants only exist with the feature enabling them, matching on it
+/// needs a wildcard arm.
+#[non_exhaustive]
 pub enum Error {
     /// The portal request didn't succeed.
     Response(ResponseError),
@@ -40,22 +48,265 @@ pub enum Error {
     NoResponse,
     /// Failed to parse a string into an enum variant
     ParseError(String),
+    /// The user didn't grant access to the device.
+    DeviceNotGranted(DeviceType),
+    /// The portals are not available on this platform, only returned with the
+    /// `fallback` feature on macOS, or the running portal doesn't
+    /// offer what was asked, the virtual sources of
+    /// [`ScreenCastProxy::create_virtual_output`](crate::desktop::screencast::ScreenCastProxy::create_virtual_output)
+    /// for example.
+    Unsupported,
+    /// The portal didn't reply within the timeout of the proxy, see
+    /// [`ScreenshotProxy::with_timeout`](crate::desktop::screenshot::ScreenshotProxy::with_timeout)
+    /// for example.
+    Timeout(std::time::Duration),
+    /// A path or an argument sent as a NUL terminated bytestring contains a
+    /// NUL byte at the given position.
+    NulTerminated(usize),
+    /// The icon can't be used for a launcher, see
+    /// [`LauncherIcon::from_bytes`](crate::desktop::dynamic_launcher::LauncherIcon::from_bytes).
+    InvalidIcon(IconError),
+    /// An argument was rejected before reaching the portal, a malformed email
+    /// address for example.
+    InvalidArgument(String),
+    /// The request needs a version of the portal, the first field, newer than
+    /// the one of the running portal, the second field.
+    RequiresVersion(u32, u32),
+    /// An I/O operation of the library failed, spawning the thread of
+    /// [`spawn_executor`](crate::spawn_executor) for example.
+    Io(std::io::Error),
+    /// A call to a portal failed, see [`Error::root`] for the cause.
+    ///
+    /// **Breaking change**: the errors of the portal calls used to be
+    /// returned as is, they are now wrapped in this variant and matching on
+    /// [`Error::Response`] or [`Error::Portal`] directly no longer catches
+    /// them. Match on the cause returned by [`Error::root`] instead, or use
+    /// [`Error::response_code`]:
+    ///
+    /// ```rust,no_run
+    /// use ashpd::{desktop::ResponseError, Error};
+    ///
+    /// fn is_cancelled(err: &Error) -> bool {
+    ///     // Previously `matches!(err, Error::Response(ResponseError::Cancelled))`.
+    ///     matches!(err.root(), Error::Response(ResponseError::Cancelled))
+    /// }
+    /// ```
+    Call {
+        /// The interface of the portal, `org.freedesktop.portal.Screenshot`
+        /// for example.
+        interface: String,
+        /// The method called, or the signal or property read.
+        method: String,
+        /// The handle of the `org.freedesktop.portal.Request` object, for the
+        /// methods that go through one.
+        handle: Option<OwnedObjectPath>,
+        /// The id given to [`with_correlation_id`](crate::with_correlation_id)
+        /// when the call was made.
+        correlation_id: Option<String>,
+        /// The cause of the failure.
+        source: Box<Error>,
+    },
+    /// The communication with the Wayland compositor failed, while capturing
+    /// the screen with the wlr-screencopy or wlr-export-dmabuf protocol.
+    #[cfg(feature = "wlr")]
+    Wayland(String),
+    /// The communication with the EIS server of a remote desktop session
+    /// failed.
+    #[cfg(feature = "libei")]
+    Ei(crate::desktop::remote_desktop::EiError),
 }
 
-impl std::error::Error for Error {}
+impl Error {
+    pub(crate) fn call(
+        proxy: &zbus::Proxy<'_>,
+        method: &str,
+        handle: Option<OwnedObjectPath>,
+        source: Error,
+    ) -> Self {
+        Self::Call {
+            interface: proxy.interface().to_string(),
+            method: method.to_owned(),
+            handle,
+            correlation_id: crate::correlation::current().map(|id| id.to_string()),
+            source: Box::new(source),
+        }
+    }
 
+    /// The cause of the error, without the context of the failed call.
+    ///
+    /// ```rust,no_run
+    /// use ashpd::{
+    ///     desktop::{screenshot, ResponseError},
+    ///     Error, WindowIdentifier,
+    /// };
+    ///
+    /// async fn run() -> ashpd::Result<()> {
+    ///     match screenshot::take(&WindowIdentifier::default(), true, false).await {
+    ///         Err(err) if matches!(err.root(), Error::Response(ResponseError::Cancelled)) => {
+    ///             println!("Cancelled by the user");
+    ///         }
+    ///         result => println!("{}", result?),
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn root(&self) -> &Error {
+        match self {
+            Self::Call { source, .. } => source.root(),
+            err => err,
+        }
+    }
+
+    /// The id given to [`with_correlation_id`](crate::with_correlation_id)
+    /// when the failed call was made.
+    pub fn correlation_id(&self) -> Option<&str> {
+        match self {
+            Self::Call { correlation_id, .. } => correlation_id.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The status code the portal replied with, if the request was answered
+    /// with something else than a success.
+    pub fn response_code(&self) -> Option<ResponseCode> {
+        match self.root() {
+            Self::Response(err) => Some(err.code()),
+            _ => None,
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            // The message of the wrapped errors is already part of the one of
+            // `Error`, forward to their cause instead.
+            Self::Call { source, .. } => source.source(),
+            Self::Zbus(e) | Self::Portal(PortalError::ZBus(e)) => e.source(),
+            Self::Io(e) => e.source(),
+            #[cfg(feature = "libei")]
+            Self::Ei(e) => e.source(),
+            _ => None,
+        }
+    }
+}
+
+/// The message of [`Error::Call`] ends with the one of its cause.
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Response(e) => f.write_str(&format!("Portal request didn't succeed: {}", e)),
-            Self::Zbus(e) => f.write_str(&format!("ZBus Error: {}", e)),
-            Self::Portal(e) => f.write_str(&format!("Portal request failed: {}", e)),
+            Self::Response(e) => write!(f, "Portal request didn't succeed: {}", e),
+            Self::Zbus(e) => write!(f, "D-Bus error: {}", e),
+            Self::Portal(e) => write!(f, "Portal request failed: {}", e),
             Self::NoResponse => f.write_str("Portal error: no response"),
             Self::ParseError(e) => f.write_str(e),
+            Self::DeviceNotGranted(d) => {
+                let device = match d {
+                    DeviceType::Keyboard => "keyboard",
+                    DeviceType::Pointer => "pointer",
+                    DeviceType::Touchscreen => "touchscreen",
+                };
+                write!(f, "Access to the {} wasn't granted", device)
+            }
+            Self::Unsupported => f.write_str("The portals are not supported on this platform"),
+            Self::Timeout(timeout) => write!(f, "No reply from the portal within {:?}", timeout),
+            Self::NulTerminated(position) => {
+                write!(f, "Unexpected NUL byte at position {}", position)
+            }
+            Self::InvalidIcon(e) => write!(f, "Invalid icon: {}", e),
+            Self::InvalidArgument(e) => write!(f, "Invalid argument: {}", e),
+            Self::RequiresVersion(required, found) => write!(
+                f,
+                "Requires version {} of the portal, found {}",
+                required, found
+            ),
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            Self::Call {
+                interface,
+                method,
+                handle,
+                correlation_id,
+                source,
+            } => {
+                write!(f, "{}.{} failed", interface, method)?;
+                if let Some(handle) = handle {
+                    write!(f, " for request {}", handle.as_str())?;
+                }
+                if let Some(id) = correlation_id {
+                    write!(f, " ({})", id)?;
+                }
+                write!(f, ": {}", source)
+            }
+            #[cfg(feature = "wlr")]
+            Self::Wayland(e) => write!(f, "Wayland error: {}", e),
+            #[cfg(feature = "libei")]
+            Self::Ei(e) => write!(f, "libei error: {}", e),
         }
     }
 }
 
+/// A value received or given as a `u32` that doesn't match any variant of
+/// the enum it is converted to, from a newer version of the portals for
+/// example.
+///
+/// ```rust
+/// use ashpd::desktop::remote_desktop::KeyState;
+///
+/// assert_eq!(KeyState::try_from(1), Ok(KeyState::Released));
+/// assert_eq!(u32::from(KeyState::Pressed), 0);
+///
+/// let err = KeyState::try_from(7).unwrap_err();
+/// assert_eq!(err.value(), 7);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownValue {
+    type_name: &'static str,
+    value: u32,
+}
+
+impl UnknownValue {
+    pub(crate) fn new(type_name: &'static str, value: u32) -> Self {
+        Self { type_name, value }
+    }
+
+    /// The name of the enum the value was converted to.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// The unknown value.
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+}
+
+impl std::fmt::Display for UnknownValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown {} value {}", self.type_name, self.value)
+    }
+}
+
+impl std::error::Error for UnknownValue {}
+
+impl From<UnknownValue> for Error {
+    fn from(e: UnknownValue) -> Self {
+        Self::ParseError(e.to_string())
+    }
+}
+
+#[cfg(feature = "libei")]
+impl From<crate::desktop::remote_desktop::EiError> for Error {
+    fn from(e: crate::desktop::remote_desktop::EiError) -> Self {
+        Self::Ei(e)
+    }
+}
+
+impl From<IconError> for Error {
+    fn from(e: IconError) -> Self {
+        Self::InvalidIcon(e)
+    }
+}
+
 impl From<ResponseError> for Error {
     fn from(e: ResponseError) -> Self {
         Self::Response(e)
@@ -80,8 +331,90 @@ impl From<zbus::Error> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
 impl From<zbus::zvariant::Error> for Error {
     fn from(e: zbus::zvariant::Error) -> Self {
         Self::Zbus(zbus::Error::Variant(e))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::error::Error as _;
+
+    use super::*;
+
+    fn call_error() -> Error {
+        Error::Call {
+            interface: "org.freedesktop.portal.Screenshot".to_owned(),
+            method: "Screenshot".to_owned(),
+            handle: Some(
+                OwnedObjectPath::try_from("/org/freedesktop/portal/desktop/request/1_2/ashpd_1")
+                    .unwrap(),
+            ),
+            correlation_id: None,
+            source: Box::new(Error::Response(ResponseError::Cancelled)),
+        }
+    }
+
+    #[test]
+    fn display() {
+        let mut err = call_error();
+        assert_eq!(
+            err.to_string(),
+            "org.freedesktop.portal.Screenshot.Screenshot failed for request /org/freedesktop/portal/desktop/request/1_2/ashpd_1: Portal request didn't succeed: Cancelled by the user"
+        );
+
+        if let Error::Call {
+            ref mut correlation_id,
+            ..
+        } = err
+        {
+            *correlation_id = Some("job-1".to_owned());
+        }
+        assert_eq!(err.correlation_id(), Some("job-1"));
+        assert_eq!(
+            err.to_string(),
+            "org.freedesktop.portal.Screenshot.Screenshot failed for request /org/freedesktop/portal/desktop/request/1_2/ashpd_1 (job-1): Portal request didn't succeed: Cancelled by the user"
+        );
+
+        let err = Error::from(PortalError::NotFound("No such portal".to_owned()));
+        assert_eq!(
+            err.to_string(),
+            "Portal request failed: org.freedesktop.portal.Error.NotFound: No such portal"
+        );
+    }
+
+    #[test]
+    fn source() {
+        // Already part of the message, the cause is reached with `root`.
+        let err = call_error();
+        assert!(err.source().is_none());
+        assert!(matches!(
+            err.root(),
+            Error::Response(ResponseError::Cancelled)
+        ));
+
+        let io = std::io::Error::new(std::io::ErrorKind::BrokenPipe, "Broken pipe");
+        let err = Error::from(zbus::Error::Io(io));
+        assert!(err.source().unwrap().is::<std::io::Error>());
+        let err = Error::Call {
+            interface: "org.freedesktop.portal.Screenshot".to_owned(),
+            method: "Screenshot".to_owned(),
+            handle: None,
+            correlation_id: None,
+            source: Box::new(err),
+        };
+        assert!(err.source().unwrap().is::<std::io::Error>());
+
+        let io = std::io::Error::new(std::io::ErrorKind::OutOfMemory, "Out of memory");
+        let err = Error::from(io);
+        assert_eq!(err.to_string(), "I/O error: Out of memory");
+        assert!(err.source().is_none());
+    }
+}
@@ -0,0 +1,75 @@
+use std::thread::JoinHandle;
+
+use futures::channel::oneshot;
+
+use crate::Error;
+
+/// Keeps the executor of a connection running, see [`spawn_executor`].
+///
+/// The executor stops once the handle is dropped.
+#[derive(Debug)]
+pub struct ExecutorHandle {
+    stop: oneshot::Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+impl ExecutorHandle {
+    /// Stop the executor and wait for its thread to exit.
+    pub fn stop(self) {
+        drop(self.stop);
+        if self.thread.join().is_err() {
+            #[cfg(feature = "log")]
+            tracing::warn!("The executor thread panicked");
+        }
+    }
+}
+
+/// Run the executor of `connection` on a dedicated thread.
+///
+/// zbus dispatches the incoming messages, the signals the portals are waiting
+/// on included, from tasks running on the executor of the connection. It is
+/// run on a thread of its own by default, but a connection built with
+/// [`zbus::ConnectionBuilder::internal_executor`] set to `false` relies on the
+/// application to tick it. Otherwise the requests never receive their response
+/// and the signal streams silently stall.
+///
+/// Applications that don't want to tick the executor from their own runtime
+/// can use this helper instead, and keep the returned handle as long as the
+/// connection is in use. Fails with [`Error::Io`] if the thread can't be
+/// spawned.
+///
+/// ```rust,no_run
+/// use ashpd::desktop::screenshot::ScreenshotProxy;
+/// use ashpd::WindowIdentifier;
+///
+/// async fn run() -> ashpd::Result<()> {
+///     let connection = zbus::ConnectionBuilder::session()?
+///         .internal_executor(false)
+///         .build()
+///         .await?;
+///     let _executor = ashpd::spawn_executor(&connection)?;
+///
+///     let proxy = ScreenshotProxy::new(&connection).await?;
+///     let uri = proxy
+///         .screenshot(&WindowIdentifier::default(), true, false)
+///         .await?;
+///     println!("{}", uri);
+///     Ok(())
+/// }
+/// ```
+///
+/// *Note* that with the `tokio` feature of zbus, the tasks are spawned on the
+/// tokio runtime instead and there is nothing to tick.
+pub fn spawn_executor(connection: &zbus::Connection) -> Result<ExecutorHandle, Error> {
+    let (stop, stopped) = oneshot::channel::<()>();
+    let connection = connection.clone();
+    let thread = std::thread::Builder::new()
+        .name("ashpd executor".into())
+        .spawn(move || {
+            futures::executor::block_on(connection.executor().run(async {
+                // Either stopped explicitly or the handle was dropped.
+                let _ = stopped.await;
+            }))
+        })?;
+    Ok(ExecutorHandle { stop, thread })
+}
@@ -0,0 +1,172 @@
+use std::fmt::{self, Debug};
+
+use futures::StreamExt;
+use zbus::zvariant::Type;
+
+use crate::{helpers::call_method, Error};
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+/// How a process spawned with [`FlatpakProxy::spawn_child`] or
+/// [`DevelopmentProxy::spawn`] ended, decoded from the wait status sent by
+/// Flatpak.
+///
+/// [`FlatpakProxy::spawn_child`]: super::FlatpakProxy::spawn_child
+/// [`DevelopmentProxy::spawn`]: super::DevelopmentProxy::spawn
+pub enum ExitStatus {
+    /// The process exited with the given code.
+    Exited(i32),
+    /// The process was terminated by the given signal.
+    Signaled(i32),
+}
+
+impl ExitStatus {
+    /// Decode a wait status, as returned by `waitpid`.
+    pub fn from_raw(status: u32) -> Self {
+        let signal = (status & 0x7f) as i32;
+        if signal == 0 {
+            Self::Exited(((status >> 8) & 0xff) as i32)
+        } else {
+            Self::Signaled(signal)
+        }
+    }
+
+    /// Whether the process exited with a code of `0`.
+    pub fn success(&self) -> bool {
+        *self == Self::Exited(0)
+    }
+
+    /// The exit code of the process, [`None`] if it was terminated by a
+    /// signal.
+    pub fn code(&self) -> Option<i32> {
+        match self {
+            Self::Exited(code) => Some(*code),
+            Self::Signaled(_) => None,
+        }
+    }
+
+    /// The signal that terminated the process, [`None`] if it exited.
+    pub fn signal(&self) -> Option<i32> {
+        match self {
+            Self::Exited(_) => None,
+            Self::Signaled(signal) => Some(*signal),
+        }
+    }
+}
+
+impl fmt::Display for ExitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exited(code) => write!(f, "exit status: {}", code),
+            Self::Signaled(signal) => write!(f, "signal: {}", signal),
+        }
+    }
+}
+
+/// A process spawned outside of the sandbox, either by the Flatpak portal or
+/// on the host.
+///
+/// Its exit is only reported once, the handle has to be kept around to
+/// [`wait`](Child::wait) for it.
+#[derive(Debug)]
+pub struct Child<'a> {
+    proxy: zbus::Proxy<'a>,
+    signal_method: &'static str,
+    exited_signal: &'static str,
+    pid: u32,
+    exited: zbus::SignalStream<'a>,
+}
+
+impl<'a> Child<'a> {
+    /// Call `method` returning the PID of the spawned process, listening to
+    /// `exited_signal` beforehand so its exit can't be missed.
+    pub(crate) async fn spawn<B>(
+        proxy: &zbus::Proxy<'a>,
+        method: &str,
+        body: &B,
+        exited_signal: &'static str,
+        signal_method: &'static str,
+    ) -> Result<Child<'a>, Error>
+    where
+        B: serde::ser::Serialize + Type + Debug,
+    {
+        let exited = proxy
+            .receive_signal(exited_signal)
+            .await
+            .map_err(|err| Error::call(proxy, exited_signal, None, err.into()))?;
+        let pid = call_method(proxy, method, body).await?;
+        Ok(Self {
+            proxy: proxy.clone(),
+            signal_method,
+            exited_signal,
+            pid,
+            exited,
+        })
+    }
+
+    /// The PID of the process, in the PID namespace of the caller of the
+    /// portal or of the host.
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Send the Unix signal `signal` to the process.
+    pub async fn kill(&self, signal: u32) -> Result<(), Error> {
+        self.send_signal(signal, false).await
+    }
+
+    /// Send the Unix signal `signal` to the process group of the process.
+    pub async fn kill_process_group(&self, signal: u32) -> Result<(), Error> {
+        self.send_signal(signal, true).await
+    }
+
+    async fn send_signal(&self, signal: u32, to_process_group: bool) -> Result<(), Error> {
+        call_method(
+            &self.proxy,
+            self.signal_method,
+            &(self.pid, signal, to_process_group),
+        )
+        .await
+    }
+
+    /// Wait for the process to exit.
+    pub async fn wait(mut self) -> Result<ExitStatus, Error> {
+        async {
+            while let Some(message) = self.exited.next().await {
+                let (pid, status) = message.body::<(u32, u32)>()?;
+                if pid == self.pid {
+                    return Ok(ExitStatus::from_raw(status));
+                }
+            }
+            Err(Error::NoResponse)
+        }
+        .await
+        .map_err(|err| Error::call(&self.proxy, self.exited_signal, None, err))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ExitStatus;
+
+    #[test]
+    fn exit_status() {
+        assert_eq!(ExitStatus::from_raw(0), ExitStatus::Exited(0));
+        assert!(ExitStatus::from_raw(0).success());
+
+        let status = ExitStatus::from_raw(2 << 8);
+        assert_eq!(status, ExitStatus::Exited(2));
+        assert_eq!(status.code(), Some(2));
+        assert_eq!(status.signal(), None);
+        assert!(!status.success());
+        assert_eq!(status.to_string(), "exit status: 2");
+
+        let status = ExitStatus::from_raw(9);
+        assert_eq!(status, ExitStatus::Signaled(9));
+        assert_eq!(status.code(), None);
+        assert_eq!(status.signal(), Some(9));
+        assert_eq!(status.to_string(), "signal: 9");
+
+        // Killed by SIGSEGV, dumping its core.
+        assert_eq!(ExitStatus::from_raw(0x80 | 11), ExitStatus::Signaled(11));
+    }
+}
@@ -0,0 +1,301 @@
+//! # Examples
+//!
+//! Run a command on the host and wait for it to exit, only works in a Flatpak
+//! with the `--talk-name=org.freedesktop.Flatpak` permission.
+//!
+//! ```rust,no_run
+//! use std::io::stdout;
+//!
+//! use ashpd::flatpak::{DevelopmentProxy, HostCommand, HostCommandFlags};
+//!
+//! async fn run() -> ashpd::Result<()> {
+//!     let connection = zbus::Connection::session().await?;
+//!     let proxy = DevelopmentProxy::new(&connection).await?;
+//!
+//!     let command = HostCommand::new("cargo")
+//!         .args(["build", "--release"])
+//!         .current_dir("/home/user/Projects/ashpd")
+//!         .env("CARGO_TERM_COLOR", "always")
+//!         .stdout(&stdout())
+//!         .flags(HostCommandFlags::WatchBus);
+//!     let process = proxy.spawn(&command).await?;
+//!     println!("Started {}", process.pid());
+//!
+//!     let status = process.wait().await?;
+//!     if let Some(signal) = status.signal() {
+//!         println!("Killed by the signal {}", signal);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    fmt::Debug,
+    os::unix::io::AsRawFd,
+    path::{Path, PathBuf},
+};
+
+use enumflags2::{bitflags, BitFlags};
+use futures::Stream;
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use zbus::zvariant::{Fd, Type};
+
+use super::{Child, DEVELOPMENT_DESTINATION, DEVELOPMENT_PATH, INTERFACE_DEVELOPMENT};
+use crate::{
+    helpers::{call_method, null_terminated_from_path, receive_signal_stream},
+    Error,
+};
+
+#[bitflags]
+#[derive(Serialize_repr, Deserialize_repr, PartialEq, Copy, Clone, Debug, Type)]
+#[repr(u32)]
+/// Flags affecting the command run on the host.
+pub enum HostCommandFlags {
+    #[doc(alias = "FLATPAK_HOST_COMMAND_FLAGS_CLEAR_ENV")]
+    /// Clear the environment, only the variables set on the command are
+    /// passed.
+    ClearEnv,
+    #[doc(alias = "FLATPAK_HOST_COMMAND_FLAGS_WATCH_BUS")]
+    /// Kill the process when the caller disappears from the session bus.
+    WatchBus,
+}
+
+#[derive(Debug, Clone, Default)]
+/// A command to run on the host with [`DevelopmentProxy::spawn`].
+///
+/// The file descriptors passed to the command are only borrowed, they have to
+/// stay open until the command is started.
+pub struct HostCommand {
+    cwd_path: Option<PathBuf>,
+    argv: Vec<OsString>,
+    fds: HashMap<u32, Fd>,
+    envs: HashMap<String, String>,
+    flags: BitFlags<HostCommandFlags>,
+}
+
+impl HostCommand {
+    /// Create a new command running `program`, looked up in the `PATH` of the
+    /// host.
+    ///
+    /// It runs in the working directory of the Flatpak session helper unless
+    /// [`HostCommand::current_dir`] is set.
+    pub fn new(program: impl AsRef<Path>) -> Self {
+        Self {
+            argv: vec![program.as_ref().into()],
+            ..Default::default()
+        }
+    }
+
+    /// Adds an argument to pass to the program.
+    #[must_use]
+    pub fn arg(mut self, arg: impl AsRef<Path>) -> Self {
+        self.argv.push(arg.as_ref().into());
+        self
+    }
+
+    /// Adds arguments to pass to the program.
+    #[must_use]
+    pub fn args(mut self, args: impl IntoIterator<Item = impl AsRef<Path>>) -> Self {
+        self.argv
+            .extend(args.into_iter().map(|arg| arg.as_ref().into()));
+        self
+    }
+
+    /// Sets the working directory of the process, a path on the host.
+    #[must_use]
+    pub fn current_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.cwd_path = Some(dir.as_ref().to_owned());
+        self
+    }
+
+    /// Sets an environment variable of the process.
+    #[must_use]
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.envs.insert(key.into(), value.into());
+        self
+    }
+
+    /// Pass `fd` to the process as the file descriptor `target`.
+    #[must_use]
+    pub fn fd(mut self, target: u32, fd: &impl AsRawFd) -> Self {
+        self.fds.insert(target, Fd::from(fd.as_raw_fd()));
+        self
+    }
+
+    /// Pass `fd` to the process as its standard input.
+    #[must_use]
+    pub fn stdin(self, fd: &impl AsRawFd) -> Self {
+        self.fd(0, fd)
+    }
+
+    /// Pass `fd` to the process as its standard output.
+    #[must_use]
+    pub fn stdout(self, fd: &impl AsRawFd) -> Self {
+        self.fd(1, fd)
+    }
+
+    /// Pass `fd` to the process as its standard error.
+    #[must_use]
+    pub fn stderr(self, fd: &impl AsRawFd) -> Self {
+        self.fd(2, fd)
+    }
+
+    /// Sets the flags affecting the process.
+    #[must_use]
+    pub fn flags(mut self, flags: impl Into<BitFlags<HostCommandFlags>>) -> Self {
+        self.flags = flags.into();
+        self
+    }
+
+    // The paths and arguments are only converted to NUL terminated
+    // bytestrings when the command is run, failing if one contains a NUL byte.
+    #[allow(clippy::type_complexity)]
+    fn body(
+        &self,
+    ) -> Result<
+        (
+            Vec<u8>,
+            Vec<Vec<u8>>,
+            &HashMap<u32, Fd>,
+            &HashMap<String, String>,
+            BitFlags<HostCommandFlags>,
+        ),
+        Error,
+    > {
+        let cwd_path = match &self.cwd_path {
+            Some(path) => null_terminated_from_path(path)?,
+            None => Vec::new(),
+        };
+        let argv = self
+            .argv
+            .iter()
+            .map(null_terminated_from_path)
+            .collect::<Result<_, _>>()?;
+        Ok((cwd_path, argv, &self.fds, &self.envs, self.flags))
+    }
+}
+
+/// The interface lets the applications running in a Flatpak, typically
+/// development tools, run commands on the host.
+///
+/// It is exposed by the Flatpak session helper and not by the portal, the
+/// applications need the `--talk-name=org.freedesktop.Flatpak` permission to
+/// use it.
+///
+/// Wrapper of the DBus interface: [`org.freedesktop.Flatpak.Development`](https://docs.flatpak.org/en/latest/libflatpak-api-reference.html#gdbus-org.freedesktop.Flatpak.Development).
+#[derive(Clone)]
+#[doc(alias = "org.freedesktop.Flatpak.Development")]
+pub struct DevelopmentProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(DevelopmentProxy, INTERFACE_DEVELOPMENT);
+impl_proxy_timeout!(DevelopmentProxy);
+
+impl<'a> DevelopmentProxy<'a> {
+    /// Create a new instance of [`DevelopmentProxy`].
+    pub async fn new(connection: &zbus::Connection) -> Result<DevelopmentProxy<'a>, Error> {
+        let proxy = zbus::ProxyBuilder::new_bare(connection)
+            .interface(INTERFACE_DEVELOPMENT)?
+            .path(DEVELOPMENT_PATH)?
+            .destination(DEVELOPMENT_DESTINATION)?
+            .build()
+            .await?;
+        Ok(Self(proxy, None))
+    }
+
+    /// Get a reference to the underlying Proxy.
+    pub fn inner(&self) -> &zbus::Proxy<'_> {
+        &self.0
+    }
+
+    /// Emitted when a process started by
+    /// [`host_command()`][`DevelopmentProxy::host_command`] exits, along with
+    /// its raw wait status.
+    ///
+    /// See also [`DevelopmentProxy::spawn`] to wait for a given process.
+    #[doc(alias = "HostCommandExited")]
+    pub async fn receive_host_command_exited(
+        &self,
+    ) -> Result<impl Stream<Item = (u32, u32)> + Unpin + 'a, Error> {
+        receive_signal_stream(&self.0, "HostCommandExited").await
+    }
+
+    /// Run a command on the host, returning the PID of the process.
+    ///
+    /// Its exit is only reported to the listeners subscribed to
+    /// [`receive_host_command_exited()`][`DevelopmentProxy::receive_host_command_exited`]
+    /// when it happens, [`DevelopmentProxy::spawn`] subscribes before starting
+    /// the command.
+    #[doc(alias = "HostCommand")]
+    pub async fn host_command(&self, command: &HostCommand) -> Result<u32, Error> {
+        call_method(self, "HostCommand", &command.body()?).await
+    }
+
+    /// Run a command on the host, its exit status can be awaited with
+    /// [`Child::wait`].
+    pub async fn spawn(&self, command: &HostCommand) -> Result<Child<'a>, Error> {
+        Child::spawn(
+            &self.0,
+            "HostCommand",
+            &command.body()?,
+            "HostCommandExited",
+            "HostCommandSignal",
+        )
+        .await
+    }
+
+    /// Send a Unix signal to a process started by
+    /// [`host_command()`][`DevelopmentProxy::host_command`].
+    ///
+    /// # Arguments
+    ///
+    /// * `pid` - The PID of the process to send the signal to.
+    /// * `signal` - The signal to send.
+    /// * `to_process_group` - Whether to send the signal to the process group.
+    #[doc(alias = "HostCommandSignal")]
+    pub async fn host_command_signal(
+        &self,
+        pid: u32,
+        signal: u32,
+        to_process_group: bool,
+    ) -> Result<(), Error> {
+        call_method(self, "HostCommandSignal", &(pid, signal, to_process_group)).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use zbus::zvariant::Type;
+
+    use super::{HostCommand, HostCommandFlags};
+    use crate::Error;
+
+    fn signature<T: Type>(_: &T) -> String {
+        T::signature().to_string()
+    }
+
+    #[test]
+    fn host_command() {
+        let command = HostCommand::new("ls")
+            .arg("-l")
+            .current_dir("/tmp")
+            .env("LANG", "C")
+            .stdout(&std::io::stdout())
+            .flags(HostCommandFlags::ClearEnv | HostCommandFlags::WatchBus);
+        let body = command.body().unwrap();
+        assert_eq!(signature(&body), "(ayaaya{uh}a{ss}u)");
+        assert_eq!(body.0, b"/tmp\0");
+        assert_eq!(body.1, vec![b"ls\0".to_vec(), b"-l\0".to_vec()]);
+        assert_eq!(body.2.len(), 1);
+        assert_eq!(body.4.bits(), 3);
+
+        assert_eq!(HostCommand::new("ls").body().unwrap().0, b"");
+    }
+
+    #[test]
+    fn nul_byte() {
+        let command = HostCommand::new("ls").arg("a\0b");
+        assert!(matches!(command.body(), Err(Error::NulTerminated(1))));
+    }
+}
@@ -26,21 +26,28 @@
 //! }
 //! ```
 
-pub(crate) const DESTINATION: &str = "org.freedesktop.portal.Flatpak";
-pub(crate) const PATH: &str = "/org/freedesktop/portal/Flatpak";
+/// The bus name of the Flatpak interfaces.
+pub const DESTINATION: &str = "org.freedesktop.portal.Flatpak";
+/// The object path the Flatpak interface is exported at.
+pub const PATH: &str = "/org/freedesktop/portal/Flatpak";
+/// The interface wrapped by [`FlatpakProxy`].
+pub const INTERFACE_FLATPAK: &str = "org.freedesktop.portal.Flatpak";
+/// The interface wrapped by [`UpdateMonitorProxy`].
+pub const INTERFACE_UPDATE_MONITOR: &str = "org.freedesktop.portal.Flatpak.UpdateMonitor";
+/// The bus name of the Flatpak session helper.
+pub const DEVELOPMENT_DESTINATION: &str = "org.freedesktop.Flatpak";
+/// The object path the Development interface is exported at.
+pub const DEVELOPMENT_PATH: &str = "/org/freedesktop/Flatpak/Development";
+/// The interface wrapped by [`DevelopmentProxy`].
+pub const INTERFACE_DEVELOPMENT: &str = "org.freedesktop.Flatpak.Development";
 
 use enumflags2::{bitflags, BitFlags};
 use serde::Serialize;
 use serde_repr::{Deserialize_repr, Serialize_repr};
-use std::os::unix::ffi::OsStrExt;
-use std::os::unix::prelude::RawFd;
-use std::{collections::HashMap, ffi::CString, fmt::Debug, os::unix::prelude::AsRawFd, path::Path};
+use std::{collections::HashMap, fmt::Debug, os::unix::prelude::AsRawFd, path::Path};
 use zbus::zvariant::{DeserializeDict, Fd, OwnedObjectPath, SerializeDict, Type};
 
-use crate::{
-    helpers::{call_method, receive_signal},
-    Error,
-};
+use crate::{helpers::null_terminated_from_path, raw_options::RawOptions, Error};
 
 #[bitflags]
 #[derive(Serialize_repr, Deserialize_repr, PartialEq, Copy, Clone, Debug, Type)]
@@ -102,45 +109,53 @@ pub enum SupportsFlags {
     ExposePids,
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+#[derive(Type, Debug, Default, Clone, PartialEq)]
 /// Specified options for a [`FlatpakProxy::spawn`] request.
 #[zvariant(signature = "dict")]
 pub struct SpawnOptions {
     /// A list of filenames for files inside the sandbox that will be exposed to
     /// the new sandbox, for reading and writing.
-    #[zvariant(rename = "sandbox-expose")]
     sandbox_expose: Option<Vec<String>>,
     /// A list of filenames for files inside the sandbox that will be exposed to
     /// the new sandbox, read-only.
-    #[zvariant(rename = "sandbox-expose-ro")]
     sandbox_expose_ro: Option<Vec<String>>,
     /// A list of file descriptor for files inside the sandbox that will be
     /// exposed to the new sandbox, for reading and writing.
-    #[zvariant(rename = "sandbox-expose-fd")]
     sandbox_expose_fd: Option<Vec<Fd>>,
     /// A list of file descriptor for files inside the sandbox that will be
     /// exposed to the new sandbox, read-only.
-    #[zvariant(rename = "sandbox-expose-fd-ro")]
     sandbox_expose_fd_ro: Option<Vec<Fd>>,
     /// Flags affecting the created sandbox.
-    #[zvariant(rename = "sandbox-flags")]
     sandbox_flags: Option<BitFlags<SandboxFlags>>,
     /// A list of environment variables to remove.
-    #[zvariant(rename = "unset-env")]
     unset_env: Option<Vec<String>>,
     /// A file descriptor of the directory that  will be used as `/usr` in the new sandbox.
-    #[zvariant(rename = "usr-fd")]
-    usr_fd: Option<RawFd>,
+    usr_fd: Option<Fd>,
     /// A file descriptor of the directory that  will be used as `/app` in the new sandbox.
-    #[zvariant(rename = "app-fd")]
-    app_fd: Option<RawFd>,
+    app_fd: Option<Fd>,
+    /// The options without a setter.
+    raw: RawOptions,
 }
 
+impl_options_dict!(SpawnOptions {
+    sandbox_expose = "sandbox-expose",
+    sandbox_expose_ro = "sandbox-expose-ro",
+    sandbox_expose_fd = "sandbox-expose-fd",
+    sandbox_expose_fd_ro = "sandbox-expose-fd-ro",
+    sandbox_flags = "sandbox-flags",
+    unset_env = "unset-env",
+    usr_fd = "usr-fd",
+    app_fd = "app-fd",
+});
+
 impl SpawnOptions {
     /// Sets the list of filenames for files to expose the new sandbox.
     /// **Note** absolute paths or subdirectories are not allowed.
     #[must_use]
-    pub fn sandbox_expose(mut self, sandbox_expose: &[impl AsRef<str> + Type + Serialize]) -> Self {
+    pub fn with_sandbox_expose(
+        mut self,
+        sandbox_expose: &[impl AsRef<str> + Type + Serialize],
+    ) -> Self {
         self.sandbox_expose = Some(
             sandbox_expose
                 .iter()
@@ -154,7 +169,7 @@ impl SpawnOptions {
     /// read-only.
     /// **Note** absolute paths or subdirectories are not allowed.
     #[must_use]
-    pub fn sandbox_expose_ro(
+    pub fn with_sandbox_expose_ro(
         mut self,
         sandbox_expose_ro: &[impl AsRef<str> + Type + Serialize],
     ) -> Self {
@@ -169,7 +184,7 @@ impl SpawnOptions {
 
     /// Sets the list of file descriptors of files to expose the new sandbox.
     #[must_use]
-    pub fn sandbox_expose_fd(mut self, sandbox_expose_fd: &[&impl AsRawFd]) -> Self {
+    pub fn with_sandbox_expose_fd(mut self, sandbox_expose_fd: &[&impl AsRawFd]) -> Self {
         self.sandbox_expose_fd = Some(
             sandbox_expose_fd
                 .iter()
@@ -182,7 +197,7 @@ impl SpawnOptions {
     /// Sets the list of file descriptors of files to expose the new sandbox,
     /// read-only.
     #[must_use]
-    pub fn sandbox_expose_fd_ro(mut self, sandbox_expose_fd_ro: &[&impl AsRawFd]) -> Self {
+    pub fn with_sandbox_expose_fd_ro(mut self, sandbox_expose_fd_ro: &[&impl AsRawFd]) -> Self {
         self.sandbox_expose_fd_ro = Some(
             sandbox_expose_fd_ro
                 .iter()
@@ -194,34 +209,75 @@ impl SpawnOptions {
 
     /// Sets the created sandbox flags.
     #[must_use]
-    pub fn sandbox_flags(mut self, sandbox_flags: BitFlags<SandboxFlags>) -> Self {
-        self.sandbox_flags = Some(sandbox_flags);
+    pub fn with_sandbox_flags(mut self, sandbox_flags: impl Into<BitFlags<SandboxFlags>>) -> Self {
+        self.sandbox_flags = Some(sandbox_flags.into());
         self
     }
 
     /// Env variables to unset.
     #[must_use]
-    pub fn unset_env(mut self, env: &[&str]) -> Self {
+    pub fn with_unset_env(mut self, env: &[&str]) -> Self {
         self.unset_env = Some(env.iter().map(|e| e.to_string()).collect());
         self
     }
 
     /// Set a file descriptor of the directory that  will be used as `/usr` in the new sandbox.
     #[must_use]
-    pub fn usr_fd(mut self, fd: impl AsRawFd) -> Self {
-        self.usr_fd = Some(fd.as_raw_fd());
+    pub fn with_usr_fd(mut self, fd: impl AsRawFd) -> Self {
+        self.usr_fd = Some(Fd::from(fd.as_raw_fd()));
         self
     }
 
     /// Set a file descriptor of the directory that  will be used as `/app` in the new sandbox.
     #[must_use]
-    pub fn app_fd(mut self, fd: impl AsRawFd) -> Self {
-        self.app_fd = Some(fd.as_raw_fd());
+    pub fn with_app_fd(mut self, fd: impl AsRawFd) -> Self {
+        self.app_fd = Some(Fd::from(fd.as_raw_fd()));
         self
     }
+
+    /// The filenames of the files exposed to the new sandbox.
+    pub fn sandbox_expose(&self) -> &[String] {
+        self.sandbox_expose.as_deref().unwrap_or_default()
+    }
+
+    /// The filenames of the files exposed to the new sandbox, read-only.
+    pub fn sandbox_expose_ro(&self) -> &[String] {
+        self.sandbox_expose_ro.as_deref().unwrap_or_default()
+    }
+
+    /// The file descriptors of the files exposed to the new sandbox.
+    pub fn sandbox_expose_fd(&self) -> &[Fd] {
+        self.sandbox_expose_fd.as_deref().unwrap_or_default()
+    }
+
+    /// The file descriptors of the files exposed to the new sandbox,
+    /// read-only.
+    pub fn sandbox_expose_fd_ro(&self) -> &[Fd] {
+        self.sandbox_expose_fd_ro.as_deref().unwrap_or_default()
+    }
+
+    /// The flags of the created sandbox, if set.
+    pub fn sandbox_flags(&self) -> Option<BitFlags<SandboxFlags>> {
+        self.sandbox_flags
+    }
+
+    /// The environment variables to unset.
+    pub fn unset_env(&self) -> &[String] {
+        self.unset_env.as_deref().unwrap_or_default()
+    }
+
+    /// The file descriptor of the directory used as `/usr`, if set.
+    pub fn usr_fd(&self) -> Option<Fd> {
+        self.usr_fd
+    }
+
+    /// The file descriptor of the directory used as `/app`, if set.
+    pub fn app_fd(&self) -> Option<Fd> {
+        self.app_fd
+    }
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
 /// Specified options for a [`FlatpakProxy::create_update_monitor`] request.
 ///
 /// Currently there are no possible options yet.
@@ -233,20 +289,23 @@ struct CreateMonitorOptions {}
 /// more sandboxed instance.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Flatpak`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Flatpak).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.Flatpak")]
-pub struct FlatpakProxy<'a>(zbus::Proxy<'a>);
+pub struct FlatpakProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(FlatpakProxy, INTERFACE_FLATPAK);
+impl_proxy_timeout!(FlatpakProxy);
 
 impl<'a> FlatpakProxy<'a> {
     /// Create a new instance of [`FlatpakProxy`].
     pub async fn new(connection: &zbus::Connection) -> Result<FlatpakProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Flatpak")?
+            .interface(INTERFACE_FLATPAK)?
             .path(PATH)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -265,30 +324,11 @@ impl<'a> FlatpakProxy<'a> {
     #[doc(alias = "xdp_portal_update_monitor_start")]
     pub async fn create_update_monitor(&self) -> Result<UpdateMonitorProxy<'a>, Error> {
         let options = CreateMonitorOptions::default();
-        let path: OwnedObjectPath =
-            call_method(self.inner(), "CreateUpdateMonitor", &(options)).await?;
+        let path = self.create_update_monitor_raw(options).await?;
 
         UpdateMonitorProxy::new(self.inner().connection(), path.into_inner()).await
     }
 
-    /// Emitted when a process starts by [`spawn()`][`FlatpakProxy::spawn`].
-    #[doc(alias = "SpawnStarted")]
-    pub async fn receive_spawn_started(&self) -> Result<(u32, u32), Error> {
-        receive_signal(self.inner(), "SpawnStarted").await
-    }
-
-    /// Emitted when a process started by [`spawn()`][`FlatpakProxy::spawn`]
-    /// exits.
-    ///
-    /// # Specifications
-    ///
-    /// See also [`SpawnExited`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-Flatpak.SpawnExited).
-    #[doc(alias = "SpawnExited")]
-    #[doc(alias = "XdpPortal::spawn-exited")]
-    pub async fn receive_spawn_existed(&self) -> Result<(u32, u32), Error> {
-        receive_signal(self.inner(), "SpawnExited").await
-    }
-
     /// This methods let you start a new instance of your application,
     /// optionally enabling a tighter sandbox.
     ///
@@ -321,73 +361,148 @@ impl<'a> FlatpakProxy<'a> {
         flags: BitFlags<SpawnFlags>,
         options: SpawnOptions,
     ) -> Result<u32, Error> {
-        let cwd_path = CString::new(cwd_path.as_ref().as_os_str().as_bytes())
-            .expect("The `cwd_path` should not contain a trailing 0 bytes");
+        let cwd_path = null_terminated_from_path(cwd_path)?;
         let argv = argv
             .iter()
-            .map(|s| {
-                CString::new(s.as_ref().as_os_str().as_bytes())
-                    .expect("The `argv` should not contain a trailing 0 bytes")
-            })
-            .collect::<Vec<_>>();
-        call_method(
-            self.inner(),
-            "Spawn",
-            &(
-                cwd_path.as_bytes_with_nul(),
-                argv.iter()
-                    .map(|c| c.as_bytes_with_nul())
-                    .collect::<Vec<_>>(),
-                fds,
-                envs,
-                flags,
-                options,
-            ),
-        )
-        .await
+            .map(null_terminated_from_path)
+            .collect::<Result<Vec<_>, _>>()?;
+        self.spawn_raw(cwd_path, argv, fds, envs, flags, options)
+            .await
     }
 
-    /// This methods let you send a Unix signal to a process that was started
-    /// [`spawn()`][`FlatpakProxy::spawn`].
-    ///
-    /// # Arguments
-    ///
-    /// * `pid` - The PID of the process to send the signal to.
-    /// * `signal` - The signal to send.
-    /// * `to_process_group` - Whether to send the signal to the process group.
-    ///
-    /// # Specifications
-    ///
-    /// See also [`SpawnSignal`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Flatpak.SpawnSignal).
-    #[doc(alias = "SpawnSignal")]
-    #[doc(alias = "xdp_portal_spawn_signal")]
-    pub async fn spawn_signal(
+    /// Like [`spawn()`][`FlatpakProxy::spawn`], returning a [`Child`] to wait
+    /// for the exit of the process or to send it signals.
+    #[doc(alias = "Spawn")]
+    pub async fn spawn_child(
         &self,
-        pid: u32,
-        signal: u32,
-        to_process_group: bool,
-    ) -> Result<(), Error> {
-        call_method(
-            self.inner(),
+        cwd_path: (impl AsRef<Path> + Type + Serialize + Debug),
+        argv: &[impl AsRef<Path> + Type + Serialize + Debug],
+        fds: HashMap<u32, Fd>,
+        envs: HashMap<&str, &str>,
+        flags: BitFlags<SpawnFlags>,
+        options: SpawnOptions,
+    ) -> Result<Child<'a>, Error> {
+        let cwd_path = null_terminated_from_path(cwd_path)?;
+        let argv = argv
+            .iter()
+            .map(null_terminated_from_path)
+            .collect::<Result<Vec<_>, _>>()?;
+        Child::spawn(
+            &self.0,
+            "Spawn",
+            &(cwd_path, argv, fds, envs, flags, options),
+            "SpawnExited",
             "SpawnSignal",
-            &(pid, signal, to_process_group),
         )
         .await
     }
 
-    /// Flags marking what optional features are available.
-    ///
-    /// # Specifications
-    ///
-    /// See also [`supports`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-property-org-freedesktop-portal-Flatpak.supports).
-    pub async fn supports(&self) -> Result<BitFlags<SupportsFlags>, Error> {
-        self.inner()
-            .get_property::<BitFlags<SupportsFlags>>("supports")
-            .await
-            .map_err(From::from)
+    portal_members! {
+        fn create_update_monitor_raw(
+            options: CreateMonitorOptions,
+        ) -> OwnedObjectPath = method "CreateUpdateMonitor";
+        fn spawn_raw(
+            cwd_path: Vec<u8>,
+            argv: Vec<Vec<u8>>,
+            fds: HashMap<u32, Fd>,
+            envs: HashMap<&str, &str>,
+            flags: BitFlags<SpawnFlags>,
+            options: SpawnOptions,
+        ) -> u32 = method "Spawn";
+
+        /// Emitted when a process starts by [`spawn()`][`FlatpakProxy::spawn`].
+        pub fn receive_spawn_started() -> Stream<(u32, u32)> = signal "SpawnStarted";
+
+        /// Emitted when a process started by [`spawn()`][`FlatpakProxy::spawn`]
+        /// exits.
+        ///
+        /// # Specifications
+        ///
+        /// See also [`SpawnExited`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-Flatpak.SpawnExited).
+        #[doc(alias = "XdpPortal::spawn-exited")]
+        pub fn receive_spawn_exited() -> Stream<(u32, u32)> = signal "SpawnExited";
+
+        /// This methods let you send a Unix signal to a process that was started
+        /// [`spawn()`][`FlatpakProxy::spawn`].
+        ///
+        /// # Arguments
+        ///
+        /// * `pid` - The PID of the process to send the signal to.
+        /// * `signal` - The signal to send.
+        /// * `to_process_group` - Whether to send the signal to the process group.
+        ///
+        /// # Specifications
+        ///
+        /// See also [`SpawnSignal`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Flatpak.SpawnSignal).
+        #[doc(alias = "xdp_portal_spawn_signal")]
+        pub fn spawn_signal(
+            pid: u32,
+            signal: u32,
+            to_process_group: bool,
+        ) -> () = method "SpawnSignal";
+
+        /// Flags marking what optional features are available.
+        ///
+        /// # Specifications
+        ///
+        /// See also [`supports`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-property-org-freedesktop-portal-Flatpak.supports).
+        pub fn supports() -> BitFlags<SupportsFlags> = property "supports";
     }
 }
 
 /// Monitor if there's an update it and install it.
 mod update_monitor;
 pub use update_monitor::{UpdateInfo, UpdateMonitorProxy, UpdateProgress, UpdateStatus};
+
+/// Run commands on the host, for the development tools.
+mod development;
+pub use development::{DevelopmentProxy, HostCommand, HostCommandFlags};
+
+/// Wait for the spawned processes.
+mod child;
+pub use child::{Child, ExitStatus};
+
+#[cfg(test)]
+mod test {
+    use zbus::zvariant::Fd;
+
+    use super::{
+        CreateMonitorOptions, FlatpakProxy, SandboxFlags, SpawnOptions, INTERFACE_FLATPAK,
+    };
+    use crate::helpers::{assert_members, assert_serialized_dict};
+
+    #[test]
+    fn members() {
+        assert_members(INTERFACE_FLATPAK, &FlatpakProxy::members());
+    }
+
+    #[test]
+    fn options() {
+        let options = SpawnOptions {
+            sandbox_expose: Some(vec!["data".to_owned()]),
+            sandbox_expose_ro: Some(vec!["config".to_owned()]),
+            sandbox_expose_fd: Some(vec![Fd::from(0)]),
+            sandbox_expose_fd_ro: Some(vec![Fd::from(0)]),
+            sandbox_flags: Some(SandboxFlags::DisplayAccess | SandboxFlags::SoundAccess),
+            unset_env: Some(vec!["LANG".to_owned()]),
+            usr_fd: Some(Fd::from(0)),
+            app_fd: Some(Fd::from(0)),
+            raw: Default::default(),
+        };
+        assert_serialized_dict(
+            &options,
+            &[
+                ("app-fd", "h"),
+                ("sandbox-expose", "as"),
+                ("sandbox-expose-fd", "ah"),
+                ("sandbox-expose-fd-ro", "ah"),
+                ("sandbox-expose-ro", "as"),
+                ("sandbox-flags", "u"),
+                ("unset-env", "as"),
+                ("usr-fd", "h"),
+            ],
+        );
+
+        assert_serialized_dict(&CreateMonitorOptions::default(), &[]);
+    }
+}
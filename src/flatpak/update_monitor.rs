@@ -6,39 +6,44 @@
 //! ```rust,no_run
 //! use ashpd::flatpak::FlatpakProxy;
 //! use ashpd::WindowIdentifier;
+//! use futures::StreamExt;
 //!
 //! async fn run() -> ashpd::Result<()> {
 //!     let connection = zbus::Connection::session().await?;
 //!     let proxy = FlatpakProxy::new(&connection).await?;
 //!
 //!     let monitor = proxy.create_update_monitor().await?;
-//!     let info = monitor.receive_update_available().await?;
+//!     let info = monitor.receive_update_available().await?.next().await;
+//!     println!("{:#?}", info);
 //!
+//!     let mut progress = monitor.receive_progress().await?;
 //!     monitor.update(&WindowIdentifier::default()).await?;
-//!     let progress = monitor.receive_progress().await?;
-//!     println!("{:#?}", progress);
+//!     while let Some(progress) = progress.next().await {
+//!         println!("{:#?}", progress);
+//!     }
 //!
 //!     Ok(())
 //! }
 //! ```
 
+use futures::Stream;
 use serde::{Deserialize, Serialize};
 use zbus::zvariant::{DeserializeDict, ObjectPath, SerializeDict, Type};
 
-use super::DESTINATION;
+use super::{DESTINATION, INTERFACE_UPDATE_MONITOR};
 use crate::{
-    helpers::{call_method, receive_signal},
+    helpers::{call_method, receive_signal_stream},
     Error, WindowIdentifier,
 };
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug, Default)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Default, Clone, PartialEq, Eq)]
 /// Specified options for a [`UpdateMonitorProxy::update`] request.
 ///
 /// Currently there are no possible options yet.
 #[zvariant(signature = "dict")]
 struct UpdateOptions {}
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Clone, PartialEq, Eq)]
 /// A response containing the update information when an update is available.
 #[zvariant(signature = "dict")]
 pub struct UpdateInfo {
@@ -67,7 +72,7 @@ impl UpdateInfo {
     }
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Copy, Clone, Debug, Type)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug, Type)]
 /// The update status.
 pub enum UpdateStatus {
     #[doc(alias = "XDP_UPDATE_STATUS_RUNNING")]
@@ -84,7 +89,7 @@ pub enum UpdateStatus {
     Failed = 3,
 }
 
-#[derive(SerializeDict, DeserializeDict, Type, Debug)]
+#[derive(SerializeDict, DeserializeDict, Type, Debug, Clone, PartialEq, Eq)]
 /// A response of the update progress signal.
 #[zvariant(signature = "dict")]
 pub struct UpdateProgress {
@@ -108,9 +113,12 @@ pub struct UpdateProgress {
 /// more sandboxed instance.
 ///
 /// Wrapper of the DBus interface: [`org.freedesktop.portal.Flatpak.UpdateMonitor`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-org.freedesktop.portal.Flatpak.UpdateMonitor).
-#[derive(Debug)]
+#[derive(Clone)]
 #[doc(alias = "org.freedesktop.portal.Flatpak.UpdateMonitor")]
-pub struct UpdateMonitorProxy<'a>(zbus::Proxy<'a>);
+pub struct UpdateMonitorProxy<'a>(zbus::Proxy<'a>, Option<std::time::Duration>);
+
+impl_try_from_proxy!(UpdateMonitorProxy, INTERFACE_UPDATE_MONITOR);
+impl_proxy_timeout!(UpdateMonitorProxy);
 
 impl<'a> UpdateMonitorProxy<'a> {
     /// Create a new instance of [`UpdateMonitorProxy`].
@@ -122,12 +130,12 @@ impl<'a> UpdateMonitorProxy<'a> {
         path: ObjectPath<'a>,
     ) -> Result<UpdateMonitorProxy<'a>, Error> {
         let proxy = zbus::ProxyBuilder::new_bare(connection)
-            .interface("org.freedesktop.portal.Flatpak.UpdateMonitor")?
+            .interface(INTERFACE_UPDATE_MONITOR)?
             .path(path)?
             .destination(DESTINATION)?
             .build()
             .await?;
-        Ok(Self(proxy))
+        Ok(Self(proxy, None))
     }
 
     /// Get a reference to the underlying Proxy.
@@ -142,8 +150,10 @@ impl<'a> UpdateMonitorProxy<'a> {
     /// See also [`Progress`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-Flatpak-UpdateMonitor.Progress).
     #[doc(alias = "Progress")]
     #[doc(alias = "XdpPortal::update-progress")]
-    pub async fn receive_progress(&self) -> Result<UpdateProgress, Error> {
-        receive_signal(self.inner(), "Progress").await
+    pub async fn receive_progress(
+        &self,
+    ) -> Result<impl Stream<Item = UpdateProgress> + Unpin + 'a, Error> {
+        receive_signal_stream(&self.0, "Progress").await
     }
 
     /// A signal received when there's an application update.
@@ -153,8 +163,10 @@ impl<'a> UpdateMonitorProxy<'a> {
     /// See also [`UpdateAvailable`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-signal-org-freedesktop-portal-Flatpak-UpdateMonitor.UpdateAvailable).
     #[doc(alias = "UpdateAvailable")]
     #[doc(alias = "XdpPortal::update-available")]
-    pub async fn receive_update_available(&self) -> Result<UpdateInfo, Error> {
-        receive_signal(self.inner(), "UpdateAvailable").await
+    pub async fn receive_update_available(
+        &self,
+    ) -> Result<impl Stream<Item = UpdateInfo> + Unpin + 'a, Error> {
+        receive_signal_stream(&self.0, "UpdateAvailable").await
     }
 
     /// Asks to install an update of the calling app.
@@ -169,7 +181,7 @@ impl<'a> UpdateMonitorProxy<'a> {
     #[doc(alias = "xdp_portal_update_install")]
     pub async fn update(&self, identifier: &WindowIdentifier) -> Result<(), Error> {
         let options = UpdateOptions::default();
-        call_method(self.inner(), "Update", &(&identifier, options)).await
+        call_method(self, "Update", &(&identifier, options)).await
     }
 
     /// Ends the update monitoring and cancels any ongoing installation.
@@ -179,6 +191,17 @@ impl<'a> UpdateMonitorProxy<'a> {
     /// See also [`Close`](https://flatpak.github.io/xdg-desktop-portal/index.html#gdbus-method-org-freedesktop-portal-Flatpak-UpdateMonitor.Close).
     #[doc(alias = "Close")]
     pub async fn close(&self) -> Result<(), Error> {
-        call_method(self.inner(), "Close", &()).await
+        call_method(self, "Close", &()).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::UpdateOptions;
+    use crate::helpers::assert_serialized_dict;
+
+    #[test]
+    fn options() {
+        assert_serialized_dict(&UpdateOptions::default(), &[]);
     }
 }
@@ -0,0 +1,157 @@
+//! Entry points used by the `cargo fuzz` targets living in `fuzz/`.
+//!
+//! The responses sent by the portal backends are deserialized into the
+//! crate's response types, a buggy or hostile backend must never be able to
+//! make those panic.
+use std::collections::HashMap;
+
+use arbitrary::Arbitrary;
+use byteorder::LE;
+use serde::Deserialize;
+use zbus::zvariant::{
+    from_slice, to_bytes, EncodingContext, ObjectPath, StructureBuilder, Type, Value,
+};
+
+use crate::desktop::{
+    account::UserInfo, background::Background, file_chooser::SelectedFiles, inhibit,
+    print::PreparePrint, remote_desktop, request::Response, screencast, screenshot,
+};
+
+/// Keys known to be used by the portals responses, they are preferred over
+/// random ones so the fuzzer reaches the typed fields quicker.
+const KNOWN_KEYS: &[&str] = &[
+    "autostart",
+    "background",
+    "choices",
+    "color",
+    "current_filter",
+    "devices",
+    "id",
+    "image",
+    "monitor_handle",
+    "name",
+    "page-setup",
+    "restore_data",
+    "restore_token",
+    "session_handle",
+    "settings",
+    "streams",
+    "token",
+    "uri",
+    "uris",
+    "writable",
+];
+
+#[derive(Arbitrary, Debug)]
+/// A key of a response vardict.
+pub enum FuzzKey {
+    Known(u8),
+    Other(String),
+}
+
+impl FuzzKey {
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Known(index) => KNOWN_KEYS[*index as usize % KNOWN_KEYS.len()],
+            Self::Other(key) => key,
+        }
+    }
+}
+
+#[derive(Arbitrary, Debug)]
+/// A value of a response vardict.
+pub enum FuzzValue {
+    Bool(bool),
+    U32(u32),
+    I32(i32),
+    U64(u64),
+    F64(f64),
+    Str(String),
+    ObjectPath,
+    Bytes(Vec<u8>),
+    Strings(Vec<String>),
+    Doubles(Vec<f64>),
+    Point((i32, i32)),
+    Variant(Box<FuzzValue>),
+    Structure(Box<FuzzValue>, Vec<FuzzValue>),
+    Dict(FuzzDict),
+}
+
+impl FuzzValue {
+    fn to_value(&self) -> Value<'_> {
+        match self {
+            Self::Bool(v) => Value::from(*v),
+            Self::U32(v) => Value::from(*v),
+            Self::I32(v) => Value::from(*v),
+            Self::U64(v) => Value::from(*v),
+            Self::F64(v) => Value::from(*v),
+            Self::Str(v) => Value::from(v.as_str()),
+            Self::ObjectPath => Value::from(ObjectPath::from_static_str_unchecked(
+                "/org/freedesktop/portal",
+            )),
+            Self::Bytes(v) => Value::from(v.clone()),
+            Self::Strings(v) => Value::from(v.clone()),
+            Self::Doubles(v) => Value::from(v.clone()),
+            Self::Point(v) => Value::from(*v),
+            Self::Variant(v) => Value::Value(Box::new(v.to_value())),
+            Self::Structure(first, rest) => Value::from(
+                rest.iter()
+                    .fold(
+                        StructureBuilder::new().append_field(first.to_value()),
+                        |builder, field| builder.append_field(field.to_value()),
+                    )
+                    .build(),
+            ),
+            Self::Dict(v) => Value::from(v.to_map()),
+        }
+    }
+}
+
+#[derive(Arbitrary, Debug)]
+/// A response vardict, `a{sv}`.
+pub struct FuzzDict(Vec<(FuzzKey, FuzzValue)>);
+
+impl FuzzDict {
+    fn to_map(&self) -> HashMap<&str, Value<'_>> {
+        self.0
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.to_value()))
+            .collect()
+    }
+}
+
+fn try_deserialize<T>(bytes: &[u8])
+where
+    T: for<'de> Deserialize<'de> + Type + std::fmt::Debug,
+{
+    let ctxt = EncodingContext::<LE>::new_dbus(0);
+    if let Ok(response) = from_slice::<_, Response<T>>(bytes, ctxt) {
+        // Make sure formatting the parsed response doesn't panic either.
+        let _ = format!("{:?}", response);
+    }
+}
+
+/// Deserialize `bytes` as the body of a `Response` signal into every response
+/// type of the crate.
+pub fn deserialize_responses(bytes: &[u8]) {
+    try_deserialize::<UserInfo>(bytes);
+    try_deserialize::<Background>(bytes);
+    try_deserialize::<SelectedFiles>(bytes);
+    try_deserialize::<inhibit::CreateMonitor>(bytes);
+    try_deserialize::<PreparePrint>(bytes);
+    try_deserialize::<remote_desktop::CreateSession>(bytes);
+    try_deserialize::<remote_desktop::SelectedDevices>(bytes);
+    try_deserialize::<screencast::CreateSession>(bytes);
+    try_deserialize::<screencast::Streams>(bytes);
+    try_deserialize::<screenshot::Screenshot>(bytes);
+    try_deserialize::<screenshot::Color>(bytes);
+}
+
+/// Encode `dict` as the results of a successful `Response` signal and
+/// deserialize it into every response type of the crate.
+pub fn deserialize_response_dict(dict: &FuzzDict) {
+    let ctxt = EncodingContext::<LE>::new_dbus(0);
+    if let Ok(bytes) = to_bytes(ctxt, &(0u32, dict.to_map())) {
+        deserialize_responses(&bytes);
+    }
+}
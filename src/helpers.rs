@@ -1,23 +1,271 @@
 use std::{
-    ffi::OsStr,
+    ffi::{CString, OsStr},
     fmt::Debug,
     os::unix::prelude::OsStrExt,
     path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
 };
 use zbus::zvariant::{ObjectPath, OwnedObjectPath, Type};
 
-use futures::StreamExt;
+use async_io::Timer;
+use futures::{
+    future::{ready, select, Either},
+    Future, Stream, StreamExt,
+};
 use serde::Deserialize;
 
 use crate::desktop::{
-    request::{BasicResponse, RequestProxy, Response},
-    HandleToken,
+    handle_path,
+    request::{BasicResponse, PortalRequest, RequestGuard, RequestProxy, Response},
+    HandleKind, HandleToken,
+};
+use crate::{
+    correlation,
+    message_hook::{self, PortalMessage},
+    window_identifier::ExportGuard,
+    Error, WindowIdentifier,
 };
-use crate::Error;
 
-pub(crate) async fn call_request_method<R, B>(
+// With the `fallback` feature, the calls fail early on the platforms without
+// portals instead of on the missing session bus or service. The BSDs run
+// `xdg-desktop-portal` as well, only macOS is ruled out. Windows isn't
+// covered, the crate passes Unix file descriptors around and doesn't build
+// there.
+fn ensure_supported() -> Result<(), Error> {
+    if cfg!(all(feature = "fallback", target_os = "macos")) {
+        Err(Error::Unsupported)
+    } else {
+        Ok(())
+    }
+}
+
+// The proxy a helper calls, along with the timeout of its method calls and
+// property reads. Implemented by the portal proxies through
+// `impl_proxy_timeout!`.
+pub(crate) trait AsProxy {
+    fn proxy(&self) -> &zbus::Proxy<'_>;
+
+    fn timeout(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl AsProxy for zbus::Proxy<'_> {
+    fn proxy(&self) -> &zbus::Proxy<'_> {
+        self
+    }
+}
+
+impl AsProxy for (&zbus::Proxy<'_>, Option<Duration>) {
+    fn proxy(&self) -> &zbus::Proxy<'_> {
+        self.0
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.1
+    }
+}
+
+// Fails with `Error::Timeout` if `future` doesn't complete within `timeout`.
+async fn with_timeout<T>(
+    timeout: Option<Duration>,
+    future: impl Future<Output = Result<T, Error>>,
+) -> Result<T, Error> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return future.await,
+    };
+    futures::pin_mut!(future);
+    match select(future, Timer::after(timeout)).await {
+        Either::Left((result, _)) => result,
+        Either::Right(_) => Err(Error::Timeout(timeout)),
+    }
+}
+
+// Calls `method_name`, passing the call and its reply to the message hook.
+async fn call_with_hook<B>(
     proxy: &zbus::Proxy<'_>,
+    method_name: &str,
+    body: &B,
+) -> zbus::Result<Arc<zbus::Message>>
+where
+    B: serde::ser::Serialize + Type,
+{
+    if message_hook::is_set() {
+        let call = zbus::Message::method(
+            proxy.connection().unique_name().map(|name| name.as_str()),
+            Some(proxy.destination().as_str()),
+            proxy.path().as_str(),
+            Some(proxy.interface().as_str()),
+            method_name,
+            body,
+        );
+        match call {
+            Ok(call) => message_hook::notify(PortalMessage::Call(&call)),
+            Err(_err) => {
+                #[cfg(feature = "log")]
+                tracing::warn!(
+                    "Failed to serialize {} for the message hook: {}",
+                    method_name,
+                    _err
+                );
+            }
+        }
+    }
+    let reply = proxy.call_method(method_name, body).await;
+    match &reply {
+        Ok(message) | Err(zbus::Error::MethodError(_, _, message)) => {
+            message_hook::notify(PortalMessage::Reply(message))
+        }
+        Err(_) => (),
+    }
+    reply
+}
+
+// Whether a proxy built by the user is for the expected interface.
+pub(crate) fn ensure_interface(proxy: &zbus::Proxy<'_>, interface: &str) -> Result<(), Error> {
+    if proxy.interface().as_str() == interface {
+        Ok(())
+    } else {
+        Err(Error::InvalidArgument(format!(
+            "Expected a proxy of {}, got {}",
+            interface,
+            proxy.interface()
+        )))
+    }
+}
+
+// The session bus connection of the free functions wrapping the proxies.
+pub(crate) async fn session_connection() -> Result<zbus::Connection, Error> {
+    ensure_supported()?;
+    Ok(zbus::Connection::session().await?)
+}
+
+pub(crate) fn call_request_method<'p, R, B>(
+    proxy: &'p (impl AsProxy + Sync),
+    handle_token: &HandleToken,
+    method_name: &'static str,
+    body: B,
+) -> PortalRequest<'p, R>
+where
+    R: for<'de> Deserialize<'de> + Type + Debug + Send + 'p,
+    B: serde::ser::Serialize + Type + Debug + Send + Sync + 'p,
+{
+    call_exported_request_method(
+        proxy,
+        ExportGuard::default(),
+        handle_token,
+        method_name,
+        body,
+    )
+}
+
+// Same as `call_request_method`, for the requests showing a dialog attached to
+// `identifier`. Its exported handle is released once the `Response` arrived.
+pub(crate) fn call_window_request_method<'p, R, B>(
+    proxy: &'p (impl AsProxy + Sync),
+    identifier: &WindowIdentifier,
+    handle_token: &HandleToken,
+    method_name: &'static str,
+    body: B,
+) -> PortalRequest<'p, R>
+where
+    R: for<'de> Deserialize<'de> + Type + Debug + Send + 'p,
+    B: serde::ser::Serialize + Type + Debug + Send + Sync + 'p,
+{
+    call_exported_request_method(
+        proxy,
+        identifier.export_guard(),
+        handle_token,
+        method_name,
+        body,
+    )
+}
+
+// The path of the request `method_name` is about to send with `handle_token`,
+// for the methods doing some work before sending it.
+pub(crate) fn request_handle(
+    proxy: &impl AsProxy,
+    handle_token: &HandleToken,
+    method_name: &str,
+) -> Result<OwnedObjectPath, Error> {
+    let proxy = proxy.proxy();
+    proxy
+        .connection()
+        .unique_name()
+        .ok_or_else(|| Error::InvalidArgument("The connection has no unique name".to_owned()))
+        .and_then(|unique_name| {
+            handle_path(HandleKind::Request, unique_name, handle_token.as_str())
+        })
+        .map_err(|err| Error::call(proxy, method_name, None, err))
+}
+
+// The handle is computed right away, nothing is sent on the bus until the
+// returned request is.
+fn call_exported_request_method<'p, R, B>(
+    proxy: &'p (impl AsProxy + Sync),
+    export: ExportGuard,
     handle_token: &HandleToken,
+    method_name: &'static str,
+    body: B,
+) -> PortalRequest<'p, R>
+where
+    R: for<'de> Deserialize<'de> + Type + Debug + Send + 'p,
+    B: serde::ser::Serialize + Type + Debug + Send + Sync + 'p,
+{
+    let timeout = proxy.timeout();
+    let handle = request_handle(proxy, handle_token, method_name);
+    let proxy = proxy.proxy();
+    let send_handle = handle.as_ref().ok().cloned();
+    PortalRequest::new(handle, async move {
+        // Only polled once the handle is known.
+        let handle = send_handle.expect("The request is sent once prepared");
+        // The id of the future sending the request, not building it.
+        let correlation_id = correlation::current();
+        ensure_supported().map_err(|err| Error::call(proxy, method_name, None, err))?;
+        #[cfg(feature = "log")]
+        tracing::info!(
+            "Calling a request method '{}:{}'",
+            proxy.interface(),
+            method_name
+        );
+        #[cfg(feature = "log")]
+        tracing::debug!("The body is: {:#?}", body);
+        #[cfg(feature = "log")]
+        tracing::info!(
+            "Creating a org.freedesktop.portal.Request {}",
+            handle.as_str()
+        );
+        let request = RequestProxy::new(proxy.connection(), handle.clone().into_inner())
+            .await
+            .map_err(|err| Error::call(proxy, method_name, None, err))?
+            .keep_exported(export)
+            .correlated(correlation_id);
+        let guard = RequestGuard::new(proxy.connection(), handle.clone());
+        let response = request_method(proxy, timeout, &request, method_name, &body);
+        #[cfg(feature = "log")]
+        let response = tracing::Instrument::instrument(
+            response,
+            tracing::info_span!(
+                "portal_request",
+                method = method_name,
+                handle = handle.as_str(),
+                correlation_id = request.correlation_id(),
+            ),
+        );
+        let response = response.await;
+        guard.disarm();
+        response.map_err(|err| Error::call(proxy, method_name, Some(handle), err))
+    })
+}
+
+// Only the method call is subject to `timeout`, the user interaction comes
+// afterwards through the `Response` signal.
+async fn request_method<R, B>(
+    proxy: &zbus::Proxy<'_>,
+    timeout: Option<Duration>,
+    request: &RequestProxy<'_>,
     method_name: &str,
     body: &B,
 ) -> Result<R, Error>
@@ -25,15 +273,6 @@ where
     R: for<'de> Deserialize<'de> + Type + Debug,
     B: serde::ser::Serialize + Type + Debug,
 {
-    #[cfg(feature = "log")]
-    tracing::info!(
-        "Calling a request method '{}:{}'",
-        proxy.interface(),
-        method_name
-    );
-    #[cfg(feature = "log")]
-    tracing::debug!("The body is: {:#?}", body);
-    let request = RequestProxy::from_unique_name(proxy.connection(), handle_token).await?;
     // We don't use receive_response because we want to create the stream in advance
     #[cfg(feature = "log")]
     tracing::info!(
@@ -45,6 +284,7 @@ where
     let (response, path) = futures::try_join!(
         async {
             let message = stream.next().await.ok_or(Error::NoResponse)?;
+            message_hook::notify(PortalMessage::Response(&message));
             #[cfg(feature = "log")]
             tracing::info!(
                 "Received signal 'Response' on '{}'",
@@ -59,7 +299,10 @@ where
             response as Result<_, Error>
         },
         async {
-            let msg = proxy.call_method(method_name, body).await?;
+            let msg = with_timeout(timeout, async {
+                Ok(call_with_hook(proxy, method_name, body).await?)
+            })
+            .await?;
             let path = msg.body::<OwnedObjectPath>()?.into_inner();
 
             #[cfg(feature = "log")]
@@ -71,22 +314,44 @@ where
     Ok(response)
 }
 
-pub(crate) async fn call_basic_response_method(
-    proxy: &zbus::Proxy<'_>,
+pub(crate) fn call_basic_response_method<'p, B>(
+    proxy: &'p (impl AsProxy + Sync),
     handle_token: &HandleToken,
-    method_name: &str,
-    body: &(impl serde::ser::Serialize + Type + Debug),
-) -> Result<(), Error> {
-    call_request_method::<BasicResponse, _>(proxy, handle_token, method_name, body).await?;
-    Ok(())
+    method_name: &'static str,
+    body: B,
+) -> PortalRequest<'p, ()>
+where
+    B: serde::ser::Serialize + Type + Debug + Send + Sync + 'p,
+{
+    call_request_method::<BasicResponse, _>(proxy, handle_token, method_name, body).map(|_| Ok(()))
 }
 
-pub(crate) async fn receive_signal<R>(
-    proxy: &zbus::Proxy<'_>,
+pub(crate) fn call_window_basic_response_method<'p, B>(
+    proxy: &'p (impl AsProxy + Sync),
+    identifier: &WindowIdentifier,
+    handle_token: &HandleToken,
+    method_name: &'static str,
+    body: B,
+) -> PortalRequest<'p, ()>
+where
+    B: serde::ser::Serialize + Type + Debug + Send + Sync + 'p,
+{
+    call_window_request_method::<BasicResponse, _>(
+        proxy,
+        identifier,
+        handle_token,
+        method_name,
+        body,
+    )
+    .map(|_| Ok(()))
+}
+
+pub(crate) async fn receive_signal_stream<'a, R>(
+    proxy: &zbus::Proxy<'a>,
     signal_name: &'static str,
-) -> Result<R, Error>
+) -> Result<impl Stream<Item = R> + Unpin + 'a, Error>
 where
-    R: for<'de> Deserialize<'de> + Type + Debug,
+    R: for<'de> Deserialize<'de> + Type + Debug + 'a,
 {
     #[cfg(feature = "log")]
     tracing::info!(
@@ -94,22 +359,59 @@ where
         signal_name,
         proxy.interface()
     );
-    let mut stream = proxy.receive_signal(signal_name).await?;
-    let message = stream.next().await.ok_or(Error::NoResponse)?;
+    let stream = async {
+        ensure_supported()?;
+        Ok(proxy.receive_signal(signal_name).await?)
+    }
+    .await
+    .map_err(|err| Error::call(proxy, signal_name, None, err))?;
+    // The emissions that can't be deserialized are skipped, a misbehaving
+    // backend doesn't end the stream.
+    Ok(stream.filter_map(move |message| {
+        let content = message.body::<R>();
+        #[cfg(feature = "log")]
+        match &content {
+            Ok(content) => tracing::debug!("Received signal '{}': {:#?}", signal_name, content),
+            Err(err) => tracing::warn!("Invalid signal '{}': {}", signal_name, err),
+        }
+        ready(content.ok())
+    }))
+}
+
+pub(crate) async fn receive_signal<R>(
+    proxy: &zbus::Proxy<'_>,
+    signal_name: &'static str,
+) -> Result<R, Error>
+where
+    R: for<'de> Deserialize<'de> + Type + Debug,
+{
     #[cfg(feature = "log")]
     tracing::info!(
-        "Received signal '{}' on '{}'",
+        "Listening to signal '{}' on '{}'",
         signal_name,
         proxy.interface()
     );
-    let content = message.body::<R>()?;
-    #[cfg(feature = "log")]
-    tracing::debug!("With body {:#?}", content);
-    Ok(content)
+    async {
+        ensure_supported()?;
+        let mut stream = proxy.receive_signal(signal_name).await?;
+        let message = stream.next().await.ok_or(Error::NoResponse)?;
+        #[cfg(feature = "log")]
+        tracing::info!(
+            "Received signal '{}' on '{}'",
+            signal_name,
+            proxy.interface()
+        );
+        let content = message.body::<R>()?;
+        #[cfg(feature = "log")]
+        tracing::debug!("With body {:#?}", content);
+        Ok(content)
+    }
+    .await
+    .map_err(|err| Error::call(proxy, signal_name, None, err))
 }
 
 pub(crate) async fn call_method<R, B>(
-    proxy: &zbus::Proxy<'_>,
+    proxy: &impl AsProxy,
     method_name: &str,
     body: &B,
 ) -> Result<R, Error>
@@ -117,20 +419,270 @@ where
     R: for<'de> Deserialize<'de> + Type,
     B: serde::ser::Serialize + Type + Debug,
 {
+    call_method_with(proxy, method_name, body, |msg| {
+        let reply = msg.body::<R>()?;
+        msg.take_fds();
+        Ok(reply)
+    })
+    .await
+}
+
+// Like `call_method`, but `f` deserializes the reply itself. Large replies can
+// then be read into types borrowing from the message body, and only copied
+// once into the returned ones, or kept to be read later.
+pub(crate) async fn call_method_with<T, B>(
+    proxy: &impl AsProxy,
+    method_name: &str,
+    body: &B,
+    f: impl FnOnce(&Arc<zbus::Message>) -> Result<T, Error>,
+) -> Result<T, Error>
+where
+    B: serde::ser::Serialize + Type + Debug,
+{
+    let timeout = proxy.timeout();
+    let proxy = proxy.proxy();
     #[cfg(feature = "log")]
     {
         tracing::info!("Calling method {}:{}", proxy.interface(), method_name);
         tracing::debug!("With body {:#?}", body);
     }
-    let msg = proxy.call_method(method_name, body).await?;
-    let reply = msg.body::<R>()?;
-    msg.take_fds();
+    async {
+        ensure_supported()?;
+        let msg = with_timeout(timeout, async {
+            Ok(call_with_hook(proxy, method_name, body).await?)
+        })
+        .await?;
+        f(&msg)
+    }
+    .await
+    .map_err(|err| Error::call(proxy, method_name, None, err))
+}
+
+pub(crate) async fn get_property<T>(proxy: &impl AsProxy, property_name: &str) -> Result<T, Error>
+where
+    T: TryFrom<zbus::zvariant::OwnedValue>,
+    <T as TryFrom<zbus::zvariant::OwnedValue>>::Error: Into<zbus::Error>,
+{
+    let timeout = proxy.timeout();
+    let proxy = proxy.proxy();
+    async {
+        ensure_supported()?;
+        with_timeout(timeout, async {
+            Ok(proxy.get_property::<T>(property_name).await?)
+        })
+        .await
+    }
+    .await
+    .map_err(|err| Error::call(proxy, property_name, None, err))
+}
 
-    Ok(reply)
+// Whether `get_property` failed because the portal doesn't have the property,
+// an older version of it for example. GDBus replies with `InvalidArgs`.
+pub(crate) fn is_unknown_property(err: &Error) -> bool {
+    match err.root() {
+        Error::Zbus(zbus::Error::FDO(err)) => matches!(
+            **err,
+            zbus::fdo::Error::UnknownProperty(_) | zbus::fdo::Error::InvalidArgs(_)
+        ),
+        Error::Zbus(zbus::Error::MethodError(name, _, _)) => matches!(
+            name.as_str(),
+            "org.freedesktop.DBus.Error.UnknownProperty" | "org.freedesktop.DBus.Error.InvalidArgs"
+        ),
+        _ => false,
+    }
 }
 
 // Some portals returns paths which are bytes and not a typical string
 // as those might be null terminated. This might make sense to provide in form of a helper in zvariant
-pub(crate) fn path_from_null_terminated(bytes: Vec<u8>) -> PathBuf {
-    Path::new(OsStr::from_bytes(bytes.split_last().unwrap().1)).to_path_buf()
+pub(crate) fn path_from_null_terminated(bytes: &[u8]) -> PathBuf {
+    let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+    Path::new(OsStr::from_bytes(bytes)).to_path_buf()
+}
+
+// The inverse of `path_from_null_terminated`, the path bytes are kept as is so
+// non UTF-8 paths survive the round trip. A path containing a NUL byte can't
+// be sent.
+pub(crate) fn null_terminated_from_path(path: impl AsRef<Path>) -> Result<Vec<u8>, Error> {
+    CString::new(path.as_ref().as_os_str().as_bytes())
+        .map(CString::into_bytes_with_nul)
+        .map_err(|err| Error::NulTerminated(err.nul_position()))
+}
+
+// Checks the keys of the vardict `options` serializes to, along with the
+// signatures of their values, against the sorted `expected` ones. Used by the
+// tests of the options structs to stick to the portals specifications.
+#[cfg(test)]
+#[track_caller]
+pub(crate) fn assert_serialized_dict<T>(options: &T, expected: &[(&str, &str)])
+where
+    T: serde::ser::Serialize + Type,
+{
+    use std::collections::HashMap;
+
+    use zbus::zvariant::{from_slice_fds, to_bytes_fds, EncodingContext, OwnedValue};
+
+    assert_eq!(T::signature(), "a{sv}");
+    let ctxt = EncodingContext::<byteorder::LE>::new_dbus(0);
+    let (bytes, fds) = to_bytes_fds(ctxt, options).unwrap();
+    let dict: HashMap<String, OwnedValue> = from_slice_fds(&bytes, Some(&fds), ctxt).unwrap();
+    let mut entries = dict
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.value_signature().to_string()))
+        .collect::<Vec<_>>();
+    entries.sort();
+    let expected = expected
+        .iter()
+        .map(|(key, signature)| (*key, signature.to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(entries, expected);
+}
+
+// A member of a portal generated by `portal_members!`, with the signatures of
+// its arguments and of its reply, the value of a property or the body of a
+// signal.
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct Member {
+    kind: &'static str,
+    name: &'static str,
+    args: String,
+    reply: String,
+}
+
+#[cfg(test)]
+impl Member {
+    pub(crate) fn new<R: Type>(
+        kind: &'static str,
+        name: &'static str,
+        args: &[zbus::zvariant::Signature<'static>],
+    ) -> Self {
+        Self {
+            kind,
+            name,
+            args: args.iter().map(|arg| arg.as_str()).collect(),
+            reply: R::signature().to_string(),
+        }
+    }
+}
+
+// Checks `members` against the introspection data of `interface` in
+// `interfaces/`, so that a typo in the name of a member or a wrong type fails
+// the tests instead of the calls.
+#[cfg(test)]
+#[track_caller]
+pub(crate) fn assert_members(interface: &str, members: &[Member]) {
+    const INTERFACES: &[&str] = &[
+        include_str!("../interfaces/org.freedesktop.portal.Desktop.xml"),
+        include_str!("../interfaces/org.freedesktop.portal.Documents.xml"),
+        include_str!("../interfaces/org.freedesktop.portal.Flatpak.xml"),
+    ];
+
+    // The value of `attribute` in the opening `tag`.
+    fn attribute<'x>(tag: &'x str, attribute: &str) -> Option<&'x str> {
+        let start = tag.find(&format!(" {}=\"", attribute))? + attribute.len() + 3;
+        let len = tag[start..].find('"')?;
+        Some(&tag[start..start + len])
+    }
+
+    let start = format!("<interface name=\"{}\"", interface);
+    let tags = INTERFACES
+        .iter()
+        .find_map(|xml| {
+            let xml = &xml[xml.find(&start)?..];
+            Some(
+                xml[..xml.find("</interface>")?]
+                    .split('<')
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .unwrap_or_else(|| panic!("No introspection data for {}", interface));
+    for member in members {
+        let position = tags
+            .iter()
+            .position(|tag| {
+                tag.starts_with(&format!("{} ", member.kind))
+                    && attribute(tag, "name") == Some(member.name)
+            })
+            .unwrap_or_else(|| panic!("No {} {} in {}", member.kind, member.name, interface));
+        if member.kind == "property" {
+            assert_eq!(
+                attribute(tags[position], "type"),
+                Some(member.reply.as_str()),
+                "Wrong type of {}.{}",
+                interface,
+                member.name
+            );
+            continue;
+        }
+        // The arguments until the end of the method or signal, if it has any.
+        let end = format!("/{}>", member.kind);
+        let inner = if tags[position].trim_end().ends_with("/>") {
+            &[][..]
+        } else {
+            &tags[position + 1..]
+        };
+        let (mut args, mut reply) = (Vec::new(), Vec::new());
+        for tag in inner
+            .iter()
+            .take_while(|tag| !tag.starts_with(&end))
+            .filter(|tag| tag.starts_with("arg "))
+        {
+            let signature = attribute(tag, "type").unwrap();
+            if member.kind == "signal" || attribute(tag, "direction") == Some("out") {
+                reply.push(signature);
+            } else {
+                args.push(signature);
+            }
+        }
+        // Several values are replied as a struct.
+        let expected_reply = if reply.len() > 1 {
+            format!("({})", reply.concat())
+        } else {
+            reply.concat()
+        };
+        assert_eq!(
+            (member.args.as_str(), member.reply.as_str()),
+            (args.concat().as_str(), expected_reply.as_str()),
+            "Wrong signature of {}.{}",
+            interface,
+            member.name
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use futures::{executor::block_on, future::pending};
+
+    use super::{is_unknown_property, with_timeout};
+    use crate::{Error, PortalError};
+
+    #[test]
+    fn timeout() {
+        let timeout = Duration::from_millis(10);
+        let result = block_on(with_timeout(Some(timeout), pending::<Result<(), Error>>()));
+        assert!(matches!(result, Err(Error::Timeout(t)) if t == timeout));
+
+        let result = block_on(with_timeout(Some(timeout), async { Ok(1) }));
+        assert_eq!(result.unwrap(), 1);
+        let result = block_on(with_timeout(None, async { Ok(2) }));
+        assert_eq!(result.unwrap(), 2);
+    }
+
+    #[test]
+    fn unknown_property() {
+        let missing = Error::from(zbus::fdo::Error::InvalidArgs(
+            "No such property \u{2018}AvailableCursorModes\u{2019}".to_owned(),
+        ));
+        assert!(is_unknown_property(&missing));
+        let missing = Error::from(zbus::fdo::Error::UnknownProperty("version".to_owned()));
+        assert!(is_unknown_property(&missing));
+
+        let denied = Error::from(zbus::fdo::Error::AccessDenied("denied".to_owned()));
+        assert!(!is_unknown_property(&denied));
+        let failed = Error::from(PortalError::Failed);
+        assert!(!is_unknown_property(&failed));
+    }
 }
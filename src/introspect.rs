@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use crate::{
+    desktop::{DESTINATION, PATH},
+    helpers::{call_method, get_property, is_unknown_property, AsProxy},
+    Error,
+};
+
+/// Retrieve the portal interfaces supported by the running
+/// `xdg-desktop-portal` alongside their version.
+///
+/// The portal object is introspected and the `version` property of each of
+/// the found `org.freedesktop.portal.*` interfaces is read. Interfaces that
+/// don't expose such a property are reported with version `1`.
+///
+/// ```rust,no_run
+/// async fn run() -> ashpd::Result<()> {
+///     let connection = zbus::Connection::session().await?;
+///     let portals = ashpd::supported_portals(&connection).await?;
+///
+///     for (interface, version) in portals {
+///         println!("{}: v{}", interface, version);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub async fn supported_portals(
+    connection: &zbus::Connection,
+) -> Result<HashMap<String, u32>, Error> {
+    let introspectable: zbus::Proxy<'_> = zbus::ProxyBuilder::new_bare(connection)
+        .interface("org.freedesktop.DBus.Introspectable")?
+        .path(PATH)?
+        .destination(DESTINATION)?
+        .build()
+        .await?;
+    let xml: String = call_method(&introspectable, "Introspect", &()).await?;
+
+    let mut portals = HashMap::new();
+    for interface in portal_interfaces(&xml)? {
+        let proxy: zbus::Proxy<'_> = zbus::ProxyBuilder::new_bare(connection)
+            .interface(interface.as_str())?
+            .path(PATH)?
+            .destination(DESTINATION)?
+            .build()
+            .await?;
+        let version = match get_property::<u32>(&proxy, "version").await {
+            Err(err) if is_unknown_property(&err) => 1,
+            version => version?,
+        };
+        portals.insert(interface, version);
+    }
+    Ok(portals)
+}
+
+// Read the version of the interface of a proxy, it stays in the property
+// cache of the proxy for its `cached_version`.
+pub(crate) async fn negotiate_version(proxy: &impl AsProxy) -> Result<u32, Error> {
+    let version = get_property::<u32>(proxy, "version").await?;
+    #[cfg(feature = "log")]
+    tracing::info!("{} version {}", proxy.proxy().interface().as_str(), version);
+    Ok(version)
+}
+
+// The `org.freedesktop.portal.*` interfaces of an introspection XML document.
+fn portal_interfaces(xml: &str) -> Result<Vec<String>, Error> {
+    let node = zbus::xml::Node::from_reader(xml.as_bytes())?;
+    Ok(node
+        .interfaces()
+        .into_iter()
+        .map(|interface| interface.name())
+        .filter(|name| name.starts_with("org.freedesktop.portal."))
+        .map(ToOwned::to_owned)
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::portal_interfaces;
+
+    // A reply of xdg-desktop-portal, trimmed, with the other quotes and
+    // attributes the XML format allows on the last interfaces.
+    const INTROSPECTION: &str = r#"<!DOCTYPE node PUBLIC "-//freedesktop//DTD D-BUS Object Introspection 1.0//EN"
+                      "http://www.freedesktop.org/standards/dbus/1.0/introspect.dtd">
+<!-- GDBus 2.76.4 -->
+<node>
+  <interface name="org.freedesktop.DBus.Properties">
+    <method name="Get">
+      <arg type="s" name="interface_name" direction="in"/>
+      <arg type="s" name="property_name" direction="in"/>
+      <arg type="v" name="value" direction="out"/>
+    </method>
+    <signal name="PropertiesChanged">
+      <arg type="s" name="interface_name"/>
+      <arg type="a{sv}" name="changed_properties"/>
+      <arg type="as" name="invalidated_properties"/>
+    </signal>
+  </interface>
+  <interface name="org.freedesktop.DBus.Introspectable">
+    <method name="Introspect">
+      <arg type="s" name="xml_data" direction="out"/>
+    </method>
+  </interface>
+  <interface name="org.freedesktop.portal.Screenshot">
+    <method name="Screenshot">
+      <arg type="s" name="parent_window" direction="in">
+      </arg>
+      <arg type="a{sv}" name="options" direction="in">
+      </arg>
+      <arg type="o" name="handle" direction="out">
+      </arg>
+    </method>
+    <property type="u" name="version" access="read">
+    </property>
+  </interface>
+  <interface name='org.freedesktop.portal.OpenURI'>
+    <property type='u' name='version' access='read'/>
+  </interface>
+  <interface xmlns:doc="http://www.freedesktop.org/dbus/1.0/doc.dtd" name = "org.freedesktop.portal.Settings">
+    <signal name="SettingChanged">
+      <arg type="s" name="namespace"/>
+      <arg type="s" name="key"/>
+      <arg type="v" name="value"/>
+    </signal>
+  </interface>
+  <node name="org"/>
+</node>
+"#;
+
+    #[test]
+    fn interfaces() {
+        assert_eq!(
+            portal_interfaces(INTROSPECTION).unwrap(),
+            [
+                "org.freedesktop.portal.Screenshot",
+                "org.freedesktop.portal.OpenURI",
+                "org.freedesktop.portal.Settings",
+            ]
+        );
+        assert!(portal_interfaces("<node>").is_err());
+    }
+
+    #[cfg(feature = "backend")]
+    #[test]
+    fn supported_portals() {
+        use futures::executor::block_on;
+        use zbus::dbus_interface;
+
+        use crate::{backend::peer_connections, desktop::PATH};
+
+        struct Screenshot;
+
+        #[dbus_interface(name = "org.freedesktop.portal.Screenshot")]
+        impl Screenshot {
+            #[dbus_interface(property, name = "version")]
+            fn version(&self) -> u32 {
+                2
+            }
+        }
+
+        struct OpenURI;
+
+        #[dbus_interface(name = "org.freedesktop.portal.OpenURI")]
+        impl OpenURI {}
+
+        struct Email;
+
+        #[dbus_interface(name = "org.freedesktop.portal.Email")]
+        impl Email {
+            #[dbus_interface(property, name = "version")]
+            fn version(&self) -> zbus::fdo::Result<u32> {
+                Err(zbus::fdo::Error::AccessDenied("Not allowed".to_owned()))
+            }
+        }
+
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            backend.object_server().at(PATH, Screenshot).await.unwrap();
+            backend.object_server().at(PATH, OpenURI).await.unwrap();
+            let portals = super::supported_portals(&frontend).await.unwrap();
+            assert_eq!(portals.len(), 2);
+            assert_eq!(portals["org.freedesktop.portal.Screenshot"], 2);
+            assert_eq!(portals["org.freedesktop.portal.OpenURI"], 1);
+
+            backend.object_server().at(PATH, Email).await.unwrap();
+            assert!(super::supported_portals(&frontend).await.is_err());
+        });
+    }
+
+    #[cfg(feature = "backend")]
+    #[test]
+    fn negotiate_version() {
+        use futures::executor::block_on;
+        use zbus::dbus_interface;
+
+        use crate::{
+            backend::peer_connections,
+            desktop::{open_uri::OpenURIProxy, screenshot::ScreenshotProxy, PATH},
+        };
+
+        struct Screenshot;
+
+        #[dbus_interface(name = "org.freedesktop.portal.Screenshot")]
+        impl Screenshot {
+            #[dbus_interface(property, name = "version")]
+            fn version(&self) -> u32 {
+                2
+            }
+        }
+
+        block_on(async {
+            let (backend, frontend) = peer_connections().await;
+            backend.object_server().at(PATH, Screenshot).await.unwrap();
+
+            let proxy = ScreenshotProxy::new(&frontend).await.unwrap();
+            assert_eq!(proxy.cached_version(), None);
+            assert_eq!(proxy.negotiate_version().await.unwrap(), 2);
+            assert_eq!(proxy.cached_version(), Some(2));
+
+            let proxy = OpenURIProxy::new(&frontend).await.unwrap();
+            assert!(proxy.negotiate_version().await.is_err());
+            assert_eq!(proxy.cached_version(), None);
+        });
+    }
+}
@@ -11,19 +11,56 @@ compile_error!("You can't enable both GTK 3 & GTK 4 features at once");
 /// Alias for a [`Result`] with the error type `ashpd::Error`.
 pub type Result<T> = std::result::Result<T, Error>;
 
+#[macro_use]
+mod macros;
+
+mod activation_token;
+mod correlation;
+pub use self::correlation::{with_correlation_id, Correlated};
+#[cfg(feature = "backend")]
+/// Implement the portals backends for a desktop environment.
+pub mod backend;
+#[cfg(feature = "blocking")]
+/// Run the portal calls without an async runtime.
+pub mod blocking;
 /// Interact with the user's desktop such as taking a screenshot, setting a
 /// background or querying the user's location.
 pub mod desktop;
 /// Interact with the documents store or transfer files across apps.
 pub mod documents;
+pub use self::activation_token::ActivationToken;
 mod error;
+mod executor;
+pub use self::executor::{spawn_executor, ExecutorHandle};
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing;
 mod window_identifier;
 pub use self::window_identifier::WindowIdentifier;
 /// Spawn commands outside the sandbox or monitor if the running application has
 /// received an update & install it.
 pub mod flatpak;
 mod helpers;
+mod introspect;
+pub use self::introspect::supported_portals;
+mod message_hook;
+pub use self::message_hook::{clear_message_hook, set_message_hook, PortalMessage};
+mod portals;
+mod raw_options;
+pub use self::portals::{Portal, Portals};
+mod reconnect;
+pub use self::reconnect::{ConnectionLost, ReconnectingPortals};
+/// Read and write the enums of the portals by name.
+pub mod str_form;
+mod tolerant_flags;
+pub use self::tolerant_flags::TolerantBitFlags;
+mod uri;
+mod watcher;
+pub use self::watcher::{PortalEvent, PortalWatcher};
+#[cfg(feature = "backend")]
+pub use async_trait::async_trait;
 pub use enumflags2;
+pub use url;
 pub use zbus;
 pub use zbus::zvariant;
 
@@ -39,4 +76,4 @@ pub fn is_sandboxed() -> bool {
             .unwrap_or(false)
 }
 
-pub use self::error::{Error, PortalError};
+pub use self::error::{Error, PortalError, UnknownValue};
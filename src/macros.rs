@@ -0,0 +1,472 @@
+// Generates the setters of the options structs, each one sets an `Option`
+// field of the same name, and their getters named after the field. The
+// getters of the strings return a `&str`, the other values are copied. The
+// private options structs, whose values are the arguments of a proxy method,
+// start with `@setters` to only get the setters.
+//
+// ```ignore
+// impl OpenFileOptions {
+//     options_setters! {
+//         /// Sets a user-visible string to the "accept" button.
+//         with_accept_label(accept_label: impl Into<String>);
+//         /// Sets whether the dialog should be a modal.
+//         with_modal(modal: bool);
+//     }
+// }
+// ```
+macro_rules! options_setters {
+    (@setters) => {};
+    (@setters $(#[$meta:meta])* $setter:ident($name:ident: impl Into<$ty:ty>); $($rest:tt)*) => {
+        $(#[$meta])*
+        #[must_use]
+        pub fn $setter(mut self, $name: impl Into<$ty>) -> Self {
+            self.$name = Some($name.into());
+            self
+        }
+
+        options_setters!(@setters $($rest)*);
+    };
+    (@setters $(#[$meta:meta])* $setter:ident($name:ident: $ty:ty); $($rest:tt)*) => {
+        $(#[$meta])*
+        #[must_use]
+        pub fn $setter(mut self, $name: $ty) -> Self {
+            self.$name = Some($name);
+            self
+        }
+
+        options_setters!(@setters $($rest)*);
+    };
+    (@getter $setter:ident $name:ident: $($ret:tt)*) => {
+        #[doc = concat!("The value set with [`", stringify!($setter), "`](Self::", stringify!($setter), "), if any.")]
+        pub fn $name(&self) -> $($ret)* {
+            options_setters!(@get self.$name, $($ret)*)
+        }
+    };
+    (@get $field:expr, Option<&str>) => {
+        $field.as_deref()
+    };
+    (@get $field:expr, $($ret:tt)*) => {
+        $field
+    };
+    () => {};
+    ($(#[$meta:meta])* $setter:ident($name:ident: impl Into<String>); $($rest:tt)*) => {
+        options_setters!(@setters $(#[$meta])* $setter($name: impl Into<String>););
+        options_setters!(@getter $setter $name: Option<&str>);
+        options_setters!($($rest)*);
+    };
+    ($(#[$meta:meta])* $setter:ident($name:ident: impl Into<$ty:ty>); $($rest:tt)*) => {
+        options_setters!(@setters $(#[$meta])* $setter($name: impl Into<$ty>););
+        options_setters!(@getter $setter $name: Option<$ty>);
+        options_setters!($($rest)*);
+    };
+    ($(#[$meta:meta])* $setter:ident($name:ident: $ty:ty); $($rest:tt)*) => {
+        options_setters!(@setters $(#[$meta])* $setter($name: $ty););
+        options_setters!(@getter $setter $name: Option<$ty>);
+        options_setters!($($rest)*);
+    };
+}
+
+// Generates the methods of a proxy wrapper mapping one to one to a method,
+// property or signal of the portal, the ones that don't go through a
+// `Request`. The D-Bus name of a member is only written once, next to the
+// types of its arguments and reply, and `members()` lists them for the tests
+// checking them against `interfaces/`, see `helpers::assert_members`.
+//
+// The properties get no `doc(alias)` as their name is often the one of the
+// method, give it as an attribute when it differs.
+//
+// The arguments are sent as is, the types needing a conversion, like the
+// file descriptors, are taken by a private method the public one calls.
+//
+// ```ignore
+// impl<'a> NetworkMonitorProxy<'a> {
+//     portal_members! {
+//         /// Returns whether the given hostname is believed to be reachable.
+//         pub fn can_reach(hostname: &str, port: u32) -> bool = method "CanReach";
+//         /// Whether the power saver is enabled.
+//         pub fn is_enabled() -> bool = property "power-saver-enabled";
+//         /// Emitted when the network configuration changes.
+//         pub fn receive_changed() -> Stream<()> = signal "changed";
+//     }
+// }
+// ```
+macro_rules! portal_members {
+    (@members [$($member:expr,)*]) => {
+        #[cfg(test)]
+        pub(crate) fn members() -> Vec<crate::helpers::Member> {
+            vec![$($member,)*]
+        }
+    };
+    (
+        @members [$($member:expr,)*]
+        $(#[$meta:meta])*
+        $vis:vis fn $name:ident($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty = method $dbus_name:literal;
+        $($rest:tt)*
+    ) => {
+        $(#[$meta])*
+        #[doc(alias = $dbus_name)]
+        $vis async fn $name(&self, $($arg: $ty),*) -> Result<$ret, crate::Error> {
+            crate::helpers::call_method(self, $dbus_name, &($($arg,)*)).await
+        }
+
+        portal_members!(
+            @members [
+                $($member,)*
+                crate::helpers::Member::new::<$ret>(
+                    "method",
+                    $dbus_name,
+                    &[$(<$ty as zbus::zvariant::Type>::signature()),*],
+                ),
+            ]
+            $($rest)*
+        );
+    };
+    (
+        @members [$($member:expr,)*]
+        $(#[$meta:meta])*
+        $vis:vis fn $name:ident() -> $ret:ty = property $dbus_name:literal;
+        $($rest:tt)*
+    ) => {
+        $(#[$meta])*
+        $vis async fn $name(&self) -> Result<$ret, crate::Error> {
+            crate::helpers::get_property::<$ret>(self, $dbus_name).await
+        }
+
+        portal_members!(
+            @members [
+                $($member,)*
+                crate::helpers::Member::new::<$ret>("property", $dbus_name, &[]),
+            ]
+            $($rest)*
+        );
+    };
+    (
+        @members [$($member:expr,)*]
+        $(#[$meta:meta])*
+        $vis:vis fn $name:ident() -> Stream<$item:ty> = signal $dbus_name:literal;
+        $($rest:tt)*
+    ) => {
+        $(#[$meta])*
+        #[doc(alias = $dbus_name)]
+        $vis async fn $name(
+            &self,
+        ) -> Result<impl futures::Stream<Item = $item> + Unpin + 'a, crate::Error> {
+            crate::helpers::receive_signal_stream(&self.0, $dbus_name).await
+        }
+
+        portal_members!(
+            @members [
+                $($member,)*
+                crate::helpers::Member::new::<$item>("signal", $dbus_name, &[]),
+            ]
+            $($rest)*
+        );
+    };
+    ($($members:tt)*) => {
+        portal_members!(@members [] $($members)*);
+    };
+}
+
+// Implements `TryFrom<zbus::Proxy>` for a proxy wrapper, for the users
+// building the proxies themselves. The fields of the wrapper after the proxy
+// and its timeout are given after the interface.
+//
+// ```ignore
+// impl_try_from_proxy!(OpenURIProxy, INTERFACE_OPEN_URI, Default::default());
+// ```
+macro_rules! impl_try_from_proxy {
+    ($proxy:ident, $interface:expr $(, $field:expr)*) => {
+        impl<'a> TryFrom<zbus::Proxy<'a>> for $proxy<'a> {
+            type Error = crate::Error;
+
+            /// Wrap a proxy built with a custom [`zbus::ProxyBuilder`], with
+            /// a different timeout or caching for example.
+            ///
+            /// Fails with [`Error::InvalidArgument`](crate::Error::InvalidArgument)
+            /// if the proxy isn't for the interface of the portal.
+            fn try_from(proxy: zbus::Proxy<'a>) -> Result<Self, Self::Error> {
+                crate::helpers::ensure_interface(&proxy, $interface)?;
+                Ok(Self(proxy, None $(, $field)*))
+            }
+        }
+    };
+}
+
+// Implements the timeout of the method calls and property reads of a proxy
+// wrapper, stored in its second field, and its `Debug` showing where the
+// calls go rather than the internals of `zbus::Proxy`.
+macro_rules! impl_proxy_timeout {
+    ($proxy:ident) => {
+        impl<'a> $proxy<'a> {
+            /// Set the duration after which the method calls and property
+            /// reads fail with [`Error::Timeout`](crate::Error::Timeout) if
+            /// the portal didn't reply.
+            ///
+            /// The requests showing a dialog reply before the user interacts
+            /// with it, their response isn't subject to the timeout.
+            #[must_use]
+            pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+                self.1 = Some(timeout);
+                self
+            }
+
+            /// Set or unset the timeout, see [`Self::with_timeout`].
+            pub fn set_timeout(&mut self, timeout: Option<std::time::Duration>) {
+                self.1 = timeout;
+            }
+
+            /// The timeout of the method calls and property reads, none by
+            /// default.
+            pub fn timeout(&self) -> Option<std::time::Duration> {
+                self.1
+            }
+
+            /// The version of the interface, if already read by
+            /// [`Self::negotiate_version`] or along with the first property
+            /// read.
+            pub fn cached_version(&self) -> Option<u32> {
+                self.0.cached_property::<u32>("version").ok().flatten()
+            }
+
+            /// Read the version of the interface, kept in the property cache
+            /// of the proxy for [`Self::cached_version`].
+            ///
+            /// The version is logged with the `log` feature, to spot a portal
+            /// older than expected.
+            pub async fn negotiate_version(&self) -> Result<u32, crate::Error> {
+                crate::introspect::negotiate_version(self).await
+            }
+        }
+
+        impl crate::helpers::AsProxy for $proxy<'_> {
+            fn proxy(&self) -> &zbus::Proxy<'_> {
+                &self.0
+            }
+
+            fn timeout(&self) -> Option<std::time::Duration> {
+                self.1
+            }
+        }
+
+        impl std::fmt::Debug for $proxy<'_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct(stringify!($proxy))
+                    .field("interface", &self.0.interface().as_str())
+                    .field("destination", &self.0.destination().as_str())
+                    .field("path", &self.0.path().as_str())
+                    .field("version", &self.cached_version())
+                    .field("timeout", &self.1)
+                    .finish()
+            }
+        }
+    };
+}
+
+// Implements the conversions of a fieldless enum from and to the `u32` it is
+// sent as on the bus, and its (de)serialization through them. A value unknown
+// to this version of the crate fails with `UnknownValue`, the public enums
+// using it are `#[non_exhaustive]` so that the variants of newer versions of
+// the portals can be added.
+//
+// ```ignore
+// impl_u32_conversions!(KeyState { Pressed = 0, Released = 1 });
+// ```
+//
+// The `#[bitflags]` enums list the bit of each flag, `DeviceType` for example.
+macro_rules! impl_u32_conversions {
+    ($ty:ident { $($variant:ident = $value:literal),* $(,)? }) => {
+        impl TryFrom<u32> for $ty {
+            type Error = crate::UnknownValue;
+
+            fn try_from(value: u32) -> Result<Self, Self::Error> {
+                match value {
+                    $($value => Ok(Self::$variant),)*
+                    _ => Err(crate::UnknownValue::new(stringify!($ty), value)),
+                }
+            }
+        }
+
+        impl From<$ty> for u32 {
+            fn from(value: $ty) -> Self {
+                match value {
+                    $($ty::$variant => $value,)*
+                }
+            }
+        }
+
+        impl serde::Serialize for $ty {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.serialize_u32(u32::from(*self))
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $ty {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let value = u32::deserialize(deserializer)?;
+                Self::try_from(value).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+// Implements `Display` and `FromStr` for a fieldless enum, with the given
+// lowercase names. The parsing ignores the case, for the names read from
+// configuration files.
+//
+// ```ignore
+// impl_str_conversions!(DeviceType { Keyboard = "keyboard", Pointer = "pointer" });
+// ```
+macro_rules! impl_str_conversions {
+    ($ty:ident { $($variant:ident = $name:literal),* $(,)? }) => {
+        impl std::fmt::Display for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(match self {
+                    $(Self::$variant => $name,)*
+                })
+            }
+        }
+
+        impl std::str::FromStr for $ty {
+            type Err = crate::Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let s = s.trim();
+                $(
+                    if s.eq_ignore_ascii_case($name) {
+                        return Ok(Self::$variant);
+                    }
+                )*
+                Err(crate::Error::ParseError(format!(
+                    "Failed to parse {}, invalid value {:?}",
+                    stringify!($ty),
+                    s
+                )))
+            }
+        }
+    };
+}
+
+// Implements `Serialize` and `Deserialize` for a public options builder as an
+// `a{sv}` dictionary, along with `insert_raw` and `raw` for the keys that
+// aren't modeled yet. The struct has a `raw: RawOptions` field besides the
+// listed ones, the typed fields take precedence over a raw entry of the same
+// key. A listed key received with another type fails the deserialization.
+//
+// ```ignore
+// impl_options_dict!(ScreenshotOptions {
+//     handle_token = "handle_token",
+//     modal = "modal",
+// });
+// ```
+macro_rules! impl_options_dict {
+    ($ty:ident { $($field:ident = $key:literal),* $(,)? }) => {
+        impl $ty {
+            /// Set the option `key` to `value`, for the keys of newer versions
+            /// of the portal that don't have a setter yet.
+            ///
+            /// The value set through a setter is sent instead if `key` has
+            /// one.
+            #[must_use]
+            pub fn insert_raw(
+                mut self,
+                key: &str,
+                value: impl Into<zbus::zvariant::Value<'static>>,
+            ) -> Self {
+                self.raw.0.insert(key.to_owned(), value.into().into());
+                self
+            }
+
+            /// The options set with `insert_raw`, or the unknown ones received
+            /// from the portal.
+            pub fn raw(&self) -> &std::collections::HashMap<String, zbus::zvariant::OwnedValue> {
+                &self.raw.0
+            }
+        }
+
+        impl serde::Serialize for $ty {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                use serde::ser::SerializeMap;
+
+                // Unused by the options without any field.
+                #[allow(unused_imports)]
+                use crate::raw_options::DictField;
+
+                let mut map = serializer.serialize_map(None)?;
+                $(
+                    if let Some(value) = self.$field.entry() {
+                        map.serialize_entry($key, &zbus::zvariant::SerializeValue(value))?;
+                    }
+                )*
+                for (key, value) in &self.raw.0 {
+                    if ![$($key),*].contains(&key.as_str()) {
+                        map.serialize_entry(key, value)?;
+                    }
+                }
+                map.end()
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $ty {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                struct Visitor;
+
+                impl<'de> serde::de::Visitor<'de> for Visitor {
+                    type Value = $ty;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        f.write_str("a dictionary")
+                    }
+
+                    fn visit_map<M>(self, mut access: M) -> Result<$ty, M::Error>
+                    where
+                        M: serde::de::MapAccess<'de>,
+                    {
+                        #[allow(unused_imports)]
+                        use crate::raw_options::DictField;
+
+                        $(let mut $field = None;)*
+                        let mut raw = crate::raw_options::RawOptions::default();
+                        while let Some(key) = access.next_key::<String>()? {
+                            match key.as_str() {
+                                $(
+                                    $key => {
+                                        $field = Some(
+                                            access
+                                                .next_value::<zbus::zvariant::DeserializeValue<_>>()?
+                                                .0,
+                                        );
+                                    }
+                                )*
+                                _ => {
+                                    let value = access.next_value::<zbus::zvariant::OwnedValue>()?;
+                                    raw.0.insert(key, value);
+                                }
+                            }
+                        }
+                        Ok($ty {
+                            $(
+                                $field: DictField::from_entry($field)
+                                    .ok_or_else(|| serde::de::Error::missing_field($key))?,
+                            )*
+                            raw,
+                        })
+                    }
+                }
+
+                deserializer.deserialize_map(Visitor)
+            }
+        }
+    };
+}
@@ -0,0 +1,88 @@
+use std::sync::{Arc, RwLock};
+
+type Hook = Arc<dyn Fn(PortalMessage<'_>) + Send + Sync>;
+
+static HOOK: RwLock<Option<Hook>> = RwLock::new(None);
+
+/// A D-Bus message exchanged with a portal, passed to the hook set with
+/// [`set_message_hook`].
+#[derive(Debug, Clone, Copy)]
+pub enum PortalMessage<'m> {
+    /// A method call, serialized the same way as the one sent to the portal.
+    ///
+    /// **Note** it is serialized again for the hook, its serial doesn't match
+    /// the one of the message that was sent.
+    Call(&'m zbus::Message),
+    /// The reply of the portal to a method call, an error one included.
+    Reply(&'m zbus::Message),
+    /// The `Response` signal ending a request.
+    Response(&'m zbus::Message),
+}
+
+impl<'m> PortalMessage<'m> {
+    /// The message.
+    pub fn message(&self) -> &'m zbus::Message {
+        match self {
+            Self::Call(message) | Self::Reply(message) | Self::Response(message) => message,
+        }
+    }
+}
+
+/// Set a hook receiving the messages of the method calls to the portals,
+/// their replies and the responses of the requests.
+///
+/// It is meant to capture the exchanges with a portal backend to report an
+/// issue, the bytes of a message can be saved with
+/// [`zbus::Message::as_bytes`]. The property reads and the other signals
+/// aren't passed to the hook.
+///
+/// The hook is shared by all the proxies and replaces the previous one. It is
+/// called from the task making the call, it should return quickly.
+///
+/// ```rust,no_run
+/// use ashpd::{set_message_hook, PortalMessage};
+///
+/// set_message_hook(|message| {
+///     if let PortalMessage::Reply(reply) = message {
+///         eprintln!("{:?}: {:02x?}", reply, reply.as_bytes());
+///     }
+/// });
+/// ```
+pub fn set_message_hook(hook: impl Fn(PortalMessage<'_>) + Send + Sync + 'static) {
+    *HOOK.write().unwrap() = Some(Arc::new(hook));
+}
+
+/// Remove the hook set with [`set_message_hook`].
+pub fn clear_message_hook() {
+    *HOOK.write().unwrap() = None;
+}
+
+// Whether a hook is set, so that the call messages are only serialized again
+// when needed.
+pub(crate) fn is_set() -> bool {
+    HOOK.read().unwrap().is_some()
+}
+
+pub(crate) fn notify(message: PortalMessage<'_>) {
+    // The lock isn't held while calling the hook, it can replace itself.
+    let hook = HOOK.read().unwrap().clone();
+    if let Some(hook) = hook {
+        hook(message);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{clear_message_hook, is_set, set_message_hook};
+
+    #[test]
+    fn hook() {
+        assert!(!is_set());
+        set_message_hook(|_| ());
+        assert!(is_set());
+        set_message_hook(|message| println!("{:?}", message.message()));
+        assert!(is_set());
+        clear_message_hook();
+        assert!(!is_set());
+    }
+}
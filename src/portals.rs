@@ -0,0 +1,201 @@
+use enumflags2::{bitflags, BitFlags};
+
+use crate::{
+    desktop::{
+        account::AccountProxy,
+        background::BackgroundProxy,
+        camera::CameraProxy,
+        clipboard::ClipboardProxy,
+        device::DeviceProxy,
+        dynamic_launcher::DynamicLauncherProxy,
+        email::EmailProxy,
+        file_chooser::{FileChooserProxy, SelectedFiles},
+        game_mode::GameModeProxy,
+        global_shortcuts::{GlobalShortcutsProxy, GlobalShortcutsSession},
+        inhibit::{InhibitMonitor, InhibitProxy},
+        location::{LocationProxy, LocationSession},
+        memory_monitor::MemoryMonitorProxy,
+        network_monitor::NetworkMonitorProxy,
+        notification::{Notification, NotificationProxy, ScopedNotification},
+        open_uri::OpenURIProxy,
+        power_profile_monitor::PowerProfileMonitorProxy,
+        print::PrintProxy,
+        proxy_resolver::ProxyResolverProxy,
+        realtime::RealtimeProxy,
+        remote_desktop::{ActiveSession, RemoteDesktopProxy, RemoteDesktopSession},
+        screencast::{ScreenCastProxy, ScreenCastSession, Streams},
+        screenshot::ScreenshotProxy,
+        secret::SecretProxy,
+        settings::SettingsProxy,
+        trash::TrashProxy,
+        wallpaper::WallpaperProxy,
+    },
+    documents::{DocumentsProxy, FileTransferProxy},
+    flatpak::FlatpakProxy,
+    Error,
+};
+
+macro_rules! portals {
+    ($($(#[$attr:meta])* $variant:ident => $field:ident: $proxy:ident,)*) => {
+        #[bitflags]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #[repr(u32)]
+        /// A portal that can be requested from [`Portals::connect`].
+        pub enum Portal {
+            $($(#[$attr])* $variant,)*
+        }
+
+        /// A set of portal proxies sharing the same connection.
+        ///
+        /// The proxies own a handle to the connection instead of borrowing it,
+        /// a `Portals<'static>` can be stored in the application state and
+        /// cloned across tasks and threads.
+        ///
+        /// ```rust,no_run
+        /// use ashpd::{Portal, Portals};
+        ///
+        /// async fn run() -> ashpd::Result<()> {
+        ///     let connection = zbus::Connection::session().await?;
+        ///     let portals = Portals::connect(
+        ///         &connection,
+        ///         Portal::Settings | Portal::Notification | Portal::ScreenCast,
+        ///     )
+        ///     .await?;
+        ///
+        ///     if let Some(settings) = portals.settings() {
+        ///         println!("{:#?}", settings.read_all(&["org.freedesktop.appearance"]).await?);
+        ///     }
+        ///     Ok(())
+        /// }
+        /// ```
+        #[derive(Clone, Debug)]
+        pub struct Portals<'a> {
+            connection: zbus::Connection,
+            $($field: Option<$proxy<'a>>,)*
+        }
+
+        impl<'a> Portals<'a> {
+            /// Create the proxies of the requested `portals` concurrently over
+            /// `connection`.
+            ///
+            /// Fails with the first error if any of the proxies couldn't be
+            /// created.
+            pub async fn connect(
+                connection: &zbus::Connection,
+                portals: impl Into<BitFlags<Portal>>,
+            ) -> Result<Portals<'a>, Error> {
+                let portals = portals.into();
+                let ($($field,)*) = futures::try_join!($(async {
+                    if portals.contains(Portal::$variant) {
+                        $proxy::new(connection).await.map(Some)
+                    } else {
+                        Ok(None)
+                    }
+                },)*)?;
+                Ok(Self {
+                    connection: connection.clone(),
+                    $($field,)*
+                })
+            }
+
+            /// The connection shared by the proxies.
+            pub fn connection(&self) -> &zbus::Connection {
+                &self.connection
+            }
+
+            $(
+                #[doc = concat!("The [`", stringify!($proxy), "`], if it was requested.")]
+                pub fn $field(&self) -> Option<&$proxy<'a>> {
+                    self.$field.as_ref()
+                }
+            )*
+        }
+
+        // The proxies have to stay usable from `'static` application state
+        // shared across threads.
+        const _: fn() = || {
+            fn assert_send_sync<T: Send + Sync + 'static>() {}
+            assert_send_sync::<Portals<'static>>();
+            assert_send_sync::<ScreenCastSession<'static>>();
+            assert_send_sync::<RemoteDesktopSession<'static>>();
+            assert_send_sync::<LocationSession<'static>>();
+            assert_send_sync::<InhibitMonitor<'static>>();
+            assert_send_sync::<GlobalShortcutsSession<'static>>();
+            assert_send_sync::<ActiveSession<'static>>();
+            assert_send_sync::<ScopedNotification>();
+            assert_send_sync::<SelectedFiles>();
+            assert_send_sync::<Streams>();
+            assert_send_sync::<Notification>();
+            $(assert_send_sync::<$proxy<'static>>();)*
+            // The sessions are cloned for the tasks sharing them.
+            fn assert_clone<T: Clone>() {}
+            assert_clone::<ScreenCastSession<'static>>();
+            assert_clone::<RemoteDesktopSession<'static>>();
+            assert_clone::<LocationSession<'static>>();
+            assert_clone::<ActiveSession<'static>>();
+        };
+    };
+}
+
+portals! {
+    /// [`AccountProxy`].
+    Account => account: AccountProxy,
+    /// [`BackgroundProxy`].
+    Background => background: BackgroundProxy,
+    /// [`CameraProxy`].
+    Camera => camera: CameraProxy,
+    /// [`ClipboardProxy`].
+    Clipboard => clipboard: ClipboardProxy,
+    /// [`DeviceProxy`].
+    Device => device: DeviceProxy,
+    /// [`DocumentsProxy`].
+    Documents => documents: DocumentsProxy,
+    /// [`DynamicLauncherProxy`].
+    DynamicLauncher => dynamic_launcher: DynamicLauncherProxy,
+    /// [`EmailProxy`].
+    Email => email: EmailProxy,
+    /// [`FileChooserProxy`].
+    FileChooser => file_chooser: FileChooserProxy,
+    /// [`FileTransferProxy`].
+    FileTransfer => file_transfer: FileTransferProxy,
+    /// [`FlatpakProxy`].
+    Flatpak => flatpak: FlatpakProxy,
+    /// [`GameModeProxy`].
+    GameMode => game_mode: GameModeProxy,
+    /// [`GlobalShortcutsProxy`].
+    GlobalShortcuts => global_shortcuts: GlobalShortcutsProxy,
+    /// [`InhibitProxy`].
+    Inhibit => inhibit: InhibitProxy,
+    /// [`LocationProxy`].
+    Location => location: LocationProxy,
+    /// [`MemoryMonitorProxy`].
+    MemoryMonitor => memory_monitor: MemoryMonitorProxy,
+    /// [`NetworkMonitorProxy`].
+    NetworkMonitor => network_monitor: NetworkMonitorProxy,
+    /// [`NotificationProxy`].
+    Notification => notification: NotificationProxy,
+    /// [`OpenURIProxy`].
+    OpenURI => open_uri: OpenURIProxy,
+    /// [`PowerProfileMonitorProxy`].
+    PowerProfileMonitor => power_profile_monitor: PowerProfileMonitorProxy,
+    /// [`PrintProxy`].
+    Print => print: PrintProxy,
+    /// [`ProxyResolverProxy`].
+    ProxyResolver => proxy_resolver: ProxyResolverProxy,
+    /// [`RealtimeProxy`].
+    Realtime => realtime: RealtimeProxy,
+    /// [`RemoteDesktopProxy`].
+    RemoteDesktop => remote_desktop: RemoteDesktopProxy,
+    /// [`ScreenCastProxy`].
+    ScreenCast => screencast: ScreenCastProxy,
+    /// [`ScreenshotProxy`].
+    Screenshot => screenshot: ScreenshotProxy,
+    /// [`SecretProxy`].
+    Secret => secret: SecretProxy,
+    /// [`SettingsProxy`].
+    Settings => settings: SettingsProxy,
+    /// [`TrashProxy`].
+    Trash => trash: TrashProxy,
+    /// [`WallpaperProxy`].
+    Wallpaper => wallpaper: WallpaperProxy,
+}
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use serde::{de::DeserializeOwned, Serialize};
+use zbus::zvariant::{OwnedValue, Type};
+
+use crate::desktop::HandleToken;
+
+// A field of an options dictionary, sent only if it has a value. The fields
+// that aren't options are required when deserializing.
+pub(crate) trait DictField: Sized {
+    type Value: Serialize + DeserializeOwned + Type;
+
+    fn entry(&self) -> Option<&Self::Value>;
+
+    // None if a required field is missing.
+    fn from_entry(value: Option<Self::Value>) -> Option<Self>;
+}
+
+impl<T: Serialize + DeserializeOwned + Type> DictField for Option<T> {
+    type Value = T;
+
+    fn entry(&self) -> Option<&T> {
+        self.as_ref()
+    }
+
+    fn from_entry(value: Option<T>) -> Option<Self> {
+        Some(value)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + Type> DictField for Vec<T> {
+    type Value = Self;
+
+    fn entry(&self) -> Option<&Self> {
+        Some(self)
+    }
+
+    fn from_entry(value: Option<Self>) -> Option<Self> {
+        value
+    }
+}
+
+impl DictField for String {
+    type Value = Self;
+
+    fn entry(&self) -> Option<&Self> {
+        Some(self)
+    }
+
+    fn from_entry(value: Option<Self>) -> Option<Self> {
+        value
+    }
+}
+
+impl DictField for HandleToken {
+    type Value = Self;
+
+    fn entry(&self) -> Option<&Self> {
+        Some(self)
+    }
+
+    fn from_entry(value: Option<Self>) -> Option<Self> {
+        value
+    }
+}
+
+// The entries of an options dictionary the crate doesn't know about, set
+// with `insert_raw` or received from a newer portal.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct RawOptions(pub(crate) HashMap<String, OwnedValue>);
@@ -0,0 +1,153 @@
+use std::{
+    future::Future,
+    sync::{Arc, RwLock},
+};
+
+use enumflags2::BitFlags;
+use futures::StreamExt;
+
+use crate::{helpers::session_connection, Error, Portal, Portals};
+
+/// Emitted by [`ReconnectingPortals::receive_connection_lost`] once the session
+/// bus connection was lost and the proxies were recreated.
+#[derive(Debug, Clone)]
+pub struct ConnectionLost {
+    portals: Portals<'static>,
+}
+
+impl ConnectionLost {
+    /// The proxies created over the new connection.
+    pub fn portals(&self) -> &Portals<'static> {
+        &self.portals
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    generation: u64,
+    portals: Portals<'static>,
+}
+
+/// A set of [`Portals`] over the session bus that are recreated when the
+/// connection is lost.
+///
+/// Losing the session bus connection is rare but happens, every proxy is dead
+/// afterwards. Long running applications, kiosks for example, can use this
+/// wrapper to recover without restarting.
+///
+/// ```rust,no_run
+/// use ashpd::{Portal, ReconnectingPortals};
+///
+/// async fn run() -> ashpd::Result<()> {
+///     let portals = ReconnectingPortals::connect(Portal::Settings).await?;
+///
+///     let scheme = portals
+///         .call(|portals| async move {
+///             portals
+///                 .settings()
+///                 .unwrap()
+///                 .color_scheme()
+///                 .await
+///         })
+///         .await?;
+///     println!("{:?}", scheme);
+///
+///     loop {
+///         portals.receive_connection_lost().await?;
+///         println!("Reconnected to the session bus");
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReconnectingPortals {
+    requested: BitFlags<Portal>,
+    state: Arc<RwLock<State>>,
+}
+
+impl ReconnectingPortals {
+    /// Connect to the session bus and create the proxies of the requested
+    /// `portals`.
+    pub async fn connect(portals: impl Into<BitFlags<Portal>>) -> Result<Self, Error> {
+        let requested = portals.into();
+        let connection = session_connection().await?;
+        let portals = Portals::connect(&connection, requested).await?;
+        Ok(Self {
+            requested,
+            state: Arc::new(RwLock::new(State {
+                generation: 0,
+                portals,
+            })),
+        })
+    }
+
+    /// The proxies over the current connection.
+    pub fn portals(&self) -> Portals<'static> {
+        self.state.read().unwrap().portals.clone()
+    }
+
+    /// Run `f` with the current proxies, retrying it once over a new
+    /// connection if it failed because the connection was lost.
+    pub async fn call<T, F, Fut>(&self, f: F) -> Result<T, Error>
+    where
+        F: Fn(Portals<'static>) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let (generation, portals) = self.current();
+        match f(portals).await {
+            Err(err) if is_disconnected(&err) => {
+                #[cfg(feature = "log")]
+                tracing::warn!("The connection was lost, reconnecting: {}", err);
+                let portals = self.reconnect(generation).await?;
+                f(portals).await
+            }
+            result => result,
+        }
+    }
+
+    /// Wait until the current connection is lost, then reconnect.
+    ///
+    /// Fails if the proxies couldn't be recreated, the previous ones are kept
+    /// in that case.
+    pub async fn receive_connection_lost(&self) -> Result<ConnectionLost, Error> {
+        let (generation, portals) = self.current();
+        let mut stream = zbus::MessageStream::from(portals.connection());
+        // The stream ends once the connection is closed, the messages have to
+        // be consumed meanwhile to not hold back the connection.
+        while stream.next().await.is_some() {}
+        #[cfg(feature = "log")]
+        tracing::warn!("The connection was lost, reconnecting");
+        let portals = self.reconnect(generation).await?;
+        Ok(ConnectionLost { portals })
+    }
+
+    fn current(&self) -> (u64, Portals<'static>) {
+        let state = self.state.read().unwrap();
+        (state.generation, state.portals.clone())
+    }
+
+    // Only reconnects if nobody else did since `generation` was current.
+    async fn reconnect(&self, generation: u64) -> Result<Portals<'static>, Error> {
+        let (current, portals) = self.current();
+        if current != generation {
+            return Ok(portals);
+        }
+        let connection = session_connection().await?;
+        let portals = Portals::connect(&connection, self.requested).await?;
+        let mut state = self.state.write().unwrap();
+        if state.generation == generation {
+            state.generation += 1;
+            state.portals = portals;
+        }
+        Ok(state.portals.clone())
+    }
+}
+
+fn is_disconnected(err: &Error) -> bool {
+    match err.root() {
+        Error::Zbus(zbus::Error::Io(_)) => true,
+        Error::Zbus(zbus::Error::FDO(err)) => {
+            matches!(**err, zbus::fdo::Error::Disconnected(_))
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,144 @@
+//! The `serde` implementations of the enums of the portals are the values
+//! sent on the bus, numbers most of the time. The modules
+//! [`str_form`](self) and [`flags`] can be used with `#[serde(with = "...")]`
+//! to read and write their names in configuration files instead,
+//! [`DeviceType`](crate::desktop::remote_desktop::DeviceType),
+//! [`SourceType`](crate::desktop::screencast::SourceType),
+//! [`CursorMode`](crate::desktop::screencast::CursorMode) and
+//! [`PersistMode`](crate::desktop::screencast::PersistMode) for example.
+//!
+//! ```rust
+//! use ashpd::{
+//!     desktop::{remote_desktop::DeviceType, screencast::PersistMode},
+//!     enumflags2::BitFlags,
+//! };
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Config {
+//!     #[serde(with = "ashpd::str_form::flags")]
+//!     devices: BitFlags<DeviceType>,
+//!     #[serde(with = "ashpd::str_form")]
+//!     persist_mode: PersistMode,
+//! }
+//!
+//! let devices = ashpd::str_form::parse_flags::<DeviceType>("keyboard,pointer").unwrap();
+//! assert_eq!(devices, DeviceType::Keyboard | DeviceType::Pointer);
+//! assert_eq!(ashpd::str_form::format_flags(devices), "keyboard,pointer");
+//! ```
+
+use std::{fmt::Display, str::FromStr};
+
+use enumflags2::{BitFlag, BitFlags};
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+use crate::Error;
+
+/// Serialize `value` as its name.
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Display,
+    S: Serializer,
+{
+    serializer.collect_str(value)
+}
+
+/// Deserialize a value from its name.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'de>,
+{
+    let name = String::deserialize(deserializer)?;
+    name.parse().map_err(de::Error::custom)
+}
+
+/// Parse the comma separated names of a set of flags, `keyboard,pointer` for
+/// example. An empty string is an empty set.
+pub fn parse_flags<T>(s: &str) -> Result<BitFlags<T>, Error>
+where
+    T: BitFlag + FromStr<Err = Error>,
+{
+    s.split(',')
+        .filter(|name| !name.trim().is_empty())
+        .try_fold(BitFlags::empty(), |flags, name| {
+            Ok(flags | name.parse::<T>()?)
+        })
+}
+
+/// The comma separated names of `flags`, parsed back by [`parse_flags`].
+pub fn format_flags<T>(flags: BitFlags<T>) -> String
+where
+    T: BitFlag + Display,
+{
+    flags
+        .iter()
+        .map(|flag| flag.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// (De)serialize a set of flags as the comma separated names of the flags,
+/// see [`parse_flags`].
+pub mod flags {
+    use super::*;
+
+    /// Serialize `flags` as their comma separated names.
+    pub fn serialize<T, S>(flags: &BitFlags<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: BitFlag + Display,
+        S: Serializer,
+    {
+        serializer.serialize_str(&format_flags(*flags))
+    }
+
+    /// Deserialize a set of flags from their comma separated names.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<BitFlags<T>, D::Error>
+    where
+        T: BitFlag + FromStr<Err = Error>,
+        D: Deserializer<'de>,
+    {
+        let names = String::deserialize(deserializer)?;
+        parse_flags(&names).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use enumflags2::BitFlags;
+
+    use super::{format_flags, parse_flags};
+    use crate::desktop::{
+        remote_desktop::DeviceType,
+        screencast::{CursorMode, PersistMode, SourceType},
+    };
+
+    #[test]
+    fn names() {
+        assert_eq!(DeviceType::Touchscreen.to_string(), "touchscreen");
+        assert_eq!(
+            "Keyboard".parse::<DeviceType>().unwrap(),
+            DeviceType::Keyboard
+        );
+        assert_eq!(
+            " window ".parse::<SourceType>().unwrap(),
+            SourceType::Window
+        );
+        assert_eq!(
+            "embedded".parse::<CursorMode>().unwrap(),
+            CursorMode::Embedded
+        );
+        assert_eq!(PersistMode::ExplicitlyRevoked.to_string(), "persistent");
+        assert!("mouse".parse::<DeviceType>().is_err());
+    }
+
+    #[test]
+    fn flags() {
+        let devices = parse_flags::<DeviceType>("pointer, keyboard").unwrap();
+        assert_eq!(devices, DeviceType::Keyboard | DeviceType::Pointer);
+        assert_eq!(format_flags(devices), "keyboard,pointer");
+        assert_eq!(parse_flags::<SourceType>("").unwrap(), BitFlags::empty());
+        assert!(parse_flags::<SourceType>("monitor,screen").is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use std::{convert::TryFrom, fmt, marker::PhantomData};
+
+use enumflags2::{BitFlag, BitFlags};
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use zbus::zvariant::{OwnedValue, Signature, Type, Value};
+
+/// A set of bit flags that doesn't fail to deserialize when it contains
+/// unknown bits.
+///
+/// Newer or non-compliant portal backends can send bits that don't map to any
+/// of the known flags, those are kept aside in
+/// [`extra_bits()`][`TolerantBitFlags::extra_bits`] instead of making the whole
+/// deserialization fail.
+///
+/// ```rust,no_run
+/// use ashpd::{desktop::remote_desktop::DeviceType, TolerantBitFlags};
+///
+/// async fn run() -> ashpd::Result<()> {
+///     let connection = zbus::Connection::session().await?;
+///     let proxy = ashpd::desktop::remote_desktop::RemoteDesktopProxy::new(&connection).await?;
+///     let devices = proxy
+///         .inner()
+///         .get_property::<TolerantBitFlags<DeviceType>>("AvailableDeviceTypes")
+///         .await?;
+///     println!("{:?}, unknown bits: {:#x}", devices.flags(), devices.extra_bits());
+///     Ok(())
+/// }
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct TolerantBitFlags<T: BitFlag<Numeric = u32>> {
+    flags: BitFlags<T>,
+    extra_bits: u32,
+}
+
+impl<T: BitFlag<Numeric = u32>> TolerantBitFlags<T> {
+    /// Split `bits` into the known flags and the unknown bits.
+    pub fn from_bits(bits: u32) -> Self {
+        Self {
+            flags: BitFlags::from_bits_truncate(bits),
+            extra_bits: bits & !BitFlags::<T>::all().bits(),
+        }
+    }
+
+    /// The known flags.
+    pub fn flags(&self) -> BitFlags<T> {
+        self.flags
+    }
+
+    /// The bits that don't correspond to any known flag.
+    pub fn extra_bits(&self) -> u32 {
+        self.extra_bits
+    }
+
+    /// All the bits, the known flags and the extra ones.
+    pub fn bits(&self) -> u32 {
+        self.flags.bits() | self.extra_bits
+    }
+}
+
+impl<T: BitFlag<Numeric = u32>> Default for TolerantBitFlags<T> {
+    fn default() -> Self {
+        Self::from_bits(0)
+    }
+}
+
+impl<T: BitFlag<Numeric = u32> + fmt::Debug> fmt::Debug for TolerantBitFlags<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TolerantBitFlags")
+            .field("flags", &self.flags)
+            .field("extra_bits", &self.extra_bits)
+            .finish()
+    }
+}
+
+impl<T: BitFlag<Numeric = u32>> From<BitFlags<T>> for TolerantBitFlags<T> {
+    fn from(flags: BitFlags<T>) -> Self {
+        Self {
+            flags,
+            extra_bits: 0,
+        }
+    }
+}
+
+impl<T: BitFlag<Numeric = u32>> From<TolerantBitFlags<T>> for BitFlags<T> {
+    fn from(flags: TolerantBitFlags<T>) -> Self {
+        flags.flags
+    }
+}
+
+impl<T: BitFlag<Numeric = u32>> Type for TolerantBitFlags<T> {
+    fn signature() -> Signature<'static> {
+        u32::signature()
+    }
+}
+
+impl<T: BitFlag<Numeric = u32>> Serialize for TolerantBitFlags<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u32(self.bits())
+    }
+}
+
+impl<'de, T: BitFlag<Numeric = u32>> Deserialize<'de> for TolerantBitFlags<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct BitsVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: BitFlag<Numeric = u32>> Visitor<'de> for BitsVisitor<T> {
+            type Value = TolerantBitFlags<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an unsigned 32 bits integer")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                u32::try_from(v)
+                    .map(TolerantBitFlags::from_bits)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                u32::try_from(v)
+                    .map(TolerantBitFlags::from_bits)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+            }
+        }
+
+        deserializer.deserialize_u32(BitsVisitor(PhantomData))
+    }
+}
+
+impl<T: BitFlag<Numeric = u32>> TryFrom<Value<'_>> for TolerantBitFlags<T> {
+    type Error = zbus::zvariant::Error;
+
+    fn try_from(value: Value<'_>) -> Result<Self, Self::Error> {
+        let bits = match value {
+            Value::U32(v) => v,
+            Value::I32(v) => v as u32,
+            Value::U16(v) => v.into(),
+            Value::U8(v) => v.into(),
+            Value::U64(v) => u32::try_from(v).map_err(|_| zbus::zvariant::Error::IncorrectType)?,
+            _ => return Err(zbus::zvariant::Error::IncorrectType),
+        };
+        Ok(Self::from_bits(bits))
+    }
+}
+
+impl<T: BitFlag<Numeric = u32>> TryFrom<OwnedValue> for TolerantBitFlags<T> {
+    type Error = zbus::zvariant::Error;
+
+    fn try_from(value: OwnedValue) -> Result<Self, Self::Error> {
+        Self::try_from(Value::from(value))
+    }
+}
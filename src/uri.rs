@@ -0,0 +1,95 @@
+use std::{fmt, path::Path};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use url::Url;
+use zbus::zvariant::{Signature, Type};
+
+/// Parse a URI sent by a portal backend.
+///
+/// Backends are expected to send valid URIs, some send plain absolute paths
+/// instead, those are converted to `file://` URIs.
+pub(crate) fn parse_uri(uri: &str) -> Result<Url, String> {
+    Url::parse(uri).or_else(|err| {
+        if Path::new(uri).is_absolute() {
+            Url::from_file_path(uri).map_err(|_| format!("Invalid URI {:?}: {}", uri, err))
+        } else {
+            Err(format!("Invalid URI {:?}: {}", uri, err))
+        }
+    })
+}
+
+/// A URI, encoded as a string on the bus.
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct Uri(pub(crate) Url);
+
+impl fmt::Debug for Uri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0.as_str())
+    }
+}
+
+impl Type for Uri {
+    fn signature() -> Signature<'static> {
+        String::signature()
+    }
+}
+
+impl Serialize for Uri {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.0.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Uri {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let uri = String::deserialize(deserializer)?;
+        parse_uri(&uri).map(Self).map_err(de::Error::custom)
+    }
+}
+
+/// A list of URIs, encoded as an array of strings on the bus.
+#[derive(Clone, PartialEq, Eq, Default)]
+pub(crate) struct Uris(pub(crate) Vec<Url>);
+
+impl fmt::Debug for Uris {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|uri| uri.as_str()))
+            .finish()
+    }
+}
+
+impl Type for Uris {
+    fn signature() -> Signature<'static> {
+        <Vec<String>>::signature()
+    }
+}
+
+impl Serialize for Uris {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.0.iter().map(|uri| uri.as_str()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Uris {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        <Vec<String>>::deserialize(deserializer)?
+            .iter()
+            .map(|uri| parse_uri(uri))
+            .collect::<Result<_, _>>()
+            .map(Self)
+            .map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,181 @@
+use std::convert::TryFrom;
+
+use futures::{future::ready, Stream, StreamExt};
+use zbus::{fdo::NameOwnerChanged, names::BusName};
+
+use crate::{desktop::DESTINATION, Error};
+
+/// A change of the process owning the portals bus name, see
+/// [`PortalWatcher::receive_events`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortalEvent {
+    /// The portals started for the first time since watching.
+    Started,
+    /// The portals exited, crashed for example. Their sessions are closed.
+    Stopped,
+    /// The portals were started again after exiting, the sessions can be
+    /// re-established.
+    Restarted,
+}
+
+impl PortalEvent {
+    // The event of an owner change, `running` being whether the portals ran
+    // at some point since watching.
+    fn from_owners(running: &mut bool, old_owner: bool, new_owner: bool) -> Option<Self> {
+        let event = match (old_owner, new_owner) {
+            (_, false) => Self::Stopped,
+            (false, true) if !*running => Self::Started,
+            _ => Self::Restarted,
+        };
+        *running |= old_owner || new_owner;
+        Some(event).filter(|_| old_owner || new_owner)
+    }
+}
+
+/// Watches the `org.freedesktop.portal.Desktop` bus name for restarts of
+/// xdg-desktop-portal.
+///
+/// The sessions of the portals, screen casts or remote desktops for example,
+/// die silently with the process serving them.
+///
+/// ```rust,no_run
+/// use ashpd::{PortalEvent, PortalWatcher};
+/// use futures::StreamExt;
+///
+/// async fn run() -> ashpd::Result<()> {
+///     let connection = zbus::Connection::session().await?;
+///     let watcher = PortalWatcher::new(&connection).await?;
+///
+///     let mut events = watcher.receive_events().await?;
+///     while let Some(event) = events.next().await {
+///         if event == PortalEvent::Restarted {
+///             println!("Re-creating the sessions");
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PortalWatcher {
+    proxy: zbus::fdo::DBusProxy<'static>,
+}
+
+impl PortalWatcher {
+    /// Create a new watcher over `connection`.
+    pub async fn new(connection: &zbus::Connection) -> Result<Self, Error> {
+        let proxy = zbus::fdo::DBusProxy::new(connection).await?;
+        Ok(Self { proxy })
+    }
+
+    /// Whether the portals are currently running.
+    pub async fn is_running(&self) -> Result<bool, Error> {
+        let name = BusName::try_from(DESTINATION).map_err(zbus::Error::from)?;
+        Ok(self.proxy.name_has_owner(name).await?)
+    }
+
+    /// The owner changes of the portals bus name, from now on.
+    ///
+    /// The bus only routes the changes of the portals bus name to the
+    /// connection, not the ones of every peer connecting to the bus.
+    pub async fn receive_events(
+        &self,
+    ) -> Result<impl Stream<Item = PortalEvent> + Unpin + Send + 'static, Error> {
+        // Created first to not miss a change happening right after the match.
+        let stream = zbus::MessageStream::from(self.proxy.connection());
+        let rule = MatchRule::add(&self.proxy).await?;
+        let mut running = self.is_running().await?;
+        Ok(stream.filter_map(move |message| {
+            let event = message
+                .ok()
+                .and_then(NameOwnerChanged::from_message)
+                .filter(|signal| rule.matches(signal))
+                .and_then(|signal| {
+                    let args = signal.args().ok()?;
+                    PortalEvent::from_owners(
+                        &mut running,
+                        args.old_owner().is_some(),
+                        args.new_owner().is_some(),
+                    )
+                });
+            #[cfg(feature = "log")]
+            if let Some(event) = event {
+                tracing::info!("The portals bus name changed owner: {:?}", event);
+            }
+            ready(event)
+        }))
+    }
+}
+
+// The match of the owner changes of the portals bus name, removed from the bus
+// once dropped.
+struct MatchRule {
+    proxy: zbus::fdo::DBusProxy<'static>,
+    rule: String,
+}
+
+impl MatchRule {
+    async fn add(proxy: &zbus::fdo::DBusProxy<'static>) -> Result<Self, Error> {
+        let rule = format!(
+            concat!(
+                "type='signal',",
+                "sender='org.freedesktop.DBus',",
+                "path='/org/freedesktop/DBus',",
+                "interface='org.freedesktop.DBus',",
+                "member='NameOwnerChanged',",
+                "arg0='{}'"
+            ),
+            DESTINATION
+        );
+        proxy.add_match(&rule).await?;
+        Ok(Self {
+            proxy: proxy.clone(),
+            rule,
+        })
+    }
+
+    // zbus adds the same match for the other well-known names its proxies
+    // talk to, the `arg0` of the rule has to be checked again.
+    fn matches(&self, signal: &NameOwnerChanged) -> bool {
+        matches!(signal.args(), Ok(args) if args.name() == DESTINATION)
+    }
+}
+
+impl Drop for MatchRule {
+    fn drop(&mut self) {
+        let proxy = self.proxy.clone();
+        let rule = std::mem::take(&mut self.rule);
+        self.proxy
+            .connection()
+            .executor()
+            .spawn(async move {
+                if let Err(_err) = proxy.remove_match(&rule).await {
+                    #[cfg(feature = "log")]
+                    tracing::warn!("Failed to remove the match rule {}: {}", rule, _err);
+                }
+            })
+            .detach();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PortalEvent;
+
+    #[test]
+    fn from_owners() {
+        let mut running = false;
+        let mut event = |old, new| PortalEvent::from_owners(&mut running, old, new);
+        assert_eq!(event(false, true), Some(PortalEvent::Started));
+        assert_eq!(event(true, false), Some(PortalEvent::Stopped));
+        assert_eq!(event(false, true), Some(PortalEvent::Restarted));
+        assert_eq!(event(true, true), Some(PortalEvent::Restarted));
+        assert_eq!(event(false, false), None);
+
+        // Running when the watch started.
+        let mut running = true;
+        assert_eq!(
+            PortalEvent::from_owners(&mut running, false, true),
+            Some(PortalEvent::Restarted)
+        );
+    }
+}
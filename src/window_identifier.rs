@@ -3,7 +3,6 @@ use zbus::zvariant::{Signature, Type};
 
 #[cfg(any(feature = "feature_gtk4", feature = "feature_gtk3"))]
 use futures::lock::Mutex;
-#[cfg(any(feature = "feature_gtk4", feature = "feature_gtk3"))]
 use std::sync::Arc;
 
 // This is needed for docs so that we include glib only once
@@ -114,6 +113,8 @@ pub enum WindowIdentifier {
         native: Arc<Mutex<Option<gtk4::Native>>>,
         /// The exported window handle
         handle: String,
+        /// Keeps the Wayland handle exported
+        export: ExportGuard,
     },
     /// GTK 3 Window Identifier
     #[cfg(feature = "feature_gtk3")]
@@ -123,13 +124,12 @@ pub enum WindowIdentifier {
         handle: String,
         // the top level window
         window: Arc<Mutex<Option<gtk3::gdk::Window>>>,
+        /// Keeps the Wayland handle exported
+        export: ExportGuard,
     },
     #[cfg(feature = "raw_handle")]
     #[doc(hidden)]
-    Exported {
-        handle: String,
-        exported: ZxdgExportedV2,
-    },
+    Exported { handle: String, export: ExportGuard },
     /// For Other Toolkits
     #[doc(hidden)]
     Other(String),
@@ -191,6 +191,24 @@ impl WindowIdentifier {
         }
     }
 
+    /// A guard keeping the exported handle alive, even after the identifier
+    /// itself has been dropped.
+    ///
+    /// The dialog of a request stays attached to the window until the
+    /// `Response` signal is received, so the request holds on to it until
+    /// then.
+    pub(crate) fn export_guard(&self) -> ExportGuard {
+        match self {
+            #[cfg(feature = "feature_gtk4")]
+            Self::Gtk4 { export, .. } => export.clone(),
+            #[cfg(feature = "feature_gtk3")]
+            Self::Gtk3 { export, .. } => export.clone(),
+            #[cfg(feature = "raw_handle")]
+            Self::Exported { export, .. } => export.clone(),
+            Self::Other(_) => ExportGuard::default(),
+        }
+    }
+
     #[cfg(feature = "feature_gtk4")]
     /// Creates a [`WindowIdentifier`] from a [`gtk4::Native`](https://docs.gtk.org/gtk4/class.Native.html).
     ///
@@ -229,10 +247,15 @@ impl WindowIdentifier {
         };
 
         match handle {
-            Some(h) => WindowIdentifier::Gtk4 {
-                native: Arc::new(Mutex::new(Some(native.clone().upcast()))),
-                handle: h,
-            },
+            Some(h) => {
+                let export = Export::Gtk4(native.upcast_ref::<gtk4::Native>().downgrade().into());
+                let native = Arc::new(Mutex::new(Some(native.clone().upcast())));
+                WindowIdentifier::Gtk4 {
+                    export: ExportGuard::new(export),
+                    native,
+                    handle: h,
+                }
+            }
             None => WindowIdentifier::default(),
         }
     }
@@ -273,10 +296,15 @@ impl WindowIdentifier {
         };
 
         match handle {
-            Some(h) => WindowIdentifier::Gtk3 {
-                handle: h,
-                window: Arc::new(Mutex::new(Some(win.clone().upcast()))),
-            },
+            Some(h) => {
+                let export = Export::Gtk3(win.upcast_ref::<gtk3::gdk::Window>().downgrade().into());
+                let window = Arc::new(Mutex::new(Some(win.clone().upcast())));
+                WindowIdentifier::Gtk3 {
+                    handle: h,
+                    export: ExportGuard::new(export),
+                    window,
+                }
+            }
             None => WindowIdentifier::default(),
         }
     }
@@ -290,7 +318,7 @@ impl WindowIdentifier {
         match handle {
             Wayland(wl_handle) => match wayland_handle_export(wl_handle.surface) {
                 Ok((exported, handle)) => Self::Exported {
-                    exported,
+                    export: ExportGuard::new(Export::Wayland(exported)),
                     handle: format!("wayland:{}", handle),
                 },
                 Err(_err) => {
@@ -422,43 +450,88 @@ impl WindowIdentifier {
     }
 }
 
-impl Drop for WindowIdentifier {
+/// A handle exported to the compositor, unexported once dropped.
+///
+/// It is dropped by the last request using it, on any thread. The GTK windows
+/// are only referenced weakly from there, and unexported on the thread of the
+/// default main context.
+enum Export {
+    #[cfg(feature = "feature_gtk4")]
+    Gtk4(glib::SendWeakRef<gtk4::Native>),
+    #[cfg(feature = "feature_gtk3")]
+    Gtk3(glib::SendWeakRef<gtk3::gdk::Window>),
+    #[cfg(feature = "raw_handle")]
+    Wayland(ZxdgExportedV2),
+}
+
+impl Drop for Export {
+    #[allow(irrefutable_let_patterns)]
     fn drop(&mut self) {
         #[cfg(feature = "feature_gtk4")]
-        if let Self::Gtk4 { native, .. } = self {
-            let ctx = glib::MainContext::default();
-            ctx.spawn_local(clone!(@strong native => async move {
-                if let Some(native) = native.lock().await.as_ref() {
+        if let Self::Gtk4(native) = self {
+            let native = native.clone();
+            glib::MainContext::default().invoke(move || {
+                if let Some(native) = native.upgrade() {
                     let surface = native.surface();
                     if surface.display().backend().is_wayland() {
-                        let top_level = surface.downcast_ref::<gdk4wayland::WaylandToplevel>().unwrap();
+                        let top_level = surface
+                            .downcast_ref::<gdk4wayland::WaylandToplevel>()
+                            .unwrap();
                         top_level.unexport_handle();
                     }
-                };
-            }));
+                }
+            });
         }
         #[cfg(feature = "raw_handle")]
-        if let Self::Exported { exported, .. } = self {
+        if let Self::Wayland(exported) = self {
             if let Err(_err) = wayland_handle_unexport(exported) {
                 #[cfg(feature = "log")]
                 tracing::error!("Failed to unexport wayland handle {}", _err);
             }
         }
         #[cfg(feature = "feature_gtk3")]
-        if let Self::Gtk3 { window, .. } = self {
-            let ctx = glib::MainContext::default();
-            ctx.spawn_local(clone!(@strong window => async move {
-                if let Some(window) = window.lock().await.as_ref() {
+        if let Self::Gtk3(window) = self {
+            let window = window.clone();
+            glib::MainContext::default().invoke(move || {
+                if let Some(window) = window.upgrade() {
                     if window.display().backend().is_wayland() {
-                        let wayland_win = window.downcast_ref::<gdk3wayland::WaylandWindow>().unwrap();
+                        let wayland_win =
+                            window.downcast_ref::<gdk3wayland::WaylandWindow>().unwrap();
                         wayland_win.unexport_handle();
                     }
                 }
-            }));
+            });
         }
     }
 }
 
+/// Shared ownership of the handle exported by a [`WindowIdentifier`].
+///
+/// The handle is unexported once the identifier and every guard cloned from
+/// it are dropped.
+#[derive(Clone, Default)]
+pub(crate) struct ExportGuard(Option<Arc<Export>>);
+
+impl ExportGuard {
+    #[allow(dead_code)]
+    fn new(export: Export) -> Self {
+        Self(Some(Arc::new(export)))
+    }
+
+    /// Whether the guard is keeping a handle exported.
+    pub(crate) fn is_exported(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+impl std::fmt::Debug for ExportGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ExportGuard")
+            .field(&self.is_exported())
+            .finish()
+    }
+}
+
 #[cfg(feature = "raw_handle")]
 #[derive(Default, Debug)]
 struct ExportedWaylandHandle(String);
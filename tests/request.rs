@@ -0,0 +1,147 @@
+//! Ordering guarantees of the `org.freedesktop.portal.Request` handling.
+//!
+//! The tests register a mock `org.freedesktop.portal.Screenshot` portal on the
+//! session bus, the parent window passed to the request tells the mock when to
+//! emit the `Response` signal. They are skipped if there is no session bus or
+//! if a portal already owns `org.freedesktop.portal.Desktop`, run them with
+//! `dbus-run-session -- cargo test --test request`.
+
+use std::{collections::HashMap, sync::OnceLock, thread, time::Duration};
+
+use ashpd::{
+    desktop::{screenshot::ScreenshotProxy, ResponseError},
+    Error, WindowIdentifier,
+};
+use zbus::{
+    dbus_interface,
+    zvariant::{ObjectPath, OwnedObjectPath, Value},
+    Connection, ConnectionBuilder, MessageHeader,
+};
+
+const URI: &str = "file:///tmp/screenshot.png";
+
+struct MockScreenshot;
+
+#[dbus_interface(name = "org.freedesktop.portal.Screenshot")]
+impl MockScreenshot {
+    async fn screenshot(
+        &self,
+        #[zbus(header)] header: MessageHeader<'_>,
+        #[zbus(connection)] connection: &Connection,
+        parent_window: &str,
+        options: HashMap<&str, Value<'_>>,
+    ) -> zbus::fdo::Result<OwnedObjectPath> {
+        let sender = header.sender()?.unwrap().trim_start_matches(':');
+        let token = match options.get("handle_token") {
+            Some(Value::Str(token)) => token.as_str(),
+            _ => return Err(zbus::fdo::Error::InvalidArgs("No handle_token".into())),
+        };
+        let handle = ObjectPath::try_from(format!(
+            "/org/freedesktop/portal/desktop/request/{}/{}",
+            sender.replace('.', "_"),
+            token
+        ))
+        .unwrap();
+        match parent_window {
+            // Respond before the reply to the method call is even sent.
+            "respond-before-reply" => respond(connection, &handle, 0).await,
+            // Respond once the caller had the time to process the reply.
+            "respond-after-reply" => {
+                let connection = connection.clone();
+                let handle = OwnedObjectPath::from(handle.to_owned());
+                thread::spawn(move || {
+                    thread::sleep(Duration::from_millis(200));
+                    futures::executor::block_on(respond(&connection, &handle, 0));
+                });
+            }
+            // The user cancelled the interaction.
+            "cancel" => respond(connection, &handle, 1).await,
+            _ => return Err(zbus::fdo::Error::InvalidArgs(parent_window.to_owned())),
+        }
+        Ok(handle.into())
+    }
+}
+
+async fn respond(connection: &Connection, handle: &ObjectPath<'_>, code: u32) {
+    let mut results = HashMap::new();
+    if code == 0 {
+        results.insert("uri", Value::from(URI));
+    }
+    connection
+        .emit_signal(
+            None::<()>,
+            handle,
+            "org.freedesktop.portal.Request",
+            "Response",
+            &(code, results),
+        )
+        .await
+        .unwrap();
+}
+
+// The mock service shared by the tests, `None` if it couldn't be registered.
+fn mock_portal() -> Option<&'static Connection> {
+    static SERVICE: OnceLock<Option<Connection>> = OnceLock::new();
+    SERVICE
+        .get_or_init(|| {
+            std::env::var_os("DBUS_SESSION_BUS_ADDRESS")?;
+            let service = futures::executor::block_on(
+                ConnectionBuilder::session()
+                    .unwrap()
+                    .name("org.freedesktop.portal.Desktop")
+                    .unwrap()
+                    .serve_at("/org/freedesktop/portal/desktop", MockScreenshot)
+                    .unwrap()
+                    .build(),
+            );
+            match service {
+                Ok(service) => Some(service),
+                Err(zbus::Error::NameTaken) => None,
+                Err(err) => panic!("Failed to register the mock portal: {}", err),
+            }
+        })
+        .as_ref()
+}
+
+fn screenshot(parent_window: &str) -> Option<Result<url::Url, Error>> {
+    if mock_portal().is_none() {
+        eprintln!("No session bus or the portal name is taken, skipping");
+        return None;
+    }
+    Some(futures::executor::block_on(async {
+        let connection = Connection::session().await?;
+        let proxy = ScreenshotProxy::new(&connection).await?;
+        proxy
+            .screenshot(&WindowIdentifier::new(parent_window), false, false)
+            .await
+    }))
+}
+
+#[test]
+fn response_before_method_reply() {
+    if let Some(response) = screenshot("respond-before-reply") {
+        assert_eq!(response.unwrap().as_str(), URI);
+    }
+}
+
+#[test]
+fn response_after_method_reply() {
+    if let Some(response) = screenshot("respond-after-reply") {
+        assert_eq!(response.unwrap().as_str(), URI);
+    }
+}
+
+#[test]
+fn cancelled_request() {
+    if let Some(response) = screenshot("cancel") {
+        let err = response.unwrap_err();
+        assert!(matches!(
+            err.root(),
+            Error::Response(ResponseError::Cancelled)
+        ));
+        assert!(matches!(
+            err,
+            Error::Call { ref method, handle: Some(_), .. } if method == "Screenshot"
+        ));
+    }
+}